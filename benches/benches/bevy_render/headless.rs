@@ -0,0 +1,62 @@
+use bevy::{
+    audio::AudioPlugin,
+    diagnostic::stress::{StressTest, StressTestPlugin},
+    prelude::*,
+    render::renderer::HeadlessRenderPlugin,
+    wgpu::WgpuPlugin,
+    winit::WinitPlugin,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn headless_app(stress_test: StressTest) -> App {
+    let mut builder = App::build();
+    builder
+        .add_resource(stress_test)
+        .add_plugin_group_with(DefaultPlugins, |group| {
+            group
+                .disable::<AudioPlugin>()
+                .disable::<WinitPlugin>()
+                .disable::<WgpuPlugin>()
+        })
+        .add_plugin(HeadlessRenderPlugin)
+        .add_plugin(StressTestPlugin);
+    let mut app = builder.app;
+    app.executor.initialize(&mut app.resources);
+    app.initialize();
+    // the first frames create windows and upload every asset
+    app.update_n(3);
+    app
+}
+
+fn bench_meshes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("headless_meshes");
+    for meshes in [100, 1000, 10000].iter() {
+        let mut app = headless_app(StressTest {
+            meshes: *meshes,
+            ..Default::default()
+        });
+        group.bench_with_input(BenchmarkId::from_parameter(meshes), meshes, |b, _| {
+            b.iter(|| app.update())
+        });
+    }
+    group.finish();
+}
+
+fn bench_hierarchies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("headless_hierarchies");
+    for depth in [8, 64].iter() {
+        let mut app = headless_app(StressTest {
+            meshes: 0,
+            hierarchies: 64,
+            hierarchy_depth: *depth,
+            ..Default::default()
+        });
+        group.bench_with_input(BenchmarkId::from_parameter(depth), depth, |b, _| {
+            b.iter(|| app.update())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_meshes, bench_hierarchies);
+criterion_main!(benches);
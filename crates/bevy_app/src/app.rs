@@ -64,6 +64,14 @@ impl App {
             .run(&mut self.schedule, &mut self.world, &mut self.resources);
     }
 
+    /// Runs `frames` updates back to back. This is meant for benchmarks and tests that step an app without a runner.
+    /// Call [App::initialize] first to run the startup systems.
+    pub fn update_n(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.update();
+        }
+    }
+
     pub fn initialize(&mut self) {
         self.startup_schedule
             .initialize(&mut self.world, &mut self.resources);
@@ -85,3 +93,30 @@ impl App {
 /// An event that indicates the app should exit. This will fully exit the app process.
 #[derive(Debug, Clone)]
 pub struct AppExit;
+
+#[cfg(test)]
+mod tests {
+    use super::App;
+    use bevy_ecs::{IntoQuerySystem, ResMut};
+
+    #[test]
+    fn update_n() {
+        fn count(mut frames: ResMut<usize>) {
+            *frames += 1;
+        }
+
+        let mut builder = App::build();
+        builder.add_resource(0usize).add_system(count.system());
+        let mut app = builder.app;
+        app.executor.measure_stage_times(true);
+        app.executor.initialize(&mut app.resources);
+        app.initialize();
+        app.update_n(3);
+        assert_eq!(*app.resources.get::<usize>().unwrap(), 3);
+        assert!(app
+            .executor
+            .stage_times()
+            .iter()
+            .any(|(stage, _time)| stage.as_ref() == crate::stage::UPDATE));
+    }
+}
@@ -1,10 +1,14 @@
 use crate::{
     app::{App, AppExit},
     event::Events,
+    game_state::{game_state_system, GameState, GameStateChanged},
     plugin::Plugin,
     stage, startup_stage, PluginGroup, PluginGroupBuilder,
 };
-use bevy_ecs::{FromResources, IntoQuerySystem, Resources, System, World};
+use bevy_ecs::{
+    entity_sparse_set_maintenance_system, init_resource_system, Component, EntitySparseSet,
+    FromResources, FromWorld, IntoQuerySystem, IntoThreadLocalSystem, Resources, System, World,
+};
 
 /// Configure [App]s using the builder pattern
 pub struct AppBuilder {
@@ -19,6 +23,11 @@ impl Default for AppBuilder {
 
         app_builder.add_default_stages();
         app_builder.add_event::<AppExit>();
+        // entity sparse sets can be created by commands at any time, so they are always maintained
+        app_builder.add_system_to_stage(
+            stage::LAST,
+            entity_sparse_set_maintenance_system.thread_local_system(),
+        );
         app_builder
     }
 }
@@ -221,6 +230,27 @@ impl AppBuilder {
             .add_system_to_stage(stage::EVENT, Events::<T>::update_system.system())
     }
 
+    /// Adds a [GameState] resource that starts in the `initial` state, and sends [GameStateChanged] events when it
+    /// changes
+    pub fn add_game_state<S>(&mut self, initial: S) -> &mut Self
+    where
+        S: Clone + PartialEq + Send + Sync + 'static,
+    {
+        self.add_resource(GameState::new(initial))
+            .add_event::<GameStateChanged<S>>()
+            .add_system_to_stage(stage::LAST, game_state_system::<S>.system())
+    }
+
+    /// Adds an [EntitySparseSet] of `T` values, which entities can get and lose without moving between archetypes.
+    /// `Commands::insert_into_sparse_set` creates the set the same way, so this only makes it available from the start.
+    pub fn add_entity_sparse_set<T>(&mut self) -> &mut Self
+    where
+        T: Component,
+    {
+        EntitySparseSet::<T>::init(self.resources_mut());
+        self
+    }
+
     /// Adds a resource to the current [App] and overwrites any resource previously added of the same type.
     pub fn add_resource<T>(&mut self, resource: T) -> &mut Self
     where
@@ -248,6 +278,19 @@ impl AppBuilder {
         self
     }
 
+    /// Initializes `R` with [FromWorld] in the [startup_stage::PRE_STARTUP] stage, after every plugin is built. Use this
+    /// for resources that need resources from other plugins, like the render resource context, so they don't depend on
+    /// the order plugins are added in. Nothing is initialized if `R` was added in the meantime.
+    pub fn init_resource_from_world<R>(&mut self) -> &mut Self
+    where
+        R: FromWorld + Send + Sync + 'static,
+    {
+        self.add_startup_system_to_stage(
+            startup_stage::PRE_STARTUP,
+            init_resource_system::<R>.thread_local_system(),
+        )
+    }
+
     pub fn init_thread_local_resource<R>(&mut self) -> &mut Self
     where
         R: FromResources + 'static,
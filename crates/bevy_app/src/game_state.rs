@@ -0,0 +1,72 @@
+use crate::event::Events;
+use bevy_ecs::ResMut;
+
+/// The state a game is in, for example its main menu or one of its levels. Add it with
+/// [AppBuilder::add_game_state](crate::AppBuilder::add_game_state). Systems check [GameState::current] to decide what
+/// to do, and [GameState::set] moves the game to another state.
+#[derive(Debug, Clone)]
+pub struct GameState<S> {
+    current: S,
+    /// The state at the end of the previous frame. `None` in the first frame.
+    previous: Option<S>,
+}
+
+/// Sent at the end of each frame the [GameState] changed in
+#[derive(Debug, Clone)]
+pub struct GameStateChanged<S> {
+    /// The state at the end of the previous frame. `None` for the initial state.
+    pub from: Option<S>,
+    pub to: S,
+}
+
+impl<S: Clone + PartialEq> GameState<S> {
+    pub fn new(initial: S) -> Self {
+        GameState {
+            current: initial,
+            previous: None,
+        }
+    }
+
+    pub fn current(&self) -> &S {
+        &self.current
+    }
+
+    pub fn set(&mut self, state: S) {
+        self.current = state;
+    }
+
+    /// Whether the state changed since the end of the previous frame
+    pub fn changed(&self) -> bool {
+        self.previous.as_ref() != Some(&self.current)
+    }
+}
+
+/// Sends a [GameStateChanged] event when the [GameState] changed during the frame
+pub fn game_state_system<S: Clone + PartialEq + Send + Sync + 'static>(
+    mut state: ResMut<GameState<S>>,
+    mut events: ResMut<Events<GameStateChanged<S>>>,
+) {
+    if state.changed() {
+        events.send(GameStateChanged {
+            from: state.previous.clone(),
+            to: state.current.clone(),
+        });
+        state.previous = Some(state.current.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_state_changes() {
+        let mut state = GameState::new(1);
+        assert!(state.changed());
+        state.previous = Some(1);
+        assert!(!state.changed());
+        state.set(2);
+        assert_eq!(*state.current(), 2);
+        assert!(state.changed());
+    }
+}
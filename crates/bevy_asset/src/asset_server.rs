@@ -1,16 +1,21 @@
 use crate::{
     path::{AssetPath, AssetPathId, SourcePathId},
-    Asset, AssetIo, AssetIoError, AssetLifecycle, AssetLifecycleChannel, AssetLifecycleEvent,
-    AssetLoader, Assets, Handle, HandleId, HandleUntyped, LabelId, LoadContext, LoadState,
-    RefChange, RefChangeChannel, SourceInfo, SourceMeta,
+    Asset, AssetImportMeta, AssetIo, AssetIoError, AssetLifecycle, AssetLifecycleChannel,
+    AssetLifecycleEvent, AssetLoader, Assets, Handle, HandleId, HandleUntyped, LabelId,
+    LoadContext, LoadProgress, LoadState, Locale, RefChange, RefChangeChannel, SourceInfo,
+    SourceMeta,
 };
 use anyhow::Result;
 use bevy_ecs::Res;
 use bevy_tasks::TaskPool;
-use bevy_utils::HashMap;
+use bevy_utils::{HashMap, HashSet};
 use crossbeam_channel::TryRecvError;
 use parking_lot::RwLock;
-use std::{collections::hash_map::Entry, path::Path, sync::Arc};
+use std::{
+    collections::hash_map::Entry,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -27,6 +32,8 @@ pub enum AssetServerError {
     AssetLoaderError(anyhow::Error),
     #[error("PathLoader encountered an error")]
     PathLoaderError(#[from] AssetIoError),
+    #[error("Failed to parse the asset meta file.")]
+    InvalidAssetMeta(PathBuf, ron::Error),
 }
 
 #[derive(Default)]
@@ -43,6 +50,10 @@ pub struct AssetServerInternal {
     loaders: RwLock<Vec<Arc<Box<dyn AssetLoader>>>>,
     extension_to_loader_index: RwLock<HashMap<String, usize>>,
     handle_to_path: Arc<RwLock<HashMap<HandleId, AssetPath<'static>>>>,
+    uuid_to_path: RwLock<HashMap<Uuid, PathBuf>>,
+    locale: RwLock<Locale>,
+    /// The locale variants that asset sources were loaded from
+    locale_variants: RwLock<HashMap<PathBuf, PathBuf>>,
     task_pool: TaskPool,
 }
 
@@ -68,6 +79,9 @@ impl AssetServer {
                 asset_sources: Default::default(),
                 asset_ref_counter: Default::default(),
                 handle_to_path: Default::default(),
+                uuid_to_path: Default::default(),
+                locale: Default::default(),
+                locale_variants: Default::default(),
                 asset_lifecycles: Default::default(),
                 task_pool,
                 asset_io: Box::new(source_io),
@@ -156,6 +170,18 @@ impl AssetServer {
         }
     }
 
+    /// Whether every strong handle to the asset was dropped, which means it was freed or will be on the next update.
+    /// Assets that never had a strong handle, like the ones added with [Assets::set_untracked](crate::Assets::set_untracked),
+    /// are never freed. Handles dropped since the last [AssetServer::free_unused_assets] aren't counted yet.
+    pub fn is_freed<H: Into<HandleId>>(&self, handle: H) -> bool {
+        self.server
+            .asset_ref_counter
+            .ref_counts
+            .read()
+            .get(&handle.into())
+            .map_or(false, |ref_count| *ref_count == 0)
+    }
+
     pub fn get_group_load_state(&self, handles: impl IntoIterator<Item = HandleId>) -> LoadState {
         let mut load_state = LoadState::Loaded;
         for handle_id in handles {
@@ -175,10 +201,223 @@ impl AssetServer {
         load_state
     }
 
+    /// Counts how many of the given assets, and of the assets they depend on, are loaded. Dependencies are only known
+    /// once the asset that depends on them is loaded, so the total can grow while loading. Handles of assets that
+    /// weren't loaded by the asset server are left out.
+    pub fn get_group_load_progress(
+        &self,
+        handles: impl IntoIterator<Item = HandleId>,
+    ) -> LoadProgress {
+        let asset_sources = self.server.asset_sources.read();
+        let mut pending = handles
+            .into_iter()
+            .filter_map(|handle_id| match handle_id {
+                HandleId::AssetPathId(id) => Some(id.source_path_id()),
+                HandleId::Id(_, _) => None,
+            })
+            .collect::<Vec<_>>();
+        let mut visited = HashSet::default();
+        let mut progress = LoadProgress::default();
+        while let Some(source_path_id) = pending.pop() {
+            if !visited.insert(source_path_id) {
+                continue;
+            }
+
+            progress.total += 1;
+            let source_info = match asset_sources.get(&source_path_id) {
+                Some(source_info) => source_info,
+                None => continue,
+            };
+            match source_info.load_state {
+                LoadState::Loaded => progress.loaded += 1,
+                LoadState::Failed => progress.failed += 1,
+                LoadState::NotLoaded | LoadState::Loading => {}
+            }
+            if let Some(meta) = source_info.meta.as_ref() {
+                for asset_meta in meta.assets.iter() {
+                    pending.extend(
+                        asset_meta
+                            .dependencies
+                            .iter()
+                            .map(|dependency| dependency.get_id().source_path_id()),
+                    );
+                }
+            }
+        }
+
+        progress
+    }
+
     pub fn load<'a, T: Asset, P: Into<AssetPath<'a>>>(&self, path: P) -> Handle<T> {
         self.load_untyped(path).typed()
     }
 
+    pub fn locale(&self) -> Locale {
+        self.server.locale.read().clone()
+    }
+
+    /// Sets the locale assets are loaded for. Loaded assets that have a variant for the new locale, or that were
+    /// loaded from a variant for the old locale, are reloaded. See [Locale].
+    pub fn set_locale(&self, locale: Locale) {
+        {
+            let mut current_locale = self.server.locale.write();
+            if *current_locale == locale {
+                return;
+            }
+            *current_locale = locale;
+        }
+
+        let loaded_paths = self
+            .server
+            .asset_sources
+            .read()
+            .values()
+            .filter(|source_info| source_info.load_state != LoadState::NotLoaded)
+            .map(|source_info| source_info.path.clone())
+            .collect::<Vec<_>>();
+        for path in loaded_paths {
+            let server = self.clone();
+            self.server
+                .task_pool
+                .spawn(async move {
+                    if let Err(err) = server.reload_for_locale(&path).await {
+                        log::error!("Failed to reload {:?} for the new locale: {}", path, err);
+                    }
+                })
+                .detach();
+        }
+    }
+
+    async fn reload_for_locale(&self, path: &Path) -> Result<(), AssetServerError> {
+        let locale = self.locale();
+        let mut variant_path = None;
+        for variant in locale.variant_paths(path) {
+            match self.server.asset_io.exists(&variant).await {
+                Ok(true) => {
+                    variant_path = Some(variant);
+                    break;
+                }
+                Ok(false) => continue,
+                // an unreadable variant is skipped, which falls back to a less specific variant or the default asset
+                Err(err) => log::warn!("Failed to check locale variant {:?}: {}", variant, err),
+            }
+        }
+
+        let variant_changed = variant_path.as_ref() != self.server.locale_variants.read().get(path);
+        if variant_changed {
+            self.load_async(path.to_owned(), true).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the bytes of the most specific locale variant of the asset source at `path`. Also returns the path of
+    /// the variant, if one was found.
+    async fn load_localized_bytes(
+        &self,
+        path: &Path,
+    ) -> Result<(Vec<u8>, Option<PathBuf>), AssetIoError> {
+        let locale = self.locale();
+        for variant_path in locale.variant_paths(path) {
+            match self.server.asset_io.load_path(&variant_path).await {
+                Ok(bytes) => return Ok((bytes, Some(variant_path))),
+                Err(AssetIoError::NotFound(_)) => continue,
+                Err(err) => {
+                    log::warn!(
+                        "Failed to load locale variant {:?}, falling back to a less specific one: {}",
+                        variant_path,
+                        err
+                    );
+                    continue;
+                }
+            }
+        }
+
+        Ok((self.server.asset_io.load_path(path).await?, None))
+    }
+
+    /// Returns the path of the asset source whose `.meta` file has the given `uuid`. Sources are only known by uuid
+    /// after they were loaded or their folder was indexed with [AssetServer::index_asset_metas].
+    pub fn get_uuid_path(&self, uuid: Uuid) -> Option<PathBuf> {
+        self.server.uuid_to_path.read().get(&uuid).cloned()
+    }
+
+    /// Loads the asset source whose `.meta` file has the given `uuid`. See [AssetServer::get_uuid_path].
+    pub fn load_by_uuid<T: Asset>(&self, uuid: Uuid) -> Option<Handle<T>> {
+        self.get_uuid_path(uuid)
+            .map(|path| self.load(path.as_path()))
+    }
+
+    /// Reads every `.meta` file in the given folder and its subfolders, so that the assets in the folder can be
+    /// loaded by uuid before they were loaded by path
+    pub fn index_asset_metas<P: AsRef<Path>>(&self, path: P) -> Result<(), AssetServerError> {
+        let mut meta_paths = Vec::new();
+        self.find_asset_metas(path.as_ref(), &mut meta_paths)?;
+
+        let asset_io = &*self.server.asset_io;
+        let results = self.server.task_pool.scope(|scope| {
+            for meta_path in meta_paths {
+                scope.spawn(async move {
+                    let bytes = asset_io.load_path(&meta_path).await?;
+                    let source_path = AssetImportMeta::source_path(&meta_path).unwrap();
+                    AssetImportMeta::from_bytes(&bytes)
+                        .map(|meta| (meta.uuid, source_path))
+                        .map_err(|err| AssetServerError::InvalidAssetMeta(meta_path, err))
+                });
+            }
+        });
+
+        let mut uuid_to_path = self.server.uuid_to_path.write();
+        for result in results {
+            let (uuid, source_path) = result?;
+            uuid_to_path.insert(uuid, source_path);
+        }
+
+        Ok(())
+    }
+
+    fn find_asset_metas(
+        &self,
+        path: &Path,
+        meta_paths: &mut Vec<PathBuf>,
+    ) -> Result<(), AssetServerError> {
+        if !self.server.asset_io.is_directory(path) {
+            return Err(AssetServerError::AssetFolderNotADirectory(
+                path.to_str().unwrap().to_string(),
+            ));
+        }
+
+        for child_path in self.server.asset_io.read_directory(path)? {
+            if self.server.asset_io.is_directory(&child_path) {
+                self.find_asset_metas(&child_path, meta_paths)?;
+            } else if AssetImportMeta::source_path(&child_path).is_some() {
+                meta_paths.push(child_path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the `.meta` file of the asset source at `path`. Sources without a `.meta` file have no metadata.
+    async fn load_asset_meta(
+        &self,
+        path: &Path,
+    ) -> Result<Option<AssetImportMeta>, AssetServerError> {
+        let meta_path = AssetImportMeta::meta_path(path);
+        let bytes = match self.server.asset_io.load_path(&meta_path).await {
+            Ok(bytes) => bytes,
+            Err(AssetIoError::NotFound(_)) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let meta = AssetImportMeta::from_bytes(&bytes)
+            .map_err(|err| AssetServerError::InvalidAssetMeta(meta_path, err))?;
+        self.server
+            .uuid_to_path
+            .write()
+            .insert(meta.uuid, path.to_owned());
+        Ok(Some(meta))
+    }
+
     // TODO: properly set failed LoadState in all failure cases
     async fn load_async<'a, P: Into<AssetPath<'a>>>(
         &self,
@@ -220,14 +459,17 @@ impl AssetServer {
             source_info.version
         };
 
-        // load the asset bytes
-        let bytes = self.server.asset_io.load_path(asset_path.path()).await?;
+        // load the asset bytes and the import settings from the asset's meta file
+        let (bytes, variant_path) = self.load_localized_bytes(asset_path.path()).await?;
+        let import_meta = self.load_asset_meta(asset_path.path()).await?;
+        let has_import_meta = import_meta.is_some();
 
         // load the asset source using the corresponding AssetLoader
         let mut load_context = LoadContext::new(
             asset_path.path(),
             &self.server.asset_ref_counter.channel,
             &*self.server.asset_io,
+            import_meta,
             version,
         );
         asset_loader
@@ -244,6 +486,15 @@ impl AssetServer {
             return Ok(asset_path_id);
         }
 
+        {
+            let mut locale_variants = self.server.locale_variants.write();
+            if let Some(variant_path) = variant_path {
+                locale_variants.insert(asset_path.path().to_owned(), variant_path);
+            } else {
+                locale_variants.remove(asset_path.path());
+            }
+        }
+
         // if all assets have been committed already (aka there were 0), set state to "Loaded"
         if source_info.is_loaded() {
             source_info.load_state = LoadState::Loaded;
@@ -272,6 +523,12 @@ impl AssetServer {
             .asset_io
             .watch_path_for_changes(asset_path.path())
             .unwrap();
+        if has_import_meta {
+            self.server
+                .asset_io
+                .watch_path_for_changes(&AssetImportMeta::meta_path(asset_path.path()))
+                .unwrap();
+        }
         self.create_assets_in_load_context(&mut load_context);
         Ok(asset_path_id)
     }
@@ -292,7 +549,15 @@ impl AssetServer {
         self.server
             .task_pool
             .spawn(async move {
-                server.load_async(owned_path, force).await.unwrap();
+                let source_path_id = owned_path.get_id().source_path_id();
+                if let Err(err) = server.load_async(owned_path.clone(), force).await {
+                    log::error!("Failed to load {:?}: {}", owned_path.path(), err);
+                    if let Some(source_info) =
+                        server.server.asset_sources.write().get_mut(&source_path_id)
+                    {
+                        source_info.load_state = LoadState::Failed;
+                    }
+                }
             })
             .detach();
         asset_path.into()
@@ -6,6 +6,7 @@ use bevy_ecs::{FromResources, IntoQuerySystem, ResMut};
 use bevy_type_registry::RegisterType;
 use bevy_utils::HashMap;
 use crossbeam_channel::Sender;
+use parking_lot::Mutex;
 use std::fmt::Debug;
 
 /// Events that happen on assets of type `T`
@@ -43,26 +44,86 @@ impl<T: Asset> Debug for AssetEvent<T> {
     }
 }
 
+/// A change to an asset that was queued with shared access to [Assets]
+enum QueuedAssetChange<T: Asset> {
+    Set(HandleId, T),
+    Modify(HandleId, Box<dyn FnOnce(&mut T) + Send + Sync>),
+    Remove(HandleId),
+}
+
+impl<T: Asset> Debug for QueuedAssetChange<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueuedAssetChange::Set(id, _) => f.debug_tuple("Set").field(id).finish(),
+            QueuedAssetChange::Modify(id, _) => f.debug_tuple("Modify").field(id).finish(),
+            QueuedAssetChange::Remove(id) => f.debug_tuple("Remove").field(id).finish(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct AssetEntry<T> {
+    id: HandleId,
+    asset: T,
+    revision: u64,
+}
+
 /// Stores Assets of a given type and tracks changes to them.
+///
+/// Any number of systems can read assets in parallel through `Res<Assets<T>>`. Those systems can still change assets
+/// by queuing the changes, with [Assets::queue_set], [Assets::queue_modify] and [Assets::queue_remove], which only need
+/// shared access. Queued changes are applied in the order they were queued in [stage::ASSET_EVENTS](crate::stage::ASSET_EVENTS),
+/// before the [AssetEvent]s of the update are sent.
 #[derive(Debug)]
 pub struct Assets<T: Asset> {
-    assets: HashMap<HandleId, T>,
+    /// Assets are stored densely so iterating over them is fast, and are found by handle through `indices`
+    entries: Vec<AssetEntry<T>>,
+    indices: HashMap<HandleId, usize>,
+    revision: u64,
     events: Events<AssetEvent<T>>,
+    queued: Mutex<Vec<QueuedAssetChange<T>>>,
     pub(crate) ref_change_sender: Sender<RefChange>,
 }
 
 impl<T: Asset> Assets<T> {
     pub(crate) fn new(ref_change_sender: Sender<RefChange>) -> Self {
         Assets {
-            assets: HashMap::default(),
+            entries: Vec::new(),
+            indices: HashMap::default(),
+            revision: 0,
             events: Events::default(),
+            queued: Mutex::new(Vec::new()),
             ref_change_sender,
         }
     }
 
+    /// Stores `asset` under `id` and returns whether it replaced an asset
+    fn insert(&mut self, id: HandleId, asset: T) -> bool {
+        self.revision += 1;
+        let revision = self.revision;
+        if let Some(entry) = self.entry_mut(id) {
+            entry.asset = asset;
+            entry.revision = revision;
+            true
+        } else {
+            self.indices.insert(id, self.entries.len());
+            self.entries.push(AssetEntry {
+                id,
+                asset,
+                revision,
+            });
+            false
+        }
+    }
+
+    fn entry_mut(&mut self, id: HandleId) -> Option<&mut AssetEntry<T>> {
+        let index = *self.indices.get(&id)?;
+        Some(&mut self.entries[index])
+    }
+
     pub fn add(&mut self, asset: T) -> Handle<T> {
         let id = HandleId::random::<T>();
-        self.assets.insert(id, asset);
+        self.insert(id, asset);
         self.events.send(AssetEvent::Created {
             handle: Handle::weak(id),
         });
@@ -71,22 +132,13 @@ impl<T: Asset> Assets<T> {
 
     pub fn set<H: Into<HandleId>>(&mut self, handle: H, asset: T) -> Handle<T> {
         let id: HandleId = handle.into();
-        if self.assets.insert(id, asset).is_some() {
-            self.events.send(AssetEvent::Modified {
-                handle: Handle::weak(id),
-            });
-        } else {
-            self.events.send(AssetEvent::Created {
-                handle: Handle::weak(id),
-            });
-        }
-
+        self.set_untracked(id, asset);
         self.get_handle(id)
     }
 
     pub fn set_untracked<H: Into<HandleId>>(&mut self, handle: H, asset: T) {
         let id: HandleId = handle.into();
-        if self.assets.insert(id, asset).is_some() {
+        if self.insert(id, asset) {
             self.events.send(AssetEvent::Modified {
                 handle: Handle::weak(id),
             });
@@ -98,11 +150,12 @@ impl<T: Asset> Assets<T> {
     }
 
     pub fn get<H: Into<HandleId>>(&self, handle: H) -> Option<&T> {
-        self.assets.get(&handle.into())
+        let index = *self.indices.get(&handle.into())?;
+        Some(&self.entries[index].asset)
     }
 
     pub fn contains<H: Into<HandleId>>(&self, handle: H) -> bool {
-        self.assets.contains_key(&handle.into())
+        self.indices.contains_key(&handle.into())
     }
 
     pub fn get_mut<H: Into<HandleId>>(&mut self, handle: H) -> Option<&mut T> {
@@ -110,7 +163,17 @@ impl<T: Asset> Assets<T> {
         self.events.send(AssetEvent::Modified {
             handle: Handle::weak(id),
         });
-        self.assets.get_mut(&id)
+        self.revision += 1;
+        let revision = self.revision;
+        let entry = self.entry_mut(id)?;
+        entry.revision = revision;
+        Some(&mut entry.asset)
+    }
+
+    /// Gets mutable access to an asset without sending a [AssetEvent::Modified] event or changing its revision. Use
+    /// this when the change is propagated some other way, like a partial gpu upload.
+    pub fn get_mut_untracked<H: Into<HandleId>>(&mut self, handle: H) -> Option<&mut T> {
+        self.entry_mut(handle.into()).map(|entry| &mut entry.asset)
     }
 
     pub fn get_handle<H: Into<HandleId>>(&self, handle: H) -> Handle<T> {
@@ -122,52 +185,123 @@ impl<T: Asset> Assets<T> {
         handle: H,
         insert_fn: impl FnOnce() -> T,
     ) -> &mut T {
-        let mut event = None;
         let id: HandleId = handle.into();
-        let borrowed = self.assets.entry(id).or_insert_with(|| {
-            event = Some(AssetEvent::Created {
+        if !self.contains(id) {
+            self.insert(id, insert_fn());
+            self.events.send(AssetEvent::Created {
                 handle: Handle::weak(id),
             });
-            insert_fn()
-        });
-
-        if let Some(event) = event {
-            self.events.send(event);
         }
-        borrowed
+        &mut self.entry_mut(id).unwrap().asset
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (HandleId, &T)> {
-        self.assets.iter().map(|(k, v)| (*k, v))
+        self.entries.iter().map(|entry| (entry.id, &entry.asset))
     }
 
     pub fn ids(&self) -> impl Iterator<Item = HandleId> + '_ {
-        self.assets.keys().cloned()
+        self.entries.iter().map(|entry| entry.id)
+    }
+
+    /// A number that increases whenever the asset is added, replaced or mutably borrowed. Systems can keep the
+    /// revision they last saw to find out whether a particular asset changed, without reading [AssetEvent]s.
+    pub fn revision<H: Into<HandleId>>(&self, handle: H) -> Option<u64> {
+        let index = *self.indices.get(&handle.into())?;
+        Some(self.entries[index].revision)
+    }
+
+    /// The revision of the most recently changed asset
+    pub fn latest_revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The assets that changed after `revision`, which is usually a [Assets::latest_revision] that was kept from an
+    /// earlier update
+    pub fn changed_since(&self, revision: u64) -> impl Iterator<Item = (HandleId, &T)> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.revision > revision)
+            .map(|entry| (entry.id, &entry.asset))
     }
 
     pub fn remove<H: Into<HandleId>>(&mut self, handle: H) -> Option<T> {
         let id: HandleId = handle.into();
-        let asset = self.assets.remove(&id);
-        if asset.is_some() {
-            self.events.send(AssetEvent::Removed {
-                handle: Handle::weak(id),
-            });
+        let index = self.indices.remove(&id)?;
+        let entry = self.entries.swap_remove(index);
+        if let Some(moved) = self.entries.get(index) {
+            self.indices.insert(moved.id, index);
+        }
+        self.events.send(AssetEvent::Removed {
+            handle: Handle::weak(id),
+        });
+        Some(entry.asset)
+    }
+
+    /// Queues `asset` to be stored under `handle`, like [Assets::set_untracked]
+    pub fn queue_set<H: Into<HandleId>>(&self, handle: H, asset: T) {
+        self.queued
+            .lock()
+            .push(QueuedAssetChange::Set(handle.into(), asset));
+    }
+
+    /// Queues `asset` to be added and returns its handle right away. The asset can't be accessed until the queued
+    /// changes are applied.
+    pub fn queue_add(&self, asset: T) -> Handle<T> {
+        let id = HandleId::random::<T>();
+        self.queue_set(id, asset);
+        self.get_handle(id)
+    }
+
+    /// Queues a change to the asset of `handle`, like [Assets::get_mut]. Nothing happens if the asset doesn't exist
+    /// when the change is applied.
+    pub fn queue_modify<H: Into<HandleId>>(
+        &self,
+        handle: H,
+        modify: impl FnOnce(&mut T) + Send + Sync + 'static,
+    ) {
+        self.queued
+            .lock()
+            .push(QueuedAssetChange::Modify(handle.into(), Box::new(modify)));
+    }
+
+    pub fn queue_remove<H: Into<HandleId>>(&self, handle: H) {
+        self.queued
+            .lock()
+            .push(QueuedAssetChange::Remove(handle.into()));
+    }
+
+    /// Applies the changes that were queued with shared access, in order. This happens automatically every update.
+    pub fn apply_queued(&mut self) {
+        let queued = std::mem::take(self.queued.get_mut());
+        for change in queued {
+            match change {
+                QueuedAssetChange::Set(id, asset) => self.set_untracked(id, asset),
+                QueuedAssetChange::Modify(id, modify) => {
+                    if self.contains(id) {
+                        modify(self.get_mut(id).unwrap());
+                    }
+                }
+                QueuedAssetChange::Remove(id) => {
+                    self.remove(id);
+                }
+            }
         }
-        asset
     }
 
     /// Clears the inner asset map, removing all key-value pairs.
     ///
     /// Keeps the allocated memory for reuse.
     pub fn clear(&mut self) {
-        self.assets.clear()
+        self.entries.clear();
+        self.indices.clear();
     }
 
     /// Reserves capacity for at least additional more elements to be inserted into the assets.
     ///
     /// The collection may reserve more space to avoid frequent reallocations.
     pub fn reserve(&mut self, additional: usize) {
-        self.assets.reserve(additional)
+        self.entries.reserve(additional);
+        self.indices.reserve(additional);
     }
 
     /// Shrinks the capacity of the asset map as much as possible.
@@ -175,22 +309,24 @@ impl<T: Asset> Assets<T> {
     /// It will drop down as much as possible while maintaining the internal rules and possibly
     /// leaving some space in accordance with the resize policy.
     pub fn shrink_to_fit(&mut self) {
-        self.assets.shrink_to_fit()
+        self.entries.shrink_to_fit();
+        self.indices.shrink_to_fit();
     }
 
     pub fn asset_event_system(
         mut events: ResMut<Events<AssetEvent<T>>>,
         mut assets: ResMut<Assets<T>>,
     ) {
+        assets.apply_queued();
         events.extend(assets.events.drain())
     }
 
     pub fn len(&self) -> usize {
-        self.assets.len()
+        self.entries.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.assets.is_empty()
+        self.entries.is_empty()
     }
 }
 
@@ -248,3 +384,49 @@ impl AddAsset for AppBuilder {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_type_registry::TypeUuid;
+    use uuid::Uuid;
+
+    #[derive(Debug, PartialEq)]
+    struct Number(u32);
+
+    impl TypeUuid for Number {
+        const TYPE_UUID: Uuid = Uuid::from_u128(0x4c7e9e5b_4d52_43f0_9a51_8e3f4cb2d8a1);
+    }
+
+    #[test]
+    fn queued_changes_are_applied_in_order() {
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut assets = Assets::<Number>::new(sender);
+        let one = assets.add(Number(1));
+        let two = assets.add(Number(2));
+        let three = assets.add(Number(3));
+        let revision = assets.latest_revision();
+
+        let four = assets.queue_add(Number(4));
+        assets.queue_modify(&two, |number| number.0 *= 10);
+        assets.queue_remove(&one);
+        assets.queue_modify(&one, |number| number.0 *= 10);
+        assert!(assets.get(&four).is_none());
+        assert_eq!(assets.len(), 3);
+
+        assets.apply_queued();
+        assert_eq!(assets.len(), 3);
+        assert!(!assets.contains(&one));
+        assert_eq!(assets.get(&two), Some(&Number(20)));
+        assert_eq!(assets.get(&three), Some(&Number(3)));
+        assert_eq!(assets.get(&four), Some(&Number(4)));
+
+        let mut changed = assets
+            .changed_since(revision)
+            .map(|(_, number)| number.0)
+            .collect::<Vec<_>>();
+        changed.sort_unstable();
+        assert_eq!(changed, vec![4, 20]);
+        assert!(assets.revision(&two).unwrap() > assets.revision(&three).unwrap());
+    }
+}
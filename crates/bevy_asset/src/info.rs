@@ -47,3 +47,30 @@ pub enum LoadState {
     Loaded,
     Failed,
 }
+
+/// How far along loading a group of assets is, from [AssetServer::get_group_load_progress](crate::AssetServer)
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct LoadProgress {
+    /// The number of asset sources that are loaded
+    pub loaded: usize,
+    /// The number of asset sources that failed to load
+    pub failed: usize,
+    /// The number of asset sources in the group, including the dependencies known so far
+    pub total: usize,
+}
+
+impl LoadProgress {
+    /// The fraction of asset sources that are loaded, from 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.loaded as f32 / self.total as f32
+        }
+    }
+
+    /// Whether every asset source is loaded. Groups with failed assets are never done.
+    pub fn is_done(&self) -> bool {
+        self.loaded == self.total
+    }
+}
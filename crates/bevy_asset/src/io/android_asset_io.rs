@@ -30,6 +30,15 @@ impl AssetIo for AndroidAssetIo {
         })
     }
 
+    fn exists<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<bool, AssetIoError>> {
+        Box::pin(async move {
+            let asset_manager = ndk_glue::native_activity().asset_manager();
+            Ok(asset_manager
+                .open(&CString::new(path.to_str().unwrap()).unwrap())
+                .is_some())
+        })
+    }
+
     fn read_directory(
         &self,
         _path: &Path,
@@ -1,4 +1,6 @@
-use crate::{filesystem_watcher::FilesystemWatcher, AssetIo, AssetIoError, AssetServer};
+use crate::{
+    filesystem_watcher::FilesystemWatcher, AssetImportMeta, AssetIo, AssetIoError, AssetServer,
+};
 use anyhow::Result;
 use bevy_ecs::{bevy_utils::BoxedFuture, Res};
 use bevy_utils::HashSet;
@@ -61,6 +63,16 @@ impl AssetIo for FileAssetIo {
         })
     }
 
+    fn exists<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<bool, AssetIoError>> {
+        Box::pin(async move {
+            match fs::metadata(self.root_path.join(path)) {
+                Ok(metadata) => Ok(metadata.is_file()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
     fn read_directory(
         &self,
         path: &Path,
@@ -129,13 +141,15 @@ pub fn filesystem_watcher_system(asset_server: Res<AssetServer>) {
                 ..
             } = event
             {
-                for path in paths.iter() {
-                    if !changed.contains(path) {
+                for path in paths {
+                    // changes to a meta file reload the asset source it belongs to
+                    let path = AssetImportMeta::source_path(&path).unwrap_or(path);
+                    if !changed.contains(&path) {
                         let relative_path = path.strip_prefix(&asset_io.root_path).unwrap();
                         let _ = asset_server.load_untracked(relative_path, true);
+                        changed.insert(path);
                     }
                 }
-                changed.extend(paths);
             }
         }
     }
@@ -35,6 +35,8 @@ pub enum AssetIoError {
 /// Handles load requests from an AssetServer
 pub trait AssetIo: Downcast + Send + Sync + 'static {
     fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>>;
+    /// Whether an asset source exists at `path`, checked without reading it
+    fn exists<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<bool, AssetIoError>>;
     fn read_directory(
         &self,
         path: &Path,
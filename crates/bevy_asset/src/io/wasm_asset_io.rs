@@ -28,12 +28,28 @@ impl AssetIo for WasmAssetIo {
                 .await
                 .unwrap();
             let resp: Response = resp_value.dyn_into().unwrap();
+            if !resp.ok() {
+                return Err(AssetIoError::NotFound(path));
+            }
             let data = JsFuture::from(resp.array_buffer().unwrap()).await.unwrap();
             let bytes = Uint8Array::new(&data).to_vec();
             Ok(bytes)
         })
     }
 
+    fn exists<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<bool, AssetIoError>> {
+        Box::pin(async move {
+            let path = self.root_path.join(path);
+            let window = web_sys::window().unwrap();
+            let resp_value = JsFuture::from(window.fetch_with_str(path.to_str().unwrap()))
+                .await
+                .unwrap();
+            let resp: Response = resp_value.dyn_into().unwrap();
+            // the body is never read
+            Ok(resp.ok())
+        })
+    }
+
     fn read_directory(
         &self,
         _path: &Path,
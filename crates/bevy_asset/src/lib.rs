@@ -9,6 +9,8 @@ mod handle;
 mod info;
 mod io;
 mod loader;
+mod locale;
+mod meta;
 mod path;
 
 pub use asset_server::*;
@@ -18,6 +20,8 @@ pub use handle::*;
 pub use info::*;
 pub use io::*;
 pub use loader::*;
+pub use locale::*;
+pub use meta::*;
 pub use path::*;
 
 /// The names of asset stages in an App Schedule
@@ -27,7 +31,7 @@ pub mod stage {
 }
 
 pub mod prelude {
-    pub use crate::{AddAsset, AssetEvent, AssetServer, Assets, Handle, HandleUntyped};
+    pub use crate::{AddAsset, AssetEvent, AssetServer, Assets, Handle, HandleUntyped, Locale};
 }
 
 use bevy_app::{prelude::Plugin, AppBuilder};
@@ -77,7 +81,9 @@ impl Plugin for AssetPlugin {
         app.add_stage_before(bevy_app::stage::PRE_UPDATE, stage::LOAD_ASSETS)
             .add_stage_after(bevy_app::stage::POST_UPDATE, stage::ASSET_EVENTS)
             .add_resource(asset_server)
+            .init_resource::<Locale>()
             .register_property::<HandleId>()
+            .add_system_to_stage(stage::LOAD_ASSETS, locale::asset_locale_system.system())
             .add_system_to_stage(
                 bevy_app::stage::PRE_UPDATE,
                 asset_server::free_unused_assets_system.system(),
@@ -1,6 +1,6 @@
 use crate::{
-    path::AssetPath, AssetIo, AssetIoError, AssetMeta, AssetServer, Assets, Handle, HandleId,
-    RefChangeChannel,
+    path::AssetPath, AssetImportMeta, AssetIo, AssetIoError, AssetMeta, AssetServer, Assets,
+    Handle, HandleId, ImportSettings, RefChangeChannel,
 };
 use anyhow::Result;
 use bevy_ecs::{Res, ResMut, Resource};
@@ -58,6 +58,7 @@ pub struct LoadContext<'a> {
     pub(crate) asset_io: &'a dyn AssetIo,
     pub(crate) labeled_assets: HashMap<Option<String>, LoadedAsset>,
     pub(crate) path: &'a Path,
+    pub(crate) import_meta: Option<AssetImportMeta>,
     pub(crate) version: usize,
 }
 
@@ -66,6 +67,7 @@ impl<'a> LoadContext<'a> {
         path: &'a Path,
         ref_change_channel: &'a RefChangeChannel,
         asset_io: &'a dyn AssetIo,
+        import_meta: Option<AssetImportMeta>,
         version: usize,
     ) -> Self {
         Self {
@@ -74,6 +76,7 @@ impl<'a> LoadContext<'a> {
             labeled_assets: Default::default(),
             version,
             path,
+            import_meta,
         }
     }
 
@@ -81,6 +84,19 @@ impl<'a> LoadContext<'a> {
         &self.path
     }
 
+    /// The contents of the asset source's `.meta` file, if it has one
+    pub fn import_meta(&self) -> Option<&AssetImportMeta> {
+        self.import_meta.as_ref()
+    }
+
+    /// The import settings from the asset source's `.meta` file. If there is no `.meta` file, all settings are unset.
+    pub fn import_settings(&self) -> &ImportSettings {
+        match self.import_meta {
+            Some(ref meta) => &meta.settings,
+            None => ImportSettings::default_ref(),
+        }
+    }
+
     pub fn has_labeled_asset(&self, label: &str) -> bool {
         self.labeled_assets.contains_key(&Some(label.to_string()))
     }
@@ -0,0 +1,92 @@
+use crate::AssetServer;
+use bevy_ecs::{ChangedRes, Res};
+use std::path::{Path, PathBuf};
+
+/// The locale assets and text are localized for, as a language tag such as `ja` or `en-US`. The default locale is
+/// empty, which disables localization.
+///
+/// When a locale is set, the [AssetServer] loads locale variants of assets if they exist. For the locale `ja-JP`,
+/// loading `textures/sign.png` loads the first of `textures/sign.ja-JP.png`, `textures/sign.ja.png` and
+/// `textures/sign.png` that exists. Handles always use the path of the base asset, so changing the locale reloads
+/// assets in place.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Locale {
+    tag: String,
+}
+
+impl Locale {
+    pub fn new<T: Into<String>>(tag: T) -> Self {
+        Locale { tag: tag.into() }
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty()
+    }
+
+    /// Returns the language tags to look for, from most to least specific. `ja-JP` falls back to `ja`.
+    pub fn fallbacks(&self) -> Vec<&str> {
+        let mut fallbacks = Vec::new();
+        let mut tag = self.tag.as_str();
+        while !tag.is_empty() {
+            fallbacks.push(tag);
+            tag = tag.rfind('-').map_or("", |index| &tag[..index]);
+        }
+        fallbacks
+    }
+
+    /// Returns the paths of the locale variants of the asset at `path`, from most to least specific
+    pub fn variant_paths(&self, path: &Path) -> Vec<PathBuf> {
+        let stem = match path.file_stem() {
+            Some(stem) => stem,
+            None => return Vec::new(),
+        };
+
+        self.fallbacks()
+            .into_iter()
+            .map(|tag| {
+                let mut file_name = stem.to_owned();
+                file_name.push(".");
+                file_name.push(tag);
+                if let Some(extension) = path.extension() {
+                    file_name.push(".");
+                    file_name.push(extension);
+                }
+                path.with_file_name(file_name)
+            })
+            .collect()
+    }
+}
+
+/// Passes changes of the [Locale] resource on to the [AssetServer]
+pub fn asset_locale_system(asset_server: Res<AssetServer>, locale: ChangedRes<Locale>) {
+    asset_server.set_locale(locale.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_paths() {
+        let locale = Locale::new("ja-JP");
+        assert_eq!(locale.fallbacks(), vec!["ja-JP", "ja"]);
+        assert_eq!(
+            locale.variant_paths(Path::new("textures/sign.png")),
+            vec![
+                PathBuf::from("textures/sign.ja-JP.png"),
+                PathBuf::from("textures/sign.ja.png")
+            ]
+        );
+        assert_eq!(
+            Locale::new("de").variant_paths(Path::new("LICENSE")),
+            vec![PathBuf::from("LICENSE.de")]
+        );
+        assert!(Locale::default()
+            .variant_paths(Path::new("textures/sign.png"))
+            .is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// The extension of asset metadata sidecar files. The metadata of `textures/sign.png` is stored in
+/// `textures/sign.png.meta`.
+pub const META_EXTENSION: &str = "meta";
+
+static DEFAULT_IMPORT_SETTINGS: ImportSettings = ImportSettings {
+    srgb: None,
+    filter_mode: None,
+    shader_defs: Vec::new(),
+    lod_levels: Vec::new(),
+};
+
+/// The texture filter an asset should be imported with
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ImportFilterMode {
+    Nearest,
+    Linear,
+}
+
+/// A level of detail generated by simplifying a mesh at import
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportLodLevel {
+    /// The fraction of the source mesh's triangles to keep
+    pub ratio: f32,
+    /// The camera distance beyond which this level replaces the previous one
+    pub distance: f32,
+}
+
+/// Settings that control how an [AssetLoader](crate::AssetLoader) imports an asset source. Every setting is optional.
+/// Loaders fall back to their own defaults for settings that aren't set or that don't apply to them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportSettings {
+    /// Whether color data is stored in the sRGB color space
+    pub srgb: Option<bool>,
+    pub filter_mode: Option<ImportFilterMode>,
+    /// Preprocessor definitions applied when the asset is a shader
+    pub shader_defs: Vec<String>,
+    /// Levels of detail to generate for mesh assets, from the most to the least detailed. The source mesh is always the
+    /// first level.
+    pub lod_levels: Vec<ImportLodLevel>,
+}
+
+impl ImportSettings {
+    pub(crate) fn default_ref() -> &'static ImportSettings {
+        &DEFAULT_IMPORT_SETTINGS
+    }
+}
+
+/// The contents of an asset source's `.meta` sidecar file. The uuid identifies the source independently of its path,
+/// so references by uuid survive renames as long as the `.meta` file is moved along with the source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetImportMeta {
+    pub uuid: Uuid,
+    #[serde(default)]
+    pub settings: ImportSettings,
+}
+
+impl Default for AssetImportMeta {
+    fn default() -> Self {
+        AssetImportMeta::new(ImportSettings::default())
+    }
+}
+
+impl AssetImportMeta {
+    /// Creates metadata with a new random uuid
+    pub fn new(settings: ImportSettings) -> Self {
+        AssetImportMeta {
+            uuid: Uuid::new_v4(),
+            settings,
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ron::Error> {
+        ron::de::from_bytes(bytes)
+    }
+
+    pub fn to_ron_string(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// Returns the path of the `.meta` file that belongs to the asset source at `path`
+    pub fn meta_path(path: &Path) -> PathBuf {
+        let mut meta_path = path.as_os_str().to_owned();
+        meta_path.push(".");
+        meta_path.push(META_EXTENSION);
+        meta_path.into()
+    }
+
+    /// Returns the path of the asset source the `.meta` file at `path` belongs to, or `None` if `path` isn't a
+    /// `.meta` file
+    pub fn source_path(path: &Path) -> Option<PathBuf> {
+        if path.extension()? == META_EXTENSION {
+            Some(path.with_extension(""))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meta_paths() {
+        let meta_path = AssetImportMeta::meta_path(Path::new("textures/sign.png"));
+        assert_eq!(meta_path, Path::new("textures/sign.png.meta"));
+        assert_eq!(
+            AssetImportMeta::source_path(&meta_path),
+            Some(PathBuf::from("textures/sign.png"))
+        );
+        assert_eq!(
+            AssetImportMeta::source_path(Path::new("textures/sign.png")),
+            None
+        );
+    }
+
+    #[test]
+    fn settings_are_optional() {
+        let meta = AssetImportMeta::from_bytes(
+            b"(uuid: \"bdd4e5e0-6d0f-4a62-8ee3-2b6f4b3cb1a1\", settings: (srgb: Some(false)))",
+        )
+        .unwrap();
+        assert_eq!(meta.settings.srgb, Some(false));
+        assert_eq!(meta.settings.filter_mode, None);
+        assert!(meta.settings.shader_defs.is_empty());
+
+        let meta = AssetImportMeta::from_bytes(meta.to_ron_string().unwrap().as_bytes()).unwrap();
+        assert_eq!(meta.settings.srgb, Some(false));
+    }
+}
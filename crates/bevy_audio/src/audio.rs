@@ -1,14 +1,17 @@
-use crate::{AudioSource, Decodable};
-use bevy_asset::Handle;
-use parking_lot::RwLock;
-use std::{collections::VecDeque, fmt};
+use crate::{AudioBus, AudioSource, Decodable};
+use bevy_app::{EventReader, Events};
+use bevy_asset::{Asset, AssetEvent, Handle, HandleId};
+use bevy_ecs::{Local, Res, ResMut};
+use bevy_utils::HashSet;
+use parking_lot::{Mutex, RwLock};
+use std::{collections::VecDeque, fmt, marker::PhantomData, sync::Arc};
 
 /// The external struct used to play audio
 pub struct Audio<P = AudioSource>
 where
     P: Decodable,
 {
-    pub queue: RwLock<VecDeque<Handle<P>>>,
+    pub queue: RwLock<VecDeque<(Handle<P>, AudioBus)>>,
 }
 
 impl<P> fmt::Debug for Audio<P>
@@ -37,7 +40,175 @@ where
     <P as Decodable>::Decoder: rodio::Source + Send + Sync,
     <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
 {
+    /// Plays `audio_source` on the [AudioBus::Sfx] bus. Sources that are still loading play once they are loaded.
     pub fn play(&self, audio_source: Handle<P>) {
-        self.queue.write().push_front(audio_source);
+        self.play_on_bus(audio_source, AudioBus::Sfx);
+    }
+
+    pub fn play_on_bus(&self, audio_source: Handle<P>, bus: AudioBus) {
+        self.queue.write().push_front((audio_source, bus));
+    }
+}
+
+/// Sent when an audio source finished loading, or failed to
+#[derive(Debug)]
+pub enum AudioLoadEvent<P: Asset> {
+    /// The source is loaded and decoded, so playing it starts right away
+    Loaded { handle: Handle<P> },
+    /// The source failed to load or decode. Queued plays of it are dropped.
+    Failed { handle: Handle<P> },
+}
+
+#[derive(Debug, Default)]
+struct LoadFailures {
+    /// Failed in the loader, but no event was sent yet
+    pending: Vec<HandleId>,
+    /// Failed in the loader and sent, until the source loads successfully
+    sent: HashSet<HandleId>,
+}
+
+/// Audio sources that failed to load. Loaders add the sources they fail to decode, and [audio_load_events_system]
+/// sends [AudioLoadEvent::Failed] for them.
+pub struct AudioLoadFailures<P> {
+    failures: Arc<Mutex<LoadFailures>>,
+    marker: PhantomData<fn() -> P>,
+}
+
+impl<P> Default for AudioLoadFailures<P> {
+    fn default() -> Self {
+        AudioLoadFailures {
+            failures: Default::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<P> Clone for AudioLoadFailures<P> {
+    fn clone(&self) -> Self {
+        AudioLoadFailures {
+            failures: self.failures.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<P> fmt::Debug for AudioLoadFailures<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AudioLoadFailures")
+            .field("failures", &self.failures)
+            .finish()
+    }
+}
+
+impl<P> AudioLoadFailures<P> {
+    /// Records that the loader failed to load the source with the id `handle`
+    pub fn push(&self, handle: HandleId) {
+        self.failures.lock().pending.push(handle);
+    }
+
+    /// Whether a [AudioLoadEvent::Failed] was or will be sent for the source because its loader failed
+    pub fn contains(&self, handle: HandleId) -> bool {
+        let failures = self.failures.lock();
+        failures.sent.contains(&handle) || failures.pending.contains(&handle)
+    }
+
+    fn take_pending(&self) -> Vec<HandleId> {
+        let mut failures = self.failures.lock();
+        let pending = std::mem::take(&mut failures.pending);
+        failures.sent.extend(pending.iter().copied());
+        pending
+    }
+
+    fn loaded(&self, handle: HandleId) {
+        self.failures.lock().sent.remove(&handle);
+    }
+}
+
+pub struct AudioLoadEventsState<P: Asset> {
+    asset_event_reader: EventReader<AssetEvent<P>>,
+}
+
+impl<P: Asset> Default for AudioLoadEventsState<P> {
+    fn default() -> Self {
+        AudioLoadEventsState {
+            asset_event_reader: Default::default(),
+        }
+    }
+}
+
+/// Sends [AudioLoadEvent::Loaded] for audio sources that were loaded or reloaded, and [AudioLoadEvent::Failed] for
+/// the ones their loader failed to load
+pub fn audio_load_events_system<P: Asset>(
+    mut state: Local<AudioLoadEventsState<P>>,
+    asset_events: Res<Events<AssetEvent<P>>>,
+    load_failures: Res<AudioLoadFailures<P>>,
+    mut load_events: ResMut<Events<AudioLoadEvent<P>>>,
+) {
+    for event in state.asset_event_reader.iter(&asset_events) {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                load_failures.loaded(handle.id);
+                load_events.send(AudioLoadEvent::Loaded {
+                    handle: handle.clone_weak(),
+                })
+            }
+            AssetEvent::Removed { .. } => {}
+        }
+    }
+
+    for handle in load_failures.take_pending() {
+        load_events.send(AudioLoadEvent::Failed {
+            handle: Handle::weak(handle),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{IntoQuerySystem, Resources, Schedule, World};
+
+    #[test]
+    fn audio_load_events() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Events::<AssetEvent<AudioSource>>::default());
+        resources.insert(Events::<AudioLoadEvent<AudioSource>>::default());
+        resources.insert(AudioLoadFailures::<AudioSource>::default());
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", audio_load_events_system::<AudioSource>.system());
+        schedule.initialize(&mut world, &mut resources);
+
+        let loaded = HandleId::random::<AudioSource>();
+        let broken = HandleId::random::<AudioSource>();
+        resources
+            .get_mut::<Events<AssetEvent<AudioSource>>>()
+            .unwrap()
+            .send(AssetEvent::Created {
+                handle: Handle::weak(loaded),
+            });
+        // what the loader does when it can't decode a source
+        let load_failures = resources
+            .get::<AudioLoadFailures<AudioSource>>()
+            .unwrap()
+            .clone();
+        load_failures.push(broken);
+        schedule.run(&mut world, &mut resources);
+
+        let load_events = resources
+            .get::<Events<AudioLoadEvent<AudioSource>>>()
+            .unwrap();
+        let mut reader = load_events.get_reader();
+        let events = reader
+            .iter(&load_events)
+            .map(|event| match event {
+                AudioLoadEvent::Loaded { handle } => (true, handle.id),
+                AudioLoadEvent::Failed { handle } => (false, handle.id),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![(true, loaded), (false, broken)]);
+        assert!(load_failures.contains(broken));
+        assert!(!load_failures.contains(loaded));
     }
 }
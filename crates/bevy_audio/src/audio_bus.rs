@@ -0,0 +1,139 @@
+use bevy_utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+/// A group of sounds that share a volume and effects. Every sound is played through exactly one bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Voice,
+}
+
+impl AudioBus {
+    pub const ALL: [AudioBus; 3] = [AudioBus::Music, AudioBus::Sfx, AudioBus::Voice];
+}
+
+impl Default for AudioBus {
+    fn default() -> Self {
+        AudioBus::Sfx
+    }
+}
+
+/// The volume and effects of an [AudioBus]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BusSettings {
+    /// Linear gain, where 1.0 plays sounds at their original volume
+    pub volume: f32,
+    pub muted: bool,
+    /// Cutoff frequency of a low-pass filter in Hz. Muffles the bus, for example while a pause menu is open.
+    pub low_pass: Option<f32>,
+    /// How much of the bus is sent to a short reverb, from 0.0 (dry) to 1.0
+    pub reverb_send: f32,
+}
+
+const DEFAULT_BUS_SETTINGS: BusSettings = BusSettings {
+    volume: 1.0,
+    muted: false,
+    low_pass: None,
+    reverb_send: 0.0,
+};
+
+impl Default for BusSettings {
+    fn default() -> Self {
+        DEFAULT_BUS_SETTINGS
+    }
+}
+
+impl BusSettings {
+    /// The gain sounds on the bus are played with, taking [BusSettings::muted] into account
+    pub fn gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume.max(0.0)
+        }
+    }
+}
+
+/// The settings of all [AudioBus]es. Changes apply to sounds that are already playing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioBuses {
+    pub master_volume: f32,
+    buses: HashMap<AudioBus, BusSettings>,
+}
+
+impl Default for AudioBuses {
+    fn default() -> Self {
+        AudioBuses {
+            master_volume: 1.0,
+            buses: AudioBus::ALL
+                .iter()
+                .map(|bus| (*bus, BusSettings::default()))
+                .collect(),
+        }
+    }
+}
+
+impl AudioBuses {
+    pub fn get(&self, bus: AudioBus) -> &BusSettings {
+        self.buses.get(&bus).unwrap_or(&DEFAULT_BUS_SETTINGS)
+    }
+
+    pub fn get_mut(&mut self, bus: AudioBus) -> &mut BusSettings {
+        self.buses.entry(bus).or_insert_with(BusSettings::default)
+    }
+
+    pub fn set_volume(&mut self, bus: AudioBus, volume: f32) {
+        self.get_mut(bus).volume = volume;
+    }
+
+    pub fn set_muted(&mut self, bus: AudioBus, muted: bool) {
+        self.get_mut(bus).muted = muted;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (AudioBus, &BusSettings)> {
+        AudioBus::ALL.iter().map(move |bus| (*bus, self.get(*bus)))
+    }
+}
+
+/// The bus settings as seen by the audio thread
+#[derive(Debug, Default)]
+pub(crate) struct BusParams {
+    gain: AtomicU32,
+    low_pass: AtomicU32,
+    reverb_send: AtomicU32,
+}
+
+impl BusParams {
+    pub(crate) fn new(settings: &BusSettings, master_volume: f32) -> Arc<Self> {
+        let params = Arc::new(BusParams::default());
+        params.update(settings, master_volume);
+        params
+    }
+
+    pub(crate) fn update(&self, settings: &BusSettings, master_volume: f32) {
+        let store = |value: &AtomicU32, float: f32| value.store(float.to_bits(), Ordering::Relaxed);
+        store(&self.gain, settings.gain() * master_volume.max(0.0));
+        store(&self.low_pass, settings.low_pass.unwrap_or(0.0));
+        store(&self.reverb_send, settings.reverb_send.max(0.0).min(1.0));
+    }
+
+    pub(crate) fn gain(&self) -> f32 {
+        f32::from_bits(self.gain.load(Ordering::Relaxed))
+    }
+
+    /// The low-pass cutoff frequency, or 0.0 if the filter is off
+    pub(crate) fn low_pass(&self) -> f32 {
+        f32::from_bits(self.low_pass.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn reverb_send(&self) -> f32 {
+        f32::from_bits(self.reverb_send.load(Ordering::Relaxed))
+    }
+}
@@ -1,8 +1,13 @@
-use crate::{Audio, AudioSource, Decodable};
-use bevy_asset::{Asset, Assets};
+use crate::{
+    audio_bus::BusParams, bus_source::BusSource, Audio, AudioBus, AudioBuses, AudioLoadEvent,
+    AudioLoadFailures, AudioSource, Decodable,
+};
+use bevy_app::Events;
+use bevy_asset::{Asset, AssetServer, Assets, LoadState};
 use bevy_ecs::{Resources, World};
-use rodio::{OutputStream, OutputStreamHandle, Sink};
-use std::marker::PhantomData;
+use bevy_utils::HashMap;
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use std::{marker::PhantomData, sync::Arc};
 
 /// Used internally to play audio on the current "audio device"
 pub struct AudioOutput<P = AudioSource>
@@ -11,6 +16,7 @@ where
 {
     _stream: OutputStream,
     stream_handle: OutputStreamHandle,
+    buses: HashMap<AudioBus, Arc<BusParams>>,
     phantom: PhantomData<P>,
 }
 
@@ -20,10 +26,15 @@ where
 {
     fn default() -> Self {
         let (stream, stream_handle) = OutputStream::try_default().unwrap();
+        let bus_settings = AudioBuses::default();
 
         Self {
             _stream: stream,
             stream_handle,
+            buses: bus_settings
+                .iter()
+                .map(|(bus, settings)| (bus, BusParams::new(settings, bus_settings.master_volume)))
+                .collect(),
             phantom: PhantomData,
         }
     }
@@ -35,23 +46,52 @@ where
     <P as Decodable>::Decoder: rodio::Source + Send + Sync,
     <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
 {
-    fn play_source(&self, audio_source: &P) {
+    fn play_source(&self, audio_source: &P, bus: AudioBus) {
         let sink = Sink::try_new(&self.stream_handle).unwrap();
-        sink.append(audio_source.decoder());
+        sink.append(BusSource::new(
+            audio_source.decoder().convert_samples::<f32>(),
+            self.buses[&bus].clone(),
+        ));
         sink.detach();
     }
 
-    fn try_play_queued(&self, audio_sources: &Assets<P>, audio: &mut Audio<P>) {
+    /// Applies changed [AudioBuses] settings to the sounds that are playing
+    fn update_buses(&self, audio_buses: &AudioBuses) {
+        for (bus, settings) in audio_buses.iter() {
+            self.buses[&bus].update(settings, audio_buses.master_volume);
+        }
+    }
+
+    fn try_play_queued(
+        &self,
+        audio_sources: &Assets<P>,
+        asset_server: Option<&AssetServer>,
+        load_failures: Option<&AudioLoadFailures<P>>,
+        audio: &mut Audio<P>,
+        load_events: &mut Events<AudioLoadEvent<P>>,
+    ) {
         let mut queue = audio.queue.write();
         let len = queue.len();
         let mut i = 0;
         while i < len {
-            let audio_source_handle = queue.pop_back().unwrap();
+            let (audio_source_handle, bus) = queue.pop_back().unwrap();
             if let Some(audio_source) = audio_sources.get(&audio_source_handle) {
-                self.play_source(audio_source);
+                self.play_source(audio_source, bus);
+            } else if asset_server.map(|server| server.get_load_state(&audio_source_handle))
+                == Some(LoadState::Failed)
+            {
+                // the audio source will never load, so drop it instead of waiting for it. failures of the loader already
+                // have an event, but the file may not have been read at all
+                if !load_failures
+                    .map_or(false, |failures| failures.contains(audio_source_handle.id))
+                {
+                    load_events.send(AudioLoadEvent::Failed {
+                        handle: audio_source_handle.clone_weak(),
+                    });
+                }
             } else {
                 // audio source hasn't loaded yet. add it back to the queue
-                queue.push_front(audio_source_handle);
+                queue.push_front((audio_source_handle, bus));
             }
             i += 1;
         }
@@ -67,8 +107,22 @@ where
 {
     let audio_output = resources.get_thread_local::<AudioOutput<P>>().unwrap();
     let mut audio = resources.get_mut::<Audio<P>>().unwrap();
+    if let Some(audio_buses) = resources.get::<AudioBuses>() {
+        audio_output.update_buses(&audio_buses);
+    }
 
-    if let Some(audio_sources) = resources.get::<Assets<P>>() {
-        audio_output.try_play_queued(&*audio_sources, &mut *audio);
+    let asset_server = resources.get::<AssetServer>();
+    let load_failures = resources.get::<AudioLoadFailures<P>>();
+    if let (Some(audio_sources), Some(mut load_events)) = (
+        resources.get::<Assets<P>>(),
+        resources.get_mut::<Events<AudioLoadEvent<P>>>(),
+    ) {
+        audio_output.try_play_queued(
+            &*audio_sources,
+            asset_server.as_deref(),
+            load_failures.as_deref(),
+            &mut *audio,
+            &mut *load_events,
+        );
     }
 }
@@ -1,14 +1,62 @@
-use anyhow::Result;
-use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use crate::AudioLoadFailures;
+use anyhow::{anyhow, Result};
+use bevy_asset::{AssetLoader, AssetPath, HandleId, LoadContext, LoadedAsset};
+use bevy_ecs::{FromResources, Resources};
 use bevy_type_registry::TypeUuid;
 use bevy_utils::BoxedFuture;
-use std::{io::Cursor, sync::Arc};
+use rodio::Source;
+use std::{io::Cursor, sync::Arc, time::Duration};
+
+/// Sounds up to this long are decoded when they load. Longer sounds, like music, and sounds whose length isn't known
+/// without decoding them are decoded while they play.
+pub const MAX_PREDECODED_SECONDS: u32 = 30;
 
 /// A source of audio data
 #[derive(Debug, Clone, TypeUuid)]
 #[uuid = "7a14806a-672b-443b-8d16-4f18afefa463"]
 pub struct AudioSource {
     pub bytes: Arc<[u8]>,
+    /// The decoded samples, for sounds short enough to be decoded when they load
+    pub decoded: Option<DecodedAudio>,
+}
+
+impl AudioSource {
+    /// An audio source that is decoded while it plays
+    pub fn new(bytes: impl Into<Arc<[u8]>>) -> Self {
+        AudioSource {
+            bytes: bytes.into(),
+            decoded: None,
+        }
+    }
+
+    /// Checks that the source is audio in a supported format by reading its header and first frame. Decodes it if
+    /// its header says it is at most `max_seconds` long.
+    pub fn decode(bytes: impl Into<Arc<[u8]>>, max_seconds: u32) -> Result<Self> {
+        let mut source = AudioSource::new(bytes);
+        let mut decoder = rodio::Decoder::new(Cursor::new(source.clone()))?;
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        let short = decoder.total_duration().map_or(false, |duration| {
+            duration <= Duration::from_secs(max_seconds as u64)
+        });
+        if !short {
+            decoder
+                .next()
+                .ok_or_else(|| anyhow!("The audio source has no samples"))?;
+            return Ok(source);
+        }
+
+        let samples = decoder.collect::<Vec<i16>>();
+        if samples.is_empty() {
+            return Err(anyhow!("The audio source has no samples"));
+        }
+        source.decoded = Some(DecodedAudio {
+            channels,
+            sample_rate,
+            samples: samples.into(),
+        });
+        Ok(source)
+    }
 }
 
 impl AsRef<[u8]> for AudioSource {
@@ -17,16 +65,52 @@ impl AsRef<[u8]> for AudioSource {
     }
 }
 
-/// Loads mp3 files as [AudioSource] [Assets](bevy_asset::Assets)
-#[derive(Default)]
-pub struct Mp3Loader;
+/// Interleaved samples of a decoded sound
+#[derive(Debug, Clone)]
+pub struct DecodedAudio {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub samples: Arc<[i16]>,
+}
+
+/// Loads mp3, flac, wav and ogg files as [AudioSource] [Assets](bevy_asset::Assets). Short sounds are decoded on the
+/// asset task pool, so playing them doesn't decode anything on the main thread. Sources that fail to load are added to
+/// the [AudioLoadFailures] resource.
+pub struct Mp3Loader {
+    load_failures: AudioLoadFailures<AudioSource>,
+}
+
+impl FromResources for Mp3Loader {
+    fn from_resources(resources: &Resources) -> Self {
+        Mp3Loader {
+            load_failures: resources
+                .get::<AudioLoadFailures<AudioSource>>()
+                .map(|load_failures| load_failures.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
 
 impl AssetLoader for Mp3Loader {
-    fn load(&self, bytes: &[u8], load_context: &mut LoadContext) -> BoxedFuture<Result<()>> {
-        load_context.set_default_asset(LoadedAsset::new(AudioSource {
-            bytes: bytes.into(),
-        }));
-        Box::pin(async move { Ok(()) })
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let audio_source = match AudioSource::decode(bytes, MAX_PREDECODED_SECONDS) {
+                Ok(audio_source) => audio_source,
+                Err(err) => {
+                    self.load_failures.push(HandleId::from(AssetPath::new_ref(
+                        load_context.path(),
+                        None,
+                    )));
+                    return Err(err);
+                }
+            };
+            load_context.set_default_asset(LoadedAsset::new(audio_source));
+            Ok(())
+        })
     }
 
     fn extensions(&self) -> &[&str] {
@@ -42,9 +126,79 @@ pub trait Decodable: Send + Sync + 'static {
 }
 
 impl Decodable for AudioSource {
-    type Decoder = rodio::Decoder<Cursor<AudioSource>>;
+    type Decoder = AudioSourceDecoder;
 
     fn decoder(&self) -> Self::Decoder {
-        rodio::Decoder::new(Cursor::new(self.clone())).unwrap()
+        match self.decoded {
+            Some(ref decoded) => AudioSourceDecoder::Decoded {
+                audio: decoded.clone(),
+                position: 0,
+            },
+            None => {
+                AudioSourceDecoder::Encoded(rodio::Decoder::new(Cursor::new(self.clone())).unwrap())
+            }
+        }
+    }
+}
+
+/// Plays an [AudioSource], from its decoded samples or by decoding it
+pub enum AudioSourceDecoder {
+    Decoded {
+        audio: DecodedAudio,
+        position: usize,
+    },
+    Encoded(rodio::Decoder<Cursor<AudioSource>>),
+}
+
+impl Iterator for AudioSourceDecoder {
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        match self {
+            AudioSourceDecoder::Decoded { audio, position } => {
+                let sample = audio.samples.get(*position).copied();
+                *position += 1;
+                sample
+            }
+            AudioSourceDecoder::Encoded(decoder) => decoder.next(),
+        }
+    }
+}
+
+impl Source for AudioSourceDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        match self {
+            AudioSourceDecoder::Decoded { audio, position } => {
+                Some(audio.samples.len().saturating_sub(*position))
+            }
+            AudioSourceDecoder::Encoded(decoder) => decoder.current_frame_len(),
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        match self {
+            AudioSourceDecoder::Decoded { audio, .. } => audio.channels,
+            AudioSourceDecoder::Encoded(decoder) => decoder.channels(),
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        match self {
+            AudioSourceDecoder::Decoded { audio, .. } => audio.sample_rate,
+            AudioSourceDecoder::Encoded(decoder) => decoder.sample_rate(),
+        }
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        match self {
+            AudioSourceDecoder::Decoded { audio, .. } => {
+                let frames = audio.samples.len() / audio.channels.max(1) as usize;
+                Some(Duration::from_secs_f64(
+                    frames as f64 / audio.sample_rate as f64,
+                ))
+            }
+            AudioSourceDecoder::Encoded(decoder) => decoder.total_duration(),
+        }
     }
 }
@@ -0,0 +1,155 @@
+use crate::audio_bus::BusParams;
+use rodio::Source;
+use std::{f32::consts::PI, sync::Arc, time::Duration};
+
+/// How many samples are played before the bus settings are read again
+const PARAMS_UPDATE_INTERVAL: usize = 512;
+const REVERB_DELAY: Duration = Duration::from_millis(70);
+const REVERB_FEEDBACK: f32 = 0.45;
+
+/// Applies the volume and effects of an [AudioBus](crate::AudioBus) to a source
+pub(crate) struct BusSource<S> {
+    source: S,
+    params: Arc<BusParams>,
+    channels: usize,
+    channel: usize,
+    samples_until_update: usize,
+    gain: f32,
+    low_pass_alpha: Option<f32>,
+    low_pass_state: Vec<f32>,
+    reverb_send: f32,
+    reverb_buffer: Vec<f32>,
+    reverb_position: usize,
+}
+
+impl<S> BusSource<S>
+where
+    S: Source<Item = f32>,
+{
+    pub(crate) fn new(source: S, params: Arc<BusParams>) -> Self {
+        let channels = source.channels().max(1) as usize;
+        let reverb_len = (source.sample_rate() as f32 * REVERB_DELAY.as_secs_f32()) as usize;
+        BusSource {
+            channels,
+            channel: 0,
+            samples_until_update: 0,
+            gain: 1.0,
+            low_pass_alpha: None,
+            low_pass_state: vec![0.0; channels],
+            reverb_send: 0.0,
+            reverb_buffer: vec![0.0; reverb_len.max(1) * channels],
+            reverb_position: 0,
+            source,
+            params,
+        }
+    }
+
+    fn update_params(&mut self) {
+        self.gain = self.params.gain();
+        self.reverb_send = self.params.reverb_send();
+        let cutoff = self.params.low_pass();
+        let sample_rate = self.source.sample_rate() as f32;
+        self.low_pass_alpha = if cutoff > 0.0 && cutoff < sample_rate / 2.0 {
+            // one pole filter
+            Some(1.0 - (-2.0 * PI * cutoff / sample_rate).exp())
+        } else {
+            None
+        };
+    }
+}
+
+impl<S> Iterator for BusSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let mut sample = self.source.next()?;
+
+        if self.samples_until_update == 0 {
+            self.update_params();
+            self.samples_until_update = PARAMS_UPDATE_INTERVAL;
+        }
+        self.samples_until_update -= 1;
+
+        if let Some(alpha) = self.low_pass_alpha {
+            let state = &mut self.low_pass_state[self.channel];
+            *state += alpha * (sample - *state);
+            sample = *state;
+        }
+
+        // a feedback delay per channel, which keeps ringing after the send is turned down
+        let delayed = self.reverb_buffer[self.reverb_position];
+        self.reverb_buffer[self.reverb_position] =
+            sample * self.reverb_send + delayed * REVERB_FEEDBACK;
+        self.reverb_position = (self.reverb_position + 1) % self.reverb_buffer.len();
+        sample += delayed;
+
+        self.channel = (self.channel + 1) % self.channels;
+        Some(sample * self.gain)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.source.size_hint()
+    }
+}
+
+impl<S> Source for BusSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BusSettings;
+    use rodio::buffer::SamplesBuffer;
+
+    fn play(settings: &BusSettings, samples: Vec<f32>) -> Vec<f32> {
+        let source = SamplesBuffer::new(1, 44100, samples);
+        BusSource::new(source, BusParams::new(settings, 1.0)).collect()
+    }
+
+    #[test]
+    fn bus_volume_and_mute() {
+        let settings = BusSettings {
+            volume: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(play(&settings, vec![1.0, -1.0]), vec![0.5, -0.5]);
+
+        let settings = BusSettings {
+            muted: true,
+            ..Default::default()
+        };
+        assert_eq!(play(&settings, vec![1.0, -1.0]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn low_pass_smooths_steps() {
+        let settings = BusSettings {
+            low_pass: Some(500.0),
+            ..Default::default()
+        };
+        let output = play(&settings, vec![1.0; 64]);
+        assert!(output[0] > 0.0 && output[0] < 0.1);
+        assert!(output.windows(2).all(|pair| pair[1] > pair[0]));
+    }
+}
@@ -1,18 +1,23 @@
 mod audio;
+mod audio_bus;
 mod audio_output;
 mod audio_source;
+mod bus_source;
 
 pub use audio::*;
+pub use audio_bus::*;
 pub use audio_output::*;
 pub use audio_source::*;
 
 pub mod prelude {
-    pub use crate::{Audio, AudioOutput, AudioSource, Decodable};
+    pub use crate::{
+        Audio, AudioBus, AudioBuses, AudioLoadEvent, AudioOutput, AudioSource, Decodable,
+    };
 }
 
 use bevy_app::prelude::*;
 use bevy_asset::AddAsset;
-use bevy_ecs::IntoThreadLocalSystem;
+use bevy_ecs::{IntoQuerySystem, IntoThreadLocalSystem};
 
 /// Adds support for audio playback to an App
 #[derive(Default)]
@@ -22,11 +27,23 @@ impl Plugin for AudioPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.init_thread_local_resource::<AudioOutput<AudioSource>>()
             .add_asset::<AudioSource>()
+            // the loader reports its failures through this
+            .init_resource::<AudioLoadFailures<AudioSource>>()
             .init_asset_loader::<Mp3Loader>()
             .init_resource::<Audio<AudioSource>>()
+            .add_event::<AudioLoadEvent<AudioSource>>()
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                audio_load_events_system::<AudioSource>.system(),
+            )
             .add_system_to_stage(
                 stage::POST_UPDATE,
                 play_queued_audio_system::<AudioSource>.thread_local_system(),
             );
+
+        // keep bus settings that were loaded before the plugin was added
+        if app.resources().get::<AudioBuses>().is_none() {
+            app.init_resource::<AudioBuses>();
+        }
     }
 }
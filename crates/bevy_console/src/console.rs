@@ -0,0 +1,382 @@
+use crate::Cvar;
+use bevy_app::{AppExit, Events};
+use bevy_ecs::{Resources, World};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConsoleError {
+    #[error("Unknown command or cvar \"{0}\".")]
+    UnknownCommand(String),
+    #[error("\"{value}\" isn't a valid {type_name} for {name}.")]
+    InvalidValue {
+        name: String,
+        value: String,
+        type_name: &'static str,
+    },
+    #[error("The {0} resource doesn't exist.")]
+    MissingResource(&'static str),
+    #[error("Usage: {0}")]
+    Usage(String),
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Runs a console command with its arguments. The returned text is printed to the console.
+pub type ConsoleCommandFn =
+    dyn Fn(&mut World, &mut Resources, &[String]) -> Result<String, ConsoleError> + Send + Sync;
+
+/// A command that can be run from the console
+#[derive(Clone)]
+pub struct ConsoleCommand {
+    help: String,
+    run: Arc<ConsoleCommandFn>,
+}
+
+impl ConsoleCommand {
+    pub fn help(&self) -> &str {
+        &self.help
+    }
+}
+
+/// The commands and cvars of the console, the lines waiting to run and the text printed so far
+pub struct Console {
+    commands: BTreeMap<String, ConsoleCommand>,
+    cvars: BTreeMap<String, Cvar>,
+    queue: Vec<String>,
+    log: VecDeque<String>,
+    /// The number of printed lines that are kept
+    pub scrollback: usize,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        let mut console = Console {
+            commands: BTreeMap::new(),
+            cvars: BTreeMap::new(),
+            queue: Vec::new(),
+            log: VecDeque::new(),
+            scrollback: 200,
+        };
+        console
+            .add_command("help", "Lists the commands, or describes one", help_command)
+            .add_command(
+                "cvars",
+                "Lists the cvars that start with a prefix, and their values",
+                cvars_command,
+            )
+            .add_command("echo", "Prints its arguments", |_, _, args| {
+                Ok(args.join(" "))
+            })
+            .add_command("clear", "Clears the console", |_, resources, _| {
+                resources.get_mut::<Console>().unwrap().clear();
+                Ok(String::new())
+            })
+            .add_command("exit", "Exits the app", |_, resources, _| {
+                let mut app_exit_events = resources
+                    .get_mut::<Events<AppExit>>()
+                    .ok_or(ConsoleError::MissingResource("Events<AppExit>"))?;
+                app_exit_events.send(AppExit);
+                Ok(String::new())
+            });
+        console
+    }
+}
+
+impl Console {
+    /// Adds the command `name`, which replaces a command or cvar with the same name
+    pub fn add_command<F>(&mut self, name: &str, help: &str, run: F) -> &mut Self
+    where
+        F: Fn(&mut World, &mut Resources, &[String]) -> Result<String, ConsoleError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.cvars.remove(name);
+        self.commands.insert(
+            name.to_string(),
+            ConsoleCommand {
+                help: help.to_string(),
+                run: Arc::new(run),
+            },
+        );
+        self
+    }
+
+    /// Adds `cvar`, which replaces a command or cvar with the same name
+    pub fn add_cvar(&mut self, cvar: Cvar) -> &mut Self {
+        self.commands.remove(cvar.name());
+        self.cvars.insert(cvar.name().to_string(), cvar);
+        self
+    }
+
+    pub fn command(&self, name: &str) -> Option<&ConsoleCommand> {
+        self.commands.get(name)
+    }
+
+    pub fn cvar(&self, name: &str) -> Option<&Cvar> {
+        self.cvars.get(name)
+    }
+
+    /// The commands, sorted by name
+    pub fn commands(&self) -> impl Iterator<Item = (&str, &ConsoleCommand)> {
+        self.commands
+            .iter()
+            .map(|(name, command)| (name.as_str(), command))
+    }
+
+    /// The cvars, sorted by name
+    pub fn cvars(&self) -> impl Iterator<Item = &Cvar> {
+        self.cvars.values()
+    }
+
+    /// Queues `line` to run in the next [console_system]. A line can hold several commands separated by `;`, and
+    /// arguments with spaces can be quoted.
+    pub fn run(&mut self, line: impl Into<String>) {
+        self.queue.push(line.into());
+    }
+
+    /// Prints each line of `text` to the console
+    pub fn print(&mut self, text: impl AsRef<str>) {
+        for line in text.as_ref().lines() {
+            self.log.push_back(line.to_string());
+        }
+        while self.log.len() > self.scrollback {
+            self.log.pop_front();
+        }
+    }
+
+    /// The printed lines, from the oldest to the newest
+    pub fn log(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.log.iter().map(|line| line.as_str())
+    }
+
+    pub fn clear(&mut self) {
+        self.log.clear();
+    }
+
+    /// The sorted names of the commands and cvars that start with `prefix`
+    pub fn complete(&self, prefix: &str) -> Vec<&str> {
+        let mut names = self
+            .commands
+            .keys()
+            .chain(self.cvars.keys())
+            .map(|name| name.as_str())
+            .filter(|name| name.starts_with(prefix))
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Splits a console line into commands, and each command into its name and arguments. Commands are separated by `;`
+/// and arguments by whitespace, except inside double quotes.
+pub fn parse_line(line: &str) -> Vec<Vec<String>> {
+    let mut commands = Vec::new();
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                // keeps "" as an empty argument
+                arg.get_or_insert_with(String::new);
+            }
+            ';' if !quoted => {
+                args.extend(arg.take());
+                if !args.is_empty() {
+                    commands.push(std::mem::take(&mut args));
+                }
+            }
+            c if c.is_whitespace() && !quoted => args.extend(arg.take()),
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    args.extend(arg.take());
+    if !args.is_empty() {
+        commands.push(args);
+    }
+    commands
+}
+
+/// The longest prefix all of `names` share
+pub fn common_prefix<'a>(names: &[&'a str]) -> &'a str {
+    let (first, rest) = match names.split_first() {
+        Some(split) => split,
+        None => return "",
+    };
+
+    let mut len = first.len();
+    for name in rest {
+        let shared = first
+            .char_indices()
+            .zip(name.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((index, c), _)| index + c.len_utf8());
+        len = len.min(shared);
+    }
+    &first[..len]
+}
+
+/// Runs the lines queued with [Console::run] and prints their output
+pub fn console_system(world: &mut World, resources: &mut Resources) {
+    let lines = std::mem::take(&mut resources.get_mut::<Console>().unwrap().queue);
+    for line in lines {
+        resources
+            .get_mut::<Console>()
+            .unwrap()
+            .print(format!("> {}", line));
+        for args in parse_line(&line) {
+            let result = execute(world, resources, &args);
+            let mut console = resources.get_mut::<Console>().unwrap();
+            match result {
+                Ok(output) => console.print(output),
+                Err(err) => console.print(err.to_string()),
+            }
+        }
+    }
+}
+
+/// Runs a command, or shows or changes a cvar
+fn execute(
+    world: &mut World,
+    resources: &mut Resources,
+    args: &[String],
+) -> Result<String, ConsoleError> {
+    let (name, args) = match args.split_first() {
+        Some(split) => split,
+        None => return Ok(String::new()),
+    };
+
+    let run = {
+        let console = resources.get::<Console>().unwrap();
+        if let Some(cvar) = console.cvar(name) {
+            if !args.is_empty() {
+                cvar.set(resources, &args.join(" "))?;
+            }
+            return Ok(cvar.describe(resources));
+        }
+
+        console
+            .command(name)
+            .ok_or_else(|| ConsoleError::UnknownCommand(name.clone()))?
+            .run
+            .clone()
+    };
+    run(world, resources, args)
+}
+
+fn help_command(
+    _world: &mut World,
+    resources: &mut Resources,
+    args: &[String],
+) -> Result<String, ConsoleError> {
+    let console = resources.get::<Console>().unwrap();
+    if let Some(name) = args.first() {
+        return if let Some(command) = console.command(name) {
+            Ok(format!("{}: {}", name, command.help()))
+        } else if let Some(cvar) = console.cvar(name) {
+            Ok(format!(
+                "{} <{}>: {}\n{}",
+                name,
+                cvar.type_name(),
+                cvar.help(),
+                cvar.describe(resources)
+            ))
+        } else {
+            Err(ConsoleError::UnknownCommand(name.clone()))
+        };
+    }
+
+    let mut output =
+        String::from("Type a cvar's name to show it, or its name and a value to change it.");
+    for (name, command) in console.commands() {
+        output.push_str(&format!("\n{}: {}", name, command.help()));
+    }
+    Ok(output)
+}
+
+fn cvars_command(
+    _world: &mut World,
+    resources: &mut Resources,
+    args: &[String],
+) -> Result<String, ConsoleError> {
+    let prefix = args.first().map_or("", |prefix| prefix.as_str());
+    let console = resources.get::<Console>().unwrap();
+    Ok(console
+        .cvars()
+        .filter(|cvar| cvar.name().starts_with(prefix))
+        .map(|cvar| format!("{} <{}>", cvar.describe(resources), cvar.type_name()))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Volume(f32);
+
+    #[test]
+    fn parse_lines() {
+        assert_eq!(
+            parse_line(r#"say "hello world" ""; r.msaa   4;;"#),
+            vec![
+                vec!["say".to_string(), "hello world".to_string(), String::new()],
+                vec!["r.msaa".to_string(), "4".to_string()],
+            ]
+        );
+        assert!(parse_line("  ").is_empty());
+    }
+
+    #[test]
+    fn complete_names() {
+        let mut console = Console::default();
+        console
+            .add_command("r.reload", "", |_, _, _| Ok(String::new()))
+            .add_cvar(Cvar::bind("r.msaa", "", |v: &Volume| v.0, |_, _| {}));
+        let names = console.complete("r.");
+        assert_eq!(names, vec!["r.msaa", "r.reload"]);
+        assert_eq!(common_prefix(&names), "r.");
+        assert_eq!(common_prefix(&console.complete("he")), "help");
+        assert!(console.complete("x").is_empty());
+    }
+
+    #[test]
+    fn cvars_write_through() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Volume(1.0));
+        let mut console = Console::default();
+        console.add_cvar(Cvar::bind(
+            "volume",
+            "",
+            |volume: &Volume| volume.0,
+            |volume, value| volume.0 = value,
+        ));
+        console.run("volume 0.5; volume");
+        console.run("volume loud; nope");
+        resources.insert(console);
+
+        console_system(&mut world, &mut resources);
+        assert_eq!(resources.get::<Volume>().unwrap().0, 0.5);
+        let console = resources.get::<Console>().unwrap();
+        assert_eq!(
+            console.log().collect::<Vec<_>>(),
+            vec![
+                "> volume 0.5; volume",
+                "volume = 0.5",
+                "volume = 0.5",
+                "> volume loud; nope",
+                "\"loud\" isn't a valid f32 for volume.",
+                "Unknown command or cvar \"nope\".",
+            ]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::ConsoleError;
+use bevy_ecs::{ResMut, Resource, Resources};
+use bevy_utils::HashMap;
+use std::any::Any;
+
+/// A value a cvar can hold. It is parsed from the text typed in the console and shown as text.
+pub trait CvarValue: Resource + Clone {
+    fn parse_cvar(value: &str) -> Option<Self>;
+    fn to_cvar_string(&self) -> String;
+}
+
+macro_rules! impl_cvar_value_from_str {
+    ($($ty:ty),*) => {
+        $(
+            impl CvarValue for $ty {
+                fn parse_cvar(value: &str) -> Option<Self> {
+                    value.parse().ok()
+                }
+
+                fn to_cvar_string(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_cvar_value_from_str!(u8, i32, i64, u32, u64, usize, f32, f64);
+
+impl CvarValue for bool {
+    fn parse_cvar(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "1" | "true" | "on" | "yes" => Some(true),
+            "0" | "false" | "off" | "no" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn to_cvar_string(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl CvarValue for String {
+    fn parse_cvar(value: &str) -> Option<Self> {
+        Some(value.to_string())
+    }
+
+    fn to_cvar_string(&self) -> String {
+        self.clone()
+    }
+}
+
+/// The values of the cvars that aren't bound to another resource, see
+/// [RegisterConsole::add_cvar](crate::RegisterConsole::add_cvar)
+#[derive(Default)]
+pub struct Cvars {
+    values: HashMap<String, Box<dyn Any + Send + Sync>>,
+}
+
+impl Cvars {
+    /// The value of the cvar `name`. Returns `None` if it doesn't exist or doesn't hold a `T`.
+    pub fn get<T: CvarValue>(&self, name: &str) -> Option<&T> {
+        self.values.get(name)?.downcast_ref()
+    }
+
+    /// Changes the value of the cvar `name`. Returns `false` if it doesn't exist or doesn't hold a `T`.
+    pub fn set<T: CvarValue>(&mut self, name: &str, value: T) -> bool {
+        match self
+            .values
+            .get_mut(name)
+            .and_then(|current| current.downcast_mut::<T>())
+        {
+            Some(current) => {
+                *current = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn insert<T: CvarValue>(&mut self, name: &str, value: T) {
+        self.values.insert(name.to_string(), Box::new(value));
+    }
+}
+
+type CvarGetter = Box<dyn Fn(&Resources) -> Option<String> + Send + Sync>;
+type CvarSetter = Box<dyn Fn(&Resources, &str) -> Result<(), ConsoleError> + Send + Sync>;
+
+/// A variable that can be read and changed from the console. Its value lives in a resource, so changes made in the
+/// console write through to the resource and changes made by systems show up in the console.
+pub struct Cvar {
+    name: String,
+    help: String,
+    type_name: &'static str,
+    get: CvarGetter,
+    set: CvarSetter,
+}
+
+impl Cvar {
+    /// A cvar that reads its value from the resource `R` with `get` and writes it with `set`. Systems that look for
+    /// changes of `R` see the changes made in the console.
+    pub fn bind<R, T>(
+        name: impl Into<String>,
+        help: impl Into<String>,
+        get: impl Fn(&R) -> T + Send + Sync + 'static,
+        set: impl Fn(&mut R, T) + Send + Sync + 'static,
+    ) -> Self
+    where
+        R: Resource,
+        T: CvarValue,
+    {
+        let name = name.into();
+        let setter_name = name.clone();
+        Cvar {
+            name,
+            help: help.into(),
+            type_name: short_type_name::<T>(),
+            get: Box::new(move |resources: &Resources| {
+                resources
+                    .get::<R>()
+                    .map(|resource| get(&resource).to_cvar_string())
+            }),
+            set: Box::new(move |resources: &Resources, value: &str| {
+                let value = T::parse_cvar(value).ok_or_else(|| ConsoleError::InvalidValue {
+                    name: setter_name.clone(),
+                    value: value.to_string(),
+                    type_name: short_type_name::<T>(),
+                })?;
+                // queried as ResMut so the change is tracked
+                let mut resource = resources
+                    .query::<ResMut<R>>()
+                    .ok_or_else(|| ConsoleError::MissingResource(short_type_name::<R>()))?;
+                set(&mut resource, value);
+                Ok(())
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn help(&self) -> &str {
+        &self.help
+    }
+
+    /// The name of the type of the value, like `f32`
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The current value as text. Returns `None` if the resource it is bound to doesn't exist.
+    pub fn value(&self, resources: &Resources) -> Option<String> {
+        (self.get)(resources)
+    }
+
+    /// Parses `value` and writes it to the resource the cvar is bound to
+    pub fn set(&self, resources: &Resources, value: &str) -> Result<(), ConsoleError> {
+        (self.set)(resources, value)
+    }
+
+    /// A line like `r.msaa = 4`
+    pub fn describe(&self, resources: &Resources) -> String {
+        match self.value(resources) {
+            Some(value) => format!("{} = {}", self.name, value),
+            None => format!("{} isn't available", self.name),
+        }
+    }
+}
+
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
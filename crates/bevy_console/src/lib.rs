@@ -0,0 +1,206 @@
+//! An in-game console that runs commands and shows and changes cvars, the variables that tune a game while it runs.
+//!
+//! Any plugin can add commands and cvars with [RegisterConsole], whether [ConsolePlugin] is added before or after it.
+//! A cvar is bound to a field of a resource, so `r.msaa 4` writes to
+//! [GraphicsQuality](bevy_render::quality::GraphicsQuality) and systems see the change like any other change to the
+//! resource. [RegisterConsole::add_cvar] adds cvars that only live in the [Cvars] resource.
+//!
+//! [ConsoleInput::toggle_key] opens the console at the top of the window. Enter runs the typed line, Up and Down
+//! browse the lines that ran before, and Tab completes the name of a command or cvar. Type `help` to list the
+//! commands, or `cvars` to list the cvars and their values.
+//!
+//! The console is drawn with ui nodes, so the app needs a [UiCameraComponents](bevy_ui::entity::UiCameraComponents).
+
+mod console;
+mod cvar;
+mod ui;
+
+pub use console::*;
+pub use cvar::*;
+pub use ui::*;
+
+pub mod prelude {
+    pub use crate::{Console, ConsoleError, ConsoleInput, ConsolePlugin, Cvars, RegisterConsole};
+}
+
+use bevy_app::prelude::*;
+use bevy_asset::AssetServer;
+use bevy_core::Time;
+use bevy_ecs::{IntoQuerySystem, IntoThreadLocalSystem, Resource, Resources, World};
+use bevy_render::{quality::GraphicsQuality, render_graph::RenderGraphDump};
+use bevy_ui::UiScale;
+
+/// Adds the console and its panel, and cvars for the engine's time, graphics and ui options
+pub struct ConsolePlugin {
+    /// The font of the console, relative to the asset folder
+    pub font: &'static str,
+    /// The number of printed lines the console shows
+    pub lines: usize,
+}
+
+impl Default for ConsolePlugin {
+    fn default() -> Self {
+        ConsolePlugin {
+            font: "fonts/FiraMono-Medium.ttf",
+            lines: 12,
+        }
+    }
+}
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let font = app
+            .resources()
+            .get::<AssetServer>()
+            .unwrap()
+            .load(self.font);
+        let style = ConsoleStyle::new(app.resources(), font, self.lines);
+        app.resources_mut().get_or_insert_with(Cvars::default);
+        app.add_resource(style)
+            .init_resource::<ConsoleInput>()
+            .add_cvar_binding(
+                "r.msaa",
+                "Samples per pixel. Only 1 and 4 are supported on every device.",
+                |quality: &GraphicsQuality| quality.settings().msaa_samples,
+                |quality, samples| {
+                    if quality.settings().msaa_samples != samples {
+                        quality.customize().msaa_samples = samples;
+                    }
+                },
+            )
+            .add_cvar_binding(
+                "r.anisotropy",
+                "Maximum anisotropic filtering level, or 1 to turn it off",
+                |quality: &GraphicsQuality| quality.settings().anisotropy,
+                |quality, anisotropy| {
+                    if quality.settings().anisotropy != anisotropy {
+                        quality.customize().anisotropy = anisotropy;
+                    }
+                },
+            )
+            .add_cvar_binding(
+                "timescale",
+                "How fast gameplay time passes compared to real time",
+                |time: &Time| time.relative_speed(),
+                |time, speed: f32| {
+                    if speed.is_finite() {
+                        time.set_relative_speed(speed.max(0.0));
+                    }
+                },
+            )
+            .add_cvar_binding(
+                "paused",
+                "Stops gameplay time",
+                |time: &Time| time.is_paused(),
+                |time, paused| {
+                    if paused {
+                        time.pause();
+                    } else {
+                        time.unpause();
+                    }
+                },
+            )
+            .add_cvar_binding(
+                "ui.scale",
+                "Multiplies the size of the ui",
+                |ui_scale: &UiScale| ui_scale.scale,
+                |ui_scale, scale| ui_scale.scale = scale,
+            )
+            .add_console_command(
+                "r.dump_graph",
+                "Writes the render graph of this frame to a Graphviz file",
+                |_, resources, _| {
+                    let mut dump = resources
+                        .get_mut::<RenderGraphDump>()
+                        .ok_or(ConsoleError::MissingResource("RenderGraphDump"))?;
+                    Ok(format!(
+                        "The render graph is written to {} after this frame.",
+                        dump.request().display()
+                    ))
+                },
+            )
+            .add_startup_system(setup_console_panel.system())
+            .add_system_to_stage(stage::PRE_UPDATE, console_system.thread_local_system())
+            .add_system(console_input_system.system())
+            .add_system(console_panel_system.system());
+    }
+}
+
+/// [AppBuilder] extension methods for console commands and cvars
+pub trait RegisterConsole {
+    /// Adds the command `name`. `run` gets the arguments typed after the name and returns the text to print.
+    fn add_console_command<F>(&mut self, name: &str, help: &str, run: F) -> &mut Self
+    where
+        F: Fn(&mut World, &mut Resources, &[String]) -> Result<String, ConsoleError>
+            + Send
+            + Sync
+            + 'static;
+
+    /// Adds the cvar `name`, which holds a `T` in [Cvars] that starts as `default`
+    fn add_cvar<T: CvarValue>(&mut self, name: &str, default: T, help: &str) -> &mut Self;
+
+    /// Adds the cvar `name`, which reads its value from the resource `R` with `get` and writes it with `set`
+    fn add_cvar_binding<R, T>(
+        &mut self,
+        name: &str,
+        help: &str,
+        get: impl Fn(&R) -> T + Send + Sync + 'static,
+        set: impl Fn(&mut R, T) + Send + Sync + 'static,
+    ) -> &mut Self
+    where
+        R: Resource,
+        T: CvarValue;
+}
+
+impl RegisterConsole for AppBuilder {
+    fn add_console_command<F>(&mut self, name: &str, help: &str, run: F) -> &mut Self
+    where
+        F: Fn(&mut World, &mut Resources, &[String]) -> Result<String, ConsoleError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.resources_mut()
+            .get_or_insert_with(Console::default)
+            .add_command(name, help, run);
+        self
+    }
+
+    fn add_cvar<T: CvarValue>(&mut self, name: &str, default: T, help: &str) -> &mut Self {
+        self.resources_mut()
+            .get_or_insert_with(Cvars::default)
+            .insert(name, default.clone());
+        let get_name = name.to_string();
+        let set_name = name.to_string();
+        self.add_cvar_binding(
+            name,
+            help,
+            move |cvars: &Cvars| {
+                cvars
+                    .get::<T>(&get_name)
+                    .cloned()
+                    .unwrap_or_else(|| default.clone())
+            },
+            move |cvars, value| {
+                cvars.set(&set_name, value);
+            },
+        )
+    }
+
+    fn add_cvar_binding<R, T>(
+        &mut self,
+        name: &str,
+        help: &str,
+        get: impl Fn(&R) -> T + Send + Sync + 'static,
+        set: impl Fn(&mut R, T) + Send + Sync + 'static,
+    ) -> &mut Self
+    where
+        R: Resource,
+        T: CvarValue,
+    {
+        self.resources_mut()
+            .get_or_insert_with(Console::default)
+            .add_cvar(Cvar::bind(name, help, get, set));
+        self
+    }
+}
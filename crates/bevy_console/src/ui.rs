@@ -0,0 +1,336 @@
+use crate::{common_prefix, Console};
+use bevy_app::{EventReader, Events};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Local, Query, Res, ResMut, Resources, With};
+use bevy_input::{keyboard::KeyCode, Input};
+use bevy_math::{Rect, Size};
+use bevy_render::prelude::Color;
+use bevy_sprite::ColorMaterial;
+use bevy_text::{Font, TextStyle};
+use bevy_transform::prelude::BuildChildren;
+use bevy_ui::{
+    entity::{NodeComponents, TextComponents},
+    widget::Text,
+    Display, FlexDirection, Interaction, PositionType, Style, Val,
+};
+use bevy_window::ReceivedCharacter;
+
+const LINE_HEIGHT: f32 = 20.0;
+
+const FONT_SIZE: f32 = 16.0;
+
+/// The number of submitted lines [ConsoleInput] remembers
+const HISTORY_LENGTH: usize = 100;
+
+/// Whether the console is open, and the line being typed into it
+pub struct ConsoleInput {
+    /// Systems that read the keyboard can check this to ignore what is typed into the console
+    pub open: bool,
+    pub toggle_key: KeyCode,
+    text: String,
+    history: Vec<String>,
+    /// The entry of `history` that is being edited, while browsing it with Up and Down
+    history_index: Option<usize>,
+}
+
+impl Default for ConsoleInput {
+    fn default() -> Self {
+        ConsoleInput {
+            open: false,
+            toggle_key: KeyCode::Grave,
+            text: String::new(),
+            history: Vec::new(),
+            history_index: None,
+        }
+    }
+}
+
+impl ConsoleInput {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Types `c`. Control characters are ignored.
+    pub fn push(&mut self, c: char) {
+        if !c.is_control() {
+            self.text.push(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.text.pop();
+    }
+
+    /// Clears the typed line and adds it to the history. Returns `None` if nothing was typed.
+    pub fn submit(&mut self) -> Option<String> {
+        self.history_index = None;
+        let line = std::mem::take(&mut self.text);
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            if self.history.len() > HISTORY_LENGTH {
+                self.history.remove(0);
+            }
+        }
+        Some(line)
+    }
+
+    /// Replaces the typed line with the previous line in the history
+    pub fn history_previous(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index.saturating_sub(1),
+            None if !self.history.is_empty() => self.history.len() - 1,
+            None => return,
+        };
+        self.history_index = Some(index);
+        self.text = self.history[index].clone();
+    }
+
+    /// Replaces the typed line with the next line in the history, or clears it after the last one
+    pub fn history_next(&mut self) {
+        match self.history_index {
+            Some(index) if index + 1 < self.history.len() => {
+                self.history_index = Some(index + 1);
+                self.text = self.history[index + 1].clone();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.text.clear();
+            }
+            None => {}
+        }
+    }
+
+    /// Completes the name of the command being typed. If several commands or cvars match, it is completed as far as
+    /// they agree and the matches are printed.
+    pub fn complete(&mut self, console: &mut Console) {
+        let start = self.text.rfind(';').map_or(0, |index| index + 1);
+        let start = start + (self.text[start..].len() - self.text[start..].trim_start().len());
+        let prefix = &self.text[start..];
+        if prefix.contains(char::is_whitespace) {
+            return;
+        }
+
+        // the names borrow the console, so the matches are printed once they're copied
+        let names = console.complete(prefix);
+        let (completion, matches) = match names.len() {
+            0 => return,
+            1 => (format!("{} ", names[0]), None),
+            _ => (common_prefix(&names).to_string(), Some(names.join("  "))),
+        };
+        if let Some(matches) = matches {
+            console.print(matches);
+        }
+        self.text.truncate(start);
+        self.text.push_str(&completion);
+    }
+}
+
+/// The font and material of the console panel
+pub struct ConsoleStyle {
+    pub font: Handle<Font>,
+    pub background: Handle<ColorMaterial>,
+    /// The number of printed lines the panel shows
+    pub lines: usize,
+}
+
+impl ConsoleStyle {
+    pub fn new(resources: &Resources, font: Handle<Font>, lines: usize) -> Self {
+        let mut materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
+        ConsoleStyle {
+            font,
+            background: materials.add(Color::rgba(0.05, 0.05, 0.05, 0.9).into()),
+            lines,
+        }
+    }
+}
+
+/// Marks the console panel, which is only shown while the console is open
+#[derive(Debug, Default)]
+pub struct ConsolePanel;
+
+/// A line of text in the console panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleText {
+    /// The line being typed
+    Prompt,
+    /// A printed line, counting back from the newest one
+    Log(usize),
+}
+
+/// Spawns the console panel across the top of the window
+pub fn setup_console_panel(mut commands: Commands, style: Res<ConsoleStyle>) {
+    let text = |value: &str| TextComponents {
+        style: Style {
+            size: Size::new(Val::Auto, Val::Px(LINE_HEIGHT)),
+            flex_shrink: 0.0,
+            ..Default::default()
+        },
+        text: Text {
+            value: value.to_string(),
+            font: style.font.clone(),
+            style: TextStyle {
+                font_size: FONT_SIZE,
+                color: Color::WHITE,
+            },
+        },
+        ..Default::default()
+    };
+
+    let height = (style.lines + 1) as f32 * LINE_HEIGHT + 8.0;
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(0.0),
+                    top: Val::Px(0.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Px(height)),
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: Rect::all(Val::Px(4.0)),
+                ..Default::default()
+            },
+            material: style.background.clone(),
+            ..Default::default()
+        })
+        // keeps clicks on the console from reaching the ui behind it
+        .with(Interaction::default())
+        .with(ConsolePanel)
+        .with_children(|parent| {
+            // the oldest line is at the top
+            for index in (0..style.lines).rev() {
+                parent.spawn(text("")).with(ConsoleText::Log(index));
+            }
+            parent.spawn(text("> ")).with(ConsoleText::Prompt);
+        });
+}
+
+#[derive(Default)]
+pub struct ConsoleInputState {
+    received_character_event_reader: EventReader<ReceivedCharacter>,
+}
+
+/// Opens and closes the console, and edits and submits the typed line
+pub fn console_input_system(
+    mut state: Local<ConsoleInputState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    received_character_events: Res<Events<ReceivedCharacter>>,
+    mut input: ResMut<ConsoleInput>,
+    mut console: ResMut<Console>,
+) {
+    let characters = state
+        .received_character_event_reader
+        .iter(&received_character_events)
+        .map(|event| event.char)
+        .collect::<Vec<_>>();
+
+    // the toggle key's character isn't typed into the console
+    if keyboard_input.just_pressed(input.toggle_key) {
+        input.open = !input.open;
+        return;
+    }
+
+    if !input.open {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        input.open = false;
+        return;
+    }
+
+    // backspace is read from the characters so it repeats while the key is held
+    for c in characters {
+        match c {
+            '\u{8}' | '\u{7f}' => input.backspace(),
+            c => input.push(c),
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        input.complete(&mut console);
+    }
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        input.history_previous();
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        input.history_next();
+    }
+    if keyboard_input.just_pressed(KeyCode::Return)
+        || keyboard_input.just_pressed(KeyCode::NumpadEnter)
+    {
+        if let Some(line) = input.submit() {
+            console.run(line);
+        }
+    }
+}
+
+/// Shows the console panel while the console is open, with the newest printed lines and the typed line
+pub fn console_panel_system(
+    console: Res<Console>,
+    input: Res<ConsoleInput>,
+    mut panel_query: Query<With<ConsolePanel, &mut Style>>,
+    mut text_query: Query<(&ConsoleText, &mut Text)>,
+) {
+    let display = if input.open {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    for mut style in panel_query.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+
+    if !input.open {
+        return;
+    }
+
+    for (console_text, mut text) in text_query.iter_mut() {
+        let value = match *console_text {
+            ConsoleText::Prompt => format!("> {}_", input.text()),
+            ConsoleText::Log(index) => console.log().rev().nth(index).unwrap_or("").to_string(),
+        };
+        if text.value != value {
+            text.value = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_and_completion() {
+        let mut input = ConsoleInput::default();
+        for line in ["echo a", "echo b", "echo b", " "].iter() {
+            input.text = line.to_string();
+            input.submit();
+        }
+        input.history_previous();
+        assert_eq!(input.text(), "echo b");
+        input.history_previous();
+        input.history_previous();
+        assert_eq!(input.text(), "echo a");
+        input.history_next();
+        input.history_next();
+        assert_eq!(input.text(), "");
+
+        let mut console = Console::default();
+        input.text = "echo a; cl".to_string();
+        input.complete(&mut console);
+        assert_eq!(input.text(), "echo a; clear ");
+        input.text = "e".to_string();
+        input.complete(&mut console);
+        assert_eq!(input.text(), "e");
+        assert_eq!(console.log().last(), Some("echo  exit"));
+    }
+}
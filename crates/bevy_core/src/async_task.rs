@@ -0,0 +1,140 @@
+use bevy_app::prelude::*;
+use bevy_ecs::{Commands, Component, Entity, IntoQuerySystem, Query, ResMut};
+use bevy_tasks::{Task, TaskPool};
+use std::future::Future;
+
+/// Where the output of a finished [AsyncTask] goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskOutput {
+    /// Inserted as a component of the task's entity
+    Component,
+    /// Sent as a [TaskCompleted] event
+    Event,
+}
+
+/// Work running on a task pool for the entity this is a component of, like finding a path, generating a mesh or
+/// waiting for a network request. [async_task_system] polls it every update, and once it finished, removes it and
+/// hands its output back as a component or a [TaskCompleted] event.
+///
+/// Despawning the entity or removing the component cancels the task: it won't be polled again, and a task that is
+/// running is stopped at its next `.await`.
+///
+/// Not available on wasm, where task pools run futures on the browser's event loop and don't hand back their output.
+#[derive(Debug)]
+pub struct AsyncTask<T> {
+    task: Task<T>,
+    output: TaskOutput,
+}
+
+impl<T: Send + 'static> AsyncTask<T> {
+    /// Spawns `future` on `task_pool`, usually the [AsyncComputeTaskPool](bevy_tasks::AsyncComputeTaskPool) for work
+    /// that takes longer than a frame or the [IoTaskPool](bevy_tasks::IoTaskPool) for work that waits on files or the
+    /// network. Its output is inserted as a component.
+    pub fn spawn(task_pool: &TaskPool, future: impl Future<Output = T> + Send + 'static) -> Self {
+        Self::from_task(task_pool.spawn(future))
+    }
+
+    pub fn from_task(task: Task<T>) -> Self {
+        AsyncTask {
+            task,
+            output: TaskOutput::Component,
+        }
+    }
+
+    /// Sends the output as a [TaskCompleted] event instead of inserting it as a component
+    pub fn send_event(mut self) -> Self {
+        self.output = TaskOutput::Event;
+        self
+    }
+
+    pub fn output(&self) -> TaskOutput {
+        self.output
+    }
+}
+
+/// Sent when an [AsyncTask] whose output goes to an event finished
+#[derive(Debug)]
+pub struct TaskCompleted<T> {
+    /// The entity the task was a component of
+    pub entity: Entity,
+    pub output: T,
+}
+
+/// Hands the output of finished [AsyncTask]s back to their entities
+pub fn async_task_system<T: Component>(
+    mut commands: Commands,
+    mut completed_events: ResMut<Events<TaskCompleted<T>>>,
+    mut query: Query<(Entity, &mut AsyncTask<T>)>,
+) {
+    for (entity, mut async_task) in query.iter_mut() {
+        let output = match async_task.task.poll_once() {
+            Some(output) => output,
+            None => continue,
+        };
+
+        commands.remove_one::<AsyncTask<T>>(entity);
+        match async_task.output {
+            TaskOutput::Component => {
+                commands.insert_one(entity, output);
+            }
+            TaskOutput::Event => completed_events.send(TaskCompleted { entity, output }),
+        }
+    }
+}
+
+/// [AppBuilder] extension methods for [AsyncTask]s
+pub trait AddAsyncTask {
+    /// Polls the [AsyncTask]s with an output of type `T` and adds the [TaskCompleted] event for them. Their outputs
+    /// are handed back at the end of [stage::PRE_UPDATE], so gameplay systems see them in the same update.
+    fn add_async_task<T: Component>(&mut self) -> &mut Self;
+}
+
+impl AddAsyncTask for AppBuilder {
+    fn add_async_task<T: Component>(&mut self) -> &mut Self {
+        self.add_event::<TaskCompleted<T>>()
+            .add_system_to_stage(stage::PRE_UPDATE, async_task_system::<T>.system())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{Resources, Schedule, World};
+    use std::time::{Duration, Instant};
+
+    #[derive(Debug, PartialEq)]
+    struct Path(Vec<u32>);
+
+    #[test]
+    fn complete_and_cancel_tasks() {
+        let task_pool = TaskPool::new();
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Events::<TaskCompleted<Path>>::default());
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", async_task_system::<Path>.system());
+
+        let component = world.spawn((AsyncTask::spawn(&task_pool, async { Path(vec![1, 2]) }),));
+        let event =
+            world.spawn((AsyncTask::spawn(&task_pool, async { Path(vec![3]) }).send_event(),));
+        let canceled =
+            world.spawn((AsyncTask::spawn(&task_pool, async { Path(vec![4]) }).send_event(),));
+        world.despawn(canceled).unwrap();
+
+        schedule.initialize(&mut world, &mut resources);
+        let start = Instant::now();
+        while world.query::<&AsyncTask<Path>>().count() > 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            schedule.run(&mut world, &mut resources);
+        }
+
+        assert_eq!(*world.get::<Path>(component).unwrap(), Path(vec![1, 2]));
+        assert!(world.get::<Path>(event).is_err());
+        let events = resources.get::<Events<TaskCompleted<Path>>>().unwrap();
+        let completed = events.get_reader().iter(&events).collect::<Vec<_>>();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].entity, event);
+        assert_eq!(completed[0].output, Path(vec![3]));
+    }
+}
@@ -1,17 +1,25 @@
+#[cfg(not(target_arch = "wasm32"))]
+mod async_task;
 mod bytes;
 mod float_ord;
 mod label;
+mod rng;
 mod task_pool_options;
 mod time;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use async_task::*;
 pub use bytes::*;
 pub use float_ord::*;
 pub use label::*;
+pub use rng::*;
 pub use task_pool_options::DefaultTaskPoolOptions;
 pub use time::*;
 
 pub mod prelude {
-    pub use crate::{DefaultTaskPoolOptions, EntityLabels, Labels, Time, Timer};
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use crate::{AddAsyncTask, AsyncTask, TaskCompleted};
+    pub use crate::{DefaultTaskPoolOptions, EntityLabels, FixedTimestep, Labels, Time, Timer};
 }
 
 use bevy_app::prelude::*;
@@ -31,8 +39,14 @@ impl Plugin for CorePlugin {
             .unwrap_or_else(DefaultTaskPoolOptions::default)
             .create_default_pools(app.resources_mut());
 
+        if app.resources().get::<FrameTimeOverride>().is_none() {
+            app.init_resource::<FrameTimeOverride>();
+        }
+
         app.init_resource::<Time>()
+            .init_resource::<FixedTimestep>()
             .init_resource::<EntityLabels>()
+            .init_resource::<Rng>()
             .register_component::<Timer>()
             .register_property::<Vec2>()
             .register_property::<Vec3>()
@@ -41,6 +55,7 @@ impl Plugin for CorePlugin {
             .register_property::<Quat>()
             .register_property::<Option<String>>()
             .add_system_to_stage(stage::FIRST, time_system.system())
+            .add_system_to_stage(stage::FIRST, fixed_timestep_system.system())
             .add_system_to_stage(stage::FIRST, timer_system.system())
             .add_system_to_stage(stage::PRE_UPDATE, entity_labels_system.system());
     }
@@ -0,0 +1,60 @@
+use std::ops::Range;
+
+/// A seeded random number generator resource. Systems that draw from it instead of a thread local generator behave
+/// the same on every run with the same seed, which keeps benchmarks and tests reproducible. The default seed is fixed.
+///
+/// This is a small xorshift generator: fast, but not suitable for anything security related.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng::with_seed(0)
+    }
+}
+
+impl Rng {
+    pub fn with_seed(seed: u64) -> Self {
+        // xorshift never leaves the all zero state, so the seed is mixed with a constant first
+        Rng {
+            state: (seed ^ 0x9e37_79b9_7f4a_7c15).max(1),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A uniformly distributed value in `[0, 1)`
+    pub fn next_f32(&mut self) -> f32 {
+        // the top 24 bits fill the f32 mantissa exactly
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A uniformly distributed value in `range`
+    pub fn range(&mut self, range: Range<f32>) -> f32 {
+        range.start + self.next_f32() * (range.end - range.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn seeded_sequences_repeat() {
+        let mut a = Rng::with_seed(42);
+        let mut b = Rng::with_seed(42);
+        for _ in 0..100 {
+            let value = a.range(-1.0..1.0);
+            assert_eq!(value, b.range(-1.0..1.0));
+            assert!((-1.0..1.0).contains(&value));
+        }
+        assert_ne!(Rng::with_seed(1).next_u64(), Rng::with_seed(2).next_u64());
+    }
+}
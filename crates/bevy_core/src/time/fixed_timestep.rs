@@ -0,0 +1,99 @@
+use crate::time::Time;
+use bevy_ecs::prelude::*;
+use std::time::Duration;
+
+/// Splits the scaled time of [Time] into steps of the same length, for gameplay and physics that should behave the
+/// same at any frame rate. Systems that run at the fixed rate repeat their work [FixedTimestep::steps] times each
+/// update, with [FixedTimestep::step_seconds] as their delta.
+///
+/// The steps are taken from the scaled delta, so fewer steps run in slow motion and none run while time is paused.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    pub step: Duration,
+    /// The most steps one update runs, so a slow update doesn't make the next one slower still. Time beyond that is
+    /// dropped.
+    pub max_steps: u32,
+    accumulator: Duration,
+    steps: u32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep::new(Duration::from_secs_f64(1.0 / 60.0))
+    }
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration) -> Self {
+        FixedTimestep {
+            step,
+            max_steps: 8,
+            accumulator: Duration::from_secs(0),
+            steps: 0,
+        }
+    }
+
+    /// The number of steps to run this update
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    pub fn step_seconds(&self) -> f32 {
+        self.step.as_secs_f32()
+    }
+
+    /// How far the time left over after this update's steps is into the next step, from 0 to 1. Rendering can use it
+    /// to interpolate between the last two steps.
+    pub fn overstep(&self) -> f32 {
+        if self.step == Duration::from_secs(0) {
+            return 0.0;
+        }
+        (self.accumulator.as_secs_f64() / self.step.as_secs_f64()) as f32
+    }
+
+    /// Adds `delta` to the accumulated time and takes as many steps out of it as fit
+    pub fn advance(&mut self, delta: Duration) {
+        self.steps = 0;
+        if self.step == Duration::from_secs(0) {
+            return;
+        }
+
+        self.accumulator += delta;
+        while self.accumulator >= self.step && self.steps < self.max_steps {
+            self.accumulator -= self.step;
+            self.steps += 1;
+        }
+
+        if self.accumulator >= self.step {
+            self.accumulator =
+                Duration::from_secs_f64(self.accumulator.as_secs_f64() % self.step.as_secs_f64());
+        }
+    }
+}
+
+pub(crate) fn fixed_timestep_system(time: Res<Time>, mut fixed_timestep: ResMut<FixedTimestep>) {
+    fixed_timestep.advance(time.delta);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulate_steps() {
+        let mut fixed_timestep = FixedTimestep::new(Duration::from_millis(10));
+        fixed_timestep.advance(Duration::from_millis(25));
+        assert_eq!(fixed_timestep.steps(), 2);
+        assert!((fixed_timestep.overstep() - 0.5).abs() < 1e-5);
+
+        fixed_timestep.advance(Duration::from_millis(5));
+        assert_eq!(fixed_timestep.steps(), 1);
+        fixed_timestep.advance(Duration::from_secs(0));
+        assert_eq!(fixed_timestep.steps(), 0);
+
+        // a long stall runs at most max_steps and drops the rest
+        fixed_timestep.advance(Duration::from_millis(1003));
+        assert_eq!(fixed_timestep.steps(), 8);
+        assert!((fixed_timestep.overstep() - 0.3).abs() < 1e-5);
+    }
+}
@@ -1,4 +1,4 @@
-use bevy_ecs::ResMut;
+use bevy_ecs::{Res, ResMut};
 use std::time::Duration;
 
 #[cfg(target_arch = "wasm32")]
@@ -6,15 +6,26 @@ use instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
-/// Tracks elapsed time since the last update and since the App has started
+/// Tracks elapsed time since the last update and since the App has started.
+///
+/// The deltas are scaled by the relative speed and are zero while time is paused, so gameplay systems that use them
+/// slow down for slow motion and stop for a pause menu. UI, audio and rendering systems that should keep running use
+/// the `raw_` deltas, which measure real time.
 #[derive(Debug)]
 pub struct Time {
     pub delta: Duration,
     pub instant: Option<Instant>,
     pub delta_seconds_f64: f64,
     pub delta_seconds: f32,
+    /// The sum of the scaled deltas
     pub seconds_since_startup: f64,
     pub startup: Instant,
+    pub raw_delta: Duration,
+    pub raw_delta_seconds_f64: f64,
+    pub raw_delta_seconds: f32,
+    pub raw_seconds_since_startup: f64,
+    relative_speed: f32,
+    paused: bool,
 }
 
 impl Default for Time {
@@ -26,6 +37,12 @@ impl Default for Time {
             delta_seconds_f64: 0.0,
             seconds_since_startup: 0.0,
             delta_seconds: 0.0,
+            raw_delta: Duration::from_secs(0),
+            raw_delta_seconds_f64: 0.0,
+            raw_delta_seconds: 0.0,
+            raw_seconds_since_startup: 0.0,
+            relative_speed: 1.0,
+            paused: false,
         }
     }
 }
@@ -33,22 +50,116 @@ impl Default for Time {
 impl Time {
     pub fn update(&mut self) {
         let now = Instant::now();
-        if let Some(instant) = self.instant {
-            self.delta = now - instant;
+        let delta = self.instant.map(|instant| now - instant);
+        self.advance(now, delta);
+    }
+
+    /// Like [Time::update], but advances time by exactly `delta` instead of the time measured since the last update
+    pub fn update_with_delta(&mut self, delta: Duration) {
+        let now = Instant::now();
+        let delta = self.instant.map(|_| delta);
+        self.advance(now, delta);
+    }
+
+    fn advance(&mut self, now: Instant, delta: Option<Duration>) {
+        if let Some(delta) = delta {
+            self.raw_delta = delta;
+            self.raw_delta_seconds_f64 = self.raw_delta.as_secs_f64();
+            self.raw_delta_seconds = self.raw_delta.as_secs_f32();
+            self.raw_seconds_since_startup += self.raw_delta_seconds_f64;
+
+            self.delta = if self.paused {
+                Duration::from_secs(0)
+            } else {
+                self.raw_delta.mul_f64(self.relative_speed as f64)
+            };
             self.delta_seconds_f64 = self.delta.as_secs_f64();
             self.delta_seconds = self.delta.as_secs_f32();
+            self.seconds_since_startup += self.delta_seconds_f64;
+        } else {
+            let duration_since_startup = now - self.startup;
+            self.seconds_since_startup = duration_since_startup.as_secs_f64();
+            self.raw_seconds_since_startup = self.seconds_since_startup;
         }
 
-        let duration_since_startup = now - self.startup;
-        self.seconds_since_startup = duration_since_startup.as_secs_f64();
         self.instant = Some(now);
     }
 
     pub fn time_since_startup(&self) -> Duration {
         Instant::now() - self.startup
     }
+
+    /// How fast the scaled time passes compared to real time
+    pub fn relative_speed(&self) -> f32 {
+        self.relative_speed
+    }
+
+    /// Makes the scaled time pass `speed` times as fast as real time from the next update on, for example 0.5 for
+    /// slow motion
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is negative or not finite.
+    pub fn set_relative_speed(&mut self, speed: f32) {
+        assert!(
+            speed.is_finite() && speed >= 0.0,
+            "the relative speed of time must be finite and not negative, got {}",
+            speed
+        );
+        self.relative_speed = speed;
+    }
+
+    /// Stops the scaled time from the next update on. The relative speed is kept for when time is unpaused.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+/// Makes [Time] advance by a fixed amount each update instead of the measured time, so time driven systems do the same
+/// work no matter how long frames take. This is meant for capturing, testing and benchmarking; while `delta` is
+/// `None`, which is the default, [Time] follows real time.
+#[derive(Debug, Clone, Default)]
+pub struct FrameTimeOverride {
+    pub delta: Option<Duration>,
+}
+
+pub(crate) fn time_system(frame_time_override: Res<FrameTimeOverride>, mut time: ResMut<Time>) {
+    match frame_time_override.delta {
+        Some(delta) => time.update_with_delta(delta),
+        None => time.update(),
+    }
 }
 
-pub(crate) fn time_system(mut time: ResMut<Time>) {
-    time.update();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_and_pause() {
+        let step = Duration::from_millis(100);
+        let mut time = Time::default();
+        time.update_with_delta(step);
+        time.set_relative_speed(0.5);
+        time.update_with_delta(step);
+        assert_eq!(time.raw_delta, Duration::from_millis(100));
+        assert_eq!(time.delta, Duration::from_millis(50));
+
+        time.pause();
+        time.update_with_delta(step);
+        assert_eq!(time.raw_delta, Duration::from_millis(100));
+        assert_eq!(time.delta, Duration::from_secs(0));
+        assert!((time.raw_seconds_since_startup - time.seconds_since_startup - 0.15).abs() < 1e-9);
+
+        time.unpause();
+        time.update_with_delta(step);
+        assert_eq!(time.delta, Duration::from_millis(50));
+    }
 }
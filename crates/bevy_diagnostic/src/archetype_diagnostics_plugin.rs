@@ -0,0 +1,80 @@
+use crate::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_app::prelude::*;
+use bevy_ecs::{IntoQuerySystem, IntoThreadLocalSystem, ResMut, Resources, World};
+
+/// Adds archetype diagnostics to an App: the number of archetypes, the average number of entities per archetype, the
+/// memory used by component storage and the archetype churn. Many small archetypes or constant churn usually come
+/// from adding and removing marker components. [World::memory_report] breaks the numbers down by archetype.
+#[derive(Default)]
+pub struct ArchetypeDiagnosticsPlugin;
+
+#[derive(Default)]
+pub struct ArchetypeDiagnosticsState {
+    archetype_entities: Vec<usize>,
+}
+
+impl Plugin for ArchetypeDiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(Self::setup_system.system())
+            .init_resource::<ArchetypeDiagnosticsState>()
+            .add_system_to_stage(stage::LAST, Self::diagnostic_system.thread_local_system());
+    }
+}
+
+impl ArchetypeDiagnosticsPlugin {
+    pub const ARCHETYPE_COUNT: DiagnosticId =
+        DiagnosticId::from_u128(90162541278913850432870524166453307231);
+    pub const ENTITIES_PER_ARCHETYPE: DiagnosticId =
+        DiagnosticId::from_u128(252473150936612347016530916374212750684);
+    pub const COMPONENT_MEMORY: DiagnosticId =
+        DiagnosticId::from_u128(194885026433707359290816380519271340918);
+    pub const ARCHETYPE_CHURN: DiagnosticId =
+        DiagnosticId::from_u128(31652219847216590733064918272630528475);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(Self::ARCHETYPE_COUNT, "archetype_count", 1));
+        diagnostics.add(Diagnostic::new(
+            Self::ENTITIES_PER_ARCHETYPE,
+            "entities_per_archetype",
+            1,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::COMPONENT_MEMORY,
+            "component_memory_bytes",
+            1,
+        ));
+        // churn is bursty, so it is averaged over more frames
+        diagnostics.add(Diagnostic::new(
+            Self::ARCHETYPE_CHURN,
+            "archetype_churn",
+            20,
+        ));
+    }
+
+    /// Churn is the number of entities that entered or left an archetype since the last frame, counted from the change
+    /// in each archetype's size. An entity that moves to another archetype counts twice.
+    pub fn diagnostic_system(world: &mut World, resources: &mut Resources) {
+        let mut diagnostics = resources.get_mut::<Diagnostics>().unwrap();
+        let mut state = resources.get_mut::<ArchetypeDiagnosticsState>().unwrap();
+        let report = world.memory_report();
+
+        let mut churn = 0;
+        state.archetype_entities.resize(report.archetypes.len(), 0);
+        for (archetype, entities) in report
+            .archetypes
+            .iter()
+            .zip(state.archetype_entities.iter_mut())
+        {
+            churn += (archetype.entities as isize - *entities as isize).abs() as usize;
+            *entities = archetype.entities;
+        }
+
+        diagnostics.add_measurement(Self::ARCHETYPE_COUNT, report.archetypes.len() as f64);
+        diagnostics.add_measurement(
+            Self::ENTITIES_PER_ARCHETYPE,
+            report.entities_per_archetype(),
+        );
+        diagnostics.add_measurement(Self::COMPONENT_MEMORY, report.bytes as f64);
+        diagnostics.add_measurement(Self::ARCHETYPE_CHURN, churn as f64);
+    }
+}
@@ -1,8 +1,12 @@
+mod archetype_diagnostics_plugin;
 mod diagnostic;
 mod frame_time_diagnostics_plugin;
 mod print_diagnostics_plugin;
+#[cfg(feature = "stress")]
+pub mod stress;
 #[cfg(feature = "profiler")]
 mod system_profiler;
+pub use archetype_diagnostics_plugin::ArchetypeDiagnosticsPlugin;
 pub use diagnostic::*;
 pub use frame_time_diagnostics_plugin::FrameTimeDiagnosticsPlugin;
 pub use print_diagnostics_plugin::PrintDiagnosticsPlugin;
@@ -69,7 +69,7 @@ impl PrintDiagnosticsPlugin {
         time: Res<Time>,
         diagnostics: Res<Diagnostics>,
     ) {
-        state.timer.tick(time.delta_seconds);
+        state.timer.tick(time.raw_delta_seconds);
         if state.timer.finished {
             println!("Diagnostics:");
             println!("{}", "-".repeat(93));
@@ -90,7 +90,7 @@ impl PrintDiagnosticsPlugin {
         time: Res<Time>,
         diagnostics: Res<Diagnostics>,
     ) {
-        state.timer.tick(time.delta_seconds);
+        state.timer.tick(time.raw_delta_seconds);
         if state.timer.finished {
             println!("Diagnostics (Debug):");
             println!("{}", "-".repeat(93));
@@ -0,0 +1,226 @@
+//! Procedurally generated workloads for benchmarks and for comparing performance across engine versions and hardware.
+//! Describe a workload with a [StressTest] and spawn it with [StressTestPlugin] or [StressTest::spawn]. Entities are
+//! laid out on regular grids, so the same descriptor always produces the same scene.
+
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, IntoQuerySystem, Res, ResMut};
+use bevy_math::{Vec2, Vec3};
+use bevy_pbr::prelude::{LightComponents, PbrComponents, StandardMaterial};
+use bevy_render::{
+    color::Color,
+    entity::{Camera2dComponents, Camera3dComponents},
+    mesh::{shape, Mesh},
+};
+use bevy_sprite::{entity::SpriteComponents, ColorMaterial, Sprite};
+use bevy_transform::prelude::{BuildChildren, ChildBuilder, Transform};
+
+/// Describes a workload to spawn
+#[derive(Debug, Clone)]
+pub struct StressTest {
+    /// Cubes, each with its own material
+    pub meshes: usize,
+    pub lights: usize,
+    pub sprites: usize,
+    /// The number of hierarchies, each a chain of `hierarchy_depth` cubes parented to each other
+    pub hierarchies: usize,
+    pub hierarchy_depth: usize,
+    /// The distance between neighboring entities
+    pub spacing: f32,
+    /// Spawns a 3d camera that looks at the meshes and, if there are sprites, a 2d camera
+    pub spawn_cameras: bool,
+}
+
+impl Default for StressTest {
+    fn default() -> Self {
+        StressTest {
+            meshes: 1000,
+            lights: 4,
+            sprites: 0,
+            hierarchies: 0,
+            hierarchy_depth: 0,
+            spacing: 2.0,
+            spawn_cameras: true,
+        }
+    }
+}
+
+impl StressTest {
+    /// Spawns the workload. Every mesh entity shares one cube mesh, so the cost is dominated by the number of
+    /// entities and materials rather than by vertex processing.
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        color_materials: &mut Assets<ColorMaterial>,
+    ) {
+        let cube = meshes.add(Mesh::from(shape::Cube { size: 1.0 }));
+        let mut material = |index: usize| {
+            materials.add(StandardMaterial {
+                albedo: palette_color(index),
+                ..Default::default()
+            })
+        };
+
+        for (index, position) in grid_positions(self.meshes, self.spacing).enumerate() {
+            commands.spawn(PbrComponents {
+                mesh: cube.clone(),
+                material: material(index),
+                transform: Transform::from_translation(position),
+                ..Default::default()
+            });
+        }
+
+        let hierarchy_offset = Vec3::new(0.0, 0.0, -grid_extent(self.meshes, self.spacing));
+        for (index, position) in grid_positions(self.hierarchies, self.spacing).enumerate() {
+            if self.hierarchy_depth == 0 {
+                break;
+            }
+            let chain_material = material(index);
+            commands
+                .spawn(PbrComponents {
+                    mesh: cube.clone(),
+                    material: chain_material.clone(),
+                    transform: Transform::from_translation(position + hierarchy_offset),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    spawn_chain(parent, &cube, &chain_material, self.hierarchy_depth - 1)
+                });
+        }
+
+        let extent = grid_extent(self.meshes.max(self.hierarchies), self.spacing);
+        for position in grid_positions(self.lights, extent.max(self.spacing)) {
+            commands.spawn(LightComponents {
+                transform: Transform::from_translation(position + Vec3::new(0.0, extent, 0.0)),
+                ..Default::default()
+            });
+        }
+
+        // sprites are spread over a square of the xy plane
+        let sprite_size = Vec2::new(8.0, 8.0);
+        let side = grid_side(self.sprites, 2);
+        let centered = (side as f32 - 1.0) / 2.0;
+        for index in 0..self.sprites {
+            let (x, y) = ((index % side) as f32, (index / side) as f32);
+            commands.spawn(SpriteComponents {
+                material: color_materials.add(palette_color(index).into()),
+                sprite: Sprite::new(sprite_size),
+                transform: Transform::from_translation(Vec3::new(
+                    (x - centered) * sprite_size.x() * 1.5,
+                    (y - centered) * sprite_size.y() * 1.5,
+                    0.0,
+                )),
+                ..Default::default()
+            });
+        }
+
+        if self.spawn_cameras {
+            let distance = extent.max(self.spacing) * 1.5;
+            commands.spawn(Camera3dComponents {
+                transform: Transform::from_translation(Vec3::new(distance, distance, distance))
+                    .looking_at(Vec3::zero(), Vec3::unit_y()),
+                ..Default::default()
+            });
+            if self.sprites > 0 {
+                commands.spawn(Camera2dComponents::default());
+            }
+        }
+    }
+}
+
+fn spawn_chain(
+    parent: &mut ChildBuilder,
+    mesh: &Handle<Mesh>,
+    material: &Handle<StandardMaterial>,
+    depth: usize,
+) {
+    if depth == 0 {
+        return;
+    }
+    parent
+        .spawn(PbrComponents {
+            mesh: mesh.clone(),
+            material: material.clone(),
+            // each child is offset from its parent so changes ripple through the whole chain
+            transform: Transform::from_translation(Vec3::new(0.0, 1.1, 0.0)),
+            ..Default::default()
+        })
+        .with_children(|parent| spawn_chain(parent, mesh, material, depth - 1));
+}
+
+/// The smallest number of entities along each side of a grid of `dimensions` that fits `count` entities
+fn grid_side(count: usize, dimensions: u32) -> usize {
+    let mut side = 1;
+    while side.pow(dimensions) < count {
+        side += 1;
+    }
+    side
+}
+
+/// The length of the side of the cube [grid_positions] fills
+fn grid_extent(count: usize, spacing: f32) -> f32 {
+    grid_side(count, 3) as f32 * spacing
+}
+
+/// `count` positions on a cubic grid centered on the origin
+fn grid_positions(count: usize, spacing: f32) -> impl Iterator<Item = Vec3> {
+    let side = grid_side(count, 3);
+    let centered = (side as f32 - 1.0) / 2.0;
+    (0..count).map(move |index| {
+        let (x, y, z) = (index % side, (index / side) % side, index / (side * side));
+        (Vec3::new(x as f32, y as f32, z as f32) - Vec3::splat(centered)) * spacing
+    })
+}
+
+fn palette_color(index: usize) -> Color {
+    // golden ratio steps spread neighboring hues far apart
+    let hue = (index as f32 * 0.618_034).fract();
+    let channel = |offset: f32| {
+        let value = ((hue + offset).fract() * 6.0 - 3.0).abs() - 1.0;
+        value.max(0.0).min(1.0)
+    };
+    Color::rgb(channel(0.0), channel(2.0 / 3.0), channel(1.0 / 3.0))
+}
+
+/// Spawns the [StressTest] resource's workload at startup, or the default workload if there is none
+#[derive(Default)]
+pub struct StressTestPlugin;
+
+impl Plugin for StressTestPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<StressTest>()
+            .add_startup_system(stress_test_startup_system.system());
+    }
+}
+
+fn stress_test_startup_system(
+    mut commands: Commands,
+    stress_test: Res<StressTest>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+) {
+    stress_test.spawn(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut color_materials,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::grid_positions;
+    use bevy_math::Vec3;
+
+    #[test]
+    fn grid_is_centered() {
+        let positions = grid_positions(27, 2.0).collect::<Vec<_>>();
+        assert_eq!(positions.len(), 27);
+        assert_eq!(positions[0], Vec3::splat(-2.0));
+        assert_eq!(positions[13], Vec3::zero());
+        assert_eq!(positions[26], Vec3::splat(2.0));
+    }
+}
@@ -25,9 +25,10 @@ use syn::{parse_macro_input, DeriveInput, Ident, Index, Lifetime, Path};
 /// Implement `Bundle` for a monomorphic struct
 ///
 /// Using derived `Bundle` impls improves spawn performance and can be convenient when combined with
-/// other derives like `serde::Deserialize`.
+/// other derives like `serde::Deserialize`. Fields marked with `#[bundle]` are bundles themselves,
+/// and their components are added as if they were fields of this bundle.
 #[allow(clippy::cognitive_complexity)]
-#[proc_macro_derive(Bundle)]
+#[proc_macro_derive(Bundle, attributes(bundle))]
 pub fn derive_bundle(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     if !input.generics.params.is_empty() {
@@ -44,7 +45,22 @@ pub fn derive_bundle(input: TokenStream) -> TokenStream {
         }
     };
     let ident = input.ident;
-    let (tys, fields) = struct_fields(&data.fields);
+    let (all_tys, all_fields, is_bundle) = struct_fields(&data.fields);
+    let mut tys = Vec::new();
+    let mut fields = Vec::new();
+    let mut bundle_tys = Vec::new();
+    let mut bundle_fields = Vec::new();
+    for ((ty, field), is_bundle) in all_tys.into_iter().zip(all_fields).zip(is_bundle) {
+        if is_bundle {
+            bundle_tys.push(ty);
+            bundle_fields.push(field);
+        } else {
+            tys.push(ty);
+            fields.push(field);
+        }
+    }
+    let field_locals = get_idents(|i| format!("field_{}", i), fields.len());
+    let bundle_locals = get_idents(|i| format!("bundle_{}", i), bundle_fields.len());
     let path_str = if crate_name("bevy").is_ok() {
         "bevy::ecs"
     } else if crate_name("bevy_ecs").is_ok() {
@@ -55,7 +71,6 @@ pub fn derive_bundle(input: TokenStream) -> TokenStream {
 
     let path: Path = syn::parse(path_str.parse::<TokenStream>().unwrap()).unwrap();
 
-    let n = tys.len();
     let code = quote! {
         impl #path::DynamicBundle for #ident {
             fn with_ids<T>(&self, f: impl FnOnce(&[std::any::TypeId]) -> T) -> T {
@@ -73,28 +88,33 @@ pub fn derive_bundle(input: TokenStream) -> TokenStream {
                         std::mem::forget(self.#fields);
                     }
                 )*
+                #(
+                    <#bundle_tys as #path::DynamicBundle>::put(self.#bundle_fields, &mut f);
+                )*
             }
         }
 
         impl #path::Bundle for #ident {
             fn with_static_ids<T>(f: impl FnOnce(&[std::any::TypeId]) -> T) -> T {
                 use std::any::TypeId;
-                use std::mem;
 
                 #path::lazy_static::lazy_static! {
-                    static ref ELEMENTS: [TypeId; #n] = {
+                    static ref ELEMENTS: Vec<TypeId> = {
                         let mut dedup = #path::bevy_utils::HashSet::default();
-                        for &(ty, name) in [#((std::any::TypeId::of::<#tys>(), std::any::type_name::<#tys>())),*].iter() {
+                        let fields: &[(TypeId, &str)] = &[#((TypeId::of::<#tys>(), std::any::type_name::<#tys>())),*];
+                        for &(ty, name) in fields.iter() {
                             if !dedup.insert(ty) {
                                 panic!("{} has multiple {} fields; each type must occur at most once!", stringify!(#ident), name);
                             }
                         }
 
-                        let mut tys = [#((mem::align_of::<#tys>(), TypeId::of::<#tys>())),*];
-                        tys.sort_unstable_by(|x, y| x.0.cmp(&y.0).reverse().then(x.1.cmp(&y.1)));
-                        let mut ids = [TypeId::of::<()>(); #n];
-                        for (id, info) in ids.iter_mut().zip(tys.iter()) {
-                            *id = info.1;
+                        // static_type_info is sorted by descending alignment then id, like the ids must be
+                        let ids = <#ident as #path::Bundle>::static_type_info()
+                            .iter()
+                            .map(|info| info.id())
+                            .collect::<Vec<_>>();
+                        if ids.windows(2).any(|pair| pair[0] == pair[1]) {
+                            panic!("{} and its nested bundles have a component type more than once; each type must occur at most once!", stringify!(#ident));
                         }
                         ids
                     };
@@ -105,6 +125,9 @@ pub fn derive_bundle(input: TokenStream) -> TokenStream {
 
             fn static_type_info() -> Vec<#path::TypeInfo> {
                 let mut info = vec![#(#path::TypeInfo::of::<#tys>()),*];
+                #(
+                    info.extend(<#bundle_tys as #path::Bundle>::static_type_info());
+                )*
                 info.sort_unstable();
                 info
             }
@@ -113,33 +136,43 @@ pub fn derive_bundle(input: TokenStream) -> TokenStream {
                 mut f: impl FnMut(std::any::TypeId, usize) -> Option<std::ptr::NonNull<u8>>,
             ) -> Result<Self, #path::MissingComponent> {
                 #(
-                    let #fields = f(std::any::TypeId::of::<#tys>(), std::mem::size_of::<#tys>())
+                    let #field_locals = f(std::any::TypeId::of::<#tys>(), std::mem::size_of::<#tys>())
                             .ok_or_else(#path::MissingComponent::new::<#tys>)?
                             .cast::<#tys>()
                         .as_ptr();
                 )*
-                Ok(Self { #( #fields: #fields.read(), )* })
+                // nested bundles read their components as soon as they are all found, so they must not be dropped
+                // if a later nested bundle is missing a component
+                #(
+                    let #bundle_locals = std::mem::ManuallyDrop::new(
+                        <#bundle_tys as #path::Bundle>::get(&mut f)?
+                    );
+                )*
+                Ok(Self {
+                    #( #fields: #field_locals.read(), )*
+                    #( #bundle_fields: std::mem::ManuallyDrop::into_inner(#bundle_locals), )*
+                })
             }
         }
     };
     TokenStream::from(code)
 }
 
-fn struct_fields(fields: &syn::Fields) -> (Vec<&syn::Type>, Vec<syn::Ident>) {
-    match fields {
-        syn::Fields::Named(ref fields) => fields
-            .named
-            .iter()
-            .map(|f| (&f.ty, f.ident.clone().unwrap()))
-            .unzip(),
-        syn::Fields::Unnamed(ref fields) => fields
-            .unnamed
-            .iter()
-            .enumerate()
-            .map(|(i, f)| (&f.ty, syn::Ident::new(&i.to_string(), Span::call_site())))
-            .unzip(),
-        syn::Fields::Unit => (Vec::new(), Vec::new()),
+fn struct_fields(fields: &syn::Fields) -> (Vec<&syn::Type>, Vec<syn::Member>, Vec<bool>) {
+    let is_bundle =
+        |field: &syn::Field| field.attrs.iter().any(|attr| attr.path.is_ident("bundle"));
+    let mut tys = Vec::new();
+    let mut members = Vec::new();
+    let mut bundles = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        tys.push(&field.ty);
+        members.push(match field.ident {
+            Some(ref ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(Index::from(i)),
+        });
+        bundles.push(is_bundle(field));
     }
+    (tys, members, bundles)
 }
 
 fn get_idents(fmt_string: fn(usize) -> String, count: usize) -> Vec<Ident> {
@@ -35,6 +35,7 @@ impl ArchetypeComponent {
     }
 }
 
+#[derive(Debug)]
 pub enum QueryAccess {
     None,
     Read(TypeId, &'static str),
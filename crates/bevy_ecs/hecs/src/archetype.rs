@@ -249,10 +249,21 @@ impl Archetype {
         }
     }
 
-    fn capacity(&self) -> usize {
+    /// The number of entities the archetype can hold before it has to grow
+    pub fn capacity(&self) -> usize {
         self.entities.len()
     }
 
+    /// The bytes allocated for the archetype's components, entities and change trackers
+    pub fn allocated_bytes(&self) -> usize {
+        let trackers = self
+            .state
+            .values()
+            .map(|state| state.mutated_entities.capacity() + state.added_entities.capacity())
+            .sum::<usize>();
+        self.data_size + self.entities.capacity() * mem::size_of::<Entity>() + trackers
+    }
+
     #[allow(missing_docs)]
     pub fn clear_trackers(&mut self) {
         for type_state in self.state.values_mut() {
@@ -484,6 +495,7 @@ pub struct TypeInfo {
     id: TypeId,
     layout: Layout,
     drop: unsafe fn(*mut u8),
+    type_name: &'static str,
 }
 
 impl TypeInfo {
@@ -497,6 +509,7 @@ impl TypeInfo {
             id: TypeId::of::<T>(),
             layout: Layout::new::<T>(),
             drop: drop_ptr::<T>,
+            type_name: core::any::type_name::<T>(),
         }
     }
 
@@ -512,6 +525,12 @@ impl TypeInfo {
         self.layout
     }
 
+    /// The name of the type, for diagnostics
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
     pub(crate) unsafe fn drop(&self, data: *mut u8) {
         (self.drop)(data)
     }
@@ -69,6 +69,7 @@ mod borrow;
 mod bundle;
 mod entities;
 mod entity_builder;
+mod memory_report;
 mod query;
 #[cfg(feature = "serde")]
 mod serde;
@@ -80,6 +81,7 @@ pub use borrow::{AtomicBorrow, Ref, RefMut};
 pub use bundle::{Bundle, DynamicBundle, MissingComponent};
 pub use entities::{Entity, EntityReserver, Location, NoSuchEntity};
 pub use entity_builder::{BuiltEntity, EntityBuilder};
+pub use memory_report::{ArchetypeMemory, WorldMemoryReport};
 pub use query::{
     Added, Batch, BatchedIter, Changed, Mut, Mutated, Or, Query, QueryIter, ReadOnlyFetch, With,
     Without,
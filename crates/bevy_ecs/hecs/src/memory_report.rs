@@ -0,0 +1,77 @@
+use crate::alloc::vec::Vec;
+use core::fmt;
+
+/// The memory held by one archetype, as reported by [World::memory_report](crate::World::memory_report)
+#[derive(Debug, Clone)]
+pub struct ArchetypeMemory {
+    /// The position of the archetype in [World::archetypes](crate::World::archetypes)
+    pub index: usize,
+    /// The names of the archetype's component types
+    pub components: Vec<&'static str>,
+    pub entities: usize,
+    /// The number of entities the archetype has room for
+    pub capacity: usize,
+    pub bytes: usize,
+}
+
+/// How the entities of a world are spread over archetypes and how much memory each archetype holds.
+///
+/// Every distinct set of components gets its own archetype, so adding and removing marker components can fragment
+/// entities into many small archetypes. Archetypes are never freed, so ones that were only used briefly keep their
+/// memory.
+#[derive(Debug, Clone, Default)]
+pub struct WorldMemoryReport {
+    pub archetypes: Vec<ArchetypeMemory>,
+    pub entities: usize,
+    pub bytes: usize,
+}
+
+impl WorldMemoryReport {
+    /// Archetypes without entities. A large number of these means entities change their set of components often.
+    pub fn empty_archetypes(&self) -> impl Iterator<Item = &ArchetypeMemory> {
+        self.archetypes
+            .iter()
+            .filter(|archetype| archetype.entities == 0)
+    }
+
+    /// The average number of entities in the archetypes that have any
+    pub fn entities_per_archetype(&self) -> f64 {
+        let occupied = self
+            .archetypes
+            .iter()
+            .filter(|archetype| archetype.entities > 0)
+            .count();
+        if occupied == 0 {
+            0.0
+        } else {
+            self.entities as f64 / occupied as f64
+        }
+    }
+}
+
+impl fmt::Display for WorldMemoryReport {
+    /// Lists the archetypes from the largest to the smallest
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} entities in {} archetypes ({} empty), {} bytes",
+            self.entities,
+            self.archetypes.len(),
+            self.empty_archetypes().count(),
+            self.bytes
+        )?;
+        let mut archetypes = self.archetypes.iter().collect::<Vec<_>>();
+        archetypes.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        for archetype in archetypes {
+            writeln!(
+                f,
+                "{:>10} bytes {:>8}/{:<8} entities  [{}]",
+                archetype.bytes,
+                archetype.entities,
+                archetype.capacity,
+                archetype.components.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
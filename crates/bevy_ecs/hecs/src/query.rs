@@ -619,6 +619,8 @@ struct ChunkInfo<Q: Query> {
 /// Iterator over the set of entities with the components in `Q`
 pub struct QueryIter<'w, Q: Query> {
     archetypes: &'w [Archetype],
+    /// The indices of the archetypes to visit, if they are known ahead of time. Otherwise all archetypes are visited.
+    archetype_indices: Option<&'w [u32]>,
     archetype_index: usize,
     chunk_info: ChunkInfo<Q>,
     chunk_position: usize,
@@ -634,9 +636,10 @@ impl<'w, Q: Query> QueryIter<'w, Q> {
 
     /// Creates a new QueryIter
     #[inline]
-    pub(crate) fn new(archetypes: &'w [Archetype]) -> Self {
+    pub(crate) fn new(archetypes: &'w [Archetype], archetype_indices: Option<&'w [u32]>) -> Self {
         Self {
             archetypes,
+            archetype_indices,
             archetype_index: 0,
             chunk_info: Self::EMPTY,
             chunk_position: 0,
@@ -652,7 +655,11 @@ impl<'w, Q: Query> Iterator for QueryIter<'w, Q> {
         unsafe {
             loop {
                 if self.chunk_position == self.chunk_info.len {
-                    let archetype = self.archetypes.get(self.archetype_index)?;
+                    let archetype = get_archetype(
+                        self.archetypes,
+                        self.archetype_indices,
+                        self.archetype_index,
+                    )?;
                     self.archetype_index += 1;
                     self.chunk_position = 0;
                     self.chunk_info = Q::Fetch::get(archetype, 0)
@@ -688,11 +695,34 @@ where
     Q::Fetch: UnfilteredFetch,
 {
     fn len(&self) -> usize {
-        self.archetypes
-            .iter()
-            .filter(|&archetype| unsafe { Q::Fetch::get(archetype, 0).is_some() })
-            .map(|x| x.len())
-            .sum()
+        let count = |archetype: &Archetype| {
+            if unsafe { Q::Fetch::get(archetype, 0).is_some() } {
+                archetype.len()
+            } else {
+                0
+            }
+        };
+        match self.archetype_indices {
+            Some(indices) => indices
+                .iter()
+                .map(|index| count(&self.archetypes[*index as usize]))
+                .sum(),
+            None => self.archetypes.iter().map(count).sum(),
+        }
+    }
+}
+
+#[inline]
+fn get_archetype<'w>(
+    archetypes: &'w [Archetype],
+    archetype_indices: Option<&[u32]>,
+    index: usize,
+) -> Option<&'w Archetype> {
+    match archetype_indices {
+        Some(indices) => indices
+            .get(index)
+            .map(|archetype_index| &archetypes[*archetype_index as usize]),
+        None => archetypes.get(index),
     }
 }
 
@@ -724,6 +754,7 @@ impl<Q: Query> ChunkIter<Q> {
 /// Batched version of `QueryIter`
 pub struct BatchedIter<'w, Q: Query> {
     archetypes: &'w [Archetype],
+    archetype_indices: Option<&'w [u32]>,
     archetype_index: usize,
     batch_size: usize,
     batch: usize,
@@ -731,9 +762,14 @@ pub struct BatchedIter<'w, Q: Query> {
 }
 
 impl<'w, Q: Query> BatchedIter<'w, Q> {
-    pub(crate) fn new(archetypes: &'w [Archetype], batch_size: usize) -> Self {
+    pub(crate) fn new(
+        archetypes: &'w [Archetype],
+        archetype_indices: Option<&'w [u32]>,
+        batch_size: usize,
+    ) -> Self {
         Self {
             archetypes,
+            archetype_indices,
             archetype_index: 0,
             batch_size,
             batch: 0,
@@ -750,7 +786,11 @@ impl<'w, Q: Query> Iterator for BatchedIter<'w, Q> {
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let archetype = self.archetypes.get(self.archetype_index)?;
+            let archetype = get_archetype(
+                self.archetypes,
+                self.archetype_indices,
+                self.archetype_index,
+            )?;
             let offset = self.batch_size * self.batch;
             if offset >= archetype.len() {
                 self.archetype_index += 1;
@@ -27,6 +27,7 @@ use std::error::Error;
 use crate::{
     archetype::Archetype,
     entities::{Entities, Location},
+    memory_report::{ArchetypeMemory, WorldMemoryReport},
     Bundle, DynamicBundle, Entity, MissingComponent, NoSuchEntity, Query, Ref,
 };
 
@@ -317,7 +318,22 @@ impl World {
     /// This does not check for mutable query correctness. To be safe, make sure mutable queries
     /// have unique access to the components they query.
     pub unsafe fn query_unchecked<Q: Query>(&self) -> QueryIter<'_, Q> {
-        QueryIter::new(&self.archetypes)
+        QueryIter::new(&self.archetypes, None)
+    }
+
+    /// Like `query_unchecked`, but only visits the archetypes at `archetype_indices`. This skips
+    /// matching the query against every archetype when the matching archetypes are already known.
+    ///
+    /// # Safety
+    /// This does not check for mutable query correctness. To be safe, make sure mutable queries
+    /// have unique access to the components they query. `archetype_indices` must be valid archetype
+    /// indices in this world.
+    #[inline]
+    pub unsafe fn query_archetypes_unchecked<'a, Q: Query>(
+        &'a self,
+        archetype_indices: &'a [u32],
+    ) -> QueryIter<'a, Q> {
+        QueryIter::new(&self.archetypes, Some(archetype_indices))
     }
 
     /// Like `query`, but instead of returning a single iterator it returns a "batched iterator",
@@ -331,7 +347,22 @@ impl World {
         &self,
         batch_size: usize,
     ) -> BatchedIter<'_, Q> {
-        BatchedIter::new(&self.archetypes, batch_size)
+        BatchedIter::new(&self.archetypes, None, batch_size)
+    }
+
+    /// Like `query_batched_unchecked`, but only visits the archetypes at `archetype_indices`.
+    ///
+    /// # Safety
+    /// This does not check for mutable query correctness. To be safe, make sure mutable queries
+    /// have unique access to the components they query. `archetype_indices` must be valid archetype
+    /// indices in this world.
+    #[inline]
+    pub unsafe fn query_archetypes_batched_unchecked<'a, Q: Query>(
+        &'a self,
+        archetype_indices: &'a [u32],
+        batch_size: usize,
+    ) -> BatchedIter<'a, Q> {
+        BatchedIter::new(&self.archetypes, Some(archetype_indices), batch_size)
     }
 
     /// Prepare a read only query against a single entity
@@ -785,6 +816,38 @@ impl World {
         self.archetypes.iter()
     }
 
+    /// Reports how entities are spread over archetypes and how much memory each archetype holds
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_hecs::*;
+    /// let mut world = World::new();
+    /// world.spawn((123, "abc"));
+    /// world.spawn((456,));
+    /// let report = world.memory_report();
+    /// assert_eq!(report.entities, 2);
+    /// assert_eq!(report.entities_per_archetype(), 1.0);
+    /// ```
+    pub fn memory_report(&self) -> WorldMemoryReport {
+        let archetypes = self
+            .archetypes
+            .iter()
+            .enumerate()
+            .map(|(index, archetype)| ArchetypeMemory {
+                index,
+                components: archetype.types().iter().map(|ty| ty.type_name()).collect(),
+                entities: archetype.len(),
+                capacity: archetype.capacity(),
+                bytes: archetype.allocated_bytes(),
+            })
+            .collect::<Vec<_>>();
+        WorldMemoryReport {
+            entities: archetypes.iter().map(|archetype| archetype.entities).sum(),
+            bytes: archetypes.iter().map(|archetype| archetype.bytes).sum(),
+            archetypes,
+        }
+    }
+
     /// Returns a distinct value after `archetypes` is changed
     ///
     /// Store the current value after deriving information from `archetypes`, then check whether the
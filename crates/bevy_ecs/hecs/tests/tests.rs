@@ -195,6 +195,37 @@ fn bad_bundle_derive() {
     world.spawn(Foo { x: 42, y: 42 });
 }
 
+#[test]
+#[cfg(feature = "macros")]
+fn nested_bundle_derive() {
+    #[derive(Bundle)]
+    struct Inner {
+        y: f64,
+        z: bool,
+    }
+
+    #[derive(Bundle)]
+    struct Outer {
+        x: i32,
+        #[bundle]
+        inner: Inner,
+    }
+
+    let mut world = World::new();
+    let e = world.spawn(Outer {
+        x: 42,
+        inner: Inner { y: 1.0, z: true },
+    });
+    assert_eq!(*world.get::<i32>(e).unwrap(), 42);
+    assert_eq!(*world.get::<f64>(e).unwrap(), 1.0);
+    assert!(*world.get::<bool>(e).unwrap());
+
+    let removed = world.remove::<Outer>(e).unwrap();
+    assert_eq!(removed.x, 42);
+    assert_eq!(removed.inner.y, 1.0);
+    assert!(world.get::<bool>(e).is_err());
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn spawn_many() {
@@ -361,3 +392,30 @@ fn added_tracking() {
     assert!(world.query_one_mut::<&i32>(a).is_ok());
     assert!(world.query_one_mut::<Added<i32>>(a).is_err());
 }
+
+#[test]
+fn memory_report() {
+    struct Marker;
+
+    let mut world = World::new();
+    let a = world.spawn((123, true));
+    world.spawn((456, false));
+    world.insert_one(a, Marker).unwrap();
+    world.remove_one::<Marker>(a).unwrap();
+
+    let report = world.memory_report();
+    assert_eq!(report.entities, 2);
+    // the archetype with the marker is empty, but it is kept around
+    assert_eq!(report.empty_archetypes().count(), 2);
+    let marked = report
+        .archetypes
+        .iter()
+        .find(|archetype| archetype.components.len() == 3)
+        .unwrap();
+    assert_eq!(marked.entities, 0);
+    assert!(marked.bytes > 0);
+    assert!(marked
+        .components
+        .iter()
+        .any(|name| name.ends_with("Marker")));
+}
@@ -11,7 +11,9 @@ pub use world::*;
 
 pub mod prelude {
     pub use crate::{
-        resource::{ChangedRes, FromResources, Local, OrRes, Res, ResMut, Resource, Resources},
+        resource::{
+            ChangedRes, FromResources, FromWorld, Local, OrRes, Res, ResMut, Resource, Resources,
+        },
         system::{
             Commands, IntoForEachSystem, IntoQuerySystem, IntoThreadLocalSystem, Query, System,
         },
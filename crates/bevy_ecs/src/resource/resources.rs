@@ -1,6 +1,6 @@
 use super::{FetchResource, ResourceQuery};
 use crate::system::SystemId;
-use bevy_hecs::{Archetype, AtomicBorrow, Entity, Ref, RefMut, TypeInfo, TypeState};
+use bevy_hecs::{Archetype, AtomicBorrow, Entity, Ref, RefMut, TypeInfo, TypeState, World};
 use bevy_utils::HashMap;
 use core::any::TypeId;
 use downcast_rs::{impl_downcast, Downcast};
@@ -402,6 +402,34 @@ where
     }
 }
 
+/// Creates `Self` using the [World] and mutable access to the `Resources` collection. Implement this instead of
+/// [FromResources] for resources that need to create assets or gpu resources when they are initialized.
+pub trait FromWorld {
+    /// Creates `Self` using the [World] and the `Resources` collection
+    fn from_world(world: &mut World, resources: &mut Resources) -> Self;
+}
+
+impl<T> FromWorld for T
+where
+    T: FromResources,
+{
+    fn from_world(_world: &mut World, resources: &mut Resources) -> Self {
+        T::from_resources(resources)
+    }
+}
+
+/// Inserts `R` if it isn't already a resource. Add it as a thread local system to initialize a resource once the
+/// resources it depends on exist.
+pub fn init_resource_system<R>(world: &mut World, resources: &mut Resources)
+where
+    R: FromWorld + Send + Sync + 'static,
+{
+    if resources.get::<R>().is_none() {
+        let resource = R::from_world(world, resources);
+        resources.insert(resource);
+    }
+}
+
 /// Shared borrow of an entity's component
 #[derive(Clone)]
 pub struct ResourceRef<'a, T: 'static> {
@@ -521,8 +549,9 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::Resources;
+    use super::{init_resource_system, FromWorld, Resources};
     use crate::system::SystemId;
+    use bevy_hecs::World;
 
     #[test]
     fn resource() {
@@ -613,4 +642,29 @@ mod tests {
         .join()
         .unwrap();
     }
+
+    #[test]
+    fn init_resource_from_world() {
+        struct EntityCount(usize);
+
+        impl FromWorld for EntityCount {
+            fn from_world(world: &mut World, resources: &mut Resources) -> Self {
+                let offset = *resources.get::<i32>().unwrap() as usize;
+                EntityCount(world.iter().count() + offset)
+            }
+        }
+
+        let mut world = World::new();
+        world.spawn((1u32,));
+        let mut resources = Resources::default();
+        resources.insert(10);
+
+        init_resource_system::<EntityCount>(&mut world, &mut resources);
+        assert_eq!(resources.get::<EntityCount>().unwrap().0, 11);
+
+        // existing resources are kept
+        world.spawn((2u32,));
+        init_resource_system::<EntityCount>(&mut world, &mut resources);
+        assert_eq!(resources.get::<EntityCount>().unwrap().0, 11);
+    }
 }
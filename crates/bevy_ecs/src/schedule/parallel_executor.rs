@@ -6,7 +6,11 @@ use crate::{
 use bevy_hecs::{ArchetypesGeneration, TypeAccess, World};
 use bevy_tasks::{ComputeTaskPool, CountdownEvent, TaskPool};
 use fixedbitset::FixedBitSet;
-use std::ops::Range;
+use std::{
+    borrow::Cow,
+    ops::Range,
+    time::{Duration, Instant},
+};
 
 /// Executes each schedule stage in parallel by analyzing system dependencies.
 /// System execution order is undefined except under the following conditions:
@@ -21,6 +25,8 @@ pub struct ParallelExecutor {
     stages: Vec<ExecutorStage>,
     last_schedule_generation: usize,
     clear_trackers: bool,
+    measure_stage_times: bool,
+    stage_times: Vec<(Cow<'static, str>, Duration)>,
 }
 
 impl Default for ParallelExecutor {
@@ -29,6 +35,8 @@ impl Default for ParallelExecutor {
             stages: Default::default(),
             last_schedule_generation: usize::MAX, // MAX forces prepare to run the first time
             clear_trackers: true,
+            measure_stage_times: false,
+            stage_times: Vec::new(),
         }
     }
 }
@@ -41,6 +49,19 @@ impl ParallelExecutor {
         }
     }
 
+    /// Enables measuring how long each stage takes to run. The times of the last run are available from
+    /// [ParallelExecutor::stage_times].
+    pub fn measure_stage_times(&mut self, enabled: bool) {
+        self.measure_stage_times = enabled;
+        self.stage_times.clear();
+    }
+
+    /// The time each stage took during the last run, in stage order. Empty unless
+    /// [ParallelExecutor::measure_stage_times] is enabled.
+    pub fn stage_times(&self) -> &[(Cow<'static, str>, Duration)] {
+        &self.stage_times
+    }
+
     pub fn initialize(&mut self, resources: &mut Resources) {
         if resources.get::<ComputeTaskPool>().is_none() {
             resources.insert(ComputeTaskPool(TaskPool::default()));
@@ -55,11 +76,20 @@ impl ParallelExecutor {
             self.stages
                 .resize_with(schedule.stage_order.len(), ExecutorStage::default);
         }
+        self.stage_times.clear();
         for (stage_name, executor_stage) in schedule.stage_order.iter().zip(self.stages.iter_mut())
         {
             log::trace!("run stage {:?}", stage_name);
             if let Some(stage_systems) = schedule.stages.get_mut(stage_name) {
+                let start = if self.measure_stage_times {
+                    Some(Instant::now())
+                } else {
+                    None
+                };
                 executor_stage.run(world, resources, stage_systems, schedule_changed);
+                if let Some(start) = start {
+                    self.stage_times.push((stage_name.clone(), start.elapsed()));
+                }
             }
         }
 
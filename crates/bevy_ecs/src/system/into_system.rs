@@ -2,7 +2,7 @@ pub use super::Query;
 use crate::{
     resource::{FetchResource, ResourceQuery, Resources, UnsafeClone},
     system::{Commands, System, SystemId, ThreadLocalExecution},
-    QueryAccess, QuerySet, QueryTuple, TypeAccess,
+    QueryAccess, QueryArchetypeCache, QuerySet, QueryTuple, TypeAccess,
 };
 use bevy_hecs::{ArchetypeComponent, Fetch, Query as HecsQuery, World};
 use std::{any::TypeId, borrow::Cow};
@@ -81,7 +81,7 @@ pub trait IntoForEachSystem<CommandBuffer, R, C> {
 
 struct ForEachState {
     commands: Commands,
-    query_access: QueryAccess,
+    archetype_cache: QueryArchetypeCache,
 }
 
 macro_rules! impl_into_foreach_system {
@@ -106,7 +106,7 @@ macro_rules! impl_into_foreach_system {
                 Box::new(SystemFn {
                     state: ForEachState {
                         commands: Commands::default(),
-                        query_access: <($($component,)*) as HecsQuery>::Fetch::access(),
+                        archetype_cache: QueryArchetypeCache::new::<($($component,)*)>(),
                     },
                     thread_local_execution: ThreadLocalExecution::NextFlush,
                     name: core::any::type_name::<Self>().into(),
@@ -117,7 +117,11 @@ macro_rules! impl_into_foreach_system {
                             if let Some(($($resource,)*)) = resources.query_system::<($($resource,)*)>(id) {
                                 // SAFE: the scheduler has ensured that there is no archetype clashing here
                                 unsafe {
-                                    for ($($component,)*) in world.query_unchecked::<($($component,)*)>() {
+                                    let query = match state.archetype_cache.archetype_indices(world) {
+                                        Some(archetype_indices) => world.query_archetypes_unchecked::<($($component,)*)>(archetype_indices),
+                                        None => world.query_unchecked::<($($component,)*)>(),
+                                    };
+                                    for ($($component,)*) in query {
                                         fn_call!(self, ($($commands, state_commands)*), ($($resource),*), ($($component),*))
                                     }
                                 }
@@ -135,7 +139,8 @@ macro_rules! impl_into_foreach_system {
                     archetype_component_access: TypeAccess::default(),
                     update_func: |world, archetype_component_access, state| {
                         archetype_component_access.clear();
-                        state.query_access.get_world_archetype_access(world, Some(archetype_component_access));
+                        state.archetype_cache.query_access().get_world_archetype_access(world, Some(archetype_component_access));
+                        state.archetype_cache.update(world);
                     },
                 })
             }
@@ -147,6 +152,8 @@ struct QuerySystemState {
     query_accesses: Vec<Vec<QueryAccess>>,
     query_type_names: Vec<&'static str>,
     archetype_component_accesses: Vec<TypeAccess<ArchetypeComponent>>,
+    /// One per [Query]. Query sets are not cached.
+    archetype_caches: Vec<QueryArchetypeCache>,
     commands: Commands,
 }
 
@@ -187,11 +194,15 @@ macro_rules! impl_into_query_system {
                     $(std::any::type_name::<$query_set>(),)*
                 ];
                 let archetype_component_accesses = vec![TypeAccess::default(); query_accesses.len()];
+                let archetype_caches = vec![
+                    $(QueryArchetypeCache::new::<$query>(),)*
+                ];
                 Box::new(SystemFn {
                     state: QuerySystemState {
                         query_accesses,
                         query_type_names,
                         archetype_component_accesses,
+                        archetype_caches,
                         commands: Commands::default(),
                     },
                     thread_local_execution: ThreadLocalExecution::NextFlush,
@@ -202,9 +213,11 @@ macro_rules! impl_into_query_system {
                             if let Some(($($resource,)*)) = resources.query_system::<($($resource,)*)>(id) {
                                 let mut i = 0;
                                 $(
-                                    let $query = Query::<$query>::new(
+                                    let $query = Query::<$query>::new_cached(
                                         world,
-                                        &state.archetype_component_accesses[i]
+                                        &state.archetype_component_accesses[i],
+                                        &state.archetype_caches[i],
+                                        core::any::type_name::<Self>()
                                     );
                                     i += 1;
                                 )*
@@ -251,6 +264,9 @@ macro_rules! impl_into_query_system {
                             }
                             archetype_component_access.union(component_access);
                         }
+                        for archetype_cache in state.archetype_caches.iter_mut() {
+                            archetype_cache.update(world);
+                        }
                         if let Some(conflict_index) = conflict_index {
                             let mut conflicts_with_index = None;
                             for prior_index in 0..conflict_index {
@@ -416,7 +432,7 @@ mod tests {
     use crate::{
         resource::{ResMut, Resources},
         schedule::Schedule,
-        ChangedRes, Mut, QuerySet,
+        ChangedRes, Mut, QuerySet, QuerySingleError,
     };
     use bevy_hecs::{Entity, With, World};
 
@@ -489,6 +505,43 @@ mod tests {
         assert!(*resources.get::<bool>().unwrap(), "system ran");
     }
 
+    #[test]
+    fn query_system_single() {
+        fn query_system(
+            mut ran: ResMut<bool>,
+            a_query: Query<&A>,
+            mut b_query: Query<&mut B>,
+            a_c_query: Query<(&A, &C)>,
+        ) {
+            assert!(b_query.single_mut().is_ok());
+            match a_query.single() {
+                Err(QuerySingleError::MultipleEntities { system, .. }) => {
+                    assert!(system.unwrap().contains("query_system"))
+                }
+                _ => panic!("a_query should find multiple entities"),
+            }
+            let error = a_c_query.single().err().unwrap();
+            assert!(matches!(error, QuerySingleError::NoEntities { .. }));
+            assert!(error.to_string().contains("found no entities"));
+
+            *ran = true;
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(false);
+        world.spawn((A,));
+        world.spawn((A, B));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", query_system.system());
+
+        schedule.run(&mut world, &mut resources);
+
+        assert!(*resources.get::<bool>().unwrap(), "system ran");
+    }
+
     #[test]
     fn changed_resource_system() {
         fn incr_e_on_flip(_run_on_flip: ChangedRes<bool>, mut i: Mut<i32>) {
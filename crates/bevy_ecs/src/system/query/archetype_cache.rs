@@ -0,0 +1,149 @@
+use bevy_hecs::{Archetype, ArchetypesGeneration, Fetch, Query, QueryAccess, World};
+use std::fmt;
+
+/// The archetypes a query matches. Systems update it when archetypes are added to the world, so iterating a query
+/// only visits the archetypes it matches instead of checking every archetype in the world each time.
+pub struct QueryArchetypeCache {
+    query_access: QueryAccess,
+    matches: fn(&Archetype) -> bool,
+    archetype_indices: Vec<u32>,
+    archetypes_seen: usize,
+    generation: Option<ArchetypesGeneration>,
+}
+
+/// Matches archetypes the same way iterating `Q` without a cache does. Filters like `With<T, ()>` declare no access,
+/// so their [QueryAccess] can't tell which archetypes they match.
+fn matches<Q: Query>(archetype: &Archetype) -> bool {
+    // SAFE: offset 0 is in bounds of every archetype, and the fetch is dropped without fetching anything
+    unsafe { <Q::Fetch as Fetch>::get(archetype, 0).is_some() }
+}
+
+impl QueryArchetypeCache {
+    pub fn new<Q: Query>() -> Self {
+        Self {
+            query_access: <Q::Fetch as Fetch>::access(),
+            matches: matches::<Q>,
+            archetype_indices: Vec::new(),
+            archetypes_seen: 0,
+            generation: None,
+        }
+    }
+
+    pub fn query_access(&self) -> &QueryAccess {
+        &self.query_access
+    }
+
+    /// Matches the archetypes that were added to `world` since the last update
+    pub fn update(&mut self, world: &World) {
+        let archetypes = world.archetypes();
+        let archetype_count = archetypes.len();
+        // archetypes are never removed from a world, so fewer archetypes means this is a different world
+        if archetype_count < self.archetypes_seen {
+            self.archetype_indices.clear();
+            self.archetypes_seen = 0;
+        }
+
+        for (index, archetype) in archetypes.enumerate().skip(self.archetypes_seen) {
+            if (self.matches)(archetype) {
+                self.archetype_indices.push(index as u32);
+            }
+        }
+
+        self.archetypes_seen = archetype_count;
+        self.generation = Some(world.archetypes_generation());
+    }
+
+    /// The indices of the matching archetypes, or `None` if archetypes were added to `world` since the last update
+    #[inline]
+    pub fn archetype_indices(&self, world: &World) -> Option<&[u32]> {
+        if self.generation == Some(world.archetypes_generation()) {
+            Some(&self.archetype_indices)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Debug for QueryArchetypeCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryArchetypeCache")
+            .field("query_access", &self.query_access)
+            .field("archetype_indices", &self.archetype_indices)
+            .field("archetypes_seen", &self.archetypes_seen)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_hecs::{Entity, Or, With};
+
+    #[test]
+    fn archetype_cache_updates_incrementally() {
+        let mut world = World::new();
+        world.spawn((1u32,));
+        world.spawn((1u32, 2.0f32));
+        world.spawn((true,));
+
+        let mut cache = QueryArchetypeCache::new::<&u32>();
+        assert_eq!(cache.archetype_indices(&world), None);
+        cache.update(&world);
+        let matched = cache.archetype_indices(&world).unwrap().to_vec();
+        assert_eq!(matched.len(), 2);
+        let values = unsafe { world.query_archetypes_unchecked::<&u32>(&matched) }
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![1, 1]);
+
+        // new archetypes invalidate the cache until it is updated
+        world.spawn((3u32, true));
+        world.spawn((3.0f64,));
+        assert_eq!(cache.archetype_indices(&world), None);
+        cache.update(&world);
+        let matched = cache.archetype_indices(&world).unwrap();
+        assert_eq!(matched.len(), 3);
+        assert_eq!(
+            unsafe { world.query_archetypes_unchecked::<&u32>(matched) }.count(),
+            3
+        );
+    }
+
+    fn cached<'a, Q: Query>(
+        world: &'a World,
+        cache: &'a mut QueryArchetypeCache,
+    ) -> Vec<<Q::Fetch as Fetch<'a>>::Item> {
+        cache.update(world);
+        let matched = cache.archetype_indices(world).unwrap();
+        unsafe { world.query_archetypes_unchecked::<Q>(matched) }.collect()
+    }
+
+    fn uncached<Q: Query>(world: &World) -> Vec<<Q::Fetch as Fetch<'_>>::Item> {
+        unsafe { world.query_unchecked::<Q>() }.collect()
+    }
+
+    #[test]
+    fn archetype_cache_matches_filters_without_access() {
+        let mut world = World::new();
+        world.spawn((1u32,));
+        world.spawn((1u32, true));
+        world.spawn((true,));
+        world.spawn((1.0f32,));
+
+        let mut cache = QueryArchetypeCache::new::<With<u32, ()>>();
+        let found = cached::<With<u32, ()>>(&world, &mut cache);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found, uncached::<With<u32, ()>>(&world));
+
+        let mut cache = QueryArchetypeCache::new::<With<u32, Entity>>();
+        let found = cached::<With<u32, Entity>>(&world, &mut cache);
+        assert_eq!(found, uncached::<With<u32, Entity>>(&world));
+
+        type Filter = Or<(With<u32, Entity>, With<bool, ()>)>;
+        let mut cache = QueryArchetypeCache::new::<Filter>();
+        let found = cached::<Filter>(&world, &mut cache);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found, uncached::<Filter>(&world));
+    }
+}
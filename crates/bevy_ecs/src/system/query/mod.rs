@@ -1,5 +1,7 @@
+mod archetype_cache;
 mod query_set;
 
+pub use archetype_cache::*;
 pub use query_set::*;
 
 use bevy_hecs::{
@@ -7,48 +9,150 @@ use bevy_hecs::{
     Query as HecsQuery, QueryIter, ReadOnlyFetch, TypeAccess, World,
 };
 use bevy_tasks::ParallelIterator;
-use std::marker::PhantomData;
+use std::{fmt, marker::PhantomData};
+use thiserror::Error;
 
 /// Provides scoped access to a World according to a given [HecsQuery]
 #[derive(Debug)]
 pub struct Query<'a, Q: HecsQuery> {
     pub(crate) world: &'a World,
     pub(crate) component_access: &'a TypeAccess<ArchetypeComponent>,
+    pub(crate) archetype_cache: Option<&'a QueryArchetypeCache>,
+    /// The name of the system the query belongs to, for error messages
+    pub(crate) system_name: Option<&'static str>,
     _marker: PhantomData<Q>,
 }
 
 /// An error that occurs when using a [Query]
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum QueryError {
+    #[error("The query does not read the entity's archetype.")]
     CannotReadArchetype,
+    #[error("The query does not write the entity's archetype.")]
     CannotWriteArchetype,
+    #[error("{0}")]
     ComponentError(ComponentError),
+    #[error("The entity does not exist.")]
     NoSuchEntity,
+    #[error("The entity does not have the components of the query.")]
+    QueryDoesNotMatch,
+}
+
+/// An error returned by [Query::single] and [Query::single_mut]
+#[derive(Debug)]
+pub enum QuerySingleError {
+    NoEntities {
+        query: &'static str,
+        system: Option<&'static str>,
+    },
+    MultipleEntities {
+        query: &'static str,
+        system: Option<&'static str>,
+    },
+}
+
+impl fmt::Display for QuerySingleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (query, system, found) = match self {
+            QuerySingleError::NoEntities { query, system } => (query, system, "no entities"),
+            QuerySingleError::MultipleEntities { query, system } => {
+                (query, system, "multiple entities")
+            }
+        };
+        write!(f, "Query<{}> ", query)?;
+        if let Some(system) = system {
+            write!(f, "in system {} ", system)?;
+        }
+        write!(f, "found {}, but exactly one was expected.", found)
+    }
 }
 
+impl std::error::Error for QuerySingleError {}
+
 impl<'a, Q: HecsQuery> Query<'a, Q> {
     #[inline]
     pub fn new(world: &'a World, component_access: &'a TypeAccess<ArchetypeComponent>) -> Self {
         Self {
             world,
             component_access,
+            archetype_cache: None,
+            system_name: None,
+            _marker: PhantomData::default(),
+        }
+    }
+
+    /// Creates a query that only visits the archetypes in `archetype_cache` while the cache is up to date
+    #[inline]
+    pub fn new_cached(
+        world: &'a World,
+        component_access: &'a TypeAccess<ArchetypeComponent>,
+        archetype_cache: &'a QueryArchetypeCache,
+        system_name: &'static str,
+    ) -> Self {
+        Self {
+            world,
+            component_access,
+            archetype_cache: Some(archetype_cache),
+            system_name: Some(system_name),
             _marker: PhantomData::default(),
         }
     }
 
+    fn entity_error(&self, entity: Entity) -> QueryError {
+        if self.world.contains(entity) {
+            QueryError::QueryDoesNotMatch
+        } else {
+            QueryError::NoSuchEntity
+        }
+    }
+
+    fn single_error(&self, found_multiple: bool) -> QuerySingleError {
+        let query = std::any::type_name::<Q>();
+        let system = self.system_name;
+        if found_multiple {
+            QuerySingleError::MultipleEntities { query, system }
+        } else {
+            QuerySingleError::NoEntities { query, system }
+        }
+    }
+
+    #[inline]
+    unsafe fn query_unchecked(&self) -> QueryIter<'a, Q> {
+        match self
+            .archetype_cache
+            .and_then(|archetype_cache| archetype_cache.archetype_indices(self.world))
+        {
+            Some(archetype_indices) => self.world.query_archetypes_unchecked(archetype_indices),
+            None => self.world.query_unchecked(),
+        }
+    }
+
+    #[inline]
+    unsafe fn query_batched_unchecked(&self, batch_size: usize) -> BatchedIter<'a, Q> {
+        match self
+            .archetype_cache
+            .and_then(|archetype_cache| archetype_cache.archetype_indices(self.world))
+        {
+            Some(archetype_indices) => self
+                .world
+                .query_archetypes_batched_unchecked(archetype_indices, batch_size),
+            None => self.world.query_batched_unchecked(batch_size),
+        }
+    }
+
     /// Iterates over the query results. This can only be called for read-only queries
     pub fn iter(&self) -> QueryIter<'_, Q>
     where
         Q::Fetch: ReadOnlyFetch,
     {
         // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
-        unsafe { self.world.query_unchecked() }
+        unsafe { self.query_unchecked() }
     }
 
     /// Iterates over the query results
     pub fn iter_mut(&mut self) -> QueryIter<'_, Q> {
         // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
-        unsafe { self.world.query_unchecked() }
+        unsafe { self.query_unchecked() }
     }
 
     /// Iterates over the query results
@@ -56,7 +160,7 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
     /// This allows aliased mutability. You must make sure this call does not result in multiple mutable references to the same component
     pub unsafe fn iter_unsafe(&self) -> QueryIter<'_, Q> {
         // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
-        self.world.query_unchecked()
+        self.query_unchecked()
     }
 
     #[inline]
@@ -65,13 +169,38 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
         Q::Fetch: ReadOnlyFetch,
     {
         // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
-        unsafe { ParIter::new(self.world.query_batched_unchecked(batch_size)) }
+        unsafe { ParIter::new(self.query_batched_unchecked(batch_size)) }
     }
 
     #[inline]
     pub fn par_iter_mut(&mut self, batch_size: usize) -> ParIter<'_, Q> {
         // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
-        unsafe { ParIter::new(self.world.query_batched_unchecked(batch_size)) }
+        unsafe { ParIter::new(self.query_batched_unchecked(batch_size)) }
+    }
+
+    /// Gets the query result of the only entity that fits the query. Use this for queries that should always
+    /// match exactly one entity, like the player or the main camera.
+    pub fn single(&self) -> Result<<Q::Fetch as Fetch>::Item, QuerySingleError>
+    where
+        Q::Fetch: ReadOnlyFetch,
+    {
+        let mut iter = self.iter();
+        match (iter.next(), iter.next()) {
+            (Some(item), None) => Ok(item),
+            (None, _) => Err(self.single_error(false)),
+            (Some(_), Some(_)) => Err(self.single_error(true)),
+        }
+    }
+
+    /// Gets the query result of the only entity that fits the query
+    pub fn single_mut(&mut self) -> Result<<Q::Fetch as Fetch>::Item, QuerySingleError> {
+        // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
+        let mut iter = unsafe { self.query_unchecked() };
+        match (iter.next(), iter.next()) {
+            (Some(item), None) => Ok(item),
+            (None, _) => Err(self.single_error(false)),
+            (Some(_), Some(_)) => Err(self.single_error(true)),
+        }
     }
 
     /// Gets the query result for the given `entity`
@@ -83,7 +212,7 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
         unsafe {
             self.world
                 .query_one_unchecked::<Q>(entity)
-                .map_err(|_err| QueryError::NoSuchEntity)
+                .map_err(|_err| self.entity_error(entity))
         }
     }
 
@@ -93,7 +222,7 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
         unsafe {
             self.world
                 .query_one_unchecked::<Q>(entity)
-                .map_err(|_err| QueryError::NoSuchEntity)
+                .map_err(|_err| self.entity_error(entity))
         }
     }
 
@@ -106,7 +235,7 @@ impl<'a, Q: HecsQuery> Query<'a, Q> {
     ) -> Result<<Q::Fetch as Fetch>::Item, QueryError> {
         self.world
             .query_one_unchecked::<Q>(entity)
-            .map_err(|_err| QueryError::NoSuchEntity)
+            .map_err(|_err| self.entity_error(entity))
     }
 
     /// Gets a reference to the entity's component of the given type. This will fail if the entity does not have
@@ -0,0 +1,291 @@
+use crate::{
+    resource::Resources,
+    system::{Command, Commands},
+};
+use bevy_hecs::{Component, Entity, World};
+use std::{any::TypeId, marker::PhantomData};
+
+/// A map from entities to `T` values, stored as a sparse set, for per-entity data that is added and removed often,
+/// like `Hovered` or `Damaged` markers.
+///
+/// This is a standalone container, not a storage option of the [World]: the values are kept in a resource outside
+/// of the archetypes, so setting and removing them never moves an entity to another archetype. They are packed
+/// densely, so iterating them is fast. A [Query](crate::Query) and `World::get` don't see them, and the `Added`,
+/// `Mutated` and `Changed` filters don't track them, so systems read them with `Res<EntitySparseSet<T>>`, look up the
+/// entities of their queries, and use [EntitySparseSet::added] and [EntitySparseSet::removed] to see what changed.
+///
+/// The resource is created by [EntitySparseSet::init], which `AppBuilder::add_entity_sparse_set` and
+/// [Commands::insert_into_sparse_set] call, and is then maintained by [entity_sparse_set_maintenance_system]: it
+/// removes the values of despawned entities and clears the added and removed lists.
+#[derive(Debug)]
+pub struct EntitySparseSet<T> {
+    dense: Vec<T>,
+    entities: Vec<Entity>,
+    /// The index in `dense` of each entity's value, by entity id
+    sparse: Vec<Option<u32>>,
+    added: Vec<Entity>,
+    removed: Vec<Entity>,
+}
+
+impl<T> Default for EntitySparseSet<T> {
+    fn default() -> Self {
+        EntitySparseSet {
+            dense: Vec::new(),
+            entities: Vec::new(),
+            sparse: Vec::new(),
+            added: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+impl<T: Component> EntitySparseSet<T> {
+    /// Inserts an empty [EntitySparseSet] if there is none yet and registers it for maintenance
+    pub fn init(resources: &mut Resources) {
+        if !resources.contains::<EntitySparseSet<T>>() {
+            resources.insert(EntitySparseSet::<T>::default());
+        }
+
+        let mut maintenance = resources.get_or_insert_with(EntitySparseSetMaintenance::default);
+        let type_id = TypeId::of::<T>();
+        if !maintenance.sets.iter().any(|(set, _)| *set == type_id) {
+            maintenance.sets.push((type_id, maintain::<T>));
+        }
+    }
+}
+
+impl<T> EntitySparseSet<T> {
+    fn index(&self, entity: Entity) -> Option<usize> {
+        let index = (*self.sparse.get(entity.id() as usize)?)? as usize;
+        // the id may belong to an older, despawned entity
+        if self.entities[index] == entity {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the value of `entity`, returning the value it replaced
+    pub fn insert(&mut self, entity: Entity, value: T) -> Option<T> {
+        let id = entity.id() as usize;
+        if id >= self.sparse.len() {
+            self.sparse.resize(id + 1, None);
+        }
+
+        if let Some(index) = self.sparse[id] {
+            let index = index as usize;
+            if self.entities[index] == entity {
+                return Some(std::mem::replace(&mut self.dense[index], value));
+            }
+
+            // a despawned entity with the same id still has a value
+            let old_entity = self.entities[index];
+            self.entities[index] = entity;
+            self.dense[index] = value;
+            self.removed.push(old_entity);
+            self.added.push(entity);
+            return None;
+        }
+
+        self.sparse[id] = Some(self.dense.len() as u32);
+        self.dense.push(value);
+        self.entities.push(entity);
+        self.added.push(entity);
+        None
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let index = self.index(entity)?;
+        self.sparse[entity.id() as usize] = None;
+        self.entities.swap_remove(index);
+        let value = self.dense.swap_remove(index);
+        if let Some(moved) = self.entities.get(index) {
+            self.sparse[moved.id() as usize] = Some(index as u32);
+        }
+
+        self.removed.push(entity);
+        Some(value)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        self.index(entity).map(|index| &self.dense[index])
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        self.index(entity).map(move |index| &mut self.dense[index])
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.index(entity).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.entities.iter().copied().zip(self.dense.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.entities.iter().copied().zip(self.dense.iter_mut())
+    }
+
+    /// Removes the values of entities `f` returns `false` for
+    pub fn retain(&mut self, mut f: impl FnMut(Entity, &T) -> bool) {
+        let mut index = 0;
+        while index < self.dense.len() {
+            if f(self.entities[index], &self.dense[index]) {
+                index += 1;
+            } else {
+                self.remove(self.entities[index]);
+            }
+        }
+    }
+
+    /// Entities that got a value since the last frame
+    pub fn added(&self) -> &[Entity] {
+        &self.added
+    }
+
+    /// Entities that lost their value since the last frame
+    pub fn removed(&self) -> &[Entity] {
+        &self.removed
+    }
+
+    pub fn clear_trackers(&mut self) {
+        self.added.clear();
+        self.removed.clear();
+    }
+}
+
+/// The maintenance of every [EntitySparseSet] resource created with [EntitySparseSet::init]
+#[derive(Default)]
+pub struct EntitySparseSetMaintenance {
+    sets: Vec<(TypeId, fn(&World, &Resources))>,
+}
+
+fn maintain<T: Component>(world: &World, resources: &Resources) {
+    if let Some(mut set) = resources.get_mut::<EntitySparseSet<T>>() {
+        set.retain(|entity, _| world.contains(entity));
+        set.clear_trackers();
+    }
+}
+
+/// Removes the values of despawned entities from every [EntitySparseSet] and clears their added and removed lists at
+/// the end of the frame
+pub fn entity_sparse_set_maintenance_system(world: &mut World, resources: &mut Resources) {
+    let sets = match resources.get::<EntitySparseSetMaintenance>() {
+        Some(maintenance) => maintenance.sets.clone(),
+        None => return,
+    };
+    for (_type_id, maintain) in sets {
+        maintain(world, resources);
+    }
+}
+
+pub(crate) struct InsertIntoSparseSet<T: Component> {
+    entity: Entity,
+    value: T,
+}
+
+impl<T: Component> Command for InsertIntoSparseSet<T> {
+    fn write(self: Box<Self>, _world: &mut World, resources: &mut Resources) {
+        EntitySparseSet::<T>::init(resources);
+        resources
+            .get_mut::<EntitySparseSet<T>>()
+            .unwrap()
+            .insert(self.entity, self.value);
+    }
+}
+
+pub(crate) struct RemoveFromSparseSet<T: Component> {
+    entity: Entity,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Component> Command for RemoveFromSparseSet<T> {
+    fn write(self: Box<Self>, _world: &mut World, resources: &mut Resources) {
+        if let Some(mut set) = resources.get_mut::<EntitySparseSet<T>>() {
+            set.remove(self.entity);
+        }
+    }
+}
+
+impl Commands {
+    /// Sets the value of `entity` in its [EntitySparseSet]
+    pub fn insert_into_sparse_set<T: Component>(&mut self, entity: Entity, value: T) -> &mut Self {
+        self.add_command(InsertIntoSparseSet { entity, value })
+    }
+
+    /// Removes the value of `entity` from its [EntitySparseSet], if it has one
+    pub fn remove_from_sparse_set<T: Component>(&mut self, entity: Entity) -> &mut Self {
+        self.add_command(RemoveFromSparseSet::<T> {
+            entity,
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entity_sparse_set() {
+        let mut world = World::new();
+        let a = world.spawn((0u8,));
+        let b = world.spawn((0u8,));
+        let c = world.spawn((0u8,));
+
+        let mut set = EntitySparseSet::default();
+        assert_eq!(set.insert(a, 1), None);
+        assert_eq!(set.insert(b, 2), None);
+        assert_eq!(set.insert(c, 3), None);
+        assert_eq!(set.insert(b, 4), Some(2));
+        assert_eq!(set.added(), &[a, b, c]);
+
+        assert_eq!(set.remove(a), Some(1));
+        assert_eq!(set.remove(a), None);
+        assert_eq!(set.get(b), Some(&4));
+        assert_eq!(set.get(c), Some(&3));
+        assert_eq!(set.len(), 2);
+
+        world.despawn(c).unwrap();
+        set.retain(|entity, _| world.contains(entity));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(b, &4)]);
+        assert_eq!(set.removed(), &[a, c]);
+
+        set.clear_trackers();
+        assert!(set.added().is_empty());
+    }
+
+    #[test]
+    fn insert_into_sparse_set_registers_maintenance() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        let a = world.spawn((0u8,));
+        let b = world.spawn((0u8,));
+
+        let mut commands = Commands::default();
+        commands
+            .insert_into_sparse_set(a, 1u32)
+            .insert_into_sparse_set(b, 2u32);
+        commands.apply(&mut world, &mut resources);
+        assert_eq!(
+            resources.get::<EntitySparseSet<u32>>().unwrap().added(),
+            &[a, b]
+        );
+
+        world.despawn(a).unwrap();
+        entity_sparse_set_maintenance_system(&mut world, &mut resources);
+        let set = resources.get::<EntitySparseSet<u32>>().unwrap();
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(b, &2)]);
+        assert!(set.added().is_empty());
+        assert!(set.removed().is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 mod entity_map;
+mod entity_sparse_set;
 mod world_builder;
 
 pub use entity_map::*;
+pub use entity_sparse_set::*;
 pub use world_builder::*;
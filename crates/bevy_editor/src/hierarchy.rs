@@ -0,0 +1,255 @@
+use crate::{
+    spawn_row, Editable, Editor, EditorStyle, HierarchyPanel, PanelTitle, UndoOperation, UndoStack,
+};
+use bevy_app::{EventReader, Events};
+use bevy_asset::Handle;
+use bevy_ecs::{Commands, Entity, Local, Mutated, Query, Res, ResMut, With};
+use bevy_sprite::ColorMaterial;
+use bevy_transform::prelude::{DespawnRecursiveExt, GlobalTransform, Parent, Transform};
+use bevy_ui::{widget::Text, Interaction};
+use bevy_utils::{HashMap, HashSet};
+
+/// How far each level of the hierarchy is indented
+const INDENT: f32 = 12.0;
+
+/// Moves `entity` under `parent`, or back to the root if it is `None`, without moving it in the world
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reparent {
+    pub entity: Entity,
+    pub parent: Option<Entity>,
+}
+
+/// A row of the hierarchy panel, which selects `entity` when clicked
+#[derive(Debug, Clone, Copy)]
+pub struct HierarchyRow {
+    pub entity: Entity,
+}
+
+/// The order the hierarchy panel lists `entities` in, given with their parents, and how deep each one is. Every
+/// entity is followed by its children. Entities whose parent isn't listed are shown at the root.
+pub fn hierarchy_order(entities: &[(Entity, Option<Entity>)]) -> Vec<(Entity, usize)> {
+    let listed = entities
+        .iter()
+        .map(|(entity, _parent)| *entity)
+        .collect::<HashSet<_>>();
+    let mut roots = Vec::new();
+    let mut children = HashMap::<Entity, Vec<Entity>>::default();
+    for (entity, parent) in entities.iter() {
+        match parent.filter(|parent| listed.contains(parent)) {
+            Some(parent) => children.entry(parent).or_default().push(*entity),
+            None => roots.push(*entity),
+        }
+    }
+
+    // sorted, so the rows don't move around between frames
+    roots.sort();
+    let mut order = Vec::with_capacity(entities.len());
+    let mut stack = roots
+        .into_iter()
+        .rev()
+        .map(|entity| (entity, 0))
+        .collect::<Vec<_>>();
+    while let Some((entity, depth)) = stack.pop() {
+        order.push((entity, depth));
+        if let Some(children) = children.get_mut(&entity) {
+            children.sort();
+            stack.extend(children.iter().rev().map(|child| (*child, depth + 1)));
+        }
+    }
+
+    order
+}
+
+/// The [Transform] that keeps an entity at `global` once its parent has the `parent` [GlobalTransform]
+pub fn local_transform(global: &GlobalTransform, parent: Option<&GlobalTransform>) -> Transform {
+    let matrix = match parent {
+        Some(parent) => parent.compute_matrix().inverse() * global.compute_matrix(),
+        None => global.compute_matrix(),
+    };
+    Transform::from_matrix(matrix)
+}
+
+#[derive(Default)]
+pub struct HierarchyPanelState {
+    rows: Vec<(Entity, usize, String)>,
+    nodes: Vec<Entity>,
+}
+
+/// Lists the [Editable] entities in the [HierarchyPanel] and selects the entity of a clicked row
+#[allow(clippy::too_many_arguments)]
+pub fn hierarchy_panel_system(
+    mut commands: Commands,
+    mut state: Local<HierarchyPanelState>,
+    style: Res<EditorStyle>,
+    mut editor: ResMut<Editor>,
+    mut reparent_events: ResMut<Events<Reparent>>,
+    panel_query: Query<With<HierarchyPanel, Entity>>,
+    editable_query: Query<(Entity, &Editable, Option<&Parent>)>,
+    mut click_query: Query<(&HierarchyRow, Mutated<Interaction>)>,
+    mut row_query: Query<(&HierarchyRow, &Interaction, &mut Handle<ColorMaterial>)>,
+    mut title_query: Query<With<PanelTitle, (&Parent, &mut Text)>>,
+) {
+    if !editor.enabled {
+        return;
+    }
+
+    for (row, interaction) in click_query.iter_mut() {
+        if *interaction == Interaction::Clicked {
+            editor.pick(Some(row.entity), &mut reparent_events);
+        }
+    }
+
+    let panel = match panel_query.iter().next() {
+        Some(panel) => panel,
+        None => return,
+    };
+
+    let mut labels = HashMap::default();
+    let mut entities = Vec::new();
+    for (entity, editable, parent) in editable_query.iter() {
+        labels.insert(entity, editable.label(entity));
+        entities.push((entity, parent.map(|parent| parent.0)));
+    }
+    let rows = hierarchy_order(&entities)
+        .into_iter()
+        .map(|(entity, depth)| (entity, depth, labels.remove(&entity).unwrap()))
+        .collect::<Vec<_>>();
+    if rows != state.rows {
+        for node in state.nodes.drain(..) {
+            commands.despawn_recursive(node);
+        }
+        for (entity, depth, label) in rows.iter() {
+            let (node, _text) = spawn_row(
+                &mut commands,
+                &style,
+                panel,
+                label.clone(),
+                *depth as f32 * INDENT,
+                HierarchyRow { entity: *entity },
+            );
+            state.nodes.push(node);
+        }
+        state.rows = rows;
+    }
+
+    let title = if editor.is_reparenting() {
+        "Pick the new parent"
+    } else {
+        "Hierarchy"
+    };
+    for (parent, mut text) in title_query.iter_mut() {
+        if parent.0 == panel && text.value != title {
+            text.value = title.to_string();
+        }
+    }
+
+    for (row, interaction, mut material) in row_query.iter_mut() {
+        let row_material = style.row_material(editor.selected == Some(row.entity), *interaction);
+        if *material != *row_material {
+            *material = row_material.clone();
+        }
+    }
+}
+
+/// Handles [Reparent] events and records them in the [UndoStack]. An entity can't become the child of one of its
+/// descendants.
+pub fn reparent_system(
+    mut commands: Commands,
+    mut reparent_event_reader: Local<EventReader<Reparent>>,
+    reparent_events: Res<Events<Reparent>>,
+    mut undo_stack: ResMut<UndoStack>,
+    parent_query: Query<&Parent>,
+    global_transform_query: Query<&GlobalTransform>,
+    mut transform_query: Query<&mut Transform>,
+) {
+    for event in reparent_event_reader.iter(&reparent_events) {
+        let global_transform = match global_transform_query.get(event.entity) {
+            Ok(global_transform) => *global_transform,
+            Err(_) => continue,
+        };
+        let parent_transform = match event.parent {
+            Some(parent) => {
+                let mut ancestor = Some(parent);
+                while let Some(entity) = ancestor {
+                    if entity == event.entity {
+                        break;
+                    }
+                    ancestor = parent_query.get(entity).ok().map(|parent| parent.0);
+                }
+                if ancestor.is_some() {
+                    log::warn!(
+                        "{:?} can't become a child of its descendant {:?}",
+                        event.entity,
+                        parent
+                    );
+                    continue;
+                }
+
+                match global_transform_query.get(parent) {
+                    Ok(parent_transform) => Some(*parent_transform),
+                    Err(_) => continue,
+                }
+            }
+            None => None,
+        };
+
+        let previous_parent = parent_query.get(event.entity).ok().cloned();
+        if previous_parent.map(|parent| parent.0) == event.parent {
+            continue;
+        }
+
+        undo_stack.begin("Reparent");
+        if let Ok(mut transform) = transform_query.get_mut(event.entity) {
+            let before = *transform;
+            *transform = local_transform(&global_transform, parent_transform.as_ref());
+            undo_stack.push(UndoOperation::set_component(
+                event.entity,
+                Some(&before),
+                Some(&*transform),
+            ));
+        }
+        let parent = event.parent.map(Parent);
+        undo_stack.push(UndoOperation::set_component(
+            event.entity,
+            previous_parent.as_ref(),
+            parent.as_ref(),
+        ));
+        undo_stack.end();
+
+        match parent {
+            Some(parent) => commands.insert_one(event.entity, parent),
+            None => commands.remove_one::<Parent>(event.entity),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::Vec3;
+
+    #[test]
+    fn hierarchy_rows() {
+        let a = Entity::new(1);
+        let b = Entity::new(2);
+        let c = Entity::new(3);
+        let d = Entity::new(4);
+        let hidden = Entity::new(5);
+        let order = hierarchy_order(&[(d, Some(hidden)), (c, Some(a)), (b, None), (a, None)]);
+        assert_eq!(order, vec![(a, 0), (c, 1), (b, 0), (d, 0)]);
+    }
+
+    #[test]
+    fn reparent_keeps_world_transform() {
+        let parent = GlobalTransform {
+            translation: Vec3::new(1.0, 0.0, 0.0),
+            scale: Vec3::splat(2.0),
+            ..Default::default()
+        };
+        let child = GlobalTransform::from_translation(Vec3::new(3.0, 0.0, 0.0));
+        let local = local_transform(&child, Some(&parent));
+        assert!((local.translation - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-5);
+        assert!((local.scale - Vec3::splat(0.5)).length() < 1e-5);
+        assert!((parent.mul_transform(local).translation - child.translation).length() < 1e-5);
+    }
+}
@@ -0,0 +1,498 @@
+use crate::{
+    shift_pressed, spawn_row, Editor, EditorStyle, InspectorPanel, UndoOperation, UndoStack,
+};
+use bevy_asset::Handle;
+use bevy_ecs::{
+    Commands, Component, Entity, Local, Mutated, Query, Res, ResMut, Resources, With, World,
+};
+use bevy_input::{keyboard::KeyCode, Input};
+use bevy_math::{Quat, Vec2, Vec3};
+use bevy_property::{Properties, Property};
+use bevy_render::{
+    camera::VisibleEntities, draw::Draw, pipeline::RenderPipelines, render_graph::base::MainPass,
+};
+use bevy_sprite::ColorMaterial;
+use bevy_transform::prelude::{Children, DespawnRecursiveExt, GlobalTransform};
+use bevy_type_registry::{ComponentRegistration, ComponentRegistry, TypeRegistry};
+use bevy_ui::{widget::Text, Interaction};
+use bevy_utils::HashSet;
+use std::any::TypeId;
+
+/// How far fields are indented under their component
+const INDENT: f32 = 12.0;
+
+const AXIS_NAMES: [&str; 3] = ["x", "y", "z"];
+
+/// A field of a component of the inspected entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectorField {
+    pub component: TypeId,
+    pub name: String,
+    /// The element of a vector field
+    pub axis: Option<usize>,
+}
+
+/// A line of the inspector panel: the name of a component, or one of its fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectorRow {
+    pub label: String,
+    pub value: String,
+    /// The field the row edits, if it can be edited
+    pub field: Option<InspectorField>,
+}
+
+impl InspectorRow {
+    fn header(label: String) -> Self {
+        InspectorRow {
+            label,
+            value: String::new(),
+            field: None,
+        }
+    }
+
+    pub fn is_header(&self) -> bool {
+        self.value.is_empty() && self.field.is_none()
+    }
+
+    fn text(&self) -> String {
+        if self.is_header() {
+            self.label.clone()
+        } else {
+            format!("{}: {}", self.label, self.value)
+        }
+    }
+}
+
+/// A change to a field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldStep {
+    /// Adds to a number
+    Add(f32),
+    /// Flips a bool
+    Toggle,
+}
+
+/// Lists the registered components of the selected entity, and edits their fields
+#[derive(Debug)]
+pub struct Inspector {
+    /// How much Left and Right change a number, or ten times as much while Shift is held
+    pub step: f32,
+    hidden: HashSet<TypeId>,
+    entity: Option<Entity>,
+    rows: Vec<InspectorRow>,
+    focused: Option<InspectorField>,
+    edits: Vec<(InspectorField, FieldStep)>,
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        let mut inspector = Inspector {
+            step: 0.1,
+            hidden: Default::default(),
+            entity: None,
+            rows: Vec::new(),
+            focused: None,
+            edits: Vec::new(),
+        };
+        // derived from other components, or only interesting to the renderer
+        inspector.hide::<GlobalTransform>();
+        inspector.hide::<Children>();
+        inspector.hide::<Draw>();
+        inspector.hide::<RenderPipelines>();
+        inspector.hide::<MainPass>();
+        inspector.hide::<VisibleEntities>();
+        inspector
+    }
+}
+
+impl Inspector {
+    /// Leaves `T` out of the inspector
+    pub fn hide<T: Component>(&mut self) {
+        self.hidden.insert(TypeId::of::<T>());
+    }
+
+    pub fn rows(&self) -> &[InspectorRow] {
+        &self.rows
+    }
+
+    /// The field Left, Right and Space change
+    pub fn focused(&self) -> Option<&InspectorField> {
+        self.focused.as_ref()
+    }
+
+    /// Changes a field of the inspected entity the next time the inspector updates
+    pub fn edit(&mut self, field: InspectorField, step: FieldStep) {
+        self.edits.push((field, step));
+    }
+}
+
+/// Adds the rows that show `property`, the field `name` of `component`
+pub fn field_rows(
+    component: TypeId,
+    name: &str,
+    property: &dyn Property,
+    rows: &mut Vec<InspectorRow>,
+) {
+    let any = property.any();
+    let field = |axis| {
+        Some(InspectorField {
+            component,
+            name: name.to_string(),
+            axis,
+        })
+    };
+    if let Some(value) = any.downcast_ref::<f32>() {
+        rows.push(InspectorRow {
+            label: name.to_string(),
+            value: format!("{:.3}", value),
+            field: field(None),
+        });
+    } else if let Some(value) = any.downcast_ref::<bool>() {
+        rows.push(InspectorRow {
+            label: name.to_string(),
+            value: value.to_string(),
+            field: field(None),
+        });
+    } else if let Some(value) = any.downcast_ref::<Vec3>() {
+        let elements: [f32; 3] = (*value).into();
+        for (axis, element) in elements.iter().enumerate() {
+            rows.push(InspectorRow {
+                label: format!("{}.{}", name, AXIS_NAMES[axis]),
+                value: format!("{:.3}", element),
+                field: field(Some(axis)),
+            });
+        }
+    } else if let Some(value) = any.downcast_ref::<Vec2>() {
+        let elements: [f32; 2] = (*value).into();
+        for (axis, element) in elements.iter().enumerate() {
+            rows.push(InspectorRow {
+                label: format!("{}.{}", name, AXIS_NAMES[axis]),
+                value: format!("{:.3}", element),
+                field: field(Some(axis)),
+            });
+        }
+    } else {
+        let value = if let Some(value) = any.downcast_ref::<Quat>() {
+            let [x, y, z, w]: [f32; 4] = (*value).into();
+            format!("[{:.3}, {:.3}, {:.3}, {:.3}]", x, y, z, w)
+        } else if let Some(value) = any.downcast_ref::<String>() {
+            format!("{:?}", value)
+        } else if let Some(value) = any.downcast_ref::<Entity>() {
+            format!("Entity {}", value.id())
+        } else {
+            // shown, but can't be edited
+            property
+                .type_name()
+                .rsplit("::")
+                .next()
+                .unwrap()
+                .to_string()
+        };
+        rows.push(InspectorRow {
+            label: name.to_string(),
+            value,
+            field: None,
+        });
+    }
+}
+
+/// Applies `step` to `property`, or to its element `axis`. Returns whether the property changed.
+pub fn step_property(property: &mut dyn Property, axis: Option<usize>, step: FieldStep) -> bool {
+    let any = property.any_mut();
+    match (step, axis) {
+        (FieldStep::Add(amount), None) => {
+            if let Some(value) = any.downcast_mut::<f32>() {
+                *value += amount;
+                return true;
+            }
+        }
+        (FieldStep::Add(amount), Some(axis)) => {
+            if let Some(value) = any.downcast_mut::<Vec3>() {
+                let mut elements: [f32; 3] = (*value).into();
+                if let Some(element) = elements.get_mut(axis) {
+                    *element += amount;
+                    *value = elements.into();
+                    return true;
+                }
+            } else if let Some(value) = any.downcast_mut::<Vec2>() {
+                let mut elements: [f32; 2] = (*value).into();
+                if let Some(element) = elements.get_mut(axis) {
+                    *element += amount;
+                    *value = elements.into();
+                    return true;
+                }
+            }
+        }
+        (FieldStep::Toggle, None) => {
+            if let Some(value) = any.downcast_mut::<bool>() {
+                *value = !*value;
+                return true;
+            }
+        }
+        (FieldStep::Toggle, Some(_)) => {}
+    }
+
+    false
+}
+
+/// The properties of the `registration` component of `entity`
+fn component_properties<'a>(
+    world: &'a World,
+    registration: &ComponentRegistration,
+    entity: Entity,
+) -> Option<&'a dyn Properties> {
+    let location = world.get_entity_location(entity)?;
+    let archetype = world.archetypes().nth(location.archetype as usize)?;
+    if archetype.has_type(registration.ty) {
+        Some(registration.get_component_properties(archetype, location.index))
+    } else {
+        None
+    }
+}
+
+fn inspect_entity(
+    world: &World,
+    component_registry: &ComponentRegistry,
+    hidden: &HashSet<TypeId>,
+    entity: Entity,
+) -> Vec<InspectorRow> {
+    let mut rows = Vec::new();
+    let location = match world.get_entity_location(entity) {
+        Some(location) => location,
+        None => return rows,
+    };
+    let archetype = world.archetypes().nth(location.archetype as usize).unwrap();
+    let mut registrations = archetype
+        .types()
+        .iter()
+        .filter(|type_info| !hidden.contains(&type_info.id()))
+        .filter_map(|type_info| component_registry.get(&type_info.id()))
+        .collect::<Vec<_>>();
+    registrations.sort_by(|a, b| a.short_name.cmp(&b.short_name));
+
+    for registration in registrations {
+        rows.push(InspectorRow::header(registration.short_name.clone()));
+        let properties = registration.get_component_properties(archetype, location.index);
+        for (index, property) in properties.iter_props().enumerate() {
+            let name = properties
+                .prop_name(index)
+                .map_or_else(|| index.to_string(), |name| name.to_string());
+            field_rows(registration.ty, &name, property, &mut rows);
+        }
+    }
+
+    rows
+}
+
+/// Applies the [Inspector] edits to the selected entity and records them in the [UndoStack], then lists its
+/// components again. Needs the whole world because components are edited through their registered properties.
+pub fn inspector_system(world: &mut World, resources: &mut Resources) {
+    let editor = resources.get::<Editor>().unwrap();
+    let mut inspector = resources.get_mut::<Inspector>().unwrap();
+    let mut undo_stack = resources.get_mut::<UndoStack>().unwrap();
+    let type_registry = resources.get::<TypeRegistry>().unwrap();
+    let component_registry = type_registry.component.read();
+    // borrows the fields separately
+    let inspector = &mut *inspector;
+
+    let entity = editor.selected.filter(|_| editor.enabled);
+    if entity != inspector.entity {
+        inspector.entity = entity;
+        inspector.focused = None;
+        inspector.edits.clear();
+    }
+    let entity = match entity {
+        Some(entity) => entity,
+        None => {
+            inspector.rows.clear();
+            return;
+        }
+    };
+
+    for (field, step) in inspector.edits.drain(..) {
+        let registration = match component_registry.get(&field.component) {
+            Some(registration) => registration,
+            None => continue,
+        };
+        let before = match component_properties(world, registration, entity) {
+            Some(properties) => properties.to_dynamic(),
+            None => continue,
+        };
+        let mut after = before.to_dynamic();
+        let changed = after
+            .prop_mut(&field.name)
+            .map_or(false, |property| step_property(property, field.axis, step));
+        if changed {
+            registration.apply_property_to_entity(world, entity, &after);
+            undo_stack.push(UndoOperation::SetComponent {
+                entity,
+                component: field.component,
+                before: Some(before),
+                after: Some(after),
+            });
+        }
+    }
+
+    inspector.rows = inspect_entity(world, &component_registry, &inspector.hidden, entity);
+}
+
+/// A row of the inspector panel, which shows the [InspectorRow] at `index`
+#[derive(Debug, Clone, Copy)]
+pub struct InspectorRowNode {
+    pub index: usize,
+}
+
+#[derive(Default)]
+pub struct InspectorPanelState {
+    labels: Vec<String>,
+    /// The rows and their text
+    nodes: Vec<(Entity, Entity)>,
+}
+
+/// Shows the [Inspector] rows in the [InspectorPanel], focuses clicked fields and steps the focused field
+#[allow(clippy::too_many_arguments)]
+pub fn inspector_panel_system(
+    mut commands: Commands,
+    mut state: Local<InspectorPanelState>,
+    style: Res<EditorStyle>,
+    editor: Res<Editor>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut inspector: ResMut<Inspector>,
+    panel_query: Query<With<InspectorPanel, Entity>>,
+    mut click_query: Query<(&InspectorRowNode, Mutated<Interaction>)>,
+    mut row_query: Query<(&InspectorRowNode, &Interaction, &mut Handle<ColorMaterial>)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !editor.enabled {
+        return;
+    }
+    let inspector = &mut *inspector;
+
+    for (node, interaction) in click_query.iter_mut() {
+        if *interaction == Interaction::Clicked {
+            if let Some(row) = inspector.rows.get(node.index) {
+                if row.field.is_some() {
+                    inspector.focused = row.field.clone();
+                }
+            }
+        }
+    }
+
+    if let Some(field) = inspector.focused.clone() {
+        let amount = if shift_pressed(&keyboard_input) {
+            inspector.step * 10.0
+        } else {
+            inspector.step
+        };
+        if keyboard_input.just_pressed(KeyCode::Right) {
+            inspector.edit(field.clone(), FieldStep::Add(amount));
+        }
+        if keyboard_input.just_pressed(KeyCode::Left) {
+            inspector.edit(field.clone(), FieldStep::Add(-amount));
+        }
+        if keyboard_input.just_pressed(KeyCode::Space) {
+            inspector.edit(field, FieldStep::Toggle);
+        }
+    }
+
+    let panel = match panel_query.iter().next() {
+        Some(panel) => panel,
+        None => return,
+    };
+
+    let labels = inspector
+        .rows
+        .iter()
+        .map(|row| row.label.clone())
+        .collect::<Vec<_>>();
+    if labels != state.labels {
+        for (node, _text) in state.nodes.drain(..) {
+            commands.despawn_recursive(node);
+        }
+        for (index, row) in inspector.rows.iter().enumerate() {
+            let indent = if row.is_header() { 0.0 } else { INDENT };
+            let node = spawn_row(
+                &mut commands,
+                &style,
+                panel,
+                row.text(),
+                indent,
+                InspectorRowNode { index },
+            );
+            state.nodes.push(node);
+        }
+        state.labels = labels;
+    } else {
+        for (row, (_node, text_entity)) in inspector.rows.iter().zip(state.nodes.iter()) {
+            if let Ok(mut text) = text_query.get_mut(*text_entity) {
+                let value = row.text();
+                if text.value != value {
+                    text.value = value;
+                }
+            }
+        }
+    }
+
+    for (node, interaction, mut material) in row_query.iter_mut() {
+        let focused = inspector.rows.get(node.index).map_or(false, |row| {
+            row.field.is_some() && row.field.as_ref() == inspector.focused.as_ref()
+        });
+        let row_material = style.row_material(focused, *interaction);
+        if *material != *row_material {
+            *material = row_material.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_transform::prelude::Transform;
+
+    #[test]
+    fn edit_transform_fields() {
+        let transform = Transform::from_translation(Vec3::new(1.0, 2.0, 3.0));
+        let component = TypeId::of::<Transform>();
+        let mut rows = Vec::new();
+        for (index, property) in transform.iter_props().enumerate() {
+            field_rows(
+                component,
+                transform.prop_name(index).unwrap(),
+                property,
+                &mut rows,
+            );
+        }
+        let labels = rows
+            .iter()
+            .map(|row| row.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec![
+                "translation.x",
+                "translation.y",
+                "translation.z",
+                "rotation",
+                "scale.x",
+                "scale.y",
+                "scale.z"
+            ]
+        );
+        assert_eq!(rows[1].value, "2.000");
+        assert!(rows[3].field.is_none());
+
+        let field = rows[1].field.clone().unwrap();
+        let mut properties = transform.to_dynamic();
+        let property = properties.prop_mut(&field.name).unwrap();
+        assert!(step_property(
+            &mut *property,
+            field.axis,
+            FieldStep::Add(0.5)
+        ));
+        assert!(!step_property(property, field.axis, FieldStep::Toggle));
+
+        let mut edited = transform;
+        edited.apply(&properties);
+        assert_eq!(edited.translation, Vec3::new(1.0, 2.5, 3.0));
+    }
+}
@@ -0,0 +1,258 @@
+//! A level editor that runs inside the game.
+//!
+//! [EditorPlugin] adds an editor mode that [Editor::toggle_key] turns on and off. While it is on, clicking an
+//! [Editable] entity in the viewport or in the hierarchy panel selects it and shows [Gizmo] handles on it, the
+//! inspector panel lists and edits its components, and Ctrl+S saves every [Editable] entity to [Editor::save_path] as
+//! a `.scn` scene. Only components registered with the [TypeRegistry](bevy_type_registry::TypeRegistry) are shown and
+//! saved, so register the game's own components to edit them.
+//!
+//! Other controls while the editor is on:
+//! - 1, 2 and 3 switch between translate, rotate and scale handles, and L toggles local axes
+//! - P makes the next clicked entity the parent of the selected one, Shift+P moves it back to the root, and Escape
+//!   cancels reparenting or clears the selection
+//! - clicking an inspector field focuses it, Left and Right step it (hold Shift for bigger steps) and Space toggles it
+//! - Delete despawns the selected entity and its children
+//! - Ctrl+Z undoes the last change, and Ctrl+Y or Ctrl+Shift+Z redoes it
+//!
+//! Every change made in the editor is recorded in the [UndoStack], which other tools can record their changes in too.
+//!
+//! The panels are ui nodes, so the app needs a [UiCameraComponents](bevy_ui::entity::UiCameraComponents).
+
+mod hierarchy;
+mod inspector;
+mod panel;
+mod picking;
+mod save;
+mod undo;
+
+pub use hierarchy::*;
+pub use inspector::*;
+pub use panel::*;
+pub use picking::*;
+pub use save::*;
+pub use undo::*;
+
+pub mod prelude {
+    pub use crate::{Editable, Editor, EditorPlugin, UndoOperation, UndoStack};
+}
+
+use bevy_app::prelude::*;
+use bevy_asset::AssetServer;
+use bevy_ecs::{Entity, IntoQuerySystem, IntoThreadLocalSystem, Query, Res, ResMut, With};
+use bevy_input::{keyboard::KeyCode, Input};
+use bevy_pbr::gizmo::{Gizmo, GizmoMode, GizmoPlugin};
+use bevy_property::Properties;
+use bevy_type_registry::RegisterType;
+use std::path::PathBuf;
+
+/// Marks an entity as part of the level: it can be selected in the editor and is saved with the level
+#[derive(Debug, Default, Clone, Properties)]
+pub struct Editable {
+    /// The name shown in the hierarchy panel
+    pub name: String,
+}
+
+impl Editable {
+    pub fn new(name: impl Into<String>) -> Self {
+        Editable { name: name.into() }
+    }
+
+    /// The name shown for `entity`, which falls back to its id
+    pub fn label(&self, entity: Entity) -> String {
+        if self.name.is_empty() {
+            format!("Entity {}", entity.id())
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// The state of the editor mode
+#[derive(Debug, Clone)]
+pub struct Editor {
+    pub enabled: bool,
+    pub toggle_key: KeyCode,
+    pub selected: Option<Entity>,
+    /// Where Ctrl+S saves the level, relative to the asset folder
+    pub save_path: PathBuf,
+    reparenting: bool,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Editor {
+            enabled: false,
+            toggle_key: KeyCode::F1,
+            selected: None,
+            save_path: PathBuf::from("scenes/level.scn"),
+            reparenting: false,
+        }
+    }
+}
+
+impl Editor {
+    /// Whether the next picked entity becomes the parent of the selected one
+    pub fn is_reparenting(&self) -> bool {
+        self.reparenting
+    }
+
+    /// Selects `entity`, or makes it the parent of the selected entity while reparenting
+    pub fn pick(&mut self, entity: Option<Entity>, reparent_events: &mut Events<Reparent>) {
+        if !self.reparenting {
+            self.selected = entity;
+            return;
+        }
+
+        self.reparenting = false;
+        if let (Some(selected), Some(parent)) = (self.selected, entity) {
+            reparent_events.send(Reparent {
+                entity: selected,
+                parent: Some(parent),
+            });
+        }
+    }
+}
+
+/// Adds the editor mode. Also adds a [GizmoPlugin] if the app doesn't have one yet.
+pub struct EditorPlugin {
+    /// The font of the panels, relative to the asset folder
+    pub font: &'static str,
+}
+
+impl Default for EditorPlugin {
+    fn default() -> Self {
+        EditorPlugin {
+            font: "fonts/FiraMono-Medium.ttf",
+        }
+    }
+}
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if app.resources().get::<Gizmo>().is_none() {
+            app.add_plugin(GizmoPlugin::default());
+        }
+
+        let font = app
+            .resources()
+            .get::<AssetServer>()
+            .unwrap()
+            .load(self.font);
+        let style = EditorStyle::new(app.resources(), font);
+        app.add_resource(style)
+            .init_resource::<Editor>()
+            .init_resource::<Inspector>()
+            .init_resource::<UndoStack>()
+            .add_event::<Reparent>()
+            .register_component::<Editable>()
+            .add_startup_system(setup_editor_panels.system())
+            .add_system(editor_input_system.system())
+            .add_system(picking_system.system())
+            .add_system(reparent_system.system())
+            .add_system(hierarchy_panel_system.system())
+            .add_system(inspector_panel_system.system())
+            .add_system(inspector_system.thread_local_system())
+            .add_system(save_level_system.thread_local_system())
+            .add_system(panel_visibility_system.system())
+            .add_system(gizmo_undo_system.system())
+            .add_system_to_stage(stage::LAST, undo_system.thread_local_system());
+    }
+}
+
+fn shift_pressed(keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift)
+}
+
+fn control_pressed(keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl)
+}
+
+/// Toggles the editor, handles its shortcuts and shows the gizmo on the selected entity
+pub fn editor_input_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut editor: ResMut<Editor>,
+    mut gizmo: ResMut<Gizmo>,
+    mut undo_stack: ResMut<UndoStack>,
+    mut reparent_events: ResMut<Events<Reparent>>,
+    editable_query: Query<With<Editable, Entity>>,
+) {
+    if keyboard_input.just_pressed(editor.toggle_key) {
+        editor.enabled = !editor.enabled;
+    }
+
+    // forgets entities that were despawned
+    if let Some(selected) = editor.selected {
+        if editable_query.get(selected).is_err() {
+            editor.selected = None;
+        }
+    }
+
+    if !editor.enabled {
+        editor.reparenting = false;
+        if gizmo.selected.is_some() {
+            gizmo.selected = None;
+        }
+        return;
+    }
+
+    if !gizmo.is_dragging() {
+        for (key, mode) in [
+            (KeyCode::Key1, GizmoMode::Translate),
+            (KeyCode::Key2, GizmoMode::Rotate),
+            (KeyCode::Key3, GizmoMode::Scale),
+        ]
+        .iter()
+        {
+            if keyboard_input.just_pressed(*key) {
+                gizmo.mode = *mode;
+            }
+        }
+
+        if keyboard_input.just_pressed(KeyCode::L) {
+            gizmo.local = !gizmo.local;
+        }
+
+        if keyboard_input.just_pressed(KeyCode::P) {
+            if let Some(selected) = editor.selected {
+                if shift_pressed(&keyboard_input) {
+                    editor.reparenting = false;
+                    reparent_events.send(Reparent {
+                        entity: selected,
+                        parent: None,
+                    });
+                } else {
+                    editor.reparenting = true;
+                }
+            }
+        }
+
+        if keyboard_input.just_pressed(KeyCode::Delete) {
+            if let Some(selected) = editor.selected.take() {
+                editor.reparenting = false;
+                undo_stack.despawn(selected);
+            }
+        }
+
+        if control_pressed(&keyboard_input) {
+            let redo = keyboard_input.just_pressed(KeyCode::Y)
+                || (keyboard_input.just_pressed(KeyCode::Z) && shift_pressed(&keyboard_input));
+            if redo {
+                undo_stack.redo();
+            } else if keyboard_input.just_pressed(KeyCode::Z) {
+                undo_stack.undo();
+            }
+        }
+
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            if editor.reparenting {
+                editor.reparenting = false;
+            } else {
+                editor.selected = None;
+            }
+        }
+    }
+
+    if gizmo.selected != editor.selected {
+        gizmo.selected = editor.selected;
+    }
+}
@@ -0,0 +1,199 @@
+use crate::Editor;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Component, Entity, Query, Res, Resources, With};
+use bevy_math::{Rect, Size};
+use bevy_render::prelude::Color;
+use bevy_sprite::ColorMaterial;
+use bevy_text::{Font, TextStyle};
+use bevy_transform::prelude::BuildChildren;
+use bevy_ui::{
+    entity::{ButtonComponents, NodeComponents, TextComponents},
+    widget::Text,
+    AlignItems, Display, FlexDirection, Interaction, JustifyContent, PositionType, Style, Val,
+};
+
+/// The width of the hierarchy and inspector panels
+const PANEL_WIDTH: f32 = 300.0;
+
+const ROW_HEIGHT: f32 = 20.0;
+
+const FONT_SIZE: f32 = 16.0;
+
+/// The font and materials of the editor panels
+pub struct EditorStyle {
+    pub font: Handle<Font>,
+    pub panel: Handle<ColorMaterial>,
+    pub row: Handle<ColorMaterial>,
+    pub hovered: Handle<ColorMaterial>,
+    pub selected: Handle<ColorMaterial>,
+}
+
+impl EditorStyle {
+    pub fn new(resources: &Resources, font: Handle<Font>) -> Self {
+        let mut materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
+        EditorStyle {
+            font,
+            panel: materials.add(Color::rgba(0.1, 0.1, 0.1, 0.85).into()),
+            row: materials.add(Color::NONE.into()),
+            hovered: materials.add(Color::rgba(0.3, 0.3, 0.3, 0.85).into()),
+            selected: materials.add(Color::rgb(0.2, 0.35, 0.55).into()),
+        }
+    }
+
+    /// The material of a row that is `selected` and has `interaction`
+    pub fn row_material(&self, selected: bool, interaction: Interaction) -> &Handle<ColorMaterial> {
+        if selected {
+            &self.selected
+        } else if interaction == Interaction::None {
+            &self.row
+        } else {
+            &self.hovered
+        }
+    }
+
+    fn text(&self, value: String) -> Text {
+        Text {
+            value,
+            font: self.font.clone(),
+            style: TextStyle {
+                font_size: FONT_SIZE,
+                color: Color::WHITE,
+            },
+        }
+    }
+}
+
+/// Marks the panels, which are only shown while the editor is on
+#[derive(Debug, Default)]
+pub struct EditorPanel;
+
+/// Lists the [Editable](crate::Editable) entities
+#[derive(Debug, Default)]
+pub struct HierarchyPanel;
+
+/// Lists the components of the selected entity
+#[derive(Debug, Default)]
+pub struct InspectorPanel;
+
+/// The text at the top of a panel
+#[derive(Debug, Default)]
+pub struct PanelTitle;
+
+/// Spawns a full height panel along the left or right edge of the window, which starts with a [PanelTitle]
+pub(crate) fn spawn_panel(
+    commands: &mut Commands,
+    style: &EditorStyle,
+    left: bool,
+    title: &str,
+    marker: impl Component,
+) -> Entity {
+    let position = if left {
+        Rect {
+            left: Val::Px(0.0),
+            bottom: Val::Px(0.0),
+            ..Default::default()
+        }
+    } else {
+        Rect {
+            right: Val::Px(0.0),
+            bottom: Val::Px(0.0),
+            ..Default::default()
+        }
+    };
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                position,
+                size: Size::new(Val::Px(PANEL_WIDTH), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::FlexStart,
+                padding: Rect::all(Val::Px(4.0)),
+                ..Default::default()
+            },
+            material: style.panel.clone(),
+            ..Default::default()
+        })
+        // keeps clicks on the panel from selecting what is behind it
+        .with(Interaction::default())
+        .with(EditorPanel)
+        .with(marker)
+        .with_children(|parent| {
+            parent
+                .spawn(TextComponents {
+                    style: Style {
+                        margin: Rect {
+                            bottom: Val::Px(4.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    text: style.text(title.to_string()),
+                    ..Default::default()
+                })
+                .with(PanelTitle);
+        });
+    commands.current_entity().unwrap()
+}
+
+/// Adds a clickable row with `value` to the end of `panel`. Returns the row and its text.
+pub(crate) fn spawn_row(
+    commands: &mut Commands,
+    style: &EditorStyle,
+    panel: Entity,
+    value: String,
+    indent: f32,
+    marker: impl Component,
+) -> (Entity, Entity) {
+    let text = commands
+        .spawn(TextComponents {
+            text: style.text(value),
+            ..Default::default()
+        })
+        .current_entity()
+        .unwrap();
+    let row = commands
+        .spawn(ButtonComponents {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Px(ROW_HEIGHT)),
+                flex_shrink: 0.0,
+                align_items: AlignItems::Center,
+                padding: Rect {
+                    left: Val::Px(4.0 + indent),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            material: style.row.clone(),
+            ..Default::default()
+        })
+        .with(marker)
+        .current_entity()
+        .unwrap();
+    commands.push_children(row, &[text]);
+    commands.push_children(panel, &[row]);
+    (row, text)
+}
+
+pub fn setup_editor_panels(mut commands: Commands, style: Res<EditorStyle>) {
+    spawn_panel(&mut commands, &style, true, "Hierarchy", HierarchyPanel);
+    spawn_panel(&mut commands, &style, false, "Inspector", InspectorPanel);
+}
+
+/// Shows the panels while the editor is on
+pub fn panel_visibility_system(
+    editor: Res<Editor>,
+    mut query: Query<With<EditorPanel, &mut Style>>,
+) {
+    let display = if editor.enabled {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    for mut style in query.iter_mut() {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
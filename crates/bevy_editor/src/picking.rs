@@ -0,0 +1,158 @@
+use crate::{Editable, Editor, Reparent};
+use bevy_app::{EventReader, Events};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Entity, Local, Query, Res, ResMut, With};
+use bevy_input::{mouse::MouseButton, Input};
+use bevy_math::{Vec2, Vec3};
+use bevy_pbr::gizmo::Gizmo;
+use bevy_render::{
+    camera::{ActiveCameras, Camera, Ray},
+    mesh::{Mesh, VertexAttributeValues},
+    render_graph::base,
+};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_ui::Interaction;
+use bevy_window::{CursorMoved, Windows};
+
+/// The radius entities without a mesh, like lights, are picked within
+const DEFAULT_PICK_RADIUS: f32 = 0.25;
+
+/// The center and radius of a sphere around all vertices of `mesh`
+pub fn mesh_bounding_sphere(mesh: &Mesh) -> Option<(Vec3, f32)> {
+    let positions = match mesh.attributes.get(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float3(positions)) if !positions.is_empty() => positions,
+        _ => return None,
+    };
+
+    let (min, max) = positions.iter().fold(
+        (Vec3::splat(std::f32::MAX), Vec3::splat(std::f32::MIN)),
+        |(min, max), position| {
+            let position = Vec3::from(*position);
+            (min.min(position), max.max(position))
+        },
+    );
+    let center = (min + max) / 2.0;
+    let radius = positions
+        .iter()
+        .map(|position| (Vec3::from(*position) - center).length())
+        .fold(0.0, f32::max);
+    Some((center, radius))
+}
+
+/// The closest of `spheres` the ray hits, given as an entity, a world space center and a radius
+pub fn pick_sphere(
+    ray: &Ray,
+    spheres: impl Iterator<Item = (Entity, Vec3, f32)>,
+) -> Option<Entity> {
+    spheres
+        .filter_map(|(entity, center, radius)| {
+            ray.intersect_sphere(center, radius)
+                .map(|distance| (entity, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(entity, _)| entity)
+}
+
+#[derive(Default)]
+pub struct PickingState {
+    cursor_moved_event_reader: EventReader<CursorMoved>,
+    cursor_position: Option<Vec2>,
+}
+
+/// Picks the [Editable] entity under the cursor when the left mouse button is pressed in the viewport. Entities are
+/// picked by the bounding sphere of their mesh, which is precise enough to tell apart the props of a level.
+#[allow(clippy::too_many_arguments)]
+pub fn picking_system(
+    mut state: Local<PickingState>,
+    cursor_moved_events: Res<Events<CursorMoved>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    active_cameras: Res<ActiveCameras>,
+    meshes: Res<Assets<Mesh>>,
+    gizmo: Res<Gizmo>,
+    mut editor: ResMut<Editor>,
+    mut reparent_events: ResMut<Events<Reparent>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    editable_query: Query<With<Editable, (Entity, &GlobalTransform, Option<&Handle<Mesh>>)>>,
+    interaction_query: Query<&Interaction>,
+) {
+    if let Some(event) = state.cursor_moved_event_reader.latest(&cursor_moved_events) {
+        state.cursor_position = Some(event.position);
+    }
+
+    if !editor.enabled || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    // the click belongs to a gizmo handle or to the ui
+    if gizmo.is_dragging()
+        || gizmo.hovered().is_some()
+        || interaction_query
+            .iter()
+            .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+
+    let ray = match (
+        state.cursor_position,
+        active_cameras
+            .get(base::camera::CAMERA3D)
+            .and_then(|entity| camera_query.get(entity).ok()),
+    ) {
+        (Some(cursor_position), Some((camera, camera_transform))) => {
+            camera.screen_to_ray(&windows, camera_transform, cursor_position)
+        }
+        _ => None,
+    };
+    let ray = match ray {
+        Some(ray) => ray,
+        None => return,
+    };
+
+    let spheres = editable_query
+        .iter()
+        .map(|(entity, global_transform, mesh)| {
+            let (center, radius) = mesh
+                .and_then(|mesh| meshes.get(mesh))
+                .and_then(mesh_bounding_sphere)
+                .unwrap_or((Vec3::zero(), DEFAULT_PICK_RADIUS));
+            let scale = global_transform.scale.abs();
+            let scale = scale.x().max(scale.y()).max(scale.z());
+            (entity, global_transform.mul_vec3(center), radius * scale)
+        });
+    let picked = pick_sphere(&ray, spheres);
+    editor.pick(picked, &mut reparent_events);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_render::mesh::shape;
+
+    #[test]
+    fn pick_closest_sphere() {
+        let cube = Mesh::from(shape::Cube { size: 1.0 });
+        let (center, radius) = mesh_bounding_sphere(&cube).unwrap();
+        assert!(center.length() < 1e-5);
+        assert!((radius - 3.0f32.sqrt()).abs() < 1e-5);
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 10.0), -Vec3::unit_z());
+        let near = Entity::new(1);
+        let far = Entity::new(2);
+        let missed = Entity::new(3);
+        let spheres = vec![
+            (far, Vec3::zero(), 1.0),
+            (near, Vec3::new(0.0, 0.0, 5.0), 1.0),
+            (missed, Vec3::new(5.0, 0.0, 8.0), 1.0),
+        ];
+        assert_eq!(pick_sphere(&ray, spheres.into_iter()), Some(near));
+        assert_eq!(
+            pick_sphere(
+                &ray,
+                vec![(missed, Vec3::new(5.0, 0.0, 8.0), 1.0)].into_iter()
+            ),
+            None
+        );
+    }
+}
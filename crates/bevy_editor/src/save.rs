@@ -0,0 +1,164 @@
+use crate::{control_pressed, Editable, Editor};
+use bevy_asset::AssetServerSettings;
+use bevy_ecs::{Resources, World};
+use bevy_input::{keyboard::KeyCode, Input};
+use bevy_property::PropertyTypeRegistry;
+use bevy_scene::DynamicScene;
+use bevy_transform::prelude::{Children, Parent};
+use bevy_type_registry::{ComponentRegistry, TypeRegistry};
+use bevy_utils::HashSet;
+use std::{any::TypeId, path::Path};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SaveLevelError {
+    #[error("Failed to serialize the level.")]
+    Serialize(#[from] ron::Error),
+    #[error("Failed to write the level file.")]
+    Io(#[from] std::io::Error),
+}
+
+/// A scene with the registered components of every [Editable] entity. [Children] are left out because the hierarchy
+/// is rebuilt from the [Parent] components when the scene is spawned, and so is the [Parent] of an entity whose parent
+/// isn't saved.
+pub fn level_scene(world: &World, component_registry: &ComponentRegistry) -> DynamicScene {
+    let saved = world
+        .archetypes()
+        .filter(|archetype| archetype.has::<Editable>())
+        .flat_map(|archetype| archetype.iter_entities().cloned())
+        .collect::<HashSet<_>>();
+
+    let mut scene = DynamicScene::default();
+    for archetype in world.archetypes() {
+        if !archetype.has::<Editable>() {
+            continue;
+        }
+
+        for (index, entity) in archetype.iter_entities().enumerate() {
+            let mut components = Vec::new();
+            for type_info in archetype.types() {
+                if type_info.id() == TypeId::of::<Children>() {
+                    continue;
+                }
+                if type_info.id() == TypeId::of::<Parent>() {
+                    let parent = world.get::<Parent>(*entity).unwrap();
+                    if !saved.contains(&parent.0) {
+                        continue;
+                    }
+                }
+
+                if let Some(registration) = component_registry.get(&type_info.id()) {
+                    components.push(
+                        registration
+                            .get_component_properties(archetype, index)
+                            .to_dynamic(),
+                    );
+                }
+            }
+
+            scene.entities.push(bevy_scene::Entity {
+                entity: entity.id(),
+                components,
+            });
+        }
+    }
+
+    scene
+}
+
+/// Saves the [Editable] entities of `world` to `path` as a `.scn` scene
+pub fn save_level(
+    world: &World,
+    component_registry: &ComponentRegistry,
+    property_type_registry: &PropertyTypeRegistry,
+    path: &Path,
+) -> Result<(), SaveLevelError> {
+    let scene = level_scene(world, component_registry);
+    let ron = scene.serialize_ron(property_type_registry)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, ron)?;
+    Ok(())
+}
+
+/// Saves the level to [Editor::save_path] when Ctrl+S is pressed in the editor
+pub fn save_level_system(world: &mut World, resources: &mut Resources) {
+    let keyboard_input = resources.get::<Input<KeyCode>>().unwrap();
+    let editor = resources.get::<Editor>().unwrap();
+    if !editor.enabled
+        || !control_pressed(&keyboard_input)
+        || !keyboard_input.just_pressed(KeyCode::S)
+    {
+        return;
+    }
+
+    let asset_folder = resources.get::<AssetServerSettings>().map_or_else(
+        || "assets".to_string(),
+        |settings| settings.asset_folder.clone(),
+    );
+    let path = bevy_asset::FileAssetIo::get_root_path()
+        .join(asset_folder)
+        .join(&editor.save_path);
+    let type_registry = resources.get::<TypeRegistry>().unwrap();
+    let component_registry = type_registry.component.read();
+    let property_registry = type_registry.property.read();
+    match save_level(world, &component_registry, &property_registry, &path) {
+        Ok(()) => log::info!("Saved the level to {}", path.display()),
+        Err(err) => log::error!("Failed to save the level to {}: {:?}", path.display(), err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::Entity;
+    use bevy_transform::prelude::Transform;
+    use bevy_type_registry::ComponentRegistration;
+
+    #[test]
+    fn save_editable_entities() {
+        let mut registry = ComponentRegistry::default();
+        registry.register::<Editable>();
+        registry.register::<Transform>();
+        registry.add_registration(
+            ComponentRegistration::build::<Parent>()
+                .map_entities()
+                .finish(),
+        );
+        registry.add_registration(
+            ComponentRegistration::build::<Children>()
+                .map_entities()
+                .finish(),
+        );
+
+        let mut world = World::default();
+        let outside = world.spawn((Transform::default(),));
+        let root = world.spawn((Editable::new("root"), Transform::default()));
+        let child = world.spawn((Editable::new("child"), Transform::default(), Parent(root)));
+        let orphan = world.spawn((Editable::new("orphan"), Parent(outside)));
+        world.insert_one(root, Children::with(&[child])).unwrap();
+
+        let scene = level_scene(&world, &registry);
+        assert_eq!(scene.entities.len(), 3);
+        let component_names = |entity: Entity| {
+            let mut names = scene
+                .entities
+                .iter()
+                .find(|scene_entity| scene_entity.entity == entity.id())
+                .unwrap()
+                .components
+                .iter()
+                .map(|component| component.type_name.rsplit("::").next().unwrap().to_string())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        assert_eq!(component_names(root), vec!["Editable", "Transform"]);
+        assert_eq!(
+            component_names(child),
+            vec!["Editable", "Parent", "Transform"]
+        );
+        assert_eq!(component_names(orphan), vec!["Editable"]);
+    }
+}
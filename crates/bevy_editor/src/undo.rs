@@ -0,0 +1,523 @@
+use bevy_ecs::{Component, Entity, Local, Query, Res, ResMut, Resources, World};
+use bevy_pbr::gizmo::Gizmo;
+use bevy_property::{DynamicProperties, Properties};
+use bevy_transform::prelude::{Children, Parent, Transform};
+use bevy_type_registry::{ComponentRegistry, TypeRegistry};
+use bevy_utils::HashMap;
+use std::any::TypeId;
+
+/// A change to the world that can be undone. Components are stored as properties, so they must be registered with
+/// the [TypeRegistry].
+#[derive(Debug)]
+pub enum UndoOperation {
+    /// Changes a component of `entity` from `before` to `after`, where `None` means the entity doesn't have it
+    SetComponent {
+        entity: Entity,
+        component: TypeId,
+        before: Option<DynamicProperties>,
+        after: Option<DynamicProperties>,
+    },
+    /// Spawns `entity` with `components`
+    Spawn {
+        entity: Entity,
+        components: Vec<DynamicProperties>,
+    },
+    /// Despawns `entity`, which had `components`
+    Despawn {
+        entity: Entity,
+        components: Vec<DynamicProperties>,
+    },
+}
+
+impl UndoOperation {
+    /// Changes the `T` component of `entity` from `before` to `after`
+    pub fn set_component<T: Component + Properties>(
+        entity: Entity,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Self {
+        UndoOperation::SetComponent {
+            entity,
+            component: TypeId::of::<T>(),
+            before: before.map(|before| before.to_dynamic()),
+            after: after.map(|after| after.to_dynamic()),
+        }
+    }
+
+    /// The registered components of `entity`, except [Children], which are rebuilt from the [Parent] components
+    pub fn snapshot(
+        world: &World,
+        component_registry: &ComponentRegistry,
+        entity: Entity,
+    ) -> Option<Vec<DynamicProperties>> {
+        let location = world.get_entity_location(entity)?;
+        let archetype = world.archetypes().nth(location.archetype as usize)?;
+        let components = archetype
+            .types()
+            .iter()
+            .filter(|type_info| type_info.id() != TypeId::of::<Children>())
+            .filter_map(|type_info| component_registry.get(&type_info.id()))
+            .map(|registration| {
+                registration
+                    .get_component_properties(archetype, location.index)
+                    .to_dynamic()
+            })
+            .collect();
+        Some(components)
+    }
+
+    /// A short description, like "Edit Transform"
+    pub fn label(&self) -> String {
+        match self {
+            UndoOperation::SetComponent { before, after, .. } => {
+                let type_name = after
+                    .as_ref()
+                    .or_else(|| before.as_ref())
+                    .map_or("component", |properties| {
+                        properties.type_name.rsplit("::").next().unwrap()
+                    });
+                format!("Edit {}", type_name)
+            }
+            UndoOperation::Spawn { .. } => "Spawn".to_string(),
+            UndoOperation::Despawn { .. } => "Delete".to_string(),
+        }
+    }
+
+    /// The operation that undoes this one
+    pub fn inverse(self) -> Self {
+        match self {
+            UndoOperation::SetComponent {
+                entity,
+                component,
+                before,
+                after,
+            } => UndoOperation::SetComponent {
+                entity,
+                component,
+                before: after,
+                after: before,
+            },
+            UndoOperation::Spawn { entity, components } => {
+                UndoOperation::Despawn { entity, components }
+            }
+            UndoOperation::Despawn { entity, components } => {
+                UndoOperation::Spawn { entity, components }
+            }
+        }
+    }
+
+    fn apply(
+        &self,
+        world: &mut World,
+        resources: &Resources,
+        component_registry: &ComponentRegistry,
+        entity_map: &mut HashMap<Entity, Entity>,
+    ) {
+        match self {
+            UndoOperation::SetComponent {
+                entity,
+                component,
+                after,
+                ..
+            } => {
+                let entity = resolve(entity_map, *entity);
+                let registration = match component_registry.get(component) {
+                    Some(registration) => registration,
+                    None => return,
+                };
+                if !world.contains(entity) {
+                    return;
+                }
+
+                match after {
+                    Some(after) if world.has_component_type(entity, *component) => {
+                        registration.apply_property_to_entity(world, entity, after)
+                    }
+                    Some(after) => {
+                        registration.add_property_to_entity(world, resources, entity, after)
+                    }
+                    None => registration.remove_component_from_entity(world, entity),
+                }
+            }
+            UndoOperation::Spawn { entity, components } => {
+                let new_entity = world.reserve_entity();
+                for component in components.iter() {
+                    match component_registry.get_with_name(&component.type_name) {
+                        Some(registration) => registration
+                            .add_property_to_entity(world, resources, new_entity, component),
+                        None => log::warn!(
+                            "Can't respawn the unregistered component {}",
+                            component.type_name
+                        ),
+                    }
+                }
+                // the parent may have been respawned too
+                if let Ok(mut parent) = world.get_mut::<Parent>(new_entity) {
+                    parent.0 = resolve(entity_map, parent.0);
+                }
+                entity_map.insert(resolve(entity_map, *entity), new_entity);
+            }
+            UndoOperation::Despawn { entity, .. } => {
+                let entity = resolve(entity_map, *entity);
+                if let Ok(parent) = world.get::<Parent>(entity).map(|parent| parent.0) {
+                    if let Ok(mut children) = world.get_mut::<Children>(parent) {
+                        children.retain(|child| *child != entity);
+                    }
+                }
+                let _ = world.despawn(entity);
+            }
+        }
+    }
+}
+
+/// The entity `entity` is now, after undo and redo respawned it
+fn resolve(entity_map: &HashMap<Entity, Entity>, mut entity: Entity) -> Entity {
+    while let Some(respawned) = entity_map.get(&entity) {
+        entity = *respawned;
+    }
+    entity
+}
+
+/// Operations that are undone and redone together
+#[derive(Debug)]
+pub struct UndoTransaction {
+    pub label: String,
+    pub operations: Vec<UndoOperation>,
+}
+
+impl UndoTransaction {
+    /// The transaction that undoes this one
+    fn inverse(self) -> Self {
+        UndoTransaction {
+            label: self.label,
+            operations: self
+                .operations
+                .into_iter()
+                .rev()
+                .map(UndoOperation::inverse)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UndoRequest {
+    Undo,
+    Redo,
+    Despawn(Entity),
+    Spawned(Entity),
+}
+
+/// The history of changes made by editor tools. Tools apply their changes, then record them with [UndoStack::push],
+/// and [UndoStack::undo] and [UndoStack::redo] revert and reapply them at the end of the frame.
+///
+/// Operations pushed between [UndoStack::begin] and [UndoStack::end] are undone as one transaction, otherwise every
+/// operation is its own. Entities that are despawned and respawned get new ids; operations recorded with the old id
+/// still apply to the respawned entity, and [UndoStack::resolve] gives its current id.
+#[derive(Debug)]
+pub struct UndoStack {
+    /// How many transactions can be undone
+    pub limit: usize,
+    undo: Vec<UndoTransaction>,
+    redo: Vec<UndoTransaction>,
+    open: Option<UndoTransaction>,
+    depth: usize,
+    requests: Vec<UndoRequest>,
+    entity_map: HashMap<Entity, Entity>,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        UndoStack {
+            limit: 100,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            open: None,
+            depth: 0,
+            requests: Vec::new(),
+            entity_map: Default::default(),
+        }
+    }
+}
+
+impl UndoStack {
+    /// Starts a transaction. Transactions can be nested, and the outermost one is recorded when it ends.
+    pub fn begin(&mut self, label: impl Into<String>) {
+        if self.depth == 0 {
+            self.open = Some(UndoTransaction {
+                label: label.into(),
+                operations: Vec::new(),
+            });
+        }
+        self.depth += 1;
+    }
+
+    /// Ends the transaction started by the last [UndoStack::begin]
+    pub fn end(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            if let Some(transaction) = self.open.take() {
+                self.commit(transaction);
+            }
+        }
+    }
+
+    /// Records an operation that was already applied
+    pub fn push(&mut self, operation: UndoOperation) {
+        match self.open {
+            Some(ref mut transaction) => transaction.operations.push(operation),
+            None => self.commit(UndoTransaction {
+                label: operation.label(),
+                operations: vec![operation],
+            }),
+        }
+    }
+
+    /// Records operations that were already applied as one transaction
+    pub fn push_transaction(&mut self, label: impl Into<String>, operations: Vec<UndoOperation>) {
+        self.begin(label);
+        for operation in operations {
+            self.push(operation);
+        }
+        self.end();
+    }
+
+    /// Reverts the last transaction at the end of the frame
+    pub fn undo(&mut self) {
+        self.requests.push(UndoRequest::Undo);
+    }
+
+    /// Reapplies the last undone transaction at the end of the frame
+    pub fn redo(&mut self) {
+        self.requests.push(UndoRequest::Redo);
+    }
+
+    /// Despawns `entity` and its descendants at the end of the frame, so it can be undone
+    pub fn despawn(&mut self, entity: Entity) {
+        self.requests.push(UndoRequest::Despawn(entity));
+    }
+
+    /// Records that `entity` was spawned, usually with [Commands](bevy_ecs::Commands). Its components are recorded at
+    /// the end of the frame, once they have been added.
+    pub fn spawned(&mut self, entity: Entity) {
+        self.requests.push(UndoRequest::Spawned(entity));
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The label of the transaction [UndoStack::undo] reverts
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo
+            .last()
+            .map(|transaction| transaction.label.as_str())
+    }
+
+    /// The label of the transaction [UndoStack::redo] reapplies
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo
+            .last()
+            .map(|transaction| transaction.label.as_str())
+    }
+
+    /// The current id of an entity that undo or redo may have respawned
+    pub fn resolve(&self, entity: Entity) -> Entity {
+        resolve(&self.entity_map, entity)
+    }
+
+    /// Forgets the history
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.entity_map.clear();
+    }
+
+    fn commit(&mut self, transaction: UndoTransaction) {
+        if transaction.operations.is_empty() {
+            return;
+        }
+
+        self.redo.clear();
+        self.undo.push(transaction);
+        if self.undo.len() > self.limit {
+            let excess = self.undo.len() - self.limit;
+            self.undo.drain(..excess);
+        }
+    }
+
+    fn apply(
+        &mut self,
+        transaction: &UndoTransaction,
+        world: &mut World,
+        resources: &Resources,
+        component_registry: &ComponentRegistry,
+    ) {
+        for operation in transaction.operations.iter() {
+            operation.apply(world, resources, component_registry, &mut self.entity_map);
+        }
+    }
+}
+
+/// Adds `entity` and its descendants to `order`, children first
+fn despawn_order(world: &World, entity: Entity, order: &mut Vec<Entity>) {
+    if let Ok(children) = world.get::<Children>(entity) {
+        for child in children.iter() {
+            despawn_order(world, *child, order);
+        }
+    }
+    order.push(entity);
+}
+
+/// Handles the [UndoStack] requests. Runs at the end of the frame, after the commands of other systems were applied.
+pub fn undo_system(world: &mut World, resources: &mut Resources) {
+    let mut undo_stack = resources.get_mut::<UndoStack>().unwrap();
+    if undo_stack.requests.is_empty() {
+        return;
+    }
+
+    let type_registry = resources.get::<TypeRegistry>().unwrap();
+    let component_registry = type_registry.component.read();
+    for request in std::mem::take(&mut undo_stack.requests) {
+        match request {
+            UndoRequest::Undo => {
+                if let Some(transaction) = undo_stack.undo.pop() {
+                    let inverse = transaction.inverse();
+                    undo_stack.apply(&inverse, world, resources, &component_registry);
+                    undo_stack.redo.push(inverse);
+                }
+            }
+            UndoRequest::Redo => {
+                if let Some(transaction) = undo_stack.redo.pop() {
+                    let inverse = transaction.inverse();
+                    undo_stack.apply(&inverse, world, resources, &component_registry);
+                    undo_stack.undo.push(inverse);
+                }
+            }
+            UndoRequest::Despawn(entity) => {
+                let entity = undo_stack.resolve(entity);
+                let mut order = Vec::new();
+                if world.contains(entity) {
+                    despawn_order(world, entity, &mut order);
+                }
+                let operations = order
+                    .into_iter()
+                    .filter_map(|entity| {
+                        UndoOperation::snapshot(world, &component_registry, entity)
+                            .map(|components| UndoOperation::Despawn { entity, components })
+                    })
+                    .collect::<Vec<_>>();
+                let transaction = UndoTransaction {
+                    label: "Delete".to_string(),
+                    operations,
+                };
+                undo_stack.apply(&transaction, world, resources, &component_registry);
+                undo_stack.commit(transaction);
+            }
+            UndoRequest::Spawned(entity) => {
+                if let Some(components) =
+                    UndoOperation::snapshot(world, &component_registry, entity)
+                {
+                    undo_stack.push(UndoOperation::Spawn { entity, components });
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GizmoUndoState {
+    drag: Option<(Entity, Transform)>,
+}
+
+/// Records every [Gizmo] drag as one change to the [Transform] of the dragged entity
+pub fn gizmo_undo_system(
+    mut state: Local<GizmoUndoState>,
+    gizmo: Res<Gizmo>,
+    mut undo_stack: ResMut<UndoStack>,
+    transform_query: Query<&Transform>,
+) {
+    match (state.drag, gizmo.is_dragging()) {
+        (None, true) => {
+            // no delta was applied yet on the frame the drag starts
+            state.drag = gizmo.selected.and_then(|entity| {
+                transform_query
+                    .get(entity)
+                    .ok()
+                    .map(|transform| (entity, *transform))
+            });
+        }
+        (Some((entity, before)), false) => {
+            state.drag = None;
+            if let Ok(after) = transform_query.get(entity) {
+                if *after != before {
+                    undo_stack.push(UndoOperation::set_component(
+                        entity,
+                        Some(&before),
+                        Some(after),
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::Vec3;
+
+    #[test]
+    fn undo_and_redo() {
+        let mut resources = Resources::default();
+        let type_registry = TypeRegistry::default();
+        type_registry.component.write().register::<Transform>();
+        resources.insert(type_registry);
+        resources.insert(UndoStack::default());
+
+        let mut world = World::default();
+        let before = Transform::default();
+        let after = Transform::from_translation(Vec3::new(1.0, 0.0, 0.0));
+        let entity = world.spawn((after,));
+        {
+            let mut undo_stack = resources.get_mut::<UndoStack>().unwrap();
+            undo_stack.push(UndoOperation::set_component(
+                entity,
+                Some(&before),
+                Some(&after),
+            ));
+            assert_eq!(undo_stack.undo_label(), Some("Edit Transform"));
+            undo_stack.undo();
+        }
+        undo_system(&mut world, &mut resources);
+        assert_eq!(*world.get::<Transform>(entity).unwrap(), before);
+
+        resources.get_mut::<UndoStack>().unwrap().redo();
+        undo_system(&mut world, &mut resources);
+        assert_eq!(*world.get::<Transform>(entity).unwrap(), after);
+
+        resources.get_mut::<UndoStack>().unwrap().despawn(entity);
+        undo_system(&mut world, &mut resources);
+        assert!(!world.contains(entity));
+
+        resources.get_mut::<UndoStack>().unwrap().undo();
+        undo_system(&mut world, &mut resources);
+        let respawned = resources.get::<UndoStack>().unwrap().resolve(entity);
+        assert_ne!(respawned, entity);
+        assert_eq!(*world.get::<Transform>(respawned).unwrap(), after);
+
+        // the edit recorded with the old id applies to the respawned entity
+        {
+            let mut undo_stack = resources.get_mut::<UndoStack>().unwrap();
+            assert!(undo_stack.can_undo());
+            undo_stack.undo();
+        }
+        undo_system(&mut world, &mut resources);
+        assert_eq!(*world.get::<Transform>(respawned).unwrap(), before);
+    }
+}
@@ -4,7 +4,7 @@ use bevy_ecs::{bevy_utils::BoxedFuture, World, WorldBuilderSource};
 use bevy_math::Mat4;
 use bevy_pbr::prelude::{PbrComponents, StandardMaterial};
 use bevy_render::{
-    mesh::{Indices, Mesh, VertexAttributeValues},
+    mesh::{Indices, Mesh, MeshLod, VertexAttributeValues},
     pipeline::PrimitiveTopology,
     prelude::{Color, Texture},
     texture::{AddressMode, FilterMode, SamplerDescriptor, TextureFormat},
@@ -110,10 +110,36 @@ async fn load_gltf<'a, 'b>(
                         .insert(Cow::Borrowed(Mesh::ATTRIBUTE_UV_0), vertex_attribute);
                 }
 
+                if let Some(vertex_attribute) = reader
+                    .read_tex_coords(1)
+                    .map(|v| VertexAttributeValues::Float2(v.into_f32().collect()))
+                {
+                    mesh.attributes
+                        .insert(Cow::Borrowed(Mesh::ATTRIBUTE_UV_1), vertex_attribute);
+                }
+
+                if let Some(vertex_attribute) = reader
+                    .read_colors(0)
+                    .map(|v| VertexAttributeValues::Float4(v.into_rgba_f32().collect()))
+                {
+                    mesh.attributes
+                        .insert(Cow::Borrowed(Mesh::ATTRIBUTE_COLOR), vertex_attribute);
+                }
+
                 if let Some(indices) = reader.read_indices() {
                     mesh.indices = Some(Indices::U32(indices.into_u32().collect()));
                 };
 
+                if primitive_topology == PrimitiveTopology::TriangleList {
+                    let lod_levels = load_context.import_settings().lod_levels.clone();
+                    for (level, lod_level) in lod_levels.iter().enumerate() {
+                        load_context.set_labeled_asset(
+                            &lod_label(&primitive_label, level + 1),
+                            LoadedAsset::new(mesh.simplified(lod_level.ratio)),
+                        );
+                    }
+                }
+
                 load_context.set_labeled_asset(&primitive_label, LoadedAsset::new(mesh));
             };
         }
@@ -143,6 +169,7 @@ async fn load_gltf<'a, 'b>(
                     size: bevy_math::f32::vec2(size.0 as f32, size.1 as f32),
                     format: TextureFormat::Rgba8Unorm,
                     sampler: texture_sampler(&texture)?,
+                    ..Default::default()
                 }),
             );
         }
@@ -177,6 +204,9 @@ async fn load_gltf<'a, 'b>(
             LoadedAsset::new(StandardMaterial {
                 albedo: Color::rgba(color[0], color[1], color[2], color[3]),
                 albedo_texture: texture_handle,
+                metallic: pbr.metallic_factor(),
+                roughness: pbr.roughness_factor(),
+                double_sided: material.double_sided(),
                 ..Default::default()
             })
             .with_dependencies(dependencies),
@@ -229,11 +259,31 @@ fn load_node(
                     let material_label = material_label(&material);
                     let material_asset_path =
                         AssetPath::new_ref(load_context.path(), Some(&material_label));
+                    let mesh_handle = load_context.get_handle(mesh_asset_path);
                     parent.spawn(PbrComponents {
-                        mesh: load_context.get_handle(mesh_asset_path),
+                        mesh: mesh_handle.clone(),
                         material: load_context.get_handle(material_asset_path),
                         ..Default::default()
                     });
+
+                    let lod_levels = &load_context.import_settings().lod_levels;
+                    if !lod_levels.is_empty() && primitive.mode() == Mode::Triangles {
+                        let distances = lod_levels
+                            .iter()
+                            .map(|lod_level| lod_level.distance)
+                            .collect();
+                        let mut levels = vec![mesh_handle];
+                        for level in 1..=lod_levels.len() {
+                            let label = lod_label(&primitive_label, level);
+                            levels.push(
+                                load_context.get_handle(AssetPath::new_ref(
+                                    load_context.path(),
+                                    Some(&label),
+                                )),
+                            );
+                        }
+                        parent.with(MeshLod { levels, distances });
+                    }
                 }
             }
 
@@ -261,6 +311,10 @@ fn primitive_label(mesh: &gltf::Mesh, primitive: &Primitive) -> String {
     format!("Mesh{}/Primitive{}", mesh.index(), primitive.index())
 }
 
+fn lod_label(primitive_label: &str, level: usize) -> String {
+    format!("{}/Lod{}", primitive_label, level)
+}
+
 fn material_label(material: &gltf::Material) -> String {
     if let Some(index) = material.index() {
         format!("Material{}", index)
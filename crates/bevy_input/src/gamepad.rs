@@ -69,13 +69,23 @@ pub enum GamepadAxisType {
 #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct GamepadAxis(pub Gamepad, pub GamepadAxisType);
 
-#[derive(Default, Debug)]
+/// How raw gamepad values are translated into [Input] and [Axis] values. Settings for a specific button or axis of a
+/// specific gamepad replace the defaults. With the `serialize` feature, it can be persisted as a settings resource.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct GamepadSettings {
     pub default_button_settings: ButtonSettings,
     pub default_axis_settings: AxisSettings,
     pub default_button_axis_settings: ButtonAxisSettings,
+    #[cfg_attr(feature = "serialize", serde(with = "map_as_pairs"))]
     pub button_settings: HashMap<GamepadButton, ButtonSettings>,
+    #[cfg_attr(feature = "serialize", serde(with = "map_as_pairs"))]
     pub axis_settings: HashMap<GamepadAxis, AxisSettings>,
+    #[cfg_attr(feature = "serialize", serde(with = "map_as_pairs"))]
     pub button_axis_settings: HashMap<GamepadButton, ButtonAxisSettings>,
 }
 
@@ -92,6 +102,14 @@ impl GamepadSettings {
             .unwrap_or(&self.default_axis_settings)
     }
 
+    /// The settings of `axis`, starting from the defaults if it doesn't have its own yet
+    pub fn axis_settings_mut(&mut self, axis: GamepadAxis) -> &mut AxisSettings {
+        let default_axis_settings = &self.default_axis_settings;
+        self.axis_settings
+            .entry(axis)
+            .or_insert_with(|| default_axis_settings.clone())
+    }
+
     pub fn get_button_axis_settings(&self, button: GamepadButton) -> &ButtonAxisSettings {
         self.button_axis_settings
             .get(&button)
@@ -99,7 +117,35 @@ impl GamepadSettings {
     }
 }
 
+/// Serializes maps with non-string keys as a list of pairs, so they can be stored in formats like json
+#[cfg(feature = "serialize")]
+mod map_as_pairs {
+    use bevy_utils::HashMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::hash::Hash;
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let pairs = Vec::<(K, V)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ButtonSettings {
     pub press: f32,
     pub release: f32,
@@ -124,13 +170,66 @@ impl ButtonSettings {
     }
 }
 
+/// Maps how far a stick or trigger is pushed, from 0.0 to 1.0, to the magnitude of its axis value
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum AxisCurve {
+    Linear,
+    /// Raises the value to this exponent. Exponents above 1.0 give finer control near the center.
+    Power(f32),
+    /// Interpolates linearly between `(input, output)` points, sorted by input. Inputs outside the points use the
+    /// output of the nearest point.
+    Points(Vec<(f32, f32)>),
+}
+
+impl Default for AxisCurve {
+    fn default() -> Self {
+        AxisCurve::Linear
+    }
+}
+
+impl AxisCurve {
+    pub fn apply(&self, value: f32) -> f32 {
+        match self {
+            AxisCurve::Linear => value,
+            AxisCurve::Power(exponent) => value.powf(*exponent),
+            AxisCurve::Points(points) => {
+                let next = match points.iter().position(|(input, _)| *input >= value) {
+                    Some(next) => next,
+                    None => return points.last().map_or(value, |(_, output)| *output),
+                };
+                if next == 0 {
+                    return points[0].1;
+                }
+
+                let (start_input, start_output) = points[next - 1];
+                let (end_input, end_output) = points[next];
+                let t = (value - start_input) / (end_input - start_input).max(std::f32::EPSILON);
+                start_output + (end_output - start_output) * t
+            }
+        }
+    }
+}
+
+/// Settings of a stick or dpad axis. Values between `negative_low` and `positive_low` are in the dead zone and read
+/// as 0.0. Values past `positive_high` or `negative_high` read as 1.0 or -1.0.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct AxisSettings {
     pub positive_high: f32,
     pub positive_low: f32,
     pub negative_high: f32,
     pub negative_low: f32,
+    /// Changes smaller than this are ignored
     pub threshold: f32,
+    /// Flips the sign of the axis, for example for inverted look controls
+    pub inverted: bool,
+    /// Applied to the magnitude of values outside the dead zone
+    pub curve: AxisCurve,
 }
 
 impl Default for AxisSettings {
@@ -141,31 +240,45 @@ impl Default for AxisSettings {
             negative_high: -0.95,
             negative_low: -0.05,
             threshold: 0.01,
+            inverted: false,
+            curve: AxisCurve::Linear,
         }
     }
 }
 
 impl AxisSettings {
+    /// Translates a raw axis value, ignoring the threshold
+    pub fn calibrate(&self, value: f32) -> f32 {
+        let value = if value <= self.positive_low && value >= self.negative_low {
+            0.0
+        } else if value >= self.positive_high {
+            1.0
+        } else if value <= self.negative_high {
+            -1.0
+        } else {
+            value.signum() * self.curve.apply(value.abs())
+        };
+
+        if self.inverted {
+            -value
+        } else {
+            value
+        }
+    }
+
     fn filter(&self, new_value: f32, old_value: Option<f32>) -> Option<f32> {
+        let new_value = self.calibrate(new_value);
         if let Some(old_value) = old_value {
             if (new_value - old_value).abs() <= self.threshold {
                 return None;
             }
         }
-        if new_value <= self.positive_low && new_value >= self.negative_low {
-            return Some(0.0);
-        }
-        if new_value >= self.positive_high {
-            return Some(1.0);
-        }
-        if new_value <= self.negative_high {
-            return Some(-1.0);
-        }
         Some(new_value)
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct ButtonAxisSettings {
     pub high: f32,
     pub low: f32,
@@ -305,3 +418,27 @@ const ALL_AXIS_TYPES: [GamepadAxisType; 8] = [
     GamepadAxisType::DPadX,
     GamepadAxisType::DPadY,
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axis_settings_calibrate() {
+        let mut settings = AxisSettings::default();
+        assert_eq!(settings.calibrate(0.03), 0.0);
+        assert_eq!(settings.calibrate(-0.97), -1.0);
+        assert_eq!(settings.calibrate(0.5), 0.5);
+
+        settings.inverted = true;
+        settings.curve = AxisCurve::Power(2.0);
+        assert_eq!(settings.calibrate(0.5), -0.25);
+        assert_eq!(settings.calibrate(-0.5), 0.25);
+
+        settings.curve = AxisCurve::Points(vec![(0.0, 0.0), (0.5, 0.2), (1.0, 1.0)]);
+        assert!((settings.calibrate(0.75) + 0.6).abs() < 1e-6);
+
+        // changes within the threshold are ignored
+        assert_eq!(settings.filter(0.755, Some(-0.6)), None);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::touch::Touches;
+use bevy_app::Events;
+use bevy_core::Time;
+use bevy_ecs::{Local, Res, ResMut};
+use bevy_math::Vec2;
+use bevy_utils::HashMap;
+
+/// A gesture recognized from the [Touches]. Positions are in window coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gesture {
+    /// A single finger touched and released the screen without moving
+    Tap { position: Vec2 },
+    /// A second tap close to the previous one, sent after its [Gesture::Tap]
+    DoubleTap { position: Vec2 },
+    /// A single finger moved across the screen
+    Pan {
+        position: Vec2,
+        delta: Vec2,
+        /// In pixels per second
+        velocity: Vec2,
+    },
+    /// The finger of a pan was released. `velocity` can be used to keep the movement going for a moment.
+    PanEnded { position: Vec2, velocity: Vec2 },
+    /// Two fingers moved relative to each other
+    Pinch {
+        /// The point between the fingers
+        center: Vec2,
+        /// How much the center moved this frame
+        delta: Vec2,
+        /// The distance between the fingers divided by their distance in the previous frame
+        scale: f32,
+    },
+}
+
+/// Thresholds for recognizing [Gesture]s
+#[derive(Debug, Clone)]
+pub struct GestureSettings {
+    /// Fingers that move farther than this, in pixels, pan instead of tapping
+    pub tap_max_distance: f32,
+    /// Fingers held longer than this, in seconds, don't tap
+    pub tap_max_duration: f64,
+    /// The most time between the taps of a double tap, in seconds
+    pub double_tap_max_interval: f64,
+    /// The farthest the taps of a double tap can be apart, in pixels
+    pub double_tap_max_distance: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        GestureSettings {
+            tap_max_distance: 10.0,
+            tap_max_duration: 0.3,
+            double_tap_max_interval: 0.3,
+            double_tap_max_distance: 30.0,
+        }
+    }
+}
+
+/// Follows the touches from frame to frame to recognize [Gesture]s. A gesture lasts from the first finger touching the
+/// screen to the last one being released. Once a second finger touches the screen, the gesture can only pinch.
+#[derive(Debug, Default)]
+pub struct GestureRecognizer {
+    /// The position of each touch in the previous frame
+    positions: HashMap<u64, Vec2>,
+    start_time: f64,
+    multi_touch: bool,
+    panning: bool,
+    pan_velocity: Vec2,
+    last_tap: Option<(f64, Vec2)>,
+}
+
+impl GestureRecognizer {
+    pub fn update(
+        &mut self,
+        touches: &Touches,
+        time: f64,
+        delta_seconds: f32,
+        settings: &GestureSettings,
+        gestures: &mut Events<Gesture>,
+    ) {
+        if self.positions.is_empty() && touches.iter_just_pressed().next().is_some() {
+            self.start_time = time;
+            self.multi_touch = false;
+            self.panning = false;
+            self.pan_velocity = Vec2::zero();
+        }
+
+        let active = touches
+            .iter()
+            .filter(|touch| !touches.just_released(touch.id) && !touches.just_cancelled(touch.id))
+            .collect::<Vec<_>>();
+        if active.len() > 1 {
+            self.multi_touch = true;
+            self.panning = false;
+        }
+
+        if active.len() == 2 && touches.iter_just_pressed().next().is_none() {
+            let (a, b) = (active[0], active[1]);
+            if let (Some(previous_a), Some(previous_b)) =
+                (self.positions.get(&a.id), self.positions.get(&b.id))
+            {
+                let previous_distance = (*previous_a - *previous_b).length();
+                let distance = (a.position - b.position).length();
+                let previous_center = (*previous_a + *previous_b) / 2.0;
+                let center = (a.position + b.position) / 2.0;
+                if previous_distance > 0.0
+                    && (distance != previous_distance || center != previous_center)
+                {
+                    gestures.send(Gesture::Pinch {
+                        center,
+                        delta: center - previous_center,
+                        scale: distance / previous_distance,
+                    });
+                }
+            }
+        }
+
+        if !self.multi_touch {
+            if let Some(touch) = touches.iter().next() {
+                if !self.panning && touch.distance().length() > settings.tap_max_distance {
+                    self.panning = true;
+                }
+
+                let previous_position = self.positions.get(&touch.id).copied();
+                if let (true, Some(previous_position)) = (self.panning, previous_position) {
+                    let delta = touch.position - previous_position;
+                    if delta != Vec2::zero() {
+                        self.pan_velocity = if delta_seconds > 0.0 {
+                            delta / delta_seconds
+                        } else {
+                            Vec2::zero()
+                        };
+                        gestures.send(Gesture::Pan {
+                            position: touch.position,
+                            delta,
+                            velocity: self.pan_velocity,
+                        });
+                    }
+                }
+
+                if touches.just_released(touch.id) {
+                    if self.panning {
+                        gestures.send(Gesture::PanEnded {
+                            position: touch.position,
+                            velocity: self.pan_velocity,
+                        });
+                    } else if time - self.start_time <= settings.tap_max_duration {
+                        self.tap(touch.position, time, settings, gestures);
+                    }
+                }
+            }
+        }
+
+        self.positions.clear();
+        for touch in active {
+            self.positions.insert(touch.id, touch.position);
+        }
+    }
+
+    fn tap(
+        &mut self,
+        position: Vec2,
+        time: f64,
+        settings: &GestureSettings,
+        gestures: &mut Events<Gesture>,
+    ) {
+        gestures.send(Gesture::Tap { position });
+        match self.last_tap.take() {
+            Some((last_time, last_position))
+                if time - last_time <= settings.double_tap_max_interval
+                    && (position - last_position).length() <= settings.double_tap_max_distance =>
+            {
+                gestures.send(Gesture::DoubleTap { position });
+            }
+            _ => self.last_tap = Some((time, position)),
+        }
+    }
+}
+
+/// Sends [Gesture] events for the gestures recognized from the [Touches]
+pub fn gesture_system(
+    mut recognizer: Local<GestureRecognizer>,
+    touches: Res<Touches>,
+    time: Res<Time>,
+    settings: Res<GestureSettings>,
+    mut gestures: ResMut<Events<Gesture>>,
+) {
+    recognizer.update(
+        &touches,
+        time.raw_seconds_since_startup,
+        time.raw_delta_seconds,
+        &settings,
+        &mut gestures,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::touch::{TouchInput, TouchPhase};
+    use bevy_app::EventReader;
+
+    struct Frame {
+        time: f64,
+        touches: Touches,
+        recognizer: GestureRecognizer,
+        gestures: Events<Gesture>,
+        reader: EventReader<Gesture>,
+    }
+
+    impl Frame {
+        fn run(&mut self, inputs: &[(u64, TouchPhase, Vec2)]) -> Vec<Gesture> {
+            self.time += 0.1;
+            self.touches.update();
+            for (id, phase, position) in inputs.iter() {
+                self.touches.process_event(&TouchInput {
+                    id: *id,
+                    phase: *phase,
+                    position: *position,
+                });
+            }
+
+            self.gestures.update();
+            self.recognizer.update(
+                &self.touches,
+                self.time,
+                0.1,
+                &GestureSettings::default(),
+                &mut self.gestures,
+            );
+            self.reader.iter(&self.gestures).cloned().collect()
+        }
+    }
+
+    #[test]
+    fn recognize_gestures() {
+        let mut frame = Frame {
+            time: 0.0,
+            touches: Touches::default(),
+            recognizer: GestureRecognizer::default(),
+            gestures: Events::default(),
+            reader: Default::default(),
+        };
+        let position = Vec2::new(100.0, 100.0);
+
+        frame.run(&[(0, TouchPhase::Started, position)]);
+        let tap = Gesture::Tap { position };
+        assert_eq!(
+            frame.run(&[(0, TouchPhase::Ended, position)]),
+            vec![tap.clone()]
+        );
+        frame.run(&[(1, TouchPhase::Started, position)]);
+        assert_eq!(
+            frame.run(&[(1, TouchPhase::Ended, position)]),
+            vec![tap, Gesture::DoubleTap { position }]
+        );
+
+        frame.run(&[(2, TouchPhase::Started, position)]);
+        assert_eq!(
+            frame.run(&[(2, TouchPhase::Moved, Vec2::new(150.0, 100.0))]),
+            vec![Gesture::Pan {
+                position: Vec2::new(150.0, 100.0),
+                delta: Vec2::new(50.0, 0.0),
+                velocity: Vec2::new(500.0, 0.0),
+            }]
+        );
+        frame.run(&[(2, TouchPhase::Ended, Vec2::new(150.0, 100.0))]);
+
+        frame.run(&[
+            (3, TouchPhase::Started, Vec2::new(0.0, 0.0)),
+            (4, TouchPhase::Started, Vec2::new(100.0, 0.0)),
+        ]);
+        assert_eq!(
+            frame.run(&[(4, TouchPhase::Moved, Vec2::new(200.0, 0.0))]),
+            vec![Gesture::Pinch {
+                center: Vec2::new(100.0, 0.0),
+                delta: Vec2::new(50.0, 0.0),
+                scale: 2.0,
+            }]
+        );
+    }
+}
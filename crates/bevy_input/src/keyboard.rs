@@ -1,10 +1,13 @@
-use crate::Input;
+use crate::{Input, WindowInput};
 use bevy_app::prelude::*;
 use bevy_ecs::{Local, Res, ResMut};
+use bevy_window::{WindowFocused, WindowId};
 
 /// A key input event from a keyboard device
 #[derive(Debug, Clone)]
 pub struct KeyboardInput {
+    /// The window that had focus when the key was pressed or released
+    pub window_id: WindowId,
     pub scan_code: u32,
     pub key_code: Option<KeyCode>,
     pub state: ElementState,
@@ -27,31 +30,52 @@ impl ElementState {
 #[derive(Default)]
 pub struct KeyboardInputState {
     keyboard_input_event_reader: EventReader<KeyboardInput>,
+    window_focused_event_reader: EventReader<WindowFocused>,
 }
 
-/// Updates the Input<KeyCode> resource with the latest KeyboardInput events
+/// Updates the Input<KeyCode> and WindowInput<KeyCode> resources with the latest KeyboardInput events. Keys that are
+/// held while their window loses focus are released.
 pub fn keyboard_input_system(
     mut state: Local<KeyboardInputState>,
     mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut window_keyboard_input: ResMut<WindowInput<KeyCode>>,
     keyboard_input_events: Res<Events<KeyboardInput>>,
+    window_focused_events: Res<Events<WindowFocused>>,
 ) {
     keyboard_input.update();
+    window_keyboard_input.update();
     for event in state
         .keyboard_input_event_reader
         .iter(&keyboard_input_events)
     {
         if let KeyboardInput {
+            window_id,
             key_code: Some(key_code),
             state,
             ..
         } = event
         {
             match state {
-                ElementState::Pressed => keyboard_input.press(*key_code),
-                ElementState::Released => keyboard_input.release(*key_code),
+                ElementState::Pressed => {
+                    keyboard_input.press(*key_code);
+                    window_keyboard_input.press(*window_id, *key_code);
+                }
+                ElementState::Released => {
+                    keyboard_input.release(*key_code);
+                    window_keyboard_input.release(*window_id, *key_code);
+                }
             }
         }
     }
+
+    for event in state
+        .window_focused_event_reader
+        .iter(&window_focused_events)
+    {
+        for key_code in window_keyboard_input.set_focused(event.id, event.focused) {
+            keyboard_input.release(key_code);
+        }
+    }
 }
 
 /// The key code of a keyboard input.
@@ -1,27 +1,32 @@
 mod axis;
 pub mod gamepad;
+pub mod gesture;
 mod input;
 pub mod keyboard;
 pub mod mouse;
 pub mod system;
 pub mod touch;
+mod window_input;
 
 pub use axis::*;
 pub use input::*;
+pub use window_input::*;
 
 pub mod prelude {
     pub use crate::{
         gamepad::{
-            Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, GamepadEvent,
-            GamepadEventType,
+            AxisCurve, AxisSettings, Gamepad, GamepadAxis, GamepadAxisType, GamepadButton,
+            GamepadButtonType, GamepadEvent, GamepadEventType, GamepadSettings,
         },
+        gesture::Gesture,
         keyboard::KeyCode,
         mouse::MouseButton,
-        Axis, Input,
+        Axis, Input, WindowInput,
     };
 }
 
 use bevy_app::prelude::*;
+use gesture::{gesture_system, Gesture, GestureSettings};
 use keyboard::{keyboard_input_system, KeyCode, KeyboardInput};
 use mouse::{mouse_button_input_system, MouseButton, MouseButtonInput, MouseMotion, MouseWheel};
 use touch::{touch_screen_input_system, TouchInput, Touches};
@@ -44,12 +49,13 @@ impl Plugin for InputPlugin {
             .add_event::<MouseMotion>()
             .add_event::<MouseWheel>()
             .init_resource::<Input<KeyCode>>()
+            .init_resource::<WindowInput<KeyCode>>()
             .add_system_to_stage(bevy_app::stage::EVENT, keyboard_input_system.system())
             .init_resource::<Input<MouseButton>>()
+            .init_resource::<WindowInput<MouseButton>>()
             .add_system_to_stage(bevy_app::stage::EVENT, mouse_button_input_system.system())
             .add_event::<GamepadEvent>()
             .add_event::<GamepadEventRaw>()
-            .init_resource::<GamepadSettings>()
             .init_resource::<Input<GamepadButton>>()
             .init_resource::<Axis<GamepadAxis>>()
             .init_resource::<Axis<GamepadButton>>()
@@ -57,6 +63,14 @@ impl Plugin for InputPlugin {
             .add_startup_system_to_stage(STARTUP, gamepad_event_system.system())
             .add_event::<TouchInput>()
             .init_resource::<Touches>()
-            .add_system_to_stage(bevy_app::stage::EVENT, touch_screen_input_system.system());
+            .add_system_to_stage(bevy_app::stage::EVENT, touch_screen_input_system.system())
+            .add_event::<Gesture>()
+            .init_resource::<GestureSettings>()
+            .add_system_to_stage(bevy_app::stage::EVENT, gesture_system.system());
+
+        // keep gamepad settings that were loaded before the plugin was added
+        if app.resources().get::<GamepadSettings>().is_none() {
+            app.init_resource::<GamepadSettings>();
+        }
     }
 }
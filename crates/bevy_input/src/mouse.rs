@@ -1,12 +1,15 @@
 use super::keyboard::ElementState;
-use crate::Input;
+use crate::{Input, WindowInput};
 use bevy_app::prelude::{EventReader, Events};
 use bevy_ecs::{Local, Res, ResMut};
 use bevy_math::Vec2;
+use bevy_window::{WindowFocused, WindowId};
 
 /// A mouse button input event
 #[derive(Debug, Clone)]
 pub struct MouseButtonInput {
+    /// The window the cursor was in when the button was pressed or released
+    pub window_id: WindowId,
     pub button: MouseButton,
     pub state: ElementState,
 }
@@ -37,6 +40,8 @@ pub enum MouseScrollUnit {
 /// A mouse scroll wheel event, where x represents horizontal scroll and y represents vertical scroll.
 #[derive(Debug, Clone)]
 pub struct MouseWheel {
+    /// The window the cursor was in when the wheel was scrolled
+    pub window_id: WindowId,
     pub unit: MouseScrollUnit,
     pub x: f32,
     pub y: f32,
@@ -46,22 +51,42 @@ pub struct MouseWheel {
 #[derive(Default)]
 pub struct MouseButtonInputState {
     mouse_button_input_event_reader: EventReader<MouseButtonInput>,
+    window_focused_event_reader: EventReader<WindowFocused>,
 }
 
-/// Updates the Input<MouseButton> resource with the latest MouseButtonInput events
+/// Updates the Input<MouseButton> and WindowInput<MouseButton> resources with the latest MouseButtonInput events.
+/// Buttons that are held while their window loses focus are released.
 pub fn mouse_button_input_system(
     mut state: Local<MouseButtonInputState>,
     mut mouse_button_input: ResMut<Input<MouseButton>>,
+    mut window_mouse_button_input: ResMut<WindowInput<MouseButton>>,
     mouse_button_input_events: Res<Events<MouseButtonInput>>,
+    window_focused_events: Res<Events<WindowFocused>>,
 ) {
     mouse_button_input.update();
+    window_mouse_button_input.update();
     for event in state
         .mouse_button_input_event_reader
         .iter(&mouse_button_input_events)
     {
         match event.state {
-            ElementState::Pressed => mouse_button_input.press(event.button),
-            ElementState::Released => mouse_button_input.release(event.button),
+            ElementState::Pressed => {
+                mouse_button_input.press(event.button);
+                window_mouse_button_input.press(event.window_id, event.button);
+            }
+            ElementState::Released => {
+                mouse_button_input.release(event.button);
+                window_mouse_button_input.release(event.window_id, event.button);
+            }
+        }
+    }
+
+    for event in state
+        .window_focused_event_reader
+        .iter(&window_focused_events)
+    {
+        for button in window_mouse_button_input.set_focused(event.id, event.focused) {
+            mouse_button_input.release(button);
         }
     }
 }
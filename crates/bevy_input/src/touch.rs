@@ -90,31 +90,24 @@ impl Touches {
             .iter()
             .map(move |id| self.active_touches.get(id).unwrap())
     }
-}
 
-/// Updates the Touches resource with the latest TouchInput events
-pub fn touch_screen_input_system(
-    mut state: Local<TouchSystemState>,
-    mut touch_state: ResMut<Touches>,
-    touch_input_events: Res<Events<TouchInput>>,
-) {
-    let touch_state = &mut *touch_state;
-    for released_id in touch_state.just_released.iter() {
-        touch_state.active_touches.remove(&released_id);
-    }
+    /// Removes the touches that ended last frame and clears the per-frame state
+    pub(crate) fn update(&mut self) {
+        for released_id in self.just_released.drain() {
+            self.active_touches.remove(&released_id);
+        }
 
-    for cancelled_id in touch_state.just_cancelled.iter() {
-        touch_state.active_touches.remove(&cancelled_id);
-    }
+        for cancelled_id in self.just_cancelled.drain() {
+            self.active_touches.remove(&cancelled_id);
+        }
 
-    touch_state.just_pressed.clear();
-    touch_state.just_cancelled.clear();
+        self.just_pressed.clear();
+    }
 
-    for event in state.touch_event_reader.iter(&touch_input_events) {
-        let active_touch = touch_state.active_touches.get(&event.id);
+    pub(crate) fn process_event(&mut self, event: &TouchInput) {
         match event.phase {
             TouchPhase::Started => {
-                touch_state.active_touches.insert(
+                self.active_touches.insert(
                     event.id,
                     Touch {
                         id: event.id,
@@ -123,21 +116,32 @@ pub fn touch_screen_input_system(
                         position: event.position,
                     },
                 );
-                touch_state.just_pressed.insert(event.id);
+                self.just_pressed.insert(event.id);
             }
             TouchPhase::Moved => {
-                let old_touch = active_touch.unwrap();
-                let mut new_touch = old_touch.clone();
-                new_touch.previous_position = new_touch.position;
-                new_touch.position = event.position;
-                touch_state.active_touches.insert(event.id, new_touch);
+                if let Some(touch) = self.active_touches.get_mut(&event.id) {
+                    touch.previous_position = touch.position;
+                    touch.position = event.position;
+                }
             }
             TouchPhase::Ended => {
-                touch_state.just_released.insert(event.id);
+                self.just_released.insert(event.id);
             }
             TouchPhase::Cancelled => {
-                touch_state.just_cancelled.insert(event.id);
+                self.just_cancelled.insert(event.id);
             }
         };
     }
 }
+
+/// Updates the Touches resource with the latest TouchInput events
+pub fn touch_screen_input_system(
+    mut state: Local<TouchSystemState>,
+    mut touch_state: ResMut<Touches>,
+    touch_input_events: Res<Events<TouchInput>>,
+) {
+    touch_state.update();
+    for event in state.touch_event_reader.iter(&touch_input_events) {
+        touch_state.process_event(event);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::Input;
+use bevy_utils::HashMap;
+use bevy_window::WindowId;
+use std::hash::Hash;
+
+/// The "press" state of inputs of type `T`, kept separately for each window.
+///
+/// [Input] tracks inputs from all windows together. With multiple windows, `WindowInput` tells which window a key or
+/// button was pressed in, and the [focused](WindowInput::focused) input only contains what was pressed while the
+/// window that currently has keyboard focus was focused.
+#[derive(Debug)]
+pub struct WindowInput<T> {
+    windows: HashMap<WindowId, Input<T>>,
+    focused_window: Option<WindowId>,
+}
+
+impl<T> Default for WindowInput<T> {
+    fn default() -> Self {
+        Self {
+            windows: Default::default(),
+            focused_window: None,
+        }
+    }
+}
+
+impl<T> WindowInput<T>
+where
+    T: Copy + Eq + Hash,
+{
+    /// The inputs of the given window, if anything was ever pressed in it
+    pub fn get(&self, window_id: WindowId) -> Option<&Input<T>> {
+        self.windows.get(&window_id)
+    }
+
+    /// The inputs of the window that currently has focus
+    pub fn focused(&self) -> Option<&Input<T>> {
+        self.focused_window
+            .and_then(|window_id| self.get(window_id))
+    }
+
+    /// The window that currently has focus
+    pub fn focused_window(&self) -> Option<WindowId> {
+        self.focused_window
+    }
+
+    pub fn pressed(&self, window_id: WindowId, input: T) -> bool {
+        self.get(window_id)
+            .map_or(false, |window_input| window_input.pressed(input))
+    }
+
+    pub fn just_pressed(&self, window_id: WindowId, input: T) -> bool {
+        self.get(window_id)
+            .map_or(false, |window_input| window_input.just_pressed(input))
+    }
+
+    pub fn just_released(&self, window_id: WindowId, input: T) -> bool {
+        self.get(window_id)
+            .map_or(false, |window_input| window_input.just_released(input))
+    }
+
+    pub fn press(&mut self, window_id: WindowId, input: T) {
+        // some platforms don't report focus, but input only arrives in focused windows
+        if self.focused_window.is_none() {
+            self.focused_window = Some(window_id);
+        }
+        self.windows
+            .entry(window_id)
+            .or_insert_with(Input::default)
+            .press(input);
+    }
+
+    pub fn release(&mut self, window_id: WindowId, input: T) {
+        self.windows
+            .entry(window_id)
+            .or_insert_with(Input::default)
+            .release(input);
+    }
+
+    /// Updates which window has focus. A window that loses focus doesn't receive the release of the inputs that are
+    /// still pressed, so they are released right away. Returns the released inputs.
+    pub fn set_focused(&mut self, window_id: WindowId, focused: bool) -> Vec<T> {
+        if focused {
+            self.focused_window = Some(window_id);
+            return Vec::new();
+        }
+
+        if self.focused_window == Some(window_id) {
+            self.focused_window = None;
+        }
+        let window_input = match self.windows.get_mut(&window_id) {
+            Some(window_input) => window_input,
+            None => return Vec::new(),
+        };
+        let released = window_input.get_pressed().copied().collect::<Vec<_>>();
+        for input in released.iter() {
+            window_input.release(*input);
+        }
+        released
+    }
+
+    pub fn update(&mut self) {
+        for window_input in self.windows.values_mut() {
+            window_input.update();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::KeyCode;
+
+    #[test]
+    fn inputs_are_scoped_by_window() {
+        let editor = WindowId::primary();
+        let preview = WindowId::new();
+        let mut input = WindowInput::<KeyCode>::default();
+        input.set_focused(editor, true);
+        input.press(editor, KeyCode::S);
+        assert!(input.just_pressed(editor, KeyCode::S));
+        assert!(!input.pressed(preview, KeyCode::S));
+        assert!(input.focused().unwrap().pressed(KeyCode::S));
+
+        input.update();
+        assert_eq!(input.set_focused(editor, false), vec![KeyCode::S]);
+        assert!(input.just_released(editor, KeyCode::S));
+        assert!(input.focused().is_none());
+
+        input.set_focused(preview, true);
+        input.press(preview, KeyCode::Space);
+        assert_eq!(input.focused_window(), Some(preview));
+        assert!(input.focused().unwrap().pressed(KeyCode::Space));
+        assert!(!input.pressed(editor, KeyCode::Space));
+    }
+}
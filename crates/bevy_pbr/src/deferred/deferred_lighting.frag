@@ -0,0 +1,110 @@
+#version 450
+
+const int MAX_LIGHTS = 128;
+const float PI = 3.141592653589793;
+
+struct Light {
+    mat4 proj;
+    vec4 pos;
+    vec4 color;
+};
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+    vec4 CameraPosition;
+    // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+    vec4 CameraExposure;
+};
+
+layout(set = 1, binding = 0) uniform Lights {
+    uvec4 NumLights;
+    vec4 AmbientColor;
+    vec4 FogColor;
+    // x: distance the fog starts at, y: distance it is thickest at
+    vec4 FogDistance;
+    Light SceneLights[MAX_LIGHTS];
+};
+
+layout(set = 2, binding = 0) uniform DeferredLightingMaterial_inverse_view_projection {
+    mat4 InverseViewProj;
+};
+layout(set = 2, binding = 1) uniform texture2D DeferredLightingMaterial_albedo;
+layout(set = 2, binding = 2) uniform sampler DeferredLightingMaterial_albedo_sampler;
+layout(set = 2, binding = 3) uniform texture2D DeferredLightingMaterial_normal;
+layout(set = 2, binding = 4) uniform sampler DeferredLightingMaterial_normal_sampler;
+layout(set = 2, binding = 5) uniform texture2D DeferredLightingMaterial_metallic_roughness;
+layout(set = 2, binding = 6) uniform sampler DeferredLightingMaterial_metallic_roughness_sampler;
+layout(set = 2, binding = 7) uniform texture2D DeferredLightingMaterial_depth;
+layout(set = 2, binding = 8) uniform sampler DeferredLightingMaterial_depth_sampler;
+
+// fades the color to the fog color with the distance from the camera
+vec3 apply_fog(vec3 color, vec3 position) {
+    float camera_distance = length(position - CameraPosition.xyz);
+    float fog = clamp((camera_distance - FogDistance.x) / max(FogDistance.y - FogDistance.x, 0.0001), 0.0, 1.0);
+    return mix(color, FogColor.rgb, fog * FogColor.a);
+}
+
+void main() {
+    float depth = textureLod(
+        sampler2D(DeferredLightingMaterial_depth, DeferredLightingMaterial_depth_sampler),
+        v_Uv, 0.0).r;
+    // nothing was drawn to the g-buffer here
+    if (depth >= 1.0) {
+        discard;
+    }
+
+    vec3 albedo = textureLod(
+        sampler2D(DeferredLightingMaterial_albedo, DeferredLightingMaterial_albedo_sampler),
+        v_Uv, 0.0).rgb;
+    vec4 normal_shaded = textureLod(
+        sampler2D(DeferredLightingMaterial_normal, DeferredLightingMaterial_normal_sampler),
+        v_Uv, 0.0);
+    vec3 metallic_roughness = textureLod(
+        sampler2D(DeferredLightingMaterial_metallic_roughness, DeferredLightingMaterial_metallic_roughness_sampler),
+        v_Uv, 0.0).rgb;
+
+    vec4 world_position = InverseViewProj * vec4(v_Uv.x * 2.0 - 1.0, 1.0 - v_Uv.y * 2.0, depth, 1.0);
+    vec3 position = world_position.xyz / world_position.w;
+    vec3 output_color = albedo;
+    if (normal_shaded.w > 0.5) {
+        vec3 normal = normalize(normal_shaded.xyz);
+        vec3 view_direction = normalize(CameraPosition.xyz - position);
+
+        float metallic = metallic_roughness.x;
+        float roughness = clamp(metallic_roughness.y, 0.05, 1.0);
+        // metals tint their highlights and have no diffuse light
+        vec3 f0 = mix(vec3(metallic_roughness.z), albedo, metallic);
+        vec3 diffuse_color = albedo * (1.0 - metallic);
+        // normalized blinn-phong, with the exponent mapped from roughness
+        float shininess = 2.0 / (roughness * roughness * roughness * roughness) - 2.0;
+
+        // the same ambient light as the forward path
+        vec3 color = albedo * AmbientColor.rgb;
+        for (int i=0; i<int(NumLights.x) && i<MAX_LIGHTS; ++i) {
+            Light light = SceneLights[i];
+            vec3 light_dir = light.pos.w == 0.0 ? normalize(light.pos.xyz) : normalize(light.pos.xyz - position);
+            float n_dot_l = max(dot(normal, light_dir), 0.0);
+            vec3 half_vector = normalize(light_dir + view_direction);
+            float specular = pow(max(dot(normal, half_vector), 0.0), shininess) * (shininess + 8.0) / (8.0 * PI);
+            // schlick's fresnel approximation
+            vec3 fresnel = f0 + (1.0 - f0) * pow(1.0 - max(dot(half_vector, view_direction), 0.0), 5.0);
+            color += (diffuse_color + fresnel * specular) * n_dot_l * light.color.rgb;
+        }
+        output_color = color;
+    }
+    output_color = apply_fog(output_color, position);
+
+    output_color *= CameraExposure.x;
+    if (CameraExposure.y > 0.5) {
+        // reinhard
+        output_color = output_color / (1.0 + output_color);
+    }
+
+    o_Target = vec4(output_color, 1.0);
+    // forward entities drawn in the main pass are depth tested against the g-buffer's surfaces
+    gl_FragDepth = depth;
+}
@@ -0,0 +1,69 @@
+#version 450
+
+layout(location = 0) in vec3 v_Position;
+layout(location = 1) in vec3 v_Normal;
+layout(location = 2) in vec2 v_Uv;
+# ifdef MESH_VERTEX_COLOR
+layout(location = 3) in vec4 v_Color;
+# endif
+
+layout(location = 0) out vec4 o_Albedo;
+// xyz: world space normal, w: 1.0 if the surface is shaded
+layout(location = 1) out vec4 o_Normal;
+// x: metallic, y: roughness, z: reflectance
+layout(location = 2) out vec4 o_MetallicRoughness;
+
+# ifdef MATERIAL_OVERRIDES
+layout(set = 2, binding = 1) uniform MaterialOverrides {
+    vec4 Tint;
+    vec4 Emissive;
+    vec2 UvOffset;
+    vec2 UvScale;
+};
+# endif
+
+layout(set = 3, binding = 0) uniform StandardMaterial_albedo {
+    vec4 Albedo;
+};
+
+# ifdef STANDARDMATERIAL_ALBEDO_TEXTURE
+layout(set = 3, binding = 1) uniform texture2D StandardMaterial_albedo_texture;
+layout(set = 3, binding = 2) uniform sampler StandardMaterial_albedo_texture_sampler;
+# endif
+
+layout(set = 3, binding = 6) uniform StandardMaterial_reflectance {
+    float Reflectance;
+};
+layout(set = 3, binding = 7) uniform StandardMaterial_metallic {
+    float Metallic;
+};
+layout(set = 3, binding = 8) uniform StandardMaterial_roughness {
+    float Roughness;
+};
+
+void main() {
+    vec4 albedo = Albedo;
+# ifdef MESH_VERTEX_COLOR
+    albedo *= v_Color;
+# endif
+# ifdef STANDARDMATERIAL_ALBEDO_TEXTURE
+    albedo *= texture(
+        sampler2D(StandardMaterial_albedo_texture, StandardMaterial_albedo_texture_sampler),
+        v_Uv);
+# endif
+# ifdef MATERIAL_OVERRIDES
+    albedo *= Tint;
+# endif
+    // the g-buffer holds one opaque surface per pixel, so transparency becomes a cutout
+    if (albedo.a < 0.5) {
+        discard;
+    }
+
+    o_Albedo = vec4(albedo.rgb, 1.0);
+# ifdef STANDARDMATERIAL_SHADED
+    o_Normal = vec4(normalize(v_Normal), 1.0);
+# else
+    o_Normal = vec4(0.0);
+# endif
+    o_MetallicRoughness = vec4(Metallic, Roughness, Reflectance, 0.0);
+}
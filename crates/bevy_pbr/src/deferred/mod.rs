@@ -0,0 +1,431 @@
+//! The deferred render path. [StandardMaterial](crate::StandardMaterial) entities are drawn into a g-buffer of albedo,
+//! normal, metallic-roughness and depth textures, and a fullscreen pass lights every pixel once. The cost of lights
+//! then depends on the number of pixels instead of the number of entities they touch, and
+//! [DEFERRED_MAX_LIGHTS] lights are supported instead of 10.
+//!
+//! The lighting pass is drawn in the main pass and writes the g-buffer's depth, so forward rendered entities like
+//! sprites, terrain and water are depth tested against the lit surfaces as usual. The g-buffer holds one opaque surface
+//! per pixel: transparent materials are cut out at half alpha, and lightmaps, reflections and emissive
+//! [MaterialOverrides](crate::MaterialOverrides) are only supported by the forward path.
+
+use crate::render_graph::node;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Query, Res, ResMut, Resources};
+use bevy_math::{Mat4, Vec2};
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    draw::Draw,
+    mesh::{shape, Mesh},
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor,
+        RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, BlendFactor, BlendOperation, ColorStateDescriptor, ColorWrite,
+        CompareFunction, CullMode, DepthStencilStateDescriptor, FrontFace, PipelineDescriptor,
+        RasterizationStateDescriptor, RenderPipelines, StencilStateDescriptor,
+        StencilStateFaceDescriptor,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, MainPass},
+        AssetRenderResourcesNode, AssetTextureNode, PassNode, RenderGraph,
+    },
+    renderer::RenderResources,
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{FilterMode, Texture, TextureFormat},
+};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_type_registry::TypeUuid;
+use bevy_window::Windows;
+
+/// The number of lights the deferred lighting pass supports
+pub const DEFERRED_MAX_LIGHTS: usize = 128;
+
+pub const GBUFFER_ALBEDO_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 8263049175302846621);
+
+/// World space normals, with 1.0 in the alpha channel of shaded surfaces
+pub const GBUFFER_NORMAL_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 3109573826450912874);
+
+/// Metallic, roughness and reflectance in the red, green and blue channels
+pub const GBUFFER_METALLIC_ROUGHNESS_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 14728365019283745610);
+
+pub const GBUFFER_DEPTH_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 5820473619284756301);
+
+pub const DEFERRED_LIGHTING_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 11937402856173928460);
+
+pub const DEFERRED_LIGHTING_MATERIAL_HANDLE: Handle<DeferredLightingMaterial> =
+    Handle::weak_from_u64(DeferredLightingMaterial::TYPE_UUID, 2649173850261937485);
+
+/// A quad that covers the whole viewport in normalized device coordinates
+pub const DEFERRED_LIGHTING_QUAD_HANDLE: Handle<Mesh> =
+    Handle::weak_from_u64(Mesh::TYPE_UUID, 9173648205917364820);
+
+/// How [StandardMaterial](crate::StandardMaterial) entities are lit. Insert it before adding the
+/// [PbrPlugin](crate::PbrPlugin) to pick a path, it can't be changed afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderPath {
+    /// Every entity is lit by every light while it is drawn
+    Forward,
+    /// Entities are drawn into a g-buffer that is lit in a fullscreen pass. Suits scenes with many lights. See the
+    /// [deferred](crate::deferred) module for its limitations.
+    Deferred,
+}
+
+impl Default for RenderPath {
+    fn default() -> Self {
+        RenderPath::Forward
+    }
+}
+
+/// Lights the g-buffer. Positions are reconstructed from the depth with the 3d camera's inverse view projection.
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "5d8e2c71-0f4b-4c3a-9e6d-2a7b81c4f093"]
+pub struct DeferredLightingMaterial {
+    pub inverse_view_projection: Mat4,
+    pub albedo: Handle<Texture>,
+    pub normal: Handle<Texture>,
+    pub metallic_roughness: Handle<Texture>,
+    pub depth: Handle<Texture>,
+}
+
+impl Default for DeferredLightingMaterial {
+    fn default() -> Self {
+        DeferredLightingMaterial {
+            inverse_view_projection: Mat4::identity(),
+            albedo: GBUFFER_ALBEDO_TEXTURE_HANDLE,
+            normal: GBUFFER_NORMAL_TEXTURE_HANDLE,
+            metallic_roughness: GBUFFER_METALLIC_ROUGHNESS_TEXTURE_HANDLE,
+            depth: GBUFFER_DEPTH_TEXTURE_HANDLE,
+        }
+    }
+}
+
+/// Marks the quad that lights the g-buffer
+#[derive(Debug, Default)]
+pub struct DeferredLighting;
+
+/// The g-buffer pipeline, which replaces the forward pipeline in the deferred path. It is only drawn by the g-buffer
+/// pass.
+pub(crate) fn build_gbuffer_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    let color_state = |format| ColorStateDescriptor {
+        format,
+        color_blend: BlendDescriptor::REPLACE,
+        alpha_blend: BlendDescriptor::REPLACE,
+        write_mask: ColorWrite::ALL,
+    };
+    PipelineDescriptor {
+        pass_tag: Some(node::GBUFFER_PASS.into()),
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![
+            color_state(TextureFormat::Rgba8UnormSrgb),
+            color_state(TextureFormat::Rgba16Float),
+            color_state(TextureFormat::Rgba8Unorm),
+        ],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("../render_graph/forward_pipeline/forward.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("gbuffer.frag"),
+            ))),
+        })
+    }
+}
+
+pub(crate) fn build_deferred_lighting_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::Zero,
+                operation: BlendOperation::Add,
+            },
+            alpha_blend: BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::Zero,
+                operation: BlendOperation::Add,
+            },
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("deferred_lighting.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("deferred_lighting.frag"),
+            ))),
+        })
+    }
+}
+
+pub(crate) fn setup_deferred_resources(resources: &Resources) {
+    let mut textures = resources.get_mut::<Assets<Texture>>().unwrap();
+    // the textures are resized to the window before the first frame is rendered
+    for (handle, format) in [
+        (GBUFFER_ALBEDO_TEXTURE_HANDLE, TextureFormat::Rgba8UnormSrgb),
+        (GBUFFER_NORMAL_TEXTURE_HANDLE, TextureFormat::Rgba16Float),
+        (
+            GBUFFER_METALLIC_ROUGHNESS_TEXTURE_HANDLE,
+            TextureFormat::Rgba8Unorm,
+        ),
+        (GBUFFER_DEPTH_TEXTURE_HANDLE, TextureFormat::Depth32Float),
+    ]
+    .iter()
+    {
+        let mut texture = Texture::new_render_target(Vec2::new(1.0, 1.0), *format);
+        // interpolating between the surfaces of neighboring pixels would light points that don't exist
+        texture.sampler.min_filter = FilterMode::Nearest;
+        texture.sampler.mag_filter = FilterMode::Nearest;
+        textures.set_untracked(handle.clone_weak(), texture);
+    }
+
+    resources
+        .get_mut::<Assets<DeferredLightingMaterial>>()
+        .unwrap()
+        .set_untracked(
+            DEFERRED_LIGHTING_MATERIAL_HANDLE,
+            DeferredLightingMaterial::default(),
+        );
+    resources.get_mut::<Assets<Mesh>>().unwrap().set_untracked(
+        DEFERRED_LIGHTING_QUAD_HANDLE,
+        Mesh::from(shape::Quad::new(Vec2::new(2.0, 2.0))),
+    );
+    resources
+        .get_mut::<Assets<PipelineDescriptor>>()
+        .unwrap()
+        .set_untracked(
+            DEFERRED_LIGHTING_PIPELINE_HANDLE,
+            build_deferred_lighting_pipeline(&mut resources.get_mut::<Assets<Shader>>().unwrap()),
+        );
+}
+
+pub(crate) fn spawn_deferred_lighting(mut commands: Commands) {
+    commands.spawn((
+        DEFERRED_LIGHTING_QUAD_HANDLE,
+        DEFERRED_LIGHTING_MATERIAL_HANDLE,
+        Draw::default(),
+        RenderPipelines::from_handles(&[DEFERRED_LIGHTING_PIPELINE_HANDLE]),
+        MainPass,
+        DeferredLighting,
+    ));
+}
+
+/// Keeps the g-buffer the size of the primary window and the lighting material's inverse view projection up to date
+pub fn deferred_lighting_system(
+    windows: Res<Windows>,
+    active_cameras: Res<ActiveCameras>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<DeferredLightingMaterial>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    if let Some(window) = windows.get_primary() {
+        let window_size = Vec2::new(window.width().max(1) as f32, window.height().max(1) as f32);
+        for handle in [
+            GBUFFER_ALBEDO_TEXTURE_HANDLE,
+            GBUFFER_NORMAL_TEXTURE_HANDLE,
+            GBUFFER_METALLIC_ROUGHNESS_TEXTURE_HANDLE,
+            GBUFFER_DEPTH_TEXTURE_HANDLE,
+        ]
+        .iter()
+        {
+            let texture_size = textures.get(handle).map(|texture| texture.size);
+            if texture_size.map_or(false, |size| size != window_size) {
+                // the changed texture is recreated at the new size
+                textures.get_mut(handle).unwrap().size = window_size;
+            }
+        }
+    }
+
+    if let Some((camera, camera_transform)) = active_cameras
+        .get(base::camera::CAMERA3D)
+        .and_then(|entity| cameras.get(entity).ok())
+    {
+        let inverse_view_projection =
+            (camera.projection_matrix * camera_transform.compute_matrix().inverse()).inverse();
+        let unchanged = materials
+            .get(&DEFERRED_LIGHTING_MATERIAL_HANDLE)
+            .map_or(true, |material| {
+                material.inverse_view_projection == inverse_view_projection
+            });
+        if !unchanged {
+            materials
+                .get_mut(&DEFERRED_LIGHTING_MATERIAL_HANDLE)
+                .unwrap()
+                .inverse_view_projection = inverse_view_projection;
+        }
+    }
+}
+
+pub(crate) fn add_deferred_graph(graph: &mut RenderGraph) {
+    graph.add_system_node(
+        node::DEFERRED_LIGHTING_MATERIAL,
+        AssetRenderResourcesNode::<DeferredLightingMaterial>::new(false),
+    );
+    graph
+        .add_node_edge(node::DEFERRED_LIGHTING_MATERIAL, base::node::MAIN_PASS)
+        .unwrap();
+
+    let color_attachment = |name: &str| RenderPassColorAttachmentDescriptor {
+        attachment: TextureAttachment::Input(name.to_string()),
+        resolve_target: None,
+        ops: Operations {
+            load: LoadOp::Clear(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+            store: true,
+        },
+    };
+    let mut gbuffer_pass_node = PassNode::<&MainPass>::new(PassDescriptor {
+        color_attachments: vec![
+            color_attachment("albedo"),
+            color_attachment("normal"),
+            color_attachment("metallic_roughness"),
+        ],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    });
+    gbuffer_pass_node.add_camera(base::camera::CAMERA3D);
+    gbuffer_pass_node.set_pass_tag(node::GBUFFER_PASS);
+
+    // the g-buffer pass draws the same entities as the main pass, so it depends on the same nodes
+    let main_pass_dependencies = graph
+        .iter_node_inputs(base::node::MAIN_PASS)
+        .map(|inputs| {
+            inputs
+                .filter(|(_edge, node)| {
+                    node.name.as_deref() != Some(node::DEFERRED_LIGHTING_MATERIAL)
+                })
+                .map(|(_edge, node)| node.id)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    graph.add_node(node::GBUFFER_PASS, gbuffer_pass_node);
+    for dependency in main_pass_dependencies {
+        // a node can be connected to the main pass more than once, in which case the edge already exists
+        let _ = graph.add_node_edge(dependency, node::GBUFFER_PASS);
+    }
+    graph
+        .add_node_edge(node::GBUFFER_PASS, base::node::MAIN_PASS)
+        .unwrap();
+
+    for (texture_node, handle, input) in [
+        (
+            node::GBUFFER_ALBEDO_TEXTURE,
+            GBUFFER_ALBEDO_TEXTURE_HANDLE,
+            "albedo",
+        ),
+        (
+            node::GBUFFER_NORMAL_TEXTURE,
+            GBUFFER_NORMAL_TEXTURE_HANDLE,
+            "normal",
+        ),
+        (
+            node::GBUFFER_METALLIC_ROUGHNESS_TEXTURE,
+            GBUFFER_METALLIC_ROUGHNESS_TEXTURE_HANDLE,
+            "metallic_roughness",
+        ),
+        (
+            node::GBUFFER_DEPTH_TEXTURE,
+            GBUFFER_DEPTH_TEXTURE_HANDLE,
+            "depth",
+        ),
+    ]
+    .iter()
+    {
+        graph.add_node(*texture_node, AssetTextureNode::new(handle.clone_weak()));
+        graph
+            .add_slot_edge(
+                *texture_node,
+                AssetTextureNode::OUT_TEXTURE,
+                node::GBUFFER_PASS,
+                *input,
+            )
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_render::render_graph::Node;
+
+    #[test]
+    fn gbuffer_pass_inputs() {
+        let mut graph = RenderGraph::default();
+        graph.add_node(
+            base::node::MAIN_PASS,
+            PassNode::<&MainPass>::new(PassDescriptor {
+                color_attachments: Vec::new(),
+                depth_stencil_attachment: None,
+                sample_count: 1,
+            }),
+        );
+        add_deferred_graph(&mut graph);
+
+        let gbuffer_pass = graph.get_node_state(node::GBUFFER_PASS).unwrap();
+        let inputs = gbuffer_pass
+            .node
+            .input()
+            .iter()
+            .map(|input| input.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            inputs,
+            vec!["albedo", "normal", "metallic_roughness", "depth"]
+        );
+    }
+}
@@ -26,21 +26,7 @@ impl Default for PbrComponents {
         Self {
             render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
                 FORWARD_PIPELINE_HANDLE,
-                PipelineSpecialization {
-                    dynamic_bindings: vec![
-                        // Transform
-                        DynamicBinding {
-                            bind_group: 2,
-                            binding: 0,
-                        },
-                        // StandardMaterial_albedo
-                        DynamicBinding {
-                            bind_group: 3,
-                            binding: 0,
-                        },
-                    ],
-                    ..Default::default()
-                },
+                forward_pipeline_specialization(),
             )]),
             mesh: Default::default(),
             material: Default::default(),
@@ -52,6 +38,45 @@ impl Default for PbrComponents {
     }
 }
 
+/// The specialization of the forward pipelines of [PbrComponents], with their uniforms bound dynamically
+pub fn forward_pipeline_specialization() -> PipelineSpecialization {
+    PipelineSpecialization {
+        dynamic_bindings: vec![
+            // Transform
+            DynamicBinding {
+                bind_group: 2,
+                binding: 0,
+            },
+            // MaterialOverrides
+            DynamicBinding {
+                bind_group: 2,
+                binding: 1,
+            },
+            // ProbeReflection_volume
+            DynamicBinding {
+                bind_group: 2,
+                binding: 2,
+            },
+            // StandardMaterial_albedo
+            DynamicBinding {
+                bind_group: 3,
+                binding: 0,
+            },
+            // StandardMaterial_uv_transform
+            DynamicBinding {
+                bind_group: 3,
+                binding: 3,
+            },
+            // StandardMaterial_reflectance
+            DynamicBinding {
+                bind_group: 3,
+                binding: 6,
+            },
+        ],
+        ..Default::default()
+    }
+}
+
 /// A component bundle for "light" entities
 #[derive(Debug, Bundle, Default)]
 pub struct LightComponents {
@@ -0,0 +1,16 @@
+#version 450
+
+layout(location = 0) in vec3 v_Normal;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 2, binding = 0) uniform GizmoMaterial_color {
+    vec4 Color;
+};
+
+void main() {
+    // a fixed light keeps the handles readable whatever the scene's lighting is
+    vec3 light_direction = normalize(vec3(0.3, 1.0, 0.5));
+    float light = 0.6 + 0.4 * max(dot(normalize(v_Normal), light_direction), 0.0);
+    o_Target = vec4(Color.rgb * light, Color.a);
+}
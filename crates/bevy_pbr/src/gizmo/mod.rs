@@ -0,0 +1,860 @@
+//! Handles for moving, rotating and scaling an entity with the mouse, the building block of in-game level editors.
+//!
+//! Set [Gizmo::selected] to show handles on an entity and [Gizmo::mode] to pick which ones. Dragging a handle with
+//! [Gizmo::button] sends [GizmoDelta] events, which the [GizmoPlugin] applies to the entity's [Transform] unless
+//! `apply_deltas` is off, for example to route the changes through an undo history.
+//!
+//! The handles are children of a [GizmoRoot] that follows the selected entity and keeps the same size on screen. The
+//! 3d camera draws them in their own pass after the main pass, on top of the scene. They are picked by intersecting
+//! the ray under the cursor with their shapes, so they don't need a collider.
+
+use crate::render_graph;
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_ecs::{Commands, Entity, IntoQuerySystem, Local, Query, Res, ResMut, With};
+use bevy_input::{mouse::MouseButton, Input};
+use bevy_math::{Mat4, Quat, Vec2, Vec3};
+use bevy_render::{
+    camera::{ActiveCameras, Camera, Ray},
+    draw::Draw,
+    mesh::{shape, Mesh},
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+        TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, ColorStateDescriptor, ColorWrite, CompareFunction, CullMode,
+        DepthStencilStateDescriptor, FrontFace, PipelineDescriptor, RasterizationStateDescriptor,
+        RenderPipelines, StencilStateDescriptor, StencilStateFaceDescriptor,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, Msaa, MsaaTexture},
+        AssetRenderResourcesNode, PassNode, RenderGraph, WindowSwapChainNode, WindowTextureNode,
+    },
+    renderer::RenderResources,
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+};
+use bevy_transform::prelude::{BuildChildren, GlobalTransform, Parent, Transform};
+use bevy_type_registry::TypeUuid;
+use bevy_window::{CursorMoved, WindowId, Windows};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+pub const GIZMO_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 7305182946650183925);
+
+/// The id of the first gizmo material. The axes use consecutive ids, followed by the highlight material.
+const GIZMO_MATERIAL_ID: u64 = 1859302746518392650;
+
+/// The id of the translate handle mesh. The rotate and scale handles use the next ids.
+const GIZMO_MESH_ID: u64 = 9940176325810475312;
+
+/// The length of the translate and scale handles, relative to the size of the gizmo
+const HANDLE_LENGTH: f32 = 1.0;
+
+/// The radius of the rotate handles, relative to the size of the gizmo
+const RING_RADIUS: f32 = 0.9;
+
+/// How close the cursor has to be to a handle to pick it, relative to the size of the gizmo
+const PICK_DISTANCE: f32 = 0.08;
+
+pub mod node {
+    pub const GIZMO_MATERIAL: &str = "gizmo_material";
+    pub const GIZMO_DEPTH_TEXTURE: &str = "gizmo_depth_texture";
+    pub const GIZMO_PASS: &str = "gizmo_pass";
+}
+
+/// The flat color of a handle
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "3b9e6f02-71c4-4d8a-a5e3-c08f2d6b1947"]
+pub struct GizmoMaterial {
+    pub color: Color,
+}
+
+/// Which handles the gizmo shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GizmoMode {
+    /// Arrows that move the entity along an axis
+    Translate,
+    /// Rings that rotate the entity around an axis
+    Rotate,
+    /// Boxes that scale the entity along one of its local axes
+    Scale,
+}
+
+impl GizmoMode {
+    const ALL: [GizmoMode; 3] = [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale];
+
+    fn mesh_handle(self) -> Handle<Mesh> {
+        let index = GizmoMode::ALL
+            .iter()
+            .position(|mode| *mode == self)
+            .unwrap();
+        Handle::weak_from_u64(Mesh::TYPE_UUID, GIZMO_MESH_ID + index as u64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GizmoAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl GizmoAxis {
+    pub const ALL: [GizmoAxis; 3] = [GizmoAxis::X, GizmoAxis::Y, GizmoAxis::Z];
+
+    pub fn unit(self) -> Vec3 {
+        match self {
+            GizmoAxis::X => Vec3::unit_x(),
+            GizmoAxis::Y => Vec3::unit_y(),
+            GizmoAxis::Z => Vec3::unit_z(),
+        }
+    }
+
+    /// The handle meshes point along the x axis, this turns them to point along this axis
+    fn handle_rotation(self) -> Quat {
+        match self {
+            GizmoAxis::X => Quat::identity(),
+            GizmoAxis::Y => Quat::from_rotation_z(FRAC_PI_2),
+            GizmoAxis::Z => Quat::from_rotation_y(-FRAC_PI_2),
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            GizmoAxis::X => Color::rgb(0.9, 0.2, 0.2),
+            GizmoAxis::Y => Color::rgb(0.3, 0.85, 0.3),
+            GizmoAxis::Z => Color::rgb(0.25, 0.4, 0.95),
+        }
+    }
+
+    fn material_handle(self) -> Handle<GizmoMaterial> {
+        let index = GizmoAxis::ALL
+            .iter()
+            .position(|axis| *axis == self)
+            .unwrap();
+        Handle::weak_from_u64(GizmoMaterial::TYPE_UUID, GIZMO_MATERIAL_ID + index as u64)
+    }
+}
+
+const HIGHLIGHT_MATERIAL_HANDLE: Handle<GizmoMaterial> =
+    Handle::weak_from_u64(GizmoMaterial::TYPE_UUID, GIZMO_MATERIAL_ID + 3);
+
+/// The entity the gizmo manipulates, and how
+#[derive(Debug, Clone)]
+pub struct Gizmo {
+    pub selected: Option<Entity>,
+    pub mode: GizmoMode,
+    /// Moves and rotates along the selected entity's axes instead of the world axes. Scaling always uses the entity's
+    /// axes.
+    pub local: bool,
+    /// The size of the gizmo, relative to its distance to the camera, so it keeps the same size on screen
+    pub size: f32,
+    pub button: MouseButton,
+    hovered: Option<GizmoAxis>,
+    drag: Option<GizmoDrag>,
+}
+
+impl Default for Gizmo {
+    fn default() -> Self {
+        Gizmo {
+            selected: None,
+            mode: GizmoMode::Translate,
+            local: false,
+            size: 0.15,
+            button: MouseButton::Left,
+            hovered: None,
+            drag: None,
+        }
+    }
+}
+
+impl Gizmo {
+    /// The handle under the cursor
+    pub fn hovered(&self) -> Option<GizmoAxis> {
+        self.hovered
+    }
+
+    /// The handle being dragged
+    pub fn dragged(&self) -> Option<GizmoAxis> {
+        self.drag.as_ref().map(|drag| drag.axis)
+    }
+
+    /// Whether a handle is being dragged. Other systems that use the mouse button should usually ignore it meanwhile.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+}
+
+/// A change to the selected entity made by dragging a handle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoDelta {
+    pub entity: Entity,
+    /// A world space offset
+    pub translation: Vec3,
+    /// A world space rotation around the entity's origin
+    pub rotation: Quat,
+    /// A factor along the entity's local axes
+    pub scale: Vec3,
+}
+
+impl GizmoDelta {
+    fn identity(entity: Entity) -> Self {
+        GizmoDelta {
+            entity,
+            translation: Vec3::zero(),
+            rotation: Quat::identity(),
+            scale: Vec3::one(),
+        }
+    }
+
+    /// Applies the change to the [Transform] of an entity whose parent has the `parent` [GlobalTransform]
+    pub fn apply(&self, transform: &mut Transform, parent: Option<&GlobalTransform>) {
+        let (parent_rotation, parent_scale) = parent
+            .map_or((Quat::identity(), Vec3::one()), |parent| {
+                (parent.rotation, parent.scale)
+            });
+        let inverse_parent_rotation = parent_rotation.conjugate();
+        transform.translation += inverse_parent_rotation * self.translation / parent_scale;
+        transform.rotation =
+            (inverse_parent_rotation * self.rotation * parent_rotation * transform.rotation)
+                .normalize();
+        transform.scale *= self.scale;
+    }
+}
+
+/// Follows the selected entity. The handles are its children.
+#[derive(Debug, Default)]
+pub struct GizmoRoot;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoHandle {
+    pub axis: GizmoAxis,
+    pub mode: GizmoMode,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GizmoDrag {
+    entity: Entity,
+    axis: GizmoAxis,
+    mode: GizmoMode,
+    /// Where the gizmo was when the drag started. The handle is dragged along the lines and planes through it.
+    origin: Vec3,
+    direction: Vec3,
+    /// How far along the handle's axis the cursor was last, for translate and scale handles
+    last_distance: f32,
+    /// Where the cursor was last relative to the origin, in the handle's plane, for rotate handles
+    last_offset: Vec3,
+}
+
+impl GizmoDrag {
+    fn start(
+        entity: Entity,
+        axis: GizmoAxis,
+        mode: GizmoMode,
+        origin: Vec3,
+        direction: Vec3,
+        ray: &Ray,
+    ) -> Option<Self> {
+        let mut drag = GizmoDrag {
+            entity,
+            axis,
+            mode,
+            origin,
+            direction,
+            last_distance: 0.0,
+            last_offset: Vec3::zero(),
+        };
+        match mode {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                drag.last_distance = ray.closest_to_line(origin, direction)?.1;
+            }
+            GizmoMode::Rotate => {
+                drag.last_offset = ray.at(ray.intersect_plane(origin, direction)?) - origin;
+            }
+        }
+        Some(drag)
+    }
+
+    /// The change since the last update, if the cursor moved
+    fn update(&mut self, ray: &Ray) -> Option<GizmoDelta> {
+        let mut delta = GizmoDelta::identity(self.entity);
+        match self.mode {
+            GizmoMode::Translate => {
+                let distance = ray.closest_to_line(self.origin, self.direction)?.1;
+                delta.translation = self.direction * (distance - self.last_distance);
+                self.last_distance = distance;
+            }
+            GizmoMode::Scale => {
+                let distance = ray.closest_to_line(self.origin, self.direction)?.1;
+                // the scale can't be dragged through zero
+                if distance <= std::f32::EPSILON || self.last_distance <= std::f32::EPSILON {
+                    return None;
+                }
+                let factor = distance / self.last_distance;
+                delta.scale = match self.axis {
+                    GizmoAxis::X => Vec3::new(factor, 1.0, 1.0),
+                    GizmoAxis::Y => Vec3::new(1.0, factor, 1.0),
+                    GizmoAxis::Z => Vec3::new(1.0, 1.0, factor),
+                };
+                self.last_distance = distance;
+            }
+            GizmoMode::Rotate => {
+                let offset =
+                    ray.at(ray.intersect_plane(self.origin, self.direction)?) - self.origin;
+                if offset.length_squared() <= std::f32::EPSILON {
+                    return None;
+                }
+                let angle = self
+                    .last_offset
+                    .cross(offset)
+                    .dot(self.direction)
+                    .atan2(self.last_offset.dot(offset));
+                delta.rotation = Quat::from_axis_angle(self.direction, angle);
+                self.last_offset = offset;
+            }
+        }
+
+        if delta == GizmoDelta::identity(self.entity) {
+            None
+        } else {
+            Some(delta)
+        }
+    }
+}
+
+/// The handle `ray` hits first, for a gizmo at `origin` with `rotation` and `size`
+fn pick_handle(
+    ray: &Ray,
+    origin: Vec3,
+    rotation: Quat,
+    size: f32,
+    mode: GizmoMode,
+) -> Option<GizmoAxis> {
+    let pick_distance = PICK_DISTANCE * size;
+    GizmoAxis::ALL
+        .iter()
+        .filter_map(|axis| {
+            let direction = rotation * axis.unit();
+            let ray_distance = match mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    let (ray_distance, line_distance) = ray.closest_to_line(origin, direction)?;
+                    if line_distance < 0.0 || line_distance > HANDLE_LENGTH * size {
+                        return None;
+                    }
+                    let closest = origin + direction * line_distance;
+                    if (ray.at(ray_distance) - closest).length() > pick_distance {
+                        return None;
+                    }
+                    ray_distance
+                }
+                GizmoMode::Rotate => {
+                    let ray_distance = ray.intersect_plane(origin, direction)?;
+                    let radius = (ray.at(ray_distance) - origin).length();
+                    if (radius - RING_RADIUS * size).abs() > pick_distance {
+                        return None;
+                    }
+                    ray_distance
+                }
+            };
+            Some((*axis, ray_distance))
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(axis, _)| axis)
+}
+
+/// Shows handles on [Gizmo::selected] and sends [GizmoDelta] events when they are dragged
+pub struct GizmoPlugin {
+    /// Applies the [GizmoDelta] events to the selected entity's [Transform]
+    pub apply_deltas: bool,
+}
+
+impl Default for GizmoPlugin {
+    fn default() -> Self {
+        GizmoPlugin { apply_deltas: true }
+    }
+}
+
+impl Plugin for GizmoPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if app.resources().get::<Gizmo>().is_none() {
+            app.resources_mut().insert(Gizmo::default());
+        }
+        app.add_asset::<GizmoMaterial>()
+            .add_event::<GizmoDelta>()
+            .add_startup_system(spawn_gizmo.system())
+            .add_system_to_stage(stage::PRE_UPDATE, gizmo_system.system());
+        if self.apply_deltas {
+            app.add_system(apply_gizmo_delta_system.system());
+        }
+
+        let resources = app.resources();
+        let mut materials = resources.get_mut::<Assets<GizmoMaterial>>().unwrap();
+        for axis in GizmoAxis::ALL.iter() {
+            materials.set_untracked(
+                axis.material_handle(),
+                GizmoMaterial {
+                    color: axis.color(),
+                },
+            );
+        }
+        materials.set_untracked(
+            HIGHLIGHT_MATERIAL_HANDLE,
+            GizmoMaterial {
+                color: Color::rgb(1.0, 0.85, 0.2),
+            },
+        );
+        let mut meshes = resources.get_mut::<Assets<Mesh>>().unwrap();
+        for mode in GizmoMode::ALL.iter() {
+            meshes.set_untracked(mode.mesh_handle(), handle_mesh(*mode));
+        }
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(
+                GIZMO_PIPELINE_HANDLE,
+                build_gizmo_pipeline(&mut resources.get_mut::<Assets<Shader>>().unwrap()),
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_gizmo_graph(&mut render_graph);
+    }
+}
+
+/// Builds the mesh of a handle that points along the x axis, from boxes given as their center, half size and rotation
+fn handle_mesh(mode: GizmoMode) -> Mesh {
+    let shaft = (
+        Vec3::new(HANDLE_LENGTH * 0.5, 0.0, 0.0),
+        Vec3::new(HANDLE_LENGTH * 0.4, 0.015, 0.015),
+        Quat::identity(),
+    );
+    let boxes = match mode {
+        GizmoMode::Translate => vec![
+            shaft,
+            (
+                Vec3::new(HANDLE_LENGTH * 0.92, 0.0, 0.0),
+                Vec3::new(0.08, 0.04, 0.04),
+                Quat::identity(),
+            ),
+        ],
+        GizmoMode::Scale => vec![
+            shaft,
+            (
+                Vec3::new(HANDLE_LENGTH * 0.93, 0.0, 0.0),
+                Vec3::splat(0.06),
+                Quat::identity(),
+            ),
+        ],
+        GizmoMode::Rotate => {
+            // a ring around the x axis, made of short segments
+            let segments = 48;
+            let segment_length = PI * RING_RADIUS / segments as f32 * 1.1;
+            (0..segments)
+                .map(|i| {
+                    let angle = i as f32 / segments as f32 * 2.0 * PI;
+                    (
+                        Vec3::new(0.0, angle.cos(), angle.sin()) * RING_RADIUS,
+                        Vec3::new(0.015, 0.015, segment_length),
+                        Quat::from_rotation_x(angle),
+                    )
+                })
+                .collect()
+        }
+    };
+
+    let cube = Mesh::from(shape::Cube { size: 1.0 });
+    let mut mesh: Option<Mesh> = None;
+    for (center, half_size, rotation) in boxes {
+        let transform = Mat4::from_scale_rotation_translation(half_size, rotation, center);
+        match mesh {
+            Some(ref mut mesh) => mesh.merge(&cube, transform).unwrap(),
+            None => {
+                let mut first = cube.clone();
+                first.transform_by(transform);
+                mesh = Some(first);
+            }
+        }
+    }
+    mesh.unwrap()
+}
+
+pub fn build_gizmo_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("gizmo.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("gizmo.frag"),
+            ))),
+        })
+    }
+}
+
+fn spawn_gizmo(mut commands: Commands) {
+    commands
+        .spawn((GizmoRoot, Transform::default(), GlobalTransform::default()))
+        .with_children(|parent| {
+            for mode in GizmoMode::ALL.iter() {
+                for axis in GizmoAxis::ALL.iter() {
+                    parent.spawn((
+                        mode.mesh_handle(),
+                        axis.material_handle(),
+                        Draw {
+                            is_visible: false,
+                            ..Default::default()
+                        },
+                        RenderPipelines::from_handles(&[GIZMO_PIPELINE_HANDLE]),
+                        GizmoHandle {
+                            axis: *axis,
+                            mode: *mode,
+                        },
+                        Transform::from_rotation(axis.handle_rotation()),
+                        GlobalTransform::default(),
+                    ));
+                }
+            }
+        });
+}
+
+#[derive(Default)]
+pub struct GizmoSystemState {
+    cursor_moved_event_reader: EventReader<CursorMoved>,
+    cursor_position: Option<Vec2>,
+}
+
+/// Moves the [GizmoRoot] to the selected entity, picks and drags handles, and highlights the active handle
+#[allow(clippy::too_many_arguments)]
+pub fn gizmo_system(
+    mut state: Local<GizmoSystemState>,
+    cursor_moved_events: Res<Events<CursorMoved>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    active_cameras: Res<ActiveCameras>,
+    mut gizmo: ResMut<Gizmo>,
+    mut delta_events: ResMut<Events<GizmoDelta>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    target_query: Query<&GlobalTransform>,
+    mut root_query: Query<With<GizmoRoot, &mut Transform>>,
+    mut handle_query: Query<(&GizmoHandle, &mut Draw, &mut Handle<GizmoMaterial>)>,
+) {
+    if let Some(event) = state.cursor_moved_event_reader.latest(&cursor_moved_events) {
+        state.cursor_position = Some(event.position);
+    }
+    // borrows the fields separately
+    let gizmo = &mut *gizmo;
+
+    let camera = active_cameras
+        .get(base::camera::CAMERA3D)
+        .and_then(|entity| camera_query.get(entity).ok());
+    let target = gizmo.selected.and_then(|entity| {
+        target_query
+            .get(entity)
+            .ok()
+            .map(|target| (entity, *target))
+    });
+    let (entity, target, (camera, camera_transform)) = match (target, camera) {
+        (Some((entity, target)), Some(camera)) => (entity, target, camera),
+        _ => {
+            gizmo.hovered = None;
+            gizmo.drag = None;
+            for (_handle, mut draw, _material) in handle_query.iter_mut() {
+                if draw.is_visible {
+                    draw.is_visible = false;
+                }
+            }
+            return;
+        }
+    };
+
+    let origin = target.translation;
+    let rotation = if gizmo.local || gizmo.mode == GizmoMode::Scale {
+        target.rotation
+    } else {
+        Quat::identity()
+    };
+    let size = (camera_transform.translation - origin).length() * gizmo.size;
+    for mut transform in root_query.iter_mut() {
+        *transform = Transform {
+            translation: origin,
+            rotation,
+            scale: Vec3::splat(size),
+        };
+    }
+
+    let ray = state.cursor_position.and_then(|cursor_position| {
+        camera.screen_to_ray(&windows, camera_transform, cursor_position)
+    });
+    let drag_lost = gizmo.drag.map_or(false, |drag| {
+        drag.entity != entity || drag.mode != gizmo.mode
+    });
+    if drag_lost || mouse_button_input.just_released(gizmo.button) {
+        gizmo.drag = None;
+    }
+
+    if let Some(ref mut drag) = gizmo.drag {
+        if let Some(delta) = ray.as_ref().and_then(|ray| drag.update(ray)) {
+            delta_events.send(delta);
+        }
+    } else {
+        gizmo.hovered = ray
+            .as_ref()
+            .and_then(|ray| pick_handle(ray, origin, rotation, size, gizmo.mode));
+        if let (Some(axis), Some(ray)) = (gizmo.hovered, ray.as_ref()) {
+            if mouse_button_input.just_pressed(gizmo.button) {
+                let direction = rotation * axis.unit();
+                gizmo.drag = GizmoDrag::start(entity, axis, gizmo.mode, origin, direction, ray);
+            }
+        }
+    }
+
+    let active = gizmo.dragged().or(gizmo.hovered);
+    for (handle, mut draw, mut material) in handle_query.iter_mut() {
+        let visible = handle.mode == gizmo.mode;
+        if draw.is_visible != visible {
+            draw.is_visible = visible;
+        }
+        let handle_material = if active == Some(handle.axis) {
+            HIGHLIGHT_MATERIAL_HANDLE
+        } else {
+            handle.axis.material_handle()
+        };
+        if *material != handle_material {
+            *material = handle_material;
+        }
+    }
+}
+
+/// Applies [GizmoDelta] events to the [Transform] of their entity
+pub fn apply_gizmo_delta_system(
+    mut delta_event_reader: Local<EventReader<GizmoDelta>>,
+    delta_events: Res<Events<GizmoDelta>>,
+    mut query: Query<(&mut Transform, Option<&Parent>)>,
+    parent_query: Query<&GlobalTransform>,
+) {
+    for delta in delta_event_reader.iter(&delta_events) {
+        if let Ok((mut transform, parent)) = query.get_mut(delta.entity) {
+            let parent_transform = parent.and_then(|parent| parent_query.get(parent.0).ok());
+            delta.apply(&mut transform, parent_transform);
+        }
+    }
+}
+
+fn add_gizmo_graph(graph: &mut RenderGraph) {
+    graph.add_system_node(
+        node::GIZMO_MATERIAL,
+        AssetRenderResourcesNode::<GizmoMaterial>::new(false),
+    );
+    // the handles have their own depth buffer, so they are drawn on top of the scene
+    graph.add_node(
+        node::GIZMO_DEPTH_TEXTURE,
+        WindowTextureNode::multisampled(
+            WindowId::primary(),
+            TextureDescriptor {
+                size: Extent3d {
+                    depth: 1,
+                    width: 1,
+                    height: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Depth32Float,
+                usage: TextureUsage::OUTPUT_ATTACHMENT,
+            },
+            MsaaTexture::Depth,
+        ),
+    );
+
+    let mut gizmo_pass_node = PassNode::<&GizmoHandle>::new(PassDescriptor {
+        color_attachments: vec![Msaa::color_attachment_descriptor(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Load,
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    });
+    gizmo_pass_node.add_camera(base::camera::CAMERA3D);
+    graph.add_node(node::GIZMO_PASS, gizmo_pass_node);
+
+    graph
+        .add_node_edge(base::node::MAIN_PASS, node::GIZMO_PASS)
+        .unwrap();
+    graph
+        .add_node_edge(base::node::CAMERA3D, node::GIZMO_PASS)
+        .unwrap();
+    graph
+        .add_node_edge(render_graph::node::TRANSFORM, node::GIZMO_PASS)
+        .unwrap();
+    graph
+        .add_node_edge(node::GIZMO_MATERIAL, node::GIZMO_PASS)
+        .unwrap();
+
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::GIZMO_PASS,
+            "color_resolve_target",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::GIZMO_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            node::GIZMO_DEPTH_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::GIZMO_PASS,
+            "depth",
+        )
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn pick_handles() {
+        let origin = Vec3::new(0.0, 0.0, -10.0);
+        // a ray straight down onto the x arrow, halfway along it
+        let ray = Ray::new(Vec3::new(1.0, 5.0, -10.0), -Vec3::unit_y());
+        let pick = |ray: &Ray, mode| pick_handle(ray, origin, Quat::identity(), 2.0, mode);
+        assert_eq!(pick(&ray, GizmoMode::Translate), Some(GizmoAxis::X));
+        // the z arrow is rotated onto the ray by a quarter turn around y
+        let rotation = Quat::from_rotation_y(FRAC_PI_2);
+        assert_eq!(
+            pick_handle(&ray, origin, rotation, 2.0, GizmoMode::Translate),
+            Some(GizmoAxis::Z)
+        );
+        // past the end of the arrow
+        let ray = Ray::new(Vec3::new(3.0, 5.0, -10.0), -Vec3::unit_y());
+        assert_eq!(pick(&ray, GizmoMode::Translate), None);
+
+        // the y ring lies in the xz plane
+        let ray = Ray::new(Vec3::new(RING_RADIUS * 2.0, 5.0, -10.0), -Vec3::unit_y());
+        assert_eq!(pick(&ray, GizmoMode::Rotate), Some(GizmoAxis::Y));
+        let ray = Ray::new(Vec3::new(0.5, 5.0, -10.0), -Vec3::unit_y());
+        assert_eq!(pick(&ray, GizmoMode::Rotate), None);
+    }
+
+    #[test]
+    fn drag_handles() {
+        let entity = Entity::new(0);
+        let ray_at = |x: f32, z: f32| Ray::new(Vec3::new(x, 5.0, z), -Vec3::unit_y());
+
+        let mut drag = GizmoDrag::start(
+            entity,
+            GizmoAxis::X,
+            GizmoMode::Translate,
+            Vec3::zero(),
+            Vec3::unit_x(),
+            &ray_at(1.0, 0.0),
+        )
+        .unwrap();
+        assert_eq!(drag.update(&ray_at(1.0, 0.0)), None);
+        let delta = drag.update(&ray_at(3.0, 1.0)).unwrap();
+        assert_near(delta.translation, Vec3::new(2.0, 0.0, 0.0));
+
+        let mut drag = GizmoDrag::start(
+            entity,
+            GizmoAxis::Z,
+            GizmoMode::Scale,
+            Vec3::zero(),
+            Vec3::unit_z(),
+            &ray_at(0.0, 1.0),
+        )
+        .unwrap();
+        assert_near(
+            drag.update(&ray_at(0.0, 3.0)).unwrap().scale,
+            Vec3::new(1.0, 1.0, 3.0),
+        );
+        assert_eq!(drag.update(&ray_at(0.0, -1.0)), None);
+
+        let mut drag = GizmoDrag::start(
+            entity,
+            GizmoAxis::Y,
+            GizmoMode::Rotate,
+            Vec3::zero(),
+            Vec3::unit_y(),
+            &ray_at(1.0, 0.0),
+        )
+        .unwrap();
+        let rotation = drag.update(&ray_at(0.0, -1.0)).unwrap().rotation;
+        assert_near(rotation * Vec3::unit_x(), -Vec3::unit_z());
+    }
+
+    #[test]
+    fn apply_delta_to_child() {
+        let parent = GlobalTransform {
+            translation: Vec3::new(1.0, 2.0, 3.0),
+            rotation: Quat::from_rotation_y(FRAC_PI_2),
+            scale: Vec3::splat(2.0),
+        };
+        let mut transform = Transform::from_translation(Vec3::new(1.0, 0.0, 0.0));
+        let before = parent.mul_transform(transform);
+        let delta = GizmoDelta {
+            translation: Vec3::new(0.0, 0.0, 4.0),
+            rotation: Quat::from_rotation_x(FRAC_PI_2),
+            ..GizmoDelta::identity(Entity::new(0))
+        };
+        delta.apply(&mut transform, Some(&parent));
+
+        let after = parent.mul_transform(transform);
+        assert_near(after.translation, before.translation + delta.translation);
+        assert_near(
+            after.rotation * Vec3::unit_y(),
+            delta.rotation * (before.rotation * Vec3::unit_y()),
+        );
+    }
+}
@@ -1,24 +1,53 @@
+pub mod deferred;
+pub mod gizmo;
+pub mod order_independent_transparency;
+pub mod particles;
+pub mod procgen;
 pub mod render_graph;
+pub mod screen_space_reflections;
+pub mod sky;
+pub mod terrain;
+pub mod vegetation;
+pub mod water;
 
 mod entity;
 mod light;
 mod material;
+mod material_overrides;
+mod reflection_probe;
+mod static_batching;
+mod uv_transform;
 
+pub use deferred::RenderPath;
 pub use entity::*;
 pub use light::*;
 pub use material::*;
+pub use material_overrides::*;
+pub use reflection_probe::*;
+pub use screen_space_reflections::*;
+pub use static_batching::*;
+pub use uv_transform::*;
 
 pub mod prelude {
-    pub use crate::{entity::*, light::Light, material::StandardMaterial};
+    pub use crate::{
+        deferred::RenderPath,
+        entity::*,
+        light::{AmbientLight, Fog, Light},
+        material::StandardMaterial,
+        material_overrides::MaterialOverrides,
+        static_batching::StaticMesh,
+    };
 }
 
 use bevy_app::prelude::*;
 use bevy_asset::{AddAsset, Assets, Handle};
 use bevy_ecs::IntoQuerySystem;
-use bevy_render::{prelude::Color, render_graph::RenderGraph, shader};
+use bevy_render::{pipeline, prelude::Color, render_graph::RenderGraph, shader, texture};
 use bevy_type_registry::RegisterType;
-use light::Light;
+use deferred::DeferredLightingMaterial;
+use light::{AmbientLight, Fog, Light};
 use material::StandardMaterial;
+use material_overrides::MaterialOverrides;
 use render_graph::add_pbr_graph;
 
 /// NOTE: this isn't PBR yet. consider this name "aspirational" :)
@@ -27,15 +56,52 @@ pub struct PbrPlugin;
 
 impl Plugin for PbrPlugin {
     fn build(&self, app: &mut AppBuilder) {
+        if app.resources().get::<AmbientLight>().is_none() {
+            app.resources_mut().insert(AmbientLight::default());
+        }
+        if app.resources().get::<Fog>().is_none() {
+            app.resources_mut().insert(Fog::default());
+        }
+
         app.add_asset::<StandardMaterial>()
             .register_component::<Light>()
+            .register_component::<MaterialOverrides>()
+            .register_component::<UvAnimation>()
+            .add_system(uv_transform::uv_animation_system.system())
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                shader::shader_defs_system::<MaterialOverrides>.system(),
+            )
             .add_system_to_stage(
                 stage::POST_UPDATE,
                 shader::asset_shader_defs_system::<StandardMaterial>.system(),
+            )
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                pipeline::asset_rasterization_overrides_system::<StandardMaterial>.system(),
+            )
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                texture::texture_streaming_distance_system::<StandardMaterial>.system(),
             );
+        let render_path = *app
+            .init_resource::<RenderPath>()
+            .resources()
+            .get::<RenderPath>()
+            .unwrap();
+        if render_path == RenderPath::Deferred {
+            app.add_asset::<DeferredLightingMaterial>()
+                .add_startup_system(deferred::spawn_deferred_lighting.system())
+                .add_system_to_stage(
+                    stage::POST_UPDATE,
+                    deferred::deferred_lighting_system.system(),
+                );
+            deferred::setup_deferred_resources(app.resources());
+        }
+
         let resources = app.resources();
         let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
-        add_pbr_graph(&mut render_graph, resources);
+        add_pbr_graph(&mut render_graph, resources, render_path);
 
         // add default StandardMaterial
         let mut materials = app
@@ -48,6 +114,12 @@ impl Plugin for PbrPlugin {
                 albedo: Color::PINK,
                 shaded: false,
                 albedo_texture: None,
+                uv_transform: Default::default(),
+                lightmap: None,
+                reflectance: 0.0,
+                metallic: 0.0,
+                roughness: 1.0,
+                double_sided: false,
             },
         );
     }
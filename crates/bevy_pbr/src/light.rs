@@ -1,4 +1,5 @@
 use bevy_core::Byteable;
+use bevy_math::Vec3;
 use bevy_property::Properties;
 use bevy_render::{
     camera::{CameraProjection, PerspectiveProjection},
@@ -7,12 +8,15 @@ use bevy_render::{
 use bevy_transform::components::GlobalTransform;
 use std::ops::Range;
 
-/// A point light
+/// A point light, or a directional light if `directional` is set
 #[derive(Debug, Properties)]
 pub struct Light {
     pub color: Color,
     pub fov: f32,
     pub depth: Range<f32>,
+    /// Directional lights shine from infinitely far away, like the sun. They light everything from the direction their
+    /// transform's +Z axis points to, and their position is ignored.
+    pub directional: bool,
 }
 
 impl Default for Light {
@@ -21,6 +25,40 @@ impl Default for Light {
             color: Color::rgb(1.0, 1.0, 1.0),
             depth: 0.1..50.0,
             fov: f32::to_radians(60.0),
+            directional: false,
+        }
+    }
+}
+
+/// The light that reaches every shaded surface, whatever direction it faces
+#[derive(Debug, Clone)]
+pub struct AmbientLight {
+    pub color: Color,
+}
+
+impl Default for AmbientLight {
+    fn default() -> Self {
+        AmbientLight {
+            color: Color::rgb(0.05, 0.05, 0.05),
+        }
+    }
+}
+
+/// Fades surfaces to a color with their distance to the camera. The fog starts at `start` and is thickest at `end`,
+/// where it covers surfaces as much as its color's alpha. The default fog is transparent, so it doesn't show.
+#[derive(Debug, Clone)]
+pub struct Fog {
+    pub color: Color,
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Fog {
+            color: Color::rgba(0.7, 0.8, 0.9, 0.0),
+            start: 10.0,
+            end: 100.0,
         }
     }
 }
@@ -45,10 +83,17 @@ impl LightRaw {
         };
 
         let proj = perspective.get_projection_matrix() * global_transform.compute_matrix();
-        let (x, y, z) = global_transform.translation.into();
+        // a w of 0 marks directional lights, whose "position" is the direction towards the light
+        let pos = if light.directional {
+            let (x, y, z) = (global_transform.rotation * Vec3::unit_z()).into();
+            [x, y, z, 0.0]
+        } else {
+            let (x, y, z) = global_transform.translation.into();
+            [x, y, z, 1.0]
+        };
         LightRaw {
             proj: proj.to_cols_array_2d(),
-            pos: [x, y, z, 1.0],
+            pos,
             color: light.color.into(),
         }
     }
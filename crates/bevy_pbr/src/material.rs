@@ -1,5 +1,12 @@
+use crate::UvTransform;
 use bevy_asset::{self, Handle};
-use bevy_render::{color::Color, renderer::RenderResources, shader::ShaderDefs, texture::Texture};
+use bevy_render::{
+    color::Color,
+    pipeline::{RasterizationOverrides, RasterizationSpecialization},
+    renderer::RenderResources,
+    shader::ShaderDefs,
+    texture::Texture,
+};
 use bevy_type_registry::TypeUuid;
 
 /// A material with "standard" properties used in PBR lighting
@@ -9,9 +16,27 @@ pub struct StandardMaterial {
     pub albedo: Color,
     #[shader_def]
     pub albedo_texture: Option<Handle<Texture>>,
+    pub uv_transform: UvTransform,
+    /// Baked lighting, sampled with the mesh's [Mesh::ATTRIBUTE_UV_1](bevy_render::mesh::Mesh::ATTRIBUTE_UV_1)
+    /// coordinates. It replaces the ambient light of shaded materials.
+    #[shader_def]
+    pub lightmap: Option<Handle<Texture>>,
+    /// How much of a [ReflectionProbe](crate::ReflectionProbe)'s environment, or of the rest of the scene with
+    /// [ScreenSpaceReflections](crate::ScreenSpaceReflections), the material reflects when looked at head-on.
+    /// Reflections get stronger at grazing angles. 0 turns reflections off.
+    pub reflectance: f32,
+    /// How metal-like the surface is, from 0 for dielectrics to 1 for metals. Only the
+    /// [deferred render path](crate::RenderPath::Deferred) uses it for now.
+    pub metallic: f32,
+    /// How rough the surface is, from 0 for mirror-like highlights to 1 for broad ones. Only the
+    /// [deferred render path](crate::RenderPath::Deferred) uses it for now.
+    pub roughness: f32,
     #[render_resources(ignore)]
     #[shader_def]
     pub shaded: bool,
+    /// Disables back face culling so both sides of the mesh are drawn
+    #[render_resources(ignore)]
+    pub double_sided: bool,
 }
 
 impl Default for StandardMaterial {
@@ -19,7 +44,23 @@ impl Default for StandardMaterial {
         StandardMaterial {
             albedo: Color::rgb(1.0, 1.0, 1.0),
             albedo_texture: None,
+            uv_transform: UvTransform::default(),
+            lightmap: None,
+            reflectance: 0.04,
+            metallic: 0.0,
+            roughness: 0.5,
             shaded: true,
+            double_sided: false,
+        }
+    }
+}
+
+impl RasterizationOverrides for StandardMaterial {
+    fn rasterization_specialization(&self) -> RasterizationSpecialization {
+        if self.double_sided {
+            RasterizationSpecialization::double_sided()
+        } else {
+            RasterizationSpecialization::default()
         }
     }
 }
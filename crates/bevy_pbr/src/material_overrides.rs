@@ -0,0 +1,68 @@
+use bevy_core::Bytes;
+use bevy_math::Vec2;
+use bevy_property::Properties;
+use bevy_render::{
+    color::Color,
+    renderer::{RenderResource, RenderResources},
+    shader::ShaderDefs,
+};
+
+/// Adjusts the [StandardMaterial](crate::StandardMaterial) of a single entity, for example for team colors or a damage
+/// flash, without creating a new material. The overrides are written to the entity's own uniform, so entities that
+/// share a material still share its bind group.
+#[derive(Debug, Clone, Copy, PartialEq, Bytes, RenderResources, RenderResource, Properties)]
+#[render_resources(from_self)]
+pub struct MaterialOverrides {
+    /// Multiplied with the material's albedo
+    pub tint: Color,
+    /// Added to the lit color. The alpha channel is ignored.
+    pub emissive: Color,
+    pub uv_offset: Vec2,
+    pub uv_scale: Vec2,
+}
+
+impl Default for MaterialOverrides {
+    fn default() -> Self {
+        MaterialOverrides {
+            tint: Color::WHITE,
+            emissive: Color::BLACK,
+            uv_offset: Vec2::zero(),
+            uv_scale: Vec2::one(),
+        }
+    }
+}
+
+impl MaterialOverrides {
+    pub fn tint(tint: Color) -> Self {
+        MaterialOverrides {
+            tint,
+            ..Default::default()
+        }
+    }
+
+    pub fn emissive(emissive: Color) -> Self {
+        MaterialOverrides {
+            emissive,
+            ..Default::default()
+        }
+    }
+}
+
+// entities without overrides use a pipeline that doesn't have the overrides binding
+impl ShaderDefs for MaterialOverrides {
+    fn shader_defs_len(&self) -> usize {
+        1
+    }
+
+    fn get_shader_def(&self, index: usize) -> Option<&str> {
+        if index == 0 {
+            Some("MATERIAL_OVERRIDES")
+        } else {
+            None
+        }
+    }
+
+    fn iter_shader_defs(&self) -> bevy_render::shader::ShaderDefIterator {
+        bevy_render::shader::ShaderDefIterator::new(self)
+    }
+}
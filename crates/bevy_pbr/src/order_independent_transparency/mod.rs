@@ -0,0 +1,415 @@
+//! Weighted blended order-independent transparency, from "Weighted Blended Order-Independent Transparency" by McGuire
+//! and Bavoil.
+//!
+//! Cameras with [TransparencyMode::WeightedBlended] don't draw transparent entities in the main pass. A second pass
+//! adds up their colors, weighted by alpha and depth, in an accumulation texture and multiplies how much of the
+//! background shows through them in a revealage texture. A composite pass then draws the average color over the opaque
+//! image. The result doesn't depend on the order the entities are drawn in, so intersecting transparent meshes don't
+//! pop in front of each other, at the cost of approximating the blended color.
+//!
+//! Transparent entities need the pipeline of the accumulation pass, see [oit_render_pipelines]. Add the plugin after
+//! the other render plugins, but before post-processing plugins that redirect the window, like
+//! `TemporalAntiAliasingPlugin`.
+
+use crate::{
+    entity::forward_pipeline_specialization,
+    render_graph::{
+        build_oit_forward_pipeline, FORWARD_PIPELINE_HANDLE, OIT_FORWARD_PIPELINE_HANDLE,
+    },
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_ecs::{Commands, IntoQuerySystem, Res, ResMut};
+use bevy_math::Vec2;
+use bevy_render::{
+    camera::TransparencyMode,
+    draw::Draw,
+    mesh::{shape, Mesh},
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+        TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, BlendFactor, BlendOperation, ColorStateDescriptor, ColorWrite, CullMode,
+        FrontFace, PipelineDescriptor, RasterizationStateDescriptor, RenderPipeline,
+        RenderPipelines,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, MainPass, Msaa, MsaaTexture},
+        AssetRenderResourcesNode, AssetTextureNode, PassNode, RenderGraph, WindowSwapChainNode,
+        WindowTextureNode,
+    },
+    renderer::RenderResources,
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{
+        Extent3d, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+    },
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+use bevy_window::{WindowId, Windows};
+
+/// The weighted sum of the transparent colors, with the sum of the weighted alphas in the alpha channel
+pub const OIT_ACCUMULATION_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 2280157389436104527);
+
+/// The product of one minus the alphas of the transparent surfaces, which is how much of the opaque image shows through
+pub const OIT_REVEALAGE_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 15573809184265301968);
+
+pub const OIT_COMPOSITE_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 9482716534093847102);
+
+pub const OIT_COMPOSITE_MATERIAL_HANDLE: Handle<OitCompositeMaterial> =
+    Handle::weak_from_u64(OitCompositeMaterial::TYPE_UUID, 1049285713650948377);
+
+/// A quad that covers the whole viewport in normalized device coordinates
+pub const OIT_COMPOSITE_QUAD_HANDLE: Handle<Mesh> =
+    Handle::weak_from_u64(Mesh::TYPE_UUID, 7760392841582046613);
+
+pub mod node {
+    pub const OIT_ACCUMULATION_TEXTURE: &str = "oit_accumulation_texture";
+    pub const OIT_REVEALAGE_TEXTURE: &str = "oit_revealage_texture";
+    pub const OIT_SAMPLED_ACCUMULATION_ATTACHMENT: &str = "oit_sampled_accumulation_attachment";
+    pub const OIT_SAMPLED_REVEALAGE_ATTACHMENT: &str = "oit_sampled_revealage_attachment";
+    pub const OIT_PASS: &str = "oit_pass";
+    pub const OIT_COMPOSITE_MATERIAL: &str = "oit_composite_material";
+    pub const OIT_COMPOSITE_PASS: &str = "oit_composite_pass";
+}
+
+/// Draws the transparent surfaces from the accumulation and revealage textures
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "b6f41d0e-5c37-4a8e-9d2a-73e0c18f4a56"]
+pub struct OitCompositeMaterial {
+    pub accumulation: Handle<Texture>,
+    pub revealage: Handle<Texture>,
+}
+
+/// Marks the quad that composites the transparent surfaces
+#[derive(Debug, Default)]
+pub struct OitComposite;
+
+/// The pipelines of a transparent [PbrComponents](crate::PbrComponents) entity that can be drawn by cameras with either
+/// [TransparencyMode]. Remember to set [Draw::is_transparent].
+pub fn oit_render_pipelines() -> RenderPipelines {
+    RenderPipelines::from_pipelines(vec![
+        RenderPipeline::specialized(FORWARD_PIPELINE_HANDLE, forward_pipeline_specialization()),
+        RenderPipeline::specialized(
+            OIT_FORWARD_PIPELINE_HANDLE,
+            forward_pipeline_specialization(),
+        ),
+    ])
+}
+
+/// Adds weighted blended transparency to the 3d camera. Set its [Camera::transparency](bevy_render::camera::Camera)
+/// to [TransparencyMode::WeightedBlended] to use it.
+#[derive(Default)]
+pub struct OrderIndependentTransparencyPlugin;
+
+impl Plugin for OrderIndependentTransparencyPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<OitCompositeMaterial>()
+            .add_startup_system(spawn_oit_composite.system())
+            .add_system_to_stage(stage::POST_UPDATE, oit_texture_size_system.system());
+
+        let resources = app.resources();
+        // the textures are resized to the window before the first frame is rendered
+        let mut textures = resources.get_mut::<Assets<Texture>>().unwrap();
+        textures.set_untracked(
+            OIT_ACCUMULATION_TEXTURE_HANDLE,
+            Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::Rgba16Float),
+        );
+        textures.set_untracked(
+            OIT_REVEALAGE_TEXTURE_HANDLE,
+            Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::R16Float),
+        );
+        resources
+            .get_mut::<Assets<OitCompositeMaterial>>()
+            .unwrap()
+            .set_untracked(
+                OIT_COMPOSITE_MATERIAL_HANDLE,
+                OitCompositeMaterial {
+                    accumulation: OIT_ACCUMULATION_TEXTURE_HANDLE,
+                    revealage: OIT_REVEALAGE_TEXTURE_HANDLE,
+                },
+            );
+        resources.get_mut::<Assets<Mesh>>().unwrap().set_untracked(
+            OIT_COMPOSITE_QUAD_HANDLE,
+            Mesh::from(shape::Quad::new(Vec2::new(2.0, 2.0))),
+        );
+
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+        pipelines.set_untracked(
+            OIT_FORWARD_PIPELINE_HANDLE,
+            build_oit_forward_pipeline(&mut shaders),
+        );
+        pipelines.set_untracked(
+            OIT_COMPOSITE_PIPELINE_HANDLE,
+            build_oit_composite_pipeline(&mut shaders),
+        );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_oit_graph(&mut render_graph);
+    }
+}
+
+pub fn build_oit_composite_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: None,
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            alpha_blend: BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("oit_composite.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("oit_composite.frag"),
+            ))),
+        })
+    }
+}
+
+fn spawn_oit_composite(mut commands: Commands) {
+    // the quad is transparent, so only weighted blended passes draw it
+    commands.spawn((
+        OIT_COMPOSITE_QUAD_HANDLE,
+        OIT_COMPOSITE_MATERIAL_HANDLE,
+        Draw {
+            is_transparent: true,
+            ..Default::default()
+        },
+        RenderPipelines::from_handles(&[OIT_COMPOSITE_PIPELINE_HANDLE]),
+        Transform::default(),
+        GlobalTransform::default(),
+        OitComposite,
+    ));
+}
+
+/// Keeps the accumulation and revealage textures the size of the primary window
+pub fn oit_texture_size_system(windows: Res<Windows>, mut textures: ResMut<Assets<Texture>>) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+
+    let window_size = Vec2::new(window.width().max(1) as f32, window.height().max(1) as f32);
+    for handle in [
+        OIT_ACCUMULATION_TEXTURE_HANDLE,
+        OIT_REVEALAGE_TEXTURE_HANDLE,
+    ]
+    .iter()
+    {
+        let texture_size = textures.get(handle).map(|texture| texture.size);
+        if texture_size.map_or(false, |size| size != window_size) {
+            // the changed texture is recreated at the new size
+            textures.get_mut(handle).unwrap().size = window_size;
+        }
+    }
+}
+
+fn add_oit_graph(graph: &mut RenderGraph) {
+    graph.add_node(
+        node::OIT_ACCUMULATION_TEXTURE,
+        AssetTextureNode::new(OIT_ACCUMULATION_TEXTURE_HANDLE),
+    );
+    graph.add_node(
+        node::OIT_REVEALAGE_TEXTURE,
+        AssetTextureNode::new(OIT_REVEALAGE_TEXTURE_HANDLE),
+    );
+
+    let mut oit_pass_node = PassNode::<&MainPass>::new(PassDescriptor {
+        color_attachments: vec![
+            Msaa::color_attachment_descriptor(
+                TextureAttachment::Input("accumulation".to_string()),
+                TextureAttachment::Input("accumulation_resolve_target".to_string()),
+                Operations {
+                    load: LoadOp::Clear(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+                    store: true,
+                },
+            ),
+            Msaa::color_attachment_descriptor(
+                TextureAttachment::Input("revealage".to_string()),
+                TextureAttachment::Input("revealage_resolve_target".to_string()),
+                Operations {
+                    load: LoadOp::Clear(Color::WHITE),
+                    store: true,
+                },
+            ),
+        ],
+        // transparent surfaces behind opaque ones are hidden by the main pass depth
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Load,
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    });
+    oit_pass_node.set_transparency(TransparencyMode::WeightedBlended);
+    oit_pass_node.add_camera(base::camera::CAMERA3D);
+    oit_pass_node.set_pass_tag(node::OIT_PASS);
+    graph.add_node(node::OIT_PASS, oit_pass_node);
+
+    let mut composite_pass_node = PassNode::<&OitComposite>::new(PassDescriptor {
+        color_attachments: vec![Msaa::color_attachment_descriptor(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Load,
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    });
+    composite_pass_node.set_transparency(TransparencyMode::WeightedBlended);
+    composite_pass_node.add_camera(base::camera::CAMERA3D);
+    graph.add_node(node::OIT_COMPOSITE_PASS, composite_pass_node);
+    graph.add_system_node(
+        node::OIT_COMPOSITE_MATERIAL,
+        AssetRenderResourcesNode::<OitCompositeMaterial>::new(false),
+    );
+
+    graph
+        .add_node_edge(base::node::MAIN_PASS, node::OIT_PASS)
+        .unwrap();
+    graph
+        .add_node_edge(node::OIT_PASS, node::OIT_COMPOSITE_PASS)
+        .unwrap();
+    graph
+        .add_node_edge(node::OIT_COMPOSITE_MATERIAL, node::OIT_COMPOSITE_PASS)
+        .unwrap();
+
+    graph
+        .add_slot_edge(
+            base::node::MAIN_DEPTH_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::OIT_PASS,
+            "depth",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::OIT_COMPOSITE_PASS,
+            "color_resolve_target",
+        )
+        .unwrap();
+
+    graph
+        .add_slot_edge(
+            base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+            WindowTextureNode::OUT_TEXTURE,
+            node::OIT_COMPOSITE_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+
+    // the transparent surfaces are accumulated with as many samples as the depth they are tested against, and
+    // resolved into the textures the composite pass samples
+    let sampled_attachment = |format| {
+        WindowTextureNode::multisampled(
+            WindowId::primary(),
+            TextureDescriptor {
+                size: Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsage::OUTPUT_ATTACHMENT,
+            },
+            MsaaTexture::Color,
+        )
+    };
+    graph.add_node(
+        node::OIT_SAMPLED_ACCUMULATION_ATTACHMENT,
+        sampled_attachment(TextureFormat::Rgba16Float),
+    );
+    graph.add_node(
+        node::OIT_SAMPLED_REVEALAGE_ATTACHMENT,
+        sampled_attachment(TextureFormat::R16Float),
+    );
+    for (attachment, texture, input) in [
+        (
+            node::OIT_SAMPLED_ACCUMULATION_ATTACHMENT,
+            node::OIT_ACCUMULATION_TEXTURE,
+            "accumulation",
+        ),
+        (
+            node::OIT_SAMPLED_REVEALAGE_ATTACHMENT,
+            node::OIT_REVEALAGE_TEXTURE,
+            "revealage",
+        ),
+    ]
+    .iter()
+    {
+        graph
+            .add_slot_edge(
+                *attachment,
+                WindowTextureNode::OUT_TEXTURE,
+                node::OIT_PASS,
+                *input,
+            )
+            .unwrap();
+        graph
+            .add_slot_edge(
+                *texture,
+                AssetTextureNode::OUT_TEXTURE,
+                node::OIT_PASS,
+                format!("{}_resolve_target", input),
+            )
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oit_pipelines() {
+        let render_pipelines = oit_render_pipelines();
+        let handles = render_pipelines
+            .pipelines
+            .iter()
+            .map(|pipeline| pipeline.pipeline.clone_weak())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            handles,
+            vec![FORWARD_PIPELINE_HANDLE, OIT_FORWARD_PIPELINE_HANDLE]
+        );
+        assert_eq!(TransparencyMode::default(), TransparencyMode::Sorted);
+    }
+}
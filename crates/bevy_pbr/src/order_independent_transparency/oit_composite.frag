@@ -0,0 +1,22 @@
+#version 450
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform texture2D OitCompositeMaterial_accumulation;
+layout(set = 0, binding = 1) uniform sampler OitCompositeMaterial_accumulation_sampler;
+layout(set = 0, binding = 2) uniform texture2D OitCompositeMaterial_revealage;
+layout(set = 0, binding = 3) uniform sampler OitCompositeMaterial_revealage_sampler;
+
+void main() {
+    ivec2 texel = ivec2(gl_FragCoord.xy);
+    vec4 accumulation = texelFetch(
+        sampler2D(OitCompositeMaterial_accumulation, OitCompositeMaterial_accumulation_sampler),
+        texel, 0);
+    float revealage = texelFetch(
+        sampler2D(OitCompositeMaterial_revealage, OitCompositeMaterial_revealage_sampler),
+        texel, 0).r;
+
+    // the weighted average color of the transparent surfaces covers as much of the opaque ones as doesn't show through
+    vec3 average = accumulation.rgb / max(accumulation.a, 0.00001);
+    o_Target = vec4(average, 1.0 - revealage);
+}
@@ -0,0 +1,10 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec3 Vertex_Normal;
+layout(location = 2) in vec2 Vertex_Uv;
+
+void main() {
+    // the quad covers the camera's viewport in normalized device coordinates
+    gl_Position = vec4(Vertex_Position.xy, 0.0, 1.0);
+}
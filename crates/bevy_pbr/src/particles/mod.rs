@@ -0,0 +1,238 @@
+//! Particles simulated on the gpu.
+//!
+//! Each [ParticleEmitter] owns a pool of particles on the gpu. Every frame, compute shaders take new particles from a
+//! list of dead ones, move the living ones and return the ones that expired, without any particle data going through
+//! the cpu. The particles that are alive are drawn as camera facing quads with a single indirect draw, optionally
+//! sorted back to front with a bitonic sort first. Needs compute support: without it, emitters draw nothing.
+
+mod particle_node;
+
+pub use particle_node::*;
+
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::Bundle;
+use bevy_math::{Vec2, Vec3};
+use bevy_render::{
+    color::Color,
+    draw::Draw,
+    mesh::{shape, Mesh},
+    pipeline::{
+        BlendDescriptor, BlendFactor, BlendOperation, ColorStateDescriptor, ColorWrite,
+        CompareFunction, ComputePipelineDescriptor, CullMode, DepthStencilStateDescriptor,
+        FrontFace, PipelineDescriptor, RasterizationStateDescriptor, RenderPipeline,
+        RenderPipelines, StencilStateDescriptor, StencilStateFaceDescriptor,
+    },
+    render_graph::{
+        base::{self, MainPass},
+        RenderGraph,
+    },
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::TextureFormat,
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+
+pub const PARTICLE_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 4916270385112907263);
+
+pub const PARTICLE_EMIT_PIPELINE_HANDLE: Handle<ComputePipelineDescriptor> =
+    Handle::weak_from_u64(ComputePipelineDescriptor::TYPE_UUID, 11780413526958316541);
+
+pub const PARTICLE_SIMULATE_PIPELINE_HANDLE: Handle<ComputePipelineDescriptor> =
+    Handle::weak_from_u64(ComputePipelineDescriptor::TYPE_UUID, 7359068812446320194);
+
+pub const PARTICLE_SORT_PIPELINE_HANDLE: Handle<ComputePipelineDescriptor> =
+    Handle::weak_from_u64(ComputePipelineDescriptor::TYPE_UUID, 2604715938820175729);
+
+/// The quad each particle is drawn with
+pub const PARTICLE_QUAD_HANDLE: Handle<Mesh> =
+    Handle::weak_from_u64(Mesh::TYPE_UUID, 16207538019126475830);
+
+pub mod node {
+    pub const PARTICLES: &str = "particles";
+}
+
+/// Spawns particles at the position of its entity and simulates them on the gpu
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    /// The most particles alive at once. No particles spawn while this many are alive. Changing it starts over with
+    /// no particles.
+    pub capacity: u32,
+    /// How many particles spawn per second
+    pub rate: f32,
+    /// How long particles live, in seconds
+    pub lifetime: f32,
+    /// The velocity particles start with, in the space of the emitter
+    pub velocity: Vec3,
+    /// Adds a random velocity of up to this speed in a random direction
+    pub velocity_randomness: f32,
+    /// Accelerates particles in world space, for example with gravity
+    pub acceleration: Vec3,
+    /// Particles spawn within a sphere of this radius around the emitter
+    pub radius: f32,
+    pub start_size: f32,
+    pub end_size: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+    /// Sorts the particles back to front before they are drawn, so alpha blended particles overlap correctly.
+    /// Changing it starts over with no particles.
+    pub sort: bool,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        ParticleEmitter {
+            capacity: 10_000,
+            rate: 1_000.0,
+            lifetime: 3.0,
+            velocity: Vec3::new(0.0, 2.0, 0.0),
+            velocity_randomness: 1.0,
+            acceleration: Vec3::new(0.0, -1.0, 0.0),
+            radius: 0.1,
+            start_size: 0.1,
+            end_size: 0.02,
+            start_color: Color::WHITE,
+            end_color: Color::rgba(1.0, 1.0, 1.0, 0.0),
+            sort: false,
+        }
+    }
+}
+
+/// A component bundle for entities that emit gpu simulated particles
+#[derive(Bundle)]
+pub struct ParticleEmitterComponents {
+    pub emitter: ParticleEmitter,
+    pub mesh: Handle<Mesh>,
+    pub main_pass: MainPass,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for ParticleEmitterComponents {
+    fn default() -> Self {
+        ParticleEmitterComponents {
+            emitter: Default::default(),
+            mesh: PARTICLE_QUAD_HANDLE,
+            main_pass: MainPass,
+            draw: Draw {
+                is_transparent: true,
+                ..Default::default()
+            },
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::new(
+                PARTICLE_PIPELINE_HANDLE,
+            )]),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+/// Simulates and draws [ParticleEmitterComponents] on the gpu. Add it after the render plugins.
+#[derive(Default)]
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let resources = app.resources();
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let mut compute_pipelines = resources
+            .get_mut::<Assets<ComputePipelineDescriptor>>()
+            .unwrap();
+        for (handle, name, source) in [
+            (
+                PARTICLE_EMIT_PIPELINE_HANDLE,
+                "particle_emit",
+                include_str!("particle_emit.comp"),
+            ),
+            (
+                PARTICLE_SIMULATE_PIPELINE_HANDLE,
+                "particle_simulate",
+                include_str!("particle_simulate.comp"),
+            ),
+            (
+                PARTICLE_SORT_PIPELINE_HANDLE,
+                "particle_sort",
+                include_str!("particle_sort.comp"),
+            ),
+        ]
+        .iter()
+        {
+            let shader = shaders.add(Shader::from_glsl(ShaderStage::Compute, source));
+            compute_pipelines.set_untracked(
+                handle.clone_weak(),
+                ComputePipelineDescriptor {
+                    name: Some(name.to_string()),
+                    ..ComputePipelineDescriptor::new(shader)
+                },
+            );
+        }
+
+        let pipeline = build_particle_pipeline(&mut shaders);
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(PARTICLE_PIPELINE_HANDLE, pipeline);
+        resources.get_mut::<Assets<Mesh>>().unwrap().set_untracked(
+            PARTICLE_QUAD_HANDLE,
+            Mesh::from(shape::Quad::new(Vec2::one())),
+        );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        render_graph.add_system_node(node::PARTICLES, ParticleNode::new(base::camera::CAMERA3D));
+        render_graph
+            .add_node_edge(node::PARTICLES, base::node::MAIN_PASS)
+            .unwrap();
+    }
+}
+
+/// Blends particles over the scene. They are tested against the depth of the scene but don't write depth themselves.
+fn build_particle_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Less,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            alpha_blend: BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("particle.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("particle.frag"),
+            ))),
+        })
+    }
+}
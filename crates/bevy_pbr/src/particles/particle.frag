@@ -0,0 +1,26 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+layout(location = 1) in vec4 v_Color;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+    vec4 CameraPosition;
+    // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+    vec4 CameraExposure;
+};
+
+void main() {
+    // round particles with soft edges
+    float falloff = 1.0 - smoothstep(0.5, 1.0, length(v_Uv * 2.0 - 1.0));
+    vec4 color = vec4(v_Color.rgb, v_Color.a * falloff);
+
+    color.rgb *= CameraExposure.x;
+    if (CameraExposure.y > 0.5) {
+        // reinhard
+        color.rgb = color.rgb / (1.0 + color.rgb);
+    }
+    o_Target = color;
+}
@@ -0,0 +1,72 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec2 Vertex_Uv;
+
+layout(location = 0) out vec2 v_Uv;
+layout(location = 1) out vec4 v_Color;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+    vec4 CameraPosition;
+    // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+    vec4 CameraExposure;
+};
+
+layout(set = 1, binding = 0) uniform ParticleEmitter {
+    mat4 EmitterTransform;
+    vec4 ViewPosition;
+    vec4 ViewRight;
+    vec4 ViewUp;
+    vec4 Velocity;
+    vec4 Acceleration;
+    vec4 StartColor;
+    vec4 EndColor;
+    // x: start size, y: end size, z: lifetime, w: radius of the sphere particles spawn in
+    vec4 Shape;
+    uint Capacity;
+    uint SpawnCount;
+    uint InList;
+    uint Seed;
+    uint SortSize;
+    float DeltaSeconds;
+};
+
+struct Particle {
+    // xyz: position in world space, w: age in seconds
+    vec4 PositionAge;
+    // xyz: velocity, w: lifetime in seconds
+    vec4 VelocityLifetime;
+};
+
+layout(set = 1, binding = 1) readonly buffer ParticleEmitter_particles {
+    Particle Particles[];
+};
+
+# ifdef PARTICLEEMITTER_SORT
+layout(set = 1, binding = 2) readonly buffer ParticleEmitter_sort_keys {
+    uvec2 SortKeys[];
+};
+# else
+layout(set = 1, binding = 2) readonly buffer ParticleEmitter_alive {
+    uint Alive[];
+};
+# endif
+
+void main() {
+    // the survivors of this frame's simulation are in the other alive list
+# ifdef PARTICLEEMITTER_SORT
+    uint index = SortKeys[gl_InstanceIndex].y;
+# else
+    uint index = Alive[(1 - InList) * Capacity + gl_InstanceIndex];
+# endif
+    Particle particle = Particles[index];
+    float t = clamp(particle.PositionAge.w / particle.VelocityLifetime.w, 0.0, 1.0);
+    float size = mix(Shape.x, Shape.y, t);
+
+    // faces the view
+    vec3 position = particle.PositionAge.xyz + (ViewRight.xyz * Vertex_Position.x + ViewUp.xyz * Vertex_Position.y) * size;
+    v_Uv = Vertex_Uv;
+    v_Color = mix(StartColor, EndColor, t);
+    gl_Position = ViewProj * vec4(position, 1.0);
+}
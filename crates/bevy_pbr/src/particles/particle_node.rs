@@ -0,0 +1,649 @@
+use super::{
+    ParticleEmitter, PARTICLE_EMIT_PIPELINE_HANDLE, PARTICLE_SIMULATE_PIPELINE_HANDLE,
+    PARTICLE_SORT_PIPELINE_HANDLE,
+};
+use bevy_asset::{Assets, Handle};
+use bevy_core::{AsBytes, Byteable, Time};
+use bevy_ecs::{
+    Commands, Entity, IntoQuerySystem, Local, Query, Res, ResMut, Resources, System, World,
+};
+use bevy_math::Vec3;
+use bevy_render::{
+    camera::ActiveCameras,
+    draw::{Draw, DrawIndexedIndirectArgs, INDIRECT_DRAW_ARGS, INDIRECT_DRAW_SHADER_DEF},
+    mesh::{Indices, Mesh},
+    pipeline::{
+        BindGroupDescriptor, BindGroupDescriptorId, ComputePipelineDescriptor, RenderPipelines,
+    },
+    render_graph::{CommandQueue, Node, ResourceSlots, SystemNode},
+    renderer::{
+        BindGroup, BindGroupId, BindGroupStatus, BufferId, BufferInfo, BufferUsage,
+        RenderCapabilities, RenderContext, RenderResourceBinding, RenderResourceBindings,
+        RenderResourceContext, RenderResourceId, RenderResourceOwner,
+    },
+    shader::Shader,
+};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_utils::HashMap;
+use parking_lot::Mutex;
+use std::{any::TypeId, borrow::Cow, ops::DerefMut, sync::Arc};
+
+/// Set on the pipelines of emitters whose particles are sorted, so the vertex shader reads the sorted order
+pub const PARTICLE_SORT_SHADER_DEF: &str = "PARTICLEEMITTER_SORT";
+
+const WORKGROUP_SIZE: u32 = 64;
+/// Uniform buffer bindings have to start at a multiple of this
+const UNIFORM_ALIGNMENT: u64 = 256;
+
+/// The emitter uniform shared by the particle shaders
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct EmitterUniform {
+    transform: [[f32; 4]; 4],
+    view_position: [f32; 4],
+    view_right: [f32; 4],
+    view_up: [f32; 4],
+    /// xyz: initial velocity, w: largest random speed
+    velocity: [f32; 4],
+    acceleration: [f32; 4],
+    start_color: [f32; 4],
+    end_color: [f32; 4],
+    /// x: start size, y: end size, z: lifetime, w: spawn radius
+    shape: [f32; 4],
+    capacity: u32,
+    spawn_count: u32,
+    in_list: u32,
+    seed: u32,
+    sort_size: u32,
+    delta_seconds: f32,
+    _padding: [u32; 2],
+}
+
+unsafe impl Byteable for EmitterUniform {}
+
+const UNIFORM_SIZE: u64 = std::mem::size_of::<EmitterUniform>() as u64;
+const DRAW_ARGS_SIZE: u64 = std::mem::size_of::<DrawIndexedIndirectArgs>() as u64;
+/// A particle is a position and age followed by a velocity and lifetime
+const PARTICLE_SIZE: u64 = std::mem::size_of::<[[f32; 4]; 2]>() as u64;
+
+/// The (j, k) steps of a bitonic sort of `size` keys, a power of two. The first step, (0, 0), fills the keys.
+fn sort_steps(size: u32) -> Vec<[u32; 2]> {
+    let mut steps = vec![[0, 0]];
+    let mut k = 2;
+    while k <= size {
+        let mut j = k / 2;
+        while j > 0 {
+            steps.push([j, k]);
+            j /= 2;
+        }
+        k *= 2;
+    }
+    steps
+}
+
+/// Simulates the particles of [ParticleEmitter]s in compute passes before the main pass
+#[derive(Debug)]
+pub struct ParticleNode {
+    command_queue: CommandQueue,
+    dispatches: Arc<Mutex<Vec<ParticleDispatch>>>,
+    camera_name: Cow<'static, str>,
+}
+
+impl ParticleNode {
+    pub fn new<T>(camera_name: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        ParticleNode {
+            command_queue: Default::default(),
+            dispatches: Default::default(),
+            camera_name: camera_name.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum ParticlePass {
+    Emit,
+    Simulate,
+    Sort,
+}
+
+impl ParticlePass {
+    fn pipeline(self) -> Handle<ComputePipelineDescriptor> {
+        match self {
+            ParticlePass::Emit => PARTICLE_EMIT_PIPELINE_HANDLE,
+            ParticlePass::Simulate => PARTICLE_SIMULATE_PIPELINE_HANDLE,
+            ParticlePass::Sort => PARTICLE_SORT_PIPELINE_HANDLE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParticleDispatch {
+    pass: ParticlePass,
+    bind_groups: Vec<(u32, BindGroupDescriptorId, BindGroupId)>,
+    workgroups: u32,
+}
+
+impl Node for ParticleNode {
+    fn update(
+        &mut self,
+        _world: &World,
+        _resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        // writes the emitter uniforms and resets the instance counts the simulation writes to
+        self.command_queue.execute(render_context);
+
+        let dispatches = std::mem::take(&mut *self.dispatches.lock());
+        if dispatches.is_empty() {
+            return;
+        }
+
+        render_context.begin_compute_pass(&mut |compute_pass| {
+            for dispatch in dispatches.iter() {
+                compute_pass.set_pipeline(&dispatch.pass.pipeline());
+                for (index, descriptor, bind_group) in dispatch.bind_groups.iter() {
+                    compute_pass.set_bind_group(*index, *descriptor, *bind_group, None);
+                }
+                compute_pass.dispatch(dispatch.workgroups, 1, 1);
+            }
+        });
+    }
+}
+
+impl SystemNode for ParticleNode {
+    fn get_system(&self, commands: &mut Commands) -> Box<dyn System> {
+        let system = particle_node_system.system();
+        commands.insert_local_resource(
+            system.id(),
+            ParticleNodeState {
+                command_queue: self.command_queue.clone(),
+                dispatches: self.dispatches.clone(),
+                camera_name: self.camera_name.clone(),
+                emitters: Default::default(),
+                frame: 0,
+            },
+        );
+        system
+    }
+}
+
+/// The gpu state of one [ParticleEmitter], owned by its entity
+#[derive(Debug)]
+struct EmitterBuffers {
+    capacity: u32,
+    /// The number of sort keys, or 0 if the particles aren't sorted
+    sort_size: u32,
+    uniform: BufferId,
+    draw_args: BufferId,
+    /// Holds the uniform and the reset draw args of the next frame
+    staging: BufferId,
+    /// The bind groups of the sort steps' uniforms
+    sort_steps: Vec<BindGroup>,
+    /// The alive list the next simulation reads from
+    in_list: u32,
+    /// Spawns that didn't make up a whole particle yet
+    spawn_remainder: f32,
+}
+
+impl EmitterBuffers {
+    fn new(
+        owner: RenderResourceOwner,
+        emitter: &ParticleEmitter,
+        index_count: u32,
+        render_resource_context: &dyn RenderResourceContext,
+        bindings: &mut RenderResourceBindings,
+    ) -> Self {
+        let capacity = emitter.capacity as u64;
+        let mut buffers = Vec::new();
+        let mut storage = |name: &str, size: u64, data: Option<&[u8]>, usage: BufferUsage| {
+            let info = BufferInfo {
+                size: size as usize,
+                buffer_usage: BufferUsage::STORAGE | usage,
+                ..Default::default()
+            };
+            let buffer = match data {
+                Some(data) => render_resource_context.create_buffer_with_data(info, data),
+                None => render_resource_context.create_buffer(info),
+            };
+            bindings.set(
+                name,
+                RenderResourceBinding::Buffer {
+                    buffer,
+                    range: 0..size,
+                    dynamic_index: None,
+                },
+            );
+            buffers.push(buffer);
+            buffer
+        };
+
+        storage(
+            "ParticleEmitter_particles",
+            capacity * PARTICLE_SIZE,
+            None,
+            BufferUsage::empty(),
+        );
+        // every particle starts out dead
+        let dead = std::iter::once(emitter.capacity)
+            .chain(0..emitter.capacity)
+            .collect::<Vec<u32>>();
+        storage(
+            "ParticleEmitter_dead",
+            dead.as_bytes().len() as u64,
+            Some(dead.as_bytes()),
+            BufferUsage::empty(),
+        );
+        storage(
+            "ParticleEmitter_alive",
+            2 * capacity * std::mem::size_of::<u32>() as u64,
+            None,
+            BufferUsage::empty(),
+        );
+        let draw_args = [DrawIndexedIndirectArgs {
+            index_count,
+            ..Default::default()
+        }; 2];
+        let draw_args = storage(
+            "ParticleEmitter_draw_args",
+            2 * DRAW_ARGS_SIZE,
+            Some(draw_args.as_bytes()),
+            BufferUsage::INDIRECT | BufferUsage::COPY_DST,
+        );
+
+        let (sort_size, sort_steps) = if emitter.sort {
+            let sort_size = emitter.capacity.next_power_of_two().max(WORKGROUP_SIZE);
+            storage(
+                "ParticleEmitter_sort_keys",
+                sort_size as u64 * std::mem::size_of::<[u32; 2]>() as u64,
+                None,
+                BufferUsage::empty(),
+            );
+
+            let steps = sort_steps(sort_size);
+            let mut data = vec![0; steps.len() * UNIFORM_ALIGNMENT as usize];
+            for (step, chunk) in steps
+                .iter()
+                .zip(data.chunks_mut(UNIFORM_ALIGNMENT as usize))
+            {
+                chunk[0..8].copy_from_slice(step.as_bytes());
+            }
+            let steps_buffer = render_resource_context.create_buffer_with_data(
+                BufferInfo {
+                    size: data.len(),
+                    buffer_usage: BufferUsage::UNIFORM,
+                    ..Default::default()
+                },
+                &data,
+            );
+            buffers.push(steps_buffer);
+            let sort_steps = (0..steps.len() as u64)
+                .map(|step| {
+                    let offset = step * UNIFORM_ALIGNMENT;
+                    BindGroup::build()
+                        .add_buffer(0, steps_buffer, offset..offset + 16)
+                        .finish()
+                })
+                .collect();
+            (sort_size, sort_steps)
+        } else {
+            (0, Vec::new())
+        };
+
+        let uniform = render_resource_context.create_buffer(BufferInfo {
+            size: UNIFORM_SIZE as usize,
+            buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            ..Default::default()
+        });
+        bindings.set(
+            "ParticleEmitter",
+            RenderResourceBinding::Buffer {
+                buffer: uniform,
+                range: 0..UNIFORM_SIZE,
+                dynamic_index: None,
+            },
+        );
+        let staging = render_resource_context.create_buffer(BufferInfo {
+            size: (UNIFORM_SIZE + DRAW_ARGS_SIZE) as usize,
+            buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+            mapped_at_creation: true,
+        });
+        for buffer in buffers.iter().chain([uniform, staging].iter()) {
+            render_resource_context.set_resource_owner(owner, RenderResourceId::Buffer(*buffer));
+        }
+
+        EmitterBuffers {
+            capacity: emitter.capacity,
+            sort_size,
+            uniform,
+            draw_args,
+            staging,
+            sort_steps,
+            in_list: 0,
+            spawn_remainder: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ParticleNodeState {
+    command_queue: CommandQueue,
+    dispatches: Arc<Mutex<Vec<ParticleDispatch>>>,
+    camera_name: Cow<'static, str>,
+    emitters: HashMap<Entity, EmitterBuffers>,
+    frame: u32,
+}
+
+fn owner(entity: Entity) -> RenderResourceOwner {
+    RenderResourceOwner::Entity(entity, TypeId::of::<ParticleEmitter>())
+}
+
+/// Reflects the layout of the pipeline the first time it is used and creates it
+fn prepare_pipeline(
+    handle: &Handle<ComputePipelineDescriptor>,
+    pipelines: &mut Assets<ComputePipelineDescriptor>,
+    shaders: &Assets<Shader>,
+    render_resource_context: &dyn RenderResourceContext,
+) {
+    if pipelines.get(handle).unwrap().layout.is_none() {
+        pipelines.get_mut(handle).unwrap().reflect_layout(shaders);
+    }
+    render_resource_context.create_compute_pipeline(
+        handle.clone_weak(),
+        pipelines.get(handle).unwrap(),
+        shaders,
+    );
+}
+
+fn bind_group_descriptor(pipeline: &ComputePipelineDescriptor, index: u32) -> &BindGroupDescriptor {
+    pipeline
+        .get_layout()
+        .unwrap()
+        .bind_groups
+        .iter()
+        .find(|bind_group| bind_group.index == index)
+        .unwrap()
+}
+
+/// Creates the bind group of `descriptor` from the emitter's `bindings` for this frame
+fn emitter_bind_group(
+    descriptor: &BindGroupDescriptor,
+    bindings: &mut RenderResourceBindings,
+    render_resource_context: &dyn RenderResourceContext,
+) -> Option<(u32, BindGroupDescriptorId, BindGroupId)> {
+    match bindings.update_bind_group(descriptor) {
+        BindGroupStatus::Changed(id) | BindGroupStatus::Unchanged(id) => {
+            render_resource_context
+                .create_bind_group(descriptor.id, bindings.get_bind_group(id).unwrap());
+            Some((descriptor.index, descriptor.id, id))
+        }
+        BindGroupStatus::NoMatch => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn particle_node_system(
+    mut state: Local<ParticleNodeState>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    render_capabilities: Res<RenderCapabilities>,
+    active_cameras: Res<ActiveCameras>,
+    time: Res<Time>,
+    shaders: Res<Assets<Shader>>,
+    meshes: Res<Assets<Mesh>>,
+    mut pipelines: ResMut<Assets<ComputePipelineDescriptor>>,
+    camera_transforms: Query<&GlobalTransform>,
+    mut query: Query<(
+        Entity,
+        &ParticleEmitter,
+        &GlobalTransform,
+        &Handle<Mesh>,
+        &Draw,
+        &mut RenderPipelines,
+    )>,
+) {
+    let state = state.deref_mut();
+    let render_resource_context = &**render_resource_context;
+
+    // this also covers despawned entities
+    for entity in query.removed::<ParticleEmitter>() {
+        state.emitters.remove(entity);
+        render_resource_context.release_owner_resources(owner(*entity));
+    }
+
+    if !render_capabilities.supports_compute() {
+        return;
+    }
+    let view = match active_cameras
+        .get(&state.camera_name)
+        .and_then(|entity| camera_transforms.get(entity).ok())
+    {
+        Some(view) => *view,
+        None => return,
+    };
+
+    for handle in [
+        &PARTICLE_EMIT_PIPELINE_HANDLE,
+        &PARTICLE_SIMULATE_PIPELINE_HANDLE,
+        &PARTICLE_SORT_PIPELINE_HANDLE,
+    ]
+    .iter()
+    {
+        prepare_pipeline(handle, &mut pipelines, &shaders, render_resource_context);
+    }
+    let emit_bind_group =
+        bind_group_descriptor(pipelines.get(&PARTICLE_EMIT_PIPELINE_HANDLE).unwrap(), 0);
+    let simulate_bind_group = bind_group_descriptor(
+        pipelines.get(&PARTICLE_SIMULATE_PIPELINE_HANDLE).unwrap(),
+        0,
+    );
+    let sort_pipeline = pipelines.get(&PARTICLE_SORT_PIPELINE_HANDLE).unwrap();
+    let sort_bind_group = bind_group_descriptor(sort_pipeline, 0);
+    let sort_step_bind_group = bind_group_descriptor(sort_pipeline, 1);
+
+    let mut dispatches = state.dispatches.lock();
+    // left over if the node didn't run last frame
+    dispatches.clear();
+    state.frame = state.frame.wrapping_add(1);
+    for (entity, emitter, global_transform, mesh_handle, draw, mut render_pipelines) in
+        query.iter_mut()
+    {
+        if !draw.is_visible || emitter.capacity == 0 {
+            continue;
+        }
+        let index_count = match meshes
+            .get(mesh_handle)
+            .and_then(|mesh| mesh.indices.as_ref())
+        {
+            Some(Indices::U16(indices)) => indices.len() as u32,
+            Some(Indices::U32(indices)) => indices.len() as u32,
+            None => continue,
+        };
+
+        let render_pipelines = render_pipelines.deref_mut();
+        let outdated = state.emitters.get(&entity).map_or(true, |buffers| {
+            buffers.capacity != emitter.capacity || (buffers.sort_size > 0) != emitter.sort
+        });
+        if outdated {
+            if state.emitters.remove(&entity).is_some() {
+                render_resource_context.release_owner_resources(owner(entity));
+            }
+            state.emitters.insert(
+                entity,
+                EmitterBuffers::new(
+                    owner(entity),
+                    emitter,
+                    index_count,
+                    render_resource_context,
+                    &mut render_pipelines.bindings,
+                ),
+            );
+        }
+        let buffers = state.emitters.get_mut(&entity).unwrap();
+
+        let spawn = emitter.rate.max(0.0) * time.delta_seconds + buffers.spawn_remainder;
+        let spawn_count = spawn.floor();
+        buffers.spawn_remainder = spawn - spawn_count;
+        let spawn_count = spawn_count as u32;
+
+        let in_list = buffers.in_list;
+        let out_list = 1 - in_list;
+        let right = view.rotation * Vec3::unit_x();
+        let up = view.rotation * Vec3::unit_y();
+        let velocity = emitter.velocity;
+        let acceleration = emitter.acceleration;
+        let uniform = EmitterUniform {
+            transform: global_transform.compute_matrix().to_cols_array_2d(),
+            view_position: view.translation.extend(1.0).into(),
+            view_right: right.extend(0.0).into(),
+            view_up: up.extend(0.0).into(),
+            velocity: velocity.extend(emitter.velocity_randomness).into(),
+            acceleration: acceleration.extend(0.0).into(),
+            start_color: emitter.start_color.into(),
+            end_color: emitter.end_color.into(),
+            shape: [
+                emitter.start_size,
+                emitter.end_size,
+                emitter.lifetime,
+                emitter.radius,
+            ],
+            capacity: buffers.capacity,
+            spawn_count,
+            in_list,
+            seed: state.frame.wrapping_mul(0x9e37_79b9) ^ entity.id(),
+            sort_size: buffers.sort_size,
+            delta_seconds: time.delta_seconds,
+            _padding: [0; 2],
+        };
+        let reset_draw_args = DrawIndexedIndirectArgs {
+            index_count,
+            ..Default::default()
+        };
+        render_resource_context.map_buffer(buffers.staging);
+        render_resource_context.write_mapped_buffer(
+            buffers.staging,
+            0..UNIFORM_SIZE + DRAW_ARGS_SIZE,
+            &mut |data, _renderer| {
+                data[0..UNIFORM_SIZE as usize].copy_from_slice(uniform.as_bytes());
+                data[UNIFORM_SIZE as usize..].copy_from_slice(reset_draw_args.as_bytes());
+            },
+        );
+        render_resource_context.unmap_buffer(buffers.staging);
+        state.command_queue.copy_buffer_to_buffer(
+            buffers.staging,
+            0,
+            buffers.uniform,
+            0,
+            UNIFORM_SIZE,
+        );
+        // the simulation counts the survivors into the other list
+        state.command_queue.copy_buffer_to_buffer(
+            buffers.staging,
+            UNIFORM_SIZE,
+            buffers.draw_args,
+            out_list as u64 * DRAW_ARGS_SIZE,
+            DRAW_ARGS_SIZE,
+        );
+
+        let bindings = &mut render_pipelines.bindings;
+        if spawn_count > 0 {
+            if let Some(bind_group) =
+                emitter_bind_group(emit_bind_group, bindings, render_resource_context)
+            {
+                dispatches.push(ParticleDispatch {
+                    pass: ParticlePass::Emit,
+                    bind_groups: vec![bind_group],
+                    workgroups: (spawn_count + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+                });
+            }
+        }
+        if let Some(bind_group) =
+            emitter_bind_group(simulate_bind_group, bindings, render_resource_context)
+        {
+            dispatches.push(ParticleDispatch {
+                pass: ParticlePass::Simulate,
+                bind_groups: vec![bind_group],
+                workgroups: (buffers.capacity + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+            });
+        }
+        if buffers.sort_size > 0 {
+            if let Some(bind_group) =
+                emitter_bind_group(sort_bind_group, bindings, render_resource_context)
+            {
+                for step in buffers.sort_steps.iter() {
+                    render_resource_context.create_bind_group(sort_step_bind_group.id, step);
+                    dispatches.push(ParticleDispatch {
+                        pass: ParticlePass::Sort,
+                        bind_groups: vec![
+                            bind_group,
+                            (sort_step_bind_group.index, sort_step_bind_group.id, step.id),
+                        ],
+                        workgroups: buffers.sort_size / WORKGROUP_SIZE,
+                    });
+                }
+            }
+        }
+
+        bindings.set(
+            INDIRECT_DRAW_ARGS,
+            RenderResourceBinding::Buffer {
+                buffer: buffers.draw_args,
+                range: out_list as u64 * DRAW_ARGS_SIZE..(out_list as u64 + 1) * DRAW_ARGS_SIZE,
+                dynamic_index: None,
+            },
+        );
+        for render_pipeline in render_pipelines.pipelines.iter_mut() {
+            let shader_defs = &mut render_pipeline
+                .specialization
+                .shader_specialization
+                .shader_defs;
+            shader_defs.insert(INDIRECT_DRAW_SHADER_DEF.to_string());
+            if buffers.sort_size > 0 {
+                shader_defs.insert(PARTICLE_SORT_SHADER_DEF.to_string());
+            }
+        }
+        buffers.in_list = out_list;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniform_layout_matches_shader() {
+        // a mat4, 8 vec4s and 6 scalars padded to a multiple of 16 bytes
+        assert_eq!(UNIFORM_SIZE, 4 * 16 + 8 * 16 + 8 * 4);
+    }
+
+    #[test]
+    fn sort_steps_sort_far_to_near() {
+        let size = 64;
+        let steps = sort_steps(size);
+        // log2(64) = 6 stages of 1 to 6 steps, after the step that fills the keys
+        assert_eq!(steps.len(), 1 + 21);
+        assert_eq!(steps[0], [0, 0]);
+
+        // runs the compare and swap of the sort shader for every invocation of each step
+        let mut keys = (0..size).map(|i| (i * 37 + 11) % 101).collect::<Vec<u32>>();
+        for &[j, k] in steps.iter().skip(1) {
+            for i in 0..size {
+                let partner = i ^ j;
+                if partner <= i {
+                    continue;
+                }
+                let descending = i & k == 0;
+                if (keys[i as usize] < keys[partner as usize]) == descending {
+                    keys.swap(i as usize, partner as usize);
+                }
+            }
+        }
+
+        let mut expected = keys.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(keys, expected);
+    }
+}
@@ -0,0 +1,106 @@
+use bevy_math::Vec3;
+
+/// A scalar field whose zero crossing is a surface. Values are negative inside the surface and positive outside, like
+/// a signed distance field (SDF). Fields don't have to be exact distances, but surfaces are smoothest and raycasts
+/// fastest when they are.
+///
+/// Any `Fn(Vec3) -> f32` closure is a scalar field.
+pub trait ScalarField: Send + Sync + 'static {
+    fn sample(&self, position: Vec3) -> f32;
+}
+
+impl<F: Fn(Vec3) -> f32 + Send + Sync + 'static> ScalarField for F {
+    fn sample(&self, position: Vec3) -> f32 {
+        self(position)
+    }
+}
+
+/// A primitive shape with an exact signed distance field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SdfShape {
+    Sphere { center: Vec3, radius: f32 },
+    Cuboid { center: Vec3, half_extents: Vec3 },
+}
+
+impl SdfShape {
+    pub fn distance(&self, position: Vec3) -> f32 {
+        match *self {
+            SdfShape::Sphere { center, radius } => (position - center).length() - radius,
+            SdfShape::Cuboid {
+                center,
+                half_extents,
+            } => {
+                let q = (position - center).abs() - half_extents;
+                q.max(Vec3::zero()).length() + q.x().max(q.y().max(q.z())).min(0.0)
+            }
+        }
+    }
+
+    /// The minimum and maximum corner of the box around the shape
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        match *self {
+            SdfShape::Sphere { center, radius } => {
+                (center - Vec3::splat(radius), center + Vec3::splat(radius))
+            }
+            SdfShape::Cuboid {
+                center,
+                half_extents,
+            } => (center - half_extents, center + half_extents),
+        }
+    }
+}
+
+impl ScalarField for SdfShape {
+    fn sample(&self, position: Vec3) -> f32 {
+        self.distance(position)
+    }
+}
+
+/// How an [SdfEdit] combines its shape with the field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    /// Fills the shape
+    Union,
+    /// Carves the shape out
+    Subtract,
+}
+
+/// A change to the field of an [IsoSurface](super::IsoSurface), like a crater from an explosion or a dug out tunnel.
+/// Edits are applied in the order they were made.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdfEdit {
+    pub shape: SdfShape,
+    pub operation: CsgOperation,
+}
+
+impl SdfEdit {
+    pub fn union(shape: SdfShape) -> Self {
+        SdfEdit {
+            shape,
+            operation: CsgOperation::Union,
+        }
+    }
+
+    pub fn subtract(shape: SdfShape) -> Self {
+        SdfEdit {
+            shape,
+            operation: CsgOperation::Subtract,
+        }
+    }
+
+    /// Combines `value`, sampled from the field at `position`, with the edit's shape
+    pub fn apply(&self, value: f32, position: Vec3) -> f32 {
+        let distance = self.shape.distance(position);
+        match self.operation {
+            CsgOperation::Union => value.min(distance),
+            CsgOperation::Subtract => value.max(-distance),
+        }
+    }
+
+    /// Whether the edit's shape overlaps the box from `min` to `max`. The surface outside the shape's bounds keeps its
+    /// shape, so chunks the edit doesn't overlap don't have to be meshed again.
+    pub fn overlaps(&self, min: Vec3, max: Vec3) -> bool {
+        let (shape_min, shape_max) = self.shape.bounds();
+        shape_min.cmple(max).all() && shape_max.cmpge(min).all()
+    }
+}
@@ -0,0 +1,372 @@
+//! Meshes generated from scalar fields like signed distance fields (SDFs), for voxel terrain, caves and destructible
+//! geometry.
+//!
+//! An [IsoSurface] is split into chunks that are meshed with [surface_nets] on the
+//! [AsyncComputeTaskPool](bevy_tasks::AsyncComputeTaskPool), so a large surface fills in over a few frames instead of
+//! stalling one. [IsoSurface::edit] changes the field, and only the chunks the edit overlaps are meshed again. Their new
+//! meshes replace the old ones in place, so the chunks keep their mesh handles.
+
+mod field;
+mod surface_nets;
+
+pub use field::*;
+pub use surface_nets::*;
+
+use crate::{entity::PbrComponents, material::StandardMaterial};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+#[cfg(not(target_arch = "wasm32"))]
+use bevy_core::{AddAsyncTask, AsyncTask};
+#[cfg(not(target_arch = "wasm32"))]
+use bevy_ecs::Res;
+use bevy_ecs::{Added, Commands, Entity, IntoQuerySystem, Query, ResMut};
+use bevy_math::Vec3;
+use bevy_render::{draw::Draw, mesh::Mesh};
+#[cfg(not(target_arch = "wasm32"))]
+use bevy_tasks::AsyncComputeTaskPool;
+use bevy_transform::prelude::{BuildChildren, Transform};
+use bevy_utils::HashMap;
+use std::sync::Arc;
+
+/// The surface where a [ScalarField] crosses zero. The surface spans `0..chunks * chunk_size` on each of its local
+/// axes, and its chunks are spawned as children of its entity once it is added.
+pub struct IsoSurface {
+    field: Arc<dyn ScalarField>,
+    edits: Vec<SdfEdit>,
+    field_revision: u32,
+    /// The material of the chunks. Only read when the chunks are spawned.
+    pub material: Handle<StandardMaterial>,
+    /// The number of chunks along the X, Y and Z axes. Only read when the chunks are spawned.
+    pub chunks: (u32, u32, u32),
+    pub chunk_size: f32,
+    /// The number of cells along each side of a chunk
+    pub chunk_resolution: u32,
+    /// The most chunks of this surface that are meshed at once. The other chunks wait for their turn.
+    pub max_meshing_chunks: usize,
+}
+
+impl IsoSurface {
+    pub fn new(field: impl ScalarField) -> Self {
+        IsoSurface {
+            field: Arc::new(field),
+            edits: Vec::new(),
+            field_revision: 0,
+            material: Default::default(),
+            chunks: (4, 4, 4),
+            chunk_size: 16.0,
+            chunk_resolution: 16,
+            max_meshing_chunks: 16,
+        }
+    }
+
+    pub fn field(&self) -> &dyn ScalarField {
+        &*self.field
+    }
+
+    /// Replaces the field and drops all edits, so every chunk is meshed again
+    pub fn set_field(&mut self, field: impl ScalarField) {
+        self.field = Arc::new(field);
+        self.edits.clear();
+        self.field_revision += 1;
+    }
+
+    /// Applies `edit` on top of the field and the previous edits. The chunks it overlaps are meshed again.
+    pub fn edit(&mut self, edit: SdfEdit) {
+        self.edits.push(edit);
+    }
+
+    pub fn edits(&self) -> &[SdfEdit] {
+        &self.edits
+    }
+
+    pub fn size(&self) -> Vec3 {
+        let (x, y, z) = self.chunks;
+        Vec3::new(x as f32, y as f32, z as f32) * self.chunk_size
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.chunk_size / self.chunk_resolution.max(1) as f32
+    }
+
+    /// The value of the edited field at `position`, in the surface's local space
+    pub fn sample(&self, position: Vec3) -> f32 {
+        sample_edited(&*self.field, &self.edits, position)
+    }
+
+    /// Returns the first point where the ray from `origin` along `direction` enters the surface, in the surface's local
+    /// space. The ray is walked in steps of half a cell, so thin features can be missed.
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<Vec3> {
+        let direction = direction.normalize();
+        let step = 0.5 * self.cell_size();
+        let size = self.size();
+        let is_inside = |point: Vec3| {
+            point.cmpge(Vec3::zero()).all() && point.cmple(size).all() && self.sample(point) < 0.0
+        };
+
+        let mut previous = 0.0;
+        let mut distance = 0.0;
+        while distance <= max_distance {
+            if is_inside(origin + direction * distance) {
+                if distance == 0.0 {
+                    return Some(origin);
+                }
+
+                // refine the intersection between the last point outside the surface and the first point inside it
+                let (mut outside, mut inside) = (previous, distance);
+                for _ in 0..16 {
+                    let middle = (outside + inside) * 0.5;
+                    if is_inside(origin + direction * middle) {
+                        inside = middle;
+                    } else {
+                        outside = middle;
+                    }
+                }
+                return Some(origin + direction * inside);
+            }
+            previous = distance;
+            distance += step;
+        }
+
+        None
+    }
+
+    fn revision(&self) -> FieldRevision {
+        FieldRevision {
+            field: self.field_revision,
+            edits: self.edits.len(),
+        }
+    }
+
+    fn chunk_origin(&self, coordinates: (u32, u32, u32)) -> Vec3 {
+        let (x, y, z) = coordinates;
+        Vec3::new(x as f32, y as f32, z as f32) * self.chunk_size
+    }
+
+    /// The box around the field samples of a chunk, grown by two cells. Edits outside of it can't change the chunk's
+    /// mesh, as long as the field and the edit shapes are distance fields.
+    fn chunk_bounds(&self, coordinates: (u32, u32, u32)) -> (Vec3, Vec3) {
+        let min = self.chunk_origin(coordinates) - Vec3::splat(2.0 * self.cell_size());
+        let max = min + Vec3::splat((self.chunk_resolution + 5) as f32 * self.cell_size());
+        (min, max)
+    }
+
+    /// Whether a chunk mesh built from `revision` matches the current field
+    fn is_current(&self, revision: Option<FieldRevision>, coordinates: (u32, u32, u32)) -> bool {
+        match revision {
+            Some(revision) if revision.field == self.field_revision => {
+                let (min, max) = self.chunk_bounds(coordinates);
+                self.edits[revision.edits..]
+                    .iter()
+                    .all(|edit| !edit.overlaps(min, max))
+            }
+            _ => false,
+        }
+    }
+}
+
+fn sample_edited(field: &dyn ScalarField, edits: &[SdfEdit], position: Vec3) -> f32 {
+    edits.iter().fold(field.sample(position), |value, edit| {
+        edit.apply(value, position)
+    })
+}
+
+/// The field and edits a chunk mesh was built from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FieldRevision {
+    field: u32,
+    edits: usize,
+}
+
+/// The field a chunk is meshed from on the task pool, with the edits that overlap the chunk
+struct ChunkField {
+    field: Arc<dyn ScalarField>,
+    edits: Vec<SdfEdit>,
+}
+
+impl ScalarField for ChunkField {
+    fn sample(&self, position: Vec3) -> f32 {
+        sample_edited(&*self.field, &self.edits, position)
+    }
+}
+
+/// One chunk of an [IsoSurface]. Chunks are spawned as children of their surface and are hidden while their part of
+/// the surface is empty.
+#[derive(Debug)]
+pub struct IsoChunk {
+    pub surface: Entity,
+    pub coordinates: (u32, u32, u32),
+    meshed: Option<FieldRevision>,
+    meshing: Option<FieldRevision>,
+}
+
+impl IsoChunk {
+    /// Whether the chunk has a mesh, even if an out of date one
+    pub fn is_meshed(&self) -> bool {
+        self.meshed.is_some()
+    }
+
+    /// Whether a new mesh is being built for the chunk
+    pub fn is_meshing(&self) -> bool {
+        self.meshing.is_some()
+    }
+}
+
+/// A finished [IsoChunk] mesh, waiting to replace the chunk's mesh
+pub struct IsoChunkMesh(Option<Mesh>);
+
+#[derive(Default)]
+pub struct ProcgenPlugin;
+
+impl Plugin for ProcgenPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_async_task::<IsoChunkMesh>();
+        app.add_system(iso_chunk_mesh_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, iso_surface_chunk_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, iso_chunk_meshing_system.system());
+    }
+}
+
+/// Spawns the chunks of new [IsoSurface]s
+pub fn iso_surface_chunk_system(mut commands: Commands, query: Query<(Entity, Added<IsoSurface>)>) {
+    for (entity, surface) in query.iter() {
+        let (x_chunks, y_chunks, z_chunks) = surface.chunks;
+        let mut chunks = Vec::new();
+        for z in 0..z_chunks {
+            for y in 0..y_chunks {
+                for x in 0..x_chunks {
+                    commands
+                        .spawn(PbrComponents {
+                            material: surface.material.clone(),
+                            draw: Draw {
+                                is_visible: false,
+                                ..Default::default()
+                            },
+                            transform: Transform::from_translation(surface.chunk_origin((x, y, z))),
+                            ..Default::default()
+                        })
+                        .with(IsoChunk {
+                            surface: entity,
+                            coordinates: (x, y, z),
+                            meshed: None,
+                            meshing: None,
+                        });
+                    chunks.push(commands.current_entity().unwrap());
+                }
+            }
+        }
+        commands.push_children(entity, &chunks);
+    }
+}
+
+/// Starts meshing the [IsoChunk]s whose meshes don't match their surface's field anymore. A chunk that is edited while
+/// it is being meshed starts over, which cancels the task that was meshing it.
+pub fn iso_chunk_meshing_system(
+    mut commands: Commands,
+    #[cfg(not(target_arch = "wasm32"))] task_pool: Res<AsyncComputeTaskPool>,
+    surface_query: Query<&IsoSurface>,
+    mut chunk_query: Query<(Entity, &mut IsoChunk)>,
+) {
+    let mut meshing_chunks = HashMap::<Entity, usize>::default();
+    for (_, chunk) in chunk_query.iter_mut() {
+        if chunk.is_meshing() {
+            *meshing_chunks.entry(chunk.surface).or_default() += 1;
+        }
+    }
+
+    for (entity, mut chunk) in chunk_query.iter_mut() {
+        let surface = if let Ok(surface) = surface_query.get(chunk.surface) {
+            surface
+        } else {
+            continue;
+        };
+
+        let revision = surface.revision();
+        let latest = chunk.meshing.or(chunk.meshed);
+        if latest == Some(revision) {
+            continue;
+        }
+        if surface.is_current(latest, chunk.coordinates) {
+            // none of the new edits touch this chunk
+            if chunk.is_meshing() {
+                chunk.meshing = Some(revision);
+            } else {
+                chunk.meshed = Some(revision);
+            }
+            continue;
+        }
+
+        if !chunk.is_meshing() {
+            let meshing = meshing_chunks.entry(chunk.surface).or_default();
+            if *meshing >= surface.max_meshing_chunks {
+                continue;
+            }
+            *meshing += 1;
+        }
+
+        let (min, max) = surface.chunk_bounds(chunk.coordinates);
+        let field = ChunkField {
+            field: surface.field.clone(),
+            edits: surface
+                .edits
+                .iter()
+                .filter(|edit| edit.overlaps(min, max))
+                .copied()
+                .collect(),
+        };
+        let origin = surface.chunk_origin(chunk.coordinates);
+        let cell_size = surface.cell_size();
+        let resolution = surface.chunk_resolution;
+        let build_mesh =
+            move || IsoChunkMesh(Some(surface_nets(&field, origin, cell_size, resolution)));
+
+        // replacing the AsyncTask of a chunk that is still being meshed cancels it
+        #[cfg(not(target_arch = "wasm32"))]
+        commands.insert_one(
+            entity,
+            AsyncTask::spawn(&task_pool, async move { build_mesh() }),
+        );
+        #[cfg(target_arch = "wasm32")]
+        commands.insert_one(entity, build_mesh());
+        chunk.meshing = Some(revision);
+    }
+}
+
+/// Replaces the meshes of [IsoChunk]s with the ones that finished meshing
+pub fn iso_chunk_mesh_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut query: Query<(
+        Entity,
+        &mut IsoChunk,
+        &mut IsoChunkMesh,
+        &mut Handle<Mesh>,
+        &mut Draw,
+    )>,
+) {
+    for (entity, mut chunk, mut chunk_mesh, mut mesh_handle, mut draw) in query.iter_mut() {
+        commands.remove_one::<IsoChunkMesh>(entity);
+        let mesh = if let Some(mesh) = chunk_mesh.0.take() {
+            mesh
+        } else {
+            continue;
+        };
+        chunk.meshed = chunk.meshing.take();
+
+        let is_empty = mesh
+            .indices
+            .as_ref()
+            .map_or(true, |indices| indices.is_empty());
+        draw.is_visible = !is_empty;
+        if is_empty {
+            if *mesh_handle != Handle::default() {
+                meshes.remove(&*mesh_handle);
+                *mesh_handle = Handle::default();
+            }
+        } else if *mesh_handle == Handle::default() {
+            *mesh_handle = meshes.add(mesh);
+        } else {
+            meshes.set(&*mesh_handle, mesh);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use super::ScalarField;
+use bevy_math::Vec3;
+use bevy_render::{
+    mesh::{Indices, Mesh},
+    pipeline::PrimitiveTopology,
+};
+use std::borrow::Cow;
+
+/// Extracts the surface of `field` in one chunk of a grid with surface nets. The chunk has `resolution` cells of
+/// `cell_size` along each axis, starting at `origin`. Vertices are relative to `origin`.
+///
+/// Surface nets places one vertex in each cell the surface passes through and connects the vertices of the four cells
+/// around each grid edge the surface crosses. Compared to marching cubes, the meshes are smoother and have fewer
+/// triangles, and vertices are shared between triangles. The field is sampled one cell beyond the chunk on each side,
+/// and each chunk only emits the faces of the grid edges it owns, so neighboring chunks fit together without gaps or
+/// overlapping triangles.
+pub fn surface_nets<F: ScalarField + ?Sized>(
+    field: &F,
+    origin: Vec3,
+    cell_size: f32,
+    resolution: u32,
+) -> Mesh {
+    let n = resolution as usize;
+    // samples cover the points 0..=n + 1 along each axis, so there are n + 1 cells per axis
+    let points = n + 2;
+    let cells = n + 1;
+    let mut samples = Vec::with_capacity(points * points * points);
+    for z in 0..points {
+        for y in 0..points {
+            for x in 0..points {
+                let local = Vec3::new(x as f32, y as f32, z as f32) * cell_size;
+                samples.push(field.sample(origin + local));
+            }
+        }
+    }
+    let sample = |[x, y, z]: [usize; 3]| samples[(z * points + y) * points + x];
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut cell_vertices = vec![u32::MAX; cells * cells * cells];
+    let cell_index = |[x, y, z]: [usize; 3]| (z * cells + y) * cells + x;
+    for z in 0..cells {
+        for y in 0..cells {
+            for x in 0..cells {
+                // corner i is offset by (i & 1, i >> 1 & 1, i >> 2 & 1)
+                let mut corners = [0.0; 8];
+                for (i, corner) in corners.iter_mut().enumerate() {
+                    *corner = sample([x + (i & 1), y + (i >> 1 & 1), z + (i >> 2 & 1)]);
+                }
+                let inside = corners.iter().filter(|value| **value < 0.0).count();
+                if inside == 0 || inside == 8 {
+                    continue;
+                }
+
+                let corner_offset =
+                    |i: usize| Vec3::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32);
+                let mut crossings = Vec3::zero();
+                let mut crossing_count = 0;
+                for (i, a) in corners.iter().enumerate() {
+                    for axis_bit in [1, 2, 4].iter() {
+                        if i & axis_bit != 0 {
+                            continue;
+                        }
+                        let b = corners[i | axis_bit];
+                        if (*a < 0.0) != (b < 0.0) {
+                            let t = a / (a - b);
+                            let (start, end) = (corner_offset(i), corner_offset(i | axis_bit));
+                            crossings += start + (end - start) * t;
+                            crossing_count += 1;
+                        }
+                    }
+                }
+                let position = (Vec3::new(x as f32, y as f32, z as f32)
+                    + crossings / crossing_count as f32)
+                    * cell_size;
+
+                // the field's gradient, averaged over the cell, points out of the surface
+                let mut gradient = Vec3::zero();
+                for (i, value) in corners.iter().enumerate() {
+                    gradient += (corner_offset(i) * 2.0 - Vec3::one()) * *value;
+                }
+                let normal = if gradient.length_squared() > 0.0 {
+                    gradient.normalize()
+                } else {
+                    Vec3::unit_y()
+                };
+
+                cell_vertices[cell_index([x, y, z])] = positions.len() as u32;
+                positions.push(position.into());
+                normals.push(normal.into());
+                uvs.push(triplanar_uv(origin + position, normal));
+            }
+        }
+    }
+
+    // the chunk owns the edges that start at the points 1..=n along each axis, the edges starting at point 0 belong
+    // to the previous chunk
+    let mut indices = Vec::new();
+    for z in 1..=n {
+        for y in 1..=n {
+            for x in 1..=n {
+                let point = [x, y, z];
+                let inside = sample(point) < 0.0;
+                for axis in 0..3 {
+                    let mut next = point;
+                    next[axis] += 1;
+                    if inside == (sample(next) < 0.0) {
+                        continue;
+                    }
+
+                    // the four cells around the edge, counterclockwise when seen from the end of the edge
+                    let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+                    let mut quad = [point; 4];
+                    quad[0][b] -= 1;
+                    quad[0][c] -= 1;
+                    quad[1][c] -= 1;
+                    quad[3][b] -= 1;
+                    let quad = [
+                        cell_vertices[cell_index(quad[0])],
+                        cell_vertices[cell_index(quad[1])],
+                        cell_vertices[cell_index(quad[2])],
+                        cell_vertices[cell_index(quad[3])],
+                    ];
+                    // the surface faces from the inside to the outside of the edge
+                    if inside {
+                        indices.extend_from_slice(&[
+                            quad[0], quad[1], quad[2], quad[0], quad[2], quad[3],
+                        ]);
+                    } else {
+                        indices.extend_from_slice(&[
+                            quad[0], quad[2], quad[1], quad[0], quad[3], quad[2],
+                        ]);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.attributes
+        .insert(Cow::Borrowed(Mesh::ATTRIBUTE_POSITION), positions.into());
+    mesh.attributes
+        .insert(Cow::Borrowed(Mesh::ATTRIBUTE_NORMAL), normals.into());
+    mesh.attributes
+        .insert(Cow::Borrowed(Mesh::ATTRIBUTE_UV_0), uvs.into());
+    mesh.indices = Some(Indices::U32(indices));
+    mesh
+}
+
+/// Projects `position` onto the plane its normal faces most, so textures tile in world units on every side
+fn triplanar_uv(position: Vec3, normal: Vec3) -> [f32; 2] {
+    let normal = normal.abs();
+    if normal.x() >= normal.y() && normal.x() >= normal.z() {
+        [position.z(), position.y()]
+    } else if normal.y() >= normal.z() {
+        [position.x(), position.z()]
+    } else {
+        [position.x(), position.y()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::surface_nets;
+    use crate::procgen::SdfShape;
+    use bevy_math::Vec3;
+    use bevy_render::mesh::{Indices, Mesh, VertexAttributeValues};
+    use bevy_utils::HashMap;
+
+    fn triangles(mesh: &Mesh) -> Vec<[u32; 3]> {
+        match mesh.indices.as_ref().unwrap() {
+            Indices::U32(indices) => indices
+                .chunks_exact(3)
+                .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                .collect(),
+            Indices::U16(_) => unreachable!(),
+        }
+    }
+
+    fn positions(mesh: &Mesh) -> &[[f32; 3]] {
+        match mesh.attributes.get(Mesh::ATTRIBUTE_POSITION).unwrap() {
+            VertexAttributeValues::Float3(positions) => positions,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn sphere_is_closed_and_faces_outwards() {
+        let sphere = SdfShape::Sphere {
+            center: Vec3::zero(),
+            radius: 2.5,
+        };
+        let origin = Vec3::splat(-4.0);
+        let mesh = surface_nets(&sphere, origin, 1.0, 8);
+        let positions = positions(&mesh);
+        let triangles = triangles(&mesh);
+        assert!(!triangles.is_empty());
+
+        let mut edges = HashMap::<(u32, u32), u32>::default();
+        for triangle in triangles.iter() {
+            for i in 0..3 {
+                let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+                *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+
+                let position = Vec3::from(positions[a as usize]) + origin;
+                assert!((position.length() - 2.5).abs() < 0.5);
+            }
+
+            let [a, b, c] = [
+                Vec3::from(positions[triangle[0] as usize]),
+                Vec3::from(positions[triangle[1] as usize]),
+                Vec3::from(positions[triangle[2] as usize]),
+            ];
+            let center = (a + b + c) / 3.0 + origin;
+            assert!((b - a).cross(c - a).dot(center) >= 0.0);
+        }
+        assert!(edges.values().all(|count| *count == 2));
+    }
+
+    #[test]
+    fn chunks_fit_together() {
+        let sphere = SdfShape::Sphere {
+            center: Vec3::zero(),
+            radius: 2.5,
+        };
+        let whole = triangles(&surface_nets(&sphere, Vec3::splat(-4.0), 1.0, 8)).len();
+        let mut chunked = 0;
+        for i in 0..8 {
+            let origin = Vec3::new(
+                (i & 1) as f32 * 4.0 - 4.0,
+                (i >> 1 & 1) as f32 * 4.0 - 4.0,
+                (i >> 2 & 1) as f32 * 4.0 - 4.0,
+            );
+            chunked += triangles(&surface_nets(&sphere, origin, 1.0, 4)).len();
+        }
+        assert_eq!(chunked, whole);
+
+        let empty = surface_nets(&|_: Vec3| 1.0, Vec3::zero(), 1.0, 4);
+        assert!(triangles(&empty).is_empty());
+    }
+}
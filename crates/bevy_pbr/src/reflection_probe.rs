@@ -0,0 +1,188 @@
+use crate::{material::StandardMaterial, render_graph::node};
+use bevy_app::prelude::*;
+use bevy_asset::Handle;
+use bevy_core::Bytes;
+use bevy_ecs::{Bundle, Commands, Entity, IntoQuerySystem, Query, With};
+use bevy_math::{Vec3, Vec4};
+use bevy_property::Properties;
+use bevy_render::{
+    render_graph::{base, RenderGraph, RenderResourcesNode},
+    renderer::{RenderResource, RenderResources},
+    shader::{self, ShaderDefs},
+    texture::Texture,
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::RegisterType;
+
+/// A local environment map for reflections on shaded [StandardMaterial]s. Entities inside the probe's box reflect its
+/// environment map, projected onto the box, so reflections of nearby walls line up with the room they were captured
+/// in. When an entity is inside several probes, the one with the closest center is used.
+///
+/// Probes are prebaked: the environment map is an equirectangular (latitude-longitude) image captured at the probe's
+/// position, for example an `.hdr` file exported from a baking tool.
+#[derive(Debug, Clone, Properties)]
+pub struct ReflectionProbe {
+    pub environment_map: Handle<Texture>,
+    /// Half the size of the axis aligned box around the probe's position that the probe affects
+    pub half_extents: Vec3,
+    pub intensity: f32,
+}
+
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        ReflectionProbe {
+            environment_map: Default::default(),
+            half_extents: Vec3::new(5.0, 5.0, 5.0),
+            intensity: 1.0,
+        }
+    }
+}
+
+impl ReflectionProbe {
+    pub fn contains(&self, center: Vec3, point: Vec3) -> bool {
+        let offset = point - center;
+        offset.x().abs() <= self.half_extents.x()
+            && offset.y().abs() <= self.half_extents.y()
+            && offset.z().abs() <= self.half_extents.z()
+    }
+}
+
+/// A component bundle for [ReflectionProbe] entities
+#[derive(Bundle, Default)]
+pub struct ReflectionProbeComponents {
+    pub reflection_probe: ReflectionProbe,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+/// The box a [ProbeReflection] is projected onto, in world space
+#[derive(Debug, Clone, Copy, PartialEq, Bytes, RenderResource)]
+pub struct ProbeVolume {
+    pub center: Vec4,
+    pub half_extents: Vec4,
+    pub intensity: f32,
+}
+
+impl ProbeVolume {
+    pub fn new(center: Vec3, half_extents: Vec3, intensity: f32) -> Self {
+        ProbeVolume {
+            center: center.extend(1.0),
+            half_extents: half_extents.extend(0.0),
+            intensity,
+        }
+    }
+}
+
+/// The [ReflectionProbe] an entity reflects. It is managed by [reflection_probe_system].
+#[derive(Debug, Clone, PartialEq, RenderResources)]
+pub struct ProbeReflection {
+    pub environment_map: Handle<Texture>,
+    pub volume: ProbeVolume,
+}
+
+impl ShaderDefs for ProbeReflection {
+    fn shader_defs_len(&self) -> usize {
+        1
+    }
+
+    fn get_shader_def(&self, index: usize) -> Option<&str> {
+        if index == 0 {
+            Some("REFLECTION_PROBE")
+        } else {
+            None
+        }
+    }
+
+    fn iter_shader_defs(&self) -> shader::ShaderDefIterator {
+        shader::ShaderDefIterator::new(self)
+    }
+}
+
+/// Adds [ReflectionProbe]s
+#[derive(Default)]
+pub struct ReflectionProbePlugin;
+
+impl Plugin for ReflectionProbePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.register_component::<ReflectionProbe>()
+            // probes are selected with last frame's transforms, so entities might enter a new probe one frame late
+            .add_system_to_stage(stage::POST_UPDATE, reflection_probe_system.system())
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                shader::shader_defs_system::<ProbeReflection>.system(),
+            );
+
+        let mut render_graph = app.resources().get_mut::<RenderGraph>().unwrap();
+        render_graph.add_system_node(
+            node::PROBE_REFLECTION,
+            RenderResourcesNode::<ProbeReflection>::new(true),
+        );
+        render_graph
+            .add_node_edge(node::PROBE_REFLECTION, base::node::MAIN_PASS)
+            .unwrap();
+    }
+}
+
+/// Assigns each entity with a [StandardMaterial] the [ReflectionProbe] it is in
+pub fn reflection_probe_system(
+    mut commands: Commands,
+    probes: Query<(&ReflectionProbe, &GlobalTransform)>,
+    entities: Query<With<Handle<StandardMaterial>, (Entity, &GlobalTransform)>>,
+    mut reflections: Query<&mut ProbeReflection>,
+) {
+    for (entity, global_transform) in entities.iter() {
+        let position = global_transform.translation;
+        let mut nearest: Option<(f32, &ReflectionProbe, Vec3)> = None;
+        for (probe, probe_transform) in probes.iter() {
+            let center = probe_transform.translation;
+            if !probe.contains(center, position) {
+                continue;
+            }
+
+            let distance = (center - position).length_squared();
+            if nearest.map_or(true, |(nearest_distance, _, _)| distance < nearest_distance) {
+                nearest = Some((distance, probe, center));
+            }
+        }
+
+        let reflection = nearest.map(|(_, probe, center)| ProbeReflection {
+            environment_map: probe.environment_map.clone_weak(),
+            volume: ProbeVolume::new(center, probe.half_extents, probe.intensity),
+        });
+        match (reflection, reflections.get_mut(entity)) {
+            (Some(reflection), Ok(mut current)) => {
+                // avoid triggering change detection every frame
+                if *current != reflection {
+                    *current = reflection;
+                }
+            }
+            (Some(reflection), Err(_)) => {
+                commands.insert_one(entity, reflection);
+            }
+            (None, Ok(_)) => {
+                commands.remove_one::<ProbeReflection>(entity);
+            }
+            (None, Err(_)) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflection_probe_contains() {
+        let probe = ReflectionProbe {
+            half_extents: Vec3::new(1.0, 2.0, 3.0),
+            ..Default::default()
+        };
+        let center = Vec3::new(10.0, 0.0, 0.0);
+        assert!(probe.contains(center, Vec3::new(11.0, -2.0, 2.5)));
+        assert!(!probe.contains(center, Vec3::new(8.5, 0.0, 0.0)));
+        assert_eq!(
+            ProbeVolume::new(center, probe.half_extents, 1.0).byte_len(),
+            36
+        );
+    }
+}
@@ -1,6 +1,7 @@
 #version 450
 
 const int MAX_LIGHTS = 10;
+const float PI = 3.141592653589793;
 
 struct Light {
     mat4 proj;
@@ -11,18 +12,53 @@ struct Light {
 layout(location = 0) in vec3 v_Position;
 layout(location = 1) in vec3 v_Normal;
 layout(location = 2) in vec2 v_Uv;
+# ifdef MESH_VERTEX_COLOR
+layout(location = 3) in vec4 v_Color;
+# endif
+# ifdef STANDARDMATERIAL_LIGHTMAP
+layout(location = 4) in vec2 v_Uv1;
+# endif
 
 layout(location = 0) out vec4 o_Target;
+# ifdef WEIGHTED_BLENDED_OIT
+layout(location = 1) out vec4 o_Revealage;
+# endif
 
 layout(set = 0, binding = 0) uniform Camera {
     mat4 ViewProj;
+    vec4 CameraPosition;
+    // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+    vec4 CameraExposure;
 };
 
 layout(set = 1, binding = 0) uniform Lights {
     uvec4 NumLights;
+    vec4 AmbientColor;
+    vec4 FogColor;
+    // x: distance the fog starts at, y: distance it is thickest at
+    vec4 FogDistance;
     Light SceneLights[MAX_LIGHTS];
 };
 
+# ifdef MATERIAL_OVERRIDES
+layout(set = 2, binding = 1) uniform MaterialOverrides {
+    vec4 Tint;
+    vec4 Emissive;
+    vec2 UvOffset;
+    vec2 UvScale;
+};
+# endif
+
+# ifdef REFLECTION_PROBE
+layout(set = 2, binding = 2) uniform ProbeReflection_volume {
+    vec4 ProbeCenter;
+    vec4 ProbeHalfExtents;
+    float ProbeIntensity;
+};
+layout(set = 2, binding = 3) uniform texture2D ProbeReflection_environment_map;
+layout(set = 2, binding = 4) uniform sampler ProbeReflection_environment_map_sampler;
+# endif
+
 layout(set = 3, binding = 0) uniform StandardMaterial_albedo {
     vec4 Albedo;
 };
@@ -32,30 +68,110 @@ layout(set = 3, binding = 1) uniform texture2D StandardMaterial_albedo_texture;
 layout(set = 3, binding = 2) uniform sampler StandardMaterial_albedo_texture_sampler;
 # endif
 
+# ifdef STANDARDMATERIAL_LIGHTMAP
+layout(set = 3, binding = 4) uniform texture2D StandardMaterial_lightmap;
+layout(set = 3, binding = 5) uniform sampler StandardMaterial_lightmap_sampler;
+# endif
+
+# ifdef REFLECTION_PROBE
+layout(set = 3, binding = 6) uniform StandardMaterial_reflectance {
+    float Reflectance;
+};
+
+// intersects the reflected ray with the probe's box, so the reflection is looked up from the probe's position
+// in the direction of the point the ray hits instead of from infinitely far away
+vec3 box_project(vec3 position, vec3 direction) {
+    vec3 box_max = ProbeCenter.xyz + ProbeHalfExtents.xyz;
+    vec3 box_min = ProbeCenter.xyz - ProbeHalfExtents.xyz;
+    vec3 furthest = max((box_max - position) / direction, (box_min - position) / direction);
+    float distance = min(min(furthest.x, furthest.y), furthest.z);
+    return position + direction * distance - ProbeCenter.xyz;
+}
+
+vec2 equirectangular_uv(vec3 direction) {
+    direction = normalize(direction);
+    return vec2(atan(direction.z, direction.x) / (2.0 * PI) + 0.5, acos(clamp(direction.y, -1.0, 1.0)) / PI);
+}
+# endif
+
+// fades the color to the fog color with the distance from the camera
+vec3 apply_fog(vec3 color, vec3 position) {
+    float camera_distance = length(position - CameraPosition.xyz);
+    float fog = clamp((camera_distance - FogDistance.x) / max(FogDistance.y - FogDistance.x, 0.0001), 0.0, 1.0);
+    return mix(color, FogColor.rgb, fog * FogColor.a);
+}
+
 void main() {
     vec4 output_color = Albedo;
+# ifdef MESH_VERTEX_COLOR
+    output_color *= v_Color;
+# endif
 # ifdef STANDARDMATERIAL_ALBEDO_TEXTURE
     output_color *= texture(
         sampler2D(StandardMaterial_albedo_texture, StandardMaterial_albedo_texture_sampler),
         v_Uv);
 # endif
+# ifdef MATERIAL_OVERRIDES
+    output_color *= Tint;
+# endif
 
 # ifdef STANDARDMATERIAL_SHADED
     vec3 normal = normalize(v_Normal);
-    vec3 ambient = vec3(0.05, 0.05, 0.05);
+# ifdef STANDARDMATERIAL_LIGHTMAP
+    // baked lighting replaces the ambient light
+    vec3 ambient = texture(
+        sampler2D(StandardMaterial_lightmap, StandardMaterial_lightmap_sampler),
+        v_Uv1).rgb;
+# else
+    vec3 ambient = AmbientColor.rgb;
+# endif
     // accumulate color
     vec3 color = ambient;
     for (int i=0; i<int(NumLights.x) && i<MAX_LIGHTS; ++i) {
         Light light = SceneLights[i];
+        // directional lights store the direction towards the light, with a w of 0
+        vec3 light_dir = light.pos.w == 0.0 ? normalize(light.pos.xyz) : normalize(light.pos.xyz - v_Position);
         // compute Lambertian diffuse term
-        vec3 light_dir = normalize(light.pos.xyz - v_Position);
         float diffuse = max(0.0, dot(normal, light_dir));
         // add light contribution
         color += diffuse * light.color.xyz;
     }
     output_color.xyz *= color;
+
+# ifdef REFLECTION_PROBE
+    vec3 view_direction = normalize(v_Position - CameraPosition.xyz);
+    vec3 reflected = reflect(view_direction, normal);
+    vec3 reflection = texture(
+        sampler2D(ProbeReflection_environment_map, ProbeReflection_environment_map_sampler),
+        equirectangular_uv(box_project(v_Position, reflected))).rgb * ProbeIntensity;
+    // schlick's fresnel approximation
+    float fresnel = 0.0;
+    if (Reflectance > 0.0) {
+        float grazing = pow(1.0 - max(dot(normal, -view_direction), 0.0), 5.0);
+        fresnel = Reflectance + (1.0 - Reflectance) * grazing;
+    }
+    output_color.xyz = mix(output_color.xyz, reflection, fresnel);
+# endif
+# endif
+# ifdef MATERIAL_OVERRIDES
+    output_color.xyz += Emissive.xyz;
 # endif
+    output_color.xyz = apply_fog(output_color.xyz, v_Position);
 
+    output_color.xyz *= CameraExposure.x;
+    if (CameraExposure.y > 0.5) {
+        // reinhard
+        output_color.xyz = output_color.xyz / (1.0 + output_color.xyz);
+    }
+
+# ifdef WEIGHTED_BLENDED_OIT
+    // weights from "Weighted Blended Order-Independent Transparency" by McGuire and Bavoil. closer and more opaque
+    // surfaces count more towards the average color.
+    float weight = clamp(output_color.a * max(0.01, 3000.0 * pow(1.0 - gl_FragCoord.z, 3.0)), 0.01, 3000.0);
+    o_Target = vec4(output_color.rgb * output_color.a, output_color.a) * weight;
+    o_Revealage = vec4(output_color.a);
+# else
     // multiply the light by material color
     o_Target = output_color;
+# endif
 }
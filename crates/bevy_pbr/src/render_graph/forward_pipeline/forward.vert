@@ -3,23 +3,66 @@
 layout(location = 0) in vec3 Vertex_Position;
 layout(location = 1) in vec3 Vertex_Normal;
 layout(location = 2) in vec2 Vertex_Uv;
+# ifdef MESH_VERTEX_COLOR
+layout(location = 3) in vec4 Vertex_Color;
+# endif
+# ifdef STANDARDMATERIAL_LIGHTMAP
+layout(location = 4) in vec2 Vertex_Uv1;
+# endif
 
 layout(location = 0) out vec3 v_Position;
 layout(location = 1) out vec3 v_Normal;
 layout(location = 2) out vec2 v_Uv;
+# ifdef MESH_VERTEX_COLOR
+layout(location = 3) out vec4 v_Color;
+# endif
+# ifdef STANDARDMATERIAL_LIGHTMAP
+layout(location = 4) out vec2 v_Uv1;
+# endif
 
 layout(set = 0, binding = 0) uniform Camera {
     mat4 ViewProj;
+    vec4 CameraPosition;
+    // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+    vec4 CameraExposure;
 };
 
 layout(set = 2, binding = 0) uniform Transform {
     mat4 Model;
 };
 
+layout(set = 3, binding = 3) uniform StandardMaterial_uv_transform {
+    vec2 UvTransformOffset;
+    vec2 UvTransformScale;
+    float UvTransformRotation;
+};
+
+# ifdef MATERIAL_OVERRIDES
+layout(set = 2, binding = 1) uniform MaterialOverrides {
+    vec4 Tint;
+    vec4 Emissive;
+    vec2 UvOffset;
+    vec2 UvScale;
+};
+# endif
+
 void main() {
     v_Normal = (Model * vec4(Vertex_Normal, 1.0)).xyz;
     v_Normal = mat3(Model) * Vertex_Normal;
     v_Position = (Model * vec4(Vertex_Position, 1.0)).xyz;
-    v_Uv = Vertex_Uv;
+    // scale and rotate around the texture's center
+    vec2 uv = (Vertex_Uv - 0.5) * UvTransformScale;
+    float uv_sin = sin(UvTransformRotation);
+    float uv_cos = cos(UvTransformRotation);
+    v_Uv = vec2(uv_cos * uv.x - uv_sin * uv.y, uv_sin * uv.x + uv_cos * uv.y) + 0.5 + UvTransformOffset;
+# ifdef MATERIAL_OVERRIDES
+    v_Uv = v_Uv * UvScale + UvOffset;
+# endif
+# ifdef MESH_VERTEX_COLOR
+    v_Color = Vertex_Color;
+# endif
+# ifdef STANDARDMATERIAL_LIGHTMAP
+    v_Uv1 = Vertex_Uv1;
+# endif
     gl_Position = ViewProj * vec4(v_Position, 1.0);
 }
@@ -1,3 +1,4 @@
+use crate::order_independent_transparency;
 use bevy_asset::{Assets, Handle};
 use bevy_render::{
     pipeline::{
@@ -13,6 +14,9 @@ use bevy_type_registry::TypeUuid;
 pub const FORWARD_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
     Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 13148362314012771389);
 
+pub const OIT_FORWARD_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 6830917462307716290);
+
 pub(crate) fn build_forward_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
     PipelineDescriptor {
         rasterization_state: Some(RasterizationStateDescriptor {
@@ -60,3 +64,76 @@ pub(crate) fn build_forward_pipeline(shaders: &mut Assets<Shader>) -> PipelineDe
         })
     }
 }
+
+/// The forward pipeline of weighted blended transparency passes. Instead of blending the colors of transparent
+/// entities, it adds them up weighted by their alpha and depth, and multiplies how much of the background shows through.
+/// It is only drawn by the accumulation pass.
+pub(crate) fn build_oit_forward_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    let fragment = include_str!("forward.frag").replacen(
+        "#version 450",
+        "#version 450\n#define WEIGHTED_BLENDED_OIT",
+        1,
+    );
+    PipelineDescriptor {
+        pass_tag: Some(order_independent_transparency::node::OIT_PASS.into()),
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        // transparent entities are hidden by opaque ones, but not by each other
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Less,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![
+            // accumulation
+            ColorStateDescriptor {
+                format: TextureFormat::Rgba16Float,
+                color_blend: BlendDescriptor {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha_blend: BlendDescriptor {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                write_mask: ColorWrite::ALL,
+            },
+            // revealage
+            ColorStateDescriptor {
+                format: TextureFormat::R16Float,
+                color_blend: BlendDescriptor {
+                    src_factor: BlendFactor::Zero,
+                    dst_factor: BlendFactor::OneMinusSrcColor,
+                    operation: BlendOperation::Add,
+                },
+                alpha_blend: BlendDescriptor {
+                    src_factor: BlendFactor::Zero,
+                    dst_factor: BlendFactor::OneMinusSrcAlpha,
+                    operation: BlendOperation::Add,
+                },
+                write_mask: ColorWrite::ALL,
+            },
+        ],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("forward.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(ShaderStage::Fragment, &fragment))),
+        })
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    light::{Light, LightRaw},
+    light::{AmbientLight, Fog, Light, LightRaw},
     render_graph::uniform,
 };
 use bevy_core::{AsBytes, Byteable};
@@ -42,13 +42,18 @@ impl Node for LightsNode {
     }
 }
 
+/// The part of the lights uniform in front of the light array
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
-struct LightCount {
+struct LightsHeader {
     pub num_lights: [u32; 4],
+    pub ambient_color: [f32; 4],
+    pub fog_color: [f32; 4],
+    /// x: the distance the fog starts at, y: the distance it is thickest at
+    pub fog_distance: [f32; 4],
 }
 
-unsafe impl Byteable for LightCount {}
+unsafe impl Byteable for LightsHeader {}
 
 impl SystemNode for LightsNode {
     fn get_system(&self, commands: &mut Commands) -> Box<dyn System> {
@@ -80,6 +85,8 @@ pub fn lights_node_system(
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
     // TODO: this write on RenderResourceBindings will prevent this system from running in parallel with other systems that do the same
     mut render_resource_bindings: ResMut<RenderResourceBindings>,
+    ambient_light: Res<AmbientLight>,
+    fog: Res<Fog>,
     query: Query<(&Light, &GlobalTransform)>,
 ) {
     let state = &mut state;
@@ -87,17 +94,14 @@ pub fn lights_node_system(
 
     let light_count = query.iter().count();
     let size = std::mem::size_of::<LightRaw>();
-    let light_count_size = std::mem::size_of::<LightCount>();
+    let header_size = std::mem::size_of::<LightsHeader>();
     let light_array_size = size * light_count;
     let light_array_max_size = size * state.max_lights;
-    let current_light_uniform_size = light_count_size + light_array_size;
-    let max_light_uniform_size = light_count_size + light_array_max_size;
+    let current_light_uniform_size = header_size + light_array_size;
+    let max_light_uniform_size = header_size + light_array_max_size;
 
+    // the header holds the ambient light and fog, so it is written even when there are no lights
     if let Some(staging_buffer) = state.staging_buffer {
-        if light_count == 0 {
-            return;
-        }
-
         render_resource_context.map_buffer(staging_buffer);
     } else {
         let buffer = render_resource_context.create_buffer(BufferInfo {
@@ -128,13 +132,18 @@ pub fn lights_node_system(
         staging_buffer,
         0..current_light_uniform_size as u64,
         &mut |data, _renderer| {
-            // light count
-            data[0..light_count_size].copy_from_slice([light_count as u32, 0, 0, 0].as_bytes());
+            let header = LightsHeader {
+                num_lights: [light_count as u32, 0, 0, 0],
+                ambient_color: ambient_light.color.into(),
+                fog_color: fog.color.into(),
+                fog_distance: [fog.start, fog.end, 0.0, 0.0],
+            };
+            data[0..header_size].copy_from_slice(header.as_bytes());
 
             // light array
             for ((light, global_transform), slot) in query
                 .iter()
-                .zip(data[light_count_size..current_light_uniform_size].chunks_exact_mut(size))
+                .zip(data[header_size..current_light_uniform_size].chunks_exact_mut(size))
             {
                 slot.copy_from_slice(LightRaw::from(&light, &global_transform).as_bytes());
             }
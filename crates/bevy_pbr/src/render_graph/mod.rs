@@ -8,7 +8,21 @@ pub use lights_node::*;
 pub mod node {
     pub const TRANSFORM: &str = "transform";
     pub const STANDARD_MATERIAL: &str = "standard_material";
+    pub const MATERIAL_OVERRIDES: &str = "material_overrides";
+    pub const PROBE_REFLECTION: &str = "probe_reflection";
+    pub const SSR_NORMAL_TEXTURE: &str = "ssr_normal_texture";
+    pub const SSR_DEPTH_TEXTURE: &str = "ssr_depth_texture";
+    pub const SSR_HISTORY_TEXTURE: &str = "ssr_history_texture";
+    pub const SSR_PREPASS: &str = "ssr_prepass";
+    pub const SCREEN_SPACE_REFLECTIONS: &str = "screen_space_reflections";
+    pub const SSR_HISTORY_BLIT_PASS: &str = "ssr_history_blit_pass";
     pub const LIGHTS: &str = "lights";
+    pub const GBUFFER_ALBEDO_TEXTURE: &str = "gbuffer_albedo_texture";
+    pub const GBUFFER_NORMAL_TEXTURE: &str = "gbuffer_normal_texture";
+    pub const GBUFFER_METALLIC_ROUGHNESS_TEXTURE: &str = "gbuffer_metallic_roughness_texture";
+    pub const GBUFFER_DEPTH_TEXTURE: &str = "gbuffer_depth_texture";
+    pub const GBUFFER_PASS: &str = "gbuffer_pass";
+    pub const DEFERRED_LIGHTING_MATERIAL: &str = "deferred_lighting_material";
 }
 
 /// the names of pbr uniforms
@@ -16,7 +30,10 @@ pub mod uniform {
     pub const LIGHTS: &str = "Lights";
 }
 
-use crate::prelude::StandardMaterial;
+use crate::{
+    deferred::{self, RenderPath},
+    prelude::{MaterialOverrides, StandardMaterial},
+};
 use bevy_asset::Assets;
 use bevy_ecs::Resources;
 use bevy_render::{
@@ -26,7 +43,11 @@ use bevy_render::{
 };
 use bevy_transform::prelude::GlobalTransform;
 
-pub(crate) fn add_pbr_graph(graph: &mut RenderGraph, resources: &Resources) {
+pub(crate) fn add_pbr_graph(
+    graph: &mut RenderGraph,
+    resources: &Resources,
+    render_path: RenderPath,
+) {
     graph.add_system_node(
         node::TRANSFORM,
         RenderResourcesNode::<GlobalTransform>::new(true),
@@ -35,13 +56,30 @@ pub(crate) fn add_pbr_graph(graph: &mut RenderGraph, resources: &Resources) {
         node::STANDARD_MATERIAL,
         AssetRenderResourcesNode::<StandardMaterial>::new(true),
     );
-    graph.add_system_node(node::LIGHTS, LightsNode::new(10));
+    graph.add_system_node(
+        node::MATERIAL_OVERRIDES,
+        RenderResourcesNode::<MaterialOverrides>::new(true),
+    );
     let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
     let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
-    pipelines.set_untracked(
-        FORWARD_PIPELINE_HANDLE,
-        build_forward_pipeline(&mut shaders),
-    );
+    match render_path {
+        RenderPath::Forward => {
+            graph.add_system_node(node::LIGHTS, LightsNode::new(10));
+            pipelines.set_untracked(
+                FORWARD_PIPELINE_HANDLE,
+                build_forward_pipeline(&mut shaders),
+            );
+        }
+        RenderPath::Deferred => {
+            // forward shaders only read the first 10 lights of the larger buffer
+            graph.add_system_node(node::LIGHTS, LightsNode::new(deferred::DEFERRED_MAX_LIGHTS));
+            // entities keep their pipeline handle, so switching paths doesn't change how they are spawned
+            pipelines.set_untracked(
+                FORWARD_PIPELINE_HANDLE,
+                deferred::build_gbuffer_pipeline(&mut shaders),
+            );
+        }
+    }
 
     // TODO: replace these with "autowire" groups
     graph
@@ -50,7 +88,14 @@ pub(crate) fn add_pbr_graph(graph: &mut RenderGraph, resources: &Resources) {
     graph
         .add_node_edge(node::TRANSFORM, base::node::MAIN_PASS)
         .unwrap();
+    graph
+        .add_node_edge(node::MATERIAL_OVERRIDES, base::node::MAIN_PASS)
+        .unwrap();
     graph
         .add_node_edge(node::LIGHTS, base::node::MAIN_PASS)
         .unwrap();
+
+    if render_path == RenderPath::Deferred {
+        deferred::add_deferred_graph(graph);
+    }
 }
@@ -0,0 +1,21 @@
+#version 450
+
+layout(location = 0) in vec3 v_Normal;
+
+// rgb: the world space normal, a: the reflectance of the material
+layout(location = 0) out vec4 o_Target;
+
+# ifdef STANDARDMATERIAL_SHADED
+layout(set = 3, binding = 6) uniform StandardMaterial_reflectance {
+    float Reflectance;
+};
+# endif
+
+void main() {
+# ifdef STANDARDMATERIAL_SHADED
+    o_Target = vec4(normalize(v_Normal), Reflectance);
+# else
+    // unshaded materials don't reflect anything
+    o_Target = vec4(normalize(v_Normal), 0.0);
+# endif
+}
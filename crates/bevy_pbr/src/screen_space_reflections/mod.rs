@@ -0,0 +1,479 @@
+//! Screen space reflections for glossy surfaces, like wet or polished floors.
+//!
+//! A depth/normal prepass renders the depth, normals and reflectance of the
+//! [StandardMaterial](crate::StandardMaterial) meshes of the main pass. After the main pass,
+//! [ScreenSpaceReflectionsNode] marches the reflected ray of every pixel with a reflectance through the prepass depth,
+//! and blends the color the previous frame had where the ray hits over the lit scene, more at grazing angles. The
+//! scene is only rendered once: its colors are drawn to a history texture after the node, for the next frame.
+//! Rays that leave the screen or don't hit anything keep the reflections the main pass shaded, which is the
+//! environment of the entity's [ReflectionProbe](crate::ReflectionProbe), or no reflection without one.
+//!
+//! Reflections are seen by the 3d camera if it has [ScreenSpaceReflections]. The reflected colors already have the
+//! camera's exposure applied, and surfaces that moved since the previous frame are reflected where they were.
+
+mod screen_space_reflections_node;
+
+pub use screen_space_reflections_node::*;
+
+use crate::{material::StandardMaterial, render_graph::node};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Entity, IntoQuerySystem, Query, Res, ResMut, With, Without};
+use bevy_math::Vec2;
+use bevy_render::{
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor,
+        RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, BlendFactor, BlendOperation, ColorStateDescriptor, ColorWrite,
+        CompareFunction, CullMode, DepthStencilStateDescriptor, DynamicBinding, FrontFace,
+        PipelineDescriptor, PipelineSpecialization, RasterizationStateDescriptor, RenderPipeline,
+        RenderPipelines, StencilStateDescriptor, StencilStateFaceDescriptor,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, BaseRenderGraphBuilder, MainPass},
+        AssetTextureNode, BlitNode, Edge, PassNode, RenderGraph,
+    },
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{Texture, TextureFormat},
+};
+use bevy_type_registry::TypeUuid;
+use bevy_window::Windows;
+
+/// The depth of the prepass
+pub const SSR_DEPTH_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 11503728461092837465);
+
+/// World space normals in the red, green and blue channels, and the reflectance of the material in alpha
+pub const SSR_NORMAL_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 4621934871028457261);
+
+/// The colors of the previous frame, which the reflections are looked up in
+pub const SSR_HISTORY_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 7395016248823190417);
+
+pub const DEPTH_NORMAL_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 15206377429184021653);
+
+pub const SCREEN_SPACE_REFLECTIONS_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 3618402957130968421);
+
+/// How far and how finely the reflected rays of a 3d camera are marched. Cameras without it don't show screen space
+/// reflections.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenSpaceReflections {
+    /// The number of samples taken along each ray. More steps find thinner objects at a higher cost.
+    pub max_steps: u32,
+    /// How far behind a surface a ray can pass and still hit it, in world units
+    pub thickness: f32,
+    /// How far the rays are marched, in world units
+    pub max_distance: f32,
+    pub intensity: f32,
+}
+
+impl Default for ScreenSpaceReflections {
+    fn default() -> Self {
+        ScreenSpaceReflections {
+            max_steps: 32,
+            thickness: 0.5,
+            max_distance: 20.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Marks the meshes the depth/normal prepass draws. [ScreenSpaceReflectionsPlugin] adds it to the
+/// [StandardMaterial](crate::StandardMaterial) meshes of the main pass, together with the prepass pipeline.
+#[derive(Debug, Default)]
+pub struct DepthNormalPrepass;
+
+/// Adds screen space reflections. Add the plugin after the other render plugins, so the prepass waits for the same
+/// nodes as the main pass.
+#[derive(Default)]
+pub struct ScreenSpaceReflectionsPlugin;
+
+impl Plugin for ScreenSpaceReflectionsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_to_stage(stage::POST_UPDATE, screen_space_reflections_system.system());
+
+        let resources = app.resources();
+        // the textures are resized to the window before the first frame is rendered
+        let mut textures = resources.get_mut::<Assets<Texture>>().unwrap();
+        for (handle, format) in [
+            (SSR_DEPTH_TEXTURE_HANDLE, TextureFormat::Depth32Float),
+            (SSR_NORMAL_TEXTURE_HANDLE, TextureFormat::Rgba16Float),
+            (SSR_HISTORY_TEXTURE_HANDLE, TextureFormat::default()),
+        ]
+        .iter()
+        {
+            textures.set_untracked(
+                handle.clone_weak(),
+                Texture::new_render_target(Vec2::new(1.0, 1.0), *format),
+            );
+        }
+
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+        pipelines.set_untracked(
+            DEPTH_NORMAL_PIPELINE_HANDLE,
+            build_depth_normal_pipeline(&mut shaders),
+        );
+        pipelines.set_untracked(
+            SCREEN_SPACE_REFLECTIONS_PIPELINE_HANDLE,
+            build_screen_space_reflections_pipeline(&mut shaders),
+        );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_ssr_graph(&mut render_graph);
+    }
+}
+
+/// Renders the depth, normals and reflectance of a mesh. It is only drawn by the depth/normal prepass.
+pub fn build_depth_normal_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        pass_tag: Some(node::SSR_PREPASS.into()),
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: CompareFunction::Less,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::Rgba16Float,
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("depth_normal.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("depth_normal.frag"),
+            ))),
+        })
+    }
+}
+
+/// Blends the reflections over the scene by their alpha and keeps the scene's alpha
+pub fn build_screen_space_reflections_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: None,
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            alpha_blend: BlendDescriptor {
+                src_factor: BlendFactor::Zero,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("screen_space_reflections.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("screen_space_reflections.frag"),
+            ))),
+        })
+    }
+}
+
+/// The prepass pipeline of a mesh, with its uniforms bound dynamically
+pub fn depth_normal_pipeline() -> RenderPipeline {
+    RenderPipeline::specialized(
+        DEPTH_NORMAL_PIPELINE_HANDLE,
+        PipelineSpecialization {
+            dynamic_bindings: vec![
+                // Transform
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 0,
+                },
+                // StandardMaterial_reflectance
+                DynamicBinding {
+                    bind_group: 3,
+                    binding: 6,
+                },
+            ],
+            ..Default::default()
+        },
+    )
+}
+
+/// Keeps the prepass and history textures the size of the primary window, and adds the prepass pipeline to new
+/// [StandardMaterial](crate::StandardMaterial) meshes
+pub fn screen_space_reflections_system(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut new_meshes: Query<
+        With<
+            MainPass,
+            With<
+                Handle<StandardMaterial>,
+                Without<DepthNormalPrepass, (Entity, &mut RenderPipelines)>,
+            >,
+        >,
+    >,
+) {
+    if let Some(window) = windows.get_primary() {
+        let window_size = Vec2::new(window.width().max(1) as f32, window.height().max(1) as f32);
+        for handle in [
+            SSR_DEPTH_TEXTURE_HANDLE,
+            SSR_NORMAL_TEXTURE_HANDLE,
+            SSR_HISTORY_TEXTURE_HANDLE,
+        ]
+        .iter()
+        {
+            let texture_size = textures.get(handle).map(|texture| texture.size);
+            if texture_size.map_or(false, |size| size != window_size) {
+                // the changed texture is recreated at the new size
+                textures.get_mut(handle).unwrap().size = window_size;
+            }
+        }
+    }
+
+    for (entity, mut render_pipelines) in new_meshes.iter_mut() {
+        render_pipelines.pipelines.push(depth_normal_pipeline());
+        commands.insert_one(entity, DepthNormalPrepass);
+    }
+}
+
+fn add_ssr_graph(graph: &mut RenderGraph) {
+    let mut prepass_node = PassNode::<&DepthNormalPrepass>::new(PassDescriptor {
+        color_attachments: vec![RenderPassColorAttachmentDescriptor {
+            attachment: TextureAttachment::Input("normal".to_string()),
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::NONE),
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    });
+    prepass_node.add_camera(base::camera::CAMERA3D);
+    prepass_node.set_pass_tag(node::SSR_PREPASS);
+
+    // the prepass draws the same entities as the main pass, so it depends on the same nodes
+    let main_pass_dependencies = graph
+        .iter_node_inputs(base::node::MAIN_PASS)
+        .map(|inputs| inputs.map(|(_edge, node)| node.id).collect::<Vec<_>>())
+        .unwrap_or_default();
+    graph.add_node(node::SSR_PREPASS, prepass_node);
+    for dependency in main_pass_dependencies {
+        // a node can be connected to the main pass more than once, in which case the edge already exists
+        let _ = graph.add_node_edge(dependency, node::SSR_PREPASS);
+    }
+
+    // the consumers of the scene colors, like the blit to the window or a post-processing effect, see the reflections
+    let (color_node, color_index) = graph.add_sampled_main_color();
+    let main_pass = graph.get_node_id(base::node::MAIN_PASS).unwrap();
+    let color_consumers = graph
+        .iter_node_outputs(color_node)
+        .unwrap()
+        .filter_map(|(edge, _node)| match edge {
+            Edge::SlotEdge {
+                input_node,
+                output_index,
+                ..
+            } if *output_index == color_index && *input_node != main_pass => Some(*input_node),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    graph.add_node(
+        node::SCREEN_SPACE_REFLECTIONS,
+        ScreenSpaceReflectionsNode::default(),
+    );
+    graph
+        .add_node_edge(base::node::MAIN_PASS, node::SCREEN_SPACE_REFLECTIONS)
+        .unwrap();
+    graph
+        .add_node_edge(node::SSR_PREPASS, node::SCREEN_SPACE_REFLECTIONS)
+        .unwrap();
+    for consumer in color_consumers {
+        // the edge exists if a node consumes the colors through several slots
+        let _ = graph.add_node_edge(node::SCREEN_SPACE_REFLECTIONS, consumer);
+    }
+    graph
+        .add_slot_edge(
+            color_node,
+            color_index,
+            node::SCREEN_SPACE_REFLECTIONS,
+            ScreenSpaceReflectionsNode::IN_COLOR_ATTACHMENT,
+        )
+        .unwrap();
+
+    for (texture_node, handle, input) in [
+        (
+            node::SSR_NORMAL_TEXTURE,
+            SSR_NORMAL_TEXTURE_HANDLE,
+            ScreenSpaceReflectionsNode::IN_NORMAL,
+        ),
+        (
+            node::SSR_DEPTH_TEXTURE,
+            SSR_DEPTH_TEXTURE_HANDLE,
+            ScreenSpaceReflectionsNode::IN_DEPTH,
+        ),
+    ]
+    .iter()
+    {
+        graph.add_node(*texture_node, AssetTextureNode::new(handle.clone_weak()));
+        graph
+            .add_slot_edge(
+                *texture_node,
+                AssetTextureNode::OUT_TEXTURE,
+                node::SSR_PREPASS,
+                *input,
+            )
+            .unwrap();
+        graph
+            .add_slot_edge(
+                *texture_node,
+                AssetTextureNode::OUT_TEXTURE,
+                node::SCREEN_SPACE_REFLECTIONS,
+                *input,
+            )
+            .unwrap();
+    }
+
+    // the reflections of the next frame are looked up in the colors of this one
+    graph.add_node(
+        node::SSR_HISTORY_TEXTURE,
+        AssetTextureNode::new(SSR_HISTORY_TEXTURE_HANDLE),
+    );
+    graph
+        .add_slot_edge(
+            node::SSR_HISTORY_TEXTURE,
+            AssetTextureNode::OUT_TEXTURE,
+            node::SCREEN_SPACE_REFLECTIONS,
+            ScreenSpaceReflectionsNode::IN_PREVIOUS_COLOR,
+        )
+        .unwrap();
+    graph.add_node(node::SSR_HISTORY_BLIT_PASS, BlitNode::default());
+    graph
+        .add_slot_edge(
+            color_node,
+            color_index,
+            node::SSR_HISTORY_BLIT_PASS,
+            BlitNode::IN_TEXTURE,
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            node::SSR_HISTORY_TEXTURE,
+            AssetTextureNode::OUT_TEXTURE,
+            node::SSR_HISTORY_BLIT_PASS,
+            BlitNode::IN_COLOR_ATTACHMENT,
+        )
+        .unwrap();
+    graph
+        .add_node_edge(node::SCREEN_SPACE_REFLECTIONS, node::SSR_HISTORY_BLIT_PASS)
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_render::render_graph::{base::Msaa, Node, WindowSwapChainNode};
+    use bevy_window::WindowId;
+
+    #[test]
+    fn ssr_graph() {
+        let mut graph = RenderGraph::default();
+        graph.add_node(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::new(WindowId::primary()),
+        );
+        graph.add_node(
+            base::node::MAIN_PASS,
+            PassNode::<&MainPass>::new(PassDescriptor {
+                color_attachments: vec![Msaa::color_attachment_descriptor(
+                    TextureAttachment::Input("color_attachment".to_string()),
+                    TextureAttachment::Input("color_resolve_target".to_string()),
+                    Operations {
+                        load: LoadOp::Clear(Color::BLACK),
+                        store: true,
+                    },
+                )],
+                depth_stencil_attachment: None,
+                sample_count: 1,
+            }),
+        );
+        graph
+            .add_slot_edge(
+                base::node::PRIMARY_SWAP_CHAIN,
+                WindowSwapChainNode::OUT_TEXTURE,
+                base::node::MAIN_PASS,
+                "color_resolve_target",
+            )
+            .unwrap();
+        add_ssr_graph(&mut graph);
+
+        let prepass = graph.get_node_state(node::SSR_PREPASS).unwrap();
+        let inputs = prepass
+            .node
+            .input()
+            .iter()
+            .map(|input| input.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(inputs, vec!["normal", "depth"]);
+
+        // the swap chain can't be drawn to and sampled, so the main pass renders to a texture that is blitted
+        let color_texture = graph.get_node_id(base::node::MAIN_COLOR_TEXTURE).unwrap();
+        let reflections = graph
+            .get_node_state(node::SCREEN_SPACE_REFLECTIONS)
+            .unwrap();
+        match reflections.edges.get_input_slot_edge(0) {
+            Ok(Edge::SlotEdge { output_node, .. }) => assert_eq!(*output_node, color_texture),
+            _ => panic!("the reflections aren't drawn over the scene colors"),
+        }
+        let blit = graph.get_node_id(base::node::MAIN_COLOR_BLIT_PASS).unwrap();
+        assert!(reflections
+            .edges
+            .output_edges
+            .iter()
+            .any(|edge| matches!(edge, Edge::NodeEdge { input_node, .. } if *input_node == blit)));
+    }
+}
@@ -0,0 +1,91 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+// rgb: the reflected color, a: how much of it covers the lit scene
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform ScreenSpaceReflections {
+    mat4 ViewProj;
+    mat4 InverseViewProj;
+    mat4 PreviousViewProj;
+    vec4 CameraPosition;
+    uint MaxSteps;
+    float Thickness;
+    float MaxDistance;
+    float Intensity;
+};
+layout(set = 0, binding = 1) uniform texture2D PreviousColor;
+layout(set = 0, binding = 2) uniform sampler PreviousColor_sampler;
+layout(set = 0, binding = 3) uniform texture2D Normal;
+layout(set = 0, binding = 4) uniform sampler Normal_sampler;
+layout(set = 0, binding = 5) uniform texture2D Depth;
+layout(set = 0, binding = 6) uniform sampler Depth_sampler;
+
+vec2 ndc_to_uv(vec2 ndc) {
+    // texture coordinates go down, normalized device coordinates go up
+    return vec2(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+}
+
+vec3 world_position(vec2 uv) {
+    // the lookups happen in a loop, so they can't use derivatives to pick a mip level
+    float depth = textureLod(sampler2D(Depth, Depth_sampler), uv, 0.0).r;
+    vec4 position = InverseViewProj * vec4(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    return position.xyz / position.w;
+}
+
+void main() {
+    vec4 normal_reflectance = textureLod(sampler2D(Normal, Normal_sampler), v_Uv, 0.0);
+    float reflectance = normal_reflectance.a;
+    if (reflectance <= 0.0) {
+        discard;
+    }
+
+    vec3 position = world_position(v_Uv);
+    vec3 normal = normalize(normal_reflectance.xyz);
+    vec3 view_direction = normalize(position - CameraPosition.xyz);
+    vec3 direction = reflect(view_direction, normal);
+
+    // marches the reflected ray in even world space steps, projecting each step onto the prepass depth. the ray hits
+    // where it passes less than Thickness behind a surface. depth isn't linear, so the ray is compared with the
+    // surface's distance to the camera.
+    float step_size = MaxDistance / float(max(MaxSteps, 1u));
+    for (uint i = 1u; i <= MaxSteps; ++i) {
+        vec3 ray = position + direction * step_size * float(i);
+        vec4 clip = ViewProj * vec4(ray, 1.0);
+        if (clip.w <= 0.0) {
+            break;
+        }
+        vec2 ndc = clip.xy / clip.w;
+        if (abs(ndc.x) > 1.0 || abs(ndc.y) > 1.0) {
+            break;
+        }
+
+        vec3 surface = world_position(ndc_to_uv(ndc));
+        float surface_distance = length(surface - CameraPosition.xyz);
+        float ray_distance = length(ray - CameraPosition.xyz);
+        if (ray_distance > surface_distance && ray_distance - surface_distance < Thickness) {
+            // the surface is looked up where it was on screen in the previous frame
+            vec4 previous_clip = PreviousViewProj * vec4(surface, 1.0);
+            vec2 previous_ndc = previous_clip.xy / previous_clip.w;
+            if (previous_clip.w <= 0.0 || abs(previous_ndc.x) > 1.0 || abs(previous_ndc.y) > 1.0) {
+                break;
+            }
+
+            // fades out towards the screen edges and the end of the ray, where reflections would be cut off abruptly
+            vec2 edge = clamp((1.0 - abs(previous_ndc)) * 10.0, 0.0, 1.0);
+            float fade = edge.x * edge.y * (1.0 - float(i - 1u) / float(MaxSteps));
+            // schlick's fresnel approximation, like the forward shader uses for reflection probes
+            float grazing = pow(1.0 - max(dot(normal, -view_direction), 0.0), 5.0);
+            float fresnel = reflectance + (1.0 - reflectance) * grazing;
+            vec3 color = textureLod(
+                sampler2D(PreviousColor, PreviousColor_sampler),
+                ndc_to_uv(previous_ndc), 0.0).rgb;
+            o_Target = vec4(color * Intensity, fresnel * fade);
+            return;
+        }
+    }
+
+    // rays that miss keep the reflections the main pass shaded, like the environment of a reflection probe
+    discard;
+}
@@ -0,0 +1,256 @@
+use super::{ScreenSpaceReflections, SCREEN_SPACE_REFLECTIONS_PIPELINE_HANDLE};
+use bevy_asset::Assets;
+use bevy_core::{AsBytes, Byteable};
+use bevy_ecs::{Resources, World};
+use bevy_math::Mat4;
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::PipelineDescriptor,
+    render_graph::{base, Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{
+        BindGroup, BufferId, BufferInfo, BufferUsage, RenderContext, RenderResourceBindings,
+        RenderResourceContext, RenderResourceId, RenderResourceType, SamplerId,
+    },
+    shader::Shader,
+    texture::{FilterMode, SamplerDescriptor, TextureUsage},
+};
+use bevy_transform::prelude::GlobalTransform;
+use std::borrow::Cow;
+
+/// The uniform of the reflection shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SsrParams {
+    view_projection: [f32; 16],
+    inverse_view_projection: [f32; 16],
+    previous_view_projection: [f32; 16],
+    camera_position: [f32; 4],
+    max_steps: u32,
+    thickness: f32,
+    max_distance: f32,
+    intensity: f32,
+}
+
+unsafe impl Byteable for SsrParams {}
+
+const PARAMS_SIZE: u64 = std::mem::size_of::<SsrParams>() as u64;
+
+#[derive(Debug)]
+struct SsrBuffers {
+    params: BufferId,
+    staging: BufferId,
+    /// Filters the previous frame's colors
+    linear_sampler: SamplerId,
+    /// Normals and depths of neighboring pixels belong to different surfaces and can't be interpolated
+    nearest_sampler: SamplerId,
+}
+
+impl SsrBuffers {
+    fn new(render_resource_context: &dyn RenderResourceContext) -> Self {
+        SsrBuffers {
+            params: render_resource_context.create_buffer(BufferInfo {
+                size: PARAMS_SIZE as usize,
+                buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+                ..Default::default()
+            }),
+            staging: render_resource_context.create_buffer(BufferInfo {
+                size: PARAMS_SIZE as usize,
+                buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+                mapped_at_creation: true,
+            }),
+            linear_sampler: render_resource_context.create_sampler(&SamplerDescriptor {
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                ..Default::default()
+            }),
+            nearest_sampler: render_resource_context.create_sampler(&SamplerDescriptor {
+                mag_filter: FilterMode::Nearest,
+                min_filter: FilterMode::Nearest,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// Marches the reflected rays of the glossy pixels of the depth/normal prepass and blends the previous frame's color
+/// where they hit over its color attachment, as seen by the 3d camera if it has [ScreenSpaceReflections]
+#[derive(Debug, Default)]
+pub struct ScreenSpaceReflectionsNode {
+    buffers: Option<SsrBuffers>,
+    previous_view_projection: Option<Mat4>,
+}
+
+impl ScreenSpaceReflectionsNode {
+    pub const IN_COLOR_ATTACHMENT: &'static str = "color_attachment";
+    pub const IN_PREVIOUS_COLOR: &'static str = "previous_color";
+    pub const IN_NORMAL: &'static str = "normal";
+    pub const IN_DEPTH: &'static str = "depth";
+}
+
+impl Node for ScreenSpaceReflectionsNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[
+            ResourceSlotInfo {
+                name: Cow::Borrowed(ScreenSpaceReflectionsNode::IN_COLOR_ATTACHMENT),
+                resource_type: RenderResourceType::Texture,
+            },
+            ResourceSlotInfo {
+                name: Cow::Borrowed(ScreenSpaceReflectionsNode::IN_PREVIOUS_COLOR),
+                resource_type: RenderResourceType::Texture,
+            },
+            ResourceSlotInfo {
+                name: Cow::Borrowed(ScreenSpaceReflectionsNode::IN_NORMAL),
+                resource_type: RenderResourceType::Texture,
+            },
+            ResourceSlotInfo {
+                name: Cow::Borrowed(ScreenSpaceReflectionsNode::IN_DEPTH),
+                resource_type: RenderResourceType::Texture,
+            },
+        ];
+        INPUT
+    }
+
+    fn input_texture_usage(&self, index: usize) -> Option<TextureUsage> {
+        const IN_COLOR_ATTACHMENT: usize = 0;
+        if index == IN_COLOR_ATTACHMENT {
+            Some(TextureUsage::OUTPUT_ATTACHMENT)
+        } else {
+            Some(TextureUsage::SAMPLED)
+        }
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        let (color_attachment, previous_color, normal, depth) =
+            match (input.get(0), input.get(1), input.get(2), input.get(3)) {
+                (
+                    Some(RenderResourceId::Texture(color_attachment)),
+                    Some(RenderResourceId::Texture(previous_color)),
+                    Some(RenderResourceId::Texture(normal)),
+                    Some(RenderResourceId::Texture(depth)),
+                ) => (color_attachment, previous_color, normal, depth),
+                _ => return,
+            };
+
+        let active_cameras = resources.get::<ActiveCameras>().unwrap();
+        let camera = match active_cameras.get(base::camera::CAMERA3D) {
+            Some(camera) => camera,
+            None => return,
+        };
+        let (projection_matrix, camera_transform) = match (
+            world.get::<Camera>(camera),
+            world.get::<GlobalTransform>(camera),
+        ) {
+            (Ok(camera), Ok(transform)) => (camera.projection_matrix, *transform),
+            _ => return,
+        };
+        let view_projection = projection_matrix * camera_transform.compute_matrix().inverse();
+        // the previous frame's color doesn't exist yet in the first frame
+        let previous_view_projection = match self.previous_view_projection.replace(view_projection)
+        {
+            Some(previous_view_projection) => previous_view_projection,
+            None => return,
+        };
+        let settings = match world.get::<ScreenSpaceReflections>(camera) {
+            Ok(settings) => *settings,
+            Err(_) => return,
+        };
+        let translation = camera_transform.translation;
+        let params = SsrParams {
+            view_projection: view_projection.to_cols_array(),
+            inverse_view_projection: view_projection.inverse().to_cols_array(),
+            previous_view_projection: previous_view_projection.to_cols_array(),
+            camera_position: [translation.x(), translation.y(), translation.z(), 1.0],
+            max_steps: settings.max_steps,
+            thickness: settings.thickness,
+            max_distance: settings.max_distance,
+            intensity: settings.intensity,
+        };
+
+        let render_resource_context = render_context.resources();
+        let bind_group_descriptor = {
+            let shaders = resources.get::<Assets<Shader>>().unwrap();
+            let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+            let pipeline = pipelines
+                .get_mut(&SCREEN_SPACE_REFLECTIONS_PIPELINE_HANDLE)
+                .unwrap();
+            if pipeline.layout.is_none() {
+                pipeline.reflect_layout(&shaders, false, &[]);
+            }
+            render_resource_context.create_render_pipeline(
+                SCREEN_SPACE_REFLECTIONS_PIPELINE_HANDLE,
+                pipeline,
+                &shaders,
+            );
+            pipeline.get_layout().unwrap().bind_groups[0].id
+        };
+
+        let buffers = self
+            .buffers
+            .get_or_insert_with(|| SsrBuffers::new(render_resource_context));
+        render_resource_context.map_buffer(buffers.staging);
+        render_resource_context.write_mapped_buffer(
+            buffers.staging,
+            0..PARAMS_SIZE,
+            &mut |data, _renderer| {
+                data.copy_from_slice(params.as_bytes());
+            },
+        );
+        render_resource_context.unmap_buffer(buffers.staging);
+
+        let bind_group = BindGroup::build()
+            .add_buffer(0, buffers.params, 0..PARAMS_SIZE)
+            .add_texture(1, previous_color)
+            .add_sampler(2, buffers.linear_sampler)
+            .add_texture(3, normal)
+            .add_sampler(4, buffers.nearest_sampler)
+            .add_texture(5, depth)
+            .add_sampler(6, buffers.nearest_sampler)
+            .finish();
+        // bind groups are cleared at the end of every frame
+        render_resource_context.create_bind_group(bind_group_descriptor, &bind_group);
+
+        render_context.copy_buffer_to_buffer(buffers.staging, 0, buffers.params, 0, PARAMS_SIZE);
+        let pass_descriptor = PassDescriptor {
+            color_attachments: vec![RenderPassColorAttachmentDescriptor {
+                attachment: TextureAttachment::Id(color_attachment),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+            sample_count: 1,
+        };
+        render_context.begin_pass(
+            &pass_descriptor,
+            &RenderResourceBindings::default(),
+            &mut |render_pass| {
+                render_pass.set_pipeline(&SCREEN_SPACE_REFLECTIONS_PIPELINE_HANDLE);
+                render_pass.set_bind_group(0, bind_group_descriptor, bind_group.id, None);
+                render_pass.draw(0..3, 0..1);
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssr_params_layout() {
+        // three mat4s, a vec4 and four scalars, without padding in std140
+        assert_eq!(PARAMS_SIZE, 3 * 64 + 16 + 16);
+    }
+}
@@ -0,0 +1,31 @@
+use bevy_math::Vec3;
+use bevy_render::{color::Color, renderer::RenderResources};
+use bevy_type_registry::TypeUuid;
+
+/// The colors of the sky drawn by [SkyComponents](super::SkyComponents). [sun_system](super::sun_system) keeps them
+/// in sync with the [Sun](super::Sun).
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "4d5e8a61-2c7b-4f03-b9e6-1a0d3f7c5e29"]
+pub struct SkyMaterial {
+    pub zenith_color: Color,
+    pub horizon_color: Color,
+    pub ground_color: Color,
+    pub sun_color: Color,
+    /// The direction towards the sun
+    pub sun_direction: Vec3,
+    /// The angular radius of the sun's disc, in radians
+    pub sun_size: f32,
+}
+
+impl Default for SkyMaterial {
+    fn default() -> Self {
+        SkyMaterial {
+            zenith_color: Color::rgb(0.25, 0.45, 0.85),
+            horizon_color: Color::rgb(0.7, 0.8, 0.95),
+            ground_color: Color::rgb(0.35, 0.33, 0.3),
+            sun_color: Color::WHITE,
+            sun_direction: Vec3::unit_y(),
+            sun_size: f32::to_radians(1.5),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+mod material;
+mod sun;
+
+pub use material::*;
+pub use sun::*;
+
+use crate::light::{AmbientLight, Fog, Light};
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_core::Time;
+use bevy_ecs::{Bundle, IntoQuerySystem, Query, Res, ResMut};
+use bevy_render::{
+    draw::Draw,
+    mesh::{shape, Mesh},
+    pass::ClearColor,
+    pipeline::{
+        CompareFunction, CullMode, DepthStencilStateDescriptor, DynamicBinding, FrontFace,
+        PipelineDescriptor, PipelineSpecialization, RasterizationStateDescriptor, RenderPipeline,
+        RenderPipelines, StencilStateDescriptor, StencilStateFaceDescriptor,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, MainPass},
+        AssetRenderResourcesNode, RenderGraph,
+    },
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::TextureFormat,
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::{RegisterType, TypeUuid};
+
+pub const SKY_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 5712093846125370418);
+
+pub const SKY_MESH_HANDLE: Handle<Mesh> =
+    Handle::weak_from_u64(Mesh::TYPE_UUID, 1637482095561120934);
+
+pub mod node {
+    pub const SKY_MATERIAL: &str = "sky_material";
+}
+
+/// A component bundle for the sun. Its light is directional, and [sun_system] points it and colors it from the
+/// [Sun]'s time of day.
+#[derive(Bundle, Default)]
+pub struct SunComponents {
+    pub sun: Sun,
+    pub atmosphere: Atmosphere,
+    pub light: Light,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+/// A component bundle for the sky. It is drawn around the camera behind everything else, so only one is needed and
+/// it doesn't need a transform.
+#[derive(Bundle)]
+pub struct SkyComponents {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<SkyMaterial>,
+    pub main_pass: MainPass,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+}
+
+impl Default for SkyComponents {
+    fn default() -> Self {
+        SkyComponents {
+            mesh: SKY_MESH_HANDLE,
+            material: Default::default(),
+            main_pass: MainPass,
+            draw: Default::default(),
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+                SKY_PIPELINE_HANDLE,
+                PipelineSpecialization {
+                    dynamic_bindings: (0..6)
+                        .map(|binding| DynamicBinding {
+                            bind_group: 1,
+                            binding,
+                        })
+                        .collect(),
+                    ..Default::default()
+                },
+            )]),
+        }
+    }
+}
+
+/// Adds a day and night cycle driven by [Sun] entities. Spawn a [SunComponents] for the sun and a [SkyComponents]
+/// for the sky.
+#[derive(Default)]
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<SkyMaterial>()
+            .register_component::<Sun>()
+            .register_component::<Atmosphere>()
+            .add_system_to_stage(stage::UPDATE, sun_animation_system.system())
+            .add_system_to_stage(stage::UPDATE, sun_system.system());
+
+        let resources = app.resources();
+        resources
+            .get_mut::<Assets<SkyMaterial>>()
+            .unwrap()
+            .set_untracked(Handle::<SkyMaterial>::default(), SkyMaterial::default());
+        resources.get_mut::<Assets<Mesh>>().unwrap().set_untracked(
+            SKY_MESH_HANDLE,
+            Mesh::from(shape::Icosphere {
+                radius: 1.0,
+                subdivisions: 3,
+            }),
+        );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        render_graph.add_system_node(
+            node::SKY_MATERIAL,
+            AssetRenderResourcesNode::<SkyMaterial>::new(true),
+        );
+        render_graph
+            .add_node_edge(node::SKY_MATERIAL, base::node::MAIN_PASS)
+            .unwrap();
+
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(SKY_PIPELINE_HANDLE, build_sky_pipeline(&mut shaders));
+    }
+}
+
+fn build_sky_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        // the camera is inside the sphere, so its back faces are the ones to draw
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::Front,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        // the sky sits exactly on the far plane, so it passes the depth test only where nothing else was drawn
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::LessEqual,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        ..PipelineDescriptor::default_config(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("sky.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("sky.frag"),
+            ))),
+        })
+    }
+}
+
+/// Advances the time of day of every [Sun]
+pub fn sun_animation_system(time: Res<Time>, mut query: Query<&mut Sun>) {
+    for mut sun in query.iter_mut() {
+        sun.advance(time.delta_seconds);
+    }
+}
+
+/// Points and colors the light of each [Sun], and updates the ambient light, the fog color, the clear color and the
+/// sky materials from its [Atmosphere]. Scenes are expected to have a single sun.
+pub fn sun_system(
+    mut ambient_light: ResMut<AmbientLight>,
+    mut fog: ResMut<Fog>,
+    mut clear_color: ResMut<ClearColor>,
+    mut sky_materials: ResMut<Assets<SkyMaterial>>,
+    mut query: Query<(&Sun, &Atmosphere, &mut Light, &mut Transform)>,
+) {
+    for (sun, atmosphere, mut light, mut transform) in query.iter_mut() {
+        let direction = sun.direction();
+        let sky = atmosphere.sky_colors(direction);
+
+        transform.rotation = sun.rotation();
+        light.directional = true;
+        light.color = sky.sun * sun.intensity;
+        ambient_light.color = sky.ambient;
+        // the fog blends into the horizon, but keeps its own thickness
+        fog.color = Color::rgba(
+            sky.horizon.r(),
+            sky.horizon.g(),
+            sky.horizon.b(),
+            fog.color.a(),
+        );
+        clear_color.0 = sky.horizon;
+
+        let ids = sky_materials.ids().collect::<Vec<_>>();
+        for id in ids {
+            if let Some(material) = sky_materials.get_mut(id) {
+                material.zenith_color = sky.zenith;
+                material.horizon_color = sky.horizon;
+                material.ground_color = sky.ground;
+                material.sun_color = sky.sun;
+                material.sun_direction = direction;
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+#version 450
+
+layout(location = 0) in vec3 v_Direction;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+    vec4 CameraPosition;
+    // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+    vec4 CameraExposure;
+};
+
+layout(set = 1, binding = 0) uniform SkyMaterial_zenith_color {
+    vec4 ZenithColor;
+};
+layout(set = 1, binding = 1) uniform SkyMaterial_horizon_color {
+    vec4 HorizonColor;
+};
+layout(set = 1, binding = 2) uniform SkyMaterial_ground_color {
+    vec4 GroundColor;
+};
+layout(set = 1, binding = 3) uniform SkyMaterial_sun_color {
+    vec4 SunColor;
+};
+layout(set = 1, binding = 4) uniform SkyMaterial_sun_direction {
+    vec3 SunDirection;
+};
+layout(set = 1, binding = 5) uniform SkyMaterial_sun_size {
+    float SunSize;
+};
+
+void main() {
+    vec3 direction = normalize(v_Direction);
+    // the horizon color fades out quickly above the horizon and the ground color below it
+    vec3 color = direction.y >= 0.0
+        ? mix(HorizonColor.rgb, ZenithColor.rgb, pow(direction.y, 0.5))
+        : mix(HorizonColor.rgb, GroundColor.rgb, pow(-direction.y, 0.3));
+
+    float sun = dot(direction, normalize(SunDirection));
+    float disc = smoothstep(cos(SunSize), cos(SunSize * 0.8), sun);
+    float glow = pow(max(sun, 0.0), 64.0) * 0.5;
+    color += SunColor.rgb * (disc * 4.0 + glow);
+
+    color *= CameraExposure.x;
+    if (CameraExposure.y > 0.5) {
+        // reinhard
+        color = color / (1.0 + color);
+    }
+    o_Target = vec4(color, 1.0);
+}
@@ -0,0 +1,19 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+
+layout(location = 0) out vec3 v_Direction;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+    vec4 CameraPosition;
+    // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+    vec4 CameraExposure;
+};
+
+void main() {
+    v_Direction = Vertex_Position;
+    // the sphere follows the camera, and setting z to w puts it on the far plane behind everything else
+    vec4 position = ViewProj * vec4(CameraPosition.xyz + Vertex_Position, 1.0);
+    gl_Position = position.xyww;
+}
@@ -0,0 +1,168 @@
+use bevy_math::{Quat, Vec3};
+use bevy_property::Properties;
+use bevy_render::color::Color;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// Moves the directional [Light](crate::Light) on the same entity across the sky over the course of a day. The sun
+/// rises towards +X at 6:00, is highest at 12:00 and sets towards -X at 18:00.
+#[derive(Debug, Clone, Properties)]
+pub struct Sun {
+    /// Hours since midnight, from 0 up to 24
+    pub time_of_day: f32,
+    /// How many hours pass per second of real time. Set it to 0 to stop the sun.
+    pub hours_per_second: f32,
+    /// How far the sun's path is tilted towards +Z, in radians. At 0 the noon sun is straight overhead.
+    pub tilt: f32,
+    /// Scales the color of the sunlight
+    pub intensity: f32,
+}
+
+impl Default for Sun {
+    fn default() -> Self {
+        Sun {
+            time_of_day: 10.0,
+            hours_per_second: 0.0,
+            tilt: f32::to_radians(30.0),
+            intensity: 1.0,
+        }
+    }
+}
+
+impl Sun {
+    /// The rotation that points a transform's +Z axis towards the sun
+    pub fn rotation(&self) -> Quat {
+        let angle = (self.time_of_day - 6.0) / 24.0 * 2.0 * PI;
+        Quat::from_rotation_x(self.tilt)
+            * Quat::from_rotation_z(angle)
+            * Quat::from_rotation_y(FRAC_PI_2)
+    }
+
+    /// The direction towards the sun
+    pub fn direction(&self) -> Vec3 {
+        self.rotation() * Vec3::unit_z()
+    }
+
+    /// Advances the time of day by `seconds` of real time, wrapping around at midnight
+    pub fn advance(&mut self, seconds: f32) {
+        self.time_of_day = (self.time_of_day + self.hours_per_second * seconds).rem_euclid(24.0);
+    }
+}
+
+/// The colors of a simple analytic sky model. The sky blends between its day and night colors as the sun rises and
+/// sets, and its horizon takes the sunset color while the sun is close to it.
+#[derive(Debug, Clone, Properties)]
+pub struct Atmosphere {
+    pub day_zenith: Color,
+    pub day_horizon: Color,
+    pub night_zenith: Color,
+    pub night_horizon: Color,
+    pub sunset_horizon: Color,
+    /// The color below the horizon, darkened along with the sky at night
+    pub ground: Color,
+    /// The color of sunlight when the sun is high
+    pub noon_sun: Color,
+    /// The color of sunlight when the sun is low, after passing through more of the atmosphere
+    pub low_sun: Color,
+    /// How much of the sky's color lights surfaces as ambient light
+    pub ambient_intensity: f32,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Atmosphere {
+            day_zenith: Color::rgb(0.25, 0.45, 0.85),
+            day_horizon: Color::rgb(0.7, 0.8, 0.95),
+            night_zenith: Color::rgb(0.01, 0.01, 0.04),
+            night_horizon: Color::rgb(0.04, 0.05, 0.1),
+            sunset_horizon: Color::rgb(0.95, 0.5, 0.25),
+            ground: Color::rgb(0.35, 0.33, 0.3),
+            noon_sun: Color::rgb(1.0, 0.98, 0.92),
+            low_sun: Color::rgb(1.0, 0.55, 0.25),
+            ambient_intensity: 0.3,
+        }
+    }
+}
+
+/// The colors of the sky for one position of the sun, from [Atmosphere::sky_colors]
+#[derive(Debug, Clone, Copy)]
+pub struct SkyColors {
+    pub zenith: Color,
+    pub horizon: Color,
+    pub ground: Color,
+    /// The color of the sunlight. It is black while the sun is below the horizon.
+    pub sun: Color,
+    pub ambient: Color,
+}
+
+impl Atmosphere {
+    /// The colors of the sky when the sun is in `sun_direction`
+    pub fn sky_colors(&self, sun_direction: Vec3) -> SkyColors {
+        let elevation = sun_direction.normalize().y();
+        // the sky brightens a little before sunrise and darkens a little after sunset
+        let day = smoothstep(-0.15, 0.2, elevation);
+        let sunset = (1.0 - (elevation.abs() / 0.3).min(1.0)).powi(2);
+        let horizon = mix_colors(self.night_horizon, self.day_horizon, day);
+        let zenith = mix_colors(self.night_zenith, self.day_zenith, day);
+        let sun = mix_colors(self.low_sun, self.noon_sun, smoothstep(0.0, 0.4, elevation))
+            * smoothstep(-0.05, 0.05, elevation);
+        let ambient = mix_colors(zenith, horizon, 0.5) * self.ambient_intensity;
+        SkyColors {
+            zenith,
+            horizon: mix_colors(horizon, self.sunset_horizon, sunset),
+            ground: self.ground * (0.05 + 0.95 * day),
+            sun,
+            ambient,
+        }
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).max(0.0).min(1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Blends the colors in srgb space, which looks more even for sky gradients than blending linear colors
+fn mix_colors(a: Color, b: Color, t: f32) -> Color {
+    let mix = |a: f32, b: f32| a + (b - a) * t;
+    Color::rgba(
+        mix(a.r(), b.r()),
+        mix(a.g(), b.g()),
+        mix(a.b(), b.b()),
+        mix(a.a(), b.a()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn sun_follows_time_of_day() {
+        let mut sun = Sun {
+            time_of_day: 6.0,
+            tilt: 0.0,
+            ..Default::default()
+        };
+        assert_near(sun.direction(), Vec3::unit_x());
+        sun.time_of_day = 12.0;
+        assert_near(sun.direction(), Vec3::unit_y());
+        sun.time_of_day = 0.0;
+        assert_near(sun.direction(), -Vec3::unit_y());
+
+        sun.hours_per_second = 2.0;
+        sun.time_of_day = 23.0;
+        sun.advance(1.0);
+        assert!((sun.time_of_day - 1.0).abs() < 1e-5);
+
+        let atmosphere = Atmosphere::default();
+        let night = atmosphere.sky_colors(-Vec3::unit_y());
+        let noon = atmosphere.sky_colors(Vec3::unit_y());
+        assert_eq!(night.sun.r(), 0.0);
+        assert!(noon.zenith.b() > night.zenith.b());
+        assert!(noon.ambient.g() > night.ambient.g());
+    }
+}
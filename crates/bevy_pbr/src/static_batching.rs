@@ -0,0 +1,234 @@
+use crate::{entity::PbrComponents, material::StandardMaterial, MaterialOverrides};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Entity, IntoQuerySystem, Query, Res, ResMut, With, Without};
+use bevy_math::{Mat4, Vec3};
+use bevy_property::Properties;
+use bevy_render::{draw::Draw, mesh::Mesh};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::RegisterType;
+use bevy_utils::HashMap;
+
+/// Marks an entity whose mesh never moves. Static meshes that share a material and a [StaticBatching] cell are merged
+/// into a single mesh once their mesh assets have loaded.
+#[derive(Debug, Default, Properties)]
+pub struct StaticMesh;
+
+/// Configures how [StaticMesh] entities are batched
+#[derive(Debug)]
+pub struct StaticBatching {
+    /// The side length of the cubic cells used to group static meshes. Each cell produces (at most) one batched mesh
+    /// per material, so smaller cells keep culling granular at the cost of more draw calls.
+    pub cell_size: f32,
+}
+
+impl Default for StaticBatching {
+    fn default() -> Self {
+        StaticBatching { cell_size: 32.0 }
+    }
+}
+
+/// Merges [StaticMesh] entities into per-cell batches to reduce draw calls
+#[derive(Default)]
+pub struct StaticBatchingPlugin;
+
+impl Plugin for StaticBatchingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<StaticBatching>()
+            .register_component::<StaticMesh>()
+            // this runs after transforms have been propagated so GlobalTransform is up to date
+            .add_system_to_stage(stage::LAST, static_batching_system.system());
+    }
+}
+
+struct BatchItem {
+    entity: Entity,
+    mesh: Handle<Mesh>,
+    transform: Mat4,
+}
+
+pub fn static_batching_system(
+    mut commands: Commands,
+    config: Res<StaticBatching>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut query: Query<
+        With<
+            StaticMesh,
+            // per-entity overrides would be lost in a batch
+            Without<
+                MaterialOverrides,
+                (
+                    Entity,
+                    &Handle<Mesh>,
+                    &Handle<StandardMaterial>,
+                    &GlobalTransform,
+                    &mut Draw,
+                ),
+            >,
+        >,
+    >,
+) {
+    let mut cells = HashMap::<BatchKey, Vec<BatchItem>>::default();
+    for (entity, mesh_handle, material, global_transform, _draw) in query.iter_mut() {
+        // wait until the mesh has loaded
+        if meshes.get(mesh_handle).is_none() {
+            continue;
+        }
+
+        cells
+            .entry(batch_key(
+                material,
+                global_transform.translation,
+                config.cell_size,
+            ))
+            .or_insert_with(Vec::new)
+            .push(BatchItem {
+                entity,
+                mesh: mesh_handle.clone_weak(),
+                transform: global_transform.compute_matrix(),
+            });
+    }
+
+    for ((material, cell), items) in cells {
+        for entity in items.iter().map(|item| item.entity) {
+            commands.remove_one::<StaticMesh>(entity);
+        }
+
+        // batched vertices are stored relative to the cell origin to preserve precision far from the world origin
+        let cell_origin =
+            Vec3::new(cell[0] as f32, cell[1] as f32, cell[2] as f32) * config.cell_size;
+        let to_cell = Mat4::from_translation(-cell_origin);
+        let (batched_mesh, batched_entities) = match merge_batch(items.iter().map(|item| {
+            (
+                item.entity,
+                meshes.get(&item.mesh).unwrap(),
+                to_cell * item.transform,
+            )
+        })) {
+            Some(batch) => batch,
+            None => continue,
+        };
+
+        // batching a single mesh doesn't save any draw calls
+        if batched_entities.len() < 2 {
+            continue;
+        }
+
+        for entity in batched_entities.iter() {
+            if let Ok(mut draw) = query.get_component_mut::<Draw>(*entity) {
+                draw.is_visible = false;
+            }
+        }
+
+        commands.spawn(PbrComponents {
+            mesh: meshes.add(batched_mesh),
+            material,
+            transform: Transform::from_translation(cell_origin),
+            ..Default::default()
+        });
+    }
+}
+
+type BatchKey = (Handle<StandardMaterial>, [i32; 3]);
+
+/// Static meshes are batched with the meshes that have the same material and are in the same cell
+fn batch_key(material: &Handle<StandardMaterial>, translation: Vec3, cell_size: f32) -> BatchKey {
+    let cell = translation / cell_size;
+    (
+        material.clone(),
+        [
+            cell.x().floor() as i32,
+            cell.y().floor() as i32,
+            cell.z().floor() as i32,
+        ],
+    )
+}
+
+/// Merges the meshes of a batch into one mesh and returns it with the entities it contains. The meshes of entities
+/// that can't be merged into the batch are left out, so those entities keep drawing on their own.
+fn merge_batch<'a>(
+    items: impl IntoIterator<Item = (Entity, &'a Mesh, Mat4)>,
+) -> Option<(Mesh, Vec<Entity>)> {
+    let mut batched_mesh: Option<Mesh> = None;
+    let mut batched_entities = Vec::new();
+    for (entity, mesh, transform) in items {
+        match batched_mesh {
+            Some(ref mut batched_mesh) => {
+                // a failed merge leaves the batch as it was
+                if let Err(err) = batched_mesh.merge(mesh, transform) {
+                    log::warn!(
+                        "Static mesh {:?} could not be batched and will be drawn on its own: {}",
+                        entity,
+                        err
+                    );
+                    continue;
+                }
+            }
+            None => {
+                let mut mesh = mesh.clone();
+                mesh.transform_by(transform);
+                mesh.attribute_buffer_descriptor_reference = None;
+                batched_mesh = Some(mesh);
+            }
+        }
+        batched_entities.push(entity);
+    }
+
+    batched_mesh.map(|mesh| (mesh, batched_entities))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_asset::HandleId;
+    use bevy_render::pipeline::PrimitiveTopology;
+    use std::borrow::Cow;
+
+    fn quad(uv_format_3d: bool) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.attributes.insert(
+            Cow::Borrowed(Mesh::ATTRIBUTE_POSITION),
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]].into(),
+        );
+        mesh.attributes.insert(
+            Cow::Borrowed(Mesh::ATTRIBUTE_UV_0),
+            if uv_format_3d {
+                vec![[0.0, 0.0, 0.0]; 3].into()
+            } else {
+                vec![[0.0, 0.0]; 3].into()
+            },
+        );
+        mesh
+    }
+
+    #[test]
+    fn batches_by_material_and_cell() {
+        let stone = Handle::<StandardMaterial>::weak(HandleId::random::<StandardMaterial>());
+        let wood = Handle::<StandardMaterial>::weak(HandleId::random::<StandardMaterial>());
+
+        let key = batch_key(&stone, Vec3::new(1.0, 2.0, 3.0), 32.0);
+        assert_eq!(key, (stone.clone(), [0, 0, 0]));
+        assert_eq!(key, batch_key(&stone, Vec3::new(31.0, 0.0, 0.0), 32.0));
+        assert_ne!(key, batch_key(&wood, Vec3::new(1.0, 2.0, 3.0), 32.0));
+        assert_eq!(
+            batch_key(&stone, Vec3::new(-1.0, 40.0, 64.0), 32.0).1,
+            [-1, 1, 2]
+        );
+    }
+
+    #[test]
+    fn meshes_that_fail_to_merge_stay_unbatched() {
+        let (a, broken, b) = (Entity::new(0), Entity::new(1), Entity::new(2));
+        let (mesh, mismatched) = (quad(false), quad(true));
+
+        let (batched_mesh, batched_entities) = merge_batch(vec![
+            (a, &mesh, Mat4::identity()),
+            (broken, &mismatched, Mat4::identity()),
+            (b, &mesh, Mat4::from_translation(Vec3::new(0.0, 0.0, 1.0))),
+        ])
+        .unwrap();
+        assert_eq!(batched_entities, vec![a, b]);
+        assert_eq!(batched_mesh.count_vertices(), 6);
+        assert!(merge_batch(Vec::new()).is_none());
+    }
+}
@@ -0,0 +1,299 @@
+use bevy_math::{Vec2, Vec3};
+use bevy_render::{
+    mesh::{Indices, Mesh},
+    pipeline::PrimitiveTopology,
+    texture::{Texture, TextureFormat},
+};
+use std::borrow::Cow;
+
+/// A grid of height samples covering a terrain. Heights are read from a heightmap [Texture] and scaled into terrain
+/// space, where the terrain spans `0..size.x` on the X axis and `0..size.y` on the Z axis.
+#[derive(Debug, Clone)]
+pub struct Heightfield {
+    width: usize,
+    depth: usize,
+    heights: Vec<f32>,
+    /// The size of the terrain on the X and Z axes
+    pub size: Vec2,
+    /// The height of a heightmap sample with the maximum value
+    pub height_scale: f32,
+}
+
+impl Heightfield {
+    /// Creates a heightfield from normalized (0.0 to 1.0) samples stored in rows along the X axis
+    pub fn new(
+        width: usize,
+        depth: usize,
+        heights: Vec<f32>,
+        size: Vec2,
+        height_scale: f32,
+    ) -> Self {
+        assert!(
+            width >= 2 && depth >= 2,
+            "Heightfields need at least 2x2 samples"
+        );
+        assert_eq!(
+            width * depth,
+            heights.len(),
+            "Heightfield sample count doesn't match its dimensions"
+        );
+        Heightfield {
+            width,
+            depth,
+            heights,
+            size,
+            height_scale,
+        }
+    }
+
+    /// Reads the first channel of `texture` as normalized heights. Returns `None` if the texture format isn't supported.
+    pub fn from_texture(texture: &Texture, size: Vec2, height_scale: f32) -> Option<Self> {
+        let width = texture.size.x() as usize;
+        let depth = texture.size.y() as usize;
+        let pixel_size = texture.format.pixel_size();
+        let heights = match texture.format {
+            TextureFormat::R8Unorm
+            | TextureFormat::Rg8Unorm
+            | TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb => texture
+                .data
+                .chunks_exact(pixel_size)
+                .map(|pixel| pixel[0] as f32 / std::u8::MAX as f32)
+                .collect(),
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => texture
+                .data
+                .chunks_exact(pixel_size)
+                .map(|pixel| pixel[2] as f32 / std::u8::MAX as f32)
+                .collect(),
+            TextureFormat::R16Uint | TextureFormat::Rg16Uint | TextureFormat::Rgba16Uint => texture
+                .data
+                .chunks_exact(pixel_size)
+                .map(|pixel| u16::from_ne_bytes([pixel[0], pixel[1]]) as f32 / std::u16::MAX as f32)
+                .collect(),
+            TextureFormat::R32Float | TextureFormat::Rg32Float | TextureFormat::Rgba32Float => {
+                texture
+                    .data
+                    .chunks_exact(pixel_size)
+                    .map(|pixel| f32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]))
+                    .collect()
+            }
+            _ => return None,
+        };
+
+        Some(Heightfield::new(width, depth, heights, size, height_scale))
+    }
+
+    /// The number of samples along the X and Z axes
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.depth)
+    }
+
+    fn sample(&self, x: usize, z: usize) -> f32 {
+        let x = x.min(self.width - 1);
+        let z = z.min(self.depth - 1);
+        self.heights[z * self.width + x] * self.height_scale
+    }
+
+    /// The bilinearly filtered height at the given normalized (0.0 to 1.0) coordinates
+    pub fn height_at_uv(&self, uv: Vec2) -> f32 {
+        let x = uv.x().max(0.0).min(1.0) * (self.width - 1) as f32;
+        let z = uv.y().max(0.0).min(1.0) * (self.depth - 1) as f32;
+        let (x0, z0) = (x.floor() as usize, z.floor() as usize);
+        let (tx, tz) = (x.fract(), z.fract());
+
+        let top = lerp(self.sample(x0, z0), self.sample(x0 + 1, z0), tx);
+        let bottom = lerp(self.sample(x0, z0 + 1), self.sample(x0 + 1, z0 + 1), tx);
+        lerp(top, bottom, tz)
+    }
+
+    /// The height at the given terrain space X and Z coordinates. Positions outside the terrain are clamped to its edge.
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        self.height_at_uv(Vec2::new(x / self.size.x(), z / self.size.y()))
+    }
+
+    /// The surface normal at the given terrain space X and Z coordinates
+    pub fn normal_at(&self, x: f32, z: f32) -> Vec3 {
+        let step_x = self.size.x() / (self.width - 1) as f32;
+        let step_z = self.size.y() / (self.depth - 1) as f32;
+        let dx = self.height_at(x + step_x, z) - self.height_at(x - step_x, z);
+        let dz = self.height_at(x, z + step_z) - self.height_at(x, z - step_z);
+        Vec3::new(-dx / (2.0 * step_x), 1.0, -dz / (2.0 * step_z)).normalize()
+    }
+
+    /// Casts a terrain space ray against the heightfield and returns the first terrain space hit, if any
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<Vec3> {
+        let direction = direction.normalize();
+        let step = 0.5
+            * (self.size.x() / (self.width - 1) as f32)
+                .min(self.size.y() / (self.depth - 1) as f32);
+        let is_inside = |point: Vec3| {
+            point.x() >= 0.0
+                && point.z() >= 0.0
+                && point.x() <= self.size.x()
+                && point.z() <= self.size.y()
+        };
+        let is_below = |point: Vec3| point.y() <= self.height_at(point.x(), point.z());
+
+        let mut previous = 0.0;
+        let mut distance = 0.0;
+        while distance <= max_distance {
+            let point = origin + direction * distance;
+            if is_inside(point) && is_below(point) {
+                if distance == 0.0 {
+                    return Some(point);
+                }
+
+                // refine the intersection between the last point above the surface and the first point below it
+                let (mut above, mut below) = (previous, distance);
+                for _ in 0..16 {
+                    let middle = (above + below) * 0.5;
+                    if is_below(origin + direction * middle) {
+                        below = middle;
+                    } else {
+                        above = middle;
+                    }
+                }
+                return Some(origin + direction * below);
+            }
+            previous = distance;
+            distance += step;
+        }
+
+        None
+    }
+
+    /// Builds the mesh for one chunk of a terrain that is split into `chunks_per_side` x `chunks_per_side` chunks. Each
+    /// chunk has `resolution` quads per side at LOD 0, and every LOD level halves that. Vertices are relative to the
+    /// chunk's minimum corner. A skirt of `skirt_depth` is added along the chunk's edges to hide cracks between chunks
+    /// with different LODs.
+    pub fn chunk_mesh(
+        &self,
+        chunks_per_side: u32,
+        chunk: (u32, u32),
+        resolution: u32,
+        lod: u32,
+        skirt_depth: f32,
+    ) -> Mesh {
+        let quads = (resolution >> lod).max(1);
+        let row = quads as usize + 1;
+        let chunk_size = self.size / chunks_per_side as f32;
+        let chunk_origin = Vec2::new(
+            chunk.0 as f32 * chunk_size.x(),
+            chunk.1 as f32 * chunk_size.y(),
+        );
+
+        let mut positions = Vec::with_capacity(row * row);
+        let mut normals: Vec<[f32; 3]> = Vec::with_capacity(row * row);
+        let mut uvs = Vec::with_capacity(row * row);
+        for z in 0..row {
+            for x in 0..row {
+                let local = Vec2::new(
+                    x as f32 / quads as f32 * chunk_size.x(),
+                    z as f32 / quads as f32 * chunk_size.y(),
+                );
+                let terrain = chunk_origin + local;
+                let height = self.height_at(terrain.x(), terrain.y());
+                positions.push([local.x(), height, local.y()]);
+                normals.push(self.normal_at(terrain.x(), terrain.y()).into());
+                uvs.push([terrain.x() / self.size.x(), terrain.y() / self.size.y()]);
+            }
+        }
+
+        let index = |x: usize, z: usize| (z * row + x) as u32;
+        let mut indices = Vec::with_capacity(quads as usize * quads as usize * 6);
+        for z in 0..quads as usize {
+            for x in 0..quads as usize {
+                indices.extend_from_slice(&[
+                    index(x, z),
+                    index(x, z + 1),
+                    index(x + 1, z),
+                    index(x + 1, z),
+                    index(x, z + 1),
+                    index(x + 1, z + 1),
+                ]);
+            }
+        }
+
+        if skirt_depth > 0.0 {
+            // each edge is walked so that its skirt faces away from the chunk
+            let last = row - 1;
+            let edges: [Vec<u32>; 4] = [
+                (0..row).map(|x| index(x, last)).collect(),
+                (0..row).rev().map(|x| index(x, 0)).collect(),
+                (0..row).rev().map(|z| index(last, z)).collect(),
+                (0..row).map(|z| index(0, z)).collect(),
+            ];
+            for edge in edges.iter() {
+                let skirt_start = positions.len() as u32;
+                for vertex in edge.iter() {
+                    let vertex = *vertex as usize;
+                    let [x, y, z] = positions[vertex];
+                    positions.push([x, y - skirt_depth, z]);
+                    normals.push(normals[vertex]);
+                    uvs.push(uvs[vertex]);
+                }
+                for i in 0..edge.len() - 1 {
+                    let (top_a, top_b) = (edge[i], edge[i + 1]);
+                    let (bottom_a, bottom_b) = (skirt_start + i as u32, skirt_start + i as u32 + 1);
+                    indices.extend_from_slice(&[top_a, bottom_a, top_b, top_b, bottom_a, bottom_b]);
+                }
+            }
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.attributes
+            .insert(Cow::Borrowed(Mesh::ATTRIBUTE_POSITION), positions.into());
+        mesh.attributes
+            .insert(Cow::Borrowed(Mesh::ATTRIBUTE_NORMAL), normals.into());
+        mesh.attributes
+            .insert(Cow::Borrowed(Mesh::ATTRIBUTE_UV_0), uvs.into());
+        mesh.indices = Some(Indices::U32(indices));
+        mesh
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Heightfield;
+    use bevy_math::{Vec2, Vec3};
+
+    fn slope() -> Heightfield {
+        // rises from 0.0 at x = 0 to 1.0 at x = 10
+        Heightfield::new(2, 2, vec![0.0, 1.0, 0.0, 1.0], Vec2::new(10.0, 10.0), 1.0)
+    }
+
+    #[test]
+    fn height_is_interpolated() {
+        let heightfield = slope();
+        assert!((heightfield.height_at(5.0, 3.0) - 0.5).abs() < 1e-5);
+        assert!((heightfield.height_at(20.0, 3.0) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn raycast_hits_surface() {
+        let heightfield = slope();
+        let hit = heightfield
+            .raycast(Vec3::new(5.0, 10.0, 5.0), Vec3::new(0.0, -1.0, 0.0), 100.0)
+            .unwrap();
+        assert!((hit.y() - 0.5).abs() < 1e-3);
+        assert!(heightfield
+            .raycast(Vec3::new(5.0, 10.0, 5.0), Vec3::new(0.0, 1.0, 0.0), 100.0)
+            .is_none());
+    }
+
+    #[test]
+    fn chunk_lod_reduces_vertices() {
+        let heightfield = slope();
+        let lod0 = heightfield.chunk_mesh(2, (1, 0), 8, 0, 0.0);
+        let lod2 = heightfield.chunk_mesh(2, (1, 0), 8, 2, 0.0);
+        assert_eq!(lod0.count_vertices(), 81);
+        assert_eq!(lod2.count_vertices(), 9);
+
+        let skirted = heightfield.chunk_mesh(2, (1, 0), 8, 2, 1.0);
+        assert_eq!(skirted.count_vertices(), 9 + 4 * 3);
+    }
+}
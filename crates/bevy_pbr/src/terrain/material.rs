@@ -0,0 +1,37 @@
+use bevy_asset::Handle;
+use bevy_render::{renderer::RenderResources, shader::ShaderDefs, texture::Texture};
+use bevy_type_registry::TypeUuid;
+
+/// A material that blends up to four texture layers across a terrain using a splat map. The red, green, blue and alpha
+/// channels of the splat map weight `layer_0` through `layer_3` respectively. Without a splat map only `layer_0` is
+/// drawn.
+///
+/// Layer textures are tiled `layer_scale` times across the terrain, so they should use a repeating sampler.
+#[derive(Debug, RenderResources, ShaderDefs, TypeUuid)]
+#[uuid = "4d62f8c5-0f52-4f3e-a1c8-1d0cf4a2d6b7"]
+pub struct TerrainMaterial {
+    pub layer_scale: f32,
+    #[shader_def]
+    pub splat_map: Option<Handle<Texture>>,
+    #[shader_def]
+    pub layer_0: Option<Handle<Texture>>,
+    #[shader_def]
+    pub layer_1: Option<Handle<Texture>>,
+    #[shader_def]
+    pub layer_2: Option<Handle<Texture>>,
+    #[shader_def]
+    pub layer_3: Option<Handle<Texture>>,
+}
+
+impl Default for TerrainMaterial {
+    fn default() -> Self {
+        TerrainMaterial {
+            layer_scale: 16.0,
+            splat_map: None,
+            layer_0: None,
+            layer_1: None,
+            layer_2: None,
+            layer_3: None,
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! Heightmap terrain split into chunks that pick their level of detail based on their distance to the 3d camera.
+//!
+//! Each chunk is its own entity, so anything that operates per entity (like visibility or culling) works per chunk.
+//! Use the [Heightfield] component that is added to the terrain entity for height queries and raycasts.
+
+mod heightfield;
+mod material;
+
+pub use heightfield::*;
+pub use material::*;
+
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_ecs::{Bundle, Commands, Entity, IntoQuerySystem, Query, Res, ResMut, Resources, Without};
+use bevy_math::{Vec2, Vec3};
+use bevy_render::{
+    camera::ActiveCameras,
+    draw::Draw,
+    mesh::Mesh,
+    pipeline::{
+        DynamicBinding, PipelineDescriptor, PipelineSpecialization, RenderPipeline, RenderPipelines,
+    },
+    render_graph::{base, AssetRenderResourcesNode, RenderGraph},
+    shader::{asset_shader_defs_system, Shader, ShaderStage, ShaderStages},
+    texture::Texture,
+};
+use bevy_transform::prelude::{BuildChildren, GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+
+pub const TERRAIN_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 7264619830247395711);
+
+pub mod node {
+    pub const TERRAIN_MATERIAL: &str = "terrain_material";
+}
+
+/// A terrain generated from a heightmap texture. The terrain spans `0..size.x` on its local X axis and `0..size.y` on
+/// its local Z axis.
+#[derive(Debug, Clone)]
+pub struct Terrain {
+    pub heightmap: Handle<Texture>,
+    pub material: Handle<TerrainMaterial>,
+    pub size: Vec2,
+    /// The height of the brightest heightmap value
+    pub height_scale: f32,
+    /// The number of chunks along each side of the terrain
+    pub chunks_per_side: u32,
+    /// The number of quads along each side of a chunk at LOD 0. Should be a power of two.
+    pub chunk_resolution: u32,
+    /// The camera distances at which chunks switch to the next LOD. Each LOD halves the chunk resolution.
+    pub lod_distances: Vec<f32>,
+    /// How far the skirts along chunk edges extend below the surface. Skirts hide cracks between neighboring chunks
+    /// with different LODs.
+    pub skirt_depth: f32,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain {
+            heightmap: Default::default(),
+            material: Default::default(),
+            size: Vec2::new(256.0, 256.0),
+            height_scale: 32.0,
+            chunks_per_side: 8,
+            chunk_resolution: 32,
+            lod_distances: vec![64.0, 128.0, 256.0],
+            skirt_depth: 1.0,
+        }
+    }
+}
+
+impl Terrain {
+    pub fn chunk_size(&self) -> Vec2 {
+        self.size / self.chunks_per_side as f32
+    }
+
+    pub fn max_lod(&self) -> u32 {
+        31 - self.chunk_resolution.max(1).leading_zeros()
+    }
+}
+
+/// A component bundle for "terrain" entities
+#[derive(Bundle, Default)]
+pub struct TerrainComponents {
+    pub terrain: Terrain,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+/// One chunk of a [Terrain]. Chunks are spawned as children of their terrain.
+#[derive(Debug)]
+pub struct TerrainChunk {
+    pub terrain: Entity,
+    pub coordinates: (u32, u32),
+    /// The current LOD, or `None` if no mesh has been assigned yet
+    pub lod: Option<u32>,
+    lod_meshes: Vec<Option<Handle<Mesh>>>,
+}
+
+#[derive(Bundle)]
+struct TerrainChunkComponents {
+    chunk: TerrainChunk,
+    mesh: Handle<Mesh>,
+    material: Handle<TerrainMaterial>,
+    main_pass: base::MainPass,
+    draw: Draw,
+    render_pipelines: RenderPipelines,
+    transform: Transform,
+    global_transform: GlobalTransform,
+}
+
+#[derive(Default)]
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<TerrainMaterial>()
+            .add_system_to_stage(stage::POST_UPDATE, terrain_chunk_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, terrain_lod_system.system())
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                asset_shader_defs_system::<TerrainMaterial>.system(),
+            );
+        let resources = app.resources();
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_terrain_graph(&mut render_graph, resources);
+    }
+}
+
+fn add_terrain_graph(graph: &mut RenderGraph, resources: &Resources) {
+    graph.add_system_node(
+        node::TERRAIN_MATERIAL,
+        AssetRenderResourcesNode::<TerrainMaterial>::new(true),
+    );
+    graph
+        .add_node_edge(node::TERRAIN_MATERIAL, base::node::MAIN_PASS)
+        .unwrap();
+
+    let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+    let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+    pipelines.set_untracked(
+        TERRAIN_PIPELINE_HANDLE,
+        PipelineDescriptor::default_config(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("terrain.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("terrain.frag"),
+            ))),
+        }),
+    );
+}
+
+fn terrain_render_pipelines() -> RenderPipelines {
+    RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+        TERRAIN_PIPELINE_HANDLE,
+        PipelineSpecialization {
+            dynamic_bindings: vec![
+                // Transform
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 0,
+                },
+                // TerrainMaterial_layer_scale
+                DynamicBinding {
+                    bind_group: 3,
+                    binding: 0,
+                },
+            ],
+            ..Default::default()
+        },
+    )])
+}
+
+/// Builds a [Heightfield] for each [Terrain] once its heightmap has loaded, then spawns the terrain's chunks
+pub fn terrain_chunk_system(
+    mut commands: Commands,
+    textures: Res<Assets<Texture>>,
+    query: Query<Without<Heightfield, (Entity, &Terrain)>>,
+) {
+    for (entity, terrain) in query.iter() {
+        let texture = if let Some(texture) = textures.get(&terrain.heightmap) {
+            texture
+        } else {
+            continue;
+        };
+
+        let heightfield = if let Some(heightfield) =
+            Heightfield::from_texture(texture, terrain.size, terrain.height_scale)
+        {
+            heightfield
+        } else {
+            log::warn!(
+                    "Terrain heightmaps must use a single channel, 8 bit, 16 bit or float format. Found {:?}",
+                    texture.format
+                );
+            continue;
+        };
+        commands.insert_one(entity, heightfield);
+
+        let chunk_size = terrain.chunk_size();
+        let mut chunks = Vec::new();
+        for z in 0..terrain.chunks_per_side {
+            for x in 0..terrain.chunks_per_side {
+                let origin = Vec3::new(x as f32 * chunk_size.x(), 0.0, z as f32 * chunk_size.y());
+                commands.spawn(TerrainChunkComponents {
+                    chunk: TerrainChunk {
+                        terrain: entity,
+                        coordinates: (x, z),
+                        lod: None,
+                        lod_meshes: vec![None; terrain.max_lod() as usize + 1],
+                    },
+                    mesh: Default::default(),
+                    material: terrain.material.clone(),
+                    main_pass: Default::default(),
+                    draw: Default::default(),
+                    render_pipelines: terrain_render_pipelines(),
+                    transform: Transform::from_translation(origin),
+                    global_transform: Default::default(),
+                });
+                chunks.push(commands.current_entity().unwrap());
+            }
+        }
+        commands.push_children(entity, &chunks);
+    }
+}
+
+/// Selects the LOD of each [TerrainChunk] based on its distance to the 3d camera. Chunk meshes are built the first time
+/// a LOD is needed and kept around afterwards.
+pub fn terrain_lod_system(
+    active_cameras: Res<ActiveCameras>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    terrain_query: Query<(&Terrain, &Heightfield)>,
+    camera_query: Query<&GlobalTransform>,
+    mut chunk_query: Query<(&mut TerrainChunk, &GlobalTransform, &mut Handle<Mesh>)>,
+) {
+    let camera_position = active_cameras
+        .get(base::camera::CAMERA3D)
+        .and_then(|camera| camera_query.get(camera).ok())
+        .map(|transform| transform.translation);
+
+    for (mut chunk, global_transform, mut mesh) in chunk_query.iter_mut() {
+        let (terrain, heightfield) = if let Ok(terrain) = terrain_query.get(chunk.terrain) {
+            terrain
+        } else {
+            continue;
+        };
+
+        let max_lod = terrain.max_lod();
+        let lod = if let Some(camera_position) = camera_position {
+            let chunk_size = terrain.chunk_size();
+            let center = global_transform.mul_vec3(Vec3::new(
+                chunk_size.x() / 2.0,
+                0.0,
+                chunk_size.y() / 2.0,
+            ));
+            let distance = (center - camera_position).length();
+            let lod = terrain
+                .lod_distances
+                .iter()
+                .filter(|lod_distance| distance > **lod_distance)
+                .count() as u32;
+            lod.min(max_lod)
+        } else {
+            max_lod
+        };
+
+        if chunk.lod == Some(lod) {
+            continue;
+        }
+
+        let coordinates = chunk.coordinates;
+        let lod_mesh = chunk.lod_meshes[lod as usize].get_or_insert_with(|| {
+            meshes.add(heightfield.chunk_mesh(
+                terrain.chunks_per_side,
+                coordinates,
+                terrain.chunk_resolution,
+                lod,
+                terrain.skirt_depth,
+            ))
+        });
+        *mesh = lod_mesh.clone();
+        chunk.lod = Some(lod);
+    }
+}
@@ -0,0 +1,112 @@
+#version 450
+
+const int MAX_LIGHTS = 10;
+
+struct Light {
+    mat4 proj;
+    vec4 pos;
+    vec4 color;
+};
+
+layout(location = 0) in vec3 v_Position;
+layout(location = 1) in vec3 v_Normal;
+layout(location = 2) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+    vec4 CameraPosition;
+};
+
+layout(set = 1, binding = 0) uniform Lights {
+    uvec4 NumLights;
+    vec4 AmbientColor;
+    vec4 FogColor;
+    // x: distance the fog starts at, y: distance it is thickest at
+    vec4 FogDistance;
+    Light SceneLights[MAX_LIGHTS];
+};
+
+layout(set = 3, binding = 0) uniform TerrainMaterial_layer_scale {
+    float LayerScale;
+};
+
+# ifdef TERRAINMATERIAL_SPLAT_MAP
+layout(set = 3, binding = 1) uniform texture2D TerrainMaterial_splat_map;
+layout(set = 3, binding = 2) uniform sampler TerrainMaterial_splat_map_sampler;
+# endif
+
+# ifdef TERRAINMATERIAL_LAYER_0
+layout(set = 3, binding = 3) uniform texture2D TerrainMaterial_layer_0;
+layout(set = 3, binding = 4) uniform sampler TerrainMaterial_layer_0_sampler;
+# endif
+
+# ifdef TERRAINMATERIAL_LAYER_1
+layout(set = 3, binding = 5) uniform texture2D TerrainMaterial_layer_1;
+layout(set = 3, binding = 6) uniform sampler TerrainMaterial_layer_1_sampler;
+# endif
+
+# ifdef TERRAINMATERIAL_LAYER_2
+layout(set = 3, binding = 7) uniform texture2D TerrainMaterial_layer_2;
+layout(set = 3, binding = 8) uniform sampler TerrainMaterial_layer_2_sampler;
+# endif
+
+# ifdef TERRAINMATERIAL_LAYER_3
+layout(set = 3, binding = 9) uniform texture2D TerrainMaterial_layer_3;
+layout(set = 3, binding = 10) uniform sampler TerrainMaterial_layer_3_sampler;
+# endif
+
+// fades the color to the fog color with the distance from the camera
+vec3 apply_fog(vec3 color, vec3 position) {
+    float camera_distance = length(position - CameraPosition.xyz);
+    float fog = clamp((camera_distance - FogDistance.x) / max(FogDistance.y - FogDistance.x, 0.0001), 0.0, 1.0);
+    return mix(color, FogColor.rgb, fog * FogColor.a);
+}
+
+void main() {
+    vec4 weights = vec4(1.0, 0.0, 0.0, 0.0);
+# ifdef TERRAINMATERIAL_SPLAT_MAP
+    weights = texture(
+        sampler2D(TerrainMaterial_splat_map, TerrainMaterial_splat_map_sampler),
+        v_Uv);
+    weights /= max(dot(weights, vec4(1.0)), 0.0001);
+# endif
+
+    vec2 layer_uv = v_Uv * LayerScale;
+    vec4 layers[4] = vec4[4](vec4(1.0), vec4(1.0), vec4(1.0), vec4(1.0));
+# ifdef TERRAINMATERIAL_LAYER_0
+    layers[0] = texture(sampler2D(TerrainMaterial_layer_0, TerrainMaterial_layer_0_sampler), layer_uv);
+# endif
+# ifdef TERRAINMATERIAL_LAYER_1
+    layers[1] = texture(sampler2D(TerrainMaterial_layer_1, TerrainMaterial_layer_1_sampler), layer_uv);
+# endif
+# ifdef TERRAINMATERIAL_LAYER_2
+    layers[2] = texture(sampler2D(TerrainMaterial_layer_2, TerrainMaterial_layer_2_sampler), layer_uv);
+# endif
+# ifdef TERRAINMATERIAL_LAYER_3
+    layers[3] = texture(sampler2D(TerrainMaterial_layer_3, TerrainMaterial_layer_3_sampler), layer_uv);
+# endif
+
+    vec4 output_color = layers[0] * weights.x
+        + layers[1] * weights.y
+        + layers[2] * weights.z
+        + layers[3] * weights.w;
+
+    vec3 normal = normalize(v_Normal);
+    vec3 ambient = AmbientColor.rgb;
+    // accumulate color
+    vec3 color = ambient;
+    for (int i=0; i<int(NumLights.x) && i<MAX_LIGHTS; ++i) {
+        Light light = SceneLights[i];
+        // directional lights store the direction towards the light, with a w of 0
+        vec3 light_dir = light.pos.w == 0.0 ? normalize(light.pos.xyz) : normalize(light.pos.xyz - v_Position);
+        // compute Lambertian diffuse term
+        float diffuse = max(0.0, dot(normal, light_dir));
+        // add light contribution
+        color += diffuse * light.color.xyz;
+    }
+    output_color.xyz *= color;
+
+    o_Target = vec4(apply_fog(output_color.xyz, v_Position), 1.0);
+}
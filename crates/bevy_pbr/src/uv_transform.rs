@@ -0,0 +1,132 @@
+use crate::material::StandardMaterial;
+use bevy_asset::{Assets, Handle, HandleId};
+use bevy_core::{Bytes, Time};
+use bevy_ecs::{Local, Query, Res, ResMut};
+use bevy_math::Vec2;
+use bevy_property::Properties;
+use bevy_render::renderer::RenderResource;
+use bevy_utils::HashSet;
+use std::f32::consts::PI;
+
+/// Transforms the texture coordinates of a [StandardMaterial]. Textures are scaled and rotated around their center,
+/// then offset.
+#[derive(Debug, Clone, Copy, PartialEq, Bytes, RenderResource, Properties)]
+pub struct UvTransform {
+    pub offset: Vec2,
+    pub scale: Vec2,
+    /// Counter-clockwise rotation in radians
+    pub rotation: f32,
+}
+
+impl Default for UvTransform {
+    fn default() -> Self {
+        UvTransform {
+            offset: Vec2::zero(),
+            scale: Vec2::one(),
+            rotation: 0.0,
+        }
+    }
+}
+
+impl UvTransform {
+    pub fn from_offset(offset: Vec2) -> Self {
+        UvTransform {
+            offset,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_scale(scale: Vec2) -> Self {
+        UvTransform {
+            scale,
+            ..Default::default()
+        }
+    }
+
+    /// Transforms `uv` the same way the forward shader does
+    pub fn transform_point(&self, uv: Vec2) -> Vec2 {
+        let center = Vec2::new(0.5, 0.5);
+        let scaled = (uv - center) * self.scale;
+        let (sin, cos) = self.rotation.sin_cos();
+        Vec2::new(
+            cos * scaled.x() - sin * scaled.y(),
+            sin * scaled.x() + cos * scaled.y(),
+        ) + center
+            + self.offset
+    }
+}
+
+/// Animates the [UvTransform] of an entity's [StandardMaterial], for example to scroll a conveyor belt or water.
+/// The material asset itself is animated, so every entity that uses it moves along. Materials shared by several
+/// animated entities are only advanced once per frame.
+#[derive(Debug, Clone, Default, Properties)]
+pub struct UvAnimation {
+    /// Offset change per second
+    pub scroll: Vec2,
+    /// Rotation change per second, in radians
+    pub rotation_speed: f32,
+}
+
+impl UvAnimation {
+    pub fn scroll(scroll: Vec2) -> Self {
+        UvAnimation {
+            scroll,
+            rotation_speed: 0.0,
+        }
+    }
+
+    pub fn rotate(rotation_speed: f32) -> Self {
+        UvAnimation {
+            scroll: Vec2::zero(),
+            rotation_speed,
+        }
+    }
+
+    pub fn advance(&self, uv_transform: &mut UvTransform, seconds: f32) {
+        // wrap around to keep precision in long running apps. textures repeat every 1.0 uv units.
+        let offset = uv_transform.offset + self.scroll * seconds;
+        uv_transform.offset = Vec2::new(offset.x().rem_euclid(1.0), offset.y().rem_euclid(1.0));
+        uv_transform.rotation =
+            (uv_transform.rotation + self.rotation_speed * seconds).rem_euclid(2.0 * PI);
+    }
+}
+
+/// Advances the [UvTransform] of materials with a [UvAnimation]
+pub fn uv_animation_system(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut animated: Local<HashSet<HandleId>>,
+    query: Query<(&UvAnimation, &Handle<StandardMaterial>)>,
+) {
+    animated.clear();
+    for (animation, handle) in query.iter() {
+        if !animated.insert(handle.id) {
+            continue;
+        }
+
+        if let Some(material) = materials.get_mut(handle) {
+            animation.advance(&mut material.uv_transform, time.delta_seconds);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uv_transform_rotates_around_center() {
+        let uv_transform = UvTransform {
+            offset: Vec2::new(0.25, 0.0),
+            scale: Vec2::new(2.0, 2.0),
+            rotation: PI / 2.0,
+        };
+        let uv = uv_transform.transform_point(Vec2::new(1.0, 0.5));
+        assert!((uv - Vec2::new(0.75, 1.5)).length() < 1e-5);
+        assert_eq!(uv_transform.byte_len(), 20);
+
+        let mut scrolled = UvTransform::default();
+        UvAnimation::scroll(Vec2::new(0.5, -0.25)).advance(&mut scrolled, 3.0);
+        assert!((scrolled.offset - Vec2::new(0.5, 0.25)).length() < 1e-5);
+    }
+}
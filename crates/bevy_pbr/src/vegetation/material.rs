@@ -0,0 +1,40 @@
+use bevy_core::Bytes;
+use bevy_math::Vec2;
+use bevy_property::Properties;
+use bevy_render::renderer::{RenderResource, RenderResources};
+use bevy_type_registry::TypeUuid;
+
+/// How the wind bends vegetation. Vertices sway more the higher they are above the mesh's origin, so roots stay in
+/// place.
+#[derive(Debug, Clone, Copy, PartialEq, Bytes, RenderResource, Properties)]
+pub struct Wind {
+    /// The direction the wind blows in on the XZ plane
+    pub direction: Vec2,
+    /// How far the top of the vegetation moves, in world units
+    pub strength: f32,
+    /// How fast the vegetation sways back and forth, in radians per second
+    pub frequency: f32,
+    /// The height above the mesh's origin at which vertices sway at full strength
+    pub height: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Wind {
+            direction: Vec2::new(1.0, 0.0),
+            strength: 0.1,
+            frequency: 2.0,
+            height: 1.0,
+        }
+    }
+}
+
+/// The wind of [VegetationComponents](super::VegetationComponents). Their colors come from their
+/// [StandardMaterial](crate::StandardMaterial).
+#[derive(Debug, Default, RenderResources, TypeUuid)]
+#[uuid = "7e3c9b1a-5d24-4f8e-a6c0-2b9d8e4f1a73"]
+pub struct VegetationMaterial {
+    pub wind: Wind,
+    /// Animates the wind. Updated every frame by [vegetation_material_time_system](super::vegetation_material_time_system).
+    pub time: f32,
+}
@@ -0,0 +1,258 @@
+mod material;
+mod scatter;
+
+pub use material::*;
+pub use scatter::*;
+
+use crate::{
+    entity::forward_pipeline_specialization, material::StandardMaterial,
+    render_graph::build_forward_pipeline,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_core::{Byteable, Time};
+use bevy_ecs::{Bundle, Changed, Entity, IntoQuerySystem, Query, Res, ResMut};
+use bevy_math::Mat4;
+use bevy_render::{
+    draw::{Draw, InstanceCount},
+    gpu_culling::GpuCulling,
+    mesh::{Mesh, VertexAttributeValues},
+    pipeline::{DynamicBinding, PipelineDescriptor, RenderPipeline, RenderPipelines},
+    render_graph::{
+        base::{self, MainPass},
+        AssetRenderResourcesNode, RenderGraph, RenderResourcesNode,
+    },
+    renderer::RenderResources,
+    shader::{Shader, ShaderStage},
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+
+pub const VEGETATION_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 8470126395318764002);
+
+pub mod node {
+    pub const VEGETATION_INSTANCES: &str = "vegetation_instances";
+    pub const VEGETATION_MATERIAL: &str = "vegetation_material";
+}
+
+/// The per-instance data of [VegetationInstances], laid out the way the vegetation shader reads it
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VegetationInstance {
+    pub transform: [[f32; 4]; 4],
+    /// x: offsets the wind's sway, so neighbouring instances don't move in lockstep
+    pub wind: [f32; 4],
+}
+
+unsafe impl Byteable for VegetationInstance {}
+
+impl VegetationInstance {
+    pub fn new(transform: Mat4, wind_phase: f32) -> Self {
+        VegetationInstance {
+            transform: transform.to_cols_array_2d(),
+            wind: [wind_phase, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+/// The instances of a [VegetationComponents] entity, relative to its transform. They are stored in a storage buffer
+/// and drawn with a single draw call. [Scatter] fills them over an area.
+#[derive(Debug, Default, Clone, RenderResources)]
+pub struct VegetationInstances {
+    #[render_resources(buffer)]
+    pub instances: Vec<VegetationInstance>,
+}
+
+/// A component bundle for a mesh drawn many times, like grass, flowers or trees, that sways in the wind
+#[derive(Bundle)]
+pub struct VegetationComponents {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+    pub vegetation_material: Handle<VegetationMaterial>,
+    pub instances: VegetationInstances,
+    /// Kept in sync with `instances` by [vegetation_instance_count_system]
+    pub instance_count: InstanceCount,
+    /// Kept in sync with `instances` by [vegetation_culling_bounds_system]. Instances are only culled with the
+    /// [GpuCullingPlugin](bevy_render::gpu_culling::GpuCullingPlugin).
+    pub gpu_culling: GpuCulling,
+    pub main_pass: MainPass,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for VegetationComponents {
+    fn default() -> Self {
+        let mut specialization = forward_pipeline_specialization();
+        // VegetationMaterial_wind and VegetationMaterial_time
+        specialization
+            .dynamic_bindings
+            .extend((10..12).map(|binding| DynamicBinding {
+                bind_group: 3,
+                binding,
+            }));
+        VegetationComponents {
+            mesh: Default::default(),
+            material: Default::default(),
+            vegetation_material: Default::default(),
+            instances: Default::default(),
+            instance_count: InstanceCount(0),
+            gpu_culling: Default::default(),
+            main_pass: MainPass,
+            draw: Default::default(),
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+                VEGETATION_PIPELINE_HANDLE,
+                specialization,
+            )]),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+/// Draws [VegetationComponents]. Their instances are lit by the forward shader, so this needs the
+/// [PbrPlugin](crate::PbrPlugin).
+#[derive(Default)]
+pub struct VegetationPlugin;
+
+impl Plugin for VegetationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<VegetationMaterial>()
+            .add_system_to_stage(stage::UPDATE, vegetation_material_time_system.system())
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                vegetation_instance_count_system.system(),
+            )
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                vegetation_culling_bounds_system.system(),
+            );
+
+        let resources = app.resources();
+        resources
+            .get_mut::<Assets<VegetationMaterial>>()
+            .unwrap()
+            .set_untracked(
+                Handle::<VegetationMaterial>::default(),
+                VegetationMaterial::default(),
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        // instance buffers differ in size, so they can't share a dynamic uniform buffer
+        render_graph.add_system_node(
+            node::VEGETATION_INSTANCES,
+            RenderResourcesNode::<VegetationInstances>::new(false),
+        );
+        render_graph.add_system_node(
+            node::VEGETATION_MATERIAL,
+            AssetRenderResourcesNode::<VegetationMaterial>::new(true),
+        );
+        render_graph
+            .add_node_edge(node::VEGETATION_INSTANCES, base::node::MAIN_PASS)
+            .unwrap();
+        render_graph
+            .add_node_edge(node::VEGETATION_MATERIAL, base::node::MAIN_PASS)
+            .unwrap();
+
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let mut pipeline = build_forward_pipeline(&mut shaders);
+        pipeline.shader_stages.vertex = shaders.add(Shader::from_glsl(
+            ShaderStage::Vertex,
+            include_str!("vegetation.vert"),
+        ));
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(VEGETATION_PIPELINE_HANDLE, pipeline);
+    }
+}
+
+pub fn vegetation_material_time_system(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<VegetationMaterial>>,
+) {
+    let ids = materials.ids().collect::<Vec<_>>();
+    for id in ids {
+        if let Some(material) = materials.get_mut(id) {
+            material.time = time.seconds_since_startup as f32;
+        }
+    }
+}
+
+/// Draws each [VegetationComponents] entity once per instance
+pub fn vegetation_instance_count_system(
+    mut query: Query<(&VegetationInstances, &mut InstanceCount)>,
+) {
+    for (instances, mut instance_count) in query.iter_mut() {
+        let count = instances.instances.len() as u32;
+        if instance_count.0 != count {
+            instance_count.0 = count;
+        }
+    }
+}
+
+/// Updates the [GpuCulling] bounds of [VegetationComponents] entities when their instances change. Each instance is
+/// bounded by a sphere around the mesh's origin that also covers the wind's sway.
+pub fn vegetation_culling_bounds_system(
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<VegetationMaterial>>,
+    changed_query: Query<(Entity, Changed<VegetationInstances>)>,
+    mut query: Query<(
+        &VegetationInstances,
+        &Handle<Mesh>,
+        &Handle<VegetationMaterial>,
+        &mut GpuCulling,
+    )>,
+) {
+    for (entity, _instances) in changed_query.iter() {
+        let (instances, mesh, material, mut gpu_culling) = query.get_mut(entity).unwrap();
+        let radius = match (meshes.get(mesh), materials.get(material)) {
+            (Some(mesh), Some(material)) => mesh_radius(mesh) + material.wind.strength,
+            _ => continue,
+        };
+        gpu_culling.instance_bounds = instance_bounds(&instances.instances, radius);
+    }
+
+    // meshes and materials that load later
+    for (instances, mesh, material, mut gpu_culling) in query.iter_mut() {
+        if gpu_culling.instance_bounds.len() == instances.instances.len() {
+            continue;
+        }
+        if let (Some(mesh), Some(material)) = (meshes.get(mesh), materials.get(material)) {
+            let radius = mesh_radius(mesh) + material.wind.strength;
+            gpu_culling.instance_bounds = instance_bounds(&instances.instances, radius);
+        }
+    }
+}
+
+/// The distance of the mesh's farthest vertex from its origin
+fn mesh_radius(mesh: &Mesh) -> f32 {
+    match mesh.attributes.get(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float3(positions)) => positions
+            .iter()
+            .map(|[x, y, z]| (x * x + y * y + z * z).sqrt())
+            .fold(0.0, f32::max),
+        _ => 0.0,
+    }
+}
+
+fn instance_bounds(instances: &[VegetationInstance], mesh_radius: f32) -> Vec<[f32; 4]> {
+    instances
+        .iter()
+        .map(|instance| {
+            let [x_axis, y_axis, z_axis, translation] = instance.transform;
+            let scale = [x_axis, y_axis, z_axis]
+                .iter()
+                .map(|axis| (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt())
+                .fold(0.0, f32::max);
+            [
+                translation[0],
+                translation[1],
+                translation[2],
+                mesh_radius * scale,
+            ]
+        })
+        .collect()
+}
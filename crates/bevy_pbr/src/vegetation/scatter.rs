@@ -0,0 +1,147 @@
+use super::VegetationInstance;
+use crate::terrain::Heightfield;
+use bevy_core::Rng;
+use bevy_math::{Mat4, Quat, Vec2, Vec3};
+use bevy_render::texture::Texture;
+use std::{f32::consts::PI, ops::Range};
+
+/// Scales how many instances [Scatter] places across its area. Values range from 0 for none to 1 for the full
+/// density.
+#[derive(Debug, Clone)]
+pub struct DensityMap {
+    samples: Heightfield,
+}
+
+impl DensityMap {
+    /// Reads the first channel of `texture`, which covers the whole scatter area and needs at least 2x2 pixels. Returns
+    /// `None` if the texture format isn't supported.
+    pub fn from_texture(texture: &Texture) -> Option<Self> {
+        Heightfield::from_texture(texture, Vec2::one(), 1.0).map(|samples| DensityMap { samples })
+    }
+
+    /// The bilinearly filtered density at the given normalized (0.0 to 1.0) coordinates of the scatter area
+    pub fn density_at_uv(&self, uv: Vec2) -> f32 {
+        self.samples.height_at_uv(uv)
+    }
+}
+
+/// Places instances at random positions within an area of the XZ plane, optionally thinned out by a [DensityMap] and
+/// placed on top of a [Heightfield]. Positions are in the space of the entity that draws the instances.
+#[derive(Debug, Clone)]
+pub struct Scatter<'a> {
+    /// The corner of the area with the lowest X and Z coordinates
+    pub min: Vec2,
+    /// The corner of the area with the highest X and Z coordinates
+    pub max: Vec2,
+    /// The average number of instances per square unit where the density is at its maximum
+    pub density: f32,
+    pub density_map: Option<&'a DensityMap>,
+    /// Instances are placed on this surface instead of at a height of 0. Its terrain space is used as is, so the area
+    /// usually covers `0..size` of the heightfield.
+    pub surface: Option<&'a Heightfield>,
+    /// The range each instance's uniform scale is picked from
+    pub scale: Range<f32>,
+    /// Rotates each instance by a random angle around the Y axis
+    pub random_rotation: bool,
+}
+
+impl Default for Scatter<'_> {
+    fn default() -> Self {
+        Scatter {
+            min: Vec2::new(-10.0, -10.0),
+            max: Vec2::new(10.0, 10.0),
+            density: 1.0,
+            density_map: None,
+            surface: None,
+            scale: 0.8..1.2,
+            random_rotation: true,
+        }
+    }
+}
+
+impl Scatter<'_> {
+    /// Places the instances. The same `rng` seed always gives the same instances.
+    pub fn instances(&self, rng: &mut Rng) -> Vec<VegetationInstance> {
+        let size = self.max - self.min;
+        let area = (size.x() * size.y()).abs();
+        let candidates = (area * self.density).round() as usize;
+        let mut instances = Vec::with_capacity(candidates);
+        for _ in 0..candidates {
+            let uv = Vec2::new(rng.next_f32(), rng.next_f32());
+            // every candidate draws the same random numbers, so a density map doesn't shift the other instances
+            let keep = rng.next_f32();
+            let angle = rng.range(0.0..2.0 * PI);
+            let scale = rng.range(self.scale.clone());
+            let wind_phase = rng.range(0.0..2.0 * PI);
+
+            if let Some(density_map) = self.density_map {
+                if keep >= density_map.density_at_uv(uv) {
+                    continue;
+                }
+            }
+
+            let position = self.min + uv * size;
+            let height = self
+                .surface
+                .map_or(0.0, |surface| surface.height_at(position.x(), position.y()));
+            let rotation = if self.random_rotation {
+                Quat::from_rotation_y(angle)
+            } else {
+                Quat::identity()
+            };
+            let transform = Mat4::from_scale_rotation_translation(
+                Vec3::splat(scale),
+                rotation,
+                Vec3::new(position.x(), height, position.y()),
+            );
+            instances.push(VegetationInstance::new(transform, wind_phase));
+        }
+        instances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_render::texture::TextureFormat;
+
+    #[test]
+    fn scatter_follows_density() {
+        let scatter = Scatter {
+            min: Vec2::new(0.0, 0.0),
+            max: Vec2::new(10.0, 10.0),
+            density: 2.0,
+            ..Default::default()
+        };
+        let instances = scatter.instances(&mut Rng::with_seed(1));
+        assert_eq!(instances.len(), 200);
+        for instance in instances.iter() {
+            let (x, y, z) = (
+                instance.transform[3][0],
+                instance.transform[3][1],
+                instance.transform[3][2],
+            );
+            assert!((0.0..10.0).contains(&x) && (0.0..10.0).contains(&z));
+            assert_eq!(y, 0.0);
+        }
+        let again = scatter.instances(&mut Rng::with_seed(1));
+        assert_eq!(instances[7].transform, again[7].transform);
+
+        // the density rises from nothing on the left edge to full on the right edge
+        let texture = Texture::new(
+            Vec2::new(2.0, 2.0),
+            vec![0, 255, 0, 255],
+            TextureFormat::R8Unorm,
+        );
+        let density_map = DensityMap::from_texture(&texture).unwrap();
+        let thinned = Scatter {
+            density_map: Some(&density_map),
+            ..scatter
+        }
+        .instances(&mut Rng::with_seed(1));
+        assert!(thinned.len() > 50 && thinned.len() < 150);
+        assert!(thinned
+            .iter()
+            .all(|instance| instance.transform[3][0] > 0.0));
+    }
+}
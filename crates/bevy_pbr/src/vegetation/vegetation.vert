@@ -0,0 +1,95 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec3 Vertex_Normal;
+layout(location = 2) in vec2 Vertex_Uv;
+# ifdef MESH_VERTEX_COLOR
+layout(location = 3) in vec4 Vertex_Color;
+# endif
+# ifdef STANDARDMATERIAL_LIGHTMAP
+layout(location = 4) in vec2 Vertex_Uv1;
+# endif
+
+layout(location = 0) out vec3 v_Position;
+layout(location = 1) out vec3 v_Normal;
+layout(location = 2) out vec2 v_Uv;
+# ifdef MESH_VERTEX_COLOR
+layout(location = 3) out vec4 v_Color;
+# endif
+# ifdef STANDARDMATERIAL_LIGHTMAP
+layout(location = 4) out vec2 v_Uv1;
+# endif
+
+struct VegetationInstance {
+    mat4 Transform;
+    // x: wind phase
+    vec4 Wind;
+};
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+    vec4 CameraPosition;
+    // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+    vec4 CameraExposure;
+};
+
+layout(set = 2, binding = 0) uniform Transform {
+    mat4 Model;
+};
+
+layout(set = 2, binding = 10) readonly buffer VegetationInstances_instances {
+    VegetationInstance Instances[];
+};
+
+# ifdef GPU_CULLING
+layout(set = 2, binding = 11) readonly buffer GpuCulling_visible {
+    uint VisibleInstances[];
+};
+# endif
+
+layout(set = 3, binding = 3) uniform StandardMaterial_uv_transform {
+    vec2 UvTransformOffset;
+    vec2 UvTransformScale;
+    float UvTransformRotation;
+};
+
+layout(set = 3, binding = 10) uniform VegetationMaterial_wind {
+    vec2 WindDirection;
+    float WindStrength;
+    float WindFrequency;
+    float WindHeight;
+};
+
+layout(set = 3, binding = 11) uniform VegetationMaterial_time {
+    float Time;
+};
+
+void main() {
+# ifdef GPU_CULLING
+    VegetationInstance instance = Instances[VisibleInstances[gl_InstanceIndex]];
+# else
+    VegetationInstance instance = Instances[gl_InstanceIndex];
+# endif
+    mat4 model = Model * instance.Transform;
+    v_Normal = mat3(model) * Vertex_Normal;
+    v_Position = (model * vec4(Vertex_Position, 1.0)).xyz;
+
+    // the vegetation leans with the wind and sways around that lean. two waves keep the motion from looking regular.
+    float bend = clamp(Vertex_Position.y / max(WindHeight, 0.0001), 0.0, 1.0);
+    float phase = Time * WindFrequency + instance.Wind.x;
+    float sway = sin(phase) * 0.7 + sin(phase * 2.3 + 1.7) * 0.3;
+    v_Position.xz += WindDirection * WindStrength * bend * bend * (0.5 + 0.5 * sway);
+
+    // scale and rotate around the texture's center
+    vec2 uv = (Vertex_Uv - 0.5) * UvTransformScale;
+    float uv_sin = sin(UvTransformRotation);
+    float uv_cos = cos(UvTransformRotation);
+    v_Uv = vec2(uv_cos * uv.x - uv_sin * uv.y, uv_sin * uv.x + uv_cos * uv.y) + 0.5 + UvTransformOffset;
+# ifdef MESH_VERTEX_COLOR
+    v_Color = Vertex_Color;
+# endif
+# ifdef STANDARDMATERIAL_LIGHTMAP
+    v_Uv1 = Vertex_Uv1;
+# endif
+    gl_Position = ViewProj * vec4(v_Position, 1.0);
+}
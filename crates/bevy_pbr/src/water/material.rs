@@ -0,0 +1,36 @@
+use super::REFLECTION_TEXTURE_HANDLE;
+use bevy_asset::Handle;
+use bevy_render::{color::Color, renderer::RenderResources, shader::ShaderDefs, texture::Texture};
+use bevy_type_registry::TypeUuid;
+
+/// A material for water surfaces that shows the planar reflection rendered by [WaterPlugin](super::WaterPlugin),
+/// distorted by a scrolling normal map.
+#[derive(Debug, RenderResources, ShaderDefs, TypeUuid)]
+#[uuid = "0b8a1c3e-6f2d-4c0a-9d7e-5a4b3c2d1e0f"]
+pub struct WaterMaterial {
+    /// The color of the water. Its alpha sets how much the color covers the reflection.
+    pub color: Color,
+    /// How far the normal map shifts the reflection, in screen space
+    pub distortion: f32,
+    /// How many times the normal map repeats across the surface's uvs
+    pub normal_map_scale: f32,
+    /// Scrolls the normal map. Updated every frame by [water_material_time_system](super::water_material_time_system).
+    pub time: f32,
+    #[shader_def]
+    pub normal_map: Option<Handle<Texture>>,
+    #[shader_def]
+    pub reflection: Option<Handle<Texture>>,
+}
+
+impl Default for WaterMaterial {
+    fn default() -> Self {
+        WaterMaterial {
+            color: Color::rgba(0.1, 0.3, 0.4, 0.3),
+            distortion: 0.02,
+            normal_map_scale: 8.0,
+            time: 0.0,
+            normal_map: None,
+            reflection: Some(REFLECTION_TEXTURE_HANDLE),
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Water surfaces with planar reflections.
+//!
+//! [WaterPlugin] adds a camera that follows the mirror image of the 3d camera below the [ReflectionPlane] and a pass
+//! that renders the main pass entities from it to a reduced resolution texture. [WaterMaterial] samples that texture
+//! in screen space. Add the plugin after the other render plugins, so the reflection pass waits for the same nodes as
+//! the main pass.
+
+mod material;
+mod reflection;
+
+pub use material::*;
+pub use reflection::*;
+
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_core::Time;
+use bevy_ecs::{Bundle, Commands, IntoQuerySystem, Res, ResMut, Resources, Without};
+use bevy_math::Vec2;
+use bevy_render::{
+    camera::{ActiveCameras, Camera, VisibleEntities},
+    draw::Draw,
+    mesh::Mesh,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+        TextureAttachment,
+    },
+    pipeline::{
+        DynamicBinding, PipelineDescriptor, PipelineSpecialization, RenderPipeline, RenderPipelines,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, MainPass, Msaa, MsaaTexture},
+        AssetRenderResourcesNode, AssetTextureNode, CameraNode, PassNode, RenderGraph, TextureNode,
+    },
+    shader::{asset_shader_defs_system, Shader, ShaderStage, ShaderStages},
+    texture::{
+        Extent3d, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+    },
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+
+pub const WATER_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 3108427150938206231);
+
+/// The texture the reflection pass renders to
+pub const REFLECTION_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 9127305718264906514);
+
+pub mod node {
+    pub const WATER_MATERIAL: &str = "water_material";
+    pub const REFLECTION_CAMERA: &str = "reflection_camera";
+    pub const REFLECTION_TEXTURE: &str = "reflection_texture";
+    pub const REFLECTION_SAMPLED_COLOR_ATTACHMENT: &str = "reflection_sampled_color_attachment";
+    pub const REFLECTION_DEPTH_TEXTURE: &str = "reflection_depth_texture";
+    pub const REFLECTION_PASS: &str = "reflection_pass";
+}
+
+pub mod camera {
+    pub use bevy_render::render_graph::base::camera::CAMERA3D;
+    pub const REFLECTION_CAMERA: &str = "ReflectionCamera";
+}
+
+/// A component bundle for water surfaces
+#[derive(Bundle)]
+pub struct WaterComponents {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<WaterMaterial>,
+    pub reflection_plane: ReflectionPlane,
+    pub main_pass: MainPass,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for WaterComponents {
+    fn default() -> Self {
+        WaterComponents {
+            mesh: Default::default(),
+            material: Default::default(),
+            reflection_plane: Default::default(),
+            main_pass: MainPass,
+            draw: Default::default(),
+            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+                WATER_PIPELINE_HANDLE,
+                PipelineSpecialization {
+                    dynamic_bindings: vec![
+                        // Transform
+                        DynamicBinding {
+                            bind_group: 2,
+                            binding: 0,
+                        },
+                        // WaterMaterial_color
+                        DynamicBinding {
+                            bind_group: 3,
+                            binding: 0,
+                        },
+                        // WaterMaterial_distortion
+                        DynamicBinding {
+                            bind_group: 3,
+                            binding: 1,
+                        },
+                        // WaterMaterial_normal_map_scale
+                        DynamicBinding {
+                            bind_group: 3,
+                            binding: 2,
+                        },
+                        // WaterMaterial_time
+                        DynamicBinding {
+                            bind_group: 3,
+                            binding: 3,
+                        },
+                    ],
+                    ..Default::default()
+                },
+            )]),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+pub struct WaterPlugin {
+    /// The size of the reflection texture. It is stretched over the whole window, so it doesn't need to match the
+    /// window's aspect ratio.
+    pub reflection_size: Vec2,
+}
+
+impl Default for WaterPlugin {
+    fn default() -> Self {
+        WaterPlugin {
+            reflection_size: Vec2::new(512.0, 512.0),
+        }
+    }
+}
+
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<WaterMaterial>()
+            .add_startup_system(spawn_reflection_camera.system())
+            .add_system_to_stage(stage::UPDATE, water_material_time_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, reflection_camera_system.system())
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                asset_shader_defs_system::<WaterMaterial>.system(),
+            );
+
+        let resources = app.resources();
+        resources
+            .get_mut::<ActiveCameras>()
+            .unwrap()
+            .add(camera::REFLECTION_CAMERA);
+        resources
+            .get_mut::<Assets<Texture>>()
+            .unwrap()
+            .set_untracked(
+                REFLECTION_TEXTURE_HANDLE,
+                Texture::new_render_target(self.reflection_size, TextureFormat::default()),
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_water_graph(&mut render_graph, resources, self.reflection_size);
+    }
+}
+
+fn spawn_reflection_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera {
+            name: Some(camera::REFLECTION_CAMERA.to_string()),
+            ..Default::default()
+        },
+        ReflectionCamera,
+        VisibleEntities::default(),
+        Transform::default(),
+        GlobalTransform::default(),
+    ));
+}
+
+/// Advances [WaterMaterial::time] to scroll the normal maps
+pub fn water_material_time_system(time: Res<Time>, mut materials: ResMut<Assets<WaterMaterial>>) {
+    let ids = materials.ids().collect::<Vec<_>>();
+    for id in ids {
+        if let Some(material) = materials.get_mut(id) {
+            material.time = time.seconds_since_startup as f32;
+        }
+    }
+}
+
+fn add_water_graph(graph: &mut RenderGraph, resources: &Resources, reflection_size: Vec2) {
+    let texture_descriptor = |format| TextureDescriptor {
+        size: Extent3d {
+            width: reflection_size.x() as u32,
+            height: reflection_size.y() as u32,
+            depth: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsage::OUTPUT_ATTACHMENT,
+    };
+
+    graph.add_system_node(
+        node::WATER_MATERIAL,
+        AssetRenderResourcesNode::<WaterMaterial>::new(true),
+    );
+    graph
+        .add_node_edge(node::WATER_MATERIAL, base::node::MAIN_PASS)
+        .unwrap();
+
+    graph.add_system_node(
+        node::REFLECTION_CAMERA,
+        CameraNode::new(camera::REFLECTION_CAMERA),
+    );
+    graph.add_node(
+        node::REFLECTION_TEXTURE,
+        AssetTextureNode::new(REFLECTION_TEXTURE_HANDLE),
+    );
+    graph.add_node(
+        node::REFLECTION_DEPTH_TEXTURE,
+        TextureNode::multisampled(
+            texture_descriptor(TextureFormat::Depth32Float),
+            MsaaTexture::Depth,
+        ),
+    );
+
+    // the water itself is left out of its reflection
+    let mut reflection_pass_node =
+        PassNode::<Without<ReflectionPlane, &MainPass>>::new(PassDescriptor {
+            color_attachments: vec![Msaa::color_attachment_descriptor(
+                TextureAttachment::Input("color_attachment".to_string()),
+                TextureAttachment::Input("color_resolve_target".to_string()),
+                Operations {
+                    load: LoadOp::Clear(Color::rgb(0.1, 0.1, 0.1)),
+                    store: true,
+                },
+            )],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+                attachment: TextureAttachment::Input("depth".to_string()),
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+            sample_count: 1,
+        });
+    reflection_pass_node.use_default_clear_color(0);
+    reflection_pass_node.add_camera(camera::REFLECTION_CAMERA);
+
+    // the reflection pass draws the same entities as the main pass, so it depends on the same nodes
+    let main_pass_dependencies = graph
+        .iter_node_inputs(base::node::MAIN_PASS)
+        .map(|inputs| {
+            inputs
+                .filter(|(_edge, node)| node.name.as_deref() != Some(node::WATER_MATERIAL))
+                .map(|(_edge, node)| node.id)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    graph.add_node(node::REFLECTION_PASS, reflection_pass_node);
+    for dependency in main_pass_dependencies {
+        // a node can be connected to the main pass more than once, in which case the edge already exists
+        let _ = graph.add_node_edge(dependency, node::REFLECTION_PASS);
+    }
+    graph
+        .add_node_edge(node::REFLECTION_CAMERA, node::REFLECTION_PASS)
+        .unwrap();
+    graph
+        .add_node_edge(node::REFLECTION_PASS, base::node::MAIN_PASS)
+        .unwrap();
+
+    graph.add_node(
+        node::REFLECTION_SAMPLED_COLOR_ATTACHMENT,
+        TextureNode::multisampled(
+            texture_descriptor(TextureFormat::default()),
+            MsaaTexture::Color,
+        ),
+    );
+    graph
+        .add_slot_edge(
+            node::REFLECTION_SAMPLED_COLOR_ATTACHMENT,
+            TextureNode::OUT_TEXTURE,
+            node::REFLECTION_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+
+    graph
+        .add_slot_edge(
+            node::REFLECTION_TEXTURE,
+            AssetTextureNode::OUT_TEXTURE,
+            node::REFLECTION_PASS,
+            "color_resolve_target",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            node::REFLECTION_DEPTH_TEXTURE,
+            TextureNode::OUT_TEXTURE,
+            node::REFLECTION_PASS,
+            "depth",
+        )
+        .unwrap();
+
+    let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+    let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+    pipelines.set_untracked(
+        WATER_PIPELINE_HANDLE,
+        PipelineDescriptor::default_config(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("water.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("water.frag"),
+            ))),
+        }),
+    );
+}
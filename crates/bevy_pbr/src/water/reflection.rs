@@ -0,0 +1,135 @@
+use super::camera;
+use bevy_ecs::{Query, QuerySet, Res, With};
+use bevy_math::{Mat4, Vec3, Vec4};
+use bevy_render::camera::{ActiveCameras, Camera};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+
+/// A plane that is reflected by the [WaterPlugin](super::WaterPlugin) reflection pass. The plane passes through the
+/// entity's position and faces along its local Y axis. Only one reflection plane is rendered at a time.
+#[derive(Debug, Clone)]
+pub struct ReflectionPlane {
+    /// Moves the clip plane of the reflection pass along the plane normal. A small positive offset hides seams where
+    /// objects intersect the surface, a negative one hides gaps caused by distortion.
+    pub clip_offset: f32,
+}
+
+impl Default for ReflectionPlane {
+    fn default() -> Self {
+        ReflectionPlane { clip_offset: 0.0 }
+    }
+}
+
+/// Marks the camera that renders the reflection pass
+#[derive(Debug, Default)]
+pub struct ReflectionCamera;
+
+/// Mirrors `transform` about the plane through `point` with `normal`. The mirrored transform is rotated upside down
+/// instead of being flipped, because a flipped transform would turn the winding order of every triangle around.
+pub fn reflect_transform(
+    transform: &GlobalTransform,
+    point: Vec3,
+    normal: Vec3,
+) -> GlobalTransform {
+    let reflect_direction = |direction: Vec3| direction - 2.0 * direction.dot(normal) * normal;
+    let translation =
+        transform.translation - 2.0 * (transform.translation - point).dot(normal) * normal;
+    let forward = reflect_direction(transform.rotation * -Vec3::unit_z());
+    let up = -reflect_direction(transform.rotation * Vec3::unit_y());
+    GlobalTransform::from_translation(translation).looking_at(translation + forward, up)
+}
+
+/// Replaces the near plane of a perspective `projection` with `clip_plane`, which is given in view space and keeps the
+/// points on its positive side. See Eric Lengyel, "Oblique View Frustum Depth Projection and Clipping".
+pub fn oblique_projection(projection: Mat4, clip_plane: Vec4) -> Mat4 {
+    // the corner of the frustum opposite to the clip plane
+    let corner = projection.inverse()
+        * Vec4::new(clip_plane.x().signum(), clip_plane.y().signum(), 1.0, 1.0);
+    let scaled_plane = clip_plane * (1.0 / clip_plane.dot(corner));
+
+    let mut columns = projection.to_cols_array();
+    columns[2] = scaled_plane.x();
+    columns[6] = scaled_plane.y();
+    columns[10] = scaled_plane.z();
+    columns[14] = scaled_plane.w();
+    Mat4::from_cols_array(&columns)
+}
+
+/// Places the [ReflectionCamera] at the mirror image of the 3d camera and clips everything below the [ReflectionPlane]
+pub fn reflection_camera_system(
+    active_cameras: Res<ActiveCameras>,
+    mut queries: QuerySet<(
+        Query<(&Camera, &GlobalTransform)>,
+        Query<(&ReflectionPlane, &GlobalTransform)>,
+        Query<With<ReflectionCamera, (&mut Camera, &mut Transform, &mut GlobalTransform)>>,
+    )>,
+) {
+    let (projection_matrix, camera_transform) = if let Some((camera, transform)) = active_cameras
+        .get(camera::CAMERA3D)
+        .and_then(|entity| queries.q0().get(entity).ok())
+    {
+        (camera.projection_matrix, transform.clone())
+    } else {
+        return;
+    };
+
+    let (clip_offset, point, normal) = if let Some((plane, transform)) = queries.q1().iter().next()
+    {
+        (
+            plane.clip_offset,
+            transform.translation,
+            (transform.rotation * Vec3::unit_y()).normalize(),
+        )
+    } else {
+        return;
+    };
+
+    let reflected_transform = reflect_transform(&camera_transform, point, normal);
+    let world_plane = normal.extend(-normal.dot(point + normal * clip_offset));
+    // planes transform with the transpose of the inverse view matrix, which is the camera's transform
+    let view_plane = reflected_transform.compute_matrix().transpose() * world_plane;
+
+    for (mut camera, mut transform, mut global_transform) in queries.q2_mut().iter_mut() {
+        camera.projection_matrix = oblique_projection(projection_matrix, view_plane);
+        *transform = Transform {
+            translation: reflected_transform.translation,
+            rotation: reflected_transform.rotation,
+            scale: reflected_transform.scale,
+        };
+        *global_transform = reflected_transform.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflect_camera_about_plane() {
+        let transform = GlobalTransform::from_translation(Vec3::new(1.0, 5.0, 10.0))
+            .looking_at(Vec3::new(1.0, 0.0, 0.0), Vec3::unit_y());
+        let reflected = reflect_transform(&transform, Vec3::new(0.0, 1.0, 0.0), Vec3::unit_y());
+
+        assert!((reflected.translation - Vec3::new(1.0, -3.0, 10.0)).length() < 1e-5);
+        let forward = reflected.rotation * -Vec3::unit_z();
+        let expected_forward = (Vec3::new(1.0, 2.0, 0.0) - reflected.translation).normalize();
+        assert!((forward - expected_forward).length() < 1e-5);
+        // the reflected camera stays upright
+        assert!((reflected.rotation * Vec3::unit_y()).y() > 0.0);
+    }
+
+    #[test]
+    fn oblique_projection_clips_at_plane() {
+        let projection = Mat4::perspective_rh(std::f32::consts::FRAC_PI_4, 1.0, 0.1, 100.0);
+        // keeps the points above y = -1 in view space
+        let plane = Vec4::new(0.0, 1.0, 0.0, 1.0);
+        let oblique = oblique_projection(projection, plane);
+
+        let on_plane = oblique * Vec4::new(0.5, -1.0, -5.0, 1.0);
+        assert!((on_plane.z() / on_plane.w()).abs() < 1e-4);
+        let above_plane = oblique * Vec4::new(0.5, 1.0, -5.0, 1.0);
+        let depth = above_plane.z() / above_plane.w();
+        assert!(depth > 0.0 && depth < 1.0);
+        let below_plane = oblique * Vec4::new(0.5, -2.0, -5.0, 1.0);
+        assert!(below_plane.z() / below_plane.w() < 0.0);
+    }
+}
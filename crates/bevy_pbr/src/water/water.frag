@@ -0,0 +1,60 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+layout(location = 1) in vec4 v_ClipPosition;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 3, binding = 0) uniform WaterMaterial_color {
+    vec4 Color;
+};
+
+layout(set = 3, binding = 1) uniform WaterMaterial_distortion {
+    float Distortion;
+};
+
+layout(set = 3, binding = 2) uniform WaterMaterial_normal_map_scale {
+    float NormalMapScale;
+};
+
+layout(set = 3, binding = 3) uniform WaterMaterial_time {
+    float Time;
+};
+
+# ifdef WATERMATERIAL_NORMAL_MAP
+layout(set = 3, binding = 4) uniform texture2D WaterMaterial_normal_map;
+layout(set = 3, binding = 5) uniform sampler WaterMaterial_normal_map_sampler;
+# endif
+
+# ifdef WATERMATERIAL_REFLECTION
+layout(set = 3, binding = 6) uniform texture2D WaterMaterial_reflection;
+layout(set = 3, binding = 7) uniform sampler WaterMaterial_reflection_sampler;
+# endif
+
+void main() {
+    vec2 distortion = vec2(0.0);
+# ifdef WATERMATERIAL_NORMAL_MAP
+    // two copies of the normal map scrolling in different directions look less repetitive than one
+    vec2 uv = v_Uv * NormalMapScale;
+    vec2 normal_a = texture(
+        sampler2D(WaterMaterial_normal_map, WaterMaterial_normal_map_sampler),
+        uv + vec2(Time * 0.02, Time * 0.01)).xy;
+    vec2 normal_b = texture(
+        sampler2D(WaterMaterial_normal_map, WaterMaterial_normal_map_sampler),
+        uv * 0.7 + vec2(-Time * 0.015, Time * 0.02)).xy;
+    distortion = (normal_a + normal_b - 1.0) * Distortion;
+# endif
+
+    vec3 color = Color.rgb;
+# ifdef WATERMATERIAL_REFLECTION
+    // the reflection camera is flipped upside down compared to a mirror, so its image is sampled with y pointing up
+    vec2 ndc = v_ClipPosition.xy / v_ClipPosition.w;
+    vec2 reflection_uv = clamp(ndc * 0.5 + 0.5 + distortion, 0.001, 0.999);
+    vec3 reflection = texture(
+        sampler2D(WaterMaterial_reflection, WaterMaterial_reflection_sampler),
+        reflection_uv).rgb;
+    color = mix(reflection, Color.rgb, Color.a);
+# endif
+
+    o_Target = vec4(color, 1.0);
+}
@@ -1,8 +1,9 @@
-use super::CameraProjection;
+use super::{CameraProjection, Ray};
 use bevy_app::prelude::{EventReader, Events};
-use bevy_ecs::{Added, Component, Entity, Local, Query, QuerySet, Res};
-use bevy_math::Mat4;
+use bevy_ecs::{Changed, Component, Entity, Local, Query, QuerySet, Res};
+use bevy_math::{Mat4, Vec2, Vec3};
 use bevy_property::Properties;
+use bevy_transform::components::GlobalTransform;
 use bevy_window::{WindowCreated, WindowId, WindowResized, Windows};
 
 #[derive(Default, Debug, Properties)]
@@ -13,6 +14,169 @@ pub struct Camera {
     pub window: WindowId,
     #[property(ignore)]
     pub depth_calculation: DepthCalculation,
+    /// The part of the render target the camera draws to. `None` uses the whole target.
+    #[property(ignore)]
+    pub viewport: Option<Viewport>,
+    #[property(ignore)]
+    pub transparency: TransparencyMode,
+}
+
+/// How a camera draws transparent entities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransparencyMode {
+    /// Transparent entities are drawn back to front by the passes that draw the opaque ones. Intersecting entities,
+    /// or entities whose order changes, can pop in front of each other.
+    Sorted,
+    /// Transparent entities are drawn in any order by weighted blended order-independent transparency passes, which
+    /// approximate the blended color. Only entities with a pipeline for those passes are drawn.
+    WeightedBlended,
+}
+
+impl Default for TransparencyMode {
+    fn default() -> Self {
+        TransparencyMode::Sorted
+    }
+}
+
+/// A rectangle of a render target, in fractions of the target's size with the origin in the top left corner. Cameras
+/// with different viewports can draw to the same window, for example for split-screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub origin: Vec2,
+    pub size: Vec2,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport {
+            origin: Vec2::zero(),
+            size: Vec2::one(),
+        }
+    }
+}
+
+impl Viewport {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Viewport {
+            origin: Vec2::new(x, y),
+            size: Vec2::new(width, height),
+        }
+    }
+
+    /// The viewport of cell `index` of a grid that splits the target into equally sized cells. Cells are counted
+    /// left to right, then top to bottom.
+    pub fn grid(columns: u32, rows: u32, index: u32) -> Self {
+        let columns = columns.max(1);
+        let rows = rows.max(1);
+        let size = Vec2::new(1.0 / columns as f32, 1.0 / rows as f32);
+        Viewport {
+            origin: Vec2::new(
+                (index % columns) as f32 * size.x(),
+                (index / columns % rows) as f32 * size.y(),
+            ),
+            size,
+        }
+    }
+
+    /// The viewport in pixels of a target that is `width` x `height` pixels large, as x, y, width and height. The
+    /// viewport is clipped to the target.
+    pub fn physical_rect(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let to_pixels = |fraction: f32, size: u32| {
+            (fraction * size as f32).round().max(0.0).min(size as f32) as u32
+        };
+        let left = to_pixels(self.origin.x(), width);
+        let top = to_pixels(self.origin.y(), height);
+        let right = to_pixels(self.origin.x() + self.size.x(), width);
+        let bottom = to_pixels(self.origin.y() + self.size.y(), height);
+        (
+            left,
+            top,
+            right.saturating_sub(left),
+            bottom.saturating_sub(top),
+        )
+    }
+}
+
+impl Camera {
+    /// Projects `world_position` to normalized device coordinates: x and y are -1.0 to 1.0 from the left and bottom
+    /// edge of the camera's viewport to the right and top edge. Returns `None` if the position is behind the camera.
+    pub fn world_to_ndc(
+        &self,
+        camera_transform: &GlobalTransform,
+        world_position: Vec3,
+    ) -> Option<Vec3> {
+        let view_projection = self.projection_matrix * camera_transform.compute_matrix().inverse();
+        let clip = view_projection * world_position.extend(1.0);
+        if clip.w() <= 0.0 {
+            return None;
+        }
+
+        Some(clip.truncate() / clip.w())
+    }
+
+    /// Converts normalized device coordinates to a position in pixels in a window of `window_size`, with the origin
+    /// in the bottom left corner like cursor positions
+    pub fn ndc_to_screen(&self, window_size: Vec2, ndc: Vec2) -> Vec2 {
+        let viewport = self.viewport.unwrap_or_default();
+        // viewports have their origin in the top left corner
+        let x = viewport.origin.x() + (ndc.x() + 1.0) / 2.0 * viewport.size.x();
+        let y = viewport.origin.y() + (1.0 - ndc.y()) / 2.0 * viewport.size.y();
+        Vec2::new(x, 1.0 - y) * window_size
+    }
+
+    /// Projects `world_position` to a position in pixels in the camera's window, with the origin in the bottom left
+    /// corner like cursor positions. Returns `None` if the position is behind the camera or the window doesn't exist.
+    pub fn world_to_screen(
+        &self,
+        windows: &Windows,
+        camera_transform: &GlobalTransform,
+        world_position: Vec3,
+    ) -> Option<Vec2> {
+        let window = windows.get(self.window)?;
+        let window_size = Vec2::new(window.width() as f32, window.height() as f32);
+        let ndc = self.world_to_ndc(camera_transform, world_position)?;
+        Some(self.ndc_to_screen(window_size, ndc.truncate()))
+    }
+
+    /// Converts a position in pixels in a window of `window_size`, with the origin in the bottom left corner, to
+    /// normalized device coordinates of the camera's viewport. The inverse of [Camera::ndc_to_screen].
+    pub fn screen_to_ndc(&self, window_size: Vec2, screen_position: Vec2) -> Vec2 {
+        let viewport = self.viewport.unwrap_or_default();
+        let position = screen_position / window_size;
+        // viewports have their origin in the top left corner
+        let x = (position.x() - viewport.origin.x()) / viewport.size.x();
+        let y = (1.0 - position.y() - viewport.origin.y()) / viewport.size.y();
+        Vec2::new(x * 2.0 - 1.0, 1.0 - y * 2.0)
+    }
+
+    /// The ray from the camera through a position in pixels in the camera's window, with the origin in the bottom left
+    /// corner like cursor positions. Returns `None` if the window doesn't exist.
+    pub fn screen_to_ray(
+        &self,
+        windows: &Windows,
+        camera_transform: &GlobalTransform,
+        screen_position: Vec2,
+    ) -> Option<Ray> {
+        let window = windows.get(self.window)?;
+        let window_size = Vec2::new(window.width() as f32, window.height() as f32);
+        Some(self.ndc_to_ray(
+            camera_transform,
+            self.screen_to_ndc(window_size, screen_position),
+        ))
+    }
+
+    /// The ray from the camera through normalized device coordinates, starting at the near plane
+    pub fn ndc_to_ray(&self, camera_transform: &GlobalTransform, ndc: Vec2) -> Ray {
+        let inverse_view_projection =
+            (self.projection_matrix * camera_transform.compute_matrix().inverse()).inverse();
+        let unproject = |depth: f32| {
+            let position = inverse_view_projection * ndc.extend(depth).extend(1.0);
+            position.truncate() / position.w()
+        };
+        // depth goes from 0.0 at the near plane to 1.0 at the far plane
+        let near = unproject(0.0);
+        Ray::new(near, unproject(1.0) - near)
+    }
 }
 
 #[derive(Debug)]
@@ -40,7 +204,7 @@ pub fn camera_system<T: CameraProjection + Component>(
     windows: Res<Windows>,
     mut queries: QuerySet<(
         Query<(Entity, &mut Camera, &mut T)>,
-        Query<(Entity, Added<Camera>)>,
+        Query<(Entity, Changed<Camera>)>,
     )>,
 ) {
     let mut changed_window_ids = Vec::new();
@@ -70,17 +234,104 @@ pub fn camera_system<T: CameraProjection + Component>(
         changed_window_ids.push(event.id);
     }
 
-    let mut added_cameras = vec![];
+    // also covers added cameras and cameras whose viewport changed
+    let mut changed_cameras = vec![];
     for (entity, _camera) in &mut queries.q1().iter() {
-        added_cameras.push(entity);
+        changed_cameras.push(entity);
     }
     for (entity, mut camera, mut camera_projection) in queries.q0_mut().iter_mut() {
         if let Some(window) = windows.get(camera.window) {
-            if changed_window_ids.contains(&window.id()) || added_cameras.contains(&entity) {
-                camera_projection.update(window.width() as usize, window.height() as usize);
+            if changed_window_ids.contains(&window.id()) || changed_cameras.contains(&entity) {
+                let (width, height) = match camera.viewport {
+                    Some(viewport) => {
+                        let (_x, _y, width, height) =
+                            viewport.physical_rect(window.width(), window.height());
+                        (width.max(1), height.max(1))
+                    }
+                    None => (window.width(), window.height()),
+                };
+                camera_projection.update(width as usize, height as usize);
                 camera.projection_matrix = camera_projection.get_projection_matrix();
                 camera.depth_calculation = camera_projection.depth_calculation();
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_physical_rect() {
+        assert_eq!(
+            Viewport::default().physical_rect(800, 600),
+            (0, 0, 800, 600)
+        );
+        assert_eq!(
+            Viewport::grid(2, 1, 1).physical_rect(801, 600),
+            (401, 0, 400, 600)
+        );
+        assert_eq!(
+            Viewport::grid(2, 2, 2).physical_rect(800, 600),
+            (0, 300, 400, 300)
+        );
+        // viewports are clipped to the target
+        assert_eq!(
+            Viewport::new(0.75, -0.5, 0.5, 1.0).physical_rect(800, 600),
+            (600, 0, 200, 300)
+        );
+    }
+
+    #[test]
+    fn camera_ndc_to_screen() {
+        let window_size = Vec2::new(800.0, 600.0);
+        let camera = Camera::default();
+        assert_eq!(
+            camera.ndc_to_screen(window_size, Vec2::new(-1.0, -1.0)),
+            Vec2::zero()
+        );
+        assert_eq!(
+            camera.ndc_to_screen(window_size, Vec2::zero()),
+            Vec2::new(400.0, 300.0)
+        );
+
+        // the right half of the window
+        let camera = Camera {
+            viewport: Some(Viewport::grid(2, 1, 1)),
+            ..Default::default()
+        };
+        assert_eq!(
+            camera.ndc_to_screen(window_size, Vec2::new(1.0, 1.0)),
+            window_size
+        );
+        assert_eq!(
+            camera.ndc_to_screen(window_size, Vec2::new(-1.0, 0.0)),
+            Vec2::new(400.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn camera_screen_to_ray() {
+        let window_size = Vec2::new(800.0, 600.0);
+        let camera = Camera {
+            projection_matrix: Mat4::perspective_rh(
+                std::f32::consts::FRAC_PI_2,
+                4.0 / 3.0,
+                1.0,
+                100.0,
+            ),
+            viewport: Some(Viewport::grid(2, 1, 1)),
+            ..Default::default()
+        };
+        let screen_position = Vec2::new(500.0, 150.0);
+        let ndc = camera.screen_to_ndc(window_size, screen_position);
+        assert!((camera.ndc_to_screen(window_size, ndc) - screen_position).length() < 1e-4);
+
+        // the center of the viewport looks straight ahead
+        let camera_transform = GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 5.0));
+        let ray = camera.ndc_to_ray(&camera_transform, Vec2::zero());
+        assert!((ray.origin - Vec3::new(0.0, 0.0, 4.0)).length() < 1e-4);
+        assert!((ray.direction + Vec3::unit_z()).length() < 1e-4);
+    }
+}
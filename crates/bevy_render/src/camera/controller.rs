@@ -0,0 +1,422 @@
+use bevy_app::prelude::*;
+use bevy_core::Time;
+use bevy_ecs::{Entity, IntoQuerySystem, Local, Query, Res, ResMut};
+use bevy_input::{
+    keyboard::KeyCode,
+    mouse::{MouseButton, MouseMotion, MouseScrollUnit, MouseWheel},
+    Input,
+};
+use bevy_math::{Quat, Vec2, Vec3};
+use bevy_transform::components::{GlobalTransform, Transform};
+use bevy_window::Windows;
+
+/// The number of pixels of [MouseWheel] scrolling that count as one "line" of scrolling
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Keeps pitch angles just short of straight up and down, where yaw becomes ambiguous
+const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+/// Adds systems for the [OrbitCamera], [FlyCamera] and [FollowCamera] controllers. Controllers only affect entities
+/// that have their component.
+#[derive(Default)]
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system(orbit_camera_system.system())
+            .add_system(fly_camera_system.system())
+            .add_system(follow_camera_system.system());
+    }
+}
+
+/// Rotates around, pans and zooms towards a `focus` point. Drag with `rotate_button` to rotate, drag with `pan_button`
+/// to move the focus and scroll to zoom.
+#[derive(Debug, Clone)]
+pub struct OrbitCamera {
+    pub focus: Vec3,
+    pub radius: f32,
+    /// Rotation around the Y axis, in radians
+    pub yaw: f32,
+    /// Rotation above (positive) or below (negative) the focus, in radians
+    pub pitch: f32,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    pub rotate_button: MouseButton,
+    pub pan_button: MouseButton,
+    /// Radians rotated per pixel of mouse motion
+    pub rotate_sensitivity: f32,
+    /// Distance panned per pixel of mouse motion, relative to `radius`
+    pub pan_sensitivity: f32,
+    /// How much `radius` changes per line of scrolling, relative to `radius`
+    pub zoom_sensitivity: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        OrbitCamera {
+            focus: Vec3::zero(),
+            radius: 10.0,
+            yaw: 0.0,
+            pitch: 0.5,
+            min_radius: 0.5,
+            max_radius: 1000.0,
+            rotate_button: MouseButton::Left,
+            pan_button: MouseButton::Middle,
+            rotate_sensitivity: 0.005,
+            pan_sensitivity: 0.002,
+            zoom_sensitivity: 0.1,
+        }
+    }
+}
+
+impl OrbitCamera {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(-self.pitch)
+    }
+
+    /// Returns the camera transform for the current focus, radius and rotation
+    pub fn transform(&self) -> Transform {
+        let rotation = self.rotation();
+        Transform {
+            translation: self.focus + rotation * Vec3::new(0.0, 0.0, self.radius),
+            rotation,
+            ..Default::default()
+        }
+    }
+}
+
+/// Moves with WASD (plus Space and Left Shift for up and down) and looks around with the mouse. If `grab_cursor` is
+/// set, clicking the window grabs the cursor and Escape releases it. Mouse look is only active while the cursor is
+/// grabbed.
+#[derive(Debug, Clone)]
+pub struct FlyCamera {
+    /// Distance moved per second
+    pub speed: f32,
+    /// Radians rotated per unit of raw mouse motion
+    pub sensitivity: f32,
+    /// Rotation around the Y axis, in radians
+    pub yaw: f32,
+    /// Rotation up (positive) or down (negative), in radians
+    pub pitch: f32,
+    pub grab_cursor: bool,
+    pub key_forward: KeyCode,
+    pub key_back: KeyCode,
+    pub key_left: KeyCode,
+    pub key_right: KeyCode,
+    pub key_up: KeyCode,
+    pub key_down: KeyCode,
+}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        FlyCamera {
+            speed: 10.0,
+            sensitivity: 0.003,
+            yaw: 0.0,
+            pitch: 0.0,
+            grab_cursor: true,
+            key_forward: KeyCode::W,
+            key_back: KeyCode::S,
+            key_left: KeyCode::A,
+            key_right: KeyCode::D,
+            key_up: KeyCode::Space,
+            key_down: KeyCode::LShift,
+        }
+    }
+}
+
+impl FlyCamera {
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(self.pitch)
+    }
+}
+
+/// Smoothly follows the `target` entity from `offset` (in the target's local space) while looking at `look_offset`.
+/// If a [CameraCollider] is between the target and the camera, the camera zooms in to stay in front of it.
+#[derive(Debug, Clone)]
+pub struct FollowCamera {
+    pub target: Option<Entity>,
+    pub offset: Vec3,
+    pub look_offset: Vec3,
+    /// How quickly the camera catches up with the target. Higher values follow more tightly.
+    pub smoothness: f32,
+    /// The distance kept between the camera and colliders in front of it
+    pub collision_margin: f32,
+    /// The closest the camera gets to the look target when zooming in to avoid colliders
+    pub min_distance: f32,
+}
+
+impl Default for FollowCamera {
+    fn default() -> Self {
+        FollowCamera {
+            target: None,
+            offset: Vec3::new(0.0, 3.0, 8.0),
+            look_offset: Vec3::new(0.0, 1.0, 0.0),
+            smoothness: 8.0,
+            collision_margin: 0.2,
+            min_distance: 1.0,
+        }
+    }
+}
+
+impl FollowCamera {
+    pub fn new(target: Entity) -> Self {
+        FollowCamera {
+            target: Some(target),
+            ..Default::default()
+        }
+    }
+}
+
+/// A sphere that blocks the view of [FollowCamera]s. The sphere is centered on the entity's [GlobalTransform].
+#[derive(Debug, Clone)]
+pub struct CameraCollider {
+    pub radius: f32,
+}
+
+/// Returns the distance along the ray to the first intersection with the sphere, if there is one. `direction` must be
+/// normalized.
+pub fn ray_sphere_intersection(
+    origin: Vec3,
+    direction: Vec3,
+    center: Vec3,
+    radius: f32,
+) -> Option<f32> {
+    let to_origin = origin - center;
+    let b = to_origin.dot(direction);
+    let c = to_origin.dot(to_origin) - radius * radius;
+    // the ray starts outside of the sphere and points away from it
+    if c > 0.0 && b > 0.0 {
+        return None;
+    }
+
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    Some((-b - discriminant.sqrt()).max(0.0))
+}
+
+#[derive(Default)]
+pub struct CameraControllerState {
+    mouse_motion_event_reader: EventReader<MouseMotion>,
+    mouse_wheel_event_reader: EventReader<MouseWheel>,
+}
+
+impl CameraControllerState {
+    fn mouse_motion(&mut self, mouse_motion_events: &Events<MouseMotion>) -> Vec2 {
+        self.mouse_motion_event_reader
+            .iter(mouse_motion_events)
+            .fold(Vec2::zero(), |motion, event| motion + event.delta)
+    }
+
+    fn scroll_lines(&mut self, mouse_wheel_events: &Events<MouseWheel>) -> f32 {
+        self.mouse_wheel_event_reader
+            .iter(mouse_wheel_events)
+            .map(|event| match event.unit {
+                MouseScrollUnit::Line => event.y,
+                MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+            })
+            .sum()
+    }
+}
+
+pub fn orbit_camera_system(
+    mut state: Local<CameraControllerState>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    mouse_motion_events: Res<Events<MouseMotion>>,
+    mouse_wheel_events: Res<Events<MouseWheel>>,
+    mut query: Query<(&mut OrbitCamera, &mut Transform)>,
+) {
+    let motion = state.mouse_motion(&mouse_motion_events);
+    let scroll = state.scroll_lines(&mouse_wheel_events);
+
+    for (mut orbit_camera, mut transform) in query.iter_mut() {
+        if mouse_button_input.pressed(orbit_camera.rotate_button) {
+            orbit_camera.yaw -= motion.x() * orbit_camera.rotate_sensitivity;
+            orbit_camera.pitch = (orbit_camera.pitch
+                + motion.y() * orbit_camera.rotate_sensitivity)
+                .max(-MAX_PITCH)
+                .min(MAX_PITCH);
+        } else if mouse_button_input.pressed(orbit_camera.pan_button) {
+            let rotation = orbit_camera.rotation();
+            let scale = orbit_camera.radius * orbit_camera.pan_sensitivity;
+            let right = rotation * Vec3::unit_x();
+            let up = rotation * Vec3::unit_y();
+            orbit_camera.focus += (up * motion.y() - right * motion.x()) * scale;
+        }
+
+        if scroll != 0.0 {
+            let radius = orbit_camera.radius * (1.0 - scroll * orbit_camera.zoom_sensitivity);
+            orbit_camera.radius = radius
+                .max(orbit_camera.min_radius)
+                .min(orbit_camera.max_radius);
+        }
+
+        *transform = Transform {
+            scale: transform.scale,
+            ..orbit_camera.transform()
+        };
+    }
+}
+
+pub fn fly_camera_system(
+    mut state: Local<CameraControllerState>,
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    mouse_motion_events: Res<Events<MouseMotion>>,
+    mut windows: ResMut<Windows>,
+    mut query: Query<(&mut FlyCamera, &mut Transform)>,
+) {
+    let motion = state.mouse_motion(&mouse_motion_events);
+    let window = if let Some(window) = windows.get_primary_mut() {
+        window
+    } else {
+        return;
+    };
+
+    for (mut fly_camera, mut transform) in query.iter_mut() {
+        if fly_camera.grab_cursor {
+            if mouse_button_input.just_pressed(MouseButton::Left) && !window.cursor_locked() {
+                window.set_cursor_lock_mode(true);
+                window.set_cursor_visibility(false);
+            } else if keyboard_input.just_pressed(KeyCode::Escape) && window.cursor_locked() {
+                window.set_cursor_lock_mode(false);
+                window.set_cursor_visibility(true);
+            }
+        }
+
+        if !fly_camera.grab_cursor || window.cursor_locked() {
+            fly_camera.yaw -= motion.x() * fly_camera.sensitivity;
+            fly_camera.pitch = (fly_camera.pitch - motion.y() * fly_camera.sensitivity)
+                .max(-MAX_PITCH)
+                .min(MAX_PITCH);
+        }
+
+        let rotation = fly_camera.rotation();
+        let mut direction = Vec3::zero();
+        let axes = [
+            (fly_camera.key_forward, rotation * -Vec3::unit_z()),
+            (fly_camera.key_back, rotation * Vec3::unit_z()),
+            (fly_camera.key_left, rotation * -Vec3::unit_x()),
+            (fly_camera.key_right, rotation * Vec3::unit_x()),
+            (fly_camera.key_up, Vec3::unit_y()),
+            (fly_camera.key_down, -Vec3::unit_y()),
+        ];
+        for (key, axis) in axes.iter() {
+            if keyboard_input.pressed(*key) {
+                direction += *axis;
+            }
+        }
+
+        if direction.length_squared() > 0.0 {
+            transform.translation +=
+                direction.normalize() * fly_camera.speed * time.raw_delta_seconds;
+        }
+        transform.rotation = rotation;
+    }
+}
+
+/// Moves [FollowCamera]s. Targets are followed at the position of their last [GlobalTransform] update.
+pub fn follow_camera_system(
+    time: Res<Time>,
+    mut camera_query: Query<(&FollowCamera, &mut Transform)>,
+    target_query: Query<&GlobalTransform>,
+    collider_query: Query<(Entity, &CameraCollider, &GlobalTransform)>,
+) {
+    for (follow_camera, mut transform) in camera_query.iter_mut() {
+        let target = if let Some(target) = follow_camera.target {
+            target
+        } else {
+            continue;
+        };
+        let target_transform = if let Ok(target_transform) = target_query.get(target) {
+            target_transform
+        } else {
+            continue;
+        };
+
+        let look_at =
+            target_transform.translation + target_transform.rotation * follow_camera.look_offset;
+        let desired =
+            target_transform.translation + target_transform.rotation * follow_camera.offset;
+
+        // smooth towards the desired position independently of the frame rate
+        let t = 1.0 - (-follow_camera.smoothness * time.delta_seconds).exp();
+        let mut position = transform.translation.lerp(desired, t.max(0.0).min(1.0));
+
+        // zoom in immediately if something blocks the view, so the camera never ends up inside of a collider
+        let to_camera = position - look_at;
+        let distance = to_camera.length();
+        if distance > std::f32::EPSILON {
+            let direction = to_camera / distance;
+            let blocked_distance = collider_query
+                .iter()
+                .filter(|(entity, _, _)| *entity != target)
+                .filter_map(|(_, collider, global_transform)| {
+                    ray_sphere_intersection(
+                        look_at,
+                        direction,
+                        global_transform.translation,
+                        collider.radius,
+                    )
+                })
+                .filter(|hit| *hit < distance)
+                .fold(distance, f32::min);
+            if blocked_distance < distance {
+                let zoomed_distance = (blocked_distance - follow_camera.collision_margin)
+                    .max(follow_camera.min_distance.min(distance));
+                position = look_at + direction * zoomed_distance;
+            }
+        }
+
+        transform.translation = position;
+        transform.look_at(look_at, Vec3::unit_y());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_sphere() {
+        let center = Vec3::new(0.0, 0.0, -10.0);
+        let hit = ray_sphere_intersection(Vec3::zero(), -Vec3::unit_z(), center, 2.0);
+        assert_eq!(hit, Some(8.0));
+        assert_eq!(
+            ray_sphere_intersection(Vec3::zero(), Vec3::unit_z(), center, 2.0),
+            None
+        );
+        assert_eq!(
+            ray_sphere_intersection(Vec3::new(5.0, 0.0, 0.0), -Vec3::unit_z(), center, 2.0),
+            None
+        );
+        // rays that start inside of the sphere hit it immediately
+        assert_eq!(
+            ray_sphere_intersection(center, Vec3::unit_x(), center, 2.0),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn orbit_camera_looks_at_focus() {
+        let orbit_camera = OrbitCamera {
+            focus: Vec3::new(1.0, 2.0, 3.0),
+            yaw: 0.7,
+            pitch: 0.3,
+            ..Default::default()
+        };
+        let transform = orbit_camera.transform();
+        let to_focus = (orbit_camera.focus - transform.translation).normalize();
+        let looking = transform.rotation * -Vec3::unit_z();
+        assert!((to_focus - looking).length() < 1e-4);
+        assert!(
+            ((transform.translation - orbit_camera.focus).length() - orbit_camera.radius).abs()
+                < 1e-4
+        );
+        assert!(transform.translation.y() > orbit_camera.focus.y());
+    }
+}
@@ -1,10 +1,20 @@
 mod active_cameras;
 #[allow(clippy::module_inception)]
 mod camera;
+mod controller;
 mod projection;
+mod ray;
+mod render_layers;
+mod shake;
 mod visible_entities;
+mod zone;
 
 pub use active_cameras::*;
 pub use camera::*;
+pub use controller::*;
 pub use projection::*;
+pub use ray::*;
+pub use render_layers::*;
+pub use shake::*;
 pub use visible_entities::*;
+pub use zone::*;
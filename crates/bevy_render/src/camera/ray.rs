@@ -0,0 +1,109 @@
+use bevy_math::Vec3;
+
+/// A half-line, for example the line under the cursor from [Camera::screen_to_ray](super::Camera::screen_to_ray)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    /// Normalized
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// Creates a ray, normalizing `direction`
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Ray {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// The point `distance` along the ray
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// The distance along the ray to the plane through `point` with `normal`. `None` if the ray is parallel to the
+    /// plane or points away from it.
+    pub fn intersect_plane(&self, point: Vec3, normal: Vec3) -> Option<f32> {
+        let denominator = self.direction.dot(normal);
+        if denominator.abs() <= std::f32::EPSILON {
+            return None;
+        }
+
+        let distance = (point - self.origin).dot(normal) / denominator;
+        if distance >= 0.0 {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+
+    /// The distance along the ray to where it enters the sphere around `center`, or 0 if it starts inside. `None` if
+    /// it misses the sphere or points away from it.
+    pub fn intersect_sphere(&self, center: Vec3, radius: f32) -> Option<f32> {
+        let offset = self.origin - center;
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - radius * radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+
+        let discriminant = b * b - c;
+        if b > 0.0 || discriminant < 0.0 {
+            return None;
+        }
+
+        Some(-b - discriminant.sqrt())
+    }
+
+    /// The distances along the ray and along the line through `line_origin` with `line_direction` of the points
+    /// where the two are closest to each other. `line_direction` must be normalized. `None` if they are parallel.
+    pub fn closest_to_line(&self, line_origin: Vec3, line_direction: Vec3) -> Option<(f32, f32)> {
+        let offset = self.origin - line_origin;
+        let b = self.direction.dot(line_direction);
+        let denominator = 1.0 - b * b;
+        if denominator <= std::f32::EPSILON {
+            return None;
+        }
+
+        let d = self.direction.dot(offset);
+        let e = line_direction.dot(offset);
+        let ray_distance = ((b * e - d) / denominator).max(0.0);
+        // the closest point on the line to the clamped point on the ray
+        let line_distance = (self.at(ray_distance) - line_origin).dot(line_direction);
+        Some((ray_distance, line_distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_intersections() {
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        assert_eq!(ray.direction, -Vec3::unit_z());
+        assert_eq!(ray.intersect_plane(Vec3::zero(), Vec3::unit_z()), Some(5.0));
+        assert_eq!(ray.intersect_plane(Vec3::zero(), Vec3::unit_x()), None);
+        assert_eq!(
+            ray.intersect_plane(Vec3::new(0.0, 0.0, 10.0), Vec3::unit_z()),
+            None
+        );
+
+        let ray = Ray::new(Vec3::new(2.0, 1.0, 5.0), -Vec3::unit_z());
+        assert_eq!(
+            ray.closest_to_line(Vec3::zero(), Vec3::unit_x()),
+            Some((5.0, 2.0))
+        );
+        assert_eq!(ray.closest_to_line(Vec3::zero(), Vec3::unit_z()), None);
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), -Vec3::unit_z());
+        assert_eq!(ray.intersect_sphere(Vec3::zero(), 1.0), Some(4.0));
+        assert_eq!(
+            ray.intersect_sphere(Vec3::new(0.0, 0.0, 5.5), 1.0),
+            Some(0.0)
+        );
+        assert_eq!(ray.intersect_sphere(Vec3::new(2.0, 0.0, 0.0), 1.0), None);
+        assert_eq!(ray.intersect_sphere(Vec3::new(0.0, 0.0, 8.0), 1.0), None);
+    }
+}
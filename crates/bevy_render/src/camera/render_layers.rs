@@ -0,0 +1,76 @@
+use bevy_property::Properties;
+
+/// The layers an entity is drawn on, or the layers a camera draws. A camera only draws entities that share at least
+/// one layer with it. Entities and cameras without this component are on layer 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Properties)]
+pub struct RenderLayers {
+    mask: u32,
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        RenderLayers::layer(0)
+    }
+}
+
+impl RenderLayers {
+    /// The number of available layers
+    pub const TOTAL_LAYERS: u8 = 32;
+
+    /// Only the given layer
+    pub fn layer(layer: u8) -> Self {
+        RenderLayers { mask: 0 }.with(layer)
+    }
+
+    /// Every layer
+    pub fn all() -> Self {
+        RenderLayers { mask: u32::MAX }
+    }
+
+    /// Adds the given layer
+    pub fn with(mut self, layer: u8) -> Self {
+        assert!(
+            layer < Self::TOTAL_LAYERS,
+            "render layer {} is out of range",
+            layer
+        );
+        self.mask |= 1 << layer;
+        self
+    }
+
+    /// Removes the given layer
+    pub fn without(mut self, layer: u8) -> Self {
+        assert!(
+            layer < Self::TOTAL_LAYERS,
+            "render layer {} is out of range",
+            layer
+        );
+        self.mask &= !(1 << layer);
+        self
+    }
+
+    pub fn contains(&self, layer: u8) -> bool {
+        layer < Self::TOTAL_LAYERS && self.mask & (1 << layer) != 0
+    }
+
+    /// Whether the two share at least one layer
+    pub fn intersects(&self, other: &RenderLayers) -> bool {
+        self.mask & other.mask != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_layers_intersect() {
+        let default = RenderLayers::default();
+        let minimap = RenderLayers::layer(1).with(2);
+        assert!(default.contains(0));
+        assert!(!default.intersects(&minimap));
+        assert!(minimap.intersects(&RenderLayers::layer(2)));
+        assert!(!minimap.without(2).intersects(&RenderLayers::layer(2)));
+        assert!(RenderLayers::all().intersects(&minimap));
+    }
+}
@@ -0,0 +1,169 @@
+use bevy_app::prelude::{EventReader, Events};
+use bevy_core::Time;
+use bevy_ecs::{Entity, Local, Query, Res};
+use bevy_math::{Quat, Vec3};
+use bevy_property::Properties;
+use bevy_transform::components::GlobalTransform;
+
+/// Shakes a camera by an amount that grows with its `trauma`. Gameplay adds trauma with [CameraShake::add_trauma] or
+/// by sending a [CameraShakeEvent], and it decays over time. The shake moves and rotates the camera's
+/// [GlobalTransform] after transforms are propagated, so it doesn't accumulate in the camera's [Transform] and
+/// doesn't move the camera's children.
+///
+/// [Transform]: bevy_transform::components::Transform
+#[derive(Debug, Clone, Properties)]
+pub struct CameraShake {
+    /// From 0.0 (no shake) to 1.0. The shake grows with the square of the trauma, so small amounts barely show.
+    pub trauma: f32,
+    /// Trauma lost per second
+    pub decay: f32,
+    /// The largest offset along the camera's local axes
+    pub max_translation: Vec3,
+    /// The largest rotation around the camera's local x (pitch), y (yaw) and z (roll) axes, in radians
+    pub max_rotation: Vec3,
+    /// How many times per second the shake changes direction, roughly
+    pub frequency: f32,
+    /// How far the shake has advanced, in seconds. Cameras that start at different times shake differently.
+    pub noise_time: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        CameraShake {
+            trauma: 0.0,
+            decay: 0.8,
+            max_translation: Vec3::new(0.3, 0.3, 0.0),
+            max_rotation: Vec3::new(0.05, 0.05, 0.1),
+            frequency: 15.0,
+            noise_time: 0.0,
+        }
+    }
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).max(0.0).min(1.0);
+    }
+
+    /// How strongly the camera shakes, from 0.0 to 1.0
+    pub fn intensity(&self) -> f32 {
+        self.trauma * self.trauma
+    }
+
+    /// The offset and rotation the camera is shaken by right now, in the camera's local space
+    pub fn offset(&self) -> (Vec3, Quat) {
+        let intensity = self.intensity();
+        if intensity == 0.0 {
+            return (Vec3::zero(), Quat::identity());
+        }
+
+        let channel =
+            |index: u32| perlin_noise(self.noise_time * self.frequency, index) * intensity;
+        let translation = self.max_translation * Vec3::new(channel(0), channel(1), channel(2));
+        let rotation = self.max_rotation * Vec3::new(channel(3), channel(4), channel(5));
+        (
+            translation,
+            Quat::from_rotation_y(rotation.y())
+                * Quat::from_rotation_x(rotation.x())
+                * Quat::from_rotation_z(rotation.z()),
+        )
+    }
+
+    /// Decays the trauma and moves the shake forward by `seconds`
+    pub fn advance(&mut self, seconds: f32) {
+        self.trauma = (self.trauma - self.decay * seconds).max(0.0);
+        self.noise_time += seconds;
+    }
+}
+
+/// Adds trauma to the [CameraShake] of `camera`, or of every camera with one if `camera` is `None`
+#[derive(Debug, Clone, Copy)]
+pub struct CameraShakeEvent {
+    pub camera: Option<Entity>,
+    pub trauma: f32,
+}
+
+impl CameraShakeEvent {
+    pub fn all(trauma: f32) -> Self {
+        CameraShakeEvent {
+            camera: None,
+            trauma,
+        }
+    }
+}
+
+/// Smooth noise from -1.0 to 1.0 that is 0.0 at whole numbers. Each `channel` is an unrelated noise.
+pub fn perlin_noise(x: f32, channel: u32) -> f32 {
+    fn gradient(cell: i32, channel: u32) -> f32 {
+        let mut hash = (cell as u32).wrapping_mul(0x9e37_79b9) ^ channel.wrapping_mul(0x85eb_ca6b);
+        hash ^= hash >> 16;
+        hash = hash.wrapping_mul(0x7feb_352d);
+        hash ^= hash >> 15;
+        (hash as f32 / std::u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    let cell = x.floor();
+    let t = x - cell;
+    let cell = cell as i32;
+    let start = gradient(cell, channel) * t;
+    let end = gradient(cell.wrapping_add(1), channel) * (t - 1.0);
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    // one dimensional perlin noise stays within -0.5 and 0.5
+    (start + (end - start) * fade) * 2.0
+}
+
+/// Applies [CameraShakeEvent]s and shakes the [GlobalTransform] of cameras with a [CameraShake]. This runs after
+/// transform propagation, before the view matrices of cameras are computed.
+pub fn camera_shake_system(
+    mut event_reader: Local<EventReader<CameraShakeEvent>>,
+    events: Res<Events<CameraShakeEvent>>,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut CameraShake, &mut GlobalTransform)>,
+) {
+    let events = event_reader.iter(&events).collect::<Vec<_>>();
+    for (entity, mut shake, mut global_transform) in query.iter_mut() {
+        for event in events.iter() {
+            if event.camera.map_or(true, |camera| camera == entity) {
+                shake.add_trauma(event.trauma);
+            }
+        }
+
+        shake.advance(time.delta_seconds);
+        let (translation, rotation) = shake.offset();
+        let camera_rotation = global_transform.rotation;
+        global_transform.translation += camera_rotation * translation;
+        global_transform.rotation = camera_rotation * rotation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_shake_decays() {
+        for i in 0..100 {
+            let x = i as f32 * 0.37;
+            let noise = perlin_noise(x, 3);
+            assert!((-1.0..=1.0).contains(&noise));
+        }
+        assert_eq!(perlin_noise(4.0, 1), 0.0);
+
+        let mut shake = CameraShake {
+            noise_time: 0.1,
+            ..Default::default()
+        };
+        assert_eq!(shake.offset(), (Vec3::zero(), Quat::identity()));
+        shake.add_trauma(0.5);
+        shake.add_trauma(0.7);
+        assert_eq!(shake.trauma, 1.0);
+        assert_eq!(shake.intensity(), 1.0);
+        assert_ne!(shake.offset().0, Vec3::zero());
+
+        shake.advance(0.5);
+        assert!((shake.trauma - 0.6).abs() < 1e-6);
+        assert!((shake.intensity() - 0.36).abs() < 1e-6);
+        shake.advance(1.0);
+        assert_eq!(shake.trauma, 0.0);
+    }
+}
@@ -1,7 +1,7 @@
-use super::{Camera, DepthCalculation};
+use super::{Camera, DepthCalculation, InZone, RenderLayers, ZoneVisibility};
 use crate::Draw;
 use bevy_core::FloatOrd;
-use bevy_ecs::{Entity, Query, With};
+use bevy_ecs::{Entity, Query, Res, With};
 use bevy_property::Properties;
 use bevy_transform::prelude::GlobalTransform;
 
@@ -24,21 +24,42 @@ impl VisibleEntities {
 }
 
 pub fn visible_entities_system(
-    mut camera_query: Query<(&Camera, &GlobalTransform, &mut VisibleEntities)>,
-    draw_query: Query<(Entity, &Draw)>,
+    zone_visibility: Res<ZoneVisibility>,
+    mut camera_query: Query<(
+        Entity,
+        &Camera,
+        &GlobalTransform,
+        Option<&RenderLayers>,
+        &mut VisibleEntities,
+    )>,
+    draw_query: Query<(Entity, &Draw, Option<&RenderLayers>)>,
     draw_transform_query: Query<With<Draw, &GlobalTransform>>,
+    draw_zone_query: Query<With<Draw, &InZone>>,
 ) {
-    for (camera, camera_global_transform, mut visible_entities) in camera_query.iter_mut() {
+    for (camera_entity, camera, camera_global_transform, camera_layers, mut visible_entities) in
+        camera_query.iter_mut()
+    {
+        let camera_layers = camera_layers.cloned().unwrap_or_default();
         visible_entities.value.clear();
         let camera_position = camera_global_transform.translation;
 
         let mut no_transform_order = 0.0;
         let mut transparent_entities = Vec::new();
-        for (entity, draw) in draw_query.iter() {
+        for (entity, draw, layers) in draw_query.iter() {
             if !draw.is_visible {
                 continue;
             }
 
+            if !camera_layers.intersects(&layers.cloned().unwrap_or_default()) {
+                continue;
+            }
+
+            if let Ok(in_zone) = draw_zone_query.get(entity) {
+                if !zone_visibility.is_visible(camera_entity, in_zone.0) {
+                    continue;
+                }
+            }
+
             let order = if let Ok(global_transform) = draw_transform_query.get(entity) {
                 let position = global_transform.translation;
                 // smaller distances are sorted to lower indices by using the distance from the camera
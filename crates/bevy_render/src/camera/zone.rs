@@ -0,0 +1,304 @@
+use super::Camera;
+use bevy_ecs::{Entity, Query, ResMut};
+use bevy_math::{Mat4, Vec2, Vec3, Vec4};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_utils::{HashMap, HashSet};
+
+/// A room that is only drawn when the camera is inside it or can see into it through a chain of [Portal]s. The room is
+/// the box of `half_extents` around the zone entity's transform.
+///
+/// Put [InZone] on the entities that belong to the room. Cameras outside of every zone draw everything.
+#[derive(Debug, Clone, Copy)]
+pub struct Zone {
+    pub half_extents: Vec3,
+}
+
+impl Zone {
+    pub fn contains(&self, transform: &GlobalTransform, point: Vec3) -> bool {
+        let local = transform.compute_matrix().inverse().transform_point3(point);
+        local.x().abs() <= self.half_extents.x()
+            && local.y().abs() <= self.half_extents.y()
+            && local.z().abs() <= self.half_extents.z()
+    }
+}
+
+/// An opening between two [Zone]s, like a doorway or a stairwell. The opening is a `size` rectangle in the XY plane of
+/// the portal entity's transform.
+#[derive(Debug, Clone, Copy)]
+pub struct Portal {
+    pub zones: [Entity; 2],
+    pub size: Vec2,
+    /// Closed portals, like shut doors, block the view into the zone behind them
+    pub open: bool,
+}
+
+impl Portal {
+    pub fn new(zones: [Entity; 2], size: Vec2) -> Self {
+        Portal {
+            zones,
+            size,
+            open: true,
+        }
+    }
+
+    fn corners(&self, transform: &GlobalTransform) -> [Vec3; 4] {
+        let half_size = self.size / 2.0;
+        let corner = |x: f32, y: f32| {
+            transform.mul_vec3(Vec3::new(x * half_size.x(), y * half_size.y(), 0.0))
+        };
+        [
+            corner(-1.0, -1.0),
+            corner(1.0, -1.0),
+            corner(1.0, 1.0),
+            corner(-1.0, 1.0),
+        ]
+    }
+}
+
+/// The [Zone] an entity belongs to
+#[derive(Debug, Clone, Copy)]
+pub struct InZone(pub Entity);
+
+/// The zones each camera can see, updated by [zone_visibility_system]. Cameras that aren't inside a zone are missing.
+#[derive(Debug, Default)]
+pub struct ZoneVisibility {
+    pub visible_zones: HashMap<Entity, HashSet<Entity>>,
+}
+
+impl ZoneVisibility {
+    /// Whether `camera` can see the entities of `zone`
+    pub fn is_visible(&self, camera: Entity, zone: Entity) -> bool {
+        self.visible_zones
+            .get(&camera)
+            .map_or(true, |zones| zones.contains(&zone))
+    }
+}
+
+/// A rectangle in normalized device coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScreenRect {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl ScreenRect {
+    fn full() -> ScreenRect {
+        ScreenRect {
+            min: Vec2::splat(-1.0),
+            max: Vec2::splat(1.0),
+        }
+    }
+
+    fn intersection(&self, other: &ScreenRect) -> Option<ScreenRect> {
+        let min = self.min.max(other.min);
+        let max = self.max.min(other.max);
+        if min.x() < max.x() && min.y() < max.y() {
+            Some(ScreenRect { min, max })
+        } else {
+            None
+        }
+    }
+
+    fn union(&self, other: &ScreenRect) -> ScreenRect {
+        ScreenRect {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    fn contains(&self, other: &ScreenRect) -> bool {
+        self.min.x() <= other.min.x()
+            && self.min.y() <= other.min.y()
+            && self.max.x() >= other.max.x()
+            && self.max.y() >= other.max.y()
+    }
+}
+
+/// The part of the screen covered by the portal's corners, or `None` if the portal is entirely behind the camera. Portals
+/// that cross the camera plane can't be projected, so they conservatively cover the whole screen.
+fn project_portal(view_projection: Mat4, corners: &[Vec3; 4]) -> Option<ScreenRect> {
+    let clip_corners = corners
+        .iter()
+        .map(|corner| view_projection * Vec4::new(corner.x(), corner.y(), corner.z(), 1.0))
+        .collect::<Vec<_>>();
+    let in_front = clip_corners
+        .iter()
+        .filter(|corner| corner.w() > 0.0)
+        .count();
+    if in_front == 0 {
+        return None;
+    } else if in_front < clip_corners.len() {
+        return Some(ScreenRect::full());
+    }
+
+    let mut rect = ScreenRect {
+        min: Vec2::splat(f32::MAX),
+        max: Vec2::splat(f32::MIN),
+    };
+    for corner in clip_corners.iter() {
+        let ndc = Vec2::new(corner.x(), corner.y()) / corner.w();
+        rect.min = rect.min.min(ndc);
+        rect.max = rect.max.max(ndc);
+    }
+    Some(rect)
+}
+
+/// Finds the zones visible from a camera at `camera_position` by walking from the camera's zone through every open
+/// portal that is on screen. Each portal narrows the part of the screen the zones behind it can be seen through.
+/// Returns `None` if the camera isn't inside a zone.
+pub fn find_visible_zones(
+    view_projection: Mat4,
+    camera_position: Vec3,
+    zones: &[(Entity, &Zone, &GlobalTransform)],
+    portals: &[(&Portal, &GlobalTransform)],
+) -> Option<HashSet<Entity>> {
+    let camera_zone = zones
+        .iter()
+        .find(|(_entity, zone, transform)| zone.contains(transform, camera_position))
+        .map(|(entity, _zone, _transform)| *entity)?;
+
+    let portal_rects = portals
+        .iter()
+        .map(|(portal, transform)| {
+            if portal.open {
+                project_portal(view_projection, &portal.corners(transform))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // a zone can be seen through several portals, so it is walked again whenever it becomes visible through more of
+    // the screen
+    let mut visited = HashMap::<Entity, ScreenRect>::default();
+    visited.insert(camera_zone, ScreenRect::full());
+    let mut stack = vec![(camera_zone, ScreenRect::full())];
+    while let Some((zone, rect)) = stack.pop() {
+        for ((portal, _transform), portal_rect) in portals.iter().zip(portal_rects.iter()) {
+            let next_zone = if portal.zones[0] == zone {
+                portal.zones[1]
+            } else if portal.zones[1] == zone {
+                portal.zones[0]
+            } else {
+                continue;
+            };
+            let next_rect = if let Some(next_rect) = portal_rect
+                .as_ref()
+                .and_then(|portal_rect| portal_rect.intersection(&rect))
+            {
+                next_rect
+            } else {
+                continue;
+            };
+
+            let next_rect = match visited.get(&next_zone) {
+                Some(visited_rect) if visited_rect.contains(&next_rect) => continue,
+                Some(visited_rect) => visited_rect.union(&next_rect),
+                None => next_rect,
+            };
+            visited.insert(next_zone, next_rect);
+            stack.push((next_zone, next_rect));
+        }
+    }
+
+    Some(visited.keys().cloned().collect())
+}
+
+/// Updates [ZoneVisibility] for every camera
+pub fn zone_visibility_system(
+    mut zone_visibility: ResMut<ZoneVisibility>,
+    camera_query: Query<(Entity, &Camera, &GlobalTransform)>,
+    zone_query: Query<(Entity, &Zone, &GlobalTransform)>,
+    portal_query: Query<(&Portal, &GlobalTransform)>,
+) {
+    zone_visibility.visible_zones.clear();
+    let zones = zone_query.iter().collect::<Vec<_>>();
+    if zones.is_empty() {
+        return;
+    }
+    let portals = portal_query.iter().collect::<Vec<_>>();
+
+    for (camera_entity, camera, camera_transform) in camera_query.iter() {
+        let view_projection =
+            camera.projection_matrix * camera_transform.compute_matrix().inverse();
+        if let Some(visible_zones) = find_visible_zones(
+            view_projection,
+            camera_transform.translation,
+            &zones,
+            &portals,
+        ) {
+            zone_visibility
+                .visible_zones
+                .insert(camera_entity, visible_zones);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::{CameraProjection, PerspectiveProjection};
+    use bevy_math::Quat;
+
+    #[test]
+    fn portals_limit_visible_zones() {
+        // three rooms along the -z axis, with a fourth room off to the side of the first one
+        let rooms = [
+            Entity::new(0),
+            Entity::new(1),
+            Entity::new(2),
+            Entity::new(3),
+        ];
+        let zone = Zone {
+            half_extents: Vec3::splat(5.0),
+        };
+        let zone_transforms = [
+            GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+            GlobalTransform::from_translation(Vec3::new(0.0, 0.0, -10.0)),
+            GlobalTransform::from_translation(Vec3::new(0.0, 0.0, -20.0)),
+            GlobalTransform::from_translation(Vec3::new(10.0, 0.0, 0.0)),
+        ];
+        let zones = rooms
+            .iter()
+            .zip(zone_transforms.iter())
+            .map(|(entity, transform)| (*entity, &zone, transform))
+            .collect::<Vec<_>>();
+
+        let mut closed_door = Portal::new([rooms[1], rooms[2]], Vec2::new(2.0, 2.0));
+        closed_door.open = false;
+        let portal_list = [
+            Portal::new([rooms[0], rooms[1]], Vec2::new(2.0, 2.0)),
+            closed_door,
+            Portal::new([rooms[0], rooms[3]], Vec2::new(2.0, 2.0)),
+        ];
+        let portal_transforms = [
+            GlobalTransform::from_translation(Vec3::new(0.0, 0.0, -5.0)),
+            GlobalTransform::from_translation(Vec3::new(0.0, 0.0, -15.0)),
+            GlobalTransform {
+                translation: Vec3::new(5.0, 0.0, 0.0),
+                rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+                scale: Vec3::one(),
+            },
+        ];
+        let portals = portal_list
+            .iter()
+            .zip(portal_transforms.iter())
+            .collect::<Vec<_>>();
+
+        // looking down -z, the side room's doorway is outside of the camera's field of view
+        let camera_position = Vec3::new(0.0, 0.0, 3.0);
+        let view_projection = PerspectiveProjection::default().get_projection_matrix()
+            * Mat4::from_translation(-camera_position);
+        let visible =
+            find_visible_zones(view_projection, camera_position, &zones, &portals).unwrap();
+        assert!(visible.contains(&rooms[0]));
+        assert!(visible.contains(&rooms[1]));
+        assert!(!visible.contains(&rooms[2]));
+        assert!(!visible.contains(&rooms[3]));
+
+        assert!(
+            find_visible_zones(view_projection, Vec3::new(0.0, 50.0, 0.0), &zones, &portals)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use super::{CapturedFrame, FrameCapture, FrameWriter};
+use crate::{
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{
+        BufferId, BufferInfo, BufferUsage, RenderContext, RenderResourceId, RenderResourceType,
+    },
+    texture::{Extent3d, TextureFormat, TextureUsage},
+};
+use bevy_ecs::{Resources, World};
+use bevy_window::{WindowId, Windows};
+use std::borrow::Cow;
+
+/// Rows copied from a texture to a buffer must be aligned to this many bytes
+pub const COPY_BYTES_PER_ROW_ALIGNMENT: u32 = 256;
+
+/// Returns the number of bytes a row of `width` pixels takes up in a texture-to-buffer copy
+pub fn padded_bytes_per_row(width: u32, pixel_size: u32) -> u32 {
+    let unpadded = width * pixel_size;
+    (unpadded + COPY_BYTES_PER_ROW_ALIGNMENT - 1) / COPY_BYTES_PER_ROW_ALIGNMENT
+        * COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
+/// Removes the row padding from copied pixel data and converts it to RGBA8
+pub fn captured_pixels_to_rgba(
+    data: &[u8],
+    width: u32,
+    height: u32,
+    bytes_per_row: u32,
+    format: TextureFormat,
+) -> Vec<u8> {
+    let row_size = width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_size * height as usize);
+    for row in data.chunks(bytes_per_row as usize).take(height as usize) {
+        pixels.extend_from_slice(&row[..row_size]);
+    }
+
+    if let TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb = format {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    pixels
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CaptureLayout {
+    width: u32,
+    height: u32,
+    bytes_per_row: u32,
+}
+
+/// Copies its input texture to a buffer and hands the copy from the previous frame to a [FrameWriter]
+pub struct FrameCaptureNode {
+    window_id: WindowId,
+    format: TextureFormat,
+    staging_buffer: Option<(BufferId, usize)>,
+    pending_capture: Option<CaptureLayout>,
+    frame: u64,
+    writer: Option<FrameWriter>,
+}
+
+impl FrameCaptureNode {
+    pub const IN_TEXTURE: &'static str = "texture";
+
+    pub fn new(window_id: WindowId) -> Self {
+        FrameCaptureNode {
+            window_id,
+            format: TextureFormat::default(),
+            staging_buffer: None,
+            pending_capture: None,
+            frame: 0,
+            writer: None,
+        }
+    }
+
+    fn read_pending_capture(&mut self, render_context: &mut dyn RenderContext) {
+        let (layout, (staging_buffer, size)) =
+            match (self.pending_capture.take(), self.staging_buffer) {
+                (Some(layout), Some(staging_buffer)) => (layout, staging_buffer),
+                _ => return,
+            };
+
+        let render_resource_context = render_context.resources();
+        let format = self.format;
+        let mut pixels = Vec::new();
+        render_resource_context.map_buffer(staging_buffer);
+        render_resource_context.read_mapped_buffer(
+            staging_buffer,
+            0..size as u64,
+            &mut |data, _renderer| {
+                pixels = captured_pixels_to_rgba(
+                    data,
+                    layout.width,
+                    layout.height,
+                    layout.bytes_per_row,
+                    format,
+                );
+            },
+        );
+        render_resource_context.unmap_buffer(staging_buffer);
+
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write(CapturedFrame {
+                width: layout.width,
+                height: layout.height,
+                pixels,
+            });
+        }
+    }
+}
+
+impl Node for FrameCaptureNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[ResourceSlotInfo {
+            name: Cow::Borrowed(FrameCaptureNode::IN_TEXTURE),
+            resource_type: RenderResourceType::Texture,
+        }];
+        INPUT
+    }
+
+    fn input_texture_usage(&self, _index: usize) -> Option<TextureUsage> {
+        Some(TextureUsage::COPY_SRC)
+    }
+
+    fn update(
+        &mut self,
+        _world: &World,
+        resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const INPUT_TEXTURE: usize = 0;
+        // the copy recorded last frame has been submitted by now
+        self.read_pending_capture(render_context);
+
+        let mut frame_capture = resources.get_mut::<FrameCapture>().unwrap();
+        if !frame_capture.recording {
+            // dropping the writer flushes the remaining frames and closes the output
+            self.writer = None;
+            self.frame = 0;
+            return;
+        }
+
+        let frame = self.frame;
+        self.frame += 1;
+        if frame % frame_capture.frame_interval.max(1) as u64 != 0 {
+            return;
+        }
+
+        let windows = resources.get::<Windows>().unwrap();
+        let window = if let Some(window) = windows.get(self.window_id) {
+            window
+        } else {
+            return;
+        };
+        let texture = if let Some(RenderResourceId::Texture(texture)) = input.get(INPUT_TEXTURE) {
+            texture
+        } else {
+            return;
+        };
+
+        if self.writer.is_none() {
+            match FrameWriter::new(&frame_capture.output, window.width(), window.height()) {
+                Ok(writer) => self.writer = Some(writer),
+                Err(err) => {
+                    log::error!("Failed to start frame capture: {}", err);
+                    frame_capture.recording = false;
+                    return;
+                }
+            }
+        }
+
+        let pixel_size = self.format.pixel_size() as u32;
+        let layout = CaptureLayout {
+            width: window.width(),
+            height: window.height(),
+            bytes_per_row: padded_bytes_per_row(window.width(), pixel_size),
+        };
+        let size = (layout.bytes_per_row * layout.height) as usize;
+        let render_resource_context = render_context.resources_mut();
+        let staging_buffer = match self.staging_buffer {
+            Some((staging_buffer, staging_buffer_size)) if staging_buffer_size == size => {
+                staging_buffer
+            }
+            _ => {
+                if let Some((old_staging_buffer, _)) = self.staging_buffer.take() {
+                    render_resource_context.remove_buffer(old_staging_buffer);
+                }
+
+                let staging_buffer = render_resource_context.create_buffer(BufferInfo {
+                    size,
+                    buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                self.staging_buffer = Some((staging_buffer, size));
+                staging_buffer
+            }
+        };
+
+        render_context.copy_texture_to_buffer(
+            texture,
+            [0, 0, 0],
+            0,
+            staging_buffer,
+            0,
+            layout.bytes_per_row,
+            Extent3d {
+                width: layout.width,
+                height: layout.height,
+                depth: 1,
+            },
+        );
+        self.pending_capture = Some(layout);
+        frame_capture.frames_captured += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captured_pixels_remove_padding() {
+        assert_eq!(padded_bytes_per_row(64, 4), 256);
+        assert_eq!(padded_bytes_per_row(65, 4), 512);
+
+        let bytes_per_row = padded_bytes_per_row(2, 4);
+        let mut data = vec![0; bytes_per_row as usize * 2];
+        data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        data[bytes_per_row as usize..bytes_per_row as usize + 8]
+            .copy_from_slice(&[9, 10, 11, 12, 13, 14, 15, 16]);
+
+        let pixels = captured_pixels_to_rgba(&data, 2, 2, bytes_per_row, TextureFormat::Bgra8Unorm);
+        assert_eq!(
+            pixels,
+            vec![3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+    }
+}
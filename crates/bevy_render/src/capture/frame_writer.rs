@@ -0,0 +1,203 @@
+use super::FrameCaptureOutput;
+use parking_lot::Mutex;
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{
+        mpsc::{self, SyncSender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+use thiserror::Error;
+
+const MAX_QUEUED_FRAMES: usize = 8;
+
+/// An RGBA8 frame read back from the gpu
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// The frames written to [FrameCaptureOutput::Memory]. Clones share the same frames.
+#[derive(Debug, Clone, Default)]
+pub struct CapturedFrames {
+    frames: Arc<Mutex<Vec<CapturedFrame>>>,
+}
+
+impl CapturedFrames {
+    /// Removes and returns the frames captured so far, oldest first
+    pub fn take(&self) -> Vec<CapturedFrame> {
+        std::mem::take(&mut *self.frames.lock())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum FrameWriterError {
+    #[error("Failed to create the capture directory {0}")]
+    CreateDirectory(PathBuf, #[source] io::Error),
+    #[error("Failed to spawn the encoder {0}")]
+    SpawnEncoder(String, #[source] io::Error),
+}
+
+enum FrameSink {
+    ImageSequence {
+        directory: PathBuf,
+        index: u64,
+    },
+    Pipe {
+        child: Child,
+        width: u32,
+        height: u32,
+    },
+    Memory(CapturedFrames),
+}
+
+impl FrameSink {
+    fn write(&mut self, frame: CapturedFrame) {
+        match self {
+            FrameSink::ImageSequence { directory, index } => {
+                let path = directory.join(format!("frame_{:06}.png", index));
+                *index += 1;
+                save_png(&path, &frame);
+            }
+            FrameSink::Pipe {
+                child,
+                width,
+                height,
+            } => {
+                // raw video streams can't change size
+                if frame.width != *width || frame.height != *height {
+                    log::warn!(
+                        "Skipped a {}x{} frame because the encoder expects {}x{} frames",
+                        frame.width,
+                        frame.height,
+                        width,
+                        height
+                    );
+                    return;
+                }
+
+                if let Some(stdin) = child.stdin.as_mut() {
+                    if let Err(err) = stdin.write_all(&frame.pixels) {
+                        log::error!("Failed to write frame to encoder: {}", err);
+                    }
+                }
+            }
+            FrameSink::Memory(frames) => frames.frames.lock().push(frame),
+        }
+    }
+
+    fn finish(self) {
+        if let FrameSink::Pipe { mut child, .. } = self {
+            // closing stdin tells the encoder that the stream has ended
+            drop(child.stdin.take());
+            if let Err(err) = child.wait() {
+                log::error!("Failed to wait for encoder: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+fn save_png(path: &std::path::Path, frame: &CapturedFrame) {
+    if let Err(err) = image::save_buffer(
+        path,
+        &frame.pixels,
+        frame.width,
+        frame.height,
+        image::ColorType::Rgba8,
+    ) {
+        log::error!("Failed to save captured frame {}: {}", path.display(), err);
+    }
+}
+
+#[cfg(not(feature = "png"))]
+fn save_png(path: &std::path::Path, _frame: &CapturedFrame) {
+    log::error!(
+        "Failed to save captured frame {}: the \"png\" feature is disabled",
+        path.display()
+    );
+}
+
+/// Writes captured frames to a [FrameCaptureOutput] on a separate thread. Dropping the writer waits until all frames
+/// are written.
+pub struct FrameWriter {
+    sender: Option<SyncSender<CapturedFrame>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FrameWriter {
+    pub fn new(
+        output: &FrameCaptureOutput,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, FrameWriterError> {
+        let mut sink = match output {
+            FrameCaptureOutput::ImageSequence { directory } => {
+                std::fs::create_dir_all(directory)
+                    .map_err(|err| FrameWriterError::CreateDirectory(directory.clone(), err))?;
+                FrameSink::ImageSequence {
+                    directory: directory.clone(),
+                    index: 0,
+                }
+            }
+            FrameCaptureOutput::Pipe { program, args } => {
+                let child = Command::new(program)
+                    .args(args.iter().map(|arg| {
+                        arg.replace("{width}", &width.to_string())
+                            .replace("{height}", &height.to_string())
+                    }))
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(|err| FrameWriterError::SpawnEncoder(program.clone(), err))?;
+                FrameSink::Pipe {
+                    child,
+                    width,
+                    height,
+                }
+            }
+            FrameCaptureOutput::Memory(frames) => FrameSink::Memory(frames.clone()),
+        };
+
+        // bounding the queue makes the renderer wait for the encoder instead of buffering frames without limit
+        let (sender, receiver) = mpsc::sync_channel::<CapturedFrame>(MAX_QUEUED_FRAMES);
+        let thread = thread::Builder::new()
+            .name("frame_writer".to_string())
+            .spawn(move || {
+                for frame in receiver {
+                    sink.write(frame);
+                }
+
+                sink.finish();
+            })
+            .expect("failed to spawn frame writer thread");
+
+        Ok(FrameWriter {
+            sender: Some(sender),
+            thread: Some(thread),
+        })
+    }
+
+    pub fn write(&mut self, frame: CapturedFrame) {
+        if let Some(sender) = self.sender.as_ref() {
+            if sender.send(frame).is_err() {
+                log::error!("Failed to send frame to the frame writer thread");
+            }
+        }
+    }
+}
+
+impl Drop for FrameWriter {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                log::error!("The frame writer thread panicked");
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use super::{CapturedFrame, CapturedFrames, FrameCapture, FrameCaptureOutput};
+use bevy_app::App;
+use bevy_core::FrameTimeOverride;
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use thiserror::Error;
+
+/// When this environment variable is set to `1`, golden image tests write their reference images from the rendered
+/// images instead of comparing against them. This creates missing references and replaces the existing ones.
+pub const UPDATE_GOLDEN_IMAGES_VAR: &str = "BEVY_UPDATE_GOLDEN";
+
+fn update_golden_images() -> bool {
+    env::var(UPDATE_GOLDEN_IMAGES_VAR).map_or(false, |value| value == "1")
+}
+
+/// How different a rendered image may be from its reference image. Gpus and drivers rasterize, filter and blend
+/// slightly differently, so a tiny amount of difference is expected.
+#[derive(Debug, Clone, Copy)]
+pub struct GoldenImageTolerance {
+    /// The perceptual difference between two pixels, from 0 to 1, above which the pixels count as different
+    pub pixel_threshold: f32,
+    /// The fraction of pixels that may be different before the images count as different
+    pub max_different_pixels: f32,
+}
+
+impl Default for GoldenImageTolerance {
+    fn default() -> Self {
+        GoldenImageTolerance {
+            pixel_threshold: 0.1,
+            max_different_pixels: 0.001,
+        }
+    }
+}
+
+/// The result of [compare_images]
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    pub different_pixels: usize,
+    pub total_pixels: usize,
+    /// The largest perceptual difference of any pixel, from 0 to 1
+    pub max_difference: f32,
+    /// The expected image faded to grayscale, with the different pixels in red
+    pub diff: CapturedFrame,
+}
+
+impl ImageDiff {
+    pub fn different_ratio(&self) -> f32 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.different_pixels as f32 / self.total_pixels as f32
+        }
+    }
+}
+
+/// The largest possible value of [yiq_delta], between black and white
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+/// Converts a pixel to the YIQ color space, blending it over white so transparent pixels compare by how they look
+fn yiq(pixel: &[u8]) -> (f32, f32, f32) {
+    let alpha = pixel[3] as f32 / 255.0;
+    let blend = |channel: u8| 255.0 + (channel as f32 - 255.0) * alpha;
+    let (r, g, b) = (blend(pixel[0]), blend(pixel[1]), blend(pixel[2]));
+    (
+        r * 0.298_895_3 + g * 0.586_622_5 + b * 0.114_482_23,
+        r * 0.595_978 - g * 0.274_176_1 - b * 0.321_801_9,
+        r * 0.211_470_17 - g * 0.522_617_1 + b * 0.311_146_94,
+    )
+}
+
+/// The perceived difference between two colors, from "Measuring perceived color difference using YIQ NTSC
+/// transmission color space in mobile applications" by Kotsarenko and Ramos
+fn yiq_delta(a: &[u8], b: &[u8]) -> f32 {
+    let (ay, ai, aq) = yiq(a);
+    let (by, bi, bq) = yiq(b);
+    let (y, i, q) = (ay - by, ai - bi, aq - bq);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+/// Compares two RGBA8 images pixel by pixel, weighting color differences by how noticeable they are to people.
+/// Returns `None` if the images have different sizes.
+pub fn compare_images(
+    actual: &CapturedFrame,
+    expected: &CapturedFrame,
+    pixel_threshold: f32,
+) -> Option<ImageDiff> {
+    if actual.width != expected.width || actual.height != expected.height {
+        return None;
+    }
+
+    let mut diff = CapturedFrame {
+        width: expected.width,
+        height: expected.height,
+        pixels: Vec::with_capacity(expected.pixels.len()),
+    };
+    let mut different_pixels = 0;
+    let mut max_difference = 0.0f32;
+    for (actual, expected) in actual
+        .pixels
+        .chunks_exact(4)
+        .zip(expected.pixels.chunks_exact(4))
+    {
+        let difference = (yiq_delta(actual, expected) / MAX_YIQ_DELTA).sqrt();
+        max_difference = max_difference.max(difference);
+        if difference > pixel_threshold {
+            different_pixels += 1;
+            diff.pixels.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            // fading the unchanged pixels keeps the differences easy to spot
+            let luma = yiq(expected).0 / 255.0;
+            let faded = (255.0 - (1.0 - luma) * 255.0 * 0.1) as u8;
+            diff.pixels.extend_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+
+    Some(ImageDiff {
+        different_pixels,
+        total_pixels: (expected.width * expected.height) as usize,
+        max_difference,
+        diff,
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum GoldenImageError {
+    #[error("No frame was captured. The app needs a primary window, a renderer and the FrameCapturePlugin")]
+    NoFrameCaptured,
+    #[error("There is no reference image {reference}. The rendered image was saved as {actual}. Run the test with BEVY_UPDATE_GOLDEN=1 to write the reference image, then check it and commit it")]
+    MissingReference { reference: PathBuf, actual: PathBuf },
+    #[error("Failed to load the reference image {0}")]
+    LoadReference(PathBuf, #[source] image::ImageError),
+    #[error("Failed to create the reference image directory {0}")]
+    CreateDirectory(PathBuf, #[source] io::Error),
+    #[error("Failed to save {0}")]
+    Save(PathBuf, #[source] image::ImageError),
+    #[error("The rendered image is {actual_width}x{actual_height} but the reference image {reference} is {expected_width}x{expected_height}")]
+    SizeMismatch {
+        reference: PathBuf,
+        actual_width: u32,
+        actual_height: u32,
+        expected_width: u32,
+        expected_height: u32,
+    },
+    #[error("{different_pixels} of {total_pixels} pixels differ from the reference image {reference}. The rendered image was saved as {actual} and the differences as {diff}")]
+    Mismatch {
+        reference: PathBuf,
+        actual: PathBuf,
+        diff: PathBuf,
+        different_pixels: usize,
+        total_pixels: usize,
+    },
+}
+
+/// Renders an app and compares its output to a reference image, so changes to the renderer can't silently change what
+/// apps look like.
+///
+/// The app needs a primary window, a renderer and the [FrameCapturePlugin](super::FrameCapturePlugin). To render
+/// without a display, leave out the winit plugin and add
+/// [headless_window_system](crate::renderer::headless_window_system) to create the windows. A missing reference image
+/// fails the test. Reference images are only written from the rendered images when [UPDATE_GOLDEN_IMAGES_VAR] is set
+/// to `1`.
+#[derive(Debug, Clone)]
+pub struct GoldenImageTest {
+    /// The directory the reference images are stored in
+    pub directory: PathBuf,
+    pub name: String,
+    /// The number of frames to render before the captured frame, so assets can load and the scene can settle
+    pub warmup_frames: usize,
+    /// The time each frame advances by, so animations look the same on every run
+    pub timestep: Duration,
+    pub tolerance: GoldenImageTolerance,
+}
+
+impl GoldenImageTest {
+    pub fn new<P: Into<PathBuf>, N: Into<String>>(directory: P, name: N) -> Self {
+        GoldenImageTest {
+            directory: directory.into(),
+            name: name.into(),
+            warmup_frames: 3,
+            timestep: Duration::from_secs_f64(1.0 / 60.0),
+            tolerance: GoldenImageTolerance::default(),
+        }
+    }
+
+    pub fn reference_path(&self) -> PathBuf {
+        self.directory.join(format!("{}.png", self.name))
+    }
+
+    /// Starts `app`, renders the warmup frames and compares the next frame to the reference image
+    pub fn run(&self, mut app: App) -> Result<(), GoldenImageError> {
+        let frames = CapturedFrames::default();
+        {
+            let mut frame_capture = app
+                .resources
+                .get_mut::<FrameCapture>()
+                .ok_or(GoldenImageError::NoFrameCaptured)?;
+            frame_capture.output = FrameCaptureOutput::Memory(frames.clone());
+            frame_capture.frame_interval = 1;
+        }
+        app.resources.insert(FrameTimeOverride {
+            delta: Some(self.timestep),
+        });
+
+        app.executor.initialize(&mut app.resources);
+        app.initialize();
+        app.update_n(self.warmup_frames);
+        app.resources.get_mut::<FrameCapture>().unwrap().start();
+        app.update();
+
+        // the frame is read back during the next update, which also flushes it to `frames` once recording stops
+        app.resources.get_mut::<FrameCapture>().unwrap().stop();
+        app.update();
+        let frame = frames
+            .take()
+            .pop()
+            .ok_or(GoldenImageError::NoFrameCaptured)?;
+        self.check(&frame)
+    }
+
+    /// Compares `frame` to the reference image. Mismatching frames are saved next to the reference image, along with
+    /// an image that highlights the differences.
+    pub fn check(&self, frame: &CapturedFrame) -> Result<(), GoldenImageError> {
+        let reference = self.reference_path();
+        if update_golden_images() {
+            return self.save(&reference, frame);
+        }
+        let actual = self.directory.join(format!("{}.actual.png", self.name));
+        if !reference.exists() {
+            self.save(&actual, frame)?;
+            return Err(GoldenImageError::MissingReference { reference, actual });
+        }
+
+        let expected = load_png(&reference)
+            .map_err(|err| GoldenImageError::LoadReference(reference.clone(), err))?;
+        let diff =
+            compare_images(frame, &expected, self.tolerance.pixel_threshold).ok_or_else(|| {
+                GoldenImageError::SizeMismatch {
+                    reference: reference.clone(),
+                    actual_width: frame.width,
+                    actual_height: frame.height,
+                    expected_width: expected.width,
+                    expected_height: expected.height,
+                }
+            })?;
+        if diff.different_ratio() <= self.tolerance.max_different_pixels {
+            return Ok(());
+        }
+
+        let diff_path = self.directory.join(format!("{}.diff.png", self.name));
+        self.save(&actual, frame)?;
+        self.save(&diff_path, &diff.diff)?;
+        Err(GoldenImageError::Mismatch {
+            reference,
+            actual,
+            diff: diff_path,
+            different_pixels: diff.different_pixels,
+            total_pixels: diff.total_pixels,
+        })
+    }
+
+    fn save(&self, path: &Path, frame: &CapturedFrame) -> Result<(), GoldenImageError> {
+        std::fs::create_dir_all(&self.directory)
+            .map_err(|err| GoldenImageError::CreateDirectory(self.directory.clone(), err))?;
+        image::save_buffer(
+            path,
+            &frame.pixels,
+            frame.width,
+            frame.height,
+            image::ColorType::Rgba8,
+        )
+        .map_err(|err| GoldenImageError::Save(path.to_path_buf(), err))
+    }
+}
+
+fn load_png(path: &Path) -> Result<CapturedFrame, image::ImageError> {
+    let image = image::open(path)?.into_rgba();
+    Ok(CapturedFrame {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> CapturedFrame {
+        CapturedFrame {
+            width,
+            height,
+            pixels: color
+                .iter()
+                .cloned()
+                .cycle()
+                .take((width * height * 4) as usize)
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn compare_images_is_perceptual() {
+        let expected = solid(4, 4, [100, 150, 200, 255]);
+        let mut actual = expected.clone();
+        // a barely visible change and a clearly visible one
+        actual.pixels[0..4].copy_from_slice(&[101, 151, 199, 255]);
+        actual.pixels[4..8].copy_from_slice(&[255, 0, 0, 255]);
+
+        let diff = compare_images(&actual, &expected, 0.1).unwrap();
+        assert_eq!(diff.different_pixels, 1);
+        assert_eq!(diff.total_pixels, 16);
+        assert_eq!(&diff.diff.pixels[4..8], &[255, 0, 0, 255]);
+        assert!(diff.max_difference <= 1.0);
+
+        let black = solid(1, 1, [0, 0, 0, 255]);
+        let white = solid(1, 1, [255, 255, 255, 255]);
+        let diff = compare_images(&black, &white, 0.1).unwrap();
+        assert!(diff.max_difference > 0.9);
+        assert!(compare_images(&black, &expected, 0.1).is_none());
+    }
+}
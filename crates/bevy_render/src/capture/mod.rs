@@ -0,0 +1,309 @@
+//! Records the main pass to an image sequence, an external encoder or memory.
+//!
+//! Add [FrameCapturePlugin] after the render plugins, then set [FrameCapture::recording] to start capturing. Captured
+//! frames are copied to a buffer on the gpu and read back one frame later, so recording doesn't stall the renderer
+//! more than necessary. Encoding and file io happen on a separate thread.
+//!
+//! [GoldenImageTest] builds on frame capture to compare the output of an app to a reference image.
+
+mod frame_capture_node;
+mod frame_writer;
+mod golden;
+
+pub use frame_capture_node::*;
+pub use frame_writer::*;
+pub use golden::*;
+
+use crate::{
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+        TextureAttachment,
+    },
+    render_graph::{
+        base::{self, MainPass, Msaa, MsaaTexture},
+        PassNode, RenderGraph, WindowTextureNode,
+    },
+    texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+    Color,
+};
+use bevy_app::prelude::*;
+use bevy_core::FrameTimeOverride;
+use bevy_ecs::{IntoQuerySystem, Local, Res, ResMut};
+use bevy_window::WindowId;
+use std::{path::PathBuf, time::Duration};
+
+pub mod node {
+    pub const CAPTURE_COLOR_TEXTURE: &str = "capture_color_texture";
+    pub const CAPTURE_SAMPLED_COLOR_ATTACHMENT: &str = "capture_sampled_color_attachment";
+    pub const CAPTURE_DEPTH_TEXTURE: &str = "capture_depth_texture";
+    pub const CAPTURE_PASS: &str = "capture_pass";
+    pub const FRAME_CAPTURE: &str = "frame_capture";
+}
+
+/// Where captured frames are written to
+#[derive(Debug, Clone)]
+pub enum FrameCaptureOutput {
+    /// Writes each frame to a numbered png file in `directory`
+    ImageSequence { directory: PathBuf },
+    /// Spawns `program` and writes each frame to its stdin as raw RGBA8 pixels. `{width}` and `{height}` in `args`
+    /// are replaced with the size of the captured frames.
+    Pipe { program: String, args: Vec<String> },
+    /// Keeps each frame in memory, for apps and tests that inspect the rendered output
+    Memory(CapturedFrames),
+}
+
+impl FrameCaptureOutput {
+    pub fn image_sequence<P: Into<PathBuf>>(directory: P) -> Self {
+        FrameCaptureOutput::ImageSequence {
+            directory: directory.into(),
+        }
+    }
+
+    /// Encodes the captured frames to `path` with ffmpeg, which must be installed and in `PATH`
+    pub fn ffmpeg<P: Into<PathBuf>>(path: P, fps: u32) -> Self {
+        let path: PathBuf = path.into();
+        FrameCaptureOutput::Pipe {
+            program: "ffmpeg".to_string(),
+            args: vec![
+                "-y".to_string(),
+                "-f".to_string(),
+                "rawvideo".to_string(),
+                "-pix_fmt".to_string(),
+                "rgba".to_string(),
+                "-s".to_string(),
+                "{width}x{height}".to_string(),
+                "-r".to_string(),
+                fps.to_string(),
+                "-i".to_string(),
+                "-".to_string(),
+                "-pix_fmt".to_string(),
+                "yuv420p".to_string(),
+                path.to_string_lossy().to_string(),
+            ],
+        }
+    }
+}
+
+/// Controls frame capture. Captures are only taken while `recording` is set.
+#[derive(Debug, Clone)]
+pub struct FrameCapture {
+    pub recording: bool,
+    pub output: FrameCaptureOutput,
+    /// Only every `frame_interval`th frame is captured
+    pub frame_interval: u32,
+    /// If set, [Time](bevy_core::Time) advances by exactly this amount each frame while recording, through
+    /// [FrameTimeOverride]. Use this to capture simulations at a steady rate no matter how long each frame takes to
+    /// render and encode.
+    pub fixed_timestep: Option<Duration>,
+    frames_captured: u64,
+}
+
+impl Default for FrameCapture {
+    fn default() -> Self {
+        FrameCapture {
+            recording: false,
+            output: FrameCaptureOutput::image_sequence("capture"),
+            frame_interval: 1,
+            fixed_timestep: None,
+            frames_captured: 0,
+        }
+    }
+}
+
+impl FrameCapture {
+    pub fn new(output: FrameCaptureOutput) -> Self {
+        FrameCapture {
+            output,
+            ..Default::default()
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.recording = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.recording = false;
+    }
+
+    /// The number of frames captured since the plugin was added
+    pub fn frames_captured(&self) -> u64 {
+        self.frames_captured
+    }
+}
+
+/// Adds a pass that renders the main pass entities to an offscreen texture and a node that captures it according to
+/// the [FrameCapture] resource. The capture pass only renders while recording.
+#[derive(Default)]
+pub struct FrameCapturePlugin;
+
+impl Plugin for FrameCapturePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if app.resources().get::<FrameCapture>().is_none() {
+            app.init_resource::<FrameCapture>();
+        }
+
+        app.add_system_to_stage(stage::LAST, frame_capture_time_system.system());
+
+        let resources = app.resources();
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        render_graph.add_frame_capture_graph();
+    }
+}
+
+/// Applies [FrameCapture::fixed_timestep] to the [FrameTimeOverride] while recording
+pub fn frame_capture_time_system(
+    mut applied_fixed_timestep: Local<bool>,
+    frame_capture: Res<FrameCapture>,
+    mut frame_time_override: ResMut<FrameTimeOverride>,
+) {
+    match frame_capture.fixed_timestep {
+        Some(fixed_timestep) if frame_capture.recording => {
+            frame_time_override.delta = Some(fixed_timestep);
+            *applied_fixed_timestep = true;
+        }
+        _ if *applied_fixed_timestep => {
+            frame_time_override.delta = None;
+            *applied_fixed_timestep = false;
+        }
+        _ => {}
+    }
+}
+
+pub trait FrameCaptureGraphBuilder {
+    fn add_frame_capture_graph(&mut self) -> &mut Self;
+}
+
+impl FrameCaptureGraphBuilder for RenderGraph {
+    fn add_frame_capture_graph(&mut self) -> &mut Self {
+        let texture_descriptor = |format, usage| TextureDescriptor {
+            size: Extent3d {
+                depth: 1,
+                width: 1,
+                height: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage,
+        };
+
+        self.add_node(
+            node::CAPTURE_COLOR_TEXTURE,
+            WindowTextureNode::new(
+                WindowId::primary(),
+                texture_descriptor(
+                    TextureFormat::default(),
+                    TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::COPY_SRC,
+                ),
+            ),
+        );
+        self.add_node(
+            node::CAPTURE_DEPTH_TEXTURE,
+            WindowTextureNode::multisampled(
+                WindowId::primary(),
+                texture_descriptor(TextureFormat::Depth32Float, TextureUsage::OUTPUT_ATTACHMENT),
+                MsaaTexture::Depth,
+            ),
+        );
+
+        let mut capture_pass_node = PassNode::<&MainPass>::new(PassDescriptor {
+            color_attachments: vec![Msaa::color_attachment_descriptor(
+                TextureAttachment::Input("color_attachment".to_string()),
+                TextureAttachment::Input("color_resolve_target".to_string()),
+                Operations {
+                    load: LoadOp::Clear(Color::rgb(0.1, 0.1, 0.1)),
+                    store: true,
+                },
+            )],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+                attachment: TextureAttachment::Input("depth".to_string()),
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+            sample_count: 1,
+        });
+        capture_pass_node.use_default_clear_color(0);
+
+        let cameras = [
+            (base::node::CAMERA3D, base::camera::CAMERA3D),
+            (base::node::CAMERA2D, base::camera::CAMERA2D),
+        ];
+        for (camera_node, camera) in cameras.iter() {
+            if self.get_node_id(*camera_node).is_ok() {
+                capture_pass_node.add_camera(camera);
+            }
+        }
+
+        self.add_node(node::CAPTURE_PASS, capture_pass_node);
+        self.set_node_condition(node::CAPTURE_PASS, |_world, resources| {
+            resources
+                .get::<FrameCapture>()
+                .map_or(false, |frame_capture| frame_capture.recording)
+        })
+        .unwrap();
+        for (camera_node, _) in cameras.iter() {
+            if self.get_node_id(*camera_node).is_ok() {
+                self.add_node_edge(*camera_node, node::CAPTURE_PASS)
+                    .unwrap();
+            }
+        }
+
+        // render after the main pass so everything the main pass depends on is ready
+        if self.get_node_id(base::node::MAIN_PASS).is_ok() {
+            self.add_node_edge(base::node::MAIN_PASS, node::CAPTURE_PASS)
+                .unwrap();
+        }
+
+        self.add_node(
+            node::CAPTURE_SAMPLED_COLOR_ATTACHMENT,
+            WindowTextureNode::multisampled(
+                WindowId::primary(),
+                texture_descriptor(TextureFormat::default(), TextureUsage::OUTPUT_ATTACHMENT),
+                MsaaTexture::Color,
+            ),
+        );
+        self.add_slot_edge(
+            node::CAPTURE_SAMPLED_COLOR_ATTACHMENT,
+            WindowTextureNode::OUT_TEXTURE,
+            node::CAPTURE_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+
+        self.add_slot_edge(
+            node::CAPTURE_COLOR_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::CAPTURE_PASS,
+            "color_resolve_target",
+        )
+        .unwrap();
+        self.add_slot_edge(
+            node::CAPTURE_DEPTH_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::CAPTURE_PASS,
+            "depth",
+        )
+        .unwrap();
+
+        self.add_node(
+            node::FRAME_CAPTURE,
+            FrameCaptureNode::new(WindowId::primary()),
+        );
+        self.add_slot_edge(
+            node::CAPTURE_COLOR_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::FRAME_CAPTURE,
+            FrameCaptureNode::IN_TEXTURE,
+        )
+        .unwrap();
+        self.add_node_edge(node::CAPTURE_PASS, node::FRAME_CAPTURE)
+            .unwrap();
+
+        self
+    }
+}
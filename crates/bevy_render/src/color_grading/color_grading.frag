@@ -0,0 +1,35 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 0, binding = 0) uniform texture2D ColorGradingTexture;
+layout(set = 0, binding = 1) uniform sampler ColorGradingTexture_sampler;
+layout(set = 0, binding = 2) uniform ColorGrading {
+    // x: intensity, y: size of the LUT
+    vec4 ColorGradingParams;
+};
+layout(set = 0, binding = 3) uniform texture2D ColorGradingLut;
+layout(set = 0, binding = 4) uniform sampler ColorGradingLut_sampler;
+
+// looks the color up in the LUT strip. the LUT is indexed with srgb encoded colors and stores linear ones.
+vec3 color_grade(vec3 color) {
+    float size = ColorGradingParams.y;
+    vec3 encoded = clamp(color, 0.0, 1.0);
+    encoded = mix(encoded * 12.92, 1.055 * pow(encoded, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, encoded));
+    vec3 texel = encoded * (size - 1.0);
+    float slice = min(floor(texel.b), size - 2.0);
+    vec2 uv = vec2((slice * size + texel.r + 0.5) / (size * size), (texel.g + 0.5) / size);
+    vec3 lower = texture(sampler2D(ColorGradingLut, ColorGradingLut_sampler), uv).rgb;
+    vec3 upper = texture(sampler2D(ColorGradingLut, ColorGradingLut_sampler), uv + vec2(1.0 / size, 0.0)).rgb;
+    return mix(lower, upper, texel.b - slice);
+}
+
+void main() {
+    vec4 color = texture(sampler2D(ColorGradingTexture, ColorGradingTexture_sampler), v_Uv);
+    if (ColorGradingParams.x > 0.0) {
+        color.rgb = mix(color.rgb, color_grade(color.rgb), ColorGradingParams.x);
+    }
+    o_Target = color;
+}
@@ -0,0 +1,196 @@
+use super::{lut_size, ColorGrading, COLOR_GRADING_PIPELINE_HANDLE, NEUTRAL_LUT_HANDLE};
+use crate::{
+    camera::ActiveCameras,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::PipelineDescriptor,
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{
+        BindGroup, BufferId, BufferInfo, BufferUsage, RenderContext, RenderResourceBindings,
+        RenderResourceContext, RenderResourceId, RenderResourceType, SamplerId, TextureId,
+    },
+    shader::Shader,
+    texture::{SamplerDescriptor, Texture, TextureUsage, SAMPLER_ASSET_INDEX, TEXTURE_ASSET_INDEX},
+    Color,
+};
+use bevy_asset::{Assets, Handle};
+use bevy_core::AsBytes;
+use bevy_ecs::{Resources, World};
+use std::borrow::Cow;
+
+/// x: intensity, y: size of the LUT
+const PARAMS_SIZE: u64 = std::mem::size_of::<[f32; 4]>() as u64;
+
+#[derive(Debug)]
+struct ColorGradingBuffers {
+    params: BufferId,
+    staging: BufferId,
+    sampler: SamplerId,
+}
+
+impl ColorGradingBuffers {
+    fn new(render_resource_context: &dyn RenderResourceContext) -> Self {
+        ColorGradingBuffers {
+            params: render_resource_context.create_buffer(BufferInfo {
+                size: PARAMS_SIZE as usize,
+                buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+                ..Default::default()
+            }),
+            staging: render_resource_context.create_buffer(BufferInfo {
+                size: PARAMS_SIZE as usize,
+                buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+                mapped_at_creation: true,
+            }),
+            sampler: render_resource_context.create_sampler(&SamplerDescriptor::default()),
+        }
+    }
+}
+
+/// Draws a texture over a color attachment of the default format through the [ColorGrading] of the first of its
+/// cameras that has one. Without a loaded LUT the texture is drawn unchanged.
+#[derive(Debug)]
+pub struct ColorGradingNode {
+    cameras: Vec<String>,
+    buffers: Option<ColorGradingBuffers>,
+}
+
+impl ColorGradingNode {
+    pub const IN_TEXTURE: &'static str = "texture";
+    pub const IN_COLOR_ATTACHMENT: &'static str = "color_attachment";
+
+    pub fn new(camera_names: &[&str]) -> Self {
+        ColorGradingNode {
+            cameras: camera_names.iter().map(|name| name.to_string()).collect(),
+            buffers: None,
+        }
+    }
+}
+
+impl Node for ColorGradingNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[
+            ResourceSlotInfo {
+                name: Cow::Borrowed(ColorGradingNode::IN_TEXTURE),
+                resource_type: RenderResourceType::Texture,
+            },
+            ResourceSlotInfo {
+                name: Cow::Borrowed(ColorGradingNode::IN_COLOR_ATTACHMENT),
+                resource_type: RenderResourceType::Texture,
+            },
+        ];
+        INPUT
+    }
+
+    fn input_texture_usage(&self, index: usize) -> Option<TextureUsage> {
+        const IN_TEXTURE: usize = 0;
+        if index == IN_TEXTURE {
+            Some(TextureUsage::SAMPLED)
+        } else {
+            Some(TextureUsage::OUTPUT_ATTACHMENT)
+        }
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        let (texture, color_attachment) = match (input.get(0), input.get(1)) {
+            (Some(RenderResourceId::Texture(texture)), Some(RenderResourceId::Texture(target))) => {
+                (texture, target)
+            }
+            _ => return,
+        };
+
+        let active_cameras = resources.get::<ActiveCameras>().unwrap();
+        let textures = resources.get::<Assets<Texture>>().unwrap();
+        let color_grading = self
+            .cameras
+            .iter()
+            .filter_map(|name| active_cameras.get(name))
+            .find_map(|entity| world.get::<ColorGrading>(entity).ok());
+
+        let render_resource_context = render_context.resources();
+        let lut_resources = |lut: &Handle<Texture>| -> Option<(TextureId, SamplerId)> {
+            let texture = render_resource_context.get_asset_resource(lut, TEXTURE_ASSET_INDEX)?;
+            let sampler = render_resource_context.get_asset_resource(lut, SAMPLER_ASSET_INDEX)?;
+            Some((texture.get_texture()?, sampler.get_sampler()?))
+        };
+        // LUTs that aren't loaded yet are skipped, the shader still needs one bound
+        let graded = color_grading.and_then(|color_grading| {
+            let size = lut_size(textures.get(&color_grading.lut)?)?;
+            let lut = lut_resources(&color_grading.lut)?;
+            Some(([color_grading.intensity, size as f32, 0.0, 0.0], lut))
+        });
+        let (params, (lut_texture, lut_sampler)) =
+            match graded.or_else(|| Some(([0.0; 4], lut_resources(&NEUTRAL_LUT_HANDLE)?))) {
+                Some(graded) => graded,
+                None => return,
+            };
+
+        let bind_group_descriptor = {
+            let shaders = resources.get::<Assets<Shader>>().unwrap();
+            let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+            let pipeline = pipelines.get_mut(&COLOR_GRADING_PIPELINE_HANDLE).unwrap();
+            if pipeline.layout.is_none() {
+                pipeline.reflect_layout(&shaders, false, &[]);
+            }
+            render_resource_context.create_render_pipeline(
+                COLOR_GRADING_PIPELINE_HANDLE,
+                pipeline,
+                &shaders,
+            );
+            pipeline.get_layout().unwrap().bind_groups[0].id
+        };
+
+        let buffers = self
+            .buffers
+            .get_or_insert_with(|| ColorGradingBuffers::new(render_resource_context));
+        render_resource_context.map_buffer(buffers.staging);
+        render_resource_context.write_mapped_buffer(
+            buffers.staging,
+            0..PARAMS_SIZE,
+            &mut |data, _renderer| {
+                data.copy_from_slice(params.as_bytes());
+            },
+        );
+        render_resource_context.unmap_buffer(buffers.staging);
+
+        let bind_group = BindGroup::build()
+            .add_texture(0, texture)
+            .add_sampler(1, buffers.sampler)
+            .add_buffer(2, buffers.params, 0..PARAMS_SIZE)
+            .add_texture(3, lut_texture)
+            .add_sampler(4, lut_sampler)
+            .finish();
+        // bind groups are cleared at the end of every frame
+        render_resource_context.create_bind_group(bind_group_descriptor, &bind_group);
+
+        render_context.copy_buffer_to_buffer(buffers.staging, 0, buffers.params, 0, PARAMS_SIZE);
+        let pass_descriptor = PassDescriptor {
+            color_attachments: vec![RenderPassColorAttachmentDescriptor {
+                attachment: TextureAttachment::Id(color_attachment),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+            sample_count: 1,
+        };
+        render_context.begin_pass(
+            &pass_descriptor,
+            &RenderResourceBindings::default(),
+            &mut |render_pass| {
+                render_pass.set_pipeline(&COLOR_GRADING_PIPELINE_HANDLE);
+                render_pass.set_bind_group(0, bind_group_descriptor, bind_group.id, None);
+                render_pass.draw(0..3, 0..1);
+            },
+        );
+    }
+}
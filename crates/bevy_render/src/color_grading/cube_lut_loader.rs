@@ -0,0 +1,150 @@
+use super::lut_from_colors;
+use crate::texture::Texture;
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_utils::BoxedFuture;
+use thiserror::Error;
+
+/// An error that occurs when parsing a `.cube` file
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CubeLutError {
+    #[error("The file is not valid utf-8")]
+    InvalidUtf8,
+    #[error("1D LUTs are not supported")]
+    Unsupported1d,
+    #[error("The file does not declare LUT_3D_SIZE")]
+    MissingSize,
+    #[error("Line {0} could not be parsed")]
+    InvalidLine(usize),
+    #[error("Expected {expected} colors, found {found}")]
+    WrongColorCount { expected: usize, found: usize },
+}
+
+/// Parses the 3D LUT in an Adobe `.cube` file into a LUT strip texture
+pub fn parse_cube_lut(source: &str) -> Result<Texture, CubeLutError> {
+    let mut size = None;
+    let mut domain_min = [0.0; 3];
+    let mut domain_max = [1.0; 3];
+    let mut colors = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid_line = || CubeLutError::InvalidLine(index + 1);
+        let mut words = line.split_whitespace();
+        let keyword = words.next().unwrap();
+        let parse_vec3 = |words: std::str::SplitWhitespace| {
+            let values = words
+                .map(|word| word.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid_line())?;
+            match values.as_slice() {
+                [x, y, z] => Ok([*x, *y, *z]),
+                _ => Err(invalid_line()),
+            }
+        };
+
+        match keyword {
+            "TITLE" => {}
+            "LUT_1D_SIZE" => return Err(CubeLutError::Unsupported1d),
+            "LUT_3D_SIZE" => {
+                let value = words
+                    .next()
+                    .and_then(|word| word.parse::<u32>().ok())
+                    .filter(|size| *size >= 2)
+                    .ok_or_else(invalid_line)?;
+                size = Some(value);
+            }
+            "DOMAIN_MIN" => domain_min = parse_vec3(words)?,
+            "DOMAIN_MAX" => domain_max = parse_vec3(words)?,
+            _ => {
+                let mut color = parse_vec3(line.split_whitespace())?;
+                for (channel, value) in color.iter_mut().enumerate() {
+                    *value = (*value - domain_min[channel])
+                        / (domain_max[channel] - domain_min[channel]);
+                }
+                colors.push(color);
+            }
+        }
+    }
+
+    let size = size.ok_or(CubeLutError::MissingSize)?;
+    let expected = (size * size * size) as usize;
+    if colors.len() != expected {
+        return Err(CubeLutError::WrongColorCount {
+            expected,
+            found: colors.len(),
+        });
+    }
+
+    Ok(lut_from_colors(size, &colors))
+}
+
+/// Loads Adobe `.cube` 3D LUTs as LUT strip textures for [ColorGrading](super::ColorGrading)
+#[derive(Clone, Default)]
+pub struct CubeLutLoader;
+
+impl AssetLoader for CubeLutLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let source = std::str::from_utf8(bytes).map_err(|_| CubeLutError::InvalidUtf8)?;
+            let texture = parse_cube_lut(source)?;
+            load_context.set_default_asset(LoadedAsset::new(texture));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["cube"];
+        EXTENSIONS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::Vec2;
+
+    #[test]
+    fn parse_cube_file() {
+        let source = "TITLE \"invert\"\n\
+            # a LUT that inverts colors\n\
+            LUT_3D_SIZE 2\n\
+            DOMAIN_MIN 0 0 0\n\
+            DOMAIN_MAX 2 2 2\n\
+            2 2 2\n0 2 2\n2 0 2\n0 0 2\n\
+            2 2 0\n0 2 0\n2 0 0\n0 0 0\n";
+        let texture = parse_cube_lut(source).unwrap();
+        assert_eq!(texture.size, Vec2::new(4.0, 2.0));
+        // red 1, green 0, blue 0 is the second texel of the first slice
+        assert_eq!(&texture.data[4..8], &[0, 255, 255, 255]);
+        // red 0, green 1, blue 1 is the first texel of the second row of the second slice
+        assert_eq!(
+            &texture.data[(4 + 2) * 4..(4 + 2) * 4 + 4],
+            &[255, 0, 0, 255]
+        );
+
+        assert_eq!(
+            parse_cube_lut("LUT_3D_SIZE 2\n0 0 0\n").unwrap_err(),
+            CubeLutError::WrongColorCount {
+                expected: 8,
+                found: 1
+            }
+        );
+        assert_eq!(
+            parse_cube_lut("0 0 0\n").unwrap_err(),
+            CubeLutError::MissingSize
+        );
+        assert_eq!(
+            parse_cube_lut("LUT_3D_SIZE 2\n0 0\n").unwrap_err(),
+            CubeLutError::InvalidLine(2)
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Color grading with 3D lookup tables.
+//!
+//! A [ColorGrading] component on a camera maps each color to the color its lookup table (LUT) stores for it, after
+//! the colors are exposed and tonemapped. [ColorGradingPlugin] points every pass that renders to the primary window at
+//! a texture instead, and a final pass draws that texture to the window through the LUT, so sprites and the ui are
+//! graded too. LUTs are strip textures: N slices of N×N texels side by side, where red
+//! grows to the right within a slice, green grows downwards and blue grows from slice to slice. 16 and 32 texel LUTs
+//! are the common sizes. Strips can be loaded from PNG files, or from Adobe `.cube` files with [CubeLutLoader].
+
+mod color_grading_node;
+mod cube_lut_loader;
+
+pub use color_grading_node::*;
+pub use cube_lut_loader::*;
+
+use crate::{
+    pipeline::{
+        BlendDescriptor, ColorStateDescriptor, ColorWrite, CullMode, FrontFace, PipelineDescriptor,
+        RasterizationStateDescriptor,
+    },
+    render_graph::{base, Edge, RenderGraph, WindowSwapChainNode, WindowTextureNode},
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{
+        Extent3d, FilterMode, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+        TextureUsage,
+    },
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Query, ResMut};
+use bevy_math::Vec2;
+use bevy_property::Properties;
+use bevy_type_registry::TypeUuid;
+use bevy_window::WindowId;
+
+/// The LUT cameras without a loaded LUT are bound to. It doesn't change colors.
+pub const NEUTRAL_LUT_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 8309284105274138163);
+
+pub const COLOR_GRADING_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 2486930142771655317);
+
+pub mod node {
+    pub const COLOR_GRADING_TEXTURE: &str = "color_grading_texture";
+    pub const COLOR_GRADING_PASS: &str = "color_grading_pass";
+}
+
+/// Grades the colors a camera renders with a lookup table. Needs the [ColorGradingPlugin].
+#[derive(Debug, Clone, Properties)]
+pub struct ColorGrading {
+    pub lut: Handle<Texture>,
+    /// How much of the graded color replaces the original color, from 0.0 to 1.0
+    pub intensity: f32,
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        ColorGrading {
+            lut: NEUTRAL_LUT_HANDLE,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl ColorGrading {
+    pub fn new(lut: Handle<Texture>) -> Self {
+        ColorGrading {
+            lut,
+            ..Default::default()
+        }
+    }
+}
+
+/// The number of texels along each axis of the LUT stored in `texture`, or `None` if it isn't a LUT strip
+pub fn lut_size(texture: &Texture) -> Option<u32> {
+    let width = texture.size.x() as u32;
+    let height = texture.size.y() as u32;
+    if height >= 2 && width == height * height {
+        Some(height)
+    } else {
+        None
+    }
+}
+
+/// Creates a LUT strip from `size`³ colors. The colors are ordered like in `.cube` files: red changes fastest, then
+/// green, then blue.
+pub fn lut_from_colors(size: u32, colors: &[[f32; 3]]) -> Texture {
+    let size = size as usize;
+    assert_eq!(colors.len(), size * size * size, "a LUT needs size³ colors");
+
+    let width = size * size;
+    let mut data = vec![0; width * size * 4];
+    for (i, color) in colors.iter().enumerate() {
+        let (r, g, b) = (i % size, (i / size) % size, i / (size * size));
+        let offset = (g * width + b * size + r) * 4;
+        for (channel, value) in color.iter().enumerate() {
+            data[offset + channel] = (value.max(0.0).min(1.0) * 255.0).round() as u8;
+        }
+        data[offset + 3] = 255;
+    }
+
+    // the LUT stores colors in the same encoding as color textures, so the graded color is linear when sampled
+    let mut texture = Texture::new(
+        Vec2::new(width as f32, size as f32),
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    set_lut_filtering(&mut texture);
+    texture
+}
+
+/// A LUT that maps every color to itself
+pub fn neutral_lut(size: u32) -> Texture {
+    let max = (size - 1) as f32;
+    let colors = (0..size * size * size)
+        .map(|i| {
+            [
+                (i % size) as f32 / max,
+                ((i / size) % size) as f32 / max,
+                (i / (size * size)) as f32 / max,
+            ]
+        })
+        .collect::<Vec<_>>();
+    lut_from_colors(size, &colors)
+}
+
+fn set_lut_filtering(texture: &mut Texture) {
+    texture.sampler.mag_filter = FilterMode::Linear;
+    texture.sampler.min_filter = FilterMode::Linear;
+    texture.sampler.mipmap_filter = FilterMode::Nearest;
+}
+
+/// LUTs are interpolated between their texels, so strips loaded as regular images are switched to linear filtering
+pub fn color_grading_system(mut textures: ResMut<Assets<Texture>>, query: Query<&ColorGrading>) {
+    for color_grading in query.iter() {
+        let needs_filtering = textures.get(&color_grading.lut).map_or(false, |texture| {
+            lut_size(texture).is_some()
+                && (texture.sampler.mag_filter != FilterMode::Linear
+                    || texture.sampler.min_filter != FilterMode::Linear)
+        });
+        if needs_filtering {
+            set_lut_filtering(textures.get_mut(&color_grading.lut).unwrap());
+        }
+    }
+}
+
+/// Grades the primary window with the [ColorGrading] of the 3d camera, or of the 2d camera if the 3d camera has none.
+/// Add the plugin after the other render plugins and post-processing effects, so it can redirect their passes.
+#[derive(Default)]
+pub struct ColorGradingPlugin;
+
+impl Plugin for ColorGradingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let resources = app.resources();
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(
+                COLOR_GRADING_PIPELINE_HANDLE,
+                build_color_grading_pipeline(&mut shaders),
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_color_grading_graph(&mut render_graph);
+    }
+}
+
+pub fn build_color_grading_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        name: Some("color_grading".to_string()),
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("../render_graph/nodes/blit.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("color_grading.frag"),
+            ))),
+        })
+    }
+}
+
+fn add_color_grading_graph(graph: &mut RenderGraph) {
+    graph.add_node(
+        node::COLOR_GRADING_TEXTURE,
+        WindowTextureNode::new(
+            WindowId::primary(),
+            TextureDescriptor {
+                size: Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::default(),
+                usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+            },
+        ),
+    );
+
+    // move every pass that renders to the window over to the graded texture
+    let redirected_edges = graph
+        .iter_node_outputs(base::node::PRIMARY_SWAP_CHAIN)
+        .unwrap()
+        .filter_map(|(edge, _node)| match edge {
+            Edge::SlotEdge {
+                input_node,
+                input_index,
+                ..
+            } => Some((*input_node, *input_index)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for (input_node, input_index) in redirected_edges.iter() {
+        graph
+            .remove_slot_edge(
+                base::node::PRIMARY_SWAP_CHAIN,
+                WindowSwapChainNode::OUT_TEXTURE,
+                *input_node,
+                *input_index,
+            )
+            .unwrap();
+        graph
+            .add_slot_edge(
+                node::COLOR_GRADING_TEXTURE,
+                WindowTextureNode::OUT_TEXTURE,
+                *input_node,
+                *input_index,
+            )
+            .unwrap();
+    }
+
+    graph.add_node(
+        node::COLOR_GRADING_PASS,
+        ColorGradingNode::new(&[base::camera::CAMERA3D, base::camera::CAMERA2D]),
+    );
+    graph
+        .add_slot_edge(
+            node::COLOR_GRADING_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::COLOR_GRADING_PASS,
+            ColorGradingNode::IN_TEXTURE,
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::COLOR_GRADING_PASS,
+            ColorGradingNode::IN_COLOR_ATTACHMENT,
+        )
+        .unwrap();
+    // the grading pass samples what the redirected passes rendered
+    for (input_node, _input_index) in redirected_edges {
+        let _ = graph.add_node_edge(input_node, node::COLOR_GRADING_PASS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lut_strip_layout() {
+        let texture = neutral_lut(2);
+        assert_eq!(lut_size(&texture), Some(2));
+        assert_eq!(texture.size, Vec2::new(4.0, 2.0));
+        let texel = |x: usize, y: usize| {
+            let offset = (y * 4 + x) * 4;
+            &texture.data[offset..offset + 4]
+        };
+        assert_eq!(texel(0, 0), &[0, 0, 0, 255]);
+        // red grows within a slice, green downwards and blue from slice to slice
+        assert_eq!(texel(1, 0), &[255, 0, 0, 255]);
+        assert_eq!(texel(0, 1), &[0, 255, 0, 255]);
+        assert_eq!(texel(2, 0), &[0, 0, 255, 255]);
+        assert_eq!(texel(3, 1), &[255, 255, 255, 255]);
+
+        assert_eq!(
+            lut_size(&Texture::new_fill(
+                Vec2::new(4.0, 4.0),
+                &[0, 0, 0, 0],
+                TextureFormat::Rgba8UnormSrgb
+            )),
+            None
+        );
+    }
+}
@@ -10,6 +10,7 @@ use crate::{
     shader::Shader,
 };
 use bevy_asset::{Assets, Handle};
+use bevy_core::Byteable;
 use bevy_ecs::{
     FetchResource, Query, Res, ResMut, ResourceIndex, ResourceQuery, Resources, SystemId,
     TypeAccess, UnsafeClone,
@@ -47,8 +48,28 @@ pub enum RenderCommand {
         vertices: Range<u32>,
         instances: Range<u32>,
     },
+    /// Draws `count` times with the [DrawIndexedIndirectArgs] stored in `indirect_buffer`, starting at
+    /// `indirect_offset`
+    DrawIndexedIndirect {
+        indirect_buffer: BufferId,
+        indirect_offset: u64,
+        count: u32,
+    },
+}
+
+/// The layout of the arguments of an indexed indirect draw, as written to an indirect buffer
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct DrawIndexedIndirectArgs {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub first_instance: u32,
 }
 
+unsafe impl Byteable for DrawIndexedIndirectArgs {}
+
 /// A component that indicates how to draw an entity.
 #[derive(Debug, Properties, Clone)]
 pub struct Draw {
@@ -68,6 +89,25 @@ impl Default for Draw {
     }
 }
 
+/// The name of the [RenderPipelines](crate::pipeline::RenderPipelines) binding of a buffer with
+/// [DrawIndexedIndirectArgs]. Pipelines with the [INDIRECT_DRAW_SHADER_DEF] shader def are drawn with the arguments at
+/// the start of the binding's range instead of the entity's [InstanceCount], so the gpu decides what gets drawn.
+pub const INDIRECT_DRAW_ARGS: &str = "IndirectDrawArgs";
+/// Shader defs are cleared after every frame, so whatever fills the [INDIRECT_DRAW_ARGS] has to set this every frame
+/// it fills them
+pub const INDIRECT_DRAW_SHADER_DEF: &str = "INDIRECT_DRAW";
+
+/// Draws an entity's mesh this many times in a single draw call. The vertex shader tells the copies apart with
+/// `gl_InstanceIndex`, usually to read per-instance data from a storage buffer. Entities without it are drawn once.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct InstanceCount(pub u32);
+
+impl Default for InstanceCount {
+    fn default() -> Self {
+        InstanceCount(1)
+    }
+}
+
 impl Draw {
     pub fn clear_render_commands(&mut self) {
         self.render_commands.clear();
@@ -107,6 +147,21 @@ impl Draw {
         });
     }
 
+    /// Draws with arguments read from `indirect_buffer`, which needs [BufferUsage::INDIRECT]. This lets the gpu
+    /// decide how many instances get drawn.
+    pub fn draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: BufferId,
+        indirect_offset: u64,
+        count: u32,
+    ) {
+        self.render_command(RenderCommand::DrawIndexedIndirect {
+            indirect_buffer,
+            indirect_offset,
+            count,
+        });
+    }
+
     #[inline]
     pub fn render_command(&mut self, render_command: RenderCommand) {
         self.render_commands.push(render_command);
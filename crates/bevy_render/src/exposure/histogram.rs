@@ -0,0 +1,113 @@
+/// A histogram of scene luminance in log2 space, measured by the [LuminanceHistogramNode](super::LuminanceHistogramNode)
+#[derive(Debug, Clone)]
+pub struct LuminanceHistogram {
+    pub bins: Vec<u32>,
+    /// log2 of the luminance at the lower edge of the first bin. Darker samples are counted in the first bin.
+    pub min_log2: f32,
+    /// log2 of the luminance at the upper edge of the last bin. Brighter samples are counted in the last bin.
+    pub max_log2: f32,
+}
+
+impl Default for LuminanceHistogram {
+    fn default() -> Self {
+        LuminanceHistogram::new(-8.0, 8.0)
+    }
+}
+
+impl LuminanceHistogram {
+    pub const BINS: usize = 64;
+
+    pub fn new(min_log2: f32, max_log2: f32) -> Self {
+        LuminanceHistogram {
+            bins: vec![0; Self::BINS],
+            min_log2,
+            max_log2,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for bin in self.bins.iter_mut() {
+            *bin = 0;
+        }
+    }
+
+    pub fn add(&mut self, luminance: f32) {
+        let log2 = if luminance > 0.0 {
+            luminance.log2()
+        } else {
+            self.min_log2
+        };
+        let position = (log2 - self.min_log2) / (self.max_log2 - self.min_log2);
+        let index = (position * self.bins.len() as f32) as isize;
+        let index = index.max(0).min(self.bins.len() as isize - 1) as usize;
+        self.bins[index] += 1;
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.bins.iter().sum()
+    }
+
+    /// log2 of the luminance at the center of the bin at `index`
+    pub fn bin_log2(&self, index: usize) -> f32 {
+        let bin_size = (self.max_log2 - self.min_log2) / self.bins.len() as f32;
+        self.min_log2 + (index as f32 + 0.5) * bin_size
+    }
+
+    /// The geometric mean of the samples between the `low` and `high` percentiles (0.0 to 1.0). Leaving out the
+    /// darkest and brightest samples keeps small light sources and deep shadows from skewing the result. Returns
+    /// `None` if the histogram is empty.
+    pub fn average_luminance(&self, low: f32, high: f32) -> Option<f32> {
+        let samples = self.samples();
+        if samples == 0 {
+            return None;
+        }
+
+        let low = low.max(0.0).min(1.0) * samples as f32;
+        let high = high.max(0.0).min(1.0) * samples as f32;
+        let mut counted = 0.0;
+        let mut sum = 0.0;
+        let mut weight = 0.0;
+        for (index, bin) in self.bins.iter().enumerate() {
+            // the part of this bin that lies between the percentiles
+            let start = counted;
+            let end = counted + *bin as f32;
+            counted = end;
+            let included = end.min(high) - start.max(low);
+            if included > 0.0 {
+                sum += self.bin_log2(index) * included;
+                weight += included;
+            }
+        }
+
+        if weight > 0.0 {
+            Some((sum / weight).exp2())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_average_ignores_outliers() {
+        let mut histogram = LuminanceHistogram::new(-8.0, 8.0);
+        for _ in 0..90 {
+            histogram.add(1.0);
+        }
+        for _ in 0..10 {
+            histogram.add(1000.0);
+        }
+        histogram.add(0.0);
+
+        let bin_size = 16.0 / LuminanceHistogram::BINS as f32;
+        let average = histogram.average_luminance(0.05, 0.85).unwrap();
+        assert!((average.log2() - bin_size * 0.5).abs() < 1e-4);
+        assert!(histogram.average_luminance(0.0, 1.0).unwrap() > average);
+
+        histogram.clear();
+        assert_eq!(histogram.average_luminance(0.0, 1.0), None);
+    }
+}
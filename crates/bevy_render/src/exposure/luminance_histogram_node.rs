@@ -0,0 +1,302 @@
+use super::{Exposure, LuminanceHistogram, Tonemapping, LUMINANCE_HISTOGRAM_PIPELINE_HANDLE};
+use crate::{
+    camera::ActiveCameras,
+    pipeline::ComputePipelineDescriptor,
+    render_graph::{base, Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{
+        BindGroup, BufferId, BufferInfo, BufferUsage, RenderCapabilities, RenderContext,
+        RenderResourceContext, RenderResourceId, RenderResourceType, SamplerId,
+    },
+    shader::Shader,
+    texture::{SamplerDescriptor, TextureUsage},
+};
+use bevy_asset::Assets;
+use bevy_core::{AsBytes, Byteable};
+use bevy_ecs::{Resources, World};
+use std::borrow::Cow;
+
+const WORKGROUP_SIZE: u32 = 16;
+/// The number of histograms that can be on their way back from the gpu. A histogram is copied to a readback buffer
+/// in the frame it is recorded, the buffer is mapped once that frame was submitted and it is read when the map
+/// completes, usually two frames after it was recorded.
+const READBACK_BUFFERS: usize = 3;
+
+/// The uniform of the histogram shader
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct HistogramParams {
+    min_log2: f32,
+    max_log2: f32,
+    exposure_multiplier: f32,
+    reinhard: u32,
+}
+
+unsafe impl Byteable for HistogramParams {}
+
+const PARAMS_SIZE: u64 = std::mem::size_of::<HistogramParams>() as u64;
+const BINS_SIZE: u64 = (LuminanceHistogram::BINS * std::mem::size_of::<u32>()) as u64;
+
+impl HistogramParams {
+    fn new(histogram: &LuminanceHistogram, exposure: &Exposure) -> Self {
+        HistogramParams {
+            min_log2: histogram.min_log2,
+            max_log2: histogram.max_log2,
+            exposure_multiplier: exposure.multiplier(),
+            reinhard: (exposure.tonemapping == Tonemapping::Reinhard) as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadbackState {
+    Free,
+    /// The histogram was copied to the buffer in the frame it was recorded in
+    Copied,
+    Mapping,
+}
+
+#[derive(Debug)]
+struct Readback {
+    buffer: BufferId,
+    state: ReadbackState,
+    /// The frame the histogram in the buffer was recorded in
+    frame: u64,
+}
+
+#[derive(Debug)]
+struct HistogramBuffers {
+    params: BufferId,
+    bins: BufferId,
+    /// Holds the params and the cleared bins of the next dispatch
+    staging: BufferId,
+    readbacks: Vec<Readback>,
+    sampler: SamplerId,
+}
+
+impl HistogramBuffers {
+    fn new(render_resource_context: &dyn RenderResourceContext) -> Self {
+        let params = render_resource_context.create_buffer(BufferInfo {
+            size: PARAMS_SIZE as usize,
+            buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            ..Default::default()
+        });
+        let bins = render_resource_context.create_buffer(BufferInfo {
+            size: BINS_SIZE as usize,
+            buffer_usage: BufferUsage::STORAGE | BufferUsage::COPY_DST | BufferUsage::COPY_SRC,
+            ..Default::default()
+        });
+        let staging = render_resource_context.create_buffer(BufferInfo {
+            size: (PARAMS_SIZE + BINS_SIZE) as usize,
+            buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+            mapped_at_creation: true,
+        });
+        let readbacks = (0..READBACK_BUFFERS)
+            .map(|_| Readback {
+                buffer: render_resource_context.create_buffer(BufferInfo {
+                    size: BINS_SIZE as usize,
+                    buffer_usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                state: ReadbackState::Free,
+                frame: 0,
+            })
+            .collect();
+
+        HistogramBuffers {
+            params,
+            bins,
+            staging,
+            readbacks,
+            sampler: render_resource_context.create_sampler(&SamplerDescriptor::default()),
+        }
+    }
+}
+
+/// Counts the luminance of the pixels of its input texture into a histogram with a compute shader, and reads the
+/// histogram back into the [LuminanceHistogram] resource a couple of frames later without waiting for the gpu.
+/// Without compute support the histogram stays empty.
+#[derive(Debug, Default)]
+pub struct LuminanceHistogramNode {
+    buffers: Option<HistogramBuffers>,
+    frame: u64,
+    /// The frame of the histogram that was read last
+    read_frame: u64,
+}
+
+impl LuminanceHistogramNode {
+    pub const IN_TEXTURE: &'static str = "texture";
+
+    /// Starts mapping the histograms that were submitted and reads the ones that finished mapping
+    fn read_histograms(&mut self, render_context: &mut dyn RenderContext, resources: &Resources) {
+        let buffers = if let Some(buffers) = self.buffers.as_mut() {
+            buffers
+        } else {
+            return;
+        };
+
+        let render_resource_context = render_context.resources();
+        for readback in buffers.readbacks.iter_mut() {
+            match readback.state {
+                ReadbackState::Copied => {
+                    render_resource_context.map_buffer_async(readback.buffer);
+                    readback.state = ReadbackState::Mapping;
+                }
+                ReadbackState::Mapping
+                    if render_resource_context.is_buffer_mapped(readback.buffer) =>
+                {
+                    // maps can complete out of order, older histograms don't replace newer ones
+                    if readback.frame > self.read_frame {
+                        self.read_frame = readback.frame;
+                        let mut histogram = resources.get_mut::<LuminanceHistogram>().unwrap();
+                        render_resource_context.read_mapped_buffer(
+                            readback.buffer,
+                            0..BINS_SIZE,
+                            &mut |data, _renderer| {
+                                for (bin, count) in
+                                    histogram.bins.iter_mut().zip(data.chunks_exact(4))
+                                {
+                                    *bin = u32::from_ne_bytes([
+                                        count[0], count[1], count[2], count[3],
+                                    ]);
+                                }
+                            },
+                        );
+                    }
+                    render_resource_context.unmap_buffer(readback.buffer);
+                    readback.state = ReadbackState::Free;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Node for LuminanceHistogramNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[ResourceSlotInfo {
+            name: Cow::Borrowed(LuminanceHistogramNode::IN_TEXTURE),
+            resource_type: RenderResourceType::Texture,
+        }];
+        INPUT
+    }
+
+    fn input_texture_usage(&self, _index: usize) -> Option<TextureUsage> {
+        Some(TextureUsage::SAMPLED)
+    }
+
+    fn update(
+        &mut self,
+        world: &World,
+        resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const INPUT_TEXTURE: usize = 0;
+        if !resources
+            .get::<RenderCapabilities>()
+            .unwrap()
+            .supports_compute()
+        {
+            return;
+        }
+        self.frame += 1;
+        // the copies recorded in earlier frames have been submitted by now
+        self.read_histograms(render_context, resources);
+
+        let texture = if let Some(RenderResourceId::Texture(texture)) = input.get(INPUT_TEXTURE) {
+            texture
+        } else {
+            return;
+        };
+        let active_cameras = resources.get::<ActiveCameras>().unwrap();
+        let exposure = match active_cameras.get(base::camera::CAMERA3D) {
+            Some(camera) => world
+                .get::<Exposure>(camera)
+                .map(|exposure| *exposure)
+                .unwrap_or_else(|_| Exposure::unchanged()),
+            None => return,
+        };
+
+        let render_resource_context = render_context.resources();
+        let buffers = self
+            .buffers
+            .get_or_insert_with(|| HistogramBuffers::new(render_resource_context));
+        // every readback buffer is still on its way back, skip metering this frame
+        let readback = match buffers
+            .readbacks
+            .iter_mut()
+            .find(|readback| readback.state == ReadbackState::Free)
+        {
+            Some(readback) => readback,
+            None => return,
+        };
+        let size = match render_resource_context.get_texture_descriptor(texture) {
+            Some(descriptor) => descriptor.size,
+            None => return,
+        };
+
+        let bind_group_descriptor = {
+            let shaders = resources.get::<Assets<Shader>>().unwrap();
+            let mut pipelines = resources
+                .get_mut::<Assets<ComputePipelineDescriptor>>()
+                .unwrap();
+            let pipeline = pipelines
+                .get_mut(&LUMINANCE_HISTOGRAM_PIPELINE_HANDLE)
+                .unwrap();
+            if pipeline.layout.is_none() {
+                pipeline.reflect_layout(&shaders);
+            }
+            render_resource_context.create_compute_pipeline(
+                LUMINANCE_HISTOGRAM_PIPELINE_HANDLE,
+                pipeline,
+                &shaders,
+            );
+            pipeline.get_layout().unwrap().bind_groups[0].id
+        };
+        let bind_group = BindGroup::build()
+            .add_buffer(0, buffers.params, 0..PARAMS_SIZE)
+            .add_texture(1, texture)
+            .add_sampler(2, buffers.sampler)
+            .add_buffer(3, buffers.bins, 0..BINS_SIZE)
+            .finish();
+        // bind groups are cleared at the end of every frame
+        render_resource_context.create_bind_group(bind_group_descriptor, &bind_group);
+
+        let params =
+            HistogramParams::new(&resources.get::<LuminanceHistogram>().unwrap(), &exposure);
+        render_resource_context.map_buffer(buffers.staging);
+        render_resource_context.write_mapped_buffer(
+            buffers.staging,
+            0..PARAMS_SIZE + BINS_SIZE,
+            &mut |data, _renderer| {
+                data[0..PARAMS_SIZE as usize].copy_from_slice(params.as_bytes());
+                for value in data[PARAMS_SIZE as usize..].iter_mut() {
+                    *value = 0;
+                }
+            },
+        );
+        render_resource_context.unmap_buffer(buffers.staging);
+
+        render_context.copy_buffer_to_buffer(buffers.staging, 0, buffers.params, 0, PARAMS_SIZE);
+        render_context.copy_buffer_to_buffer(
+            buffers.staging,
+            PARAMS_SIZE,
+            buffers.bins,
+            0,
+            BINS_SIZE,
+        );
+        render_context.begin_compute_pass(&mut |compute_pass| {
+            compute_pass.set_pipeline(&LUMINANCE_HISTOGRAM_PIPELINE_HANDLE);
+            compute_pass.set_bind_group(0, bind_group_descriptor, bind_group.id, None);
+            compute_pass.dispatch(
+                (size.width + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+                (size.height + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+                1,
+            );
+        });
+        render_context.copy_buffer_to_buffer(buffers.bins, 0, readback.buffer, 0, BINS_SIZE);
+        readback.state = ReadbackState::Copied;
+        readback.frame = self.frame;
+    }
+}
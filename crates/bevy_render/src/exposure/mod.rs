@@ -0,0 +1,231 @@
+//! Camera exposure and tonemapping.
+//!
+//! Scene colors are multiplied with the [Exposure] of the camera and tonemapped at the end of the main pass shaders
+//! that read the camera's exposure, like the pbr forward shader. [AutoExposurePlugin] adapts the exposure of cameras
+//! with [AutoExposure] to the brightness of the scene. A compute shader builds the [LuminanceHistogram] from the colors
+//! the main pass rendered, and the histogram is read back without waiting for the gpu, a couple of frames later.
+
+mod histogram;
+mod luminance_histogram_node;
+
+pub use histogram::*;
+pub use luminance_histogram_node::*;
+
+use crate::{
+    pipeline::ComputePipelineDescriptor,
+    render_graph::{
+        base::{self, BaseRenderGraphBuilder},
+        RenderGraph,
+    },
+    shader::{Shader, ShaderStage},
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_core::Time;
+use bevy_ecs::{IntoQuerySystem, Query, Res};
+use bevy_property::Properties;
+use bevy_type_registry::{RegisterType, TypeUuid};
+use serde::{Deserialize, Serialize};
+
+/// How exposed colors are mapped to the displayable range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tonemapping {
+    /// Colors above 1.0 are clipped
+    None,
+    Reinhard,
+}
+
+impl Default for Tonemapping {
+    fn default() -> Self {
+        Tonemapping::Reinhard
+    }
+}
+
+/// The exposure of a camera, in EV100: the exposure value of a camera with an ISO 100 sensor. Brighter scenes need
+/// higher exposure values. Cameras without this component show scene colors unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Properties)]
+pub struct Exposure {
+    pub ev100: f32,
+    #[property(ignore)]
+    pub tonemapping: Tonemapping,
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Exposure {
+            ev100: Exposure::EV100_UNITY,
+            tonemapping: Tonemapping::default(),
+        }
+    }
+}
+
+impl Exposure {
+    /// The exposure at which scene colors are shown unchanged
+    pub const EV100_UNITY: f32 = -0.263_034_4;
+
+    /// The exposure of a physical camera with the given f-number, shutter speed in seconds and ISO sensitivity
+    pub fn from_physical(aperture: f32, shutter_speed: f32, iso: f32) -> Self {
+        Exposure {
+            ev100: (aperture * aperture / shutter_speed * 100.0 / iso).log2(),
+            ..Default::default()
+        }
+    }
+
+    /// The exposure that shows scene colors unchanged and doesn't tonemap them
+    pub fn unchanged() -> Self {
+        Exposure {
+            ev100: Exposure::EV100_UNITY,
+            tonemapping: Tonemapping::None,
+        }
+    }
+
+    /// The factor scene colors are multiplied with
+    pub fn multiplier(&self) -> f32 {
+        1.0 / (1.2 * self.ev100.exp2())
+    }
+}
+
+/// Adapts the [Exposure] of a camera to the average brightness of the scene measured by [AutoExposurePlugin]
+#[derive(Debug, Clone, Properties)]
+pub struct AutoExposure {
+    pub min_ev100: f32,
+    pub max_ev100: f32,
+    /// Added to the measured exposure value. Negative values brighten the image.
+    pub compensation: f32,
+    /// How fast the exposure adapts when the scene gets darker, in stops per second at a difference of one stop
+    pub speed_brighten: f32,
+    /// How fast the exposure adapts when the scene gets brighter
+    pub speed_darken: f32,
+    /// The fraction of the darkest samples that are ignored
+    pub low_percentile: f32,
+    /// Samples brighter than this fraction of the samples are ignored
+    pub high_percentile: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        AutoExposure {
+            min_ev100: -8.0,
+            max_ev100: 16.0,
+            compensation: 0.0,
+            speed_brighten: 1.5,
+            speed_darken: 3.0,
+            low_percentile: 0.5,
+            high_percentile: 0.95,
+        }
+    }
+}
+
+impl AutoExposure {
+    /// The exposure value that brings the `average_luminance` of a scene to middle gray
+    pub fn target_ev100(&self, average_luminance: f32) -> f32 {
+        // the standard reflected-light meter calibration, with a calibration constant of 12.5
+        let ev100 = (average_luminance * 100.0 / 12.5).log2() + self.compensation;
+        ev100.max(self.min_ev100).min(self.max_ev100)
+    }
+}
+
+/// Adapts the [Exposure] of cameras with [AutoExposure] to the [LuminanceHistogram]
+pub fn auto_exposure_system(
+    time: Res<Time>,
+    histogram: Res<LuminanceHistogram>,
+    mut query: Query<(&AutoExposure, &mut Exposure)>,
+) {
+    for (auto_exposure, mut exposure) in query.iter_mut() {
+        let average_luminance = match histogram
+            .average_luminance(auto_exposure.low_percentile, auto_exposure.high_percentile)
+        {
+            Some(average_luminance) => average_luminance,
+            None => continue,
+        };
+
+        let target = auto_exposure.target_ev100(average_luminance);
+        let speed = if target < exposure.ev100 {
+            auto_exposure.speed_brighten
+        } else {
+            auto_exposure.speed_darken
+        };
+        let ev100 = exposure.ev100
+            + (target - exposure.ev100) * (1.0 - (-speed * time.raw_delta_seconds).exp());
+        if (ev100 - exposure.ev100).abs() > 1e-4 {
+            exposure.ev100 = ev100;
+        }
+    }
+}
+
+pub const LUMINANCE_HISTOGRAM_PIPELINE_HANDLE: Handle<ComputePipelineDescriptor> =
+    Handle::weak_from_u64(ComputePipelineDescriptor::TYPE_UUID, 15593348411852323860);
+
+pub mod node {
+    pub const LUMINANCE_HISTOGRAM: &str = "luminance_histogram";
+}
+
+/// Measures the brightness of what the 3d camera sees and adapts the [Exposure] of cameras with [AutoExposure] to it.
+/// The histogram is built from the colors the main pass rendered, so the plugin needs compute support. Add the plugin
+/// after the render plugins.
+#[derive(Default)]
+pub struct AutoExposurePlugin;
+
+impl Plugin for AutoExposurePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<LuminanceHistogram>()
+            .register_component::<AutoExposure>()
+            .add_system_to_stage(stage::POST_UPDATE, auto_exposure_system.system());
+
+        let resources = app.resources();
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let shader = shaders.add(Shader::from_glsl(
+            ShaderStage::Compute,
+            include_str!("luminance_histogram.comp"),
+        ));
+        resources
+            .get_mut::<Assets<ComputePipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(
+                LUMINANCE_HISTOGRAM_PIPELINE_HANDLE,
+                ComputePipelineDescriptor {
+                    name: Some("luminance_histogram".to_string()),
+                    ..ComputePipelineDescriptor::new(shader)
+                },
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        render_graph.add_auto_exposure_graph();
+    }
+}
+
+pub trait AutoExposureGraphBuilder {
+    fn add_auto_exposure_graph(&mut self) -> &mut Self;
+}
+
+impl AutoExposureGraphBuilder for RenderGraph {
+    fn add_auto_exposure_graph(&mut self) -> &mut Self {
+        self.add_node(node::LUMINANCE_HISTOGRAM, LuminanceHistogramNode::default());
+        self.add_node_edge(base::node::MAIN_PASS, node::LUMINANCE_HISTOGRAM)
+            .unwrap();
+
+        let (color_node, color_index) = self.add_sampled_main_color();
+        self.add_slot_edge(
+            color_node,
+            color_index,
+            node::LUMINANCE_HISTOGRAM,
+            LuminanceHistogramNode::IN_TEXTURE,
+        )
+        .unwrap();
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exposure_values() {
+        assert!((Exposure::default().multiplier() - 1.0).abs() < 1e-5);
+        // sunny 16: f/16, 1/100s, ISO 100
+        let sunny = Exposure::from_physical(16.0, 0.01, 100.0);
+        assert!((sunny.ev100 - 14.644).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,221 @@
+use bevy_app::prelude::*;
+use bevy_ecs::{Component, Entity, IntoThreadLocalSystem, Resources, World};
+use bevy_utils::{HashMap, HashSet};
+use std::{any::TypeId, marker::PhantomData};
+
+/// A component that is copied into the [RenderWorld] every frame, once it is registered with an
+/// [ExtractComponentPlugin].
+///
+/// `extract` can copy the component as is or transform it into what the renderer needs, like packing a material
+/// into gpu-ready values. Returning `None` leaves the entity's component out of the render world for this frame.
+///
+/// ```
+/// # use bevy_render::extract::ExtractComponent;
+/// #[derive(Clone)]
+/// struct Outline {
+///     width: f32,
+///     visible: bool,
+/// }
+///
+/// impl ExtractComponent for Outline {
+///     type Extracted = Outline;
+///
+///     fn extract(&self) -> Option<Outline> {
+///         if self.visible {
+///             Some(self.clone())
+///         } else {
+///             None
+///         }
+///     }
+/// }
+/// ```
+pub trait ExtractComponent: Component {
+    type Extracted: Component;
+
+    fn extract(&self) -> Option<Self::Extracted>;
+}
+
+/// The entity in the main world that a render world entity was extracted from
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MainEntity(pub Entity);
+
+/// A snapshot of the components the renderer was told about, taken in the [EXTRACT](crate::stage::EXTRACT) stage.
+///
+/// Every main world entity with at least one extracted component gets one render world entity that holds all of its
+/// extracted components and a [MainEntity]. The render world is rebuilt every frame, so render world entities only
+/// live for the frame they were extracted in. Render graph nodes and systems in later render stages read it with
+/// `resources.get::<RenderWorld>()`.
+///
+/// [RenderResourcesNode::extracted](crate::render_graph::RenderResourcesNode::extracted) binds extracted components
+/// to the entities they were extracted from, and
+/// [PassNode::query_render_world](crate::render_graph::PassNode::query_render_world) draws the entities whose
+/// extracted components match its query.
+#[derive(Default)]
+pub struct RenderWorld {
+    world: World,
+    render_entities: HashMap<Entity, Entity>,
+}
+
+impl RenderWorld {
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// The render world entity that `main_entity` was extracted to this frame
+    pub fn get_render_entity(&self, main_entity: Entity) -> Option<Entity> {
+        self.render_entities.get(&main_entity).copied()
+    }
+
+    /// Adds an extracted `component` of `main_entity` to its render world entity
+    pub fn insert(&mut self, main_entity: Entity, component: impl Component) {
+        let world = &mut self.world;
+        let render_entity = *self
+            .render_entities
+            .entry(main_entity)
+            .or_insert_with(|| world.spawn((MainEntity(main_entity),)));
+        self.world.insert_one(render_entity, component).unwrap();
+    }
+
+    pub fn clear(&mut self) {
+        self.world.clear();
+        // nothing reacts to removals in the render world, so they aren't kept around
+        self.world.clear_trackers();
+        self.render_entities.clear();
+    }
+}
+
+type Extractor = Box<dyn Fn(&World, &mut RenderWorld) + Send + Sync>;
+
+/// The extraction functions of the components registered with [ExtractComponentPlugin]
+#[derive(Default)]
+pub struct RenderWorldExtractors {
+    extractors: Vec<Extractor>,
+    registered: HashSet<TypeId>,
+}
+
+impl RenderWorldExtractors {
+    /// Registers the component `C` for extraction. Registering a component more than once has no effect.
+    pub fn add<C: ExtractComponent>(&mut self) {
+        if !self.registered.insert(TypeId::of::<C>()) {
+            return;
+        }
+
+        self.extractors
+            .push(Box::new(|world: &World, render_world: &mut RenderWorld| {
+                for (entity, component) in world.query::<(Entity, &C)>() {
+                    if let Some(extracted) = component.extract() {
+                        render_world.insert(entity, extracted);
+                    }
+                }
+            }));
+    }
+
+    pub fn extract(&self, world: &World, render_world: &mut RenderWorld) {
+        render_world.clear();
+        for extractor in self.extractors.iter() {
+            extractor(world, render_world);
+        }
+    }
+}
+
+/// Rebuilds the [RenderWorld] from the components registered in [RenderWorldExtractors]
+pub fn extract_render_world_system(world: &mut World, resources: &mut Resources) {
+    let extractors = resources.get::<RenderWorldExtractors>().unwrap();
+    let mut render_world = resources.get_mut::<RenderWorld>().unwrap();
+    extractors.extract(world, &mut render_world);
+}
+
+/// Copies the component `C` into the [RenderWorld] every frame
+pub struct ExtractComponentPlugin<C> {
+    marker: PhantomData<fn() -> C>,
+}
+
+impl<C> Default for ExtractComponentPlugin<C> {
+    fn default() -> Self {
+        ExtractComponentPlugin {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<C: ExtractComponent> Plugin for ExtractComponentPlugin<C> {
+    fn build(&self, app: &mut AppBuilder) {
+        app.resources()
+            .get_mut::<RenderWorldExtractors>()
+            .expect("ExtractComponentPlugin must be added after RenderPlugin")
+            .add::<C>();
+    }
+}
+
+pub(crate) fn add_extraction(app: &mut AppBuilder) {
+    app.init_resource::<RenderWorld>()
+        .init_resource::<RenderWorldExtractors>()
+        .add_system_to_stage(
+            crate::stage::EXTRACT,
+            extract_render_world_system.thread_local_system(),
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Health(u32);
+    struct HealthBar(f32);
+
+    impl ExtractComponent for Health {
+        type Extracted = HealthBar;
+
+        fn extract(&self) -> Option<HealthBar> {
+            if self.0 > 0 {
+                Some(HealthBar(self.0 as f32 / 100.0))
+            } else {
+                None
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct Tint(u8);
+
+    impl ExtractComponent for Tint {
+        type Extracted = Tint;
+
+        fn extract(&self) -> Option<Tint> {
+            Some(self.clone())
+        }
+    }
+
+    #[test]
+    fn extracts_registered_components() {
+        let mut world = World::new();
+        let hurt = world.spawn((Health(50), Tint(3)));
+        let dead = world.spawn((Health(0),));
+        let untinted = world.spawn((Tint(7), 1.0f32));
+
+        let mut extractors = RenderWorldExtractors::default();
+        extractors.add::<Health>();
+        extractors.add::<Tint>();
+        extractors.add::<Tint>();
+        let mut render_world = RenderWorld::default();
+        extractors.extract(&world, &mut render_world);
+        // extracting again replaces the previous frame
+        extractors.extract(&world, &mut render_world);
+
+        let hurt_render = render_world.get_render_entity(hurt).unwrap();
+        let render = render_world.world();
+        assert_eq!(render.get::<HealthBar>(hurt_render).unwrap().0, 0.5);
+        assert_eq!(render.get::<Tint>(hurt_render).unwrap().0, 3);
+        assert_eq!(render.get::<MainEntity>(hurt_render).unwrap().0, hurt);
+        assert!(render_world.get_render_entity(dead).is_none());
+
+        let untinted_render = render_world.get_render_entity(untinted).unwrap();
+        assert_eq!(render.get::<Tint>(untinted_render).unwrap().0, 7);
+        assert!(render.get::<f32>(untinted_render).is_err());
+        assert_eq!(render.query::<&MainEntity>().count(), 2);
+    }
+}
@@ -0,0 +1,541 @@
+//! Frustum culling of instances on the gpu.
+//!
+//! Entities with [GpuCulling] that draw many instances have their instance bounds tested against the view frustum of
+//! a camera by a compute shader every frame. The shader compacts the indices of the visible instances into a storage
+//! buffer and counts them into the arguments of an indirect draw, so the cpu never touches individual instances.
+//! Vertex shaders read the visible instances like this, when the `GPU_CULLING` shader def is set:
+//! ```glsl
+//! layout(set = 2, binding = 11) readonly buffer GpuCulling_visible {
+//!     uint VisibleInstances[];
+//! };
+//! // ...
+//! uint instance = VisibleInstances[gl_InstanceIndex];
+//! ```
+//! Without compute support, entities are drawn with all their instances as before. Occlusion culling against a depth
+//! pyramid isn't supported yet.
+
+use crate::{
+    camera::{ActiveCameras, Camera},
+    draw::{Draw, DrawIndexedIndirectArgs, INDIRECT_DRAW_ARGS, INDIRECT_DRAW_SHADER_DEF},
+    mesh::{Indices, Mesh},
+    pipeline::{BindGroupDescriptorId, ComputePipelineDescriptor, RenderPipelines},
+    render_graph::{base, CommandQueue, Node, RenderGraph, ResourceSlots, SystemNode},
+    renderer::{
+        BindGroup, BindGroupId, BufferId, BufferInfo, BufferUsage, RenderCapabilities,
+        RenderContext, RenderResourceBinding, RenderResourceContext, RenderResourceId,
+        RenderResourceOwner,
+    },
+    shader::{Shader, ShaderStage},
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_core::{AsBytes, Byteable};
+use bevy_ecs::{
+    Changed, Commands, Entity, IntoQuerySystem, Local, Query, Res, ResMut, Resources, System, World,
+};
+use bevy_math::Mat4;
+use bevy_transform::prelude::GlobalTransform;
+use bevy_type_registry::TypeUuid;
+use bevy_utils::HashMap;
+use parking_lot::Mutex;
+use std::{any::TypeId, borrow::Cow, ops::DerefMut, sync::Arc};
+
+pub const GPU_CULLING_PIPELINE_HANDLE: Handle<ComputePipelineDescriptor> =
+    Handle::weak_from_u64(ComputePipelineDescriptor::TYPE_UUID, 3170859260143622951);
+
+/// The shader def that is set on the pipelines of entities whose instances are culled on the gpu this frame
+pub const GPU_CULLING_SHADER_DEF: &str = "GPU_CULLING";
+/// The binding of the storage buffer with the indices of the visible instances
+pub const GPU_CULLING_VISIBLE: &str = "GpuCulling_visible";
+
+const WORKGROUP_SIZE: u32 = 64;
+
+pub mod node {
+    pub const GPU_CULLING: &str = "gpu_culling";
+}
+
+/// Culls the instances of an entity drawn with an [InstanceCount](crate::draw::InstanceCount) on the gpu. The entity
+/// is drawn with an indirect draw of the instances that are visible to the 3d camera, see the
+/// [module docs](crate::gpu_culling) for how its vertex shader reads them. Needs the [GpuCullingPlugin].
+///
+/// Other passes that draw the entity, like shadow passes, draw the same instances as the main pass.
+#[derive(Debug, Default, Clone)]
+pub struct GpuCulling {
+    /// The bounding sphere of each instance in the space of the entity. xyz: center, w: radius. Changing them
+    /// uploads them again, so keep changes rare.
+    pub instance_bounds: Vec<[f32; 4]>,
+}
+
+/// The per-entity uniform of the culling shader
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct CullParams {
+    planes: [[f32; 4]; 6],
+    instance_count: u32,
+    radius_scale: f32,
+    _padding: [u32; 2],
+}
+
+unsafe impl Byteable for CullParams {}
+
+const PARAMS_SIZE: u64 = std::mem::size_of::<CullParams>() as u64;
+const DRAW_ARGS_SIZE: u64 = std::mem::size_of::<DrawIndexedIndirectArgs>() as u64;
+
+impl CullParams {
+    /// Moves the frustum planes of `view_proj` into the space of `model`. The planes keep measuring world units, so
+    /// instance radii only need to be scaled by the largest scale of the model.
+    fn new(view_proj: Mat4, model: &GlobalTransform, instance_count: u32) -> Self {
+        let model_matrix = model.compute_matrix().to_cols_array_2d();
+        let mut planes = frustum_planes(view_proj);
+        for plane in planes.iter_mut() {
+            let world = *plane;
+            for (local, column) in plane.iter_mut().zip(model_matrix.iter()) {
+                *local = dot(column, &world);
+            }
+        }
+
+        let scale = model.scale;
+        CullParams {
+            planes,
+            instance_count,
+            radius_scale: scale.x().abs().max(scale.y().abs()).max(scale.z().abs()),
+            _padding: [0; 2],
+        }
+    }
+}
+
+/// The planes of the frustum of `view_proj`, with normals pointing inwards and normalized so that they return world
+/// distances. Clip space depth ranges from 0 to 1.
+fn frustum_planes(view_proj: Mat4) -> [[f32; 4]; 6] {
+    let columns = view_proj.to_cols_array_2d();
+    let row = |index: usize| {
+        [
+            columns[0][index],
+            columns[1][index],
+            columns[2][index],
+            columns[3][index],
+        ]
+    };
+    let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+    let add = |a: [f32; 4], b: [f32; 4]| [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]];
+    let sub = |a: [f32; 4], b: [f32; 4]| [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]];
+
+    let mut planes = [add(w, x), sub(w, x), add(w, y), sub(w, y), z, sub(w, z)];
+    for plane in planes.iter_mut() {
+        let length = (plane[0] * plane[0] + plane[1] * plane[1] + plane[2] * plane[2]).sqrt();
+        if length > 0.0 {
+            for value in plane.iter_mut() {
+                *value /= length;
+            }
+        }
+    }
+    planes
+}
+
+fn dot(a: &[f32; 4], b: &[f32; 4]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+}
+
+/// Culls the instances of [GpuCulling] entities against the frustum of a camera with a compute shader
+#[derive(Debug)]
+pub struct GpuCullingNode {
+    command_queue: CommandQueue,
+    dispatches: Arc<Mutex<Vec<CullDispatch>>>,
+    camera_name: Cow<'static, str>,
+}
+
+impl GpuCullingNode {
+    pub fn new<T>(camera_name: T) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        GpuCullingNode {
+            command_queue: Default::default(),
+            dispatches: Default::default(),
+            camera_name: camera_name.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CullDispatch {
+    bind_group_descriptor: BindGroupDescriptorId,
+    bind_group: BindGroupId,
+    workgroups: u32,
+}
+
+impl Node for GpuCullingNode {
+    fn update(
+        &mut self,
+        _world: &World,
+        _resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        // resets the draw args and writes the frustum before the shader runs
+        self.command_queue.execute(render_context);
+
+        let dispatches = std::mem::take(&mut *self.dispatches.lock());
+        if dispatches.is_empty() {
+            return;
+        }
+
+        render_context.begin_compute_pass(&mut |compute_pass| {
+            compute_pass.set_pipeline(&GPU_CULLING_PIPELINE_HANDLE);
+            for dispatch in dispatches.iter() {
+                compute_pass.set_bind_group(
+                    0,
+                    dispatch.bind_group_descriptor,
+                    dispatch.bind_group,
+                    None,
+                );
+                compute_pass.dispatch(dispatch.workgroups, 1, 1);
+            }
+        });
+    }
+}
+
+impl SystemNode for GpuCullingNode {
+    fn get_system(&self, commands: &mut Commands) -> Box<dyn System> {
+        let system = gpu_culling_node_system.system();
+        commands.insert_local_resource(
+            system.id(),
+            GpuCullingNodeState {
+                command_queue: self.command_queue.clone(),
+                dispatches: self.dispatches.clone(),
+                camera_name: self.camera_name.clone(),
+                buffers: Default::default(),
+            },
+        );
+        system
+    }
+}
+
+/// The buffers of one [GpuCulling] entity. They are owned by the entity and recreated when its bounds change.
+#[derive(Debug)]
+struct CullingBuffers {
+    params: BufferId,
+    visible: BufferId,
+    draw_args: BufferId,
+    /// Holds the params and the reset draw args of the next dispatch
+    staging: BufferId,
+    bind_group: BindGroup,
+    instance_count: u32,
+}
+
+impl CullingBuffers {
+    fn new(
+        owner: RenderResourceOwner,
+        instance_bounds: &[[f32; 4]],
+        render_resource_context: &dyn RenderResourceContext,
+    ) -> Self {
+        let instance_count = instance_bounds.len() as u32;
+        let params = render_resource_context.create_buffer(BufferInfo {
+            size: PARAMS_SIZE as usize,
+            buffer_usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+            ..Default::default()
+        });
+        let bounds = render_resource_context.create_buffer_with_data(
+            BufferInfo {
+                size: instance_bounds.as_bytes().len(),
+                buffer_usage: BufferUsage::STORAGE,
+                ..Default::default()
+            },
+            instance_bounds.as_bytes(),
+        );
+        let visible_size = instance_count as u64 * std::mem::size_of::<u32>() as u64;
+        let visible = render_resource_context.create_buffer(BufferInfo {
+            size: visible_size as usize,
+            buffer_usage: BufferUsage::STORAGE,
+            ..Default::default()
+        });
+        let draw_args = render_resource_context.create_buffer(BufferInfo {
+            size: DRAW_ARGS_SIZE as usize,
+            buffer_usage: BufferUsage::STORAGE | BufferUsage::INDIRECT | BufferUsage::COPY_DST,
+            ..Default::default()
+        });
+        let staging = render_resource_context.create_buffer(BufferInfo {
+            size: (PARAMS_SIZE + DRAW_ARGS_SIZE) as usize,
+            buffer_usage: BufferUsage::COPY_SRC | BufferUsage::MAP_WRITE,
+            mapped_at_creation: true,
+        });
+        for buffer in [params, bounds, visible, draw_args, staging].iter() {
+            render_resource_context.set_resource_owner(owner, RenderResourceId::Buffer(*buffer));
+        }
+
+        let bind_group = BindGroup::build()
+            .add_buffer(0, params, 0..PARAMS_SIZE)
+            .add_buffer(1, bounds, 0..instance_bounds.as_bytes().len() as u64)
+            .add_buffer(2, visible, 0..visible_size)
+            .add_buffer(3, draw_args, 0..DRAW_ARGS_SIZE)
+            .finish();
+
+        CullingBuffers {
+            params,
+            visible,
+            draw_args,
+            staging,
+            bind_group,
+            instance_count,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GpuCullingNodeState {
+    command_queue: CommandQueue,
+    dispatches: Arc<Mutex<Vec<CullDispatch>>>,
+    camera_name: Cow<'static, str>,
+    buffers: HashMap<Entity, CullingBuffers>,
+}
+
+fn owner(entity: Entity) -> RenderResourceOwner {
+    RenderResourceOwner::Entity(entity, TypeId::of::<GpuCulling>())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn gpu_culling_node_system(
+    mut state: Local<GpuCullingNodeState>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    render_capabilities: Res<RenderCapabilities>,
+    active_cameras: Res<ActiveCameras>,
+    shaders: Res<Assets<Shader>>,
+    meshes: Res<Assets<Mesh>>,
+    mut pipelines: ResMut<Assets<ComputePipelineDescriptor>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    changed_query: Query<(Entity, Changed<GpuCulling>)>,
+    mut query: Query<(
+        Entity,
+        &GpuCulling,
+        &GlobalTransform,
+        &Handle<Mesh>,
+        &Draw,
+        &mut RenderPipelines,
+    )>,
+) {
+    let state = state.deref_mut();
+    let render_resource_context = &**render_resource_context;
+
+    // this also covers despawned entities
+    for entity in query.removed::<GpuCulling>() {
+        state.buffers.remove(entity);
+        render_resource_context.release_owner_resources(owner(*entity));
+    }
+
+    // the bounds are uploaded once, so new bounds need new buffers
+    for (entity, _culling) in changed_query.iter() {
+        if state.buffers.remove(&entity).is_some() {
+            render_resource_context.release_owner_resources(owner(entity));
+        }
+    }
+
+    // without compute or a camera, entities draw all of their instances
+    if !render_capabilities.supports_compute() {
+        return;
+    }
+    let view_proj = match active_cameras
+        .get(&state.camera_name)
+        .and_then(|entity| cameras.get(entity).ok())
+    {
+        Some((camera, global_transform)) => {
+            camera.projection_matrix * global_transform.compute_matrix().inverse()
+        }
+        None => return,
+    };
+
+    if pipelines
+        .get(&GPU_CULLING_PIPELINE_HANDLE)
+        .unwrap()
+        .layout
+        .is_none()
+    {
+        pipelines
+            .get_mut(&GPU_CULLING_PIPELINE_HANDLE)
+            .unwrap()
+            .reflect_layout(&shaders);
+    }
+    let pipeline = pipelines.get(&GPU_CULLING_PIPELINE_HANDLE).unwrap();
+    render_resource_context.create_compute_pipeline(
+        GPU_CULLING_PIPELINE_HANDLE,
+        pipeline,
+        &shaders,
+    );
+    let bind_group_descriptor = pipeline.get_layout().unwrap().bind_groups[0].id;
+
+    let mut dispatches = state.dispatches.lock();
+    // left over if the node didn't run last frame
+    dispatches.clear();
+    for (entity, culling, global_transform, mesh_handle, draw, mut render_pipelines) in
+        query.iter_mut()
+    {
+        if !draw.is_visible || culling.instance_bounds.is_empty() {
+            continue;
+        }
+        let index_count = match meshes
+            .get(mesh_handle)
+            .and_then(|mesh| mesh.indices.as_ref())
+        {
+            Some(Indices::U16(indices)) => indices.len() as u32,
+            Some(Indices::U32(indices)) => indices.len() as u32,
+            None => continue,
+        };
+
+        let buffers = state.buffers.entry(entity).or_insert_with(|| {
+            CullingBuffers::new(
+                owner(entity),
+                &culling.instance_bounds,
+                render_resource_context,
+            )
+        });
+
+        let params = CullParams::new(view_proj, global_transform, buffers.instance_count);
+        let draw_args = DrawIndexedIndirectArgs {
+            index_count,
+            ..Default::default()
+        };
+        render_resource_context.map_buffer(buffers.staging);
+        render_resource_context.write_mapped_buffer(
+            buffers.staging,
+            0..PARAMS_SIZE + DRAW_ARGS_SIZE,
+            &mut |data, _renderer| {
+                data[0..PARAMS_SIZE as usize].copy_from_slice(params.as_bytes());
+                data[PARAMS_SIZE as usize..].copy_from_slice(draw_args.as_bytes());
+            },
+        );
+        render_resource_context.unmap_buffer(buffers.staging);
+        state.command_queue.copy_buffer_to_buffer(
+            buffers.staging,
+            0,
+            buffers.params,
+            0,
+            PARAMS_SIZE,
+        );
+        state.command_queue.copy_buffer_to_buffer(
+            buffers.staging,
+            PARAMS_SIZE,
+            buffers.draw_args,
+            0,
+            DRAW_ARGS_SIZE,
+        );
+
+        // bind groups are cleared at the end of every frame
+        render_resource_context.create_bind_group(bind_group_descriptor, &buffers.bind_group);
+        dispatches.push(CullDispatch {
+            bind_group_descriptor,
+            bind_group: buffers.bind_group.id,
+            workgroups: (buffers.instance_count + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE,
+        });
+
+        render_pipelines.bindings.set(
+            GPU_CULLING_VISIBLE,
+            RenderResourceBinding::Buffer {
+                buffer: buffers.visible,
+                range: 0..buffers.instance_count as u64 * std::mem::size_of::<u32>() as u64,
+                dynamic_index: None,
+            },
+        );
+        render_pipelines.bindings.set(
+            INDIRECT_DRAW_ARGS,
+            RenderResourceBinding::Buffer {
+                buffer: buffers.draw_args,
+                range: 0..DRAW_ARGS_SIZE,
+                dynamic_index: None,
+            },
+        );
+        for render_pipeline in render_pipelines.pipelines.iter_mut() {
+            let shader_defs = &mut render_pipeline
+                .specialization
+                .shader_specialization
+                .shader_defs;
+            shader_defs.insert(GPU_CULLING_SHADER_DEF.to_string());
+            shader_defs.insert(INDIRECT_DRAW_SHADER_DEF.to_string());
+        }
+    }
+}
+
+/// Adds the [GpuCullingNode] for the 3d camera, which culls the instances of [GpuCulling] entities before the main
+/// pass. Add it after the render plugins.
+#[derive(Default)]
+pub struct GpuCullingPlugin;
+
+impl Plugin for GpuCullingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let resources = app.resources();
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let shader = shaders.add(Shader::from_glsl(
+            ShaderStage::Compute,
+            include_str!("cull.comp"),
+        ));
+        resources
+            .get_mut::<Assets<ComputePipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(
+                GPU_CULLING_PIPELINE_HANDLE,
+                ComputePipelineDescriptor {
+                    name: Some("gpu_culling".to_string()),
+                    ..ComputePipelineDescriptor::new(shader)
+                },
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        render_graph.add_system_node(
+            node::GPU_CULLING,
+            GpuCullingNode::new(base::camera::CAMERA3D),
+        );
+        render_graph
+            .add_node_edge(node::GPU_CULLING, base::node::MAIN_PASS)
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::{Quat, Vec3};
+
+    fn distance(plane: &[f32; 4], point: Vec3) -> f32 {
+        dot(plane, &[point.x(), point.y(), point.z(), 1.0])
+    }
+
+    fn visible(params: &CullParams, center: Vec3, radius: f32) -> bool {
+        let radius = radius * params.radius_scale;
+        params
+            .planes
+            .iter()
+            .all(|plane| distance(plane, center) >= -radius)
+    }
+
+    #[test]
+    fn params_layout_matches_shader() {
+        // 6 planes, then the instance count and radius scale padded to a vec4
+        assert_eq!(PARAMS_SIZE, 112);
+    }
+
+    #[test]
+    fn culls_against_frustum_in_instance_space() {
+        // looks down -Z from the origin
+        let view_proj = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 100.0);
+
+        let params = CullParams::new(view_proj, &GlobalTransform::identity(), 1);
+        assert!(visible(&params, Vec3::new(0.0, 0.0, -10.0), 1.0));
+        assert!(!visible(&params, Vec3::new(0.0, 0.0, 10.0), 1.0));
+        assert!(!visible(&params, Vec3::new(0.0, 0.0, -110.0), 1.0));
+        assert!(!visible(&params, Vec3::new(20.0, 0.0, -10.0), 1.0));
+        // the sphere reaches into the frustum
+        assert!(visible(&params, Vec3::new(11.0, 0.0, -10.0), 2.0));
+
+        // the entity is moved behind the camera and scaled up, so distances are measured in world units
+        let model = GlobalTransform {
+            translation: Vec3::new(0.0, 0.0, 20.0),
+            rotation: Quat::identity(),
+            scale: Vec3::new(2.0, 2.0, 2.0),
+        };
+        let params = CullParams::new(view_proj, &model, 1);
+        assert!(!visible(&params, Vec3::new(0.0, 0.0, -5.0), 1.0));
+        assert!(visible(&params, Vec3::new(0.0, 0.0, -15.0), 1.0));
+        // at (22, 0, -11) in the world, about 7.8 units from the right plane
+        assert!(!visible(&params, Vec3::new(11.0, 0.0, -15.5), 3.5));
+        assert!(visible(&params, Vec3::new(11.0, 0.0, -15.5), 4.5));
+    }
+}
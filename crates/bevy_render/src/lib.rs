@@ -1,11 +1,18 @@
 pub mod camera;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod capture;
 pub mod color;
+pub mod color_grading;
 pub mod colorspace;
 pub mod draw;
 pub mod entity;
+pub mod exposure;
+pub mod extract;
+pub mod gpu_culling;
 pub mod mesh;
 pub mod pass;
 pub mod pipeline;
+pub mod quality;
 pub mod render_graph;
 pub mod renderer;
 pub mod shader;
@@ -17,43 +24,63 @@ pub use once_cell;
 pub mod prelude {
     pub use crate::{
         base::Msaa,
+        camera::{
+            CameraCollider, CameraControllerPlugin, CameraShake, CameraShakeEvent, FlyCamera,
+            FollowCamera, InZone, OrbitCamera, Portal, RenderLayers, Viewport, Zone,
+        },
         color::Color,
+        color_grading::{ColorGrading, ColorGradingPlugin},
         draw::Draw,
         entity::*,
-        mesh::{shape, Mesh},
+        exposure::{AutoExposure, AutoExposurePlugin, Exposure, Tonemapping},
+        extract::{ExtractComponent, ExtractComponentPlugin},
+        gpu_culling::{GpuCulling, GpuCullingPlugin},
+        mesh::{shape, Mesh, MeshLod},
         pass::ClearColor,
         pipeline::RenderPipelines,
+        quality::{GraphicsQuality, QualitySettings},
         shader::Shader,
         texture::Texture,
     };
 }
 
 use crate::prelude::*;
-use base::{MainPass, Msaa};
+use base::{MainPass, Msaa, SingleSampledFeatures};
 use bevy_app::prelude::*;
-use bevy_asset::AddAsset;
+use bevy_asset::{AddAsset, Assets};
 use bevy_ecs::{IntoQuerySystem, IntoThreadLocalSystem};
 use camera::{
-    ActiveCameras, Camera, OrthographicProjection, PerspectiveProjection, VisibleEntities,
+    ActiveCameras, Camera, CameraShake, CameraShakeEvent, OrthographicProjection,
+    PerspectiveProjection, RenderLayers, VisibleEntities, ZoneVisibility,
 };
+use color_grading::{CubeLutLoader, NEUTRAL_LUT_HANDLE};
 use pipeline::{
-    DynamicBinding, IndexFormat, PipelineCompiler, PipelineDescriptor, PipelineSpecialization,
-    PrimitiveTopology, ShaderSpecialization,
+    ComputePipelineDescriptor, CullMode, DynamicBinding, FrontFace, IndexFormat, PipelineCompiler,
+    PipelineDescriptor, PipelineSpecialization, PrimitiveTopology, RasterizationSpecialization,
+    ShaderSpecialization,
 };
 use render_graph::{
     base::{self, BaseRenderGraphBuilder, BaseRenderGraphConfig},
-    RenderGraph,
+    RenderGraph, RenderGraphBlackboard, RenderGraphDump, RenderGraphValidation,
 };
-use renderer::{AssetRenderResourceBindings, RenderResourceBindings};
+use renderer::{
+    AdapterInfo, AssetRenderResourceBindings, RenderCapabilities, RenderResourceBindings,
+};
+use shader::ShaderLoader;
 use std::ops::Range;
 #[cfg(feature = "hdr")]
 use texture::HdrTextureLoader;
 #[cfg(feature = "png")]
 use texture::ImageTextureLoader;
-use texture::TextureResourceSystemState;
+use texture::{
+    TextureRegionWrites, TextureResidency, TextureResourceSystemState, TextureStreaming,
+};
 
 /// The names of "render" App stages
 pub mod stage {
+    /// Stage where the components registered with an [ExtractComponentPlugin](crate::extract::ExtractComponentPlugin)
+    /// are copied into the [RenderWorld](crate::extract::RenderWorld)
+    pub static EXTRACT: &str = "extract";
     /// Stage where render resources are set up
     pub static RENDER_RESOURCE: &str = "render_resource";
     /// Stage where Render Graph systems are run. In general you shouldn't add systems to this stage manually.
@@ -89,11 +116,15 @@ impl Plugin for RenderPlugin {
             app.init_asset_loader::<HdrTextureLoader>();
         }
 
+        app.init_asset_loader::<ShaderLoader>()
+            .init_asset_loader::<CubeLutLoader>();
+
         if app.resources().get::<ClearColor>().is_none() {
             app.resources_mut().insert(ClearColor::default());
         }
 
-        app.add_stage_after(bevy_asset::stage::ASSET_EVENTS, stage::RENDER_RESOURCE)
+        app.add_stage_after(bevy_asset::stage::ASSET_EVENTS, stage::EXTRACT)
+            .add_stage_after(stage::EXTRACT, stage::RENDER_RESOURCE)
             .add_stage_after(stage::RENDER_RESOURCE, stage::RENDER_GRAPH_SYSTEMS)
             .add_stage_after(stage::RENDER_GRAPH_SYSTEMS, stage::DRAW)
             .add_stage_after(stage::DRAW, stage::RENDER)
@@ -102,26 +133,43 @@ impl Plugin for RenderPlugin {
             .add_asset::<Texture>()
             .add_asset::<Shader>()
             .add_asset::<PipelineDescriptor>()
+            .add_asset::<ComputePipelineDescriptor>()
+            .add_event::<CameraShakeEvent>()
             .register_component::<Camera>()
+            .register_component::<CameraShake>()
+            .register_component::<Exposure>()
+            .register_component::<ColorGrading>()
             .register_component::<Draw>()
             .register_component::<RenderPipelines>()
             .register_component::<OrthographicProjection>()
             .register_component::<PerspectiveProjection>()
             .register_component::<MainPass>()
             .register_component::<VisibleEntities>()
+            .register_component::<RenderLayers>()
             .register_property::<Color>()
             .register_property::<Range<f32>>()
             .register_property::<ShaderSpecialization>()
             .register_property::<DynamicBinding>()
             .register_property::<PrimitiveTopology>()
             .register_property::<IndexFormat>()
+            .register_property::<CullMode>()
+            .register_property::<FrontFace>()
+            .register_property::<RasterizationSpecialization>()
             .register_properties::<PipelineSpecialization>()
             .init_resource::<RenderGraph>()
+            .init_resource::<RenderGraphBlackboard>()
             .init_resource::<PipelineCompiler>()
             .init_resource::<RenderResourceBindings>()
             .init_resource::<TextureResourceSystemState>()
+            .init_resource::<TextureRegionWrites>()
+            .init_resource::<TextureStreaming>()
+            .init_resource::<TextureResidency>()
             .init_resource::<AssetRenderResourceBindings>()
             .init_resource::<ActiveCameras>()
+            .init_resource::<mesh::MeshBuffers>()
+            .init_resource::<ZoneVisibility>()
+            .init_resource::<AdapterInfo>()
+            .init_resource::<RenderCapabilities>()
             .add_system_to_stage(
                 bevy_app::stage::PRE_UPDATE,
                 draw::clear_draw_system.system(),
@@ -138,11 +186,34 @@ impl Plugin for RenderPlugin {
                 bevy_app::stage::POST_UPDATE,
                 camera::camera_system::<PerspectiveProjection>.system(),
             )
-            // registration order matters here. this must come after all camera_system::<T> systems
+            // registration order matters here. this must come after transform propagation
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                camera::camera_shake_system.system(),
+            )
+            .add_system_to_stage(bevy_app::stage::POST_UPDATE, mesh::mesh_lod_system.system())
+            // registration order matters here. these must come after all camera_system::<T> systems
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                camera::zone_visibility_system.system(),
+            )
             .add_system_to_stage(
                 bevy_app::stage::POST_UPDATE,
                 camera::visible_entities_system.system(),
             )
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                color_grading::color_grading_system.system(),
+            )
+            // registration order matters here. features that need a single sampled main pass override the quality
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                quality::graphics_quality_system.system(),
+            )
+            .add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                base::single_sampled_features_system.system(),
+            )
             // TODO: turn these "resource systems" into graph nodes and remove the RENDER_RESOURCE stage
             .add_system_to_stage(
                 stage::RENDER_RESOURCE,
@@ -160,17 +231,77 @@ impl Plugin for RenderPlugin {
             .add_system_to_stage(
                 stage::POST_RENDER,
                 shader::clear_shader_defs_system.system(),
+            )
+            .add_system_to_stage(
+                stage::POST_RENDER,
+                texture::texture_residency_system.system(),
+            )
+            .add_system_to_stage(
+                stage::POST_RENDER,
+                render_graph::render_graph_dump_system.thread_local_system(),
+            )
+            .add_system_to_stage(
+                stage::POST_RENDER,
+                renderer::free_released_render_resources_system.system(),
             );
 
-        if app.resources().get::<Msaa>().is_none() {
-            app.init_resource::<Msaa>();
+        extract::add_extraction(app);
+
+        {
+            let resources = app.resources();
+            let mut textures = resources.get_mut::<Assets<Texture>>().unwrap();
+            textures.set_untracked(NEUTRAL_LUT_HANDLE, color_grading::neutral_lut(16));
+            texture::add_fallback_textures(&mut textures);
+            let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+            shader::add_error_shaders(&mut shaders);
+            resources
+                .get_mut::<Assets<PipelineDescriptor>>()
+                .unwrap()
+                .set_untracked(
+                    render_graph::BLIT_PIPELINE_HANDLE,
+                    render_graph::build_blit_pipeline(&mut shaders),
+                );
+        }
+
+        if app.resources().get::<RenderGraphValidation>().is_none() {
+            app.init_resource::<RenderGraphValidation>();
+        }
+
+        if app.resources().get::<RenderGraphDump>().is_none() {
+            app.init_resource::<RenderGraphDump>();
+        }
+
+        app.resources_mut()
+            .get_or_insert_with(SingleSampledFeatures::default);
+
+        // Msaa follows GraphicsQuality, so an app that only inserts Msaa starts with a quality that has its samples
+        let quality = app
+            .resources()
+            .get::<GraphicsQuality>()
+            .map(|quality| *quality);
+        let msaa = app.resources().get::<Msaa>().map(|msaa| *msaa);
+        match (quality, msaa) {
+            (Some(quality), _) => {
+                app.add_resource(quality.settings().msaa());
+            }
+            (None, Some(msaa)) => {
+                let mut quality = GraphicsQuality::default();
+                if quality.settings().msaa() != msaa {
+                    quality.customize().msaa_samples = msaa.samples;
+                }
+                app.add_resource(quality);
+            }
+            (None, None) => {
+                let quality = GraphicsQuality::default();
+                app.add_resource(quality)
+                    .add_resource(quality.settings().msaa());
+            }
         }
 
         if let Some(ref config) = self.base_render_graph_config {
             let resources = app.resources();
             let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
-            let msaa = resources.get::<Msaa>().unwrap();
-            render_graph.add_base_graph(config, &msaa);
+            render_graph.add_base_graph(config);
             let mut active_cameras = resources.get_mut::<ActiveCameras>().unwrap();
             if config.add_3d_camera {
                 active_cameras.add(base::camera::CAMERA3D);
@@ -179,6 +310,34 @@ impl Plugin for RenderPlugin {
             if config.add_2d_camera {
                 active_cameras.add(base::camera::CAMERA2D);
             }
+
+            if config.exposes_main_depth_texture() {
+                resources
+                    .get_mut::<SingleSampledFeatures>()
+                    .unwrap()
+                    .add("the exposed main depth texture");
+                let mut textures = resources.get_mut::<Assets<Texture>>().unwrap();
+                textures.set_untracked(
+                    base::MAIN_DEPTH_TEXTURE_HANDLE,
+                    Texture {
+                        size: bevy_math::Vec2::new(1.0, 1.0),
+                        format: texture::TextureFormat::Depth32Float,
+                        usage: texture::TextureUsage::SAMPLED | texture::TextureUsage::COPY_DST,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        if self
+            .base_render_graph_config
+            .as_ref()
+            .map_or(false, |config| config.expose_main_depth_texture)
+        {
+            app.add_system_to_stage(
+                bevy_app::stage::POST_UPDATE,
+                base::main_depth_texture_size_system.system(),
+            );
         }
     }
 }
@@ -0,0 +1,55 @@
+use super::Mesh;
+use crate::{camera::ActiveCameras, render_graph::base};
+use bevy_asset::Handle;
+use bevy_ecs::{Query, Res};
+use bevy_transform::prelude::GlobalTransform;
+
+/// Swaps an entity's [Handle<Mesh>] for less detailed meshes as it gets further away from the 3d camera. Levels can be
+/// authored by hand or generated with [Mesh::simplified], which the GLTF loader does when a model's import settings ask
+/// for levels of detail.
+#[derive(Debug, Clone, Default)]
+pub struct MeshLod {
+    /// The meshes of each level, starting with the most detailed one
+    pub levels: Vec<Handle<Mesh>>,
+    /// The camera distances at which the entity switches to the next level
+    pub distances: Vec<f32>,
+}
+
+impl MeshLod {
+    /// The level to draw at `distance` from the camera
+    pub fn level(&self, distance: f32) -> usize {
+        let level = self
+            .distances
+            .iter()
+            .filter(|lod_distance| distance > **lod_distance)
+            .count();
+        level.min(self.levels.len().saturating_sub(1))
+    }
+}
+
+/// Selects the [MeshLod] level of each entity based on its distance to the 3d camera
+pub fn mesh_lod_system(
+    active_cameras: Res<ActiveCameras>,
+    camera_query: Query<&GlobalTransform>,
+    mut query: Query<(&MeshLod, &GlobalTransform, &mut Handle<Mesh>)>,
+) {
+    let camera_position = if let Some(camera_position) = active_cameras
+        .get(base::camera::CAMERA3D)
+        .and_then(|camera| camera_query.get(camera).ok())
+        .map(|transform| transform.translation)
+    {
+        camera_position
+    } else {
+        return;
+    };
+
+    for (lod, global_transform, mut mesh) in query.iter_mut() {
+        let distance = (global_transform.translation - camera_position).length();
+        if let Some(level_mesh) = lod.levels.get(lod.level(distance)) {
+            // only write the handle when the level changes, so the mesh isn't flagged as changed every frame
+            if *mesh != *level_mesh {
+                *mesh = level_mesh.clone();
+            }
+        }
+    }
+}
@@ -9,7 +9,9 @@ use bevy_ecs::{Local, Query, Res, ResMut};
 use bevy_math::*;
 use bevy_type_registry::TypeUuid;
 use std::borrow::Cow;
+use thiserror::Error;
 
+use super::{MeshBufferSet, MeshBuffers};
 use crate::pipeline::{InputStepMode, VertexAttributeDescriptor, VertexBufferDescriptor};
 use bevy_utils::HashMap;
 
@@ -38,6 +40,49 @@ impl VertexAttributeValues {
         self.len() == 0
     }
 
+    /// Creates new values by reading the value at each of the given `indices`
+    pub(super) fn duplicate(&self, indices: &Indices) -> VertexAttributeValues {
+        fn duplicate<T: Copy>(values: &[T], indices: &Indices) -> Vec<T> {
+            indices.iter().map(|index| values[index]).collect()
+        }
+
+        match self {
+            VertexAttributeValues::Float(values) => {
+                VertexAttributeValues::Float(duplicate(values, indices))
+            }
+            VertexAttributeValues::Float2(values) => {
+                VertexAttributeValues::Float2(duplicate(values, indices))
+            }
+            VertexAttributeValues::Float3(values) => {
+                VertexAttributeValues::Float3(duplicate(values, indices))
+            }
+            VertexAttributeValues::Float4(values) => {
+                VertexAttributeValues::Float4(duplicate(values, indices))
+            }
+        }
+    }
+
+    /// Appends `other` to the end of these values. Returns `false` if the formats don't match.
+    fn append(&mut self, other: &VertexAttributeValues) -> bool {
+        match (self, other) {
+            (VertexAttributeValues::Float(values), VertexAttributeValues::Float(other)) => {
+                values.extend_from_slice(other)
+            }
+            (VertexAttributeValues::Float2(values), VertexAttributeValues::Float2(other)) => {
+                values.extend_from_slice(other)
+            }
+            (VertexAttributeValues::Float3(values), VertexAttributeValues::Float3(other)) => {
+                values.extend_from_slice(other)
+            }
+            (VertexAttributeValues::Float4(values), VertexAttributeValues::Float4(other)) => {
+                values.extend_from_slice(other)
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
     // TODO: add vertex format as parameter here and perform type conversions
     pub fn get_bytes(&self) -> &[u8] {
         match self {
@@ -84,15 +129,66 @@ impl From<Vec<[f32; 4]>> for VertexAttributeValues {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Indices {
     U16(Vec<u16>),
     U32(Vec<u32>),
 }
+
+impl Indices {
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the indices, widened to `usize`
+    pub fn iter(&self) -> IndicesIter {
+        match self {
+            Indices::U16(indices) => IndicesIter::U16(indices.iter()),
+            Indices::U32(indices) => IndicesIter::U32(indices.iter()),
+        }
+    }
+}
+
+/// An iterator over the values of [Indices]
+pub enum IndicesIter<'a> {
+    U16(std::slice::Iter<'a, u16>),
+    U32(std::slice::Iter<'a, u32>),
+}
+
+impl Iterator for IndicesIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IndicesIter::U16(iter) => iter.next().map(|index| *index as usize),
+            IndicesIter::U32(iter) => iter.next().map(|index| *index as usize),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MeshMergeError {
+    #[error("Cannot merge a mesh with {other:?} topology into a mesh with {this:?} topology.")]
+    IncompatibleTopology {
+        this: PrimitiveTopology,
+        other: PrimitiveTopology,
+    },
+    #[error("Attribute {0} is not present in both meshes.")]
+    MissingAttribute(String),
+    #[error("Attribute {0} has a different format in each mesh.")]
+    IncompatibleAttribute(String),
+}
 // TODO: allow values to be unloaded after been submitting to the GPU to conserve memory
 pub type VertexAttributesHashMap = HashMap<Cow<'static, str>, VertexAttributeValues>;
 
-#[derive(Debug, TypeUuid)]
+#[derive(Debug, Clone, TypeUuid)]
 #[uuid = "8ecbac0f-f545-4473-ad43-e1f4243af51e"]
 pub struct Mesh {
     pub primitive_topology: PrimitiveTopology,
@@ -104,9 +200,16 @@ pub struct Mesh {
 }
 
 impl Mesh {
+    /// Per-vertex colors, stored as [VertexAttributeValues::Float4] in linear RGBA
+    pub const ATTRIBUTE_COLOR: &'static str = "Vertex_Color";
     pub const ATTRIBUTE_NORMAL: &'static str = "Vertex_Normal";
     pub const ATTRIBUTE_POSITION: &'static str = "Vertex_Position";
     pub const ATTRIBUTE_UV_0: &'static str = "Vertex_Uv";
+    /// A second set of texture coordinates, usually unique per surface so baked lightmaps can be applied
+    pub const ATTRIBUTE_UV_1: &'static str = "Vertex_Uv1";
+
+    /// The shader def that is defined for pipelines drawing a mesh with [Mesh::ATTRIBUTE_COLOR]
+    pub const VERTEX_COLOR_SHADER_DEF: &'static str = "MESH_VERTEX_COLOR";
 
     pub fn new(primitive_topology: PrimitiveTopology) -> Self {
         Mesh {
@@ -123,6 +226,203 @@ impl Mesh {
             Indices::U32(indices) => indices.as_slice().as_bytes().to_vec(),
         })
     }
+
+    /// The number of vertices in this mesh. Panics if the attributes have different lengths.
+    pub fn count_vertices(&self) -> usize {
+        attributes_count_vertices(&self.attributes).unwrap_or(0) as usize
+    }
+
+    pub(super) fn positions(&self) -> &[[f32; 3]] {
+        match self.attributes.get(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float3(positions)) => positions,
+            _ => panic!("Mesh::ATTRIBUTE_POSITION must be a Float3 attribute"),
+        }
+    }
+
+    /// Expands indexed geometry so that every index gets its own copy of the vertex it refers to, then removes the
+    /// indices. This does nothing if the mesh isn't indexed.
+    pub fn duplicate_vertices(&mut self) {
+        let indices = if let Some(indices) = self.indices.take() {
+            indices
+        } else {
+            return;
+        };
+
+        for values in self.attributes.values_mut() {
+            *values = values.duplicate(&indices);
+        }
+    }
+
+    /// Replaces [Mesh::ATTRIBUTE_NORMAL] with per-face normals. Faces can't share vertices when flat shaded, so
+    /// indexed meshes are expanded with [Mesh::duplicate_vertices] first.
+    ///
+    /// Panics if the mesh isn't a [PrimitiveTopology::TriangleList] or doesn't have Float3 positions.
+    pub fn compute_flat_normals(&mut self) {
+        assert_eq!(
+            self.primitive_topology,
+            PrimitiveTopology::TriangleList,
+            "Normals can only be computed for triangle lists"
+        );
+        self.duplicate_vertices();
+
+        let normals = self
+            .positions()
+            .chunks_exact(3)
+            .flat_map(|triangle| {
+                let normal: [f32; 3] = face_normal(triangle[0], triangle[1], triangle[2])
+                    .normalize()
+                    .into();
+                std::iter::repeat(normal).take(3)
+            })
+            .collect::<Vec<[f32; 3]>>();
+
+        self.attributes
+            .insert(Cow::Borrowed(Mesh::ATTRIBUTE_NORMAL), normals.into());
+    }
+
+    /// Replaces [Mesh::ATTRIBUTE_NORMAL] with normals averaged across every face that shares a vertex. Faces are
+    /// weighted by their area.
+    ///
+    /// Panics if the mesh isn't a [PrimitiveTopology::TriangleList] or doesn't have Float3 positions.
+    pub fn compute_smooth_normals(&mut self) {
+        assert_eq!(
+            self.primitive_topology,
+            PrimitiveTopology::TriangleList,
+            "Normals can only be computed for triangle lists"
+        );
+
+        let positions = self.positions();
+        let indices = match self.indices {
+            Some(ref indices) => indices.iter().collect::<Vec<usize>>(),
+            None => (0..positions.len()).collect::<Vec<usize>>(),
+        };
+
+        let mut normals = vec![Vec3::zero(); positions.len()];
+        for triangle in indices.chunks_exact(3) {
+            // the cross product isn't normalized, so larger faces contribute more
+            let normal = face_normal(
+                positions[triangle[0]],
+                positions[triangle[1]],
+                positions[triangle[2]],
+            );
+            for index in triangle.iter() {
+                normals[*index] += normal;
+            }
+        }
+
+        let normals = normals
+            .into_iter()
+            .map(|normal| {
+                if normal.length_squared() > 0.0 {
+                    normal.normalize().into()
+                } else {
+                    [0.0, 0.0, 0.0]
+                }
+            })
+            .collect::<Vec<[f32; 3]>>();
+
+        self.attributes
+            .insert(Cow::Borrowed(Mesh::ATTRIBUTE_NORMAL), normals.into());
+    }
+
+    /// Transforms the positions and normals of this mesh by `transform`
+    pub fn transform_by(&mut self, transform: Mat4) {
+        if let Some(VertexAttributeValues::Float3(positions)) =
+            self.attributes.get_mut(Mesh::ATTRIBUTE_POSITION)
+        {
+            for position in positions.iter_mut() {
+                *position = transform.transform_point3(Vec3::from(*position)).into();
+            }
+        }
+
+        if let Some(VertexAttributeValues::Float3(normals)) =
+            self.attributes.get_mut(Mesh::ATTRIBUTE_NORMAL)
+        {
+            // normals must be transformed by the inverse transpose to stay perpendicular under non-uniform scale
+            let normal_transform = transform.inverse().transpose();
+            for normal in normals.iter_mut() {
+                let transformed = normal_transform.transform_vector3(Vec3::from(*normal));
+                if transformed.length_squared() > 0.0 {
+                    *normal = transformed.normalize().into();
+                }
+            }
+        }
+    }
+
+    /// Appends `other`, transformed by `transform`, to this mesh. Both meshes must have the same topology and the same
+    /// set of attributes.
+    pub fn merge(&mut self, other: &Mesh, transform: Mat4) -> Result<(), MeshMergeError> {
+        if self.primitive_topology != other.primitive_topology {
+            return Err(MeshMergeError::IncompatibleTopology {
+                this: self.primitive_topology,
+                other: other.primitive_topology,
+            });
+        }
+
+        // everything is checked before this mesh is changed, so a failed merge leaves it as it was
+        for (name, values) in self.attributes.iter() {
+            let other_values = other
+                .attributes
+                .get(name)
+                .ok_or_else(|| MeshMergeError::MissingAttribute(name.to_string()))?;
+            if VertexFormat::from(values) != VertexFormat::from(other_values) {
+                return Err(MeshMergeError::IncompatibleAttribute(name.to_string()));
+            }
+        }
+        if let Some(name) = other
+            .attributes
+            .keys()
+            .find(|name| !self.attributes.contains_key(*name))
+        {
+            return Err(MeshMergeError::MissingAttribute(name.to_string()));
+        }
+
+        let mut other = other.clone();
+        other.transform_by(transform);
+
+        let vertex_offset = self.count_vertices();
+        let other_vertex_count = other.count_vertices();
+        for (name, values) in self.attributes.iter_mut() {
+            let appended = values.append(&other.attributes[name]);
+            debug_assert!(appended, "attribute formats are checked before merging");
+        }
+
+        // if either mesh is indexed the merged mesh needs indices for both. Indices of either type are widened, so
+        // the index types never conflict.
+        if self.indices.is_some() || other.indices.is_some() {
+            let mut indices = match self.indices.take() {
+                Some(indices) => indices
+                    .iter()
+                    .map(|index| index as u32)
+                    .collect::<Vec<u32>>(),
+                None => (0..vertex_offset as u32).collect::<Vec<u32>>(),
+            };
+            match other.indices {
+                Some(ref other_indices) => indices.extend(
+                    other_indices
+                        .iter()
+                        .map(|index| (index + vertex_offset) as u32),
+                ),
+                None => indices
+                    .extend((vertex_offset..vertex_offset + other_vertex_count).map(|i| i as u32)),
+            }
+
+            self.indices = Some(if vertex_offset + other_vertex_count <= u16::MAX as usize {
+                Indices::U16(indices.into_iter().map(|index| index as u16).collect())
+            } else {
+                Indices::U32(indices)
+            });
+        }
+
+        self.attribute_buffer_descriptor_reference = None;
+        Ok(())
+    }
+}
+
+/// The (non-normalized) normal of a counter-clockwise triangle. Its length is twice the triangle's area.
+pub(super) fn face_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> Vec3 {
+    let (a, b, c) = (Vec3::from(a), Vec3::from(b), Vec3::from(c));
+    (b - a).cross(c - a)
 }
 
 /// Generation for some primitive shape meshes.
@@ -427,25 +727,24 @@ pub mod shape {
     }
 }
 
-fn remove_resource_save(
-    render_resource_context: &dyn RenderResourceContext,
-    handle: &Handle<Mesh>,
-    index: u64,
-) {
-    if let Some(RenderResourceId::Buffer(buffer)) =
-        render_resource_context.get_asset_resource(&handle, index)
-    {
-        render_resource_context.remove_buffer(buffer);
-        render_resource_context.remove_asset_resource(handle, index);
-    }
+fn free_mesh_buffers(render_resource_context: &dyn RenderResourceContext, buffers: MeshBufferSet) {
+    render_resource_context.release_resource(RenderResourceId::Buffer(buffers.index));
+    render_resource_context.release_resource(RenderResourceId::Buffer(buffers.vertex));
+    render_resource_context.release_resource(RenderResourceId::Buffer(buffers.vertex_fallback));
 }
+
 fn remove_current_mesh_resources(
     render_resource_context: &dyn RenderResourceContext,
+    mesh_buffers: &mut MeshBuffers,
     handle: &Handle<Mesh>,
 ) {
-    remove_resource_save(render_resource_context, handle, VERTEX_ATTRIBUTE_BUFFER_ID);
-    remove_resource_save(render_resource_context, handle, VERTEX_FALLBACK_BUFFER_ID);
-    remove_resource_save(render_resource_context, handle, INDEX_BUFFER_ASSET_INDEX);
+    // other meshes with the same data may still use the buffers
+    if let Some(buffers) = mesh_buffers.release(handle.id) {
+        free_mesh_buffers(render_resource_context, buffers);
+    }
+    render_resource_context.remove_asset_resource(handle, VERTEX_ATTRIBUTE_BUFFER_ID);
+    render_resource_context.remove_asset_resource(handle, VERTEX_FALLBACK_BUFFER_ID);
+    render_resource_context.remove_asset_resource(handle, INDEX_BUFFER_ASSET_INDEX);
 }
 
 #[derive(Default)]
@@ -457,6 +756,7 @@ pub fn mesh_resource_provider_system(
     mut state: Local<MeshResourceProviderState>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut mesh_buffers: ResMut<MeshBuffers>,
     mesh_events: Res<Events<AssetEvent<Mesh>>>,
     mut query: Query<(&Handle<Mesh>, &mut RenderPipelines)>,
 ) {
@@ -464,15 +764,13 @@ pub fn mesh_resource_provider_system(
     let render_resource_context = &**render_resource_context;
     for event in state.mesh_event_reader.iter(&mesh_events) {
         match event {
-            AssetEvent::Created { ref handle } => {
-                changed_meshes.insert(handle.clone_weak());
-            }
-            AssetEvent::Modified { ref handle } => {
+            // a handle can be set again without being removed first, e.g. after `Assets::clear`
+            AssetEvent::Created { ref handle } | AssetEvent::Modified { ref handle } => {
                 changed_meshes.insert(handle.clone_weak());
-                remove_current_mesh_resources(render_resource_context, handle);
+                remove_current_mesh_resources(render_resource_context, &mut mesh_buffers, handle);
             }
             AssetEvent::Removed { ref handle } => {
-                remove_current_mesh_resources(render_resource_context, handle);
+                remove_current_mesh_resources(render_resource_context, &mut mesh_buffers, handle);
                 // if mesh was modified and removed in the same update, ignore the modification
                 // events are ordered so future modification events are ok
                 changed_meshes.remove(handle);
@@ -484,49 +782,69 @@ pub fn mesh_resource_provider_system(
     for changed_mesh_handle in changed_meshes.iter() {
         if let Some(mesh) = meshes.get_mut(changed_mesh_handle) {
             // TODO: check for individual buffer changes in non-interleaved mode
-            let index_buffer = render_resource_context.create_buffer_with_data(
-                BufferInfo {
-                    buffer_usage: BufferUsage::INDEX,
-                    ..Default::default()
+            let index_bytes = mesh.get_index_buffer_bytes().unwrap();
+            let vertex_count = attributes_count_vertices(&mesh.attributes).unwrap();
+            let (vertex_bytes, vertex_buffer_descriptor) =
+                attributes_to_vertex_buffer_data(&mesh.attributes, vertex_count);
+            mesh.attribute_buffer_descriptor_reference = Some(vertex_buffer_descriptor);
+
+            let (buffers, released) = mesh_buffers.get_or_create(
+                changed_mesh_handle.id,
+                &index_bytes,
+                &vertex_bytes,
+                vertex_count,
+                || {
+                    let index = render_resource_context.create_buffer_with_data(
+                        BufferInfo {
+                            buffer_usage: BufferUsage::INDEX,
+                            ..Default::default()
+                        },
+                        &index_bytes,
+                    );
+                    let vertex = render_resource_context.create_buffer_with_data(
+                        BufferInfo {
+                            buffer_usage: BufferUsage::VERTEX,
+                            ..Default::default()
+                        },
+                        &vertex_bytes,
+                    );
+                    // TODO: can be done with a 1 byte buffer + zero stride?
+                    let fallback_bytes =
+                        vec![0; (vertex_count * VertexFormat::Float4.get_size() as u32) as usize];
+                    let vertex_fallback = render_resource_context.create_buffer_with_data(
+                        BufferInfo {
+                            buffer_usage: BufferUsage::VERTEX,
+                            ..Default::default()
+                        },
+                        &fallback_bytes,
+                    );
+                    (
+                        MeshBufferSet {
+                            index,
+                            vertex,
+                            vertex_fallback,
+                        },
+                        index_bytes.len() + vertex_bytes.len() + fallback_bytes.len(),
+                    )
                 },
-                &mesh.get_index_buffer_bytes().unwrap(),
             );
+            if let Some(released) = released {
+                free_mesh_buffers(render_resource_context, released);
+            }
 
             render_resource_context.set_asset_resource(
                 changed_mesh_handle,
-                RenderResourceId::Buffer(index_buffer),
+                RenderResourceId::Buffer(buffers.index),
                 INDEX_BUFFER_ASSET_INDEX,
             );
-
-            // Vertex buffer
-            let vertex_count = attributes_count_vertices(&mesh.attributes).unwrap();
-            let interleaved_buffer =
-                attributes_to_vertex_buffer_data(&mesh.attributes, vertex_count);
-
-            mesh.attribute_buffer_descriptor_reference = Some(interleaved_buffer.1);
             render_resource_context.set_asset_resource(
                 changed_mesh_handle,
-                RenderResourceId::Buffer(render_resource_context.create_buffer_with_data(
-                    BufferInfo {
-                        buffer_usage: BufferUsage::VERTEX,
-                        ..Default::default()
-                    },
-                    &interleaved_buffer.0,
-                )),
+                RenderResourceId::Buffer(buffers.vertex),
                 VERTEX_ATTRIBUTE_BUFFER_ID,
             );
-
-            // Fallback buffer
-            // TODO: can be done with a 1 byte buffer + zero stride?
             render_resource_context.set_asset_resource(
                 changed_mesh_handle,
-                RenderResourceId::Buffer(render_resource_context.create_buffer_with_data(
-                    BufferInfo {
-                        buffer_usage: BufferUsage::VERTEX,
-                        ..Default::default()
-                    },
-                    &vec![0; (vertex_count * VertexFormat::Float4.get_size() as u32) as usize],
-                )),
+                RenderResourceId::Buffer(buffers.vertex_fallback),
                 VERTEX_FALLBACK_BUFFER_ID,
             );
         }
@@ -536,8 +854,16 @@ pub fn mesh_resource_provider_system(
     // TODO: remove this once batches are pipeline specific and deprecate assigned_meshes draw target
     for (handle, mut render_pipelines) in query.iter_mut() {
         if let Some(mesh) = meshes.get(handle) {
+            let has_vertex_colors = mesh.attributes.contains_key(Mesh::ATTRIBUTE_COLOR);
             for render_pipeline in render_pipelines.pipelines.iter_mut() {
                 render_pipeline.specialization.primitive_topology = mesh.primitive_topology;
+                if has_vertex_colors {
+                    render_pipeline
+                        .specialization
+                        .shader_specialization
+                        .shader_defs
+                        .insert(Mesh::VERTEX_COLOR_SHADER_DEF.to_string());
+                }
             }
 
             if let Some(RenderResourceId::Buffer(index_buffer_resource)) =
@@ -628,3 +954,111 @@ pub fn attributes_to_vertex_buffer_data(
         vertex_buffer_descriptor_reference,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Indices, Mesh, MeshMergeError, VertexAttributeValues};
+    use crate::pipeline::PrimitiveTopology;
+    use bevy_math::{Mat4, Vec3};
+    use std::borrow::Cow;
+
+    fn triangle() -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.attributes.insert(
+            Cow::Borrowed(Mesh::ATTRIBUTE_POSITION),
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]].into(),
+        );
+        mesh.indices = Some(Indices::U32(vec![0, 1, 2]));
+        mesh
+    }
+
+    fn normals(mesh: &Mesh) -> &[[f32; 3]] {
+        match mesh.attributes.get(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float3(normals)) => normals,
+            _ => panic!("mesh has no normals"),
+        }
+    }
+
+    #[test]
+    fn flat_normals() {
+        let mut mesh = triangle();
+        mesh.compute_flat_normals();
+        assert!(mesh.indices.is_none());
+        assert_eq!(mesh.count_vertices(), 3);
+        assert_eq!(normals(&mesh), &[[0.0, 0.0, 1.0]; 3]);
+    }
+
+    #[test]
+    fn smooth_normals() {
+        let mut mesh = triangle();
+        mesh.compute_smooth_normals();
+        assert!(mesh.indices.is_some());
+        assert_eq!(normals(&mesh), &[[0.0, 0.0, 1.0]; 3]);
+    }
+
+    #[test]
+    fn merge() {
+        let mut mesh = triangle();
+        mesh.merge(
+            &triangle(),
+            Mat4::from_translation(Vec3::new(0.0, 0.0, 2.0)),
+        )
+        .unwrap();
+
+        assert_eq!(mesh.count_vertices(), 6);
+        assert_eq!(
+            mesh.indices
+                .as_ref()
+                .unwrap()
+                .iter()
+                .collect::<Vec<usize>>(),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        match mesh.attributes.get(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float3(positions)) => {
+                assert_eq!(positions[3], [0.0, 0.0, 2.0])
+            }
+            _ => panic!("mesh has no positions"),
+        }
+
+        let mut mesh_with_normals = triangle();
+        mesh_with_normals.compute_smooth_normals();
+        assert!(mesh.merge(&mesh_with_normals, Mat4::identity()).is_err());
+    }
+
+    #[test]
+    fn failed_merge_leaves_mesh_unchanged() {
+        let mut mesh = triangle();
+        mesh.attributes.insert(
+            Cow::Borrowed(Mesh::ATTRIBUTE_NORMAL),
+            vec![[0.0, 0.0, 1.0]; 3].into(),
+        );
+        mesh.attributes.insert(
+            Cow::Borrowed(Mesh::ATTRIBUTE_UV_0),
+            vec![[0.0, 0.0]; 3].into(),
+        );
+        let mut other = triangle();
+        other.attributes.insert(
+            Cow::Borrowed(Mesh::ATTRIBUTE_NORMAL),
+            vec![[0.0, 0.0, 1.0]; 3].into(),
+        );
+        other.attributes.insert(
+            Cow::Borrowed(Mesh::ATTRIBUTE_UV_0),
+            vec![[0.0, 0.0, 0.0]; 3].into(),
+        );
+
+        let before = mesh.clone();
+        assert!(matches!(
+            mesh.merge(&other, Mat4::identity()),
+            Err(MeshMergeError::IncompatibleAttribute(_))
+        ));
+        assert_eq!(mesh.count_vertices(), 3);
+        for (name, values) in before.attributes.iter() {
+            assert_eq!(mesh.attributes[name].get_bytes(), values.get_bytes());
+        }
+        assert_eq!(
+            mesh.indices.as_ref().unwrap().iter().collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::renderer::BufferId;
+use bevy_asset::HandleId;
+use bevy_utils::HashMap;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// The gpu buffers a mesh is drawn with
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MeshBufferSet {
+    pub index: BufferId,
+    pub vertex: BufferId,
+    pub vertex_fallback: BufferId,
+}
+
+/// Buckets the gpu data of meshes by a hash of its contents. Meshes in the same bucket only share buffers if their
+/// data is actually the same.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct MeshBufferKey {
+    hash: u64,
+    vertex_count: u32,
+}
+
+impl MeshBufferKey {
+    /// The vertex count is part of the key because the size of the fallback buffer depends on it
+    fn new(index_bytes: &[u8], vertex_bytes: &[u8], vertex_count: u32) -> Self {
+        let mut hasher = DefaultHasher::new();
+        index_bytes.hash(&mut hasher);
+        vertex_bytes.hash(&mut hasher);
+        MeshBufferKey {
+            hash: hasher.finish(),
+            vertex_count,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SharedMeshBufferSet {
+    buffers: MeshBufferSet,
+    /// The data the buffers were created from, to tell apart meshes whose hashes collide
+    index_bytes: Vec<u8>,
+    vertex_bytes: Vec<u8>,
+    bytes: usize,
+    meshes: usize,
+}
+
+/// Shares gpu buffers between mesh assets with identical vertex and index data, like the same shape added to
+/// [Assets](bevy_asset::Assets) once per prop. The buffers are freed when the last mesh that uses them is modified
+/// or removed.
+#[derive(Debug, Default)]
+pub struct MeshBuffers {
+    sets: HashMap<MeshBufferKey, Vec<SharedMeshBufferSet>>,
+    /// The bucket of each mesh's buffers and the index buffer that identifies them within it
+    meshes: HashMap<HandleId, (MeshBufferKey, BufferId)>,
+}
+
+impl MeshBuffers {
+    /// Returns the buffers of `mesh` for the given data, creating them with `create` if no other mesh has the same
+    /// data. `create` returns the buffers and their size in bytes. If `mesh` had buffers for other data, it stops
+    /// using them, and they are returned as well if no other mesh uses them, which the caller should then free.
+    pub fn get_or_create(
+        &mut self,
+        mesh: HandleId,
+        index_bytes: &[u8],
+        vertex_bytes: &[u8],
+        vertex_count: u32,
+        create: impl FnOnce() -> (MeshBufferSet, usize),
+    ) -> (MeshBufferSet, Option<MeshBufferSet>) {
+        let key = MeshBufferKey::new(index_bytes, vertex_bytes, vertex_count);
+        let matches = |set: &SharedMeshBufferSet| {
+            set.index_bytes == index_bytes && set.vertex_bytes == vertex_bytes
+        };
+
+        let mut released = None;
+        if let Some(&(previous_key, index)) = self.meshes.get(&mesh) {
+            if let Some(set) = self.find(previous_key, index) {
+                if previous_key == key && matches(set) {
+                    return (set.buffers, None);
+                }
+            }
+            released = self.release(mesh);
+        }
+
+        let sets = self.sets.entry(key).or_insert_with(Vec::new);
+        let set = match sets.iter().position(matches) {
+            Some(position) => &mut sets[position],
+            None => {
+                let (buffers, bytes) = create();
+                sets.push(SharedMeshBufferSet {
+                    buffers,
+                    index_bytes: index_bytes.to_vec(),
+                    vertex_bytes: vertex_bytes.to_vec(),
+                    bytes,
+                    meshes: 0,
+                });
+                sets.last_mut().unwrap()
+            }
+        };
+        set.meshes += 1;
+        self.meshes.insert(mesh, (key, set.buffers.index));
+        (set.buffers, released)
+    }
+
+    fn find(&self, key: MeshBufferKey, index: BufferId) -> Option<&SharedMeshBufferSet> {
+        self.sets
+            .get(&key)?
+            .iter()
+            .find(|set| set.buffers.index == index)
+    }
+
+    /// Stops `mesh` from using its buffers. Returns the buffers if no other mesh uses them, which the caller should
+    /// then free.
+    pub fn release(&mut self, mesh: HandleId) -> Option<MeshBufferSet> {
+        let (key, index) = self.meshes.remove(&mesh)?;
+        let sets = self.sets.get_mut(&key)?;
+        let position = sets.iter().position(|set| set.buffers.index == index)?;
+        sets[position].meshes -= 1;
+        if sets[position].meshes > 0 {
+            return None;
+        }
+
+        let set = sets.swap_remove(position);
+        if sets.is_empty() {
+            self.sets.remove(&key);
+        }
+        Some(set.buffers)
+    }
+
+    /// The number of meshes that have gpu buffers
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.len()
+    }
+
+    /// The number of distinct buffer sets the meshes share
+    pub fn buffer_set_count(&self) -> usize {
+        self.sets.values().map(|sets| sets.len()).sum()
+    }
+
+    /// How many meshes use each buffer set on average. 1.0 means no buffers are shared.
+    pub fn deduplication_ratio(&self) -> f64 {
+        if self.sets.is_empty() {
+            1.0
+        } else {
+            self.meshes.len() as f64 / self.buffer_set_count() as f64
+        }
+    }
+
+    /// The size of all mesh buffers in bytes
+    pub fn bytes(&self) -> usize {
+        self.sets().map(|set| set.bytes).sum()
+    }
+
+    /// The bytes meshes would additionally use if each of them had its own buffers
+    pub fn saved_bytes(&self) -> usize {
+        self.sets().map(|set| set.bytes * (set.meshes - 1)).sum()
+    }
+
+    fn sets(&self) -> impl Iterator<Item = &SharedMeshBufferSet> {
+        self.sets.values().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::Mesh;
+
+    fn buffer_set() -> MeshBufferSet {
+        MeshBufferSet {
+            index: BufferId::new(),
+            vertex: BufferId::new(),
+            vertex_fallback: BufferId::new(),
+        }
+    }
+
+    #[test]
+    fn identical_meshes_share_buffers() {
+        let mut mesh_buffers = MeshBuffers::default();
+        let crate_a = HandleId::random::<Mesh>();
+        let crate_b = HandleId::random::<Mesh>();
+        let ball = HandleId::random::<Mesh>();
+
+        let (shared, _) =
+            mesh_buffers.get_or_create(crate_a, &[0, 1, 2], &[1; 36], 3, || (buffer_set(), 100));
+        assert_eq!(
+            mesh_buffers.get_or_create(crate_b, &[0, 1, 2], &[1; 36], 3, || {
+                panic!("buffers are shared")
+            }),
+            (shared, None)
+        );
+        assert_eq!(
+            mesh_buffers.get_or_create(crate_a, &[0, 1, 2], &[1; 36], 3, || unreachable!()),
+            (shared, None)
+        );
+        let (ball_buffers, _) =
+            mesh_buffers.get_or_create(ball, &[0, 2, 1], &[1; 36], 3, || (buffer_set(), 40));
+        assert_ne!(ball_buffers, shared);
+        assert_eq!(mesh_buffers.mesh_count(), 3);
+        assert_eq!(mesh_buffers.buffer_set_count(), 2);
+        assert_eq!(mesh_buffers.deduplication_ratio(), 1.5);
+        assert_eq!(mesh_buffers.bytes(), 140);
+        assert_eq!(mesh_buffers.saved_bytes(), 100);
+
+        assert_eq!(mesh_buffers.release(crate_a), None);
+        assert_eq!(mesh_buffers.release(crate_a), None);
+        assert_eq!(mesh_buffers.release(crate_b), Some(shared));
+        assert_eq!(mesh_buffers.saved_bytes(), 0);
+        assert_eq!(mesh_buffers.deduplication_ratio(), 1.0);
+    }
+
+    #[test]
+    fn colliding_hashes_keep_separate_buffers() {
+        let mut mesh_buffers = MeshBuffers::default();
+        let a = HandleId::random::<Mesh>();
+        let b = HandleId::random::<Mesh>();
+        let (a_buffers, _) =
+            mesh_buffers.get_or_create(a, &[0, 1, 2], &[1; 36], 3, || (buffer_set(), 10));
+
+        // forces b's different data into a's bucket, as if their hashes collided
+        let key = MeshBufferKey::new(&[0, 1, 2], &[1; 36], 3);
+        let b_buffers = buffer_set();
+        mesh_buffers
+            .sets
+            .get_mut(&key)
+            .unwrap()
+            .push(SharedMeshBufferSet {
+                buffers: b_buffers,
+                index_bytes: vec![2, 1, 0],
+                vertex_bytes: vec![2; 36],
+                bytes: 10,
+                meshes: 1,
+            });
+        mesh_buffers.meshes.insert(b, (key, b_buffers.index));
+
+        assert_eq!(mesh_buffers.buffer_set_count(), 2);
+        assert_eq!(mesh_buffers.release(b), Some(b_buffers));
+        assert_eq!(
+            mesh_buffers.get_or_create(a, &[0, 1, 2], &[1; 36], 3, || unreachable!()),
+            (a_buffers, None)
+        );
+    }
+
+    #[test]
+    fn new_data_replaces_the_buffers_of_a_mesh() {
+        let mut mesh_buffers = MeshBuffers::default();
+        let a = HandleId::random::<Mesh>();
+        let (old, _) =
+            mesh_buffers.get_or_create(a, &[0, 1, 2], &[1; 36], 3, || (buffer_set(), 10));
+
+        // e.g. `Assets::clear` followed by `Assets::set` with another mesh, which sends no `Removed` event
+        let (new, released) =
+            mesh_buffers.get_or_create(a, &[0, 2, 1], &[2; 36], 3, || (buffer_set(), 10));
+        assert_ne!(new, old);
+        assert_eq!(released, Some(old));
+        assert_eq!(mesh_buffers.mesh_count(), 1);
+        assert_eq!(mesh_buffers.buffer_set_count(), 1);
+    }
+}
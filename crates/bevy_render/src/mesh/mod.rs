@@ -1,4 +1,9 @@
+mod lod;
 #[allow(clippy::module_inception)]
 mod mesh;
+mod mesh_buffers;
+mod simplify;
 
+pub use lod::*;
 pub use mesh::*;
+pub use mesh_buffers::*;
@@ -0,0 +1,365 @@
+use super::{face_normal, Indices, Mesh};
+use crate::pipeline::PrimitiveTopology;
+use bevy_math::Vec3;
+use bevy_utils::{HashMap, HashSet};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+/// The error of a point measured against a set of planes: the sum of the squared distances between the point and each
+/// plane. The symmetric 4x4 matrix is stored as its upper triangle.
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// The plane `normal . p + d = 0`, weighted by `weight`
+    fn from_plane(normal: Vec3, d: f32, weight: f32) -> Self {
+        let (a, b, c, d) = (
+            normal.x() as f64,
+            normal.y() as f64,
+            normal.z() as f64,
+            d as f64,
+        );
+        let weight = weight as f64;
+        Quadric([
+            a * a * weight,
+            a * b * weight,
+            a * c * weight,
+            a * d * weight,
+            b * b * weight,
+            b * c * weight,
+            b * d * weight,
+            c * c * weight,
+            c * d * weight,
+            d * d * weight,
+        ])
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (value, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *value += other;
+        }
+    }
+
+    fn error(&self, point: Vec3) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (point.x() as f64, point.y() as f64, point.z() as f64);
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/// Merging `from` into `to`, valid as long as neither vertex changed since the cost was computed
+#[derive(Debug)]
+struct Collapse {
+    cost: f64,
+    from: usize,
+    to: usize,
+    from_version: u32,
+    to_version: u32,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cost
+            .partial_cmp(&other.cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+struct Simplifier<'a> {
+    positions: &'a [[f32; 3]],
+    triangles: Vec<[usize; 3]>,
+    triangle_alive: Vec<bool>,
+    vertex_triangles: Vec<Vec<usize>>,
+    quadrics: Vec<Quadric>,
+    /// Vertices on open borders, which includes the seams where vertices are split to give faces different normals or
+    /// uvs. Moving them would tear the mesh open.
+    locked: Vec<bool>,
+    collapsed: Vec<bool>,
+    versions: Vec<u32>,
+    collapses: BinaryHeap<Reverse<Collapse>>,
+}
+
+impl<'a> Simplifier<'a> {
+    fn new(positions: &'a [[f32; 3]], triangles: Vec<[usize; 3]>) -> Self {
+        let vertex_count = positions.len();
+        let mut quadrics = vec![Quadric::default(); vertex_count];
+        let mut vertex_triangles = vec![Vec::new(); vertex_count];
+        let mut edge_counts = HashMap::<(usize, usize), u32>::default();
+        for (index, triangle) in triangles.iter().enumerate() {
+            let normal = face_normal(
+                positions[triangle[0]],
+                positions[triangle[1]],
+                positions[triangle[2]],
+            );
+            let area = normal.length() / 2.0;
+            if area > 0.0 {
+                let normal = normal.normalize();
+                let d = -normal.dot(Vec3::from(positions[triangle[0]]));
+                // weighting by area keeps many small faces from outvoting a few large ones
+                let quadric = Quadric::from_plane(normal, d, area);
+                for vertex in triangle.iter() {
+                    quadrics[*vertex].add(&quadric);
+                }
+            }
+
+            for (corner, vertex) in triangle.iter().enumerate() {
+                vertex_triangles[*vertex].push(index);
+                let next = triangle[(corner + 1) % 3];
+                *edge_counts
+                    .entry((*vertex.min(&next), *vertex.max(&next)))
+                    .or_insert(0) += 1;
+            }
+        }
+
+        let mut locked = vec![false; vertex_count];
+        for ((a, b), count) in edge_counts.iter() {
+            if *count == 1 {
+                locked[*a] = true;
+                locked[*b] = true;
+            }
+        }
+
+        let mut simplifier = Simplifier {
+            positions,
+            triangle_alive: vec![true; triangles.len()],
+            triangles,
+            vertex_triangles,
+            quadrics,
+            locked,
+            collapsed: vec![false; vertex_count],
+            versions: vec![0; vertex_count],
+            collapses: BinaryHeap::new(),
+        };
+        for (a, b) in edge_counts.keys() {
+            simplifier.push_edge(*a, *b);
+        }
+        simplifier
+    }
+
+    fn position(&self, vertex: usize) -> Vec3 {
+        Vec3::from(self.positions[vertex])
+    }
+
+    /// Queues the collapse of the edge in both directions
+    fn push_edge(&mut self, a: usize, b: usize) {
+        for (from, to) in [(a, b), (b, a)].iter() {
+            if self.locked[*from] {
+                continue;
+            }
+            let mut quadric = self.quadrics[*from];
+            quadric.add(&self.quadrics[*to]);
+            self.collapses.push(Reverse(Collapse {
+                cost: quadric.error(self.position(*to)),
+                from: *from,
+                to: *to,
+                from_version: self.versions[*from],
+                to_version: self.versions[*to],
+            }));
+        }
+    }
+
+    fn is_valid(&self, collapse: &Collapse) -> bool {
+        if self.collapsed[collapse.from]
+            || self.collapsed[collapse.to]
+            || self.versions[collapse.from] != collapse.from_version
+            || self.versions[collapse.to] != collapse.to_version
+        {
+            return false;
+        }
+
+        let mut shares_triangle = false;
+        for triangle in self.vertex_triangles[collapse.from].iter() {
+            if !self.triangle_alive[*triangle] {
+                continue;
+            }
+            let vertices = self.triangles[*triangle];
+            if vertices.contains(&collapse.to) {
+                shares_triangle = true;
+                continue;
+            }
+
+            // moving the vertex must not turn any of the remaining triangles inside out
+            let corners = |moved: Vec3| {
+                let mut corners = [[0.0; 3]; 3];
+                for (corner, vertex) in corners.iter_mut().zip(vertices.iter()) {
+                    *corner = if *vertex == collapse.from {
+                        moved.into()
+                    } else {
+                        self.positions[*vertex]
+                    };
+                }
+                face_normal(corners[0], corners[1], corners[2])
+            };
+            let before = corners(self.position(collapse.from));
+            let after = corners(self.position(collapse.to));
+            if before.dot(after) < 0.0 {
+                return false;
+            }
+        }
+        shares_triangle
+    }
+
+    fn collapse(&mut self, collapse: &Collapse) -> usize {
+        let (from, to) = (collapse.from, collapse.to);
+        let mut removed = 0;
+        for triangle in std::mem::take(&mut self.vertex_triangles[from]) {
+            if !self.triangle_alive[triangle] {
+                continue;
+            }
+            let vertices = &mut self.triangles[triangle];
+            if vertices.contains(&to) {
+                self.triangle_alive[triangle] = false;
+                removed += 1;
+            } else {
+                for vertex in vertices.iter_mut() {
+                    if *vertex == from {
+                        *vertex = to;
+                    }
+                }
+                self.vertex_triangles[to].push(triangle);
+            }
+        }
+
+        self.collapsed[from] = true;
+        let from_quadric = self.quadrics[from];
+        self.quadrics[to].add(&from_quadric);
+        self.versions[to] += 1;
+
+        let triangle_alive = &self.triangle_alive;
+        self.vertex_triangles[to].retain(|triangle| triangle_alive[*triangle]);
+        let neighbors = self.vertex_triangles[to]
+            .iter()
+            .flat_map(|triangle| self.triangles[*triangle].iter().cloned())
+            .filter(|vertex| *vertex != to)
+            .collect::<HashSet<_>>();
+        for neighbor in neighbors {
+            self.push_edge(to, neighbor);
+        }
+        removed
+    }
+}
+
+impl Mesh {
+    /// Returns a copy of this mesh with about `target_ratio` of its triangles. Triangles are removed by collapsing the
+    /// edges whose removal changes the surface the least, as measured by quadric error metrics. A collapsed vertex is
+    /// merged into the other vertex of its edge, so every attribute keeps its original values.
+    ///
+    /// Vertices on open borders and seams are kept in place, so meshes with many seams may end up with more triangles
+    /// than requested.
+    ///
+    /// Panics if the mesh isn't a [PrimitiveTopology::TriangleList] or doesn't have Float3 positions.
+    pub fn simplified(&self, target_ratio: f32) -> Mesh {
+        assert_eq!(
+            self.primitive_topology,
+            PrimitiveTopology::TriangleList,
+            "Only triangle lists can be simplified"
+        );
+
+        let positions = self.positions();
+        let indices = match self.indices {
+            Some(ref indices) => indices.iter().collect::<Vec<usize>>(),
+            None => (0..positions.len()).collect::<Vec<usize>>(),
+        };
+        let triangles = indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect::<Vec<_>>();
+        let target_triangles =
+            (triangles.len() as f32 * target_ratio.max(0.0).min(1.0)).ceil() as usize;
+
+        let mut simplifier = Simplifier::new(positions, triangles);
+        let mut triangle_count = simplifier.triangles.len();
+        while triangle_count > target_triangles {
+            let collapse = if let Some(Reverse(collapse)) = simplifier.collapses.pop() {
+                collapse
+            } else {
+                break;
+            };
+            if simplifier.is_valid(&collapse) {
+                triangle_count -= simplifier.collapse(&collapse);
+            }
+        }
+
+        // drop the vertices that are no longer referenced and renumber the rest
+        let mut remap = vec![None; positions.len()];
+        let mut kept = Vec::new();
+        let mut indices = Vec::with_capacity(triangle_count * 3);
+        for (triangle, alive) in simplifier
+            .triangles
+            .iter()
+            .zip(simplifier.triangle_alive.iter())
+        {
+            if !alive {
+                continue;
+            }
+            for vertex in triangle.iter() {
+                let index = *remap[*vertex].get_or_insert_with(|| {
+                    kept.push(*vertex as u32);
+                    kept.len() as u32 - 1
+                });
+                indices.push(index);
+            }
+        }
+
+        let kept = Indices::U32(kept);
+        let mut mesh = Mesh::new(self.primitive_topology);
+        for (name, values) in self.attributes.iter() {
+            mesh.attributes
+                .insert(name.clone(), values.duplicate(&kept));
+        }
+        mesh.indices = Some(if kept.len() <= u16::MAX as usize {
+            Indices::U16(indices.into_iter().map(|index| index as u16).collect())
+        } else {
+            Indices::U32(indices)
+        });
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{shape, Mesh};
+
+    #[test]
+    fn simplify_icosphere() {
+        let mesh = Mesh::from(shape::Icosphere {
+            radius: 1.0,
+            subdivisions: 4,
+        });
+        let triangles = mesh.indices.as_ref().unwrap().len() / 3;
+
+        let simplified = mesh.simplified(0.25);
+        let indices = simplified.indices.as_ref().unwrap();
+        assert!(indices.len() / 3 <= (triangles + 3) / 4);
+        assert!(!indices.is_empty());
+        assert!(simplified.count_vertices() < mesh.count_vertices());
+        assert!(indices
+            .iter()
+            .all(|index| index < simplified.count_vertices()));
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{
+    pipeline::{BindGroupDescriptorId, ComputePipelineDescriptor},
+    renderer::{BindGroupId, BufferId, RenderContext},
+};
+use bevy_asset::Handle;
+
+/// Records compute work. Begin one with [RenderContext::begin_compute_pass] once
+/// [RenderCapabilities::supports_compute](crate::renderer::RenderCapabilities::supports_compute) is true.
+pub trait ComputePass {
+    fn get_render_context(&self) -> &dyn RenderContext;
+    /// Sets a pipeline created with [RenderResourceContext::create_compute_pipeline](crate::renderer::RenderResourceContext::create_compute_pipeline)
+    fn set_pipeline(&mut self, pipeline_handle: &Handle<ComputePipelineDescriptor>);
+    fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group_descriptor_id: BindGroupDescriptorId,
+        bind_group: BindGroupId,
+        dynamic_uniform_indices: Option<&[u32]>,
+    );
+    /// Runs `x * y * z` work groups of the current pipeline
+    fn dispatch(&mut self, x: u32, y: u32, z: u32);
+    /// Runs the number of work groups stored as three consecutive `u32`s in `indirect_buffer`, for example by a
+    /// previous dispatch
+    fn dispatch_indirect(&mut self, indirect_buffer: BufferId, indirect_offset: u64);
+}
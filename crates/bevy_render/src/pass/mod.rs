@@ -1,8 +1,10 @@
+mod compute_pass;
 mod ops;
 #[allow(clippy::module_inception)]
 mod pass;
 mod render_pass;
 
+pub use compute_pass::*;
 pub use ops::*;
 pub use pass::*;
 pub use render_pass::*;
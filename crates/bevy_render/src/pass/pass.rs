@@ -1,10 +1,13 @@
 use super::Operations;
-use crate::{renderer::TextureId, Color};
+use crate::{
+    renderer::{BindingName, TextureId},
+    Color,
+};
 
 #[derive(Debug, Clone)]
 pub enum TextureAttachment {
     Id(TextureId),
-    Name(String),
+    Name(BindingName),
     Input(String),
 }
 
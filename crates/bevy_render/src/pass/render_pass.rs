@@ -11,9 +11,19 @@ pub trait RenderPass {
     fn set_vertex_buffer(&mut self, start_slot: u32, buffer: BufferId, offset: u64);
     fn set_pipeline(&mut self, pipeline_handle: &Handle<PipelineDescriptor>);
     fn set_viewport(&mut self, x: f32, y: f32, w: f32, h: f32, min_depth: f32, max_depth: f32);
+    /// Discards fragments outside of the given rectangle. The rectangle must lie within the attachments.
+    fn set_scissor_rect(&mut self, x: u32, y: u32, w: u32, h: u32);
     fn set_stencil_reference(&mut self, reference: u32);
     fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>);
     fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>);
+    /// Draws `count` times with consecutive [DrawIndexedIndirectArgs](crate::draw::DrawIndexedIndirectArgs) read from
+    /// `indirect_buffer`
+    fn draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: BufferId,
+        indirect_offset: u64,
+        count: u32,
+    );
     fn set_bind_group(
         &mut self,
         index: u32,
@@ -1,5 +1,8 @@
 use super::UniformProperty;
-use crate::texture::{TextureComponentType, TextureFormat, TextureViewDimension};
+use crate::{
+    renderer::BindingName,
+    texture::{TextureComponentType, TextureFormat, TextureViewDimension},
+};
 
 bitflags::bitflags! {
     pub struct BindingShaderStage: u32 {
@@ -11,7 +14,7 @@ bitflags::bitflags! {
 
 #[derive(Hash, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct BindingDescriptor {
-    pub name: String,
+    pub name: BindingName,
     pub index: u32,
     pub bind_type: BindType,
     pub shader_stage: BindingShaderStage,
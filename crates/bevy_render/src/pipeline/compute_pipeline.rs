@@ -0,0 +1,44 @@
+use super::PipelineLayout;
+use crate::shader::Shader;
+use bevy_asset::{Assets, Handle};
+use bevy_type_registry::TypeUuid;
+
+/// A compute shader and the layout of the resources it binds. Unlike render pipelines, compute pipelines aren't
+/// specialized, so the layout is reflected once from the shader.
+#[derive(Clone, Debug, TypeUuid)]
+#[uuid = "3a1e5c0b-9d4f-4f6a-8c2e-61b7d0a94e15"]
+pub struct ComputePipelineDescriptor {
+    pub name: Option<String>,
+    pub layout: Option<PipelineLayout>,
+    pub shader: Handle<Shader>,
+}
+
+impl ComputePipelineDescriptor {
+    pub fn new(shader: Handle<Shader>) -> Self {
+        ComputePipelineDescriptor {
+            name: None,
+            layout: None,
+            shader,
+        }
+    }
+
+    pub fn get_layout(&self) -> Option<&PipelineLayout> {
+        self.layout.as_ref()
+    }
+
+    pub fn get_layout_mut(&mut self) -> Option<&mut PipelineLayout> {
+        self.layout.as_mut()
+    }
+
+    /// Reflects the pipeline layout from its shader, compiling the shader to SpirV first if needed. Storage buffers are
+    /// writable unless they are declared `readonly`.
+    pub fn reflect_layout(&mut self, shaders: &Assets<Shader>) {
+        let shader = shaders
+            .get(&self.shader)
+            .unwrap()
+            .get_spirv_shader(None)
+            .expect("compute shaders compile before their layout is reflected");
+        let mut layouts = vec![shader.reflect_layout(false).unwrap()];
+        self.layout = Some(PipelineLayout::from_shader_layouts(&mut layouts));
+    }
+}
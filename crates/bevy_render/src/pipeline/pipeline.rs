@@ -12,11 +12,15 @@ use crate::{
 };
 use bevy_asset::Assets;
 use bevy_type_registry::TypeUuid;
+use std::borrow::Cow;
 
 #[derive(Clone, Debug, TypeUuid)]
 #[uuid = "ebfc1d11-a2a4-44cb-8f12-c49cc631146c"]
 pub struct PipelineDescriptor {
     pub name: Option<String>,
+    /// The pass that draws this pipeline, see [PassNode::set_pass_tag](crate::render_graph::PassNode::set_pass_tag).
+    /// Pipelines without a tag are drawn by passes without one, like the main pass.
+    pub pass_tag: Option<Cow<'static, str>>,
     pub layout: Option<PipelineLayout>,
     pub shader_stages: ShaderStages,
     pub rasterization_state: Option<RasterizationStateDescriptor>,
@@ -51,6 +55,7 @@ impl PipelineDescriptor {
     pub fn new(shader_stages: ShaderStages) -> Self {
         PipelineDescriptor {
             name: None,
+            pass_tag: None,
             layout: None,
             color_states: Vec::new(),
             depth_stencil_state: None,
@@ -67,6 +72,7 @@ impl PipelineDescriptor {
     pub fn default_config(shader_stages: ShaderStages) -> Self {
         PipelineDescriptor {
             name: None,
+            pass_tag: None,
             primitive_topology: PrimitiveTopology::TriangleList,
             layout: None,
             index_format: IndexFormat::Uint32,
@@ -1,11 +1,16 @@
-use super::{state_descriptors::PrimitiveTopology, IndexFormat, PipelineDescriptor};
+use super::{
+    state_descriptors::PrimitiveTopology, CullMode, FrontFace, IndexFormat, PipelineDescriptor,
+    RasterizationStateDescriptor,
+};
 use crate::{
     pipeline::{
         InputStepMode, VertexAttributeDescriptor, VertexBufferDescriptor, VertexFormat,
         VERTEX_FALLBACK_LAYOUT_NAME,
     },
     renderer::RenderResourceContext,
-    shader::{Shader, ShaderSource},
+    shader::{
+        Shader, ShaderError, ShaderSource, ERROR_FRAGMENT_SHADER_HANDLE, ERROR_VERTEX_SHADER_HANDLE,
+    },
 };
 use bevy_asset::{Assets, Handle};
 use bevy_property::{Properties, Property};
@@ -22,6 +27,7 @@ pub struct PipelineSpecialization {
     pub index_format: IndexFormat,
     pub mesh_attribute_layout: VertexBufferDescriptor,
     pub sample_count: u32,
+    pub rasterization: RasterizationSpecialization,
 }
 
 impl Default for PipelineSpecialization {
@@ -33,6 +39,7 @@ impl Default for PipelineSpecialization {
             dynamic_bindings: Default::default(),
             index_format: IndexFormat::Uint32,
             mesh_attribute_layout: Default::default(),
+            rasterization: Default::default(),
         }
     }
 }
@@ -49,6 +56,37 @@ pub struct ShaderSpecialization {
     pub shader_defs: HashSet<String>,
 }
 
+/// Overrides for a pipeline's [RasterizationStateDescriptor]. Fields set to `None` keep the value from the source
+/// pipeline.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, Property, Serialize, Deserialize)]
+pub struct RasterizationSpecialization {
+    pub cull_mode: Option<CullMode>,
+    pub front_face: Option<FrontFace>,
+}
+
+impl RasterizationSpecialization {
+    /// Disables back face culling so both sides of a mesh are drawn
+    pub fn double_sided() -> Self {
+        RasterizationSpecialization {
+            cull_mode: Some(CullMode::None),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cull_mode.is_none() && self.front_face.is_none()
+    }
+
+    pub fn apply(&self, rasterization_state: &mut RasterizationStateDescriptor) {
+        if let Some(cull_mode) = self.cull_mode {
+            rasterization_state.cull_mode = cull_mode;
+        }
+        if let Some(front_face) = self.front_face {
+            rasterization_state.front_face = front_face;
+        }
+    }
+}
+
 #[derive(Debug)]
 struct SpecializedShader {
     shader: Handle<Shader>,
@@ -79,7 +117,7 @@ impl PipelineCompiler {
         shaders: &mut Assets<Shader>,
         shader_handle: &Handle<Shader>,
         shader_specialization: &ShaderSpecialization,
-    ) -> Handle<Shader> {
+    ) -> Result<Handle<Shader>, ShaderError> {
         let specialized_shaders = self
             .specialized_shaders
             .entry(shader_handle.clone_weak())
@@ -89,7 +127,7 @@ impl PipelineCompiler {
 
         // don't produce new shader if the input source is already spirv
         if let ShaderSource::Spirv(_) = shader.source {
-            return shader_handle.clone_weak();
+            return Ok(shader_handle.clone_weak());
         }
 
         if let Some(specialized_shader) =
@@ -100,7 +138,7 @@ impl PipelineCompiler {
                 })
         {
             // if shader has already been compiled with current configuration, use existing shader
-            specialized_shader.shader.clone_weak()
+            Ok(specialized_shader.shader.clone_weak())
         } else {
             // if no shader exists with the current configuration, create new shader and compile
             let shader_def_vec = shader_specialization
@@ -108,14 +146,14 @@ impl PipelineCompiler {
                 .iter()
                 .cloned()
                 .collect::<Vec<String>>();
-            let compiled_shader = shader.get_spirv_shader(Some(&shader_def_vec));
+            let compiled_shader = shader.get_spirv_shader(Some(&shader_def_vec))?;
             let specialized_handle = shaders.add(compiled_shader);
             let weak_specialized_handle = specialized_handle.clone_weak();
             specialized_shaders.push(SpecializedShader {
                 shader: specialized_handle,
                 specialization: shader_specialization.clone(),
             });
-            weak_specialized_handle
+            Ok(weak_specialized_handle)
         }
     }
 
@@ -146,22 +184,44 @@ impl PipelineCompiler {
     ) -> Handle<PipelineDescriptor> {
         let source_descriptor = pipelines.get(source_pipeline).unwrap();
         let mut specialized_descriptor = source_descriptor.clone();
-        specialized_descriptor.shader_stages.vertex = self.compile_shader(
-            shaders,
-            &specialized_descriptor.shader_stages.vertex,
-            &pipeline_specialization.shader_specialization,
-        );
-        specialized_descriptor.shader_stages.fragment = specialized_descriptor
-            .shader_stages
-            .fragment
-            .as_ref()
-            .map(|fragment| {
-                self.compile_shader(
-                    shaders,
-                    fragment,
-                    &pipeline_specialization.shader_specialization,
-                )
+        let shader_specialization = &pipeline_specialization.shader_specialization;
+        let shader_stages = &specialized_descriptor.shader_stages;
+        let compiled_stages = self
+            .compile_shader(shaders, &shader_stages.vertex, shader_specialization)
+            .and_then(|vertex| {
+                let fragment = shader_stages
+                    .fragment
+                    .as_ref()
+                    .map(|fragment| self.compile_shader(shaders, fragment, shader_specialization))
+                    .transpose()?;
+                Ok((vertex, fragment))
             });
+        let (vertex, fragment) = match compiled_stages {
+            Ok(compiled_stages) => compiled_stages,
+            Err(err) => {
+                // both stages are replaced, because the inputs of the fragment shader must match the outputs of the
+                // vertex shader
+                log::error!(
+                    "{}. The pipeline {:?} draws with the error shaders instead.",
+                    err,
+                    source_pipeline
+                );
+                let vertex = self
+                    .compile_shader(shaders, &ERROR_VERTEX_SHADER_HANDLE, shader_specialization)
+                    .expect("The error vertex shader should compile");
+                let fragment = shader_stages.fragment.as_ref().map(|_| {
+                    self.compile_shader(
+                        shaders,
+                        &ERROR_FRAGMENT_SHADER_HANDLE,
+                        shader_specialization,
+                    )
+                    .expect("The error fragment shader should compile")
+                });
+                (vertex, fragment)
+            }
+        };
+        specialized_descriptor.shader_stages.vertex = vertex;
+        specialized_descriptor.shader_stages.fragment = fragment;
 
         specialized_descriptor.reflect_layout(
             shaders,
@@ -225,6 +285,13 @@ impl PipelineCompiler {
         specialized_descriptor.sample_count = pipeline_specialization.sample_count;
         specialized_descriptor.primitive_topology = pipeline_specialization.primitive_topology;
         specialized_descriptor.index_format = pipeline_specialization.index_format;
+        if !pipeline_specialization.rasterization.is_empty() {
+            pipeline_specialization.rasterization.apply(
+                specialized_descriptor
+                    .rasterization_state
+                    .get_or_insert_with(Default::default),
+            );
+        }
 
         let specialized_pipeline_handle = pipelines.add(specialized_descriptor);
         render_resource_context.create_render_pipeline(
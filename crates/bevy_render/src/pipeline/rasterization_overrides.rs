@@ -0,0 +1,29 @@
+use super::{RasterizationSpecialization, RenderPipelines};
+use bevy_asset::{Asset, Assets, Handle};
+use bevy_ecs::{Query, Res};
+
+/// Something (usually a material) that wants to change the rasterization state of the pipelines it is drawn with,
+/// such as disabling back face culling for double sided geometry.
+pub trait RasterizationOverrides {
+    fn rasterization_specialization(&self) -> RasterizationSpecialization;
+}
+
+/// Updates [RenderPipelines] with the latest [RasterizationOverrides] from a given asset type
+pub fn asset_rasterization_overrides_system<T: Asset>(
+    assets: Res<Assets<T>>,
+    mut query: Query<(&Handle<T>, &mut RenderPipelines)>,
+) where
+    T: RasterizationOverrides + Send + Sync + 'static,
+{
+    for (asset_handle, mut render_pipelines) in query.iter_mut() {
+        let rasterization = if let Some(asset) = assets.get(asset_handle) {
+            asset.rasterization_specialization()
+        } else {
+            continue;
+        };
+
+        for render_pipeline in render_pipelines.pipelines.iter_mut() {
+            render_pipeline.specialization.rasterization = rasterization;
+        }
+    }
+}
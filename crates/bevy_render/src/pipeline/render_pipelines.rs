@@ -1,9 +1,9 @@
 use super::{IndexFormat, PipelineDescriptor, PipelineSpecialization};
 use crate::{
-    draw::{Draw, DrawContext},
+    draw::{Draw, DrawContext, InstanceCount, INDIRECT_DRAW_ARGS, INDIRECT_DRAW_SHADER_DEF},
     mesh::{Indices, Mesh},
     prelude::Msaa,
-    renderer::RenderResourceBindings,
+    renderer::{RenderResourceBinding, RenderResourceBindings},
 };
 use bevy_asset::{Assets, Handle};
 use bevy_ecs::{Query, Res, ResMut};
@@ -77,10 +77,16 @@ pub fn draw_render_pipelines_system(
     mut render_resource_bindings: ResMut<RenderResourceBindings>,
     msaa: Res<Msaa>,
     meshes: Res<Assets<Mesh>>,
-    mut query: Query<(&mut Draw, &mut RenderPipelines, &Handle<Mesh>)>,
+    mut query: Query<(
+        &mut Draw,
+        &mut RenderPipelines,
+        &Handle<Mesh>,
+        Option<&InstanceCount>,
+    )>,
 ) {
-    for (mut draw, mut render_pipelines, mesh_handle) in query.iter_mut() {
-        if !draw.is_visible {
+    for (mut draw, mut render_pipelines, mesh_handle, instance_count) in query.iter_mut() {
+        let instance_count = instance_count.map_or(1, |count| count.0);
+        if !draw.is_visible || instance_count == 0 {
             continue;
         }
 
@@ -129,8 +135,25 @@ pub fn draw_render_pipelines_system(
                 .set_vertex_buffers_from_bindings(&mut draw, &[&render_pipelines.bindings])
                 .unwrap();
 
-            if let Some(indices) = index_range.clone() {
-                draw.draw_indexed(indices, 0, 0..1);
+            let indirect_draw_args = if render_pipeline
+                .specialization
+                .shader_specialization
+                .shader_defs
+                .contains(INDIRECT_DRAW_SHADER_DEF)
+            {
+                match render_pipelines.bindings.get(INDIRECT_DRAW_ARGS) {
+                    Some(RenderResourceBinding::Buffer { buffer, range, .. }) => {
+                        Some((*buffer, range.start))
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            if let Some((buffer, offset)) = indirect_draw_args {
+                draw.draw_indexed_indirect(buffer, offset, 1);
+            } else if let Some(indices) = index_range.clone() {
+                draw.draw_indexed(indices, 0, 0..instance_count);
             }
         }
     }
@@ -74,7 +74,7 @@ impl Default for PrimitiveTopology {
     }
 }
 
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, Property)]
 pub enum FrontFace {
     Ccw = 0,
     Cw = 1,
@@ -86,7 +86,7 @@ impl Default for FrontFace {
     }
 }
 
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, Property)]
 pub enum CullMode {
     None = 0,
     Front = 1,
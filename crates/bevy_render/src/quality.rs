@@ -0,0 +1,132 @@
+use crate::render_graph::base::Msaa;
+use bevy_ecs::{ChangedRes, ResMut};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU8;
+
+/// The graphics options that [GraphicsQuality] configures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualitySettings {
+    /// Samples per pixel. Only 1 and 4 are supported on every device.
+    pub msaa_samples: u32,
+    /// Maximum anisotropic filtering level of linearly filtered textures, or 1 to turn it off
+    pub anisotropy: u8,
+}
+
+impl QualitySettings {
+    pub const LOW: QualitySettings = QualitySettings {
+        msaa_samples: 1,
+        anisotropy: 1,
+    };
+
+    pub const MEDIUM: QualitySettings = QualitySettings {
+        msaa_samples: 1,
+        anisotropy: 4,
+    };
+
+    pub const HIGH: QualitySettings = QualitySettings {
+        msaa_samples: 4,
+        anisotropy: 16,
+    };
+
+    pub fn msaa(&self) -> Msaa {
+        Msaa {
+            samples: self.msaa_samples.max(1),
+        }
+    }
+
+    /// The anisotropy clamp texture samplers are created with
+    pub fn anisotropy_clamp(&self) -> Option<NonZeroU8> {
+        if self.anisotropy > 1 {
+            NonZeroU8::new(self.anisotropy)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for QualitySettings {
+    fn default() -> Self {
+        QualitySettings::MEDIUM
+    }
+}
+
+/// Configures the graphics options of all render plugins at once, for example from a settings menu. Every option
+/// applies as soon as the resource changes.
+///
+/// The [Msaa] resource follows [QualitySettings::msaa_samples], so [Msaa] itself only needs to be set for apps that
+/// don't use this resource. The renderer has no shadows, bloom or ambient occlusion yet, so there are no options for
+/// them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphicsQuality {
+    Low,
+    Medium,
+    High,
+    Custom(QualitySettings),
+}
+
+impl Default for GraphicsQuality {
+    fn default() -> Self {
+        GraphicsQuality::Medium
+    }
+}
+
+impl GraphicsQuality {
+    /// The presets, from lowest to highest quality
+    pub const PRESETS: [GraphicsQuality; 3] = [
+        GraphicsQuality::Low,
+        GraphicsQuality::Medium,
+        GraphicsQuality::High,
+    ];
+
+    pub fn settings(&self) -> QualitySettings {
+        match self {
+            GraphicsQuality::Low => QualitySettings::LOW,
+            GraphicsQuality::Medium => QualitySettings::MEDIUM,
+            GraphicsQuality::High => QualitySettings::HIGH,
+            GraphicsQuality::Custom(settings) => *settings,
+        }
+    }
+
+    /// Starts customizing the current preset
+    pub fn customize(&mut self) -> &mut QualitySettings {
+        if !matches!(self, GraphicsQuality::Custom(_)) {
+            *self = GraphicsQuality::Custom(self.settings());
+        }
+
+        match self {
+            GraphicsQuality::Custom(settings) => settings,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Sets [Msaa] when the quality changes
+pub fn graphics_quality_system(quality: ChangedRes<GraphicsQuality>, mut msaa: ResMut<Msaa>) {
+    let quality_msaa = quality.settings().msaa();
+    if *msaa != quality_msaa {
+        *msaa = quality_msaa;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn customize_keeps_preset_settings() {
+        let mut quality = GraphicsQuality::High;
+        assert_eq!(quality.customize(), &QualitySettings::HIGH);
+        quality.customize().anisotropy = 8;
+        assert_eq!(
+            quality,
+            GraphicsQuality::Custom(QualitySettings {
+                anisotropy: 8,
+                ..QualitySettings::HIGH
+            })
+        );
+        assert_eq!(quality.settings().msaa().samples, 4);
+        assert_eq!(quality.settings().anisotropy_clamp(), NonZeroU8::new(8));
+        assert_eq!(GraphicsQuality::Low.settings().anisotropy_clamp(), None);
+    }
+}
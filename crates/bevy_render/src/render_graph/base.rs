@@ -1,23 +1,32 @@
 use super::{
-    CameraNode, PassNode, RenderGraph, SharedBuffersNode, TextureCopyNode, WindowSwapChainNode,
-    WindowTextureNode,
+    AssetTextureCopyNode, BlitNode, CameraNode, Edge, NodeId, PassNode, RenderGraph,
+    SharedBuffersNode, TextureCopyNode, WindowSwapChainNode, WindowTextureNode,
 };
 use crate::{
     pass::{
         LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor,
         RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
     },
-    texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
+    texture::{
+        Extent3d, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+    },
     Color,
 };
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Res, ResMut};
+use bevy_math::Vec2;
 use bevy_property::Properties;
-use bevy_window::WindowId;
+use bevy_type_registry::TypeUuid;
+use bevy_window::{WindowId, Windows};
 
 /// A component that indicates that an entity should be drawn in the "main pass"
 #[derive(Default, Properties)]
 pub struct MainPass;
 
-#[derive(Debug)]
+/// Samples per pixel of the passes that draw to the window, like the main pass and the ui pass. It can change while
+/// the app runs: passes that resolve their color attachments and the textures with an [MsaaTexture] follow it.
+/// [GraphicsQuality](crate::quality::GraphicsQuality) sets it when the quality changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Msaa {
     pub samples: u32,
 }
@@ -29,28 +38,86 @@ impl Default for Msaa {
 }
 
 impl Msaa {
+    /// A color attachment that is multisampled while [Msaa] has more than one sample. The pass draws to
+    /// `attachment`, which should be a texture with [MsaaTexture::Color], and resolves it to `resolve_target`. While
+    /// [Msaa] has one sample, the pass draws to `resolve_target` directly.
     pub fn color_attachment_descriptor(
-        &self,
         attachment: TextureAttachment,
         resolve_target: TextureAttachment,
         ops: Operations<Color>,
     ) -> RenderPassColorAttachmentDescriptor {
-        if self.samples > 1 {
-            RenderPassColorAttachmentDescriptor {
-                attachment,
-                resolve_target: Some(resolve_target),
-                ops,
-            }
-        } else {
-            RenderPassColorAttachmentDescriptor {
-                attachment,
-                resolve_target: None,
-                ops,
-            }
+        RenderPassColorAttachmentDescriptor {
+            attachment,
+            resolve_target: Some(resolve_target),
+            ops,
+        }
+    }
+}
+
+/// How the texture of a [WindowTextureNode] or [TextureNode](super::TextureNode) follows [Msaa]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsaaTexture {
+    /// The depth attachment of multisampled passes, with as many samples as [Msaa]
+    Depth,
+    /// The color attachment multisampled passes resolve. It isn't drawn to while [Msaa] has one sample, so then it is
+    /// a 1x1 placeholder.
+    Color,
+}
+
+impl MsaaTexture {
+    /// The descriptor of the texture while [Msaa] has `samples` samples
+    pub fn descriptor(&self, descriptor: TextureDescriptor, samples: u32) -> TextureDescriptor {
+        match self {
+            MsaaTexture::Color if samples == 1 => TextureDescriptor {
+                size: Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth: 1,
+                },
+                sample_count: 1,
+                ..descriptor
+            },
+            _ => TextureDescriptor {
+                sample_count: samples,
+                ..descriptor
+            },
         }
     }
 }
 
+/// Features that only work while the main pass has one sample, like temporal anti-aliasing, which sample the depth
+/// or color the main pass renders. [Msaa] is kept at one sample while any of them is added.
+#[derive(Debug, Default)]
+pub struct SingleSampledFeatures {
+    features: Vec<&'static str>,
+}
+
+impl SingleSampledFeatures {
+    pub fn add(&mut self, feature: &'static str) {
+        if !self.features.contains(&feature) {
+            self.features.push(feature);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &&'static str> {
+        self.features.iter()
+    }
+}
+
+/// Keeps [Msaa] at one sample while [SingleSampledFeatures] has a feature
+pub fn single_sampled_features_system(
+    features: Res<SingleSampledFeatures>,
+    mut msaa: ResMut<Msaa>,
+) {
+    if msaa.samples > 1 && !features.features.is_empty() {
+        log::warn!(
+            "Msaa is kept at one sample because {} need a single sampled main pass",
+            features.features.join(", ")
+        );
+        msaa.samples = 1;
+    }
+}
+
 #[derive(Debug)]
 pub struct BaseRenderGraphConfig {
     pub add_2d_camera: bool,
@@ -59,16 +126,30 @@ pub struct BaseRenderGraphConfig {
     pub add_main_pass: bool,
     pub connect_main_pass_to_swapchain: bool,
     pub connect_main_pass_to_main_depth_texture: bool,
+    /// Copies the main pass's depth to [MAIN_DEPTH_TEXTURE_HANDLE] after the main pass, so materials can sample the
+    /// depth of the scene, for example to fade particles and water where they meet other geometry. Materials see the
+    /// depth of the previous frame. Multisampled depth can't be sampled like a regular texture, so this keeps [Msaa]
+    /// at one sample with [SingleSampledFeatures].
+    pub expose_main_depth_texture: bool,
 }
 
+/// A copy of the main pass's depth, if [BaseRenderGraphConfig::expose_main_depth_texture] is set. The texture is the
+/// size of the primary window and holds non-linear depth from 0 at the near plane to 1 at the far plane. Depth can't
+/// be filtered, so the texture is always sampled with the nearest filter.
+pub const MAIN_DEPTH_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 13378939762009864029);
+
 pub mod node {
     pub const PRIMARY_SWAP_CHAIN: &str = "swapchain";
     pub const CAMERA3D: &str = "camera3d";
     pub const CAMERA2D: &str = "camera2d";
     pub const TEXTURE_COPY: &str = "texture_copy";
     pub const MAIN_DEPTH_TEXTURE: &str = "main_pass_depth_texture";
+    pub const MAIN_DEPTH_TEXTURE_COPY: &str = "main_pass_depth_texture_copy";
     pub const MAIN_SAMPLED_COLOR_ATTACHMENT: &str = "main_pass_sampled_color_attachment";
     pub const MAIN_PASS: &str = "main_pass";
+    pub const MAIN_COLOR_TEXTURE: &str = "main_pass_color_texture";
+    pub const MAIN_COLOR_BLIT_PASS: &str = "main_pass_color_blit_pass";
     pub const SHARED_BUFFERS: &str = "shared_buffers";
 }
 
@@ -77,6 +158,37 @@ pub mod camera {
     pub const CAMERA2D: &str = "Camera2d";
 }
 
+/// Keeps [MAIN_DEPTH_TEXTURE_HANDLE] at the size of the primary window
+pub fn main_depth_texture_size_system(
+    windows: Res<Windows>,
+    mut textures: ResMut<Assets<Texture>>,
+) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+
+    let size = Vec2::new(window.width() as f32, window.height() as f32);
+    let texture_size = textures
+        .get(&MAIN_DEPTH_TEXTURE_HANDLE)
+        .map(|texture| texture.size);
+    if texture_size.map_or(false, |texture_size| texture_size != size) {
+        // the changed texture is recreated at the new size
+        textures.get_mut(&MAIN_DEPTH_TEXTURE_HANDLE).unwrap().size = size;
+    }
+}
+
+impl BaseRenderGraphConfig {
+    /// Whether the main depth texture is copied to [MAIN_DEPTH_TEXTURE_HANDLE] with these settings
+    pub fn exposes_main_depth_texture(&self) -> bool {
+        self.expose_main_depth_texture
+            && self.add_main_pass
+            && self.add_main_depth_texture
+            && self.connect_main_pass_to_main_depth_texture
+    }
+}
+
 impl Default for BaseRenderGraphConfig {
     fn default() -> Self {
         BaseRenderGraphConfig {
@@ -86,6 +198,7 @@ impl Default for BaseRenderGraphConfig {
             add_main_depth_texture: true,
             connect_main_pass_to_swapchain: true,
             connect_main_pass_to_main_depth_texture: true,
+            expose_main_depth_texture: false,
         }
     }
 }
@@ -93,11 +206,26 @@ impl Default for BaseRenderGraphConfig {
 /// By itself this graph doesn't do much, but it allows Render plugins to interop with each other by having a common
 /// set of nodes. It can be customized using `BaseRenderGraphConfig`.
 pub trait BaseRenderGraphBuilder {
-    fn add_base_graph(&mut self, config: &BaseRenderGraphConfig, msaa: &Msaa) -> &mut Self;
+    fn add_base_graph(&mut self, config: &BaseRenderGraphConfig) -> &mut Self;
+
+    /// Adds a pass called `name` that draws [MainPass] entities as seen by the camera `camera_name` over the result
+    /// of the main pass. The pass clears depth but keeps the color, so a camera with a
+    /// [Viewport](crate::camera::Viewport) can draw over other cameras, for example for a picture-in-picture view.
+    /// Cameras whose viewports don't overlap can share the main pass instead. The camera has to be added to
+    /// [ActiveCameras](crate::camera::ActiveCameras), and passes that should draw on top of it, like the ui pass,
+    /// need a node edge from the new pass.
+    fn add_camera_pass(&mut self, name: &'static str, camera_name: &'static str) -> &mut Self;
+
+    /// Makes the main pass render its colors to a texture that later nodes can sample, and returns the node and the
+    /// output slot of that texture. If the main pass renders to the window, it renders to [node::MAIN_COLOR_TEXTURE]
+    /// instead, which [node::MAIN_COLOR_BLIT_PASS] draws to the window before the passes that draw over the scene,
+    /// like the ui pass. Otherwise the texture a post-processing effect already redirected the main pass to is
+    /// returned.
+    fn add_sampled_main_color(&mut self) -> (NodeId, usize);
 }
 
 impl BaseRenderGraphBuilder for RenderGraph {
-    fn add_base_graph(&mut self, config: &BaseRenderGraphConfig, msaa: &Msaa) -> &mut Self {
+    fn add_base_graph(&mut self, config: &BaseRenderGraphConfig) -> &mut Self {
         self.add_node(node::TEXTURE_COPY, TextureCopyNode::default());
         if config.add_3d_camera {
             self.add_system_node(node::CAMERA3D, CameraNode::new(camera::CAMERA3D));
@@ -111,7 +239,7 @@ impl BaseRenderGraphBuilder for RenderGraph {
         if config.add_main_depth_texture {
             self.add_node(
                 node::MAIN_DEPTH_TEXTURE,
-                WindowTextureNode::new(
+                WindowTextureNode::multisampled(
                     WindowId::primary(),
                     TextureDescriptor {
                         size: Extent3d {
@@ -120,18 +248,23 @@ impl BaseRenderGraphBuilder for RenderGraph {
                             height: 1,
                         },
                         mip_level_count: 1,
-                        sample_count: msaa.samples,
+                        sample_count: 1,
                         dimension: TextureDimension::D2,
                         format: TextureFormat::Depth32Float, // PERF: vulkan docs recommend using 24 bit depth for better performance
-                        usage: TextureUsage::OUTPUT_ATTACHMENT,
+                        usage: if config.exposes_main_depth_texture() {
+                            TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::COPY_SRC
+                        } else {
+                            TextureUsage::OUTPUT_ATTACHMENT
+                        },
                     },
+                    MsaaTexture::Depth,
                 ),
             );
         }
 
         if config.add_main_pass {
             let mut main_pass_node = PassNode::<&MainPass>::new(PassDescriptor {
-                color_attachments: vec![msaa.color_attachment_descriptor(
+                color_attachments: vec![Msaa::color_attachment_descriptor(
                     TextureAttachment::Input("color_attachment".to_string()),
                     TextureAttachment::Input("color_resolve_target".to_string()),
                     Operations {
@@ -147,7 +280,7 @@ impl BaseRenderGraphBuilder for RenderGraph {
                     }),
                     stencil_ops: None,
                 }),
-                sample_count: msaa.samples,
+                sample_count: 1,
             });
 
             main_pass_node.use_default_clear_color(0);
@@ -186,35 +319,32 @@ impl BaseRenderGraphBuilder for RenderGraph {
                 node::PRIMARY_SWAP_CHAIN,
                 WindowSwapChainNode::OUT_TEXTURE,
                 node::MAIN_PASS,
-                if msaa.samples > 1 {
-                    "color_resolve_target"
-                } else {
-                    "color_attachment"
-                },
+                "color_resolve_target",
             )
             .unwrap();
         }
 
-        if msaa.samples > 1 {
-            self.add_node(
-                node::MAIN_SAMPLED_COLOR_ATTACHMENT,
-                WindowTextureNode::new(
-                    WindowId::primary(),
-                    TextureDescriptor {
-                        size: Extent3d {
-                            depth: 1,
-                            width: 1,
-                            height: 1,
-                        },
-                        mip_level_count: 1,
-                        sample_count: msaa.samples,
-                        dimension: TextureDimension::D2,
-                        format: TextureFormat::default(),
-                        usage: TextureUsage::OUTPUT_ATTACHMENT,
+        self.add_node(
+            node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+            WindowTextureNode::multisampled(
+                WindowId::primary(),
+                TextureDescriptor {
+                    size: Extent3d {
+                        depth: 1,
+                        width: 1,
+                        height: 1,
                     },
-                ),
-            );
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: TextureFormat::default(),
+                    usage: TextureUsage::OUTPUT_ATTACHMENT,
+                },
+                MsaaTexture::Color,
+            ),
+        );
 
+        if config.add_main_pass {
             self.add_slot_edge(
                 node::MAIN_SAMPLED_COLOR_ATTACHMENT,
                 WindowSwapChainNode::OUT_TEXTURE,
@@ -234,6 +364,164 @@ impl BaseRenderGraphBuilder for RenderGraph {
             .unwrap();
         }
 
+        if config.exposes_main_depth_texture() {
+            self.add_node(
+                node::MAIN_DEPTH_TEXTURE_COPY,
+                AssetTextureCopyNode::new(MAIN_DEPTH_TEXTURE_HANDLE),
+            );
+            self.add_slot_edge(
+                node::MAIN_DEPTH_TEXTURE,
+                WindowTextureNode::OUT_TEXTURE,
+                node::MAIN_DEPTH_TEXTURE_COPY,
+                AssetTextureCopyNode::IN_TEXTURE,
+            )
+            .unwrap();
+            self.add_node_edge(node::MAIN_PASS, node::MAIN_DEPTH_TEXTURE_COPY)
+                .unwrap();
+        }
+
         self
     }
+
+    fn add_camera_pass(&mut self, name: &'static str, camera_name: &'static str) -> &mut Self {
+        let camera_node =
+            self.add_system_node(format!("{}_camera", name), CameraNode::new(camera_name));
+
+        let mut pass_node = PassNode::<&MainPass>::new(PassDescriptor {
+            color_attachments: vec![Msaa::color_attachment_descriptor(
+                TextureAttachment::Input("color_attachment".to_string()),
+                TextureAttachment::Input("color_resolve_target".to_string()),
+                Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            )],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+                attachment: TextureAttachment::Input("depth".to_string()),
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+            sample_count: 1,
+        });
+        pass_node.add_camera(camera_name);
+        self.add_node(name, pass_node);
+
+        self.add_node_edge(camera_node, name).unwrap();
+        self.add_node_edge(node::TEXTURE_COPY, name).unwrap();
+        self.add_node_edge(node::SHARED_BUFFERS, name).unwrap();
+        self.add_node_edge(node::MAIN_PASS, name).unwrap();
+
+        self.add_slot_edge(
+            node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            name,
+            "color_resolve_target",
+        )
+        .unwrap();
+        self.add_slot_edge(
+            node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+            WindowSwapChainNode::OUT_TEXTURE,
+            name,
+            "color_attachment",
+        )
+        .unwrap();
+
+        self.add_slot_edge(
+            node::MAIN_DEPTH_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            name,
+            "depth",
+        )
+        .unwrap();
+
+        self
+    }
+    fn add_sampled_main_color(&mut self) -> (NodeId, usize) {
+        // the main pass draws to its resolve target directly while it isn't multisampled
+        let color_slot = "color_resolve_target";
+        let main_pass = self.get_node_state(node::MAIN_PASS).unwrap();
+        let color_index = main_pass.input_slots.get_slot_index(color_slot).unwrap();
+        let (output_node, output_index) = match main_pass.edges.get_input_slot_edge(color_index) {
+            Ok(Edge::SlotEdge {
+                output_node,
+                output_index,
+                ..
+            }) => (*output_node, *output_index),
+            _ => panic!("the main pass doesn't render its colors to a texture"),
+        };
+        if self.get_node_id(node::PRIMARY_SWAP_CHAIN).ok() != Some(output_node) {
+            return (output_node, output_index);
+        }
+
+        // the swap chain can't be sampled, so the main pass renders to a texture that is drawn to the window after
+        let texture_node = self.add_node(
+            node::MAIN_COLOR_TEXTURE,
+            WindowTextureNode::new(
+                WindowId::primary(),
+                TextureDescriptor {
+                    size: Extent3d {
+                        width: 1,
+                        height: 1,
+                        depth: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: TextureFormat::default(),
+                    usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED,
+                },
+            ),
+        );
+        self.remove_slot_edge(
+            node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::MAIN_PASS,
+            color_slot,
+        )
+        .unwrap();
+        self.add_slot_edge(
+            node::MAIN_COLOR_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::MAIN_PASS,
+            color_slot,
+        )
+        .unwrap();
+
+        let blit_pass = self.add_node(node::MAIN_COLOR_BLIT_PASS, BlitNode::default());
+        self.add_slot_edge(
+            node::MAIN_COLOR_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::MAIN_COLOR_BLIT_PASS,
+            BlitNode::IN_TEXTURE,
+        )
+        .unwrap();
+        self.add_slot_edge(
+            node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::MAIN_COLOR_BLIT_PASS,
+            BlitNode::IN_COLOR_ATTACHMENT,
+        )
+        .unwrap();
+        self.add_node_edge(node::MAIN_PASS, node::MAIN_COLOR_BLIT_PASS)
+            .unwrap();
+
+        // the passes that draw over the scene, like the ui pass, draw over the blit
+        let consumers = self
+            .iter_node_outputs(node::PRIMARY_SWAP_CHAIN)
+            .unwrap()
+            .filter_map(|(edge, _)| match edge {
+                Edge::SlotEdge { input_node, .. } if *input_node != blit_pass => Some(*input_node),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for consumer in consumers {
+            // the edge exists if a pass consumes the swap chain through several slots
+            let _ = self.add_node_edge(blit_pass, consumer);
+        }
+
+        (texture_node, 0)
+    }
 }
@@ -0,0 +1,297 @@
+use crate::{
+    renderer::{
+        BufferId, BufferInfo, BufferUsage, RenderResourceContext, RenderResourceId, TextureId,
+    },
+    texture::{TextureDescriptor, TextureUsage},
+};
+use bevy_utils::HashMap;
+use std::borrow::Cow;
+
+#[derive(Debug)]
+struct BlackboardTexture {
+    descriptor: TextureDescriptor,
+    usage: TextureUsage,
+    declared: bool,
+    written: bool,
+    texture: Option<(TextureId, TextureDescriptor)>,
+}
+
+#[derive(Debug)]
+struct BlackboardBuffer {
+    size: usize,
+    usage: BufferUsage,
+    declared: bool,
+    written: bool,
+    buffer: Option<(BufferId, BufferInfo)>,
+}
+
+/// Named textures and buffers that one render graph node writes and other nodes read.
+///
+/// Resources are declared every frame in [Node::prepare](super::Node::prepare). Each node that touches a resource
+/// adds the usage it needs, and the resource is created with the combined usage the first time a node gets it in
+/// [Node::update](super::Node::update). Resources stay alive as long as they are declared each frame and are
+/// released in the first frame nobody declares them. The blackboard doesn't order nodes, so readers still need an
+/// edge from the writer.
+#[derive(Debug, Default)]
+pub struct RenderGraphBlackboard {
+    textures: HashMap<Cow<'static, str>, BlackboardTexture>,
+    buffers: HashMap<Cow<'static, str>, BlackboardBuffer>,
+}
+
+impl RenderGraphBlackboard {
+    /// Declares a texture for this frame. The usage of `descriptor` is combined with the usage of all accesses.
+    pub fn declare_texture(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        descriptor: TextureDescriptor,
+    ) {
+        let texture = self
+            .textures
+            .entry(name.into())
+            .or_insert_with(|| BlackboardTexture {
+                descriptor,
+                usage: TextureUsage::empty(),
+                declared: false,
+                written: false,
+                texture: None,
+            });
+        texture.descriptor = descriptor;
+        texture.usage |= descriptor.usage;
+        texture.declared = true;
+    }
+
+    /// Declares a buffer of `size` bytes for this frame
+    pub fn declare_buffer(&mut self, name: impl Into<Cow<'static, str>>, size: usize) {
+        let buffer = self
+            .buffers
+            .entry(name.into())
+            .or_insert_with(|| BlackboardBuffer {
+                size,
+                usage: BufferUsage::empty(),
+                declared: false,
+                written: false,
+                buffer: None,
+            });
+        buffer.size = size;
+        buffer.declared = true;
+    }
+
+    pub fn write_texture(&mut self, name: &str, usage: TextureUsage) {
+        self.access_texture(name, usage, true);
+    }
+
+    pub fn read_texture(&mut self, name: &str, usage: TextureUsage) {
+        self.access_texture(name, usage, false);
+    }
+
+    pub fn write_buffer(&mut self, name: &str, usage: BufferUsage) {
+        self.access_buffer(name, usage, true);
+    }
+
+    pub fn read_buffer(&mut self, name: &str, usage: BufferUsage) {
+        self.access_buffer(name, usage, false);
+    }
+
+    fn access_texture(&mut self, name: &str, usage: TextureUsage, write: bool) {
+        match self.textures.get_mut(name) {
+            Some(texture) if texture.declared => {
+                texture.usage |= usage;
+                texture.written |= write;
+            }
+            _ => log::warn!(
+                "Blackboard texture \"{}\" was accessed before it was declared",
+                name
+            ),
+        }
+    }
+
+    fn access_buffer(&mut self, name: &str, usage: BufferUsage, write: bool) {
+        match self.buffers.get_mut(name) {
+            Some(buffer) if buffer.declared => {
+                buffer.usage |= usage;
+                buffer.written |= write;
+            }
+            _ => log::warn!(
+                "Blackboard buffer \"{}\" was accessed before it was declared",
+                name
+            ),
+        }
+    }
+
+    /// Returns the texture declared as `name`, creating it if necessary
+    pub fn get_texture(
+        &mut self,
+        name: &str,
+        render_resource_context: &dyn RenderResourceContext,
+    ) -> Option<TextureId> {
+        let texture = self
+            .textures
+            .get_mut(name)
+            .filter(|texture| texture.declared)?;
+        if let Some((texture_id, _)) = texture.texture {
+            return Some(texture_id);
+        }
+
+        let mut descriptor = texture.descriptor;
+        descriptor.usage = texture.usage;
+        let texture_id = render_resource_context.create_texture(descriptor);
+        texture.texture = Some((texture_id, descriptor));
+        Some(texture_id)
+    }
+
+    /// Returns the buffer declared as `name`, creating it if necessary
+    pub fn get_buffer(
+        &mut self,
+        name: &str,
+        render_resource_context: &dyn RenderResourceContext,
+    ) -> Option<BufferId> {
+        let buffer = self
+            .buffers
+            .get_mut(name)
+            .filter(|buffer| buffer.declared)?;
+        if let Some((buffer_id, _)) = buffer.buffer {
+            return Some(buffer_id);
+        }
+
+        let buffer_info = BufferInfo {
+            size: buffer.size,
+            buffer_usage: buffer.usage,
+            mapped_at_creation: false,
+        };
+        let buffer_id = render_resource_context.create_buffer(buffer_info.clone());
+        buffer.buffer = Some((buffer_id, buffer_info));
+        Some(buffer_id)
+    }
+
+    /// Starts a new frame of declarations
+    pub fn begin_frame(&mut self) {
+        for texture in self.textures.values_mut() {
+            texture.declared = false;
+            texture.written = false;
+            texture.usage = TextureUsage::empty();
+        }
+
+        for buffer in self.buffers.values_mut() {
+            buffer.declared = false;
+            buffer.written = false;
+            buffer.usage = BufferUsage::empty();
+        }
+    }
+
+    /// Releases resources that weren't declared this frame and recreates resources whose declaration changed
+    pub fn finish_declarations(&mut self, render_resource_context: &dyn RenderResourceContext) {
+        self.textures.retain(|name, texture| {
+            if texture.declared && !texture.written {
+                log::warn!("Blackboard texture \"{}\" is never written", name);
+            }
+
+            let mut descriptor = texture.descriptor;
+            descriptor.usage = texture.usage;
+            let outdated = match texture.texture {
+                Some((_, current_descriptor)) => {
+                    !texture.declared || current_descriptor != descriptor
+                }
+                None => false,
+            };
+            if outdated {
+                let (texture_id, _) = texture.texture.take().unwrap();
+                render_resource_context.release_resource(RenderResourceId::Texture(texture_id));
+            }
+
+            texture.declared
+        });
+
+        self.buffers.retain(|name, buffer| {
+            if buffer.declared && !buffer.written {
+                log::warn!("Blackboard buffer \"{}\" is never written", name);
+            }
+
+            let outdated = match buffer.buffer.as_ref() {
+                Some((_, current_info)) => {
+                    !buffer.declared
+                        || current_info.size != buffer.size
+                        || current_info.buffer_usage != buffer.usage
+                }
+                None => false,
+            };
+            if outdated {
+                let (buffer_id, _) = buffer.buffer.take().unwrap();
+                render_resource_context.release_resource(RenderResourceId::Buffer(buffer_id));
+            }
+
+            buffer.declared
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        renderer::HeadlessRenderResourceContext,
+        texture::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    fn descriptor(width: u32) -> TextureDescriptor {
+        TextureDescriptor {
+            size: Extent3d {
+                width,
+                height: 1,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsage::empty(),
+        }
+    }
+
+    #[test]
+    fn blackboard_texture_lifetime() {
+        let render_resource_context = HeadlessRenderResourceContext::default();
+        let mut blackboard = RenderGraphBlackboard::default();
+
+        blackboard.begin_frame();
+        blackboard.declare_texture("shared", descriptor(4));
+        blackboard.write_texture("shared", TextureUsage::OUTPUT_ATTACHMENT);
+        blackboard.read_texture("shared", TextureUsage::SAMPLED);
+        blackboard.finish_declarations(&render_resource_context);
+        let texture = blackboard
+            .get_texture("shared", &render_resource_context)
+            .unwrap();
+        assert_eq!(
+            blackboard.textures["shared"].texture.unwrap().1.usage,
+            TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::SAMPLED
+        );
+
+        // the same declaration keeps the texture
+        blackboard.begin_frame();
+        blackboard.declare_texture("shared", descriptor(4));
+        blackboard.write_texture("shared", TextureUsage::OUTPUT_ATTACHMENT);
+        blackboard.read_texture("shared", TextureUsage::SAMPLED);
+        blackboard.finish_declarations(&render_resource_context);
+        assert_eq!(
+            blackboard.get_texture("shared", &render_resource_context),
+            Some(texture)
+        );
+
+        // a changed declaration recreates it
+        blackboard.begin_frame();
+        blackboard.declare_texture("shared", descriptor(8));
+        blackboard.write_texture("shared", TextureUsage::OUTPUT_ATTACHMENT);
+        blackboard.finish_declarations(&render_resource_context);
+        assert_ne!(
+            blackboard.get_texture("shared", &render_resource_context),
+            Some(texture)
+        );
+
+        // textures nobody declares are released
+        blackboard.begin_frame();
+        blackboard.finish_declarations(&render_resource_context);
+        assert_eq!(
+            blackboard.get_texture("shared", &render_resource_context),
+            None
+        );
+    }
+}
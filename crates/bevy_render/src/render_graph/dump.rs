@@ -0,0 +1,99 @@
+use super::RenderGraph;
+use crate::renderer::RenderResourceContext;
+use bevy_ecs::{Resources, World};
+use bevy_input::{keyboard::KeyCode, Input};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Writes the [RenderGraph] of the current frame to a Graphviz file, to see how the passes added by different plugins
+/// are wired together. A dump is written when [RenderGraphDump::key] is pressed or after [RenderGraphDump::request] is
+/// called, once the frame has rendered. It shows the resources bound to the slots of every node, see
+/// [RenderGraph::export_graphviz_with_resources].
+#[derive(Debug)]
+pub struct RenderGraphDump {
+    /// The key that dumps the render graph, or `None` to only dump it on request
+    pub key: Option<KeyCode>,
+    /// The folder the dumps are written to
+    pub directory: PathBuf,
+    requested: bool,
+    dumps: usize,
+}
+
+impl Default for RenderGraphDump {
+    fn default() -> Self {
+        RenderGraphDump {
+            key: None,
+            directory: PathBuf::from("render_graph"),
+            requested: false,
+            dumps: 0,
+        }
+    }
+}
+
+impl RenderGraphDump {
+    pub fn on_key(key: KeyCode) -> Self {
+        RenderGraphDump {
+            key: Some(key),
+            ..Default::default()
+        }
+    }
+
+    /// Dumps the render graph at the end of this frame. Returns the file it is written to.
+    pub fn request(&mut self) -> PathBuf {
+        self.requested = true;
+        self.next_path()
+    }
+
+    fn next_path(&self) -> PathBuf {
+        self.directory
+            .join(format!("render_graph_{}.dot", self.dumps))
+    }
+
+    /// Writes `render_graph` to the next dump file and returns its path
+    pub fn write(
+        &mut self,
+        render_graph: &RenderGraph,
+        render_resource_context: &dyn RenderResourceContext,
+    ) -> io::Result<PathBuf> {
+        let path = self.next_path();
+        write_dump(
+            &path,
+            &render_graph.export_graphviz_with_resources(render_resource_context),
+        )?;
+        self.dumps += 1;
+        Ok(path)
+    }
+}
+
+fn write_dump(path: &Path, dot: &str) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    std::fs::write(path, dot)
+}
+
+/// Writes a [RenderGraphDump] if its key was pressed or one was requested this frame
+pub fn render_graph_dump_system(_world: &mut World, resources: &mut Resources) {
+    let mut dump = resources.get_mut::<RenderGraphDump>().unwrap();
+    // apps without input can still request dumps
+    let key_pressed = match (dump.key, resources.get::<Input<KeyCode>>()) {
+        (Some(key), Some(input)) => input.just_pressed(key),
+        _ => false,
+    };
+    if !dump.requested && !key_pressed {
+        return;
+    }
+
+    dump.requested = false;
+    let render_resource_context = match resources.get::<Box<dyn RenderResourceContext>>() {
+        Some(render_resource_context) => render_resource_context,
+        None => return,
+    };
+    let render_graph = resources.get::<RenderGraph>().unwrap();
+    match dump.write(&render_graph, &**render_resource_context) {
+        Ok(path) => log::info!("Wrote the render graph to {}", path.display()),
+        Err(err) => log::error!("Failed to write the render graph: {}", err),
+    }
+}
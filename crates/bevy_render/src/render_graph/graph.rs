@@ -1,5 +1,9 @@
-use super::{Edge, Node, NodeId, NodeLabel, NodeState, RenderGraphError, SlotLabel, SystemNode};
-use bevy_ecs::{Commands, Schedule};
+use super::{
+    Edge, Node, NodeId, NodeLabel, NodeState, RenderGraphBlackboard, RenderGraphError, SlotLabel,
+    SystemNode,
+};
+use crate::renderer::RenderResourceContext;
+use bevy_ecs::{Commands, Resources, Schedule, World};
 use bevy_utils::HashMap;
 use std::{borrow::Cow, fmt::Debug};
 pub struct RenderGraph {
@@ -47,6 +51,65 @@ impl RenderGraph {
         self.add_node(name, node)
     }
 
+    /// Removes a node and all edges connected to it. The systems of [SystemNode]s are not removed, so only remove
+    /// nodes added with [RenderGraph::add_node].
+    pub fn remove_node(&mut self, label: impl Into<NodeLabel>) -> Result<(), RenderGraphError> {
+        let label = label.into();
+        let id = self.get_node_id(&label)?;
+        let node_state = self
+            .nodes
+            .remove(&id)
+            .ok_or(RenderGraphError::InvalidNode(label))?;
+
+        for edge in node_state.edges.input_edges.iter() {
+            if let Some(output_node) = self.nodes.get_mut(&edge.get_output_node()) {
+                output_node.edges.remove_output_edge(edge)?;
+            }
+        }
+
+        for edge in node_state.edges.output_edges.iter() {
+            if let Some(input_node) = self.nodes.get_mut(&edge.get_input_node()) {
+                input_node.edges.remove_input_edge(edge)?;
+            }
+        }
+
+        if let Some(name) = node_state.name.as_ref() {
+            self.node_names.remove(name);
+        }
+
+        Ok(())
+    }
+
+    /// Enables or disables a node without removing it from the graph. Disabled nodes don't record any commands and
+    /// keep the outputs of the last frame they ran, so only disable nodes whose outputs stay valid, like passes.
+    pub fn set_node_enabled(
+        &mut self,
+        label: impl Into<NodeLabel>,
+        enabled: bool,
+    ) -> Result<(), RenderGraphError> {
+        self.get_node_state_mut(label)?.enabled = enabled;
+        Ok(())
+    }
+
+    /// Runs a node only in frames where `condition` returns true. The condition is checked before the graph records
+    /// any commands. Like disabled nodes, skipped nodes keep their last outputs.
+    pub fn set_node_condition(
+        &mut self,
+        label: impl Into<NodeLabel>,
+        condition: impl Fn(&World, &Resources) -> bool + Send + Sync + 'static,
+    ) -> Result<(), RenderGraphError> {
+        self.get_node_state_mut(label)?.condition = Some(Box::new(condition));
+        Ok(())
+    }
+
+    pub fn remove_node_condition(
+        &mut self,
+        label: impl Into<NodeLabel>,
+    ) -> Result<(), RenderGraphError> {
+        self.get_node_state_mut(label)?.condition = None;
+        Ok(())
+    }
+
     pub fn get_node_state(
         &self,
         label: impl Into<NodeLabel>,
@@ -161,6 +224,60 @@ impl RenderGraph {
         Ok(())
     }
 
+    pub fn remove_slot_edge(
+        &mut self,
+        output_node: impl Into<NodeLabel>,
+        output_slot: impl Into<SlotLabel>,
+        input_node: impl Into<NodeLabel>,
+        input_slot: impl Into<SlotLabel>,
+    ) -> Result<(), RenderGraphError> {
+        let output_node_id = self.get_node_id(output_node)?;
+        let input_node_id = self.get_node_id(input_node)?;
+
+        let output_index = self
+            .get_node_state(output_node_id)?
+            .output_slots
+            .get_slot_index(output_slot)?;
+        let input_index = self
+            .get_node_state(input_node_id)?
+            .input_slots
+            .get_slot_index(input_slot)?;
+
+        self.remove_edge(Edge::SlotEdge {
+            output_node: output_node_id,
+            output_index,
+            input_node: input_node_id,
+            input_index,
+        })
+    }
+
+    pub fn remove_node_edge(
+        &mut self,
+        output_node: impl Into<NodeLabel>,
+        input_node: impl Into<NodeLabel>,
+    ) -> Result<(), RenderGraphError> {
+        let output_node_id = self.get_node_id(output_node)?;
+        let input_node_id = self.get_node_id(input_node)?;
+
+        self.remove_edge(Edge::NodeEdge {
+            output_node: output_node_id,
+            input_node: input_node_id,
+        })
+    }
+
+    fn remove_edge(&mut self, edge: Edge) -> Result<(), RenderGraphError> {
+        if !self.has_edge(&edge) {
+            return Err(RenderGraphError::EdgeDoesNotExist(edge));
+        }
+
+        self.get_node_state_mut(edge.get_output_node())?
+            .edges
+            .remove_output_edge(&edge)?;
+        self.get_node_state_mut(edge.get_input_node())?
+            .edges
+            .remove_input_edge(&edge)
+    }
+
     pub fn validate_edge(&mut self, edge: &Edge) -> Result<(), RenderGraphError> {
         if self.has_edge(edge) {
             return Err(RenderGraphError::EdgeAlreadyExists(edge.clone()));
@@ -275,6 +392,28 @@ impl RenderGraph {
             .map(move |(edge, input_node_id)| (edge, self.get_node_state(input_node_id).unwrap())))
     }
 
+    /// Decides which nodes run this frame, calls [Node::prepare] on them and updates the [RenderGraphBlackboard] with
+    /// the resources they declared
+    pub fn prepare(&mut self, world: &mut World, resources: &Resources) {
+        if let Some(mut blackboard) = resources.get_mut::<RenderGraphBlackboard>() {
+            blackboard.begin_frame();
+        }
+
+        for node_state in self.nodes.values_mut() {
+            node_state.update_active(world, resources);
+            if node_state.is_active() {
+                node_state.node.prepare(world, resources);
+            }
+        }
+
+        if let (Some(mut blackboard), Some(render_resource_context)) = (
+            resources.get_mut::<RenderGraphBlackboard>(),
+            resources.get::<Box<dyn RenderResourceContext>>(),
+        ) {
+            blackboard.finish_declarations(&**render_resource_context);
+        }
+    }
+
     pub fn take_commands(&mut self) -> Commands {
         std::mem::take(&mut self.commands)
     }
@@ -404,6 +543,28 @@ mod tests {
         assert!(output_nodes("D", &graph).is_empty(), "D has no outputs");
     }
 
+    #[test]
+    pub fn test_export_graphviz() {
+        let mut graph = RenderGraph::default();
+        graph.add_node("A", TestNode::new(0, 1));
+        graph.add_node("B", TestNode::new(0, 0));
+        graph.add_node("C<main>", TestNode::new(1, 0));
+        graph
+            .add_slot_edge("A", "out_0", "C<main>", "in_0")
+            .unwrap();
+        graph.add_node_edge("B", "C<main>").unwrap();
+        graph.set_node_enabled("B", false).unwrap();
+
+        let dot = graph.export_graphviz();
+        assert!(dot.starts_with("digraph render_graph {"));
+        assert!(dot.contains("<b>C&lt;main&gt;</b>"));
+        assert!(dot.contains("<td port=\"o0\">out_0: Texture</td>"));
+        assert!(dot.contains("    n1 [fontcolor=gray"));
+        assert!(dot.contains("<b>B</b><br/>(disabled)"));
+        assert!(dot.contains("    n0:o0 -> n2:i0;\n"));
+        assert!(dot.contains("    n1 -> n2 [style=dashed];\n"));
+    }
+
     #[test]
     pub fn test_get_node_typed() {
         struct MyNode {
@@ -457,6 +618,45 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_remove_edges_and_nodes() {
+        let mut graph = RenderGraph::default();
+
+        graph.add_node("A", TestNode::new(0, 1));
+        graph.add_node("B", TestNode::new(1, 1));
+        graph.add_node("C", TestNode::new(1, 0));
+
+        graph.add_slot_edge("A", 0, "B", 0).unwrap();
+        graph.add_slot_edge("B", 0, "C", 0).unwrap();
+        graph.add_node_edge("A", "C").unwrap();
+
+        graph.remove_node_edge("A", "C").unwrap();
+        assert_eq!(
+            graph.remove_node_edge("A", "C"),
+            Err(RenderGraphError::EdgeDoesNotExist(Edge::NodeEdge {
+                output_node: graph.get_node_id("A").unwrap(),
+                input_node: graph.get_node_id("C").unwrap(),
+            })),
+            "Removing an edge twice should return an error"
+        );
+
+        // a new node can take over the input slot of the removed one
+        graph.remove_slot_edge("B", 0, "C", 0).unwrap();
+        graph.add_slot_edge("A", 0, "C", 0).unwrap();
+
+        let b_id = graph.get_node_id("B").unwrap();
+        graph.remove_node("B").unwrap();
+        assert_eq!(
+            graph.get_node_id("B"),
+            Err(RenderGraphError::InvalidNode("B".into()))
+        );
+        assert!(graph
+            .iter_node_outputs("A")
+            .unwrap()
+            .all(|(edge, _node)| edge.get_input_node() != b_id));
+        assert_eq!(graph.iter_nodes().count(), 2);
+    }
+
     #[test]
     pub fn test_edge_already_exists() {
         let mut graph = RenderGraph::default();
@@ -476,4 +676,36 @@ mod tests {
             "Adding to a duplicate edge should return an error"
         );
     }
+
+    #[test]
+    pub fn test_node_conditions() {
+        struct SkipA(bool);
+
+        let mut graph = RenderGraph::default();
+        graph.add_node("A", TestNode::new(0, 1));
+        graph.add_node("B", TestNode::new(0, 1));
+        graph
+            .set_node_condition("A", |_world, resources| {
+                !resources.get::<SkipA>().unwrap().0
+            })
+            .unwrap();
+        graph.set_node_enabled("B", false).unwrap();
+
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(SkipA(false));
+        graph.prepare(&mut world, &resources);
+        assert!(graph.get_node_state("A").unwrap().is_active());
+        assert!(!graph.get_node_state("B").unwrap().is_active());
+
+        resources.insert(SkipA(true));
+        graph.set_node_enabled("B", true).unwrap();
+        graph.prepare(&mut world, &resources);
+        assert!(!graph.get_node_state("A").unwrap().is_active());
+        assert!(graph.get_node_state("B").unwrap().is_active());
+
+        graph.remove_node_condition("A").unwrap();
+        graph.prepare(&mut world, &resources);
+        assert!(graph.get_node_state("A").unwrap().is_active());
+    }
 }
@@ -0,0 +1,189 @@
+use super::{validation::node_name, Edge, NodeId, NodeState, RenderGraph, ResourceSlot};
+use crate::renderer::{RenderResourceContext, RenderResourceId};
+use bevy_utils::HashMap;
+use std::fmt::Write;
+
+impl RenderGraph {
+    /// Describes the graph in the Graphviz dot language: every node with its input slots on the left and output slots
+    /// on the right, slot edges between the slots they connect and node edges as dashed lines. Disabled nodes and
+    /// nodes that were skipped in the last frame are grayed out. Render the result with `dot -Tsvg graph.dot`.
+    pub fn export_graphviz(&self) -> String {
+        export_graphviz(self, None)
+    }
+
+    /// Like [RenderGraph::export_graphviz], and also shows the resource each slot was bound to in the last frame, with
+    /// the format and size of textures and the size of buffers
+    pub fn export_graphviz_with_resources(
+        &self,
+        render_resource_context: &dyn RenderResourceContext,
+    ) -> String {
+        export_graphviz(self, Some(render_resource_context))
+    }
+}
+
+fn export_graphviz(
+    graph: &RenderGraph,
+    render_resource_context: Option<&dyn RenderResourceContext>,
+) -> String {
+    // sorted so that dumps of the same graph can be diffed
+    let mut nodes = graph.iter_nodes().collect::<Vec<_>>();
+    nodes.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+    let indices = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node_state)| (node_state.id, index))
+        .collect::<HashMap<NodeId, usize>>();
+
+    let mut dot = String::new();
+    dot.push_str("digraph render_graph {\n");
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    node [shape=plaintext];\n");
+    for (index, node_state) in nodes.iter().enumerate() {
+        write_node(&mut dot, index, node_state, render_resource_context);
+    }
+
+    for node_state in nodes.iter() {
+        for edge in node_state.edges.output_edges.iter() {
+            match edge {
+                Edge::SlotEdge {
+                    output_node,
+                    output_index,
+                    input_node,
+                    input_index,
+                } => writeln!(
+                    dot,
+                    "    n{}:o{} -> n{}:i{};",
+                    indices[output_node], output_index, indices[input_node], input_index
+                ),
+                Edge::NodeEdge {
+                    output_node,
+                    input_node,
+                } => writeln!(
+                    dot,
+                    "    n{} -> n{} [style=dashed];",
+                    indices[output_node], indices[input_node]
+                ),
+            }
+            .unwrap();
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+fn write_node(
+    dot: &mut String,
+    index: usize,
+    node_state: &NodeState,
+    render_resource_context: Option<&dyn RenderResourceContext>,
+) {
+    let status = if !node_state.enabled {
+        Some("disabled")
+    } else if !node_state.is_active() {
+        Some("skipped")
+    } else {
+        None
+    };
+
+    write!(dot, "    n{} [", index).unwrap();
+    if status.is_some() {
+        dot.push_str("fontcolor=gray, color=gray, ");
+    }
+    dot.push_str("label=<<table border=\"0\" cellborder=\"1\" cellspacing=\"0\">");
+    write!(
+        dot,
+        "<tr><td colspan=\"2\"><b>{}</b>",
+        escape(&node_name(node_state))
+    )
+    .unwrap();
+    if let Some(status) = status {
+        write!(dot, "<br/>({})", status).unwrap();
+    }
+    dot.push_str("</td></tr>");
+
+    let rows = node_state
+        .input_slots
+        .len()
+        .max(node_state.output_slots.len());
+    for row in 0..rows {
+        dot.push_str("<tr>");
+        for (slots, port) in [
+            (&node_state.input_slots, 'i'),
+            (&node_state.output_slots, 'o'),
+        ]
+        .iter()
+        {
+            match slots.get_slot(row) {
+                Ok(slot) => write!(
+                    dot,
+                    "<td port=\"{}{}\">{}</td>",
+                    port,
+                    row,
+                    slot_label(slot, render_resource_context)
+                )
+                .unwrap(),
+                Err(_) => dot.push_str("<td border=\"0\"></td>"),
+            }
+        }
+        dot.push_str("</tr>");
+    }
+    dot.push_str("</table>>];\n");
+}
+
+fn slot_label(
+    slot: &ResourceSlot,
+    render_resource_context: Option<&dyn RenderResourceContext>,
+) -> String {
+    let mut label = format!("{}: {:?}", escape(&slot.info.name), slot.info.resource_type);
+    if let Some(render_resource_context) = render_resource_context {
+        label.push_str("<br/>");
+        label.push_str(&escape(&describe_resource(
+            slot.resource.as_ref(),
+            render_resource_context,
+        )));
+    }
+
+    label
+}
+
+fn describe_resource(
+    resource: Option<&RenderResourceId>,
+    render_resource_context: &dyn RenderResourceContext,
+) -> String {
+    match resource {
+        None => "not bound".to_string(),
+        Some(RenderResourceId::Texture(texture)) => {
+            match render_resource_context.get_texture_descriptor(*texture) {
+                Some(descriptor) => {
+                    let size = descriptor.size;
+                    let mut description =
+                        format!("{:?} {}x{}", descriptor.format, size.width, size.height);
+                    if size.depth > 1 {
+                        write!(description, "x{}", size.depth).unwrap();
+                    }
+                    if descriptor.sample_count > 1 {
+                        write!(description, " {}x msaa", descriptor.sample_count).unwrap();
+                    }
+                    description
+                }
+                // the texture was freed since the frame rendered
+                None => "texture".to_string(),
+            }
+        }
+        Some(RenderResourceId::Buffer(buffer)) => {
+            match render_resource_context.get_buffer_info(*buffer) {
+                Some(info) => format!("buffer of {} bytes", info.size),
+                None => "buffer".to_string(),
+            }
+        }
+        Some(RenderResourceId::Sampler(_)) => "sampler".to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,14 +1,20 @@
 pub mod base;
+mod blackboard;
 mod command;
+mod dump;
 mod edge;
 mod graph;
+mod graphviz;
 mod node;
 mod node_slot;
 mod nodes;
 mod schedule;
 mod system;
+mod validation;
 
+pub use blackboard::*;
 pub use command::*;
+pub use dump::*;
 pub use edge::*;
 pub use graph::*;
 pub use node::*;
@@ -16,6 +22,7 @@ pub use node_slot::*;
 pub use nodes::*;
 pub use schedule::*;
 pub use system::*;
+pub use validation::*;
 
 use thiserror::Error;
 
@@ -36,6 +43,8 @@ pub enum RenderGraphError {
     },
     #[error("Attempted to add an edge that already exists")]
     EdgeAlreadyExists(Edge),
+    #[error("Attempted to remove an edge that does not exist")]
+    EdgeDoesNotExist(Edge),
     #[error("Node has an unconnected input slot.")]
     UnconnectedNodeInputSlot { node: NodeId, input_slot: usize },
     #[error("Node has an unconnected output slot.")]
@@ -1,5 +1,5 @@
 use super::{Edge, RenderGraphError, ResourceSlotInfo, ResourceSlots};
-use crate::renderer::RenderContext;
+use crate::{renderer::RenderContext, texture::TextureUsage};
 use bevy_ecs::{Commands, Resources, System, World};
 use downcast_rs::{impl_downcast, Downcast};
 use std::{borrow::Cow, fmt::Debug};
@@ -15,15 +15,34 @@ impl NodeId {
     }
 }
 
+/// A step of the [RenderGraph](super::RenderGraph). Plugins can implement this to add their own passes and connect
+/// them to the existing nodes with [RenderGraph::add_slot_edge](super::RenderGraph::add_slot_edge) and
+/// [RenderGraph::add_node_edge](super::RenderGraph::add_node_edge).
 pub trait Node: Downcast + Send + Sync + 'static {
+    /// The resources this node consumes. Each input slot must be connected to an output slot of another node.
     fn input(&self) -> &[ResourceSlotInfo] {
         &[]
     }
 
+    /// The resources this node produces. They have to be set in [Node::update].
     fn output(&self) -> &[ResourceSlotInfo] {
         &[]
     }
 
+    /// Runs once per frame before any node of the graph is updated. Nodes get exclusive access to the [World] here,
+    /// so this is the place to gather the data needed to record commands later. The [RenderGraph](super::RenderGraph)
+    /// resource is borrowed while this runs.
+    fn prepare(&mut self, _world: &mut World, _resources: &Resources) {}
+
+    /// How the node uses the texture in input slot `index`, if it is a texture. Render graph validation checks that
+    /// the bound texture has this usage, that attachments have the same size and that no texture is rendered to
+    /// while the node uses it in another slot.
+    fn input_texture_usage(&self, _index: usize) -> Option<TextureUsage> {
+        None
+    }
+
+    /// Records the node's gpu commands to `render_context`. `input` holds the resources of the connected output slots
+    /// and the resources this node produces have to be written to `output`.
     fn update(
         &mut self,
         world: &World,
@@ -64,6 +83,24 @@ impl Edges {
         Ok(())
     }
 
+    pub(crate) fn remove_input_edge(&mut self, edge: &Edge) -> Result<(), RenderGraphError> {
+        if let Some(index) = self.input_edges.iter().position(|e| e == edge) {
+            self.input_edges.swap_remove(index);
+            Ok(())
+        } else {
+            Err(RenderGraphError::EdgeDoesNotExist(edge.clone()))
+        }
+    }
+
+    pub(crate) fn remove_output_edge(&mut self, edge: &Edge) -> Result<(), RenderGraphError> {
+        if let Some(index) = self.output_edges.iter().position(|e| e == edge) {
+            self.output_edges.swap_remove(index);
+            Ok(())
+        } else {
+            Err(RenderGraphError::EdgeDoesNotExist(edge.clone()))
+        }
+    }
+
     pub fn has_input_edge(&self, edge: &Edge) -> bool {
         self.input_edges.contains(edge)
     }
@@ -105,6 +142,9 @@ impl Edges {
     }
 }
 
+/// Decides each frame whether a node runs, for example to skip a pass that has nothing to draw
+pub type NodeCondition = Box<dyn Fn(&World, &Resources) -> bool + Send + Sync>;
+
 pub struct NodeState {
     pub id: NodeId,
     pub name: Option<Cow<'static, str>>,
@@ -112,6 +152,10 @@ pub struct NodeState {
     pub input_slots: ResourceSlots,
     pub output_slots: ResourceSlots,
     pub edges: Edges,
+    /// Disabled nodes are skipped, see [RenderGraph::set_node_enabled](super::RenderGraph::set_node_enabled)
+    pub enabled: bool,
+    pub condition: Option<NodeCondition>,
+    active: bool,
 }
 
 impl Debug for NodeState {
@@ -136,9 +180,26 @@ impl NodeState {
                 input_edges: Vec::new(),
                 output_edges: Vec::new(),
             },
+            enabled: true,
+            condition: None,
+            active: true,
         }
     }
 
+    /// Whether the node runs this frame. Updated by [RenderGraph::prepare](super::RenderGraph::prepare) from
+    /// [NodeState::enabled] and [NodeState::condition].
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub(crate) fn update_active(&mut self, world: &World, resources: &Resources) {
+        self.active = self.enabled
+            && self
+                .condition
+                .as_ref()
+                .map_or(true, |condition| condition(world, resources));
+    }
+
     pub fn node<T>(&self) -> Result<&T, RenderGraphError>
     where
         T: Node,
@@ -0,0 +1,155 @@
+use crate::{
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, ColorStateDescriptor, ColorWrite, CullMode, FrontFace, PipelineDescriptor,
+        RasterizationStateDescriptor,
+    },
+    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    renderer::{
+        BindGroup, RenderContext, RenderResourceBindings, RenderResourceId, RenderResourceType,
+        SamplerId,
+    },
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{SamplerDescriptor, TextureFormat, TextureUsage},
+    Color,
+};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Resources, World};
+use bevy_type_registry::TypeUuid;
+use std::borrow::Cow;
+
+pub const BLIT_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 1225712623164748908);
+
+/// Draws a texture over a color attachment of the default format with a fullscreen triangle, for example to show a
+/// texture the scene was rendered to in the window
+#[derive(Debug, Default)]
+pub struct BlitNode {
+    sampler: Option<SamplerId>,
+}
+
+impl BlitNode {
+    pub const IN_TEXTURE: &'static str = "texture";
+    pub const IN_COLOR_ATTACHMENT: &'static str = "color_attachment";
+}
+
+impl Node for BlitNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        static INPUT: &[ResourceSlotInfo] = &[
+            ResourceSlotInfo {
+                name: Cow::Borrowed(BlitNode::IN_TEXTURE),
+                resource_type: RenderResourceType::Texture,
+            },
+            ResourceSlotInfo {
+                name: Cow::Borrowed(BlitNode::IN_COLOR_ATTACHMENT),
+                resource_type: RenderResourceType::Texture,
+            },
+        ];
+        INPUT
+    }
+
+    fn input_texture_usage(&self, index: usize) -> Option<TextureUsage> {
+        const IN_TEXTURE: usize = 0;
+        if index == IN_TEXTURE {
+            Some(TextureUsage::SAMPLED)
+        } else {
+            Some(TextureUsage::OUTPUT_ATTACHMENT)
+        }
+    }
+
+    fn update(
+        &mut self,
+        _world: &World,
+        resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        let (texture, color_attachment) = match (input.get(0), input.get(1)) {
+            (Some(RenderResourceId::Texture(texture)), Some(RenderResourceId::Texture(target))) => {
+                (texture, target)
+            }
+            _ => return,
+        };
+
+        let bind_group_descriptor = {
+            let shaders = resources.get::<Assets<Shader>>().unwrap();
+            let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+            let pipeline = pipelines.get_mut(&BLIT_PIPELINE_HANDLE).unwrap();
+            if pipeline.layout.is_none() {
+                pipeline.reflect_layout(&shaders, false, &[]);
+            }
+            render_context.resources().create_render_pipeline(
+                BLIT_PIPELINE_HANDLE,
+                pipeline,
+                &shaders,
+            );
+            pipeline.get_layout().unwrap().bind_groups[0].id
+        };
+
+        let render_resource_context = render_context.resources();
+        let sampler = *self.sampler.get_or_insert_with(|| {
+            render_resource_context.create_sampler(&SamplerDescriptor::default())
+        });
+        let bind_group = BindGroup::build()
+            .add_texture(0, texture)
+            .add_sampler(1, sampler)
+            .finish();
+        // bind groups are cleared at the end of every frame
+        render_resource_context.create_bind_group(bind_group_descriptor, &bind_group);
+
+        let pass_descriptor = PassDescriptor {
+            color_attachments: vec![RenderPassColorAttachmentDescriptor {
+                attachment: TextureAttachment::Id(color_attachment),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+            sample_count: 1,
+        };
+        render_context.begin_pass(
+            &pass_descriptor,
+            &RenderResourceBindings::default(),
+            &mut |render_pass| {
+                render_pass.set_pipeline(&BLIT_PIPELINE_HANDLE);
+                render_pass.set_bind_group(0, bind_group_descriptor, bind_group.id, None);
+                render_pass.draw(0..3, 0..1);
+            },
+        );
+    }
+}
+
+pub(crate) fn build_blit_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        name: Some("blit".to_string()),
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("blit.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("blit.frag"),
+            ))),
+        })
+    }
+}
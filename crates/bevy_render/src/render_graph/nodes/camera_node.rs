@@ -1,5 +1,6 @@
 use crate::{
     camera::{ActiveCameras, Camera},
+    exposure::{Exposure, Tonemapping},
     render_graph::{CommandQueue, Node, ResourceSlots, SystemNode},
     renderer::{
         BufferId, BufferInfo, BufferUsage, RenderContext, RenderResourceBinding,
@@ -12,6 +13,17 @@ use bevy_ecs::{Commands, IntoQuerySystem, Local, Query, Res, ResMut, Resources,
 use bevy_transform::prelude::*;
 use std::borrow::Cow;
 
+/// The size of the camera uniform. Shaders can leave out the members at the end that they don't need:
+/// ```glsl
+/// layout(set = 0, binding = 0) uniform Camera {
+///     mat4 ViewProj;
+///     vec4 CameraPosition;
+///     // x: exposure multiplier, y: 1.0 if colors should be tonemapped
+///     vec4 CameraExposure;
+/// };
+/// ```
+const CAMERA_UNIFORM_SIZE: usize = std::mem::size_of::<[[f32; 4]; 6]>();
+
 #[derive(Debug)]
 pub struct CameraNode {
     command_queue: CommandQueue,
@@ -74,21 +86,22 @@ pub fn camera_node_system(
     // PERF: this write on RenderResourceAssignments will prevent this system from running in parallel
     // with other systems that do the same
     mut render_resource_bindings: ResMut<RenderResourceBindings>,
-    query: Query<(&Camera, &GlobalTransform)>,
+    query: Query<(&Camera, &GlobalTransform, Option<&Exposure>)>,
 ) {
     let render_resource_context = &**render_resource_context;
 
-    let (camera, global_transform) = if let Some(entity) = active_cameras.get(&state.camera_name) {
-        query.get(entity).unwrap()
-    } else {
-        return;
-    };
+    let (camera, global_transform, exposure) =
+        if let Some(entity) = active_cameras.get(&state.camera_name) {
+            query.get(entity).unwrap()
+        } else {
+            return;
+        };
 
     let staging_buffer = if let Some(staging_buffer) = state.staging_buffer {
         render_resource_context.map_buffer(staging_buffer);
         staging_buffer
     } else {
-        let size = std::mem::size_of::<[[f32; 4]; 4]>();
+        let size = CAMERA_UNIFORM_SIZE;
         let buffer = render_resource_context.create_buffer(BufferInfo {
             size,
             buffer_usage: BufferUsage::COPY_DST | BufferUsage::UNIFORM,
@@ -117,12 +130,24 @@ pub fn camera_node_system(
     let matrix_size = std::mem::size_of::<[[f32; 4]; 4]>();
     let camera_matrix: [f32; 16] =
         (camera.projection_matrix * global_transform.compute_matrix().inverse()).to_cols_array();
+    let translation = global_transform.translation;
+    let camera_position = [translation.x(), translation.y(), translation.z(), 1.0];
+    let exposure = exposure.copied().unwrap_or_else(Exposure::unchanged);
+    let tonemapping = match exposure.tonemapping {
+        Tonemapping::None => 0.0,
+        Tonemapping::Reinhard => 1.0,
+    };
+    let camera_exposure = [exposure.multiplier(), tonemapping, 0.0, 0.0];
+    let position_offset = matrix_size;
+    let exposure_offset = position_offset + std::mem::size_of::<[f32; 4]>();
 
     render_resource_context.write_mapped_buffer(
         staging_buffer,
-        0..matrix_size as u64,
+        0..CAMERA_UNIFORM_SIZE as u64,
         &mut |data, _renderer| {
             data[0..matrix_size].copy_from_slice(camera_matrix.as_bytes());
+            data[position_offset..exposure_offset].copy_from_slice(camera_position.as_bytes());
+            data[exposure_offset..CAMERA_UNIFORM_SIZE].copy_from_slice(camera_exposure.as_bytes());
         },
     );
     render_resource_context.unmap_buffer(staging_buffer);
@@ -133,6 +158,6 @@ pub fn camera_node_system(
         0,
         camera_buffer,
         0,
-        matrix_size as u64,
+        CAMERA_UNIFORM_SIZE as u64,
     );
 }
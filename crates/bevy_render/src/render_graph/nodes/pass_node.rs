@@ -1,19 +1,23 @@
 use crate::{
-    camera::{ActiveCameras, VisibleEntities},
+    camera::{ActiveCameras, Camera, TransparencyMode, VisibleEntities},
     draw::{Draw, RenderCommand},
+    extract::RenderWorld,
     pass::{ClearColor, LoadOp, PassDescriptor, TextureAttachment},
     pipeline::{
         BindGroupDescriptor, BindType, BindingDescriptor, BindingShaderStage, PipelineDescriptor,
         UniformProperty,
     },
-    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    render_graph::{base::Msaa, Node, ResourceSlotInfo, ResourceSlots},
     renderer::{
         BindGroup, BindGroupId, BufferId, RenderContext, RenderResourceBindings, RenderResourceType,
     },
+    texture::{TextureFormat, TextureUsage},
 };
 use bevy_asset::{Assets, Handle};
 use bevy_ecs::{HecsQuery, ReadOnlyFetch, Resources, World};
-use std::{fmt, marker::PhantomData, ops::Deref};
+use bevy_utils::HashSet;
+use smallvec::SmallVec;
+use std::{borrow::Cow, cell::RefCell, fmt, marker::PhantomData, ops::Deref};
 
 #[derive(Debug)]
 struct CameraInfo {
@@ -28,8 +32,17 @@ pub struct PassNode<Q: HecsQuery> {
     color_attachment_input_indices: Vec<Option<usize>>,
     color_resolve_target_indices: Vec<Option<usize>>,
     depth_stencil_attachment_input_index: Option<usize>,
+    /// Whether a color attachment and its resolve target are inputs, which makes the pass follow [Msaa]
+    multisampled: bool,
     default_clear_color_inputs: Vec<usize>,
-    camera_bind_group_descriptor: BindGroupDescriptor,
+    transparency: TransparencyMode,
+    pass_tag: Option<Cow<'static, str>>,
+    /// Match `Q` against the [RenderWorld] entities extracted from visible entities instead of the visible entities
+    query_render_world: bool,
+    /// Pipelines of this pass that can't draw to its attachments, which were warned about already
+    mismatched_pipelines: HashSet<Handle<PipelineDescriptor>>,
+    /// Shaders can declare just the start of the camera uniform
+    camera_bind_group_descriptors: Vec<BindGroupDescriptor>,
     _marker: PhantomData<Q>,
 }
 
@@ -55,15 +68,22 @@ impl<Q: HecsQuery> fmt::Debug for PassNode<Q> {
                 "default_clear_color_inputs",
                 &self.default_clear_color_inputs,
             )
+            .field("transparency", &self.transparency)
+            .field("pass_tag", &self.pass_tag)
+            .field("query_render_world", &self.query_render_world)
+            .field("mismatched_pipelines", &self.mismatched_pipelines)
             .field(
-                "camera_bind_group_descriptor",
-                &self.camera_bind_group_descriptor,
+                "camera_bind_group_descriptors",
+                &self.camera_bind_group_descriptors,
             )
             .finish()
     }
 }
 
 impl<Q: HecsQuery> PassNode<Q> {
+    /// Passes whose color attachments and resolve targets are both inputs follow [Msaa]: they draw with as many
+    /// samples as [Msaa] and resolve their attachments, or draw to the resolve targets directly while [Msaa] has one
+    /// sample. The `sample_count` of their descriptor is ignored.
     pub fn new(descriptor: PassDescriptor) -> Self {
         let mut inputs = Vec::new();
         let mut color_attachment_input_indices = Vec::new();
@@ -90,6 +110,11 @@ impl<Q: HecsQuery> PassNode<Q> {
             }
         }
 
+        let multisampled = color_attachment_input_indices
+            .iter()
+            .zip(color_resolve_target_indices.iter())
+            .any(|(attachment, resolve_target)| attachment.is_some() && resolve_target.is_some());
+
         let mut depth_stencil_attachment_input_index = None;
         if let Some(ref depth_stencil_attachment) = descriptor.depth_stencil_attachment {
             if let TextureAttachment::Input(ref name) = depth_stencil_attachment.attachment {
@@ -101,18 +126,15 @@ impl<Q: HecsQuery> PassNode<Q> {
             }
         }
 
-        let camera_bind_group_descriptor = BindGroupDescriptor::new(
-            0,
-            vec![BindingDescriptor {
-                name: "Camera".to_string(),
-                index: 0,
-                bind_type: BindType::Uniform {
-                    dynamic: false,
-                    property: UniformProperty::Struct(vec![UniformProperty::Mat4]),
-                },
-                shader_stage: BindingShaderStage::VERTEX | BindingShaderStage::FRAGMENT,
-            }],
-        );
+        let camera_bind_group_descriptors = vec![
+            camera_bind_group_descriptor(vec![UniformProperty::Mat4]),
+            camera_bind_group_descriptor(vec![UniformProperty::Mat4, UniformProperty::Vec4]),
+            camera_bind_group_descriptor(vec![
+                UniformProperty::Mat4,
+                UniformProperty::Vec4,
+                UniformProperty::Vec4,
+            ]),
+        ];
 
         PassNode {
             descriptor,
@@ -121,8 +143,13 @@ impl<Q: HecsQuery> PassNode<Q> {
             color_attachment_input_indices,
             color_resolve_target_indices,
             depth_stencil_attachment_input_index,
+            multisampled,
             default_clear_color_inputs: Vec::new(),
-            camera_bind_group_descriptor,
+            transparency: TransparencyMode::Sorted,
+            pass_tag: None,
+            query_render_world: false,
+            mismatched_pipelines: HashSet::default(),
+            camera_bind_group_descriptors,
             _marker: PhantomData::default(),
         }
     }
@@ -137,6 +164,40 @@ impl<Q: HecsQuery> PassNode<Q> {
     pub fn use_default_clear_color(&mut self, color_attachment_index: usize) {
         self.default_clear_color_inputs.push(color_attachment_index);
     }
+
+    /// Passes are [TransparencyMode::Sorted] by default: they draw every entity of sorted cameras, and only the
+    /// opaque entities of weighted blended cameras. [TransparencyMode::WeightedBlended] passes only draw the
+    /// transparent entities of weighted blended cameras.
+    pub fn set_transparency(&mut self, transparency: TransparencyMode) {
+        self.transparency = transparency;
+    }
+
+    /// Entities can have pipelines for several passes. A pass only draws the pipelines with the same
+    /// [PipelineDescriptor::pass_tag] as it, so passes without a tag, like the main pass, only draw untagged pipelines.
+    pub fn set_pass_tag(&mut self, pass_tag: &'static str) {
+        self.pass_tag = Some(Cow::Borrowed(pass_tag));
+    }
+
+    /// Only draws the visible entities whose components extracted to the [RenderWorld] match `Q`, like the ones an
+    /// [extracted](crate::render_graph::RenderResourcesNode::extracted) render resources node binds
+    pub fn query_render_world(&mut self) {
+        self.query_render_world = true;
+    }
+}
+
+fn camera_bind_group_descriptor(properties: Vec<UniformProperty>) -> BindGroupDescriptor {
+    BindGroupDescriptor::new(
+        0,
+        vec![BindingDescriptor {
+            name: "Camera".into(),
+            index: 0,
+            bind_type: BindType::Uniform {
+                dynamic: false,
+                property: UniformProperty::Struct(properties),
+            },
+            shader_stage: BindingShaderStage::VERTEX | BindingShaderStage::FRAGMENT,
+        }],
+    )
 }
 
 impl<Q: HecsQuery + Send + Sync + 'static> Node for PassNode<Q>
@@ -147,6 +208,15 @@ where
         &self.inputs
     }
 
+    fn input_texture_usage(&self, index: usize) -> Option<TextureUsage> {
+        // every input of a pass is an attachment
+        if index < self.inputs.len() {
+            Some(TextureUsage::OUTPUT_ATTACHMENT)
+        } else {
+            None
+        }
+    }
+
     fn update(
         &mut self,
         world: &World,
@@ -158,21 +228,41 @@ where
         let render_resource_bindings = resources.get::<RenderResourceBindings>().unwrap();
         let pipelines = resources.get::<Assets<PipelineDescriptor>>().unwrap();
         let active_cameras = resources.get::<ActiveCameras>().unwrap();
+        let render_world = if self.query_render_world {
+            resources.get::<RenderWorld>()
+        } else {
+            None
+        };
 
+        if self.multisampled {
+            self.descriptor.sample_count = resources.get::<Msaa>().map_or(1, |msaa| msaa.samples);
+        }
+        let input_texture = |input_index: usize| {
+            TextureAttachment::Id(input.get(input_index).unwrap().get_texture().unwrap())
+        };
         for (i, color_attachment) in self.descriptor.color_attachments.iter_mut().enumerate() {
             if self.default_clear_color_inputs.contains(&i) {
                 if let Some(default_clear_color) = resources.get::<ClearColor>() {
                     color_attachment.ops.load = LoadOp::Clear(default_clear_color.0);
                 }
             }
-            if let Some(input_index) = self.color_attachment_input_indices[i] {
-                color_attachment.attachment =
-                    TextureAttachment::Id(input.get(input_index).unwrap().get_texture().unwrap());
-            }
-            if let Some(input_index) = self.color_resolve_target_indices[i] {
-                color_attachment.resolve_target = Some(TextureAttachment::Id(
-                    input.get(input_index).unwrap().get_texture().unwrap(),
-                ));
+            match (
+                self.color_attachment_input_indices[i],
+                self.color_resolve_target_indices[i],
+            ) {
+                // the multisampled attachment is a placeholder while the pass isn't multisampled
+                (Some(_), Some(resolve_index)) if self.descriptor.sample_count == 1 => {
+                    color_attachment.attachment = input_texture(resolve_index);
+                    color_attachment.resolve_target = None;
+                }
+                (attachment_index, resolve_index) => {
+                    if let Some(input_index) = attachment_index {
+                        color_attachment.attachment = input_texture(input_index);
+                    }
+                    if let Some(input_index) = resolve_index {
+                        color_attachment.resolve_target = Some(input_texture(input_index));
+                    }
+                }
             }
         }
 
@@ -191,22 +281,62 @@ where
                 } else {
                     continue;
                 };
-            if render_context
-                .resources()
-                .bind_group_descriptor_exists(self.camera_bind_group_descriptor.id)
-            {
-                let camera_bind_group = BindGroup::build().add_binding(0, camera_binding).finish();
-                render_context
+            let camera_bind_group = BindGroup::build().add_binding(0, camera_binding).finish();
+            for descriptor in self.camera_bind_group_descriptors.iter() {
+                if render_context
                     .resources()
-                    .create_bind_group(self.camera_bind_group_descriptor.id, &camera_bind_group);
-                camera_info.bind_group_id = Some(camera_bind_group.id);
+                    .bind_group_descriptor_exists(descriptor.id)
+                {
+                    render_context
+                        .resources()
+                        .create_bind_group(descriptor.id, &camera_bind_group);
+                    camera_info.bind_group_id = Some(camera_bind_group.id);
+                }
             }
         }
 
+        // swap chain textures have no descriptor, they use the default format
+        let color_attachment_formats = self
+            .descriptor
+            .color_attachments
+            .iter()
+            .map(|color_attachment| match color_attachment.attachment {
+                TextureAttachment::Id(texture) => render_context
+                    .resources()
+                    .get_texture_descriptor(texture)
+                    .map(|descriptor| descriptor.format)
+                    .unwrap_or_default(),
+                _ => TextureFormat::default(),
+            })
+            .collect::<SmallVec<[TextureFormat; 4]>>();
+
+        // camera viewports are relative to the size of the attachments
+        let target_size = self
+            .descriptor
+            .color_attachments
+            .first()
+            .map(|color_attachment| &color_attachment.attachment)
+            .or_else(|| {
+                self.descriptor
+                    .depth_stencil_attachment
+                    .as_ref()
+                    .map(|depth_stencil_attachment| &depth_stencil_attachment.attachment)
+            })
+            .and_then(|attachment| match attachment {
+                TextureAttachment::Id(texture) => {
+                    render_context.resources().get_texture_descriptor(*texture)
+                }
+                _ => None,
+            })
+            .map(|descriptor| (descriptor.size.width, descriptor.size.height));
+
+        // the pass callback can't mutate what it captures
+        let mismatched_pipelines = RefCell::new(HashSet::default());
         render_context.begin_pass(
             &self.descriptor,
             &render_resource_bindings,
             &mut |render_pass| {
+                let mut viewport_set = false;
                 for camera_info in self.cameras.iter() {
                     let camera_bind_group_id= if let Some(bind_group_id) = camera_info.bind_group_id {
                         bind_group_id
@@ -215,16 +345,50 @@ where
                     };
 
                     // get an ordered list of entities visible to the camera
-                    let visible_entities = if let Some(camera_entity) = active_cameras.get(&camera_info.name) {
-                        world.get::<VisibleEntities>(camera_entity).unwrap()
+                    let camera_entity = if let Some(camera_entity) = active_cameras.get(&camera_info.name) {
+                        camera_entity
                     } else {
                         continue;
                     };
+                    let visible_entities = world.get::<VisibleEntities>(camera_entity).unwrap();
+                    let camera_transparency = world
+                        .get::<Camera>(camera_entity)
+                        .map(|camera| camera.transparency)
+                        .unwrap_or_default();
+
+                    if let Some((width, height)) = target_size {
+                        let viewport = world.get::<Camera>(camera_entity).ok().and_then(|camera| camera.viewport);
+                        match viewport {
+                            Some(viewport) => {
+                                let (x, y, w, h) = viewport.physical_rect(width, height);
+                                if w == 0 || h == 0 {
+                                    continue;
+                                }
+                                render_pass.set_viewport(x as f32, y as f32, w as f32, h as f32, 0.0, 1.0);
+                                render_pass.set_scissor_rect(x, y, w, h);
+                                viewport_set = true;
+                            }
+                            None if viewport_set => {
+                                render_pass.set_viewport(0.0, 0.0, width as f32, height as f32, 0.0, 1.0);
+                                render_pass.set_scissor_rect(0, 0, width, height);
+                                viewport_set = false;
+                            }
+                            None => {}
+                        }
+                    }
 
                     // attempt to draw each visible entity
                     let mut draw_state = DrawState::default();
                     for visible_entity in visible_entities.iter() {
-                        if world.query_one::<Q>(visible_entity.entity).is_err() {
+                        let matches_query = match render_world {
+                            Some(ref render_world) => render_world
+                                .get_render_entity(visible_entity.entity)
+                                .map_or(false, |render_entity| {
+                                    render_world.world().query_one::<Q>(render_entity).is_ok()
+                                }),
+                            None => world.query_one::<Q>(visible_entity.entity).is_ok(),
+                        };
+                        if !matches_query {
                             // visible entity does not match the Pass query
                             continue;
                         }
@@ -239,19 +403,51 @@ where
                             continue;
                         }
 
+                        let draws_entity = match (camera_transparency, self.transparency) {
+                            (TransparencyMode::Sorted, TransparencyMode::Sorted) => true,
+                            (TransparencyMode::Sorted, TransparencyMode::WeightedBlended) => false,
+                            (TransparencyMode::WeightedBlended, pass_transparency) => {
+                                draw.is_transparent == (pass_transparency == TransparencyMode::WeightedBlended)
+                            }
+                        };
+                        if !draws_entity {
+                            continue;
+                        }
+
                         // each Draw component contains an ordered list of render commands. we turn those into actual render commands here
+                        let mut skip_pipeline = false;
                         for render_command in draw.render_commands.iter() {
+                            if skip_pipeline && !matches!(render_command, RenderCommand::SetPipeline { .. }) {
+                                continue;
+                            }
+
                             match render_command {
                                 RenderCommand::SetPipeline { pipeline } => {
-                                    // TODO: Filter pipelines
-                                    render_pass.set_pipeline(pipeline);
                                     let descriptor = pipelines.get(pipeline).unwrap();
+                                    // pipelines of other passes are drawn by those passes
+                                    skip_pipeline = descriptor.pass_tag != self.pass_tag;
+                                    if skip_pipeline {
+                                        continue;
+                                    }
+                                    // drawing a pipeline to attachments it wasn't made for is an error of the backend
+                                    skip_pipeline = descriptor.color_states.len() != color_attachment_formats.len()
+                                        || descriptor
+                                            .color_states
+                                            .iter()
+                                            .zip(color_attachment_formats.iter())
+                                            .any(|(color_state, format)| color_state.format != *format);
+                                    if skip_pipeline {
+                                        mismatched_pipelines.borrow_mut().insert(pipeline.clone_weak());
+                                        continue;
+                                    }
+
+                                    render_pass.set_pipeline(pipeline);
                                     draw_state.set_pipeline(pipeline, descriptor);
 
                                     // try to set current camera bind group
                                     let layout = descriptor.get_layout().unwrap();
                                     if let Some(descriptor) = layout.get_bind_group(0) {
-                                        if *descriptor == self.camera_bind_group_descriptor {
+                                        if self.camera_bind_group_descriptors.contains(descriptor) {
                                             draw_state.set_bind_group(0, camera_bind_group_id);
                                             render_pass.set_bind_group(
                                                 0,
@@ -284,6 +480,21 @@ where
                                         log::info!("Could not draw because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
                                     }
                                 }
+                                RenderCommand::DrawIndexedIndirect {
+                                    indirect_buffer,
+                                    indirect_offset,
+                                    count,
+                                } => {
+                                    if draw_state.can_draw_indexed() {
+                                        render_pass.draw_indexed_indirect(
+                                            *indirect_buffer,
+                                            *indirect_offset,
+                                            *count,
+                                        );
+                                    } else {
+                                        log::info!("Could not draw indexed indirect because the pipeline layout wasn't fully set for pipeline: {:?}", draw_state.pipeline);
+                                    }
+                                }
                                 RenderCommand::SetVertexBuffer {
                                     buffer,
                                     offset,
@@ -320,6 +531,19 @@ where
                 }
             },
         );
+
+        // the draws are skipped every frame, but only reported once
+        for pipeline in mismatched_pipelines.into_inner() {
+            if !self.mismatched_pipelines.contains(&pipeline) {
+                log::warn!(
+                    "pipeline {:?} with pass tag {:?} wasn't drawn because its color states don't match the color attachments of its pass: {:?}",
+                    pipeline,
+                    self.pass_tag,
+                    color_attachment_formats,
+                );
+                self.mismatched_pipelines.insert(pipeline);
+            }
+        }
     }
 }
 
@@ -327,8 +551,9 @@ where
 #[derive(Debug, Default)]
 struct DrawState {
     pipeline: Option<Handle<PipelineDescriptor>>,
-    bind_groups: Vec<Option<BindGroupId>>,
-    vertex_buffers: Vec<Option<BufferId>>,
+    // a draw state is created for every camera each frame, so these stay inline for typical pipeline layouts
+    bind_groups: SmallVec<[Option<BindGroupId>; 4]>,
+    vertex_buffers: SmallVec<[Option<BufferId>; 4]>,
     index_buffer: Option<BufferId>,
 }
 
@@ -1,21 +1,24 @@
 use crate::{
     draw::Draw,
+    extract::{MainEntity, RenderWorld},
     pipeline::RenderPipelines,
     render_graph::{CommandQueue, Node, ResourceSlots, SystemNode},
     renderer::{
         self, BufferInfo, BufferUsage, RenderContext, RenderResourceBinding,
-        RenderResourceBindings, RenderResourceContext, RenderResourceHints,
+        RenderResourceBindings, RenderResourceContext, RenderResourceHints, RenderResourceId,
+        RenderResourceOwner,
     },
     texture,
 };
 
-use bevy_asset::{Asset, Assets, Handle, HandleId};
+use bevy_app::{EventReader, Events};
+use bevy_asset::{Asset, AssetEvent, Assets, Handle, HandleId};
 use bevy_ecs::{
     Commands, Entity, IntoQuerySystem, Local, Query, Res, ResMut, Resources, System, World,
 };
-use bevy_utils::HashMap;
+use bevy_utils::{HashMap, HashSet};
 use renderer::{AssetRenderResourceBindings, BufferId, RenderResourceType, RenderResources};
-use std::{hash::Hash, marker::PhantomData, ops::DerefMut};
+use std::{any::TypeId, hash::Hash, marker::PhantomData, ops::DerefMut};
 
 pub const BIND_BUFFER_ALIGNMENT: usize = 256;
 
@@ -103,7 +106,7 @@ impl<I: Hash + Eq> BufferArray<I> {
 
     pub fn allocate_buffer(&mut self, render_resource_context: &dyn RenderResourceContext) {
         if let Some(old_buffer) = self.buffer.take() {
-            render_resource_context.remove_buffer(old_buffer);
+            render_resource_context.release_resource(RenderResourceId::Buffer(old_buffer));
         }
 
         let new_len = if self.buffer_capacity == 0 {
@@ -209,7 +212,7 @@ where
         // TODO: allow staging buffer to scale down
         if self.required_staging_buffer_size > self.staging_buffer_size {
             if let Some(staging_buffer) = self.staging_buffer {
-                render_resource_context.remove_buffer(staging_buffer);
+                render_resource_context.release_resource(RenderResourceId::Buffer(staging_buffer));
             }
 
             if self.required_staging_buffer_size > 0 {
@@ -235,9 +238,11 @@ where
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn write_uniform_buffers(
         &mut self,
         id: I,
+        owner: RenderResourceOwner,
         uniforms: &T,
         dynamic_uniforms: bool,
         render_resource_context: &dyn RenderResourceContext,
@@ -275,7 +280,8 @@ where
                                 if size == current_size {
                                     matching_buffer = Some(buffer_id);
                                 } else {
-                                    render_resource_context.remove_buffer(buffer_id);
+                                    render_resource_context
+                                        .release_resource(RenderResourceId::Buffer(buffer_id));
                                 }
                             }
                         }
@@ -297,6 +303,8 @@ where
                                 buffer_usage: BufferUsage::COPY_DST | usage,
                                 ..Default::default()
                             });
+                            render_resource_context
+                                .set_resource_owner(owner, RenderResourceId::Buffer(buffer));
 
                             render_resource_bindings.set(
                                 render_resource_name,
@@ -356,6 +364,7 @@ where
 {
     command_queue: CommandQueue,
     dynamic_uniforms: bool,
+    extracted: bool,
     _marker: PhantomData<T>,
 }
 
@@ -367,9 +376,21 @@ where
         RenderResourcesNode {
             command_queue: CommandQueue::default(),
             dynamic_uniforms,
+            extracted: false,
             _marker: PhantomData::default(),
         }
     }
+
+    /// Binds the `T` components of the [RenderWorld] instead of the main world. Each of them is bound to the
+    /// [RenderPipelines] of the main world entity it was extracted from, so `T` is usually the
+    /// [ExtractComponent::Extracted](crate::extract::ExtractComponent::Extracted) type of a component registered with
+    /// an [ExtractComponentPlugin](crate::extract::ExtractComponentPlugin).
+    pub fn extracted(dynamic_uniforms: bool) -> Self {
+        RenderResourcesNode {
+            extracted: true,
+            ..Self::new(dynamic_uniforms)
+        }
+    }
 }
 
 impl<T> Node for RenderResourcesNode<T>
@@ -393,17 +414,27 @@ where
     T: renderer::RenderResources,
 {
     fn get_system(&self, commands: &mut Commands) -> Box<dyn System> {
-        let system = render_resources_node_system::<T>.system();
-        commands.insert_local_resource(
-            system.id(),
-            RenderResourcesNodeState {
-                command_queue: self.command_queue.clone(),
-                uniform_buffer_arrays: UniformBufferArrays::<Entity, T>::default(),
-                dynamic_uniforms: self.dynamic_uniforms,
-            },
-        );
-
-        system
+        let node_state = RenderResourcesNodeState {
+            command_queue: self.command_queue.clone(),
+            uniform_buffer_arrays: UniformBufferArrays::<Entity, T>::default(),
+            dynamic_uniforms: self.dynamic_uniforms,
+        };
+        if self.extracted {
+            let system = extracted_render_resources_node_system::<T>.system();
+            commands.insert_local_resource(
+                system.id(),
+                ExtractedRenderResourcesNodeState {
+                    node_state,
+                    bound_entities: HashSet::default(),
+                    extracted_entities: HashSet::default(),
+                },
+            );
+            system
+        } else {
+            let system = render_resources_node_system::<T>.system();
+            commands.insert_local_resource(system.id(), node_state);
+            system
+        }
     }
 }
 
@@ -423,6 +454,41 @@ impl<I, T: RenderResources> Default for RenderResourcesNodeState<I, T> {
     }
 }
 
+struct ExtractedRenderResourcesNodeState<T: RenderResources> {
+    node_state: RenderResourcesNodeState<Entity, T>,
+    /// The main world entities that were bound last frame
+    bound_entities: HashSet<Entity>,
+    /// Scratch set of the main world entities extracted this frame, kept to reuse its allocation
+    extracted_entities: HashSet<Entity>,
+}
+
+impl<T: RenderResources> Default for ExtractedRenderResourcesNodeState<T> {
+    fn default() -> Self {
+        Self {
+            node_state: Default::default(),
+            bound_entities: Default::default(),
+            extracted_entities: Default::default(),
+        }
+    }
+}
+
+struct AssetRenderResourcesNodeState<T: RenderResources + Asset> {
+    node_state: RenderResourcesNodeState<HandleId, T>,
+    asset_event_reader: EventReader<AssetEvent<T>>,
+    /// Scratch buffer for the assets updated this frame, kept to reuse its allocation
+    modified_assets: Vec<HandleId>,
+}
+
+impl<T: RenderResources + Asset> Default for AssetRenderResourcesNodeState<T> {
+    fn default() -> Self {
+        Self {
+            node_state: Default::default(),
+            asset_event_reader: Default::default(),
+            modified_assets: Vec::new(),
+        }
+    }
+}
+
 fn render_resources_node_system<T: RenderResources>(
     mut state: Local<RenderResourcesNodeState<Entity, T>>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
@@ -437,8 +503,11 @@ fn render_resources_node_system<T: RenderResources>(
         uniform_buffer_arrays.initialize(first);
     }
 
+    // this also covers despawned entities
     for entity in query.removed::<T>() {
         uniform_buffer_arrays.remove_bindings(*entity);
+        render_resource_context
+            .release_owner_resources(RenderResourceOwner::Entity(*entity, TypeId::of::<T>()));
     }
 
     for (entity, uniforms, draw, mut render_pipelines) in query.iter_mut() {
@@ -470,6 +539,7 @@ fn render_resources_node_system<T: RenderResources>(
 
                     state.uniform_buffer_arrays.write_uniform_buffers(
                         entity,
+                        RenderResourceOwner::Entity(entity, TypeId::of::<T>()),
                         &uniforms,
                         state.dynamic_uniforms,
                         render_resource_context,
@@ -494,6 +564,7 @@ fn render_resources_node_system<T: RenderResources>(
 
             state.uniform_buffer_arrays.write_uniform_buffers(
                 entity,
+                RenderResourceOwner::Entity(entity, TypeId::of::<T>()),
                 &uniforms,
                 state.dynamic_uniforms,
                 render_resource_context,
@@ -504,6 +575,101 @@ fn render_resources_node_system<T: RenderResources>(
     }
 }
 
+fn extracted_render_resources_node_system<T: RenderResources>(
+    mut state: Local<ExtractedRenderResourcesNodeState<T>>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+    render_world: Res<RenderWorld>,
+    mut query: Query<(&Draw, &mut RenderPipelines)>,
+) {
+    let ExtractedRenderResourcesNodeState {
+        node_state: state,
+        bound_entities,
+        extracted_entities,
+    } = state.deref_mut();
+    let uniform_buffer_arrays = &mut state.uniform_buffer_arrays;
+    let render_resource_context = &**render_resource_context;
+    let render_world = render_world.world();
+
+    // the render world is rebuilt every frame, so entities that weren't extracted this frame lost their component
+    extracted_entities.clear();
+    extracted_entities.extend(
+        render_world
+            .query::<(&MainEntity, &T)>()
+            .map(|(main_entity, _)| main_entity.0),
+    );
+    for entity in bound_entities.difference(extracted_entities) {
+        uniform_buffer_arrays.remove_bindings(*entity);
+        render_resource_context
+            .release_owner_resources(RenderResourceOwner::Entity(*entity, TypeId::of::<T>()));
+    }
+    std::mem::swap(bound_entities, extracted_entities);
+
+    uniform_buffer_arrays.begin_update();
+    // initialize uniform buffer arrays using the first RenderResources
+    if let Some((_, first)) = render_world.query::<(&MainEntity, &T)>().next() {
+        uniform_buffer_arrays.initialize(first);
+    }
+
+    for (main_entity, uniforms) in render_world.query::<(&MainEntity, &T)>() {
+        let (draw, mut render_pipelines) = match query.get_mut(main_entity.0) {
+            Ok(draw) => draw,
+            Err(_) => continue,
+        };
+        if !draw.is_visible {
+            continue;
+        }
+
+        uniform_buffer_arrays.prepare_uniform_buffers(main_entity.0, uniforms);
+        setup_uniform_texture_resources::<T>(
+            &uniforms,
+            render_resource_context,
+            &mut render_pipelines.bindings,
+        )
+    }
+
+    uniform_buffer_arrays.resize_buffer_arrays(render_resource_context);
+    uniform_buffer_arrays.resize_staging_buffer(render_resource_context);
+    let staging_buffer = uniform_buffer_arrays.staging_buffer;
+    let staging_buffer_size = uniform_buffer_arrays.staging_buffer_size;
+
+    let mut write_uniform_buffers = |staging_buffer: &mut [u8]| {
+        for (main_entity, uniforms) in render_world.query::<(&MainEntity, &T)>() {
+            let (draw, mut render_pipelines) = match query.get_mut(main_entity.0) {
+                Ok(draw) => draw,
+                Err(_) => continue,
+            };
+            if !draw.is_visible {
+                continue;
+            }
+
+            state.uniform_buffer_arrays.write_uniform_buffers(
+                main_entity.0,
+                RenderResourceOwner::Entity(main_entity.0, TypeId::of::<T>()),
+                &uniforms,
+                state.dynamic_uniforms,
+                render_resource_context,
+                &mut render_pipelines.bindings,
+                staging_buffer,
+            );
+        }
+    };
+    if let Some(staging_buffer) = staging_buffer {
+        render_resource_context.map_buffer(staging_buffer);
+        render_resource_context.write_mapped_buffer(
+            staging_buffer,
+            0..staging_buffer_size as u64,
+            &mut |staging_buffer, _render_resource_context| write_uniform_buffers(staging_buffer),
+        );
+        render_resource_context.unmap_buffer(staging_buffer);
+
+        state
+            .uniform_buffer_arrays
+            .copy_staging_buffer_to_final_buffers(&mut state.command_queue, staging_buffer);
+    } else {
+        write_uniform_buffers(&mut []);
+    }
+}
+
 #[derive(Default)]
 pub struct AssetRenderResourcesNode<T>
 where
@@ -553,10 +719,14 @@ where
         let system = asset_render_resources_node_system::<T>.system();
         commands.insert_local_resource(
             system.id(),
-            RenderResourcesNodeState {
-                command_queue: self.command_queue.clone(),
-                uniform_buffer_arrays: UniformBufferArrays::<HandleId, T>::default(),
-                dynamic_uniforms: self.dynamic_uniforms,
+            AssetRenderResourcesNodeState {
+                node_state: RenderResourcesNodeState {
+                    command_queue: self.command_queue.clone(),
+                    uniform_buffer_arrays: UniformBufferArrays::<HandleId, T>::default(),
+                    dynamic_uniforms: self.dynamic_uniforms,
+                },
+                asset_event_reader: Default::default(),
+                modified_assets: Vec::new(),
             },
         );
 
@@ -565,17 +735,31 @@ where
 }
 
 fn asset_render_resources_node_system<T: RenderResources + Asset>(
-    mut state: Local<RenderResourcesNodeState<HandleId, T>>,
+    mut state: Local<AssetRenderResourcesNodeState<T>>,
     assets: Res<Assets<T>>,
+    asset_events: Res<Events<AssetEvent<T>>>,
     mut asset_render_resource_bindings: ResMut<AssetRenderResourceBindings>,
     render_resource_context: Res<Box<dyn RenderResourceContext>>,
     mut query: Query<(&Handle<T>, &Draw, &mut RenderPipelines)>,
 ) {
-    let state = state.deref_mut();
+    let AssetRenderResourcesNodeState {
+        node_state: state,
+        asset_event_reader,
+        modified_assets,
+    } = state.deref_mut();
     let uniform_buffer_arrays = &mut state.uniform_buffer_arrays;
     let render_resource_context = &**render_resource_context;
 
-    let modified_assets = assets.ids().collect::<Vec<_>>();
+    for event in asset_event_reader.iter(&asset_events) {
+        if let AssetEvent::Removed { handle } = event {
+            uniform_buffer_arrays.remove_bindings(handle.id);
+            asset_render_resource_bindings.remove(handle);
+            render_resource_context.release_owner_resources(RenderResourceOwner::Asset(handle.id));
+        }
+    }
+
+    modified_assets.clear();
+    modified_assets.extend(assets.ids());
 
     uniform_buffer_arrays.begin_update();
     // initialize uniform buffer arrays using the first RenderResources
@@ -608,6 +792,7 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
                     // TODO: only setup buffer if we haven't seen this handle before
                     state.uniform_buffer_arrays.write_uniform_buffers(
                         *asset_handle,
+                        RenderResourceOwner::Asset(*asset_handle),
                         &asset,
                         state.dynamic_uniforms,
                         render_resource_context,
@@ -631,6 +816,7 @@ fn asset_render_resources_node_system<T: RenderResources + Asset>(
             // TODO: only setup buffer if we haven't seen this handle before
             state.uniform_buffer_arrays.write_uniform_buffers(
                 *asset_handle,
+                RenderResourceOwner::Asset(*asset_handle),
                 &asset,
                 state.dynamic_uniforms,
                 render_resource_context,
@@ -662,6 +848,19 @@ fn setup_uniform_texture_resources<T>(
             let render_resource_name = uniforms.get_render_resource_name(i).unwrap();
             let sampler_name = format!("{}_sampler", render_resource_name);
             if let Some(texture_handle) = render_resource.texture() {
+                // textures that are still loading are replaced with a fallback, so the material's bind group can be
+                // created and it renders while the texture loads
+                let fallback_handle;
+                let texture_handle = if render_resource_context
+                    .get_asset_resource(texture_handle, texture::TEXTURE_ASSET_INDEX)
+                    .is_some()
+                {
+                    texture_handle
+                } else {
+                    fallback_handle = texture::fallback_texture(render_resource_name);
+                    &fallback_handle
+                };
+
                 if let Some(texture_resource) = render_resource_context
                     .get_asset_resource(texture_handle, texture::TEXTURE_ASSET_INDEX)
                 {
@@ -683,3 +882,71 @@ fn setup_uniform_texture_resources<T>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        extract::{ExtractComponent, RenderWorldExtractors},
+        renderer::HeadlessRenderResourceContext,
+    };
+    use bevy_ecs::Schedule;
+    use bevy_math::Vec3;
+    use bevy_transform::prelude::GlobalTransform;
+
+    struct Placement {
+        transform: GlobalTransform,
+        hidden: bool,
+    }
+
+    impl ExtractComponent for Placement {
+        type Extracted = GlobalTransform;
+
+        fn extract(&self) -> Option<GlobalTransform> {
+            if self.hidden {
+                None
+            } else {
+                Some(self.transform)
+            }
+        }
+    }
+
+    #[test]
+    fn binds_extracted_components() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert::<Box<dyn RenderResourceContext>>(Box::new(
+            HeadlessRenderResourceContext::default(),
+        ));
+        let placement = Placement {
+            transform: GlobalTransform::from_translation(Vec3::new(1.0, 2.0, 3.0)),
+            hidden: false,
+        };
+        let shown = world.spawn((placement, Draw::default(), RenderPipelines::default()));
+        let placement = Placement {
+            transform: GlobalTransform::identity(),
+            hidden: true,
+        };
+        let hidden = world.spawn((placement, Draw::default(), RenderPipelines::default()));
+
+        let mut extractors = RenderWorldExtractors::default();
+        extractors.add::<Placement>();
+        let mut render_world = RenderWorld::default();
+        extractors.extract(&world, &mut render_world);
+        resources.insert(render_world);
+
+        let mut commands = Commands::default();
+        let system =
+            RenderResourcesNode::<GlobalTransform>::extracted(true).get_system(&mut commands);
+        commands.apply(&mut world, &mut resources);
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", system);
+        schedule.run(&mut world, &mut resources);
+
+        let bindings = &world.get::<RenderPipelines>(shown).unwrap().bindings;
+        assert!(bindings.get("Transform").is_some());
+        let bindings = &world.get::<RenderPipelines>(hidden).unwrap().bindings;
+        assert!(bindings.get("Transform").is_none());
+    }
+}
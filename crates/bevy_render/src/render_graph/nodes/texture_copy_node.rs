@@ -1,22 +1,21 @@
 use crate::{
     render_graph::{Node, ResourceSlots},
     renderer::{BufferInfo, BufferUsage, RenderContext},
-    texture::{Texture, TextureDescriptor, TEXTURE_ASSET_INDEX},
+    texture::{
+        Extent3d, Texture, TextureRegionWrites, TextureResidency, TextureStreaming,
+        TEXTURE_ASSET_INDEX,
+    },
 };
 use bevy_app::prelude::{EventReader, Events};
 use bevy_asset::{AssetEvent, Assets};
 use bevy_ecs::{Resources, World};
+use bevy_utils::HashSet;
 
 #[derive(Default)]
 pub struct TextureCopyNode {
     pub texture_event_reader: EventReader<AssetEvent<Texture>>,
 }
 
-pub const ALIGNMENT: usize = 256;
-fn get_aligned(data_size: f32) -> usize {
-    ALIGNMENT * ((data_size / ALIGNMENT as f32).ceil() as usize)
-}
-
 impl Node for TextureCopyNode {
     fn update(
         &mut self,
@@ -28,52 +27,124 @@ impl Node for TextureCopyNode {
     ) {
         let texture_events = resources.get::<Events<AssetEvent<Texture>>>().unwrap();
         let textures = resources.get::<Assets<Texture>>().unwrap();
+        let mut streaming = resources.get_mut::<TextureStreaming>().unwrap();
         for event in self.texture_event_reader.iter(&texture_events) {
             match event {
                 AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
                     if let Some(texture) = textures.get(handle) {
-                        let texture_descriptor: TextureDescriptor = texture.into();
-                        let width = texture.size.x() as usize;
-                        let aligned_width = get_aligned(texture.size.x());
-                        let format_size = texture.format.pixel_size();
-                        let mut aligned_data =
-                            vec![0; format_size * aligned_width * texture.size.y() as usize];
-                        texture
-                            .data
-                            .chunks_exact(format_size * width)
-                            .enumerate()
-                            .for_each(|(index, row)| {
-                                let offset = index * aligned_width * format_size;
-                                aligned_data[offset..(offset + width * format_size)]
-                                    .copy_from_slice(row);
-                            });
-                        let texture_buffer = render_context.resources().create_buffer_with_data(
-                            BufferInfo {
-                                buffer_usage: BufferUsage::COPY_SRC,
-                                ..Default::default()
-                            },
-                            &aligned_data,
-                        );
+                        // render targets have no cpu side data to copy
+                        if texture.data.is_empty() {
+                            continue;
+                        }
 
-                        let texture_resource = render_context
-                            .resources()
-                            .get_asset_resource(handle, TEXTURE_ASSET_INDEX)
-                            .unwrap();
-
-                        render_context.copy_buffer_to_texture(
-                            texture_buffer,
-                            0,
-                            (format_size * aligned_width) as u32,
-                            texture_resource.get_texture().unwrap(),
-                            [0, 0, 0],
-                            0,
-                            texture_descriptor.size,
-                        );
-                        render_context.resources().remove_buffer(texture_buffer);
+                        streaming.queue(handle.clone_weak(), texture, 0);
                     }
                 }
-                AssetEvent::Removed { .. } => {}
+                AssetEvent::Removed { handle } => streaming.texture_removed(handle),
             }
         }
+
+        let mut residency = resources.get_mut::<TextureResidency>().unwrap();
+        let mut copied_textures = HashSet::default();
+        for upload in streaming.next_uploads() {
+            let texture = if upload.level > 0 {
+                residency.reduced_texture(&upload.texture)
+            } else {
+                textures.get(&upload.texture)
+            };
+            let texture = if let Some(texture) = texture {
+                texture
+            } else {
+                continue;
+            };
+            let texture_resource = if let Some(texture_resource) = render_context
+                .resources()
+                .get_asset_resource(&upload.texture, TEXTURE_ASSET_INDEX)
+                .and_then(|resource| resource.get_texture())
+            {
+                texture_resource
+            } else {
+                continue;
+            };
+
+            let width = texture.size.x() as u32;
+            let row_size = width as usize * texture.format.pixel_size();
+            let mut aligned_data =
+                vec![0; upload.bytes_per_row * (upload.rows.end - upload.rows.start) as usize];
+            texture.data
+                [upload.rows.start as usize * row_size..upload.rows.end as usize * row_size]
+                .chunks_exact(row_size)
+                .zip(aligned_data.chunks_exact_mut(upload.bytes_per_row))
+                .for_each(|(row, aligned_row)| aligned_row[..row_size].copy_from_slice(row));
+            let texture_buffer = render_context.resources().create_buffer_with_data(
+                BufferInfo {
+                    buffer_usage: BufferUsage::COPY_SRC,
+                    ..Default::default()
+                },
+                &aligned_data,
+            );
+
+            render_context.copy_buffer_to_texture(
+                texture_buffer,
+                0,
+                upload.bytes_per_row as u32,
+                texture_resource,
+                [0, upload.rows.start, 0],
+                0,
+                Extent3d {
+                    width,
+                    height: upload.rows.end - upload.rows.start,
+                    depth: 1,
+                },
+            );
+            render_context.resources().remove_buffer(texture_buffer);
+            if upload.level == 0 && upload.rows == (0..texture.size.y() as u32) {
+                copied_textures.insert(upload.texture);
+            }
+        }
+
+        let mut region_writes = resources.get_mut::<TextureRegionWrites>().unwrap();
+        for write in region_writes.drain() {
+            // textures that are written in place stay at full size. reduced textures get the write when they are
+            // uploaded at full size again
+            residency.keep_full_size(&write.texture);
+            // full copies already contain every region written this frame. partially uploaded textures still need
+            // the writes to the rows that were uploaded before
+            if copied_textures.contains(&write.texture) || residency.level(&write.texture) > 0 {
+                continue;
+            }
+
+            let texture_resource = if let Some(texture_resource) = render_context
+                .resources()
+                .get_asset_resource(&write.texture, TEXTURE_ASSET_INDEX)
+                .and_then(|resource| resource.get_texture())
+            {
+                texture_resource
+            } else {
+                continue;
+            };
+
+            let region_buffer = render_context.resources().create_buffer_with_data(
+                BufferInfo {
+                    buffer_usage: BufferUsage::COPY_SRC,
+                    ..Default::default()
+                },
+                &write.data,
+            );
+            render_context.copy_buffer_to_texture(
+                region_buffer,
+                0,
+                write.bytes_per_row,
+                texture_resource,
+                [write.region.x, write.region.y, 0],
+                0,
+                Extent3d {
+                    width: write.region.width,
+                    height: write.region.height,
+                    depth: 1,
+                },
+            );
+            render_context.resources().remove_buffer(region_buffer);
+        }
     }
 }
@@ -0,0 +1,186 @@
+use crate::{
+    render_graph::{
+        base::{Msaa, MsaaTexture},
+        Node, ResourceSlotInfo, ResourceSlots,
+    },
+    renderer::{RenderContext, RenderResourceId, RenderResourceType},
+    texture::{Texture, TextureDescriptor, TextureUsage, TEXTURE_ASSET_INDEX},
+};
+use bevy_asset::Handle;
+use bevy_ecs::{Resources, World};
+use std::borrow::Cow;
+
+static TEXTURE_SLOT: &[ResourceSlotInfo] = &[ResourceSlotInfo {
+    name: Cow::Borrowed("texture"),
+    resource_type: RenderResourceType::Texture,
+}];
+
+/// Creates a texture with a fixed size. Unlike [WindowTextureNode](super::WindowTextureNode), the texture isn't
+/// resized with a window.
+pub struct TextureNode {
+    descriptor: TextureDescriptor,
+    msaa: Option<MsaaTexture>,
+    /// The samples per pixel of the created texture
+    samples: Option<u32>,
+}
+
+impl TextureNode {
+    pub const OUT_TEXTURE: &'static str = "texture";
+
+    pub fn new(descriptor: TextureDescriptor) -> Self {
+        TextureNode {
+            descriptor,
+            msaa: None,
+            samples: None,
+        }
+    }
+
+    /// A texture whose sample count follows [Msaa], which is recreated when [Msaa] changes
+    pub fn multisampled(descriptor: TextureDescriptor, msaa: MsaaTexture) -> Self {
+        TextureNode {
+            msaa: Some(msaa),
+            ..TextureNode::new(descriptor)
+        }
+    }
+}
+
+impl Node for TextureNode {
+    fn output(&self) -> &[ResourceSlotInfo] {
+        TEXTURE_SLOT
+    }
+
+    fn update(
+        &mut self,
+        _world: &World,
+        resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        output: &mut ResourceSlots,
+    ) {
+        const TEXTURE: usize = 0;
+        let samples = match self.msaa {
+            Some(_) => resources.get::<Msaa>().map_or(1, |msaa| msaa.samples),
+            None => self.descriptor.sample_count,
+        };
+        if self.samples != Some(samples) {
+            let render_resource_context = render_context.resources_mut();
+            if let Some(RenderResourceId::Texture(old_texture)) = output.get(TEXTURE) {
+                render_resource_context.remove_texture(old_texture);
+            }
+
+            let descriptor = match self.msaa {
+                Some(msaa) => msaa.descriptor(self.descriptor, samples),
+                None => self.descriptor,
+            };
+            let texture = render_resource_context.create_texture(descriptor);
+            output.set(TEXTURE, RenderResourceId::Texture(texture));
+            self.samples = Some(samples);
+        }
+    }
+}
+
+/// Outputs the gpu texture of a [Texture] asset, so passes can render to textures created with
+/// [Texture::new_render_target]. Materials that use the same handle sample whatever the pass rendered.
+pub struct AssetTextureNode {
+    texture: Handle<Texture>,
+}
+
+impl AssetTextureNode {
+    pub const OUT_TEXTURE: &'static str = "texture";
+
+    pub fn new(texture: Handle<Texture>) -> Self {
+        AssetTextureNode { texture }
+    }
+}
+
+impl Node for AssetTextureNode {
+    fn output(&self) -> &[ResourceSlotInfo] {
+        TEXTURE_SLOT
+    }
+
+    fn update(
+        &mut self,
+        _world: &World,
+        _resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        output: &mut ResourceSlots,
+    ) {
+        const TEXTURE: usize = 0;
+        // the texture is recreated whenever the asset changes, so look it up every frame
+        if let Some(texture) = render_context
+            .resources()
+            .get_asset_resource(&self.texture, TEXTURE_ASSET_INDEX)
+        {
+            output.set(TEXTURE, texture);
+        }
+    }
+}
+
+/// Copies its input texture to the gpu texture of a [Texture] asset, so materials can sample a copy of an attachment
+/// that is still being rendered to. The asset needs the input's size, format and sample count and
+/// [TextureUsage::COPY_DST](crate::texture::TextureUsage::COPY_DST). Frames where the sizes differ, for example
+/// right after a window is resized, are skipped.
+pub struct AssetTextureCopyNode {
+    texture: Handle<Texture>,
+}
+
+impl AssetTextureCopyNode {
+    pub const IN_TEXTURE: &'static str = "texture";
+
+    pub fn new(texture: Handle<Texture>) -> Self {
+        AssetTextureCopyNode { texture }
+    }
+}
+
+impl Node for AssetTextureCopyNode {
+    fn input(&self) -> &[ResourceSlotInfo] {
+        TEXTURE_SLOT
+    }
+
+    fn input_texture_usage(&self, _index: usize) -> Option<TextureUsage> {
+        Some(TextureUsage::COPY_SRC)
+    }
+
+    fn update(
+        &mut self,
+        _world: &World,
+        _resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        input: &ResourceSlots,
+        _output: &mut ResourceSlots,
+    ) {
+        const INPUT_TEXTURE: usize = 0;
+        let render_resource_context = render_context.resources();
+        let source = if let Some(RenderResourceId::Texture(source)) = input.get(INPUT_TEXTURE) {
+            source
+        } else {
+            return;
+        };
+        let destination = if let Some(RenderResourceId::Texture(destination)) =
+            render_resource_context.get_asset_resource(&self.texture, TEXTURE_ASSET_INDEX)
+        {
+            destination
+        } else {
+            return;
+        };
+
+        let descriptors = (
+            render_resource_context.get_texture_descriptor(source),
+            render_resource_context.get_texture_descriptor(destination),
+        );
+        if let (Some(source_descriptor), Some(destination_descriptor)) = descriptors {
+            if source_descriptor.size == destination_descriptor.size {
+                render_context.copy_texture_to_texture(
+                    source,
+                    [0, 0, 0],
+                    0,
+                    destination,
+                    [0, 0, 0],
+                    0,
+                    source_descriptor.size,
+                );
+            }
+        }
+    }
+}
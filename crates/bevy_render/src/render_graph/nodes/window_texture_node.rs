@@ -1,5 +1,8 @@
 use crate::{
-    render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+    render_graph::{
+        base::{Msaa, MsaaTexture},
+        Node, ResourceSlotInfo, ResourceSlots,
+    },
     renderer::{RenderContext, RenderResourceId, RenderResourceType},
     texture::TextureDescriptor,
 };
@@ -11,6 +14,9 @@ use std::borrow::Cow;
 pub struct WindowTextureNode {
     window_id: WindowId,
     descriptor: TextureDescriptor,
+    msaa: Option<MsaaTexture>,
+    /// The samples per pixel of the current texture
+    samples: u32,
     window_created_event_reader: EventReader<WindowCreated>,
     window_resized_event_reader: EventReader<WindowResized>,
 }
@@ -22,10 +28,24 @@ impl WindowTextureNode {
         WindowTextureNode {
             window_id,
             descriptor,
+            msaa: None,
+            samples: descriptor.sample_count,
             window_created_event_reader: Default::default(),
             window_resized_event_reader: Default::default(),
         }
     }
+
+    /// A texture whose sample count follows [Msaa], which is recreated when [Msaa] changes
+    pub fn multisampled(
+        window_id: WindowId,
+        descriptor: TextureDescriptor,
+        msaa: MsaaTexture,
+    ) -> Self {
+        WindowTextureNode {
+            msaa: Some(msaa),
+            ..WindowTextureNode::new(window_id, descriptor)
+        }
+    }
 }
 
 impl Node for WindowTextureNode {
@@ -53,6 +73,10 @@ impl Node for WindowTextureNode {
         let window = windows
             .get(self.window_id)
             .expect("Received window resized event for non-existent window");
+        let samples = match self.msaa {
+            Some(_) => resources.get::<Msaa>().map_or(1, |msaa| msaa.samples),
+            None => self.descriptor.sample_count,
+        };
 
         if self
             .window_created_event_reader
@@ -62,6 +86,7 @@ impl Node for WindowTextureNode {
                 .window_resized_event_reader
                 .find_latest(&window_resized_events, |e| e.id == window.id())
                 .is_some()
+            || samples != self.samples
         {
             let render_resource_context = render_context.resources_mut();
             if let Some(RenderResourceId::Texture(old_texture)) = output.get(WINDOW_TEXTURE) {
@@ -70,7 +95,12 @@ impl Node for WindowTextureNode {
 
             self.descriptor.size.width = window.width();
             self.descriptor.size.height = window.height();
-            let texture_resource = render_resource_context.create_texture(self.descriptor);
+            self.samples = samples;
+            let descriptor = match self.msaa {
+                Some(msaa) => msaa.descriptor(self.descriptor, samples),
+                None => self.descriptor,
+            };
+            let texture_resource = render_resource_context.create_texture(descriptor);
             output.set(WINDOW_TEXTURE, RenderResourceId::Texture(texture_resource));
         }
     }
@@ -0,0 +1,328 @@
+use super::{NodeState, RenderGraph};
+use crate::{
+    renderer::{RenderResourceContext, RenderResourceId, TextureId},
+    texture::{Extent3d, TextureUsage},
+};
+use bevy_utils::HashSet;
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// A misuse of resources in the [RenderGraph] found by [RenderGraphValidation]
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum RenderGraphValidationError {
+    #[error("Input slot \"{slot}\" of node \"{node}\" is not connected")]
+    UnconnectedInput {
+        node: Cow<'static, str>,
+        slot: Cow<'static, str>,
+    },
+    #[error("Output slot \"{slot}\" of node \"{node}\" is never consumed")]
+    UnconsumedOutput {
+        node: Cow<'static, str>,
+        slot: Cow<'static, str>,
+    },
+    #[error("Node \"{node}\" renders to the texture in slot \"{slot}\" while it also uses it in slot \"{other_slot}\"")]
+    AttachmentReadWrite {
+        node: Cow<'static, str>,
+        slot: Cow<'static, str>,
+        other_slot: Cow<'static, str>,
+    },
+    #[error("The texture in slot \"{slot}\" of node \"{node}\" was created without the {missing:?} usage")]
+    MissingTextureUsage {
+        node: Cow<'static, str>,
+        slot: Cow<'static, str>,
+        missing: TextureUsage,
+    },
+    #[error("Attachment \"{slot}\" of node \"{node}\" is {size:?}, but the node's other attachments are {expected:?}")]
+    AttachmentSizeMismatch {
+        node: Cow<'static, str>,
+        slot: Cow<'static, str>,
+        size: Extent3d,
+        expected: Extent3d,
+    },
+}
+
+impl RenderGraphValidationError {
+    /// Warnings point at likely mistakes that don't fail rendering on their own
+    pub fn is_warning(&self) -> bool {
+        matches!(self, RenderGraphValidationError::UnconsumedOutput { .. })
+    }
+}
+
+/// Checks the [RenderGraph] for resource misuse every frame and reports it with the names of the nodes and slots
+/// involved, instead of leaving it to the graphics backend's validation. Validation costs some cpu time every frame,
+/// so it is disabled by default. Each error is only reported once.
+#[derive(Debug, Default)]
+pub struct RenderGraphValidation {
+    pub enabled: bool,
+    /// Panic on errors instead of logging them. Warnings are always logged.
+    pub panic_on_error: bool,
+    reported: HashSet<String>,
+}
+
+impl RenderGraphValidation {
+    pub fn enabled() -> Self {
+        RenderGraphValidation {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn report(&mut self, errors: impl IntoIterator<Item = RenderGraphValidationError>) {
+        for error in errors {
+            let message = error.to_string();
+            if self.reported.contains(&message) {
+                continue;
+            }
+
+            if error.is_warning() {
+                log::warn!("Render graph validation: {}", message);
+            } else if self.panic_on_error {
+                panic!("Render graph validation: {}", message);
+            } else {
+                log::error!("Render graph validation: {}", message);
+            }
+            self.reported.insert(message);
+        }
+    }
+}
+
+pub(super) fn node_name(node_state: &NodeState) -> Cow<'static, str> {
+    node_state
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("{:?}", node_state.id).into())
+}
+
+impl RenderGraph {
+    /// Checks that every input slot is connected and every output slot is consumed
+    pub fn validate(&self) -> Vec<RenderGraphValidationError> {
+        let mut errors = Vec::new();
+        for node_state in self.iter_nodes() {
+            for (index, slot) in node_state.input_slots.iter().enumerate() {
+                if node_state.edges.get_input_slot_edge(index).is_err() {
+                    errors.push(RenderGraphValidationError::UnconnectedInput {
+                        node: node_name(node_state),
+                        slot: slot.info.name.clone(),
+                    });
+                }
+            }
+
+            for (index, slot) in node_state.output_slots.iter().enumerate() {
+                if node_state.edges.get_output_slot_edge(index).is_err() {
+                    errors.push(RenderGraphValidationError::UnconsumedOutput {
+                        node: node_name(node_state),
+                        slot: slot.info.name.clone(),
+                    });
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// Checks the textures bound to the input slots of a node against the usage the node declares with
+/// [Node::input_texture_usage](super::Node::input_texture_usage). Call this after the inputs are bound and before the
+/// node is updated.
+pub fn validate_node_inputs(
+    node_state: &NodeState,
+    render_resource_context: &dyn RenderResourceContext,
+) -> Vec<RenderGraphValidationError> {
+    let mut errors = Vec::new();
+    let mut textures: Vec<(usize, TextureId, TextureUsage)> = Vec::new();
+    for (index, slot) in node_state.input_slots.iter().enumerate() {
+        if let (Some(usage), Some(RenderResourceId::Texture(texture))) =
+            (node_state.node.input_texture_usage(index), &slot.resource)
+        {
+            textures.push((index, *texture, usage));
+        }
+    }
+
+    let slot_name = |index: usize| {
+        node_state
+            .input_slots
+            .get_slot(index)
+            .map(|slot| slot.info.name.clone())
+            .unwrap_or_else(|_| index.to_string().into())
+    };
+
+    let mut attachment_size = None;
+    for (i, (index, texture, usage)) in textures.iter().enumerate() {
+        if usage.contains(TextureUsage::OUTPUT_ATTACHMENT) {
+            for (other_index, other_texture, _) in textures.iter().skip(i + 1) {
+                if other_texture == texture {
+                    errors.push(RenderGraphValidationError::AttachmentReadWrite {
+                        node: node_name(node_state),
+                        slot: slot_name(*index),
+                        other_slot: slot_name(*other_index),
+                    });
+                }
+            }
+        }
+
+        // textures the backend doesn't know the descriptor of can't be checked further
+        let descriptor = match render_resource_context.get_texture_descriptor(*texture) {
+            Some(descriptor) => descriptor,
+            None => continue,
+        };
+
+        let missing = *usage - descriptor.usage;
+        if !missing.is_empty() {
+            errors.push(RenderGraphValidationError::MissingTextureUsage {
+                node: node_name(node_state),
+                slot: slot_name(*index),
+                missing,
+            });
+        }
+
+        if usage.contains(TextureUsage::OUTPUT_ATTACHMENT) {
+            match attachment_size {
+                Some(expected) if expected != descriptor.size => {
+                    errors.push(RenderGraphValidationError::AttachmentSizeMismatch {
+                        node: node_name(node_state),
+                        slot: slot_name(*index),
+                        size: descriptor.size,
+                        expected,
+                    })
+                }
+                Some(_) => {}
+                None => attachment_size = Some(descriptor.size),
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+        renderer::{HeadlessRenderResourceContext, RenderContext, RenderResourceType},
+        texture::{TextureDescriptor, TextureDimension, TextureFormat},
+    };
+    use bevy_ecs::{Resources, World};
+
+    struct AttachmentNode {
+        inputs: Vec<ResourceSlotInfo>,
+    }
+
+    impl AttachmentNode {
+        fn new(inputs: &[&'static str]) -> Self {
+            AttachmentNode {
+                inputs: inputs
+                    .iter()
+                    .map(|name| ResourceSlotInfo::new(*name, RenderResourceType::Texture))
+                    .collect(),
+            }
+        }
+    }
+
+    impl Node for AttachmentNode {
+        fn input(&self) -> &[ResourceSlotInfo] {
+            &self.inputs
+        }
+
+        fn output(&self) -> &[ResourceSlotInfo] {
+            &self.inputs
+        }
+
+        fn input_texture_usage(&self, _index: usize) -> Option<TextureUsage> {
+            Some(TextureUsage::OUTPUT_ATTACHMENT)
+        }
+
+        fn update(
+            &mut self,
+            _: &World,
+            _: &Resources,
+            _: &mut dyn RenderContext,
+            _: &ResourceSlots,
+            _: &mut ResourceSlots,
+        ) {
+        }
+    }
+
+    fn texture(width: u32, usage: TextureUsage) -> TextureDescriptor {
+        TextureDescriptor {
+            size: Extent3d {
+                width,
+                height: 4,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage,
+        }
+    }
+
+    #[test]
+    fn validate_graph_slots() {
+        let mut graph = RenderGraph::default();
+        graph.add_node("a", AttachmentNode::new(&["color"]));
+        graph.add_node("b", AttachmentNode::new(&["color"]));
+        graph.add_slot_edge("a", "color", "b", "color").unwrap();
+
+        let mut errors = graph.validate();
+        errors.sort_by_key(|error| error.to_string());
+        assert_eq!(
+            errors,
+            vec![
+                RenderGraphValidationError::UnconnectedInput {
+                    node: "a".into(),
+                    slot: "color".into(),
+                },
+                RenderGraphValidationError::UnconsumedOutput {
+                    node: "b".into(),
+                    slot: "color".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_attachments() {
+        let render_resource_context = HeadlessRenderResourceContext::default();
+        let mut graph = RenderGraph::default();
+        graph.add_node("pass", AttachmentNode::new(&["color", "depth", "resolve"]));
+        let node_state = graph.get_node_state_mut("pass").unwrap();
+
+        let color =
+            render_resource_context.create_texture(texture(4, TextureUsage::OUTPUT_ATTACHMENT));
+        let depth = render_resource_context.create_texture(texture(8, TextureUsage::SAMPLED));
+        node_state
+            .input_slots
+            .set(0, RenderResourceId::Texture(color));
+        node_state
+            .input_slots
+            .set(1, RenderResourceId::Texture(depth));
+        node_state
+            .input_slots
+            .set(2, RenderResourceId::Texture(color));
+
+        let errors = validate_node_inputs(node_state, &render_resource_context);
+        assert_eq!(
+            errors,
+            vec![
+                RenderGraphValidationError::AttachmentReadWrite {
+                    node: "pass".into(),
+                    slot: "color".into(),
+                    other_slot: "resolve".into(),
+                },
+                RenderGraphValidationError::MissingTextureUsage {
+                    node: "pass".into(),
+                    slot: "depth".into(),
+                    missing: TextureUsage::OUTPUT_ATTACHMENT,
+                },
+                RenderGraphValidationError::AttachmentSizeMismatch {
+                    node: "pass".into(),
+                    slot: "depth".into(),
+                    size: texture(8, TextureUsage::empty()).size,
+                    expected: texture(4, TextureUsage::empty()).size,
+                },
+            ]
+        );
+    }
+}
@@ -1,7 +1,10 @@
 use super::RenderResourceContext;
 use crate::{
-    pipeline::{BindGroupDescriptorId, PipelineDescriptor},
-    renderer::{BindGroup, BufferId, BufferInfo, RenderResourceId, SamplerId, TextureId},
+    pipeline::{BindGroupDescriptorId, ComputePipelineDescriptor, PipelineDescriptor},
+    renderer::{
+        BindGroup, BufferId, BufferInfo, RenderResourceId, RenderResourceLifetimes,
+        RenderResourceOwner, SamplerId, TextureId,
+    },
     shader::Shader,
     texture::{SamplerDescriptor, TextureDescriptor},
 };
@@ -11,11 +14,12 @@ use bevy_window::Window;
 use parking_lot::RwLock;
 use std::{ops::Range, sync::Arc};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct HeadlessRenderResourceContext {
     buffer_info: Arc<RwLock<HashMap<BufferId, BufferInfo>>>,
     texture_descriptors: Arc<RwLock<HashMap<TextureId, TextureDescriptor>>>,
     pub asset_resources: Arc<RwLock<HashMap<(HandleUntyped, u64), RenderResourceId>>>,
+    pub resource_lifetimes: Arc<RwLock<RenderResourceLifetimes>>,
 }
 
 impl HeadlessRenderResourceContext {
@@ -66,8 +70,25 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
         write(&mut buffer, self);
     }
 
+    fn read_mapped_buffer(
+        &self,
+        id: BufferId,
+        _range: Range<u64>,
+        read: &mut dyn FnMut(&[u8], &dyn RenderResourceContext),
+    ) {
+        let size = self.buffer_info.read().get(&id).unwrap().size;
+        let buffer = vec![0; size];
+        read(&buffer, self);
+    }
+
     fn map_buffer(&self, _id: BufferId) {}
 
+    fn map_buffer_async(&self, _id: BufferId) {}
+
+    fn is_buffer_mapped(&self, _id: BufferId) -> bool {
+        true
+    }
+
     fn unmap_buffer(&self, _id: BufferId) {}
 
     fn create_buffer_with_data(&self, buffer_info: BufferInfo, _data: &[u8]) -> BufferId {
@@ -115,6 +136,14 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
     ) {
     }
 
+    fn create_compute_pipeline(
+        &self,
+        _pipeline_handle: Handle<ComputePipelineDescriptor>,
+        _pipeline_descriptor: &ComputePipelineDescriptor,
+        _shaders: &Assets<Shader>,
+    ) {
+    }
+
     fn create_bind_group(
         &self,
         _bind_group_descriptor_id: BindGroupDescriptorId,
@@ -130,10 +159,37 @@ impl RenderResourceContext for HeadlessRenderResourceContext {
 
     fn clear_bind_groups(&self) {}
 
+    fn set_resource_owner(&self, owner: RenderResourceOwner, resource: RenderResourceId) {
+        self.resource_lifetimes.write().set_owner(owner, resource);
+    }
+
+    fn release_owner_resources(&self, owner: RenderResourceOwner) {
+        self.resource_lifetimes.write().release_owner(owner);
+    }
+
+    fn release_resource(&self, resource: RenderResourceId) {
+        self.resource_lifetimes.write().release(resource);
+    }
+
+    fn free_released_resources(&self) {
+        let expired = self.resource_lifetimes.write().end_frame();
+        for resource in expired {
+            match resource {
+                RenderResourceId::Buffer(buffer) => self.remove_buffer(buffer),
+                RenderResourceId::Texture(texture) => self.remove_texture(texture),
+                RenderResourceId::Sampler(sampler) => self.remove_sampler(sampler),
+            }
+        }
+    }
+
     fn get_buffer_info(&self, buffer: BufferId) -> Option<BufferInfo> {
         self.buffer_info.read().get(&buffer).cloned()
     }
 
+    fn get_texture_descriptor(&self, texture: TextureId) -> Option<TextureDescriptor> {
+        self.texture_descriptors.read().get(&texture).copied()
+    }
+
     fn bind_group_descriptor_exists(
         &self,
         _bind_group_descriptor_id: BindGroupDescriptorId,
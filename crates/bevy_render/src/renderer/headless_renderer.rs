@@ -0,0 +1,293 @@
+use super::{
+    free_shared_buffers_system, BindGroupId, BufferId, HeadlessRenderResourceContext,
+    RenderContext, RenderResourceBindings, RenderResourceContext, SharedBuffers, TextureId,
+};
+use crate::{
+    pass::{ComputePass, PassDescriptor, RenderPass},
+    pipeline::{BindGroupDescriptorId, ComputePipelineDescriptor, PipelineDescriptor},
+    render_graph::{
+        DependentNodeStager, Edge, NodeId, RenderGraph, RenderGraphStager, ResourceSlots,
+    },
+    texture::Extent3d,
+};
+use bevy_app::prelude::*;
+use bevy_asset::Handle;
+use bevy_core::{FrameTimeOverride, Rng};
+use bevy_ecs::{IntoQuerySystem, IntoThreadLocalSystem, Local, Res, ResMut, Resources, World};
+use bevy_utils::HashMap;
+use bevy_window::{CreateWindow, Window, WindowCreated, Windows};
+use std::{ops::Range, time::Duration};
+
+/// Configures the [HeadlessRenderPlugin]
+#[derive(Debug, Clone)]
+pub struct HeadlessRenderOptions {
+    /// Replaces the measured frame time, so time driven systems do the same work on every run
+    pub fixed_delta: Option<Duration>,
+    /// The seed of the [Rng] resource
+    pub seed: u64,
+}
+
+impl Default for HeadlessRenderOptions {
+    fn default() -> Self {
+        HeadlessRenderOptions {
+            fixed_delta: Some(Duration::from_secs_f64(1.0 / 60.0)),
+            seed: 0,
+        }
+    }
+}
+
+/// A render backend that runs the whole render graph without a gpu. Windows are created without swap chains and every
+/// gpu command is discarded, so only the cpu side of rendering is measured. Use it instead of the wgpu and winit
+/// plugins to benchmark apps with [App::update_n].
+#[derive(Default)]
+pub struct HeadlessRenderPlugin;
+
+impl Plugin for HeadlessRenderPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let options = app
+            .resources()
+            .get_cloned::<HeadlessRenderOptions>()
+            .unwrap_or_default();
+        let render_resource_context = HeadlessRenderResourceContext::default();
+        app.add_resource(FrameTimeOverride {
+            delta: options.fixed_delta,
+        })
+        .add_resource(Rng::with_seed(options.seed))
+        .add_resource::<Box<dyn RenderResourceContext>>(Box::new(render_resource_context.clone()))
+        .add_resource(SharedBuffers::new(Box::new(render_resource_context)))
+        .add_system_to_stage(stage::PRE_UPDATE, headless_window_system.system())
+        .add_system_to_stage(
+            crate::stage::RENDER,
+            headless_render_system.thread_local_system(),
+        )
+        .add_system_to_stage(
+            crate::stage::POST_RENDER,
+            free_shared_buffers_system.system(),
+        );
+    }
+}
+
+fn headless_render_system(world: &mut World, resources: &mut Resources) {
+    let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+    render_graph.prepare(world, resources);
+    let mut stager = DependentNodeStager::loose_grouping();
+    let stages = stager.get_stages(&render_graph).unwrap();
+    let mut borrowed = stages.borrow(&mut render_graph);
+
+    let render_resource_context = resources
+        .get::<Box<dyn RenderResourceContext>>()
+        .unwrap()
+        .downcast_ref::<HeadlessRenderResourceContext>()
+        .expect("the headless renderer only works with a HeadlessRenderResourceContext")
+        .clone();
+    let mut render_context = HeadlessRenderContext {
+        render_resource_context,
+    };
+    let mut node_outputs = HashMap::<NodeId, ResourceSlots>::default();
+    for stage in borrowed.iter_mut() {
+        for job in stage.jobs.iter_mut() {
+            for node_state in job.node_states.iter_mut() {
+                if node_state.is_active() {
+                    for (i, input_slot) in node_state.input_slots.iter_mut().enumerate() {
+                        if let Some(Edge::SlotEdge {
+                            output_node,
+                            output_index,
+                            ..
+                        }) = node_state.edges.get_input_slot_edge(i).ok()
+                        {
+                            input_slot.resource = node_outputs
+                                .get(output_node)
+                                .and_then(|outputs| outputs.get(*output_index));
+                        }
+                    }
+                    node_state.node.update(
+                        world,
+                        resources,
+                        &mut render_context,
+                        &node_state.input_slots,
+                        &mut node_state.output_slots,
+                    );
+                }
+                node_outputs.insert(node_state.id, node_state.output_slots.clone());
+            }
+        }
+    }
+
+    render_context
+        .render_resource_context
+        .drop_all_swap_chain_textures();
+    render_context.render_resource_context.clear_bind_groups();
+}
+
+/// Stands in for the windowing backend by creating the requested windows without a surface to present to. The wgpu
+/// renderer draws such windows to offscreen textures, which lets apps render on machines without a display.
+pub fn headless_window_system(
+    mut create_window_event_reader: Local<EventReader<CreateWindow>>,
+    create_window_events: Res<Events<CreateWindow>>,
+    mut window_created_events: ResMut<Events<WindowCreated>>,
+    mut windows: ResMut<Windows>,
+) {
+    for create_window_event in create_window_event_reader.iter(&create_window_events) {
+        windows.add(Window::new(
+            create_window_event.id,
+            &create_window_event.descriptor,
+        ));
+        window_created_events.send(WindowCreated {
+            id: create_window_event.id,
+        });
+    }
+}
+
+/// Discards every command
+#[derive(Debug)]
+pub struct HeadlessRenderContext {
+    pub render_resource_context: HeadlessRenderResourceContext,
+}
+
+impl RenderContext for HeadlessRenderContext {
+    fn resources(&self) -> &dyn RenderResourceContext {
+        &self.render_resource_context
+    }
+
+    fn resources_mut(&mut self) -> &mut dyn RenderResourceContext {
+        &mut self.render_resource_context
+    }
+
+    fn copy_buffer_to_buffer(
+        &mut self,
+        _source_buffer: BufferId,
+        _source_offset: u64,
+        _destination_buffer: BufferId,
+        _destination_offset: u64,
+        _size: u64,
+    ) {
+    }
+
+    fn copy_buffer_to_texture(
+        &mut self,
+        _source_buffer: BufferId,
+        _source_offset: u64,
+        _source_bytes_per_row: u32,
+        _destination_texture: TextureId,
+        _destination_origin: [u32; 3],
+        _destination_mip_level: u32,
+        _size: Extent3d,
+    ) {
+    }
+
+    fn copy_texture_to_buffer(
+        &mut self,
+        _source_texture: TextureId,
+        _source_origin: [u32; 3],
+        _source_mip_level: u32,
+        _destination_buffer: BufferId,
+        _destination_offset: u64,
+        _destination_bytes_per_row: u32,
+        _size: Extent3d,
+    ) {
+    }
+
+    fn copy_texture_to_texture(
+        &mut self,
+        _source_texture: TextureId,
+        _source_origin: [u32; 3],
+        _source_mip_level: u32,
+        _destination_texture: TextureId,
+        _destination_origin: [u32; 3],
+        _destination_mip_level: u32,
+        _size: Extent3d,
+    ) {
+    }
+
+    fn begin_pass(
+        &mut self,
+        _pass_descriptor: &PassDescriptor,
+        _render_resource_bindings: &RenderResourceBindings,
+        run_pass: &mut dyn Fn(&mut dyn RenderPass),
+    ) {
+        run_pass(&mut HeadlessPass {
+            render_context: self,
+        });
+    }
+
+    fn begin_compute_pass(&mut self, run_pass: &mut dyn FnMut(&mut dyn ComputePass)) {
+        run_pass(&mut HeadlessPass {
+            render_context: self,
+        });
+    }
+}
+
+/// A render and compute pass that discards every command
+#[derive(Debug)]
+pub struct HeadlessPass<'a> {
+    render_context: &'a HeadlessRenderContext,
+}
+
+impl<'a> RenderPass for HeadlessPass<'a> {
+    fn get_render_context(&self) -> &dyn RenderContext {
+        self.render_context
+    }
+
+    fn set_index_buffer(&mut self, _buffer: BufferId, _offset: u64) {}
+
+    fn set_vertex_buffer(&mut self, _start_slot: u32, _buffer: BufferId, _offset: u64) {}
+
+    fn set_pipeline(&mut self, _pipeline_handle: &Handle<PipelineDescriptor>) {}
+
+    fn set_viewport(
+        &mut self,
+        _x: f32,
+        _y: f32,
+        _w: f32,
+        _h: f32,
+        _min_depth: f32,
+        _max_depth: f32,
+    ) {
+    }
+
+    fn set_scissor_rect(&mut self, _x: u32, _y: u32, _w: u32, _h: u32) {}
+
+    fn set_stencil_reference(&mut self, _reference: u32) {}
+
+    fn draw(&mut self, _vertices: Range<u32>, _instances: Range<u32>) {}
+
+    fn draw_indexed(&mut self, _indices: Range<u32>, _base_vertex: i32, _instances: Range<u32>) {}
+
+    fn draw_indexed_indirect(
+        &mut self,
+        _indirect_buffer: BufferId,
+        _indirect_offset: u64,
+        _count: u32,
+    ) {
+    }
+
+    fn set_bind_group(
+        &mut self,
+        _index: u32,
+        _bind_group_descriptor_id: BindGroupDescriptorId,
+        _bind_group: BindGroupId,
+        _dynamic_uniform_indices: Option<&[u32]>,
+    ) {
+    }
+}
+
+impl<'a> ComputePass for HeadlessPass<'a> {
+    fn get_render_context(&self) -> &dyn RenderContext {
+        self.render_context
+    }
+
+    fn set_pipeline(&mut self, _pipeline_handle: &Handle<ComputePipelineDescriptor>) {}
+
+    fn set_bind_group(
+        &mut self,
+        _index: u32,
+        _bind_group_descriptor_id: BindGroupDescriptorId,
+        _bind_group: BindGroupId,
+        _dynamic_uniform_indices: Option<&[u32]>,
+    ) {
+    }
+
+    fn dispatch(&mut self, _x: u32, _y: u32, _z: u32) {}
+
+    fn dispatch_indirect(&mut self, _indirect_buffer: BufferId, _indirect_offset: u64) {}
+}
@@ -0,0 +1,158 @@
+/// The graphics api used by the render backend
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RenderBackend {
+    Empty,
+    Vulkan,
+    Metal,
+    Dx12,
+    Dx11,
+    Gl,
+    BrowserWebGpu,
+}
+
+impl Default for RenderBackend {
+    fn default() -> Self {
+        RenderBackend::Empty
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RenderDeviceType {
+    Other,
+    IntegratedGpu,
+    DiscreteGpu,
+    VirtualGpu,
+    Cpu,
+}
+
+impl Default for RenderDeviceType {
+    fn default() -> Self {
+        RenderDeviceType::Other
+    }
+}
+
+bitflags::bitflags! {
+    /// Optional features that are enabled on the render device
+    #[derive(Default)]
+    pub struct RenderFeatures: u64 {
+        const COMPUTE = 1;
+        const SAMPLER_ANISOTROPY = 2;
+        const TEXTURE_COMPRESSION_BC = 4;
+        const DEPTH_CLAMPING = 8;
+        const PUSH_CONSTANTS = 16;
+        const MULTI_DRAW_INDIRECT = 32;
+    }
+}
+
+/// Limits of the render device. The defaults are the limits every device is guaranteed to support.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RenderLimits {
+    pub max_bind_groups: u32,
+    pub max_dynamic_uniform_buffers_per_pipeline_layout: u32,
+    pub max_dynamic_storage_buffers_per_pipeline_layout: u32,
+    pub max_sampled_textures_per_shader_stage: u32,
+    pub max_samplers_per_shader_stage: u32,
+    pub max_storage_buffers_per_shader_stage: u32,
+    pub max_storage_textures_per_shader_stage: u32,
+    pub max_uniform_buffers_per_shader_stage: u32,
+    pub max_uniform_buffer_binding_size: u32,
+    pub max_push_constant_size: u32,
+}
+
+impl Default for RenderLimits {
+    fn default() -> Self {
+        RenderLimits {
+            max_bind_groups: 4,
+            max_dynamic_uniform_buffers_per_pipeline_layout: 8,
+            max_dynamic_storage_buffers_per_pipeline_layout: 4,
+            max_sampled_textures_per_shader_stage: 16,
+            max_samplers_per_shader_stage: 16,
+            max_storage_buffers_per_shader_stage: 4,
+            max_storage_textures_per_shader_stage: 4,
+            max_uniform_buffers_per_shader_stage: 12,
+            max_uniform_buffer_binding_size: 16384,
+            max_push_constant_size: 0,
+        }
+    }
+}
+
+/// Information about the graphics adapter the renderer is using. This is inserted as a resource by the render backend
+/// once the render device has been created.
+#[derive(Debug, Clone, Default)]
+pub struct AdapterInfo {
+    pub name: String,
+    /// The PCI vendor id of the adapter
+    pub vendor: usize,
+    /// The PCI device id of the adapter
+    pub device: usize,
+    pub device_type: RenderDeviceType,
+    pub backend: RenderBackend,
+    pub limits: RenderLimits,
+    pub features: RenderFeatures,
+}
+
+/// The features and limits of the render device. Systems can use this to disable functionality that the current
+/// device doesn't support instead of failing at runtime.
+///
+/// Without a render backend (ex: in headless apps) no optional features are supported.
+#[derive(Debug, Clone, Default)]
+pub struct RenderCapabilities {
+    pub features: RenderFeatures,
+    pub limits: RenderLimits,
+}
+
+impl RenderCapabilities {
+    pub fn from_adapter_info(adapter_info: &AdapterInfo) -> Self {
+        RenderCapabilities {
+            features: adapter_info.features,
+            limits: adapter_info.limits,
+        }
+    }
+
+    /// Returns true if all of the given `features` are supported
+    pub fn supports(&self, features: RenderFeatures) -> bool {
+        self.features.contains(features)
+    }
+
+    pub fn supports_compute(&self) -> bool {
+        self.supports(RenderFeatures::COMPUTE)
+    }
+
+    pub fn supports_anisotropy(&self) -> bool {
+        self.supports(RenderFeatures::SAMPLER_ANISOTROPY)
+    }
+
+    pub fn supports_texture_compression(&self) -> bool {
+        self.supports(RenderFeatures::TEXTURE_COMPRESSION_BC)
+    }
+
+    /// Returns the given anisotropy clamp if anisotropic filtering is supported, otherwise `None`. Use this when
+    /// building a [SamplerDescriptor](crate::texture::SamplerDescriptor) to fall back to regular filtering.
+    pub fn anisotropy_clamp(
+        &self,
+        anisotropy_clamp: Option<std::num::NonZeroU8>,
+    ) -> Option<std::num::NonZeroU8> {
+        if self.supports_anisotropy() {
+            anisotropy_clamp
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headless_supports_no_optional_features() {
+        let capabilities = RenderCapabilities::default();
+        assert!(!capabilities.supports_compute());
+        assert!(!capabilities.supports_texture_compression());
+        assert!(capabilities.supports(RenderFeatures::empty()));
+        assert_eq!(
+            capabilities.anisotropy_clamp(std::num::NonZeroU8::new(16)),
+            None
+        );
+    }
+}
@@ -1,11 +1,14 @@
 use super::RenderResourceContext;
 use crate::{
-    pass::{PassDescriptor, RenderPass},
+    pass::{ComputePass, PassDescriptor, RenderPass},
     renderer::{BufferId, RenderResourceBindings, TextureId},
     texture::Extent3d,
 };
+use downcast_rs::{impl_downcast, Downcast};
 
-pub trait RenderContext {
+/// Records the gpu commands of a render graph node. Renderer specific contexts can be reached with `downcast_mut`,
+/// for example to encode commands the engine doesn't know about.
+pub trait RenderContext: Downcast {
     fn resources(&self) -> &dyn RenderResourceContext;
     fn resources_mut(&mut self) -> &mut dyn RenderResourceContext;
     fn copy_buffer_to_buffer(
@@ -27,10 +30,36 @@ pub trait RenderContext {
         destination_mip_level: u32,
         size: Extent3d,
     );
+    #[allow(clippy::too_many_arguments)]
+    fn copy_texture_to_buffer(
+        &mut self,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_buffer: BufferId,
+        destination_offset: u64,
+        destination_bytes_per_row: u32,
+        size: Extent3d,
+    );
+    /// Both textures need the same format and sample count
+    #[allow(clippy::too_many_arguments)]
+    fn copy_texture_to_texture(
+        &mut self,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_texture: TextureId,
+        destination_origin: [u32; 3],
+        destination_mip_level: u32,
+        size: Extent3d,
+    );
     fn begin_pass(
         &mut self,
         pass_descriptor: &PassDescriptor,
         render_resource_bindings: &RenderResourceBindings,
         run_pass: &mut dyn Fn(&mut dyn RenderPass),
     );
+    fn begin_compute_pass(&mut self, run_pass: &mut dyn FnMut(&mut dyn ComputePass));
 }
+
+impl_downcast!(RenderContext);
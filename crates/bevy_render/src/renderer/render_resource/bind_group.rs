@@ -1,5 +1,6 @@
 use super::{BufferId, RenderResourceBinding, SamplerId, TextureId};
 use bevy_utils::AHasher;
+use smallvec::SmallVec;
 use std::{
     hash::{Hash, Hasher},
     ops::Range,
@@ -9,7 +10,7 @@ use std::{
 #[derive(Hash, Eq, PartialEq, Debug, Copy, Clone)]
 pub struct BindGroupId(pub u64);
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub struct IndexedBindGroupEntry {
     pub index: u32,
     pub entry: RenderResourceBinding,
@@ -30,8 +31,9 @@ impl BindGroup {
 
 #[derive(Debug, Default)]
 pub struct BindGroupBuilder {
-    pub indexed_bindings: Vec<IndexedBindGroupEntry>,
-    pub dynamic_uniform_indices: Vec<u32>,
+    // bind groups rarely have more than a handful of bindings, so building one doesn't need to allocate
+    pub indexed_bindings: SmallVec<[IndexedBindGroupEntry; 8]>,
+    pub dynamic_uniform_indices: SmallVec<[u32; 4]>,
     pub hasher: AHasher,
 }
 
@@ -94,11 +96,11 @@ impl BindGroupBuilder {
         self.indexed_bindings.sort_by_key(|i| i.index);
         BindGroup {
             id: BindGroupId(self.hasher.finish()),
-            indexed_bindings: self.indexed_bindings.into(),
+            indexed_bindings: Arc::from(&self.indexed_bindings[..]),
             dynamic_uniform_indices: if self.dynamic_uniform_indices.is_empty() {
                 None
             } else {
-                Some(self.dynamic_uniform_indices.into())
+                Some(Arc::from(&self.dynamic_uniform_indices[..]))
             },
         }
     }
@@ -0,0 +1,129 @@
+use bevy_utils::HashMap;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::{borrow::Cow, cmp::Ordering, fmt};
+
+/// An interned render resource name, like "Camera" or "StandardMaterial_albedo". Bindings are looked up by name every
+/// frame, so names are interned once and compared and hashed as ids afterwards.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BindingName(u32);
+
+#[derive(Default)]
+struct BindingNameRegistry {
+    ids: HashMap<&'static str, BindingName>,
+    names: Vec<&'static str>,
+}
+
+// interned names are never freed. there is a small, fixed set of them per app: one per shader binding and render
+// resource field
+static REGISTRY: Lazy<RwLock<BindingNameRegistry>> = Lazy::new(Default::default);
+
+impl BindingName {
+    pub fn new(name: &str) -> Self {
+        if let Some(id) = REGISTRY.read().ids.get(name) {
+            return *id;
+        }
+
+        let mut registry = REGISTRY.write();
+        // another thread may have interned the name between the locks
+        if let Some(id) = registry.ids.get(name) {
+            return *id;
+        }
+
+        let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+        let id = BindingName(registry.names.len() as u32);
+        registry.names.push(name);
+        registry.ids.insert(name, id);
+        id
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        REGISTRY.read().names[self.0 as usize]
+    }
+}
+
+impl From<&str> for BindingName {
+    fn from(name: &str) -> Self {
+        BindingName::new(name)
+    }
+}
+
+impl From<&String> for BindingName {
+    fn from(name: &String) -> Self {
+        BindingName::new(name)
+    }
+}
+
+impl From<String> for BindingName {
+    fn from(name: String) -> Self {
+        BindingName::new(&name)
+    }
+}
+
+impl From<&Cow<'_, str>> for BindingName {
+    fn from(name: &Cow<'_, str>) -> Self {
+        BindingName::new(name)
+    }
+}
+
+impl From<&BindingName> for BindingName {
+    fn from(name: &BindingName) -> Self {
+        *name
+    }
+}
+
+impl PartialEq<str> for BindingName {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for BindingName {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+// names are ordered alphabetically rather than by id, so the order doesn't depend on the order names were interned in
+impl Ord for BindingName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            Ordering::Equal
+        } else {
+            self.as_str().cmp(other.as_str())
+        }
+    }
+}
+
+impl PartialOrd for BindingName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for BindingName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for BindingName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_names_are_interned() {
+        let camera = BindingName::new("Camera");
+        assert_eq!(camera, BindingName::from("Camera".to_string()));
+        assert_ne!(camera, BindingName::new("Camera2"));
+        assert_eq!(camera.as_str(), "Camera");
+        assert_eq!(format!("{:?}", camera), "\"Camera\"");
+        assert!(BindingName::new("Zebra") > BindingName::new("Aardvark"));
+    }
+}
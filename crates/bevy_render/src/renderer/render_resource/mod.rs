@@ -1,14 +1,18 @@
 mod bind_group;
+mod binding_name;
 mod buffer;
 #[allow(clippy::module_inception)]
 mod render_resource;
 mod render_resource_bindings;
+mod render_resource_lifetimes;
 mod shared_buffers;
 mod texture;
 
 pub use bind_group::*;
+pub use binding_name::*;
 pub use buffer::*;
 pub use render_resource::*;
 pub use render_resource_bindings::*;
+pub use render_resource_lifetimes::*;
 pub use shared_buffers::*;
 pub use texture::*;
@@ -1,4 +1,6 @@
-use super::{BindGroup, BindGroupId, BufferId, RenderResourceId, SamplerId, TextureId};
+use super::{
+    BindGroup, BindGroupId, BindingName, BufferId, RenderResourceId, SamplerId, TextureId,
+};
 use crate::{
     pipeline::{BindGroupDescriptor, BindGroupDescriptorId, PipelineDescriptor},
     renderer::RenderResourceContext,
@@ -103,7 +105,7 @@ pub enum BindGroupStatus {
 // PERF: if the bindings are scoped to a specific pipeline layout, then names could be replaced with indices here for a perf boost
 #[derive(Eq, PartialEq, Debug, Default, Clone)]
 pub struct RenderResourceBindings {
-    bindings: HashMap<String, RenderResourceBinding>,
+    bindings: HashMap<BindingName, RenderResourceBinding>,
     /// A Buffer that contains all attributes a mesh has defined
     pub vertex_attribute_buffer: Option<BufferId>,
     /// A Buffer that is filled with zeros that will be used for attributes required by the shader, but undefined by the mesh.
@@ -115,17 +117,18 @@ pub struct RenderResourceBindings {
 }
 
 impl RenderResourceBindings {
-    pub fn get(&self, name: &str) -> Option<&RenderResourceBinding> {
-        self.bindings.get(name)
+    pub fn get(&self, name: impl Into<BindingName>) -> Option<&RenderResourceBinding> {
+        self.bindings.get(&name.into())
     }
 
-    pub fn set(&mut self, name: &str, binding: RenderResourceBinding) {
+    pub fn set(&mut self, name: impl Into<BindingName>, binding: RenderResourceBinding) {
+        let name = name.into();
         self.try_set_dirty(name, &binding);
-        self.bindings.insert(name.to_string(), binding);
+        self.bindings.insert(name, binding);
     }
 
-    fn try_set_dirty(&mut self, name: &str, binding: &RenderResourceBinding) {
-        if let Some(current_binding) = self.bindings.get(name) {
+    fn try_set_dirty(&mut self, name: BindingName, binding: &RenderResourceBinding) {
+        if let Some(current_binding) = self.bindings.get(&name) {
             if current_binding != binding {
                 // TODO: this is crude. we shouldn't need to invalidate all bind groups
                 for id in self.bind_groups.keys() {
@@ -137,7 +140,7 @@ impl RenderResourceBindings {
 
     pub fn extend(&mut self, render_resource_bindings: &RenderResourceBindings) {
         for (name, binding) in render_resource_bindings.bindings.iter() {
-            self.set(name, binding.clone());
+            self.set(*name, binding.clone());
         }
     }
 
@@ -226,7 +229,7 @@ impl RenderResourceBindings {
     fn build_bind_group(&self, bind_group_descriptor: &BindGroupDescriptor) -> Option<BindGroup> {
         let mut bind_group_builder = BindGroup::build();
         for binding_descriptor in bind_group_descriptor.bindings.iter() {
-            if let Some(binding) = self.get(&binding_descriptor.name) {
+            if let Some(binding) = self.get(binding_descriptor.name) {
                 bind_group_builder =
                     bind_group_builder.add_binding(binding_descriptor.index, binding.clone());
             } else {
@@ -260,6 +263,10 @@ impl AssetRenderResourceBindings {
     pub fn get_mut<T: Asset>(&mut self, handle: &Handle<T>) -> Option<&mut RenderResourceBindings> {
         self.bindings.get_mut(&handle.clone_weak_untyped())
     }
+
+    pub fn remove<T: Asset>(&mut self, handle: &Handle<T>) -> Option<RenderResourceBindings> {
+        self.bindings.remove(&handle.clone_weak_untyped())
+    }
 }
 
 #[cfg(test)]
@@ -274,7 +281,7 @@ mod tests {
             vec![
                 BindingDescriptor {
                     index: 0,
-                    name: "a".to_string(),
+                    name: "a".into(),
                     bind_type: BindType::Uniform {
                         dynamic: false,
                         property: UniformProperty::Struct(vec![UniformProperty::Mat4]),
@@ -283,7 +290,7 @@ mod tests {
                 },
                 BindingDescriptor {
                     index: 1,
-                    name: "b".to_string(),
+                    name: "b".into(),
                     bind_type: BindType::Uniform {
                         dynamic: false,
                         property: UniformProperty::Float,
@@ -0,0 +1,155 @@
+use super::RenderResourceId;
+use crate::renderer::RenderResourceContext;
+use bevy_asset::HandleId;
+use bevy_ecs::{Entity, Res};
+use bevy_utils::HashMap;
+use std::{any::TypeId, collections::VecDeque};
+
+/// The number of frames a released resource is kept alive for. This gives frames that are still in flight on the gpu
+/// a chance to finish using the resource before it is freed.
+pub const DEFAULT_RESOURCE_FREE_DELAY: u64 = 2;
+
+/// Something that keeps render resources alive
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RenderResourceOwner {
+    /// Resources created for an entity's `RenderResources` component of the given type
+    Entity(Entity, TypeId),
+    /// Resources created for an asset
+    Asset(HandleId),
+}
+
+/// Tracks which render resources belong to which owner and which released resources are waiting to be freed
+#[derive(Debug)]
+pub struct RenderResourceLifetimes {
+    owned_resources: HashMap<RenderResourceOwner, Vec<RenderResourceId>>,
+    released_resources: VecDeque<(u64, RenderResourceId)>,
+    frame: u64,
+    pub free_delay: u64,
+}
+
+impl Default for RenderResourceLifetimes {
+    fn default() -> Self {
+        RenderResourceLifetimes {
+            owned_resources: Default::default(),
+            released_resources: Default::default(),
+            frame: 0,
+            free_delay: DEFAULT_RESOURCE_FREE_DELAY,
+        }
+    }
+}
+
+impl RenderResourceLifetimes {
+    pub fn set_owner(&mut self, owner: RenderResourceOwner, resource: RenderResourceId) {
+        let resources = self.owned_resources.entry(owner).or_insert_with(Vec::new);
+        if !resources.contains(&resource) {
+            resources.push(resource);
+        }
+    }
+
+    /// Queues all resources of the given `owner` to be freed
+    pub fn release_owner(&mut self, owner: RenderResourceOwner) {
+        if let Some(resources) = self.owned_resources.remove(&owner) {
+            for resource in resources {
+                self.released_resources.push_back((self.frame, resource));
+            }
+        }
+    }
+
+    /// Queues the given `resource` to be freed. The resource is removed from its owner, if it has one.
+    pub fn release(&mut self, resource: RenderResourceId) {
+        for resources in self.owned_resources.values_mut() {
+            resources.retain(|owned| *owned != resource);
+        }
+        self.owned_resources
+            .retain(|_, resources| !resources.is_empty());
+        self.released_resources.push_back((self.frame, resource));
+    }
+
+    /// Ends the current frame and returns the released resources that are now safe to free
+    pub fn end_frame(&mut self) -> Vec<RenderResourceId> {
+        let mut expired = Vec::new();
+        while let Some((frame, _)) = self.released_resources.front() {
+            if frame + self.free_delay > self.frame {
+                break;
+            }
+
+            expired.push(self.released_resources.pop_front().unwrap().1);
+        }
+
+        self.frame += 1;
+        expired
+    }
+
+    pub fn owned_len(&self) -> usize {
+        self.owned_resources
+            .values()
+            .map(|resources| resources.len())
+            .sum()
+    }
+
+    pub fn released_len(&self) -> usize {
+        self.released_resources.len()
+    }
+
+    /// The owners that currently keep resources alive, with their resources
+    pub fn owners(&self) -> impl Iterator<Item = (RenderResourceOwner, &[RenderResourceId])> {
+        self.owned_resources
+            .iter()
+            .map(|(owner, resources)| (*owner, resources.as_slice()))
+    }
+
+    /// The resources that were released and are waiting to be freed
+    pub fn released(&self) -> impl Iterator<Item = &RenderResourceId> {
+        self.released_resources.iter().map(|(_, resource)| resource)
+    }
+}
+
+/// Frees render resources that were released at least [RenderResourceLifetimes::free_delay] frames ago
+pub fn free_released_render_resources_system(
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+) {
+    render_resource_context.free_released_resources();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        renderer::{BufferId, TextureId},
+        texture::Texture,
+    };
+
+    #[test]
+    fn released_resources_are_freed_after_delay() {
+        let mut lifetimes = RenderResourceLifetimes::default();
+        let owner = RenderResourceOwner::Entity(Entity::new(0), TypeId::of::<u32>());
+        let buffer = RenderResourceId::Buffer(BufferId::new());
+        let texture = RenderResourceId::Texture(TextureId::new());
+        lifetimes.set_owner(owner, buffer.clone());
+        lifetimes.set_owner(owner, texture.clone());
+        assert_eq!(lifetimes.owned_len(), 2);
+
+        lifetimes.release_owner(owner);
+        assert_eq!(lifetimes.owned_len(), 0);
+        assert_eq!(lifetimes.released_len(), 2);
+
+        for _ in 0..DEFAULT_RESOURCE_FREE_DELAY {
+            assert!(lifetimes.end_frame().is_empty());
+        }
+        assert_eq!(lifetimes.end_frame(), vec![buffer, texture]);
+        assert_eq!(lifetimes.released_len(), 0);
+    }
+
+    #[test]
+    fn release_removes_resource_from_owner() {
+        let mut lifetimes = RenderResourceLifetimes::default();
+        let buffer = RenderResourceId::Buffer(BufferId::new());
+        lifetimes.set_owner(
+            RenderResourceOwner::Asset(HandleId::random::<Texture>()),
+            buffer.clone(),
+        );
+        lifetimes.release(buffer);
+        assert_eq!(lifetimes.owned_len(), 0);
+        assert_eq!(lifetimes.released_len(), 1);
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
-    pipeline::{BindGroupDescriptorId, PipelineDescriptor},
-    renderer::{BindGroup, BufferId, BufferInfo, RenderResourceId, SamplerId, TextureId},
+    pipeline::{BindGroupDescriptorId, ComputePipelineDescriptor, PipelineDescriptor},
+    renderer::{
+        BindGroup, BufferId, BufferInfo, RenderResourceId, RenderResourceOwner, SamplerId,
+        TextureId,
+    },
     shader::Shader,
     texture::{SamplerDescriptor, TextureDescriptor},
 };
@@ -24,7 +27,22 @@ pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
         range: Range<u64>,
         write: &mut dyn FnMut(&mut [u8], &dyn RenderResourceContext),
     );
+    fn read_mapped_buffer(
+        &self,
+        id: BufferId,
+        range: Range<u64>,
+        read: &mut dyn FnMut(&[u8], &dyn RenderResourceContext),
+    );
+    /// Maps the buffer to host memory. Buffers with [BufferUsage::MAP_READ](crate::renderer::BufferUsage::MAP_READ)
+    /// are mapped for reading, all other buffers are mapped for writing. This blocks until the gpu is done with the
+    /// buffer.
     fn map_buffer(&self, id: BufferId);
+    /// Starts mapping the buffer like [RenderResourceContext::map_buffer] without waiting for the gpu. The buffer can be
+    /// read once [RenderResourceContext::is_buffer_mapped] returns true. The commands that write the buffer have to be
+    /// submitted before the map starts, so a buffer written by a render graph node is mapped in a later frame.
+    fn map_buffer_async(&self, id: BufferId);
+    /// Whether the map started by [RenderResourceContext::map_buffer_async] has completed. This doesn't block.
+    fn is_buffer_mapped(&self, id: BufferId) -> bool;
     fn unmap_buffer(&self, id: BufferId);
     fn create_buffer_with_data(&self, buffer_info: BufferInfo, data: &[u8]) -> BufferId;
     fn create_shader_module(&self, shader_handle: &Handle<Shader>, shaders: &Assets<Shader>);
@@ -33,6 +51,7 @@ pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
     fn remove_texture(&self, texture: TextureId);
     fn remove_sampler(&self, sampler: SamplerId);
     fn get_buffer_info(&self, buffer: BufferId) -> Option<BufferInfo>;
+    fn get_texture_descriptor(&self, texture: TextureId) -> Option<TextureDescriptor>;
 
     fn set_asset_resource_untyped(
         &self,
@@ -46,12 +65,29 @@ pub trait RenderResourceContext: Downcast + Send + Sync + 'static {
         index: u64,
     ) -> Option<RenderResourceId>;
     fn remove_asset_resource_untyped(&self, handle: HandleUntyped, index: u64);
+    /// Ties the lifetime of `resource` to `owner`. Use [RenderResourceContext::release_owner_resources] to free all
+    /// resources of an owner once it goes away.
+    fn set_resource_owner(&self, owner: RenderResourceOwner, resource: RenderResourceId);
+    /// Queues all resources of `owner` to be freed once frames that might still use them have completed
+    fn release_owner_resources(&self, owner: RenderResourceOwner);
+    /// Queues `resource` to be freed once frames that might still use it have completed
+    fn release_resource(&self, resource: RenderResourceId);
+    /// Ends the frame and frees released resources that are no longer in use. This runs once per frame in
+    /// [stage::POST_RENDER](crate::stage::POST_RENDER).
+    fn free_released_resources(&self);
     fn create_render_pipeline(
         &self,
         pipeline_handle: Handle<PipelineDescriptor>,
         pipeline_descriptor: &PipelineDescriptor,
         shaders: &Assets<Shader>,
     );
+    /// Creates the pipeline if it doesn't exist yet. The descriptor's layout must already be reflected.
+    fn create_compute_pipeline(
+        &self,
+        pipeline_handle: Handle<ComputePipelineDescriptor>,
+        pipeline_descriptor: &ComputePipelineDescriptor,
+        shaders: &Assets<Shader>,
+    );
     fn bind_group_descriptor_exists(&self, bind_group_descriptor_id: BindGroupDescriptorId)
         -> bool;
     fn create_bind_group(
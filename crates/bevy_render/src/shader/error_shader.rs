@@ -0,0 +1,24 @@
+use super::{Shader, ShaderStage};
+use bevy_asset::{Assets, Handle};
+use bevy_type_registry::TypeUuid;
+
+/// Replaces the vertex shader of pipelines whose shaders fail to compile. It only needs the `Camera` and `Transform`
+/// bindings and the `Vertex_Position` attribute, which every mesh drawn by a camera has.
+pub const ERROR_VERTEX_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u64(Shader::TYPE_UUID, 9173843217618492375);
+
+/// Replaces the fragment shader of pipelines whose shaders fail to compile. It draws everything magenta, so broken
+/// materials stand out instead of taking the app down.
+pub const ERROR_FRAGMENT_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u64(Shader::TYPE_UUID, 3385704906321788542);
+
+pub(crate) fn add_error_shaders(shaders: &mut Assets<Shader>) {
+    shaders.set_untracked(
+        ERROR_VERTEX_SHADER_HANDLE,
+        Shader::from_glsl(ShaderStage::Vertex, include_str!("error.vert")),
+    );
+    shaders.set_untracked(
+        ERROR_FRAGMENT_SHADER_HANDLE,
+        Shader::from_glsl(ShaderStage::Fragment, include_str!("error.frag")),
+    );
+}
@@ -1,6 +1,8 @@
+mod error_shader;
 #[allow(clippy::module_inception)]
 mod shader;
 mod shader_defs;
+mod shader_loader;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod shader_reflect;
@@ -9,8 +11,10 @@ mod shader_reflect;
 #[path = "shader_reflect_wasm.rs"]
 mod shader_reflect;
 
+pub use error_shader::*;
 pub use shader::*;
 pub use shader_defs::*;
+pub use shader_loader::*;
 pub use shader_reflect::*;
 
 use crate::pipeline::{BindGroupDescriptor, VertexBufferDescriptor};
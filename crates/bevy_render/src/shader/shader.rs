@@ -2,6 +2,13 @@ use super::ShaderLayout;
 use bevy_asset::Handle;
 use bevy_type_registry::TypeUuid;
 use std::marker::Copy;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone)]
+pub enum ShaderError {
+    #[error("Shader compilation error: {0}")]
+    Compilation(String),
+}
 
 /// The stage of a shader
 #[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
@@ -27,13 +34,14 @@ fn glsl_to_spirv(
     glsl_source: &str,
     stage: ShaderStage,
     shader_defs: Option<&[String]>,
-) -> Vec<u32> {
+) -> Result<Vec<u32>, ShaderError> {
     use std::io::Read;
 
-    let mut output = bevy_glsl_to_spirv::compile(glsl_source, stage.into(), shader_defs).unwrap();
+    let mut output = bevy_glsl_to_spirv::compile(glsl_source, stage.into(), shader_defs)
+        .map_err(ShaderError::Compilation)?;
     let mut spv_bytes = Vec::new();
     output.read_to_end(&mut spv_bytes).unwrap();
-    bytes_to_words(&spv_bytes)
+    Ok(bytes_to_words(&spv_bytes))
 }
 
 #[cfg(target_os = "ios")]
@@ -52,7 +60,7 @@ fn glsl_to_spirv(
     glsl_source: &str,
     stage: ShaderStage,
     shader_defs: Option<&[String]>,
-) -> Vec<u32> {
+) -> Result<Vec<u32>, ShaderError> {
     let mut compiler = shaderc::Compiler::new().unwrap();
     let mut options = shaderc::CompileOptions::new().unwrap();
     if let Some(shader_defs) = shader_defs {
@@ -69,9 +77,9 @@ fn glsl_to_spirv(
             "main",
             Some(&options),
         )
-        .unwrap();
+        .map_err(|err| ShaderError::Compilation(err.to_string()))?;
 
-    binary_result.as_binary().to_vec()
+    Ok(binary_result.as_binary().to_vec())
 }
 
 fn bytes_to_words(bytes: &[u8]) -> Vec<u32> {
@@ -119,22 +127,22 @@ impl Shader {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn get_spirv(&self, macros: Option<&[String]>) -> Vec<u32> {
+    pub fn get_spirv(&self, macros: Option<&[String]>) -> Result<Vec<u32>, ShaderError> {
         match self.source {
-            ShaderSource::Spirv(ref bytes) => bytes.clone(),
+            ShaderSource::Spirv(ref bytes) => Ok(bytes.clone()),
             ShaderSource::Glsl(ref source) => glsl_to_spirv(&source, self.stage, macros),
         }
     }
 
     #[allow(unused_variables)]
-    pub fn get_spirv_shader(&self, macros: Option<&[String]>) -> Shader {
-        Shader {
+    pub fn get_spirv_shader(&self, macros: Option<&[String]>) -> Result<Shader, ShaderError> {
+        Ok(Shader {
             #[cfg(not(target_arch = "wasm32"))]
-            source: ShaderSource::Spirv(self.get_spirv(macros)),
+            source: ShaderSource::Spirv(self.get_spirv(macros)?),
             #[cfg(target_arch = "wasm32")]
             source: self.source.clone(),
             stage: self.stage,
-        }
+        })
     }
 
     pub fn reflect_layout(&self, enforce_bevy_conventions: bool) -> Option<ShaderLayout> {
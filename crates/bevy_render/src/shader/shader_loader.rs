@@ -0,0 +1,90 @@
+use super::{Shader, ShaderStage};
+use anyhow::Result;
+use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy_utils::BoxedFuture;
+
+/// Loads GLSL shaders. The shader stage is determined by the file extension: `vert`, `frag` or `comp`.
+///
+/// The `shader_defs` in the shader's import settings are defined at the top of the source.
+#[derive(Clone, Default)]
+pub struct ShaderLoader;
+
+impl AssetLoader for ShaderLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let ext = load_context.path().extension().unwrap().to_str().unwrap();
+            let stage = match ext {
+                "vert" => ShaderStage::Vertex,
+                "frag" => ShaderStage::Fragment,
+                "comp" => ShaderStage::Compute,
+                _ => panic!(
+                    "Unexpected shader extension {:?} for file {}, this is an error in `bevy_render`.",
+                    ext,
+                    load_context.path().display()
+                ),
+            };
+
+            let source = std::str::from_utf8(bytes)?;
+            let source = define_shader_defs(source, &load_context.import_settings().shader_defs);
+            load_context.set_default_asset(LoadedAsset::new(Shader::from_glsl(stage, &source)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        static EXTENSIONS: &[&str] = &["vert", "frag", "comp"];
+        EXTENSIONS
+    }
+}
+
+/// Adds a `#define` for each of the `shader_defs` to the glsl `source`. GLSL requires `#version` to come first, so
+/// the defines are inserted right after it.
+fn define_shader_defs(source: &str, shader_defs: &[String]) -> String {
+    if shader_defs.is_empty() {
+        return source.to_string();
+    }
+
+    let mut defines = String::new();
+    for shader_def in shader_defs.iter() {
+        defines.push_str("#define ");
+        defines.push_str(shader_def);
+        defines.push('\n');
+    }
+
+    let version_end = source
+        .lines()
+        .next()
+        .filter(|line| line.trim_start().starts_with("#version"))
+        .map(|line| (line.len() + 1).min(source.len()));
+    match version_end {
+        Some(version_end) => {
+            let (version, rest) = source.split_at(version_end);
+            let separator = if version.ends_with('\n') { "" } else { "\n" };
+            format!("{}{}{}{}", version, separator, defines, rest)
+        }
+        None => format!("{}{}", defines, source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::define_shader_defs;
+
+    #[test]
+    fn shader_defs_follow_version() {
+        let defs = vec!["SHADOWS".to_string(), "MAX_LIGHTS 4".to_string()];
+        assert_eq!(
+            define_shader_defs("#version 450\nvoid main() {}\n", &defs),
+            "#version 450\n#define SHADOWS\n#define MAX_LIGHTS 4\nvoid main() {}\n"
+        );
+        assert_eq!(
+            define_shader_defs("void main() {}\n", &defs[..1]),
+            "#define SHADOWS\nvoid main() {}\n"
+        );
+        assert_eq!(define_shader_defs("#version 450", &[]), "#version 450");
+    }
+}
@@ -9,8 +9,9 @@ use crate::{
 use bevy_core::AsBytes;
 use spirv_reflect::{
     types::{
-        ReflectDescriptorBinding, ReflectDescriptorSet, ReflectDescriptorType, ReflectDimension,
-        ReflectShaderStageFlags, ReflectTypeDescription, ReflectTypeFlags,
+        ReflectDecorationFlags, ReflectDescriptorBinding, ReflectDescriptorSet,
+        ReflectDescriptorType, ReflectDimension, ReflectShaderStageFlags, ReflectTypeDescription,
+        ReflectTypeFlags,
     },
     ShaderModule,
 };
@@ -137,7 +138,9 @@ fn reflect_binding(
             &type_description.type_name,
             BindType::StorageBuffer {
                 dynamic: false,
-                readonly: true,
+                // render stages can't write storage buffers, so only compute shaders have writable ones
+                readonly: shader_stage != ReflectShaderStageFlags::COMPUTE
+                    || is_non_writable(binding),
             },
         ),
         // TODO: detect comparison "true" case: https://github.com/gpuweb/gpuweb/issues/552
@@ -152,8 +155,6 @@ fn reflect_binding(
         _ => panic!("Only one specified shader stage is supported."),
     };
 
-    let name = name.to_string();
-
     if name == "Camera" {
         shader_stage = BindingShaderStage::VERTEX | BindingShaderStage::FRAGMENT;
     }
@@ -161,11 +162,24 @@ fn reflect_binding(
     BindingDescriptor {
         index: binding.binding,
         bind_type,
-        name,
+        name: name.into(),
         shader_stage,
     }
 }
 
+fn is_non_writable(binding: &ReflectDescriptorBinding) -> bool {
+    let block = &binding.block;
+    block
+        .decoration_flags
+        .contains(ReflectDecorationFlags::NON_WRITABLE)
+        || (!block.members.is_empty()
+            && block.members.iter().all(|member| {
+                member
+                    .decoration_flags
+                    .contains(ReflectDecorationFlags::NON_WRITABLE)
+            }))
+}
+
 #[derive(Debug)]
 enum NumberType {
     Int,
@@ -328,7 +342,8 @@ mod tests {
             }
         "#,
         )
-        .get_spirv_shader(None);
+        .get_spirv_shader(None)
+        .unwrap();
 
         let layout = vertex_shader.reflect_layout(true).unwrap();
         assert_eq!(
@@ -397,4 +412,43 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_compute_storage_buffer_reflection() {
+        let compute_shader = Shader::from_glsl(
+            ShaderStage::Compute,
+            r#"
+            #version 450
+            layout(local_size_x = 64) in;
+            layout(set = 0, binding = 0) readonly buffer Input {
+                float[] inputs;
+            };
+            layout(set = 0, binding = 1) buffer Output {
+                float[] outputs;
+            };
+
+            void main() {
+                outputs[gl_GlobalInvocationID.x] = inputs[gl_GlobalInvocationID.x] * 2.0;
+            }
+        "#,
+        )
+        .get_spirv_shader(None)
+        .unwrap();
+
+        let layout = compute_shader.reflect_layout(false).unwrap();
+        let mut readonly = layout.bind_groups[0]
+            .bindings
+            .iter()
+            .map(|binding| match binding.bind_type {
+                BindType::StorageBuffer { readonly, .. } => (binding.index, readonly),
+                _ => panic!("expected a storage buffer"),
+            })
+            .collect::<Vec<_>>();
+        readonly.sort();
+        assert_eq!(readonly, vec![(0, true), (1, false)]);
+        assert!(layout.bind_groups[0]
+            .bindings
+            .iter()
+            .all(|binding| binding.shader_stage == BindingShaderStage::COMPUTE));
+    }
 }
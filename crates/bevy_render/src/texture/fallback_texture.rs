@@ -0,0 +1,87 @@
+use super::{Texture, TextureFormat};
+use bevy_asset::{Assets, Handle};
+use bevy_math::Vec2;
+use bevy_type_registry::TypeUuid;
+
+/// Bound in place of color textures that haven't loaded yet, so they don't tint the material
+pub const WHITE_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 2839361426871296271);
+
+/// Bound in place of emissive textures that haven't loaded yet, so materials don't glow while they load
+pub const BLACK_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 13210391632466410453);
+
+/// A flat normal map, bound in place of normal maps that haven't loaded yet
+pub const NORMAL_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 5496823709152436018);
+
+/// Binding names ending with one of these get [NORMAL_TEXTURE_HANDLE] as their fallback
+pub const NORMAL_TEXTURE_SUFFIXES: &[&str] = &["_normal_map", "_normal_texture"];
+
+/// Binding names ending with one of these get [BLACK_TEXTURE_HANDLE] as their fallback
+pub const EMISSIVE_TEXTURE_SUFFIXES: &[&str] = &["_emissive_map", "_emissive_texture"];
+
+/// The texture bound to the 2D texture binding `name` while the texture of the binding loads. Bindings are named
+/// `{Type}_{field}`, so the suffix of the name decides the fallback: normal maps are flat, emissive textures are
+/// black and everything else is white. See [NORMAL_TEXTURE_SUFFIXES] and [EMISSIVE_TEXTURE_SUFFIXES].
+pub fn fallback_texture(name: &str) -> Handle<Texture> {
+    let has_suffix = |suffixes: &[&str]| suffixes.iter().any(|suffix| name.ends_with(suffix));
+    if has_suffix(NORMAL_TEXTURE_SUFFIXES) {
+        NORMAL_TEXTURE_HANDLE
+    } else if has_suffix(EMISSIVE_TEXTURE_SUFFIXES) {
+        BLACK_TEXTURE_HANDLE
+    } else {
+        WHITE_TEXTURE_HANDLE
+    }
+}
+
+pub(crate) fn add_fallback_textures(textures: &mut Assets<Texture>) {
+    let size = Vec2::new(1.0, 1.0);
+    textures.set_untracked(
+        WHITE_TEXTURE_HANDLE,
+        Texture::new_fill(size, &[255, 255, 255, 255], TextureFormat::Rgba8UnormSrgb),
+    );
+    textures.set_untracked(
+        BLACK_TEXTURE_HANDLE,
+        Texture::new_fill(size, &[0, 0, 0, 255], TextureFormat::Rgba8UnormSrgb),
+    );
+    // normals are stored in linear space, with +z pointing out of the surface
+    textures.set_untracked(
+        NORMAL_TEXTURE_HANDLE,
+        Texture::new_fill(size, &[128, 128, 255, 255], TextureFormat::Rgba8Unorm),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_textures_match_binding_names() {
+        assert_eq!(
+            fallback_texture("StandardMaterial_albedo_texture"),
+            WHITE_TEXTURE_HANDLE
+        );
+        assert_eq!(
+            fallback_texture("WaterMaterial_normal_map"),
+            NORMAL_TEXTURE_HANDLE
+        );
+        assert_eq!(
+            fallback_texture("StandardMaterial_emissive_texture"),
+            BLACK_TEXTURE_HANDLE
+        );
+        // only whole field names count
+        assert_eq!(
+            fallback_texture("RockMaterial_abnormal_roughness"),
+            WHITE_TEXTURE_HANDLE
+        );
+        assert_eq!(
+            fallback_texture("RockMaterial_subnormal_map"),
+            WHITE_TEXTURE_HANDLE
+        );
+        assert_eq!(
+            fallback_texture("RockMaterial_emissive_strength_texture"),
+            WHITE_TEXTURE_HANDLE
+        );
+    }
+}
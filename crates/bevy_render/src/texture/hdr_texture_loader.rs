@@ -36,11 +36,12 @@ impl AssetLoader for HdrTextureLoader {
                 rgba_data.extend_from_slice(&alpha.to_ne_bytes());
             }
 
-            let texture = Texture::new(
+            let mut texture = Texture::new(
                 Vec2::new(info.width as f32, info.height as f32),
                 rgba_data,
                 format,
             );
+            texture.apply_import_settings(load_context.import_settings());
 
             load_context.set_default_asset(LoadedAsset::new(texture));
             Ok(())
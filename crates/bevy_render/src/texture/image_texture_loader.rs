@@ -148,7 +148,8 @@ impl AssetLoader for ImageTextureLoader {
                 }
             }
 
-            let texture = Texture::new(Vec2::new(width as f32, height as f32), data, format);
+            let mut texture = Texture::new(Vec2::new(width as f32, height as f32), data, format);
+            texture.apply_import_settings(load_context.import_settings());
             load_context.set_default_asset(LoadedAsset::new(texture));
             Ok(())
         })
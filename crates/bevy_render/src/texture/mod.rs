@@ -1,3 +1,4 @@
+mod fallback_texture;
 #[cfg(feature = "hdr")]
 mod hdr_texture_loader;
 #[cfg(feature = "png")]
@@ -7,7 +8,11 @@ mod sampler_descriptor;
 mod texture;
 mod texture_descriptor;
 mod texture_dimension;
+mod texture_region;
+mod texture_residency;
+mod texture_streaming;
 
+pub use fallback_texture::*;
 #[cfg(feature = "hdr")]
 pub use hdr_texture_loader::*;
 #[cfg(feature = "png")]
@@ -16,3 +21,6 @@ pub use sampler_descriptor::*;
 pub use texture::*;
 pub use texture_descriptor::*;
 pub use texture_dimension::*;
+pub use texture_region::*;
+pub use texture_residency::*;
+pub use texture_streaming::*;
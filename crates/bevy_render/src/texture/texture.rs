@@ -1,13 +1,18 @@
-use super::{SamplerDescriptor, TextureDescriptor, TextureFormat};
-use crate::renderer::{
-    RenderResource, RenderResourceContext, RenderResourceId, RenderResourceType,
+use super::{FilterMode, SamplerDescriptor, TextureDescriptor, TextureFormat, TextureUsage};
+use crate::{
+    quality::GraphicsQuality,
+    renderer::{
+        RenderCapabilities, RenderResource, RenderResourceContext, RenderResourceId,
+        RenderResourceType,
+    },
 };
 use bevy_app::prelude::{EventReader, Events};
-use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_asset::{AssetEvent, Assets, Handle, ImportFilterMode, ImportSettings};
 use bevy_ecs::{Res, ResMut};
 use bevy_math::Vec2;
 use bevy_type_registry::TypeUuid;
 use bevy_utils::HashSet;
+use std::num::NonZeroU8;
 
 pub const TEXTURE_ASSET_INDEX: u64 = 0;
 pub const SAMPLER_ASSET_INDEX: u64 = 1;
@@ -19,6 +24,7 @@ pub struct Texture {
     pub size: Vec2,
     pub format: TextureFormat,
     pub sampler: SamplerDescriptor,
+    pub usage: TextureUsage,
 }
 
 impl Default for Texture {
@@ -28,6 +34,7 @@ impl Default for Texture {
             size: Default::default(),
             format: TextureFormat::Rgba8UnormSrgb,
             sampler: Default::default(),
+            usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
         }
     }
 }
@@ -47,6 +54,17 @@ impl Texture {
         }
     }
 
+    /// Creates a texture without cpu side data that render passes can draw to and materials can sample. Use
+    /// [AssetTextureNode](crate::render_graph::AssetTextureNode) to pass it to a pass as an attachment.
+    pub fn new_render_target(size: Vec2, format: TextureFormat) -> Self {
+        Self {
+            size,
+            format,
+            usage: TextureUsage::SAMPLED | TextureUsage::OUTPUT_ATTACHMENT,
+            ..Default::default()
+        }
+    }
+
     pub fn new_fill(size: Vec2, pixel: &[u8], format: TextureFormat) -> Self {
         let mut value = Self::default();
         value.format = format;
@@ -68,6 +86,30 @@ impl Texture {
         value
     }
 
+    /// Applies the sRGB flag and filter mode of the given import settings. The sRGB flag only affects 8 bit color
+    /// formats.
+    pub fn apply_import_settings(&mut self, settings: &ImportSettings) {
+        if let Some(srgb) = settings.srgb {
+            self.format = match (self.format, srgb) {
+                (TextureFormat::Rgba8Unorm, true) => TextureFormat::Rgba8UnormSrgb,
+                (TextureFormat::Rgba8UnormSrgb, false) => TextureFormat::Rgba8Unorm,
+                (TextureFormat::Bgra8Unorm, true) => TextureFormat::Bgra8UnormSrgb,
+                (TextureFormat::Bgra8UnormSrgb, false) => TextureFormat::Bgra8Unorm,
+                (format, _) => format,
+            };
+        }
+
+        if let Some(filter_mode) = settings.filter_mode {
+            let filter_mode = match filter_mode {
+                ImportFilterMode::Nearest => FilterMode::Nearest,
+                ImportFilterMode::Linear => FilterMode::Linear,
+            };
+            self.sampler.mag_filter = filter_mode;
+            self.sampler.min_filter = filter_mode;
+            self.sampler.mipmap_filter = filter_mode;
+        }
+    }
+
     pub fn aspect(&self) -> f32 {
         self.size.y() / self.size.x()
     }
@@ -80,13 +122,54 @@ impl Texture {
             .resize(width * height * self.format.pixel_size(), 0);
     }
 
+    /// The sampler the texture is created with. Textures that are minified linearly and don't set an anisotropy clamp
+    /// use `anisotropy_clamp`. Depth textures always get a non-filtering sampler, because not every gpu can filter
+    /// them.
+    pub fn sampler_descriptor(&self, anisotropy_clamp: Option<NonZeroU8>) -> SamplerDescriptor {
+        let mut sampler = self.sampler;
+        if self.format.is_depth() {
+            sampler.mag_filter = FilterMode::Nearest;
+            sampler.min_filter = FilterMode::Nearest;
+            sampler.mipmap_filter = FilterMode::Nearest;
+            sampler.anisotropy_clamp = None;
+            return sampler;
+        }
+        if sampler.anisotropy_clamp.is_none() && sampler.min_filter == FilterMode::Linear {
+            sampler.anisotropy_clamp = anisotropy_clamp;
+        }
+        sampler
+    }
+
     pub fn texture_resource_system(
         mut state: ResMut<TextureResourceSystemState>,
         render_resource_context: Res<Box<dyn RenderResourceContext>>,
         textures: Res<Assets<Texture>>,
         texture_events: Res<Events<AssetEvent<Texture>>>,
+        quality: Res<GraphicsQuality>,
+        render_capabilities: Res<RenderCapabilities>,
     ) {
         let render_resource_context = &**render_resource_context;
+        let anisotropy_clamp =
+            render_capabilities.anisotropy_clamp(quality.settings().anisotropy_clamp());
+        if anisotropy_clamp != state.anisotropy_clamp {
+            state.anisotropy_clamp = anisotropy_clamp;
+            for (id, texture) in textures.iter() {
+                let handle = Handle::<Texture>::weak(id);
+                if let Some(RenderResourceId::Sampler(resource)) =
+                    render_resource_context.get_asset_resource(&handle, SAMPLER_ASSET_INDEX)
+                {
+                    render_resource_context.release_resource(RenderResourceId::Sampler(resource));
+                    let sampler_resource = render_resource_context
+                        .create_sampler(&texture.sampler_descriptor(anisotropy_clamp));
+                    render_resource_context.set_asset_resource(
+                        &handle,
+                        RenderResourceId::Sampler(sampler_resource),
+                        SAMPLER_ASSET_INDEX,
+                    );
+                }
+            }
+        }
+
         let mut changed_textures = HashSet::default();
         for event in state.event_reader.iter(&texture_events) {
             match event {
@@ -111,7 +194,8 @@ impl Texture {
                 let texture_descriptor: TextureDescriptor = texture.into();
                 let texture_resource = render_resource_context.create_texture(texture_descriptor);
 
-                let sampler_resource = render_resource_context.create_sampler(&texture.sampler);
+                let sampler_resource = render_resource_context
+                    .create_sampler(&texture.sampler_descriptor(anisotropy_clamp));
 
                 render_resource_context.set_asset_resource(
                     texture_handle,
@@ -134,13 +218,13 @@ impl Texture {
         if let Some(RenderResourceId::Texture(resource)) =
             render_resource_context.get_asset_resource(handle, TEXTURE_ASSET_INDEX)
         {
-            render_resource_context.remove_texture(resource);
+            render_resource_context.release_resource(RenderResourceId::Texture(resource));
             render_resource_context.remove_asset_resource(handle, TEXTURE_ASSET_INDEX);
         }
         if let Some(RenderResourceId::Sampler(resource)) =
             render_resource_context.get_asset_resource(handle, SAMPLER_ASSET_INDEX)
         {
-            render_resource_context.remove_sampler(resource);
+            render_resource_context.release_resource(RenderResourceId::Sampler(resource));
             render_resource_context.remove_asset_resource(handle, SAMPLER_ASSET_INDEX);
         }
     }
@@ -149,6 +233,7 @@ impl Texture {
 #[derive(Default)]
 pub struct TextureResourceSystemState {
     event_reader: EventReader<AssetEvent<Texture>>,
+    anisotropy_clamp: Option<NonZeroU8>,
 }
 
 impl RenderResource for Option<Handle<Texture>> {
@@ -23,7 +23,7 @@ impl From<&Texture> for TextureDescriptor {
             sample_count: 1,
             dimension: TextureDimension::D2,
             format: texture.format,
-            usage: TextureUsage::SAMPLED | TextureUsage::COPY_DST,
+            usage: texture.usage,
         }
     }
 }
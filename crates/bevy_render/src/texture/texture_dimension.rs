@@ -204,6 +204,15 @@ impl TextureFormat {
         let info = self.pixel_info();
         info.type_size * info.num_components
     }
+
+    pub fn is_depth(&self) -> bool {
+        matches!(
+            self,
+            TextureFormat::Depth32Float
+                | TextureFormat::Depth24Plus
+                | TextureFormat::Depth24PlusStencil8
+        )
+    }
 }
 
 impl Default for TextureFormat {
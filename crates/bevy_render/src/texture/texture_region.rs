@@ -0,0 +1,175 @@
+use super::Texture;
+use bevy_asset::{Assets, Handle};
+use thiserror::Error;
+
+/// Rows copied from a buffer to a texture must be aligned to this many bytes
+pub const REGION_BYTES_PER_ROW_ALIGNMENT: usize = 256;
+
+/// A rectangle of pixels in a texture, in texels from the top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TextureRegion {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        TextureRegion {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TextureRegionError {
+    #[error("Texture does not exist")]
+    MissingTexture,
+    #[error("Region {0:?} does not fit in a {1}x{2} texture")]
+    OutOfBounds(TextureRegion, u32, u32),
+    #[error("Expected {expected} bytes of pixel data for the region but got {actual}")]
+    DataSizeMismatch { expected: usize, actual: usize },
+}
+
+impl Texture {
+    /// Copies tightly packed pixel `data` into `region` of this texture's cpu side data
+    pub fn write_region(
+        &mut self,
+        region: TextureRegion,
+        data: &[u8],
+    ) -> Result<(), TextureRegionError> {
+        let texture_width = self.size.x() as u32;
+        let texture_height = self.size.y() as u32;
+        if region.x + region.width > texture_width || region.y + region.height > texture_height {
+            return Err(TextureRegionError::OutOfBounds(
+                region,
+                texture_width,
+                texture_height,
+            ));
+        }
+
+        let pixel_size = self.format.pixel_size();
+        let row_size = region.width as usize * pixel_size;
+        let expected = row_size * region.height as usize;
+        if data.len() != expected {
+            return Err(TextureRegionError::DataSizeMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        if row_size == 0 {
+            return Ok(());
+        }
+
+        for (row_index, row) in data.chunks_exact(row_size).enumerate() {
+            let begin = ((region.y as usize + row_index) * texture_width as usize
+                + region.x as usize)
+                * pixel_size;
+            self.data[begin..begin + row_size].copy_from_slice(row);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct TextureRegionWrite {
+    pub texture: Handle<Texture>,
+    pub region: TextureRegion,
+    /// Pixel data with each row padded to [REGION_BYTES_PER_ROW_ALIGNMENT]
+    pub data: Vec<u8>,
+    pub bytes_per_row: u32,
+}
+
+/// Queues writes to regions of textures that are already on the gpu. Unlike modifying a texture through
+/// [Assets::get_mut], this doesn't recreate the gpu texture and only uploads the written pixels.
+#[derive(Debug, Default)]
+pub struct TextureRegionWrites {
+    writes: Vec<TextureRegionWrite>,
+}
+
+impl TextureRegionWrites {
+    /// Writes tightly packed pixel `data` to `region` of the texture. The cpu side texture data is updated
+    /// immediately and the region is uploaded the next time the render graph runs.
+    pub fn write(
+        &mut self,
+        textures: &mut Assets<Texture>,
+        handle: &Handle<Texture>,
+        region: TextureRegion,
+        data: &[u8],
+    ) -> Result<(), TextureRegionError> {
+        let texture = textures
+            .get_mut_untracked(handle)
+            .ok_or(TextureRegionError::MissingTexture)?;
+        texture.write_region(region, data)?;
+        if region.width == 0 || region.height == 0 {
+            return Ok(());
+        }
+
+        let row_size = region.width as usize * texture.format.pixel_size();
+        let bytes_per_row = aligned_bytes_per_row(row_size);
+        let mut aligned_data = vec![0; bytes_per_row * region.height as usize];
+        for (row_index, row) in data.chunks_exact(row_size).enumerate() {
+            let offset = row_index * bytes_per_row;
+            aligned_data[offset..offset + row_size].copy_from_slice(row);
+        }
+
+        self.writes.push(TextureRegionWrite {
+            texture: handle.clone_weak(),
+            region,
+            data: aligned_data,
+            bytes_per_row: bytes_per_row as u32,
+        });
+        Ok(())
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = TextureRegionWrite> + '_ {
+        self.writes.drain(..)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+}
+
+pub fn aligned_bytes_per_row(row_size: usize) -> usize {
+    (row_size + REGION_BYTES_PER_ROW_ALIGNMENT - 1) / REGION_BYTES_PER_ROW_ALIGNMENT
+        * REGION_BYTES_PER_ROW_ALIGNMENT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::TextureFormat;
+    use bevy_math::Vec2;
+
+    #[test]
+    fn write_region() {
+        let mut texture = Texture::new_fill(Vec2::new(4.0, 3.0), &[0], TextureFormat::R8Unorm);
+        texture
+            .write_region(TextureRegion::new(1, 1, 2, 2), &[1, 2, 3, 4])
+            .unwrap();
+        assert_eq!(texture.data, vec![0, 0, 0, 0, 0, 1, 2, 0, 0, 3, 4, 0]);
+
+        assert_eq!(
+            texture.write_region(TextureRegion::new(3, 0, 2, 1), &[1, 2]),
+            Err(TextureRegionError::OutOfBounds(
+                TextureRegion::new(3, 0, 2, 1),
+                4,
+                3
+            ))
+        );
+        assert_eq!(
+            texture.write_region(TextureRegion::new(0, 0, 2, 1), &[1]),
+            Err(TextureRegionError::DataSizeMismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use super::{Texture, TextureDescriptor, TextureFormat, TextureStreaming, TEXTURE_ASSET_INDEX};
+use crate::renderer::{RenderResourceContext, RenderResourceId};
+use bevy_app::prelude::{EventReader, Events};
+use bevy_asset::{AssetEvent, Assets, Handle};
+use bevy_ecs::{Local, Res, ResMut};
+use bevy_math::Vec2;
+use bevy_utils::HashMap;
+
+#[derive(Debug)]
+struct ResidentTexture {
+    width: u32,
+    height: u32,
+    pixel_size: usize,
+    /// How many times the resident texture is halved
+    level: u32,
+    max_level: u32,
+    distance: f32,
+}
+
+impl ResidentTexture {
+    fn bytes(&self, level: u32) -> usize {
+        let width = (self.width >> level).max(1) as usize;
+        let height = (self.height >> level).max(1) as usize;
+        width * height * self.pixel_size
+    }
+}
+
+/// Keeps the gpu memory used by texture assets within a budget. While the textures are over the budget, the textures
+/// farthest from the cameras, or not used by anything near them, are replaced on the gpu with versions of half their
+/// size, like dropping their largest mip. They are uploaded at full size again once there is room for them.
+///
+/// Only textures with 8 bit unsigned normalized formats can be reduced. Render targets aren't counted.
+#[derive(Debug)]
+pub struct TextureResidency {
+    /// The most bytes of texture data kept on the gpu. `None` keeps every texture at full size.
+    pub budget_bytes: Option<usize>,
+    /// Textures aren't reduced below this many texels on their shorter side
+    pub min_size: u32,
+    textures: HashMap<Handle<Texture>, ResidentTexture>,
+    reduced: HashMap<Handle<Texture>, Texture>,
+}
+
+impl Default for TextureResidency {
+    fn default() -> Self {
+        TextureResidency {
+            budget_bytes: None,
+            min_size: 64,
+            textures: HashMap::default(),
+            reduced: HashMap::default(),
+        }
+    }
+}
+
+impl TextureResidency {
+    /// Bytes of texture data currently on the gpu
+    pub fn resident_bytes(&self) -> usize {
+        self.textures
+            .values()
+            .map(|texture| texture.bytes(texture.level))
+            .sum()
+    }
+
+    /// Bytes of texture data the textures would use at full size
+    pub fn full_bytes(&self) -> usize {
+        self.textures.values().map(|texture| texture.bytes(0)).sum()
+    }
+
+    /// How many times `texture` is halved on the gpu
+    pub fn level(&self, texture: &Handle<Texture>) -> u32 {
+        self.textures
+            .get(texture)
+            .map_or(0, |texture| texture.level)
+    }
+
+    /// Records the distance of something that uses `texture` to a camera. Textures without a distance this frame are
+    /// reduced first.
+    pub fn set_distance(&mut self, texture: &Handle<Texture>, distance: f32) {
+        if let Some(texture) = self.textures.get_mut(texture) {
+            texture.distance = texture.distance.min(distance);
+        }
+    }
+
+    /// Never reduces `texture`, restoring it if it is reduced. Textures that are updated in place, like glyph atlases,
+    /// are kept at full size automatically.
+    pub fn keep_full_size(&mut self, texture: &Handle<Texture>) {
+        if let Some(texture) = self.textures.get_mut(texture) {
+            texture.max_level = 0;
+        }
+    }
+
+    /// The reduced copy of `texture` that is on the gpu, if it is reduced
+    pub fn reduced_texture(&self, texture: &Handle<Texture>) -> Option<&Texture> {
+        self.reduced.get(texture)
+    }
+
+    /// Starts tracking a texture that was just created on the gpu at full size
+    pub(crate) fn track(&mut self, handle: Handle<Texture>, texture: &Texture) {
+        let width = texture.size.x() as u32;
+        let height = texture.size.y() as u32;
+        let max_level = if is_reducible(texture.format) {
+            let mut level = 0;
+            while (width.min(height) >> (level + 1)) >= self.min_size.max(1) {
+                level += 1;
+            }
+            level
+        } else {
+            0
+        };
+
+        self.reduced.remove(&handle);
+        self.textures.insert(
+            handle,
+            ResidentTexture {
+                width,
+                height,
+                pixel_size: texture.format.pixel_size(),
+                level: 0,
+                max_level,
+                distance: std::f32::INFINITY,
+            },
+        );
+    }
+
+    pub(crate) fn untrack(&mut self, handle: &Handle<Texture>) {
+        self.textures.remove(handle);
+        self.reduced.remove(handle);
+    }
+
+    /// The level each texture should have to fit in `budget`, reducing the farthest textures first
+    fn target_levels(&self, budget: usize) -> HashMap<Handle<Texture>, u32> {
+        let mut textures = self.textures.iter().collect::<Vec<_>>();
+        textures.sort_by(|(_, a), (_, b)| {
+            b.distance
+                .partial_cmp(&a.distance)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut total = self.full_bytes();
+        let mut levels = HashMap::default();
+        for (handle, texture) in textures {
+            let mut level = 0;
+            while total > budget && level < texture.max_level {
+                total = total - texture.bytes(level) + texture.bytes(level + 1);
+                level += 1;
+            }
+            levels.insert(handle.clone_weak(), level);
+        }
+
+        levels
+    }
+
+    /// Decides which textures change their level this frame and returns their new levels. Textures are only restored
+    /// once they fit with some room to spare, so textures near the budget don't switch back and forth every frame.
+    pub(crate) fn update_levels(&mut self) -> Vec<(Handle<Texture>, u32)> {
+        let (reduce_targets, restore_targets) = match self.budget_bytes {
+            Some(budget) => (
+                self.target_levels(budget),
+                self.target_levels(budget / 10 * 9),
+            ),
+            None => (HashMap::default(), HashMap::default()),
+        };
+
+        let mut changes = Vec::new();
+        for (handle, texture) in self.textures.iter_mut() {
+            let reduce_target = reduce_targets.get(handle).copied().unwrap_or(0);
+            let restore_target = restore_targets.get(handle).copied().unwrap_or(0);
+            let level = if reduce_target > texture.level {
+                reduce_target
+            } else if restore_target < texture.level {
+                restore_target
+            } else {
+                texture.level
+            };
+
+            if level != texture.level {
+                texture.level = level;
+                changes.push((handle.clone_weak(), level));
+            }
+            texture.distance = std::f32::INFINITY;
+        }
+
+        changes
+    }
+}
+
+fn is_reducible(format: TextureFormat) -> bool {
+    matches!(
+        format,
+        TextureFormat::R8Unorm
+            | TextureFormat::Rg8Unorm
+            | TextureFormat::Rgba8Unorm
+            | TextureFormat::Rgba8UnormSrgb
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Bgra8UnormSrgb
+    )
+}
+
+/// A copy of `texture` halved `level` times, averaging each block of texels
+pub fn reduce_texture(texture: &Texture, level: u32) -> Texture {
+    let width = texture.size.x() as usize;
+    let height = texture.size.y() as usize;
+    let block = 1 << level;
+    let reduced_width = (width >> level).max(1);
+    let reduced_height = (height >> level).max(1);
+    let pixel_size = texture.format.pixel_size();
+
+    let mut data = Vec::with_capacity(reduced_width * reduced_height * pixel_size);
+    for y in 0..reduced_height {
+        for x in 0..reduced_width {
+            for channel in 0..pixel_size {
+                let mut sum = 0u32;
+                let mut count = 0u32;
+                for block_y in (y * block)..((y + 1) * block).min(height) {
+                    for block_x in (x * block)..((x + 1) * block).min(width) {
+                        sum +=
+                            texture.data[(block_y * width + block_x) * pixel_size + channel] as u32;
+                        count += 1;
+                    }
+                }
+                data.push((sum / count.max(1)) as u8);
+            }
+        }
+    }
+
+    Texture {
+        data,
+        size: Vec2::new(reduced_width as f32, reduced_height as f32),
+        format: texture.format,
+        sampler: texture.sampler,
+        usage: texture.usage,
+    }
+}
+
+#[derive(Default)]
+pub struct TextureResidencyState {
+    event_reader: EventReader<AssetEvent<Texture>>,
+}
+
+/// Changes the size of textures on the gpu to keep them within the [TextureResidency] budget
+pub fn texture_residency_system(
+    mut state: Local<TextureResidencyState>,
+    mut residency: ResMut<TextureResidency>,
+    mut streaming: ResMut<TextureStreaming>,
+    textures: Res<Assets<Texture>>,
+    texture_events: Res<Events<AssetEvent<Texture>>>,
+    render_resource_context: Res<Box<dyn RenderResourceContext>>,
+) {
+    let render_resource_context = &**render_resource_context;
+    for event in state.event_reader.iter(&texture_events) {
+        match event {
+            // the texture resource system creates new and modified textures at full size
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                match textures.get(handle) {
+                    Some(texture) if !texture.data.is_empty() => {
+                        residency.track(handle.clone_weak(), texture)
+                    }
+                    _ => residency.untrack(handle),
+                }
+            }
+            AssetEvent::Removed { handle } => residency.untrack(handle),
+        }
+    }
+
+    for (handle, level) in residency.update_levels() {
+        let texture = if let Some(texture) = textures.get(&handle) {
+            texture
+        } else {
+            continue;
+        };
+
+        if let Some(RenderResourceId::Texture(resource)) =
+            render_resource_context.get_asset_resource(&handle, TEXTURE_ASSET_INDEX)
+        {
+            render_resource_context.release_resource(RenderResourceId::Texture(resource));
+        }
+
+        let reduced = if level > 0 {
+            Some(reduce_texture(texture, level))
+        } else {
+            None
+        };
+        let resident_texture = reduced.as_ref().unwrap_or(texture);
+        let texture_descriptor: TextureDescriptor = resident_texture.into();
+        let texture_resource = render_resource_context.create_texture(texture_descriptor);
+        render_resource_context.set_asset_resource(
+            &handle,
+            RenderResourceId::Texture(texture_resource),
+            TEXTURE_ASSET_INDEX,
+        );
+        streaming.queue(handle.clone_weak(), resident_texture, level);
+
+        match reduced {
+            Some(reduced) => {
+                residency.reduced.insert(handle, reduced);
+            }
+            None => {
+                residency.reduced.remove(&handle);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_asset::HandleId;
+
+    fn texture(size: f32) -> Texture {
+        Texture::new_fill(
+            Vec2::new(size, size),
+            &[255, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    #[test]
+    fn farthest_textures_are_reduced_first() {
+        let near = Handle::<Texture>::weak(HandleId::random::<Texture>());
+        let far = Handle::<Texture>::weak(HandleId::random::<Texture>());
+        let mut residency = TextureResidency {
+            min_size: 16,
+            ..Default::default()
+        };
+        residency.track(near.clone_weak(), &texture(128.0));
+        residency.track(far.clone_weak(), &texture(128.0));
+        assert_eq!(residency.resident_bytes(), 2 * 128 * 128 * 4);
+        assert!(residency.update_levels().is_empty());
+
+        // the far texture has to drop two levels to fit next to the full size near texture
+        residency.budget_bytes = Some(128 * 128 * 4 + 32 * 32 * 4);
+        residency.set_distance(&near, 1.0);
+        residency.set_distance(&far, 100.0);
+        assert_eq!(residency.update_levels(), vec![(far.clone_weak(), 2)]);
+        assert_eq!(residency.resident_bytes(), 128 * 128 * 4 + 32 * 32 * 4);
+
+        // textures are restored once they fit
+        residency.budget_bytes = None;
+        assert_eq!(residency.update_levels(), vec![(far.clone_weak(), 0)]);
+    }
+
+    #[test]
+    fn reduce_texture_averages_blocks() {
+        let mut texture = Texture::new_fill(
+            Vec2::new(4.0, 2.0),
+            &[0, 100, 200, 255],
+            TextureFormat::Rgba8Unorm,
+        );
+        texture.data[0] = 200;
+        let reduced = reduce_texture(&texture, 1);
+        assert_eq!(reduced.size, Vec2::new(2.0, 1.0));
+        assert_eq!(reduced.data, vec![50, 100, 200, 255, 0, 100, 200, 255]);
+    }
+}
@@ -0,0 +1,267 @@
+use super::{aligned_bytes_per_row, Texture, TextureResidency};
+use crate::{camera::ActiveCameras, renderer::RenderResources};
+use bevy_asset::{Asset, Assets, Handle};
+use bevy_ecs::{Query, Res, ResMut};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::HashMap;
+use std::ops::Range;
+
+/// Rows of a texture to copy to the gpu this frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureUpload {
+    pub texture: Handle<Texture>,
+    pub rows: Range<u32>,
+    /// The aligned size of a row in the staging buffer
+    pub bytes_per_row: usize,
+    /// How many times the uploaded texture is halved. Reduced textures are uploaded from
+    /// [TextureResidency::reduced_texture](super::TextureResidency::reduced_texture).
+    pub level: u32,
+}
+
+#[derive(Debug)]
+struct PendingTexture {
+    texture: Handle<Texture>,
+    next_row: u32,
+    rows: u32,
+    bytes_per_row: usize,
+    level: u32,
+}
+
+/// Spreads the uploads of new and modified textures over multiple frames, so loading a large level doesn't stall a
+/// frame while every texture is copied to the gpu. Each frame, pending textures are uploaded in order of their
+/// priority, then their distance to the closest camera, until [TextureStreaming::bytes_per_frame] is spent. Textures
+/// larger than the budget are uploaded a few rows at a time.
+#[derive(Debug)]
+pub struct TextureStreaming {
+    /// The most texture data uploaded per frame. At least one row is uploaded each frame, even if it is larger than
+    /// this. `None` uploads every pending texture right away.
+    pub bytes_per_frame: Option<usize>,
+    pending: Vec<PendingTexture>,
+    priorities: HashMap<Handle<Texture>, i32>,
+    distances: HashMap<Handle<Texture>, f32>,
+}
+
+impl Default for TextureStreaming {
+    fn default() -> Self {
+        TextureStreaming {
+            bytes_per_frame: Some(16 * 1024 * 1024),
+            pending: Vec::new(),
+            priorities: HashMap::default(),
+            distances: HashMap::default(),
+        }
+    }
+}
+
+impl TextureStreaming {
+    /// Uploads `texture` before textures with a lower priority, regardless of their distance to the cameras. Textures
+    /// have a priority of 0 by default.
+    pub fn set_priority(&mut self, texture: Handle<Texture>, priority: i32) {
+        self.priorities.insert(texture.as_weak(), priority);
+    }
+
+    /// Records the distance of something that uses `texture` to a camera. The shortest distance recorded this frame
+    /// orders the uploads of textures with the same priority.
+    pub fn set_distance(&mut self, texture: &Handle<Texture>, distance: f32) {
+        let current = self
+            .distances
+            .entry(texture.as_weak())
+            .or_insert(std::f32::INFINITY);
+        *current = current.min(distance);
+    }
+
+    /// Whether `texture` still has data that hasn't been uploaded
+    pub fn is_pending(&self, texture: &Handle<Texture>) -> bool {
+        self.pending
+            .iter()
+            .any(|pending| pending.texture == *texture)
+    }
+
+    /// The number of textures that haven't been fully uploaded
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Bytes of texture data that haven't been uploaded
+    pub fn pending_bytes(&self) -> usize {
+        self.pending
+            .iter()
+            .map(|pending| (pending.rows - pending.next_row) as usize * pending.bytes_per_row)
+            .sum()
+    }
+
+    /// Uploads all of `texture`'s data, starting over if it is already pending. `texture` is the texture that is on the
+    /// gpu, which is halved `level` times.
+    pub(crate) fn queue(&mut self, handle: Handle<Texture>, texture: &Texture, level: u32) {
+        self.remove(&handle);
+        let row_size = texture.size.x() as usize * texture.format.pixel_size();
+        self.pending.push(PendingTexture {
+            texture: handle,
+            next_row: 0,
+            rows: texture.size.y() as u32,
+            bytes_per_row: aligned_bytes_per_row(row_size),
+            level,
+        });
+    }
+
+    pub(crate) fn remove(&mut self, texture: &Handle<Texture>) {
+        self.pending.retain(|pending| pending.texture != *texture);
+    }
+
+    pub(crate) fn texture_removed(&mut self, texture: &Handle<Texture>) {
+        self.remove(texture);
+        self.priorities.remove(texture);
+    }
+
+    /// Takes the uploads for this frame from the pending textures
+    pub(crate) fn next_uploads(&mut self) -> Vec<TextureUpload> {
+        let priorities = &self.priorities;
+        let distances = &self.distances;
+        let key = |pending: &PendingTexture| {
+            (
+                priorities.get(&pending.texture).copied().unwrap_or(0),
+                distances
+                    .get(&pending.texture)
+                    .copied()
+                    .unwrap_or(std::f32::INFINITY),
+            )
+        };
+        // the sort is stable, so textures that are equally important upload in the order they were queued
+        self.pending.sort_by(|a, b| {
+            let (a_priority, a_distance) = key(a);
+            let (b_priority, b_distance) = key(b);
+            b_priority.cmp(&a_priority).then(
+                a_distance
+                    .partial_cmp(&b_distance)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+        });
+
+        let mut budget = self.bytes_per_frame.unwrap_or(usize::MAX);
+        let mut uploads = Vec::new();
+        for pending in self.pending.iter_mut() {
+            let remaining_rows = pending.rows - pending.next_row;
+            let affordable_rows =
+                (budget / pending.bytes_per_row.max(1)).min(remaining_rows as usize);
+            // the first upload of a frame always makes progress, even if a single row is over the budget
+            let rows = if uploads.is_empty() {
+                affordable_rows.max(1) as u32
+            } else {
+                affordable_rows as u32
+            };
+            if rows == 0 {
+                break;
+            }
+
+            uploads.push(TextureUpload {
+                texture: pending.texture.clone_weak(),
+                rows: pending.next_row..pending.next_row + rows,
+                bytes_per_row: pending.bytes_per_row,
+                level: pending.level,
+            });
+            pending.next_row += rows;
+            budget = budget.saturating_sub(rows as usize * pending.bytes_per_row);
+        }
+
+        self.pending
+            .retain(|pending| pending.next_row < pending.rows);
+        self.distances.clear();
+        uploads
+    }
+}
+
+/// Records how close the entities using each `T` material are to the active cameras, so the textures of nearby
+/// materials are uploaded first and reduced last
+pub fn texture_streaming_distance_system<T: RenderResources + Asset>(
+    mut streaming: ResMut<TextureStreaming>,
+    mut residency: ResMut<TextureResidency>,
+    active_cameras: Res<ActiveCameras>,
+    materials: Res<Assets<T>>,
+    camera_query: Query<&GlobalTransform>,
+    query: Query<(&Handle<T>, &GlobalTransform)>,
+) {
+    if streaming.pending_count() == 0 && residency.budget_bytes.is_none() {
+        return;
+    }
+
+    let camera_positions = active_cameras
+        .cameras
+        .values()
+        .filter_map(|entity| {
+            let transform = camera_query.get((*entity)?).ok()?;
+            Some(transform.translation)
+        })
+        .collect::<Vec<_>>();
+    if camera_positions.is_empty() {
+        return;
+    }
+
+    for (handle, transform) in query.iter() {
+        let material = if let Some(material) = materials.get(handle) {
+            material
+        } else {
+            continue;
+        };
+
+        let distance = camera_positions
+            .iter()
+            .map(|position| (*position - transform.translation).length())
+            .fold(std::f32::INFINITY, f32::min);
+        for render_resource in material.iter() {
+            if let Some(texture) = render_resource.texture() {
+                if streaming.is_pending(texture) {
+                    streaming.set_distance(texture, distance);
+                }
+                residency.set_distance(texture, distance);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::TextureFormat;
+    use bevy_asset::HandleId;
+    use bevy_math::Vec2;
+
+    fn texture(width: f32, height: f32) -> Texture {
+        Texture::new_fill(
+            Vec2::new(width, height),
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    #[test]
+    fn uploads_are_prioritized_and_budgeted() {
+        let near = Handle::<Texture>::weak(HandleId::random::<Texture>());
+        let far = Handle::<Texture>::weak(HandleId::random::<Texture>());
+        let important = Handle::<Texture>::weak(HandleId::random::<Texture>());
+
+        let mut streaming = TextureStreaming::default();
+        // 64 pixel rows are exactly 256 bytes, so 4 rows fit in the budget
+        streaming.bytes_per_frame = Some(4 * 256);
+        streaming.queue(far.clone_weak(), &texture(64.0, 2.0), 0);
+        streaming.queue(near.clone_weak(), &texture(64.0, 3.0), 0);
+        streaming.queue(important.clone_weak(), &texture(64.0, 1.0), 0);
+        streaming.set_priority(important.clone_weak(), 1);
+        streaming.set_distance(&near, 1.0);
+        streaming.set_distance(&far, 10.0);
+
+        let uploads = streaming.next_uploads();
+        assert_eq!(
+            uploads
+                .iter()
+                .map(|upload| (upload.texture.clone_weak(), upload.rows.clone()))
+                .collect::<Vec<_>>(),
+            vec![(important.clone_weak(), 0..1), (near.clone_weak(), 0..3)]
+        );
+        assert_eq!(streaming.pending_bytes(), 2 * 256);
+
+        // distances only last a frame, but the far texture is the only one left
+        let uploads = streaming.next_uploads();
+        assert_eq!(uploads.len(), 1);
+        assert_eq!(uploads[0].rows, 0..2);
+        assert_eq!(streaming.pending_count(), 0);
+    }
+}
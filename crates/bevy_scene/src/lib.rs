@@ -4,15 +4,17 @@ mod scene;
 mod scene_loader;
 mod scene_spawner;
 pub mod serde;
+mod streaming;
 
 pub use command::*;
 pub use dynamic_scene::*;
 pub use scene::*;
 pub use scene_loader::*;
 pub use scene_spawner::*;
+pub use streaming::*;
 
 pub mod prelude {
-    pub use crate::{DynamicScene, Scene, SceneSpawner, SpawnSceneCommands};
+    pub use crate::{DynamicScene, Scene, SceneSpawner, SpawnSceneCommands, StreamingViewer};
 }
 
 use bevy_app::prelude::*;
@@ -1,7 +1,8 @@
 use crate::{DynamicScene, Scene};
 use bevy_app::prelude::*;
 use bevy_asset::{AssetEvent, Assets, Handle};
-use bevy_ecs::{EntityMap, Resources, World};
+use bevy_ecs::{Entity, EntityMap, Resources, World};
+use bevy_transform::prelude::Parent;
 use bevy_type_registry::TypeRegistry;
 use bevy_utils::HashMap;
 use thiserror::Error;
@@ -30,6 +31,8 @@ pub struct SceneSpawner {
     dynamic_scenes_to_spawn: Vec<Handle<DynamicScene>>,
     scenes_to_spawn: Vec<Handle<Scene>>,
     scenes_to_despawn: Vec<Handle<DynamicScene>>,
+    dynamic_scenes_to_spawn_as_child: Vec<(Handle<DynamicScene>, Entity)>,
+    scenes_to_spawn_as_child: Vec<(Handle<Scene>, Entity)>,
 }
 
 #[derive(Error, Debug)]
@@ -51,6 +54,20 @@ impl SceneSpawner {
         self.scenes_to_spawn.push(scene_handle);
     }
 
+    /// Spawns the scene with its root entities as children of `parent`, for example to load a level into a streamed
+    /// chunk. The instance belongs to `parent`: it is despawned with it by
+    /// [despawn_recursive](bevy_transform::hierarchy::DespawnRecursiveExt::despawn_recursive) and isn't updated when
+    /// the scene changes. Nothing is spawned if `parent` was despawned before the scene finished loading.
+    pub fn spawn_dynamic_as_child(&mut self, scene_handle: Handle<DynamicScene>, parent: Entity) {
+        self.dynamic_scenes_to_spawn_as_child
+            .push((scene_handle, parent));
+    }
+
+    /// Spawns the scene with its root entities as children of `parent`. See [SceneSpawner::spawn_dynamic_as_child].
+    pub fn spawn_as_child(&mut self, scene_handle: Handle<Scene>, parent: Entity) {
+        self.scenes_to_spawn_as_child.push((scene_handle, parent));
+    }
+
     pub fn despawn(&mut self, scene_handle: Handle<DynamicScene>) {
         self.scenes_to_despawn.push(scene_handle);
     }
@@ -144,12 +161,28 @@ impl SceneSpawner {
         let mut instance_info = InstanceInfo {
             entity_map: EntityMap::default(),
         };
+        Self::spawn_internal(world, resources, &scene_handle, &mut instance_info)?;
+        self.spawned_instances.insert(instance_id, instance_info);
+        let spawned = self
+            .spawned_scenes
+            .entry(scene_handle)
+            .or_insert_with(Vec::new);
+        spawned.push(instance_id);
+        Ok(())
+    }
+
+    fn spawn_internal(
+        world: &mut World,
+        resources: &Resources,
+        scene_handle: &Handle<Scene>,
+        instance_info: &mut InstanceInfo,
+    ) -> Result<(), SceneSpawnError> {
         let type_registry = resources.get::<TypeRegistry>().unwrap();
         let component_registry = type_registry.component.read();
         let scenes = resources.get::<Assets<Scene>>().unwrap();
         let scene =
             scenes
-                .get(&scene_handle)
+                .get(scene_handle)
                 .ok_or_else(|| SceneSpawnError::NonExistentRealScene {
                     handle: scene_handle.clone(),
                 })?;
@@ -178,12 +211,28 @@ impl SceneSpawner {
                 .map_entities(world, &instance_info.entity_map)
                 .unwrap();
         }
-        self.spawned_instances.insert(instance_id, instance_info);
-        let spawned = self
-            .spawned_scenes
-            .entry(scene_handle)
-            .or_insert_with(Vec::new);
-        spawned.push(instance_id);
+        Ok(())
+    }
+
+    /// Spawns an instance that belongs to `parent` and isn't tracked by the spawner
+    fn spawn_child_instance(
+        world: &mut World,
+        parent: Entity,
+        spawn: impl FnOnce(&mut World, &mut InstanceInfo) -> Result<(), SceneSpawnError>,
+    ) -> Result<(), SceneSpawnError> {
+        if !world.contains(parent) {
+            return Ok(());
+        }
+
+        let mut instance_info = InstanceInfo {
+            entity_map: EntityMap::default(),
+        };
+        spawn(world, &mut instance_info)?;
+        for entity in instance_info.entity_map.values() {
+            if world.get::<Parent>(entity).is_err() {
+                world.insert_one(entity, Parent(parent)).unwrap();
+            }
+        }
         Ok(())
     }
 
@@ -248,6 +297,34 @@ impl SceneSpawner {
             }
         }
 
+        let scenes_to_spawn = std::mem::take(&mut self.dynamic_scenes_to_spawn_as_child);
+
+        for (scene_handle, parent) in scenes_to_spawn {
+            match Self::spawn_child_instance(world, parent, |world, instance_info| {
+                Self::spawn_dynamic_internal(world, resources, &scene_handle, instance_info)
+            }) {
+                Ok(_) => {}
+                Err(SceneSpawnError::NonExistentScene { .. }) => self
+                    .dynamic_scenes_to_spawn_as_child
+                    .push((scene_handle, parent)),
+                Err(err) => return Err(err),
+            }
+        }
+
+        let scenes_to_spawn = std::mem::take(&mut self.scenes_to_spawn_as_child);
+
+        for (scene_handle, parent) in scenes_to_spawn {
+            match Self::spawn_child_instance(world, parent, |world, instance_info| {
+                Self::spawn_internal(world, resources, &scene_handle, instance_info)
+            }) {
+                Ok(_) => {}
+                Err(SceneSpawnError::NonExistentRealScene { .. }) => {
+                    self.scenes_to_spawn_as_child.push((scene_handle, parent))
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
         Ok(())
     }
 }
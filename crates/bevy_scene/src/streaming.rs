@@ -0,0 +1,298 @@
+use bevy_app::prelude::*;
+use bevy_asset::HandleUntyped;
+use bevy_ecs::{Entity, IntoThreadLocalSystem, Resources, With, World};
+use bevy_math::Vec3;
+use bevy_transform::{
+    hierarchy::despawn_with_children_recursive,
+    prelude::{GlobalTransform, Transform},
+};
+use bevy_utils::HashMap;
+use std::sync::Arc;
+
+/// The position of a chunk on the [WorldStreaming] grid. Chunk `(x, y, z)` starts at `(x, y, z) * chunk_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkCoordinates {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl ChunkCoordinates {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        ChunkCoordinates { x, y, z }
+    }
+}
+
+/// Fills a chunk that was just loaded. It is called with the chunk's root entity, which has a [StreamedChunk],
+/// [Transform] and [GlobalTransform] at the chunk's origin. Anything that belongs to the chunk should be spawned as a
+/// child of it, for example with [SceneSpawner::spawn_dynamic_as_child](crate::SceneSpawner::spawn_dynamic_as_child)
+/// or with `world.build().set_entity(chunk).with_children(...)`.
+pub type ChunkProviderFn =
+    dyn Fn(&mut World, &mut Resources, ChunkCoordinates, Entity) + Send + Sync + 'static;
+
+/// Loads the chunks of a grid around the [StreamingViewer]s and unloads the chunks they moved away from, so worlds can
+/// be larger than what fits in memory.
+///
+/// Unloading a chunk despawns its root entity and all of its children. Assets that are only used by a chunk are freed
+/// with it once the last handle to them is dropped, so chunks should keep their assets in components of their entities,
+/// like [ChunkAssets].
+pub struct WorldStreaming {
+    pub chunk_size: f32,
+    /// Chunks whose center is within this distance of a viewer are loaded
+    pub load_distance: f32,
+    /// Loaded chunks are unloaded once their center is farther than this from every viewer. Keeping it larger than
+    /// `load_distance` stops chunks at the edge from loading and unloading over and over while a viewer moves back and
+    /// forth.
+    pub unload_distance: f32,
+    /// Whether chunks are stacked along the Y axis too. Otherwise the grid is flat, all chunks have a `y` of 0 and the
+    /// distances to viewers ignore their height.
+    pub vertical: bool,
+    /// The most chunks that are loaded per update, nearest first, so moving quickly doesn't stall a frame
+    pub max_loads_per_update: usize,
+    provider: Arc<ChunkProviderFn>,
+    loaded: HashMap<ChunkCoordinates, Entity>,
+}
+
+impl WorldStreaming {
+    pub fn new(
+        chunk_size: f32,
+        provider: impl Fn(&mut World, &mut Resources, ChunkCoordinates, Entity) + Send + Sync + 'static,
+    ) -> Self {
+        WorldStreaming {
+            chunk_size,
+            load_distance: chunk_size * 2.0,
+            unload_distance: chunk_size * 3.0,
+            vertical: false,
+            max_loads_per_update: 4,
+            provider: Arc::new(provider),
+            loaded: Default::default(),
+        }
+    }
+
+    pub fn is_loaded(&self, coordinates: ChunkCoordinates) -> bool {
+        self.loaded.contains_key(&coordinates)
+    }
+
+    /// The root entity of a loaded chunk
+    pub fn chunk(&self, coordinates: ChunkCoordinates) -> Option<Entity> {
+        self.loaded.get(&coordinates).copied()
+    }
+
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = (ChunkCoordinates, Entity)> + '_ {
+        self.loaded
+            .iter()
+            .map(|(coordinates, entity)| (*coordinates, *entity))
+    }
+
+    /// The chunk that contains `position`
+    pub fn chunk_at(&self, position: Vec3) -> ChunkCoordinates {
+        let chunk = position / self.chunk_size;
+        ChunkCoordinates::new(
+            chunk.x().floor() as i32,
+            if self.vertical {
+                chunk.y().floor() as i32
+            } else {
+                0
+            },
+            chunk.z().floor() as i32,
+        )
+    }
+
+    pub fn chunk_origin(&self, coordinates: ChunkCoordinates) -> Vec3 {
+        Vec3::new(
+            coordinates.x as f32,
+            coordinates.y as f32,
+            coordinates.z as f32,
+        ) * self.chunk_size
+    }
+
+    fn distance(&self, coordinates: ChunkCoordinates, position: Vec3) -> f32 {
+        let mut offset =
+            self.chunk_origin(coordinates) + Vec3::splat(self.chunk_size * 0.5) - position;
+        if !self.vertical {
+            offset.set_y(0.0);
+        }
+        offset.length()
+    }
+
+    /// The chunks that should be loaded but aren't, nearest to a viewer first
+    fn chunks_to_load(&self, viewers: &[Vec3]) -> Vec<ChunkCoordinates> {
+        let radius = (self.load_distance / self.chunk_size).ceil() as i32 + 1;
+        let vertical_radius = if self.vertical { radius } else { 0 };
+        let mut candidates = HashMap::<ChunkCoordinates, f32>::default();
+        for viewer in viewers.iter() {
+            let center = self.chunk_at(*viewer);
+            for z in -radius..=radius {
+                for y in -vertical_radius..=vertical_radius {
+                    for x in -radius..=radius {
+                        let coordinates =
+                            ChunkCoordinates::new(center.x + x, center.y + y, center.z + z);
+                        let distance = self.distance(coordinates, *viewer);
+                        if distance > self.load_distance || self.is_loaded(coordinates) {
+                            continue;
+                        }
+                        let nearest = candidates.entry(coordinates).or_insert(distance);
+                        *nearest = nearest.min(distance);
+                    }
+                }
+            }
+        }
+
+        let mut candidates = candidates.into_iter().collect::<Vec<_>>();
+        candidates.sort_by(|(a, a_distance), (b, b_distance)| {
+            a_distance
+                .partial_cmp(b_distance)
+                .unwrap()
+                .then_with(|| a.cmp(b))
+        });
+        candidates
+            .into_iter()
+            .map(|(coordinates, _)| coordinates)
+            .collect()
+    }
+
+    /// The loaded chunks that are farther than `unload_distance` from every viewer
+    fn chunks_to_unload(&self, viewers: &[Vec3]) -> Vec<ChunkCoordinates> {
+        let unload_distance = self.unload_distance.max(self.load_distance);
+        self.loaded
+            .keys()
+            .filter(|coordinates| {
+                viewers
+                    .iter()
+                    .all(|viewer| self.distance(**coordinates, *viewer) > unload_distance)
+            })
+            .copied()
+            .collect()
+    }
+}
+
+/// Marks the entities that [WorldStreaming] loads chunks around, usually the cameras or the player
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamingViewer;
+
+/// The root entity of a chunk loaded by [WorldStreaming]
+#[derive(Debug, Clone, Copy)]
+pub struct StreamedChunk {
+    pub coordinates: ChunkCoordinates,
+}
+
+/// Handles to assets that a streamed chunk uses. They are kept loaded while the chunk is, and freed when it is unloaded
+/// if nothing else uses them.
+#[derive(Debug, Default)]
+pub struct ChunkAssets {
+    pub handles: Vec<HandleUntyped>,
+}
+
+/// Loads and unloads chunks around the [StreamingViewer]s. Does nothing while there are no viewers.
+pub fn world_streaming_system(world: &mut World, resources: &mut Resources) {
+    let viewers = world
+        .query::<With<StreamingViewer, &GlobalTransform>>()
+        .map(|transform| transform.translation)
+        .collect::<Vec<_>>();
+    if viewers.is_empty() {
+        return;
+    }
+
+    let (provider, to_unload, to_load) = {
+        let mut streaming = if let Some(streaming) = resources.get_mut::<WorldStreaming>() {
+            streaming
+        } else {
+            return;
+        };
+        // chunks that were despawned by something else count as unloaded
+        streaming.loaded.retain(|_, entity| world.contains(*entity));
+
+        let to_unload = streaming
+            .chunks_to_unload(&viewers)
+            .into_iter()
+            .filter_map(|coordinates| streaming.loaded.remove(&coordinates))
+            .collect::<Vec<_>>();
+        let mut to_load = streaming.chunks_to_load(&viewers);
+        to_load.truncate(streaming.max_loads_per_update);
+        let to_load = to_load
+            .into_iter()
+            .map(|coordinates| {
+                let origin = streaming.chunk_origin(coordinates);
+                let entity = world.spawn((
+                    StreamedChunk { coordinates },
+                    Transform::from_translation(origin),
+                    GlobalTransform::from_translation(origin),
+                ));
+                streaming.loaded.insert(coordinates, entity);
+                (coordinates, entity)
+            })
+            .collect::<Vec<_>>();
+        (streaming.provider.clone(), to_unload, to_load)
+    };
+
+    for entity in to_unload {
+        despawn_with_children_recursive(world, entity);
+    }
+    for (coordinates, entity) in to_load {
+        provider(world, resources, coordinates, entity);
+    }
+}
+
+/// Adds [world_streaming_system]. Insert a [WorldStreaming] resource to define the grid and how chunks are filled, and
+/// add [StreamingViewer] to the entities to load chunks around.
+#[derive(Default)]
+pub struct WorldStreamingPlugin;
+
+impl Plugin for WorldStreamingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_to_stage(
+            stage::PRE_UPDATE,
+            world_streaming_system.thread_local_system(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::WorldBuilderSource;
+    use bevy_transform::prelude::BuildWorldChildren;
+
+    /// A child of each loaded chunk
+    struct Content;
+
+    #[test]
+    fn load_and_unload_chunks() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        let mut streaming = WorldStreaming::new(10.0, |world, _, _, chunk| {
+            world.build().set_entity(chunk).with_children(|chunk| {
+                chunk.spawn((Content,));
+            });
+        });
+        streaming.load_distance = 20.0;
+        streaming.unload_distance = 30.0;
+        streaming.max_loads_per_update = 100;
+        resources.insert(streaming);
+        let viewer = world.spawn((StreamingViewer, GlobalTransform::identity()));
+
+        let update = |world: &mut World, resources: &mut Resources| {
+            world_streaming_system(world, resources);
+            resources.get::<WorldStreaming>().unwrap().loaded.len()
+        };
+
+        // the chunks with centers within 20 of the origin on the XZ plane
+        assert_eq!(update(&mut world, &mut resources), 12);
+        assert_eq!(world.query::<&Content>().count(), 12);
+
+        // chunk (-2, 0, -1) is too far away to be loaded now, but not far enough to be unloaded
+        *world.get_mut::<GlobalTransform>(viewer).unwrap() =
+            GlobalTransform::from_translation(Vec3::new(10.0, 0.0, 0.0));
+        update(&mut world, &mut resources);
+        assert!(resources
+            .get::<WorldStreaming>()
+            .unwrap()
+            .is_loaded(ChunkCoordinates::new(-2, 0, -1)));
+
+        *world.get_mut::<GlobalTransform>(viewer).unwrap() =
+            GlobalTransform::from_translation(Vec3::new(1000.0, 0.0, 0.0));
+        assert_eq!(update(&mut world, &mut resources), 12);
+        assert_eq!(world.query::<&StreamedChunk>().count(), 12);
+        assert_eq!(world.query::<&Content>().count(), 12);
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Migrates a [SettingsDocument] from one version to the next
+pub type SettingsMigration = fn(&mut SettingsDocument);
+
+/// The contents of a settings file. Each registered settings resource is stored in its own section.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettingsDocument {
+    pub version: u32,
+    pub sections: BTreeMap<String, serde_json::Value>,
+}
+
+impl SettingsDocument {
+    pub fn new(version: u32) -> Self {
+        SettingsDocument {
+            version,
+            sections: BTreeMap::new(),
+        }
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<Result<T, serde_json::Error>> {
+        self.sections
+            .get(key)
+            .map(|value| serde_json::from_value(value.clone()))
+    }
+
+    /// Stores `value` in the section `key`. Returns `true` if the section changed.
+    pub fn set<T: Serialize>(&mut self, key: &str, value: &T) -> Result<bool, serde_json::Error> {
+        let value = serde_json::to_value(value)?;
+        if self.sections.get(key) == Some(&value) {
+            return Ok(false);
+        }
+
+        self.sections.insert(key.to_string(), value);
+        Ok(true)
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<serde_json::Value> {
+        self.sections.remove(key)
+    }
+
+    pub fn rename(&mut self, from: &str, to: &str) {
+        if let Some(value) = self.sections.remove(from) {
+            self.sections.insert(to.to_string(), value);
+        }
+    }
+
+    /// Runs the migrations from the document's version up to `version`. `migrations` contains the version each
+    /// migration starts from. Versions without a migration are skipped.
+    pub fn migrate(&mut self, version: u32, migrations: &[(u32, SettingsMigration)]) {
+        if self.version > version {
+            log::warn!(
+                "Settings were saved by a newer version ({} > {}), some settings may not load",
+                self.version,
+                version
+            );
+            return;
+        }
+
+        while self.version < version {
+            let current_version = self.version;
+            for (_from, migration) in migrations
+                .iter()
+                .filter(|(from, _migration)| *from == current_version)
+            {
+                migration(self);
+            }
+            self.version = current_version + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Audio {
+        volume: f32,
+        muted: bool,
+    }
+
+    #[test]
+    fn settings_document_migrations() {
+        let mut document = SettingsDocument::new(0);
+        document.set("sound", &0.5f32).unwrap();
+
+        fn rename_sound(document: &mut SettingsDocument) {
+            document.rename("sound", "volume");
+        }
+        fn nest_volume(document: &mut SettingsDocument) {
+            let volume = document.get::<f32>("volume").unwrap().unwrap();
+            document.remove("volume");
+            document
+                .set(
+                    "audio",
+                    &Audio {
+                        volume,
+                        muted: false,
+                    },
+                )
+                .unwrap();
+        }
+
+        let migrations: [(u32, SettingsMigration); 2] = [(1, nest_volume), (0, rename_sound)];
+        document.migrate(2, &migrations);
+        assert_eq!(document.version, 2);
+        assert_eq!(
+            document.get::<Audio>("audio").unwrap().unwrap(),
+            Audio {
+                volume: 0.5,
+                muted: false
+            }
+        );
+        assert!(document.get::<f32>("sound").is_none());
+
+        assert!(!document
+            .set(
+                "audio",
+                &Audio {
+                    volume: 0.5,
+                    muted: false
+                }
+            )
+            .unwrap());
+    }
+}
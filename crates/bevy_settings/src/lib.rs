@@ -0,0 +1,187 @@
+//! Persists settings resources like audio volumes, key bindings or graphics options between runs.
+//!
+//! Add [SettingsPlugin] before the plugins whose settings it should load, then register each settings resource with
+//! [RegisterSettings::register_settings]. Registered resources are loaded from storage when they are registered and
+//! saved whenever they change.
+
+mod document;
+mod storage;
+
+pub use document::*;
+pub use storage::*;
+
+pub mod prelude {
+    pub use crate::{RegisterSettings, Settings, SettingsPlugin};
+}
+
+use bevy_app::prelude::*;
+use bevy_ecs::{ChangedRes, IntoQuerySystem, ResMut, Resource};
+use bevy_utils::HashMap;
+use serde::{de::DeserializeOwned, Serialize};
+use std::any::TypeId;
+
+pub mod stage {
+    /// Name of the app stage that saves changed settings. Runs after LAST.
+    pub const SAVE_SETTINGS: &str = "save_settings";
+}
+
+/// The persisted settings of an app
+#[derive(Debug)]
+pub struct Settings {
+    document: SettingsDocument,
+    storage: SettingsStorage,
+    keys: HashMap<TypeId, &'static str>,
+    changed: bool,
+}
+
+impl Settings {
+    /// Loads the settings from `storage` and migrates them to `version`
+    pub fn load(
+        storage: SettingsStorage,
+        version: u32,
+        migrations: &[(u32, SettingsMigration)],
+    ) -> Self {
+        let mut document = match storage.load() {
+            Ok(Some(document)) => document,
+            Ok(None) => SettingsDocument::new(version),
+            Err(err) => {
+                log::warn!("Failed to load settings, using defaults: {}", err);
+                SettingsDocument::new(version)
+            }
+        };
+        let migrated = document.version != version;
+        document.migrate(version, migrations);
+
+        Settings {
+            document,
+            storage,
+            keys: HashMap::default(),
+            changed: migrated,
+        }
+    }
+
+    pub fn document(&self) -> &SettingsDocument {
+        &self.document
+    }
+
+    /// Reads the section `key`. Returns `None` if it doesn't exist or can't be read as `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        match self.document.get(key)? {
+            Ok(value) => Some(value),
+            Err(err) => {
+                log::warn!("Failed to read settings \"{}\": {}", key, err);
+                None
+            }
+        }
+    }
+
+    /// Writes `value` to the section `key`. The settings are saved at the end of the frame if the section changed.
+    pub fn set<T: Serialize>(&mut self, key: &str, value: &T) {
+        match self.document.set(key, value) {
+            Ok(changed) => self.changed |= changed,
+            Err(err) => log::warn!("Failed to write settings \"{}\": {}", key, err),
+        }
+    }
+
+    /// Saves the settings now, whether they changed or not
+    pub fn save(&mut self) -> Result<(), SettingsError> {
+        self.storage.save(&self.document)?;
+        self.changed = false;
+        Ok(())
+    }
+
+    fn set_resource<T: Resource + Serialize>(&mut self, value: &T) {
+        if let Some(key) = self.keys.get(&TypeId::of::<T>()).copied() {
+            self.set(key, value);
+        }
+    }
+}
+
+pub struct SettingsPlugin {
+    /// The name of the directory in the user's config directory, or the browser storage key, that settings are
+    /// saved in
+    pub name: String,
+    /// Overrides where settings are stored
+    pub storage: Option<SettingsStorage>,
+    /// The current version of the settings. Increment it when a settings resource changes in a way that old
+    /// settings can't be read anymore, and add a migration from the previous version.
+    pub version: u32,
+    /// Migrations that upgrade stored settings, by the version they start from
+    pub migrations: Vec<(u32, SettingsMigration)>,
+}
+
+impl SettingsPlugin {
+    pub fn new(name: impl Into<String>) -> Self {
+        SettingsPlugin {
+            name: name.into(),
+            storage: None,
+            version: 0,
+            migrations: Vec::new(),
+        }
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let storage = self
+            .storage
+            .clone()
+            .unwrap_or_else(|| SettingsStorage::new(&self.name));
+        app.add_resource(Settings::load(storage, self.version, &self.migrations))
+            .add_stage_after(bevy_app::stage::LAST, stage::SAVE_SETTINGS)
+            .add_system_to_stage(stage::SAVE_SETTINGS, save_settings_system.system());
+    }
+}
+
+/// [AppBuilder] extension methods for persisted settings
+pub trait RegisterSettings {
+    /// Persists the resource `T` in the section `key`. A stored value replaces the current resource, otherwise the
+    /// current resource (or `T::default()`) is kept. Plugins that check whether a resource exists before adding their
+    /// own can be configured by registering their settings before adding them.
+    fn register_settings<T>(&mut self, key: &'static str) -> &mut Self
+    where
+        T: Resource + Default + Serialize + DeserializeOwned;
+}
+
+impl RegisterSettings for AppBuilder {
+    fn register_settings<T>(&mut self, key: &'static str) -> &mut Self
+    where
+        T: Resource + Default + Serialize + DeserializeOwned,
+    {
+        let stored = {
+            let mut settings = self
+                .resources()
+                .get_mut::<Settings>()
+                .expect("Settings do not exist. Consider adding the SettingsPlugin.");
+            settings.keys.insert(TypeId::of::<T>(), key);
+            settings.get::<T>(key)
+        };
+
+        if let Some(value) = stored {
+            self.add_resource(value);
+        } else if self.resources().get::<T>().is_none() {
+            self.add_resource(T::default());
+        }
+
+        self.add_system_to_stage(bevy_app::stage::LAST, store_settings_system::<T>.system())
+    }
+}
+
+/// Writes a changed settings resource to [Settings]
+pub fn store_settings_system<T: Resource + Serialize>(
+    value: ChangedRes<T>,
+    mut settings: ResMut<Settings>,
+) {
+    settings.set_resource(&*value);
+}
+
+/// Saves [Settings] if they changed this frame
+pub fn save_settings_system(mut settings: ResMut<Settings>) {
+    if settings.changed {
+        if let Err(err) = settings.save() {
+            log::warn!("Failed to save settings: {}", err);
+            // don't try again every frame
+            settings.changed = false;
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::SettingsDocument;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("No config directory is available on this platform")]
+    NoConfigDirectory,
+    #[error("Failed to access the settings file")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to serialize settings")]
+    Serialize(#[from] serde_json::Error),
+    #[error("Failed to access browser storage")]
+    BrowserStorage,
+}
+
+/// Where settings are persisted. Native platforms use a json file in the user's config directory, the web uses the
+/// browser's local storage.
+#[derive(Debug, Clone)]
+pub struct SettingsStorage {
+    #[cfg(not(target_arch = "wasm32"))]
+    path: Option<std::path::PathBuf>,
+    #[cfg(target_arch = "wasm32")]
+    key: String,
+}
+
+impl SettingsStorage {
+    /// The default storage for an app called `name`
+    pub fn new(name: &str) -> Self {
+        SettingsStorage {
+            #[cfg(not(target_arch = "wasm32"))]
+            path: dirs::config_dir().map(|dir| dir.join(name).join("settings.json")),
+            #[cfg(target_arch = "wasm32")]
+            key: format!("{}.settings", name),
+        }
+    }
+
+    /// Stores the settings in the file at `path`, for example next to a portable executable
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path(path: impl Into<std::path::PathBuf>) -> Self {
+        SettingsStorage {
+            path: Some(path.into()),
+        }
+    }
+
+    /// Reads the stored settings. Returns `None` if no settings were saved yet.
+    pub fn load(&self) -> Result<Option<SettingsDocument>, SettingsError> {
+        match self.read()? {
+            Some(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(&self, document: &SettingsDocument) -> Result<(), SettingsError> {
+        let contents = serde_json::to_string_pretty(document)?;
+        self.write(&contents)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read(&self) -> Result<Option<String>, SettingsError> {
+        let path = self.path.as_ref().ok_or(SettingsError::NoConfigDirectory)?;
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write(&self, contents: &str) -> Result<(), SettingsError> {
+        let path = self.path.as_ref().ok_or(SettingsError::NoConfigDirectory)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        // write to a temporary file first, so a crash while saving doesn't lose the old settings
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, contents)?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn local_storage() -> Result<web_sys::Storage, SettingsError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or(SettingsError::BrowserStorage)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read(&self) -> Result<Option<String>, SettingsError> {
+        Self::local_storage()?
+            .get_item(&self.key)
+            .map_err(|_| SettingsError::BrowserStorage)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn write(&self, contents: &str) -> Result<(), SettingsError> {
+        Self::local_storage()?
+            .set_item(&self.key, contents)
+            .map_err(|_| SettingsError::BrowserStorage)
+    }
+}
@@ -0,0 +1,462 @@
+//! A post-process pass that simulates color vision deficiencies, or shifts colors so players with one can tell them
+//! apart more easily.
+//!
+//! [ColorblindFilterPlugin] points every pass that renders to the primary window at a texture instead, and a final
+//! pass draws that texture to the window through the [ColorblindFilter]. The filter can be changed at any time, for
+//! example from an accessibility settings menu. Add the plugin after the other render plugins, including
+//! [VirtualResolutionPlugin](crate::virtual_resolution::VirtualResolutionPlugin), so it can redirect their passes.
+
+use crate::{virtual_resolution::redirect_slot_edges, Sprite, SpriteResizeMode, QUAD_HANDLE};
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_ecs::{Commands, IntoQuerySystem, Query, Res, ResMut, With};
+use bevy_math::{Mat4, Vec2, Vec4};
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    entity::Camera2dComponents,
+    pass::{LoadOp, Operations, PassDescriptor, TextureAttachment},
+    pipeline::{
+        BlendDescriptor, ColorStateDescriptor, ColorWrite, CullMode, DynamicBinding, FrontFace,
+        PipelineDescriptor, PipelineSpecialization, RasterizationStateDescriptor, RenderPipeline,
+        RenderPipelines,
+    },
+    prelude::{Color, Draw},
+    render_graph::{
+        base::{self, Msaa},
+        AssetRenderResourcesNode, AssetTextureNode, CameraNode, PassNode, RenderGraph,
+        WindowSwapChainNode, WindowTextureNode,
+    },
+    renderer::RenderResources,
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{Texture, TextureFormat},
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+use bevy_window::Windows;
+
+/// The texture the passes that render to the primary window render to instead
+pub const COLORBLIND_FILTER_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 1563871045370826151);
+
+pub const COLORBLIND_FILTER_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 7081657466912503372);
+
+pub const COLORBLIND_FILTER_MATERIAL_HANDLE: Handle<ColorblindFilterMaterial> =
+    Handle::weak_from_u64(ColorblindFilterMaterial::TYPE_UUID, 2922105393218447712);
+
+pub mod node {
+    pub const COLORBLIND_FILTER_TEXTURE: &str = "colorblind_filter_texture";
+    pub const COLORBLIND_FILTER_MATERIAL: &str = "colorblind_filter_material";
+    pub const COLORBLIND_FILTER_CAMERA: &str = "colorblind_filter_camera";
+    pub const COLORBLIND_FILTER_PASS: &str = "colorblind_filter_pass";
+}
+
+pub mod camera {
+    pub const COLORBLIND_FILTER_CAMERA: &str = "ColorblindFilterCamera";
+}
+
+/// A kind of color blindness, where one of the three types of cone cells in the eye is missing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDeficiency {
+    /// Red cones are missing, so red and green are hard to tell apart and reds look dark
+    Protanopia,
+    /// Green cones are missing, so red and green are hard to tell apart. This is the most common kind.
+    Deuteranopia,
+    /// Blue cones are missing, so blue and green, and yellow and violet, are hard to tell apart
+    Tritanopia,
+}
+
+impl ColorDeficiency {
+    /// The colors seen with the deficiency, as a row-major matrix that transforms linear RGB. From "A Physiologically
+    /// based Model for Simulation of Color Vision Deficiency" by Machado et al., at full severity.
+    pub fn simulation_matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorDeficiency::Protanopia => [
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            ColorDeficiency::Deuteranopia => [
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            ColorDeficiency::Tritanopia => [
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ],
+        }
+    }
+}
+
+/// What the [ColorblindFilter] does with the colors of a [ColorDeficiency]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorblindFilterMode {
+    /// Shows the colors the way they are seen with the deficiency, to check that the game is playable with it
+    Simulate,
+    /// Moves the color differences that can't be seen with the deficiency to colors that can be seen (daltonization)
+    Assist,
+}
+
+/// The filter the primary window is drawn through by [ColorblindFilterPlugin]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorblindFilter {
+    /// `None` leaves the colors unchanged
+    pub deficiency: Option<ColorDeficiency>,
+    pub mode: ColorblindFilterMode,
+    /// How much of the filter is applied, from 0.0 to 1.0
+    pub strength: f32,
+}
+
+impl Default for ColorblindFilter {
+    fn default() -> Self {
+        ColorblindFilter {
+            deficiency: None,
+            mode: ColorblindFilterMode::Assist,
+            strength: 1.0,
+        }
+    }
+}
+
+impl ColorblindFilter {
+    /// The matrix the filter transforms linear RGB colors with, row-major
+    pub fn matrix(&self) -> [[f32; 3]; 3] {
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        let deficiency = match self.deficiency {
+            Some(deficiency) => deficiency,
+            None => return identity,
+        };
+
+        let simulation = deficiency.simulation_matrix();
+        let filter = match self.mode {
+            ColorblindFilterMode::Simulate => simulation,
+            ColorblindFilterMode::Assist => {
+                // adds the error between the original and the simulated color to the channels that can be seen
+                let shift = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+                let error = zip_with(identity, simulation, |a, b| a - b);
+                zip_with(identity, multiply(shift, error), |a, b| a + b)
+            }
+        };
+
+        let strength = self.strength.max(0.0).min(1.0);
+        zip_with(identity, filter, |a, b| a + (b - a) * strength)
+    }
+}
+
+fn zip_with(a: [[f32; 3]; 3], b: [[f32; 3]; 3], f: impl Fn(f32, f32) -> f32) -> [[f32; 3]; 3] {
+    let mut result = a;
+    for (result_row, b_row) in result.iter_mut().zip(b.iter()) {
+        for (value, b) in result_row.iter_mut().zip(b_row.iter()) {
+            *value = f(*value, *b);
+        }
+    }
+    result
+}
+
+fn multiply(a: [[f32; 3]; 3], b: [[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut result = [[0.0; 3]; 3];
+    for (result_row, a_row) in result.iter_mut().zip(a.iter()) {
+        for (column, value) in result_row.iter_mut().enumerate() {
+            *value = a_row
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b_row)| a * b_row[column])
+                .sum();
+        }
+    }
+    result
+}
+
+/// Draws the redirected window texture through the filter matrix
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "7c1f7d2c-1a4e-4f3e-9a52-2e3c0f9d6b18"]
+pub struct ColorblindFilterMaterial {
+    pub matrix: Mat4,
+    pub texture: Handle<Texture>,
+}
+
+/// Marks the camera that draws the filtered window texture
+#[derive(Debug, Default)]
+pub struct ColorblindFilterCamera;
+
+/// Marks the sprite that draws the filtered window texture
+#[derive(Debug, Default)]
+pub struct ColorblindFilterBlit;
+
+#[derive(Default)]
+pub struct ColorblindFilterPlugin;
+
+impl Plugin for ColorblindFilterPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<ColorblindFilterMaterial>()
+            .init_resource::<ColorblindFilter>()
+            .add_startup_system(spawn_colorblind_filter_blit.system())
+            .add_system_to_stage(stage::POST_UPDATE, colorblind_filter_system.system());
+
+        let resources = app.resources();
+        resources
+            .get_mut::<ActiveCameras>()
+            .unwrap()
+            .add(camera::COLORBLIND_FILTER_CAMERA);
+
+        // the texture is resized to the window before the first frame is rendered
+        resources
+            .get_mut::<Assets<Texture>>()
+            .unwrap()
+            .set_untracked(
+                COLORBLIND_FILTER_TEXTURE_HANDLE,
+                Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::default()),
+            );
+        resources
+            .get_mut::<Assets<ColorblindFilterMaterial>>()
+            .unwrap()
+            .set_untracked(
+                COLORBLIND_FILTER_MATERIAL_HANDLE,
+                ColorblindFilterMaterial {
+                    matrix: Mat4::identity(),
+                    texture: COLORBLIND_FILTER_TEXTURE_HANDLE,
+                },
+            );
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(
+                COLORBLIND_FILTER_PIPELINE_HANDLE,
+                build_colorblind_filter_pipeline(&mut shaders),
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_colorblind_filter_graph(&mut render_graph);
+    }
+}
+
+pub fn build_colorblind_filter_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: None,
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("render/colorblind_filter.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("render/colorblind_filter.frag"),
+            ))),
+        })
+    }
+}
+
+fn spawn_colorblind_filter_blit(mut commands: Commands) {
+    commands.spawn(Camera2dComponents {
+        camera: Camera {
+            name: Some(camera::COLORBLIND_FILTER_CAMERA.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    commands.with(ColorblindFilterCamera);
+
+    // the sprite isn't part of the main pass, so it is only drawn by the filter pass
+    commands.spawn((
+        Sprite {
+            resize_mode: SpriteResizeMode::Manual,
+            ..Default::default()
+        },
+        QUAD_HANDLE,
+        COLORBLIND_FILTER_MATERIAL_HANDLE,
+        Draw::default(),
+        RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+            COLORBLIND_FILTER_PIPELINE_HANDLE,
+            PipelineSpecialization {
+                dynamic_bindings: vec![
+                    // Transform
+                    DynamicBinding {
+                        bind_group: 2,
+                        binding: 0,
+                    },
+                    // Sprite_size
+                    DynamicBinding {
+                        bind_group: 2,
+                        binding: 1,
+                    },
+                ],
+                ..Default::default()
+            },
+        )]),
+        Transform::default(),
+        GlobalTransform::default(),
+        ColorblindFilterBlit,
+    ));
+}
+
+/// Applies changes of the [ColorblindFilter] and keeps the filtered texture the size of the primary window
+pub fn colorblind_filter_system(
+    filter: Res<ColorblindFilter>,
+    windows: Res<Windows>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<ColorblindFilterMaterial>>,
+    mut query: Query<With<ColorblindFilterBlit, &mut Sprite>>,
+) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+
+    let window_size = Vec2::new(window.width().max(1) as f32, window.height().max(1) as f32);
+    let texture_size = textures
+        .get(&COLORBLIND_FILTER_TEXTURE_HANDLE)
+        .map(|texture| texture.size);
+    if texture_size.map_or(false, |size| size != window_size) {
+        // the changed texture is recreated at the new size
+        textures
+            .get_mut(&COLORBLIND_FILTER_TEXTURE_HANDLE)
+            .unwrap()
+            .size = window_size;
+    }
+
+    for mut sprite in query.iter_mut() {
+        if sprite.size != window_size {
+            sprite.size = window_size;
+        }
+    }
+
+    let rows = filter.matrix();
+    let column = |i: usize| Vec4::new(rows[0][i], rows[1][i], rows[2][i], 0.0);
+    let matrix = Mat4::from_cols(column(0), column(1), column(2), Vec4::unit_w());
+    let current = materials
+        .get(&COLORBLIND_FILTER_MATERIAL_HANDLE)
+        .map(|material| material.matrix);
+    if current.map_or(false, |current| current != matrix) {
+        materials
+            .get_mut(&COLORBLIND_FILTER_MATERIAL_HANDLE)
+            .unwrap()
+            .matrix = matrix;
+    }
+}
+
+fn add_colorblind_filter_graph(graph: &mut RenderGraph) {
+    graph.add_node(
+        node::COLORBLIND_FILTER_TEXTURE,
+        AssetTextureNode::new(COLORBLIND_FILTER_TEXTURE_HANDLE),
+    );
+
+    // move every pass that renders to the window over to the filtered texture
+    let redirected_nodes = redirect_slot_edges(
+        graph,
+        base::node::PRIMARY_SWAP_CHAIN,
+        node::COLORBLIND_FILTER_TEXTURE,
+    );
+
+    let mut pass_node = PassNode::<&ColorblindFilterBlit>::new(PassDescriptor {
+        color_attachments: vec![Msaa::color_attachment_descriptor(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Clear(Color::BLACK),
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    });
+    pass_node.add_camera(camera::COLORBLIND_FILTER_CAMERA);
+    graph.add_node(node::COLORBLIND_FILTER_PASS, pass_node);
+
+    graph.add_system_node(
+        node::COLORBLIND_FILTER_CAMERA,
+        CameraNode::new(camera::COLORBLIND_FILTER_CAMERA),
+    );
+    graph.add_system_node(
+        node::COLORBLIND_FILTER_MATERIAL,
+        AssetRenderResourcesNode::<ColorblindFilterMaterial>::new(false),
+    );
+    for dependency in [
+        node::COLORBLIND_FILTER_CAMERA,
+        node::COLORBLIND_FILTER_MATERIAL,
+        base::node::TEXTURE_COPY,
+        base::node::SHARED_BUFFERS,
+    ]
+    .iter()
+    {
+        graph
+            .add_node_edge(*dependency, node::COLORBLIND_FILTER_PASS)
+            .unwrap();
+    }
+    // the filter pass samples what the redirected passes rendered
+    for redirected_node in redirected_nodes {
+        let _ = graph.add_node_edge(redirected_node, node::COLORBLIND_FILTER_PASS);
+    }
+
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::COLORBLIND_FILTER_PASS,
+            "color_resolve_target",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+            WindowTextureNode::OUT_TEXTURE,
+            node::COLORBLIND_FILTER_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(matrix: [[f32; 3]; 3], color: [f32; 3]) -> [f32; 3] {
+        let mut result = [0.0; 3];
+        for (value, row) in result.iter_mut().zip(matrix.iter()) {
+            *value = row.iter().zip(color.iter()).map(|(a, b)| a * b).sum();
+        }
+        result
+    }
+
+    #[test]
+    fn colorblind_filter_matrix() {
+        let mut filter = ColorblindFilter::default();
+        assert_eq!(apply(filter.matrix(), [0.2, 0.4, 0.6]), [0.2, 0.4, 0.6]);
+
+        // white and gray stay the same with every filter
+        for deficiency in [
+            ColorDeficiency::Protanopia,
+            ColorDeficiency::Deuteranopia,
+            ColorDeficiency::Tritanopia,
+        ]
+        .iter()
+        {
+            for mode in [ColorblindFilterMode::Simulate, ColorblindFilterMode::Assist].iter() {
+                filter.deficiency = Some(*deficiency);
+                filter.mode = *mode;
+                for channel in apply(filter.matrix(), [0.5, 0.5, 0.5]).iter() {
+                    assert!((channel - 0.5).abs() < 1e-3);
+                }
+            }
+        }
+
+        filter.strength = 0.0;
+        assert_eq!(apply(filter.matrix(), [1.0, 0.0, 0.0]), [1.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,562 @@
+//! Depth of field: things in front of and behind the focus distance of the 3d camera's [DepthOfField] are blurred
+//! like they would be by a camera lens.
+//!
+//! [DepthOfFieldPlugin] points the main pass at a texture instead of the primary window, and renders its depth to a
+//! texture that can be sampled. Passes that draw over the scene, like the ui pass, still render to the window after
+//! the effect, so they aren't blurred. The size of the blur of each pixel, its circle of confusion, is
+//! computed from its depth. A horizontal blur pass splits the scene into a near layer in front of the focus distance
+//! and a far layer behind it and blurs both. A composite pass blurs them vertically and draws them over the sharp
+//! scene to the window. Each pixel gathers the neighbors whose circle of confusion reaches it, so blurred foreground
+//! objects spread over what is behind them, while the blurred background doesn't bleed over sharp objects in front of
+//! it.
+//!
+//! Add the plugin after the other render plugins, so it can redirect their passes. With
+//! [TemporalAntiAliasingPlugin](crate::temporal_anti_aliasing::TemporalAntiAliasingPlugin), add it after that plugin
+//! so the anti-aliased frame is blurred, and before
+//! [ColorblindFilterPlugin](crate::colorblind_filter::ColorblindFilterPlugin).
+
+use crate::{
+    temporal_anti_aliasing::{self, TAA_DEPTH_TEXTURE_HANDLE},
+    virtual_resolution::{order_before_slot_consumers, redirect_scene_slot_edges},
+    Sprite, SpriteResizeMode, QUAD_HANDLE,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_ecs::{Commands, IntoQuerySystem, Query, Res, ResMut};
+use bevy_math::{Mat4, Vec2, Vec4};
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    entity::Camera2dComponents,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, ColorStateDescriptor, ColorWrite, CullMode, DynamicBinding, FrontFace,
+        PipelineDescriptor, PipelineSpecialization, RasterizationStateDescriptor, RenderPipeline,
+        RenderPipelines,
+    },
+    prelude::{Color, Draw},
+    render_graph::{
+        base::{self, Msaa, SingleSampledFeatures},
+        AssetRenderResourcesNode, AssetTextureNode, CameraNode, PassNode, RenderGraph,
+        WindowSwapChainNode,
+    },
+    renderer::RenderResources,
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{FilterMode, Texture, TextureFormat},
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+use bevy_window::Windows;
+
+/// The texture the passes that render to the primary window render to instead
+pub const DOF_COLOR_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 10872301598741326713);
+
+/// The texture the main pass renders its depth to, unless temporal anti-aliasing already does
+pub const DOF_DEPTH_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 2394871236650129384);
+
+/// The horizontally blurred near layer, premultiplied by its coverage
+pub const DOF_NEAR_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 15032457012935670441);
+
+/// The horizontally blurred far layer, with the radius of the near layer in alpha
+pub const DOF_FAR_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 6471920385517203266);
+
+pub const DOF_BLUR_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 9125774361209846615);
+
+pub const DOF_COMPOSITE_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 3578196502114478890);
+
+pub const DOF_MATERIAL_HANDLE: Handle<DofMaterial> =
+    Handle::weak_from_u64(DofMaterial::TYPE_UUID, 12490113865273145607);
+
+pub mod node {
+    pub const DOF_COLOR_TEXTURE: &str = "dof_color_texture";
+    pub const DOF_DEPTH_TEXTURE: &str = "dof_depth_texture";
+    pub const DOF_NEAR_TEXTURE: &str = "dof_near_texture";
+    pub const DOF_FAR_TEXTURE: &str = "dof_far_texture";
+    pub const DOF_MATERIAL: &str = "dof_material";
+    pub const DOF_CAMERA: &str = "dof_camera";
+    pub const DOF_BLUR_PASS: &str = "dof_blur_pass";
+    pub const DOF_COMPOSITE_PASS: &str = "dof_composite_pass";
+}
+
+pub mod camera {
+    pub const DOF_CAMERA: &str = "DofCamera";
+}
+
+/// The lens of a 3d camera, which decides how much things are blurred by [DepthOfFieldPlugin]. Cameras without one
+/// are sharp everywhere.
+///
+/// The blur follows the thin lens model. The lens only affects the blur, the field of view still comes from the
+/// camera's projection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthOfField {
+    /// The distance from the camera that is in focus, in world units
+    pub focus_distance: f32,
+    /// The focal length of the lens, in world units. Longer lenses blur more.
+    pub focal_length: f32,
+    /// The aperture as an f-number, the focal length divided by the diameter of the aperture. Smaller f-numbers blur
+    /// more.
+    pub f_number: f32,
+    /// The height of the camera's sensor, in world units
+    pub sensor_height: f32,
+    /// The largest radius things are blurred by, in pixels. Larger blurs take fewer samples per pixel, so they get
+    /// noisier.
+    pub max_blur_radius: f32,
+}
+
+impl Default for DepthOfField {
+    /// A 50mm lens at f/2.8 on a full frame sensor, in meters
+    fn default() -> Self {
+        DepthOfField {
+            focus_distance: 10.0,
+            focal_length: 0.05,
+            f_number: 2.8,
+            sensor_height: 0.024,
+            max_blur_radius: 16.0,
+        }
+    }
+}
+
+impl DepthOfField {
+    /// The radius of the circle of confusion of something at `distance` from the camera, in pixels of a screen that is
+    /// `screen_height` pixels high. It is negative in front of the focus distance and positive behind it, and isn't
+    /// limited to [DepthOfField::max_blur_radius].
+    pub fn circle_of_confusion(&self, distance: f32, screen_height: f32) -> f32 {
+        self.infinity_blur_radius(screen_height) * (distance - self.focus_distance) / distance
+    }
+
+    /// The radius of the circle of confusion of things infinitely far away, in pixels
+    pub fn infinity_blur_radius(&self, screen_height: f32) -> f32 {
+        let aperture = self.focal_length / self.f_number;
+        let diameter = aperture * self.focal_length
+            / (self.focus_distance - self.focal_length).max(f32::EPSILON);
+        diameter * 0.5 / self.sensor_height * screen_height
+    }
+}
+
+/// The textures and parameters of both depth of field passes
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "b5d1e0a7-3c9f-4e26-8d14-7f0a2c6b9e31"]
+pub struct DofMaterial {
+    /// Turns depths back into distances from the camera
+    pub inverse_projection: Mat4,
+    /// x: the focus distance, y: [DepthOfField::infinity_blur_radius], z: [DepthOfField::max_blur_radius]
+    pub params: Vec4,
+    pub color: Handle<Texture>,
+    pub depth: Handle<Texture>,
+    pub near: Handle<Texture>,
+    pub far: Handle<Texture>,
+}
+
+/// Marks the camera that draws the blur and composite passes
+#[derive(Debug, Default)]
+pub struct DofCamera;
+
+/// Marks the sprite that draws the horizontal blur
+#[derive(Debug, Default)]
+pub struct DofBlurBlit;
+
+/// Marks the sprite that draws the blurred layers over the scene
+#[derive(Debug, Default)]
+pub struct DofCompositeBlit;
+
+#[derive(Default)]
+pub struct DepthOfFieldPlugin;
+
+impl Plugin for DepthOfFieldPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // the depth is sampled by both passes, which isn't possible with multisampled depth
+        if app.resources().get::<Msaa>().unwrap().samples > 1 {
+            log::warn!("depth of field is disabled because Msaa uses more than one sample");
+            return;
+        }
+        // keeps Msaa from changing to more samples later, for example with GraphicsQuality
+        app.resources()
+            .get_mut::<SingleSampledFeatures>()
+            .unwrap()
+            .add("depth of field");
+
+        app.add_asset::<DofMaterial>()
+            .add_startup_system(spawn_dof_blits.system())
+            .add_system_to_stage(stage::POST_UPDATE, depth_of_field_system.system());
+
+        let resources = app.resources();
+        resources
+            .get_mut::<ActiveCameras>()
+            .unwrap()
+            .add(camera::DOF_CAMERA);
+
+        // the textures are resized to the window before the first frame is rendered
+        let mut textures = resources.get_mut::<Assets<Texture>>().unwrap();
+        textures.set_untracked(
+            DOF_COLOR_TEXTURE_HANDLE,
+            Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::default()),
+        );
+        let mut depth =
+            Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::Depth32Float);
+        depth.sampler.min_filter = FilterMode::Nearest;
+        textures.set_untracked(DOF_DEPTH_TEXTURE_HANDLE, depth);
+        for layer in [DOF_NEAR_TEXTURE_HANDLE, DOF_FAR_TEXTURE_HANDLE].iter() {
+            textures.set_untracked(
+                layer,
+                Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::Rgba16Float),
+            );
+        }
+
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+        pipelines.set_untracked(
+            DOF_BLUR_PIPELINE_HANDLE,
+            build_dof_blur_pipeline(&mut shaders),
+        );
+        pipelines.set_untracked(
+            DOF_COMPOSITE_PIPELINE_HANDLE,
+            build_dof_composite_pipeline(&mut shaders),
+        );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        let depth = add_dof_graph(&mut render_graph);
+        resources
+            .get_mut::<Assets<DofMaterial>>()
+            .unwrap()
+            .set_untracked(
+                DOF_MATERIAL_HANDLE,
+                DofMaterial {
+                    inverse_projection: Mat4::identity(),
+                    params: Vec4::zero(),
+                    color: DOF_COLOR_TEXTURE_HANDLE,
+                    depth,
+                    near: DOF_NEAR_TEXTURE_HANDLE,
+                    far: DOF_FAR_TEXTURE_HANDLE,
+                },
+            );
+    }
+}
+
+fn build_dof_pipeline(
+    shaders: &mut Assets<Shader>,
+    fragment: &str,
+    color_states: Vec<ColorStateDescriptor>,
+) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: None,
+        color_states,
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("render/dof.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(ShaderStage::Fragment, fragment))),
+        })
+    }
+}
+
+fn color_state(format: TextureFormat) -> ColorStateDescriptor {
+    ColorStateDescriptor {
+        format,
+        color_blend: BlendDescriptor::REPLACE,
+        alpha_blend: BlendDescriptor::REPLACE,
+        write_mask: ColorWrite::ALL,
+    }
+}
+
+pub fn build_dof_blur_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    build_dof_pipeline(
+        shaders,
+        include_str!("render/dof_blur.frag"),
+        // the near and the far layer
+        vec![
+            color_state(TextureFormat::Rgba16Float),
+            color_state(TextureFormat::Rgba16Float),
+        ],
+    )
+}
+
+pub fn build_dof_composite_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    build_dof_pipeline(
+        shaders,
+        include_str!("render/dof_composite.frag"),
+        vec![color_state(TextureFormat::default())],
+    )
+}
+
+fn spawn_dof_blits(mut commands: Commands) {
+    commands.spawn(Camera2dComponents {
+        camera: Camera {
+            name: Some(camera::DOF_CAMERA.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    commands.with(DofCamera);
+
+    // the sprites aren't part of the main pass, so they are only drawn by their own pass
+    for pipeline in [DOF_BLUR_PIPELINE_HANDLE, DOF_COMPOSITE_PIPELINE_HANDLE].iter() {
+        commands.spawn((
+            Sprite {
+                resize_mode: SpriteResizeMode::Manual,
+                ..Default::default()
+            },
+            QUAD_HANDLE,
+            DOF_MATERIAL_HANDLE,
+            Draw::default(),
+            RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+                pipeline.clone_weak(),
+                PipelineSpecialization {
+                    dynamic_bindings: vec![
+                        // Transform
+                        DynamicBinding {
+                            bind_group: 2,
+                            binding: 0,
+                        },
+                        // Sprite_size
+                        DynamicBinding {
+                            bind_group: 2,
+                            binding: 1,
+                        },
+                    ],
+                    ..Default::default()
+                },
+            )]),
+            Transform::default(),
+            GlobalTransform::default(),
+        ));
+        if *pipeline == DOF_BLUR_PIPELINE_HANDLE {
+            commands.with(DofBlurBlit);
+        } else {
+            commands.with(DofCompositeBlit);
+        }
+    }
+}
+
+/// Keeps the textures the size of the primary window and passes the lens of the 3d camera to the passes
+pub fn depth_of_field_system(
+    active_cameras: Res<ActiveCameras>,
+    windows: Res<Windows>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<DofMaterial>>,
+    cameras: Query<(&Camera, Option<&DepthOfField>)>,
+    mut blits: Query<(&Handle<DofMaterial>, &mut Sprite)>,
+) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+
+    let window_size = Vec2::new(window.width().max(1) as f32, window.height().max(1) as f32);
+    for handle in [
+        DOF_COLOR_TEXTURE_HANDLE,
+        DOF_DEPTH_TEXTURE_HANDLE,
+        DOF_NEAR_TEXTURE_HANDLE,
+        DOF_FAR_TEXTURE_HANDLE,
+    ]
+    .iter()
+    {
+        let texture_size = textures.get(handle).map(|texture| texture.size);
+        if texture_size.map_or(false, |size| size != window_size) {
+            // the changed texture is recreated at the new size
+            textures.get_mut(handle).unwrap().size = window_size;
+        }
+    }
+    for (_material, mut sprite) in blits.iter_mut() {
+        if sprite.size != window_size {
+            sprite.size = window_size;
+        }
+    }
+
+    let (inverse_projection, params) = match active_cameras
+        .get(base::camera::CAMERA3D)
+        .and_then(|entity| cameras.get(entity).ok())
+    {
+        Some((camera, Some(depth_of_field))) => (
+            camera.projection_matrix.inverse(),
+            Vec4::new(
+                depth_of_field.focus_distance,
+                depth_of_field.infinity_blur_radius(window_size.y()),
+                depth_of_field.max_blur_radius.max(0.0),
+                0.0,
+            ),
+        ),
+        // nothing is blurred
+        Some((camera, None)) => (camera.projection_matrix.inverse(), Vec4::zero()),
+        None => return,
+    };
+    let current = materials
+        .get(&DOF_MATERIAL_HANDLE)
+        .map(|material| (material.inverse_projection, material.params));
+    if current.map_or(false, |current| current != (inverse_projection, params)) {
+        let material = materials.get_mut(&DOF_MATERIAL_HANDLE).unwrap();
+        material.inverse_projection = inverse_projection;
+        material.params = params;
+    }
+}
+
+/// Returns the depth texture the passes sample
+fn add_dof_graph(graph: &mut RenderGraph) -> Handle<Texture> {
+    graph.add_node(
+        node::DOF_COLOR_TEXTURE,
+        AssetTextureNode::new(DOF_COLOR_TEXTURE_HANDLE),
+    );
+    graph.add_node(
+        node::DOF_NEAR_TEXTURE,
+        AssetTextureNode::new(DOF_NEAR_TEXTURE_HANDLE),
+    );
+    graph.add_node(
+        node::DOF_FAR_TEXTURE,
+        AssetTextureNode::new(DOF_FAR_TEXTURE_HANDLE),
+    );
+
+    // move the passes that render the scene over to the sharp texture, the passes that draw over it keep rendering
+    // to the window
+    let redirected_nodes = redirect_scene_slot_edges(
+        graph,
+        base::node::PRIMARY_SWAP_CHAIN,
+        node::DOF_COLOR_TEXTURE,
+    );
+    // temporal anti-aliasing already renders the main pass depth to a texture that can be sampled
+    let depth = if graph
+        .get_node_state(temporal_anti_aliasing::node::TAA_DEPTH_TEXTURE)
+        .is_ok()
+    {
+        TAA_DEPTH_TEXTURE_HANDLE
+    } else {
+        graph.add_node(
+            node::DOF_DEPTH_TEXTURE,
+            AssetTextureNode::new(DOF_DEPTH_TEXTURE_HANDLE),
+        );
+        redirect_scene_slot_edges(
+            graph,
+            base::node::MAIN_DEPTH_TEXTURE,
+            node::DOF_DEPTH_TEXTURE,
+        );
+        DOF_DEPTH_TEXTURE_HANDLE
+    };
+
+    let layer_attachment = |name: &str| RenderPassColorAttachmentDescriptor {
+        attachment: TextureAttachment::Input(name.to_string()),
+        resolve_target: None,
+        ops: Operations {
+            load: LoadOp::Clear(Color::NONE),
+            store: true,
+        },
+    };
+    let mut blur_pass_node = PassNode::<&DofBlurBlit>::new(PassDescriptor {
+        color_attachments: vec![layer_attachment("near"), layer_attachment("far")],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    });
+    blur_pass_node.add_camera(camera::DOF_CAMERA);
+    graph.add_node(node::DOF_BLUR_PASS, blur_pass_node);
+
+    let mut composite_pass_node = PassNode::<&DofCompositeBlit>::new(PassDescriptor {
+        color_attachments: vec![layer_attachment("color_attachment")],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    });
+    composite_pass_node.add_camera(camera::DOF_CAMERA);
+    graph.add_node(node::DOF_COMPOSITE_PASS, composite_pass_node);
+
+    graph.add_system_node(node::DOF_CAMERA, CameraNode::new(camera::DOF_CAMERA));
+    graph.add_system_node(
+        node::DOF_MATERIAL,
+        AssetRenderResourcesNode::<DofMaterial>::new(false),
+    );
+    for pass in [node::DOF_BLUR_PASS, node::DOF_COMPOSITE_PASS].iter() {
+        for dependency in [
+            node::DOF_CAMERA,
+            node::DOF_MATERIAL,
+            base::node::TEXTURE_COPY,
+            base::node::SHARED_BUFFERS,
+        ]
+        .iter()
+        {
+            graph.add_node_edge(*dependency, *pass).unwrap();
+        }
+    }
+    // the blur pass samples what the redirected passes rendered, and the composite pass samples the blurred layers
+    for redirected_node in redirected_nodes {
+        let _ = graph.add_node_edge(redirected_node, node::DOF_BLUR_PASS);
+    }
+    graph
+        .add_node_edge(node::DOF_BLUR_PASS, node::DOF_COMPOSITE_PASS)
+        .unwrap();
+
+    for (texture_node, slot) in [
+        (node::DOF_NEAR_TEXTURE, "near"),
+        (node::DOF_FAR_TEXTURE, "far"),
+    ]
+    .iter()
+    {
+        graph
+            .add_slot_edge(
+                *texture_node,
+                AssetTextureNode::OUT_TEXTURE,
+                node::DOF_BLUR_PASS,
+                *slot,
+            )
+            .unwrap();
+    }
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::DOF_COMPOSITE_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+    order_before_slot_consumers(
+        graph,
+        base::node::PRIMARY_SWAP_CHAIN,
+        node::DOF_COMPOSITE_PASS,
+    );
+
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle_of_confusion() {
+        let depth_of_field = DepthOfField {
+            focus_distance: 2.0,
+            focal_length: 0.05,
+            f_number: 2.0,
+            sensor_height: 0.024,
+            max_blur_radius: 16.0,
+        };
+        let screen_height = 1080.0;
+        assert_eq!(depth_of_field.circle_of_confusion(2.0, screen_height), 0.0);
+        assert!(depth_of_field.circle_of_confusion(1.0, screen_height) < 0.0);
+
+        // a 25mm aperture focused at 2m blurs points at infinity to a 0.64mm circle on the 24mm sensor
+        let infinity = depth_of_field.infinity_blur_radius(screen_height);
+        assert!((infinity - 14.42).abs() < 0.01);
+        let far = depth_of_field.circle_of_confusion(4.0, screen_height);
+        assert!((far - infinity * 0.5).abs() < 1e-4);
+        assert!(depth_of_field.circle_of_confusion(1000.0, screen_height) < infinity);
+
+        // a narrower aperture blurs less
+        let narrow = DepthOfField {
+            f_number: 8.0,
+            ..depth_of_field
+        };
+        assert!((narrow.infinity_blur_radius(screen_height) - infinity / 4.0).abs() < 1e-4);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    render::SPRITE_PIPELINE_HANDLE, sprite::Sprite, ColorMaterial, TextureAtlas,
-    TextureAtlasSprite, QUAD_HANDLE, SPRITE_SHEET_PIPELINE_HANDLE,
+    render::SPRITE_PIPELINE_HANDLE, sprite::Sprite, sprite_material_pipeline,
+    sprite_sheet_material_pipeline, ColorMaterial, TextureAtlas, TextureAtlasSprite, QUAD_HANDLE,
+    SPRITE_SHEET_PIPELINE_HANDLE,
 };
 use bevy_asset::Handle;
 use bevy_ecs::Bundle;
@@ -11,6 +12,7 @@ use bevy_render::{
     render_graph::base::MainPass,
 };
 use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
 
 #[derive(Bundle)]
 pub struct SpriteComponents {
@@ -37,11 +39,16 @@ impl Default for SpriteComponents {
                             bind_group: 2,
                             binding: 0,
                         },
-                        // Sprite
+                        // Sprite_size
                         DynamicBinding {
                             bind_group: 2,
                             binding: 1,
                         },
+                        // Sprite_image_mode
+                        DynamicBinding {
+                            bind_group: 2,
+                            binding: 2,
+                        },
                     ],
                     ..Default::default()
                 },
@@ -110,3 +117,75 @@ impl Default for SpriteSheetComponents {
         }
     }
 }
+
+/// A Bundle of components for drawing a single sprite with a custom material added by a
+/// [SpriteMaterialPlugin](crate::SpriteMaterialPlugin). Spawn it together with the `Handle` of the material.
+#[derive(Bundle)]
+pub struct MaterialSpriteComponents {
+    pub sprite: Sprite,
+    pub mesh: Handle<Mesh>,
+    pub main_pass: MainPass,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl MaterialSpriteComponents {
+    /// A sprite drawn with the material `M`. Custom materials don't resize sprites to their texture, so the sprite
+    /// needs a size.
+    pub fn new<M: TypeUuid>(sprite: Sprite) -> Self {
+        Self {
+            sprite,
+            mesh: QUAD_HANDLE,
+            main_pass: MainPass,
+            draw: Draw {
+                is_transparent: true,
+                ..Default::default()
+            },
+            render_pipelines: RenderPipelines::from_pipelines(
+                vec![sprite_material_pipeline::<M>()],
+            ),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+/// A Bundle of components for drawing a single sprite from a sprite sheet with a custom material added by a
+/// [SpriteMaterialPlugin](crate::SpriteMaterialPlugin). Spawn it together with the `Handle` of the material.
+#[derive(Bundle)]
+pub struct MaterialSpriteSheetComponents {
+    pub sprite: TextureAtlasSprite,
+    pub texture_atlas: Handle<TextureAtlas>,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub main_pass: MainPass,
+    pub mesh: Handle<Mesh>,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl MaterialSpriteSheetComponents {
+    /// A sprite of `texture_atlas` drawn with the material `M`
+    pub fn new<M: TypeUuid>(
+        sprite: TextureAtlasSprite,
+        texture_atlas: Handle<TextureAtlas>,
+    ) -> Self {
+        Self {
+            sprite,
+            texture_atlas,
+            draw: Draw {
+                is_transparent: true,
+                ..Default::default()
+            },
+            render_pipelines: RenderPipelines::from_pipelines(vec![
+                sprite_sheet_material_pipeline::<M>(),
+            ]),
+            main_pass: MainPass,
+            mesh: QUAD_HANDLE,
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
@@ -1,26 +1,42 @@
 pub mod collide_aabb;
+pub mod colorblind_filter;
+pub mod depth_of_field;
 pub mod entity;
+pub mod motion_blur;
+pub mod motion_vectors;
+pub mod temporal_anti_aliasing;
+pub mod virtual_resolution;
 
 mod color_material;
 mod dynamic_texture_atlas_builder;
+mod parallax;
 mod rect;
 mod render;
 mod sprite;
+mod sprite_material;
 mod texture_atlas;
 mod texture_atlas_builder;
+mod texture_atlas_packer;
 
 pub use color_material::*;
 pub use dynamic_texture_atlas_builder::*;
+pub use parallax::*;
 pub use rect::*;
 pub use render::*;
 pub use sprite::*;
+pub use sprite_material::*;
 pub use texture_atlas::*;
 pub use texture_atlas_builder::*;
+pub use texture_atlas_packer::*;
 
 pub mod prelude {
     pub use crate::{
-        entity::{SpriteComponents, SpriteSheetComponents},
-        ColorMaterial, Sprite, SpriteResizeMode, TextureAtlas, TextureAtlasSprite,
+        entity::{
+            MaterialSpriteComponents, MaterialSpriteSheetComponents, SpriteComponents,
+            SpriteSheetComponents,
+        },
+        ColorMaterial, ImageMode, ParallaxLayer, SliceBorder, Sprite, SpriteMaterialPlugin,
+        SpriteResizeMode, TextureAtlas, TextureAtlasSprite,
     };
 }
 
@@ -39,15 +55,23 @@ use sprite::sprite_system;
 #[derive(Default)]
 pub struct SpritePlugin;
 
+pub mod stage {
+    pub const PARALLAX: &str = "parallax";
+}
+
 pub const QUAD_HANDLE: Handle<Mesh> = Handle::weak_from_u64(Mesh::TYPE_UUID, 14240461981130137526);
 
 impl Plugin for SpritePlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_asset::<ColorMaterial>()
             .add_asset::<TextureAtlas>()
-            .add_system_to_stage(stage::POST_UPDATE, sprite_system.system())
+            .init_resource::<virtual_resolution::VirtualResolution>()
+            // parallax layers are moved before transforms are updated
+            .add_stage_before(bevy_app::stage::POST_UPDATE, stage::PARALLAX)
+            .add_system_to_stage(stage::PARALLAX, parallax_system.system())
+            .add_system_to_stage(bevy_app::stage::POST_UPDATE, sprite_system.system())
             .add_system_to_stage(
-                stage::POST_UPDATE,
+                bevy_app::stage::POST_UPDATE,
                 asset_shader_defs_system::<ColorMaterial>.system(),
             );
 
@@ -0,0 +1,501 @@
+//! Motion blur: the frames of a 3d camera with [MotionBlur] are blurred along how far things moved on screen since
+//! the previous frame, like the shutter of a film camera that stays open while they move.
+//!
+//! [MotionBlurPlugin] renders how far each mesh moved on screen with the velocity pass of
+//! [motion_vectors](crate::motion_vectors), which it shares with temporal anti-aliasing. Pixels no mesh covers get
+//! their motion from the camera's movement, by reprojecting their depth with the camera's previous view projection
+//! like temporal anti-aliasing does. A blur pass then averages samples along the motion of each pixel and draws the
+//! result to the window, before passes that draw over the scene like the ui pass. Each pixel is blurred along its own
+//! motion, so the silhouettes of moving objects stay sharp against a still background.
+//!
+//! Add the plugin after the other render plugins, so it can redirect their passes. With
+//! [TemporalAntiAliasingPlugin](crate::temporal_anti_aliasing::TemporalAntiAliasingPlugin) or
+//! [DepthOfFieldPlugin](crate::depth_of_field::DepthOfFieldPlugin), add it after those plugins so it can sample the
+//! depth texture they render, and before [ColorblindFilterPlugin](crate::colorblind_filter::ColorblindFilterPlugin).
+
+use crate::{
+    depth_of_field::{self, DOF_DEPTH_TEXTURE_HANDLE},
+    motion_vectors::{self, MOTION_VECTORS_TEXTURE_HANDLE},
+    temporal_anti_aliasing::{self, TAA_DEPTH_TEXTURE_HANDLE},
+    virtual_resolution::{order_before_slot_consumers, redirect_scene_slot_edges},
+    Sprite, SpriteResizeMode, QUAD_HANDLE,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_ecs::{Commands, IntoQuerySystem, Local, Query, Res, ResMut, With};
+use bevy_math::{Mat4, Vec2, Vec4};
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    entity::Camera2dComponents,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, ColorStateDescriptor, ColorWrite, CullMode, DynamicBinding, FrontFace,
+        PipelineDescriptor, PipelineSpecialization, RasterizationStateDescriptor, RenderPipeline,
+        RenderPipelines,
+    },
+    prelude::{Color, Draw},
+    render_graph::{
+        base::{self, Msaa, SingleSampledFeatures},
+        AssetRenderResourcesNode, AssetTextureNode, CameraNode, PassNode, RenderGraph,
+        WindowSwapChainNode,
+    },
+    renderer::RenderResources,
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{FilterMode, Texture, TextureFormat},
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+use bevy_window::Windows;
+
+/// The texture the passes that render to the primary window render to instead
+pub const MOTION_BLUR_COLOR_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 7710348162093575127);
+
+/// The texture the main pass renders its depth to, unless temporal anti-aliasing or depth of field already do
+pub const MOTION_BLUR_DEPTH_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 12943308715625470309);
+
+pub const MOTION_BLUR_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 2867193450918837264);
+
+pub const MOTION_BLUR_MATERIAL_HANDLE: Handle<MotionBlurMaterial> =
+    Handle::weak_from_u64(MotionBlurMaterial::TYPE_UUID, 9391842207561336478);
+
+pub mod node {
+    pub const MOTION_BLUR_COLOR_TEXTURE: &str = "motion_blur_color_texture";
+    pub const MOTION_BLUR_DEPTH_TEXTURE: &str = "motion_blur_depth_texture";
+    pub const MOTION_BLUR_MATERIAL: &str = "motion_blur_material";
+    pub const MOTION_BLUR_CAMERA: &str = "motion_blur_camera";
+    pub const MOTION_BLUR_PASS: &str = "motion_blur_pass";
+}
+
+pub mod camera {
+    pub const MOTION_BLUR_CAMERA: &str = "MotionBlurCamera";
+}
+
+/// The shutter of a 3d camera, which decides how much [MotionBlurPlugin] blurs moving things. Cameras without one, or
+/// with `enabled` set to false, aren't blurred.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionBlur {
+    pub enabled: bool,
+    /// How long the shutter stays open each frame, in degrees of a full frame. 360.0 blurs along the whole motion
+    /// since the previous frame, 180.0 along half of it like most film cameras.
+    pub shutter_angle: f32,
+    /// The number of samples taken along the motion of each pixel. Long blurs need more samples to look smooth.
+    pub samples: u32,
+    /// The longest blur, in pixels
+    pub max_blur: f32,
+}
+
+impl Default for MotionBlur {
+    fn default() -> Self {
+        MotionBlur {
+            enabled: true,
+            shutter_angle: 180.0,
+            samples: 8,
+            max_blur: 48.0,
+        }
+    }
+}
+
+impl MotionBlur {
+    /// The fraction of the motion since the previous frame that is blurred
+    pub fn shutter_fraction(&self) -> f32 {
+        if self.enabled {
+            (self.shutter_angle / 360.0).max(0.0).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// The blur of something that moved by `motion` pixels since the previous frame, in pixels. Blurs shorter than a
+    /// pixel aren't visible and are skipped, which also keeps the jitter of temporal anti-aliasing from blurring still
+    /// frames.
+    pub fn blur(&self, motion: Vec2) -> Vec2 {
+        let blur = motion * self.shutter_fraction();
+        let length = blur.length();
+        if length < 1.0 {
+            Vec2::zero()
+        } else {
+            blur * (length.min(self.max_blur.max(1.0)) / length)
+        }
+    }
+}
+
+/// The textures and parameters of the blur pass
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "7c2e9b14-5a3d-4f80-b6e1-0d9a4c7f2e58"]
+pub struct MotionBlurMaterial {
+    /// Turns depths back into world positions, for the pixels no mesh covers
+    pub inverse_view_projection: Mat4,
+    pub previous_view_projection: Mat4,
+    /// x: [MotionBlur::shutter_fraction], y: [MotionBlur::samples], z: [MotionBlur::max_blur]
+    pub params: Vec4,
+    pub color: Handle<Texture>,
+    pub depth: Handle<Texture>,
+    pub velocity: Handle<Texture>,
+}
+
+/// Marks the camera that draws the blur pass
+#[derive(Debug, Default)]
+pub struct MotionBlurCamera;
+
+/// Marks the sprite that draws the blurred frame
+#[derive(Debug, Default)]
+pub struct MotionBlurBlit;
+
+#[derive(Default)]
+pub struct MotionBlurPlugin;
+
+impl Plugin for MotionBlurPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // the depth is sampled by the blur pass, which isn't possible with multisampled depth
+        if app.resources().get::<Msaa>().unwrap().samples > 1 {
+            log::warn!("motion blur is disabled because Msaa uses more than one sample");
+            return;
+        }
+        // keeps Msaa from changing to more samples later, for example with GraphicsQuality
+        app.resources()
+            .get_mut::<SingleSampledFeatures>()
+            .unwrap()
+            .add("motion blur");
+
+        app.add_asset::<MotionBlurMaterial>()
+            .add_startup_system(spawn_motion_blur_blit.system())
+            // runs after the camera and transform systems, so it sees where things are this frame
+            .add_system_to_stage(stage::POST_UPDATE, motion_blur_system.system());
+        motion_vectors::add_motion_vectors(app);
+
+        let resources = app.resources();
+        resources
+            .get_mut::<ActiveCameras>()
+            .unwrap()
+            .add(camera::MOTION_BLUR_CAMERA);
+
+        // the textures are resized to the window before the first frame is rendered
+        let mut textures = resources.get_mut::<Assets<Texture>>().unwrap();
+        textures.set_untracked(
+            MOTION_BLUR_COLOR_TEXTURE_HANDLE,
+            Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::default()),
+        );
+        let mut depth =
+            Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::Depth32Float);
+        depth.sampler.min_filter = FilterMode::Nearest;
+        textures.set_untracked(MOTION_BLUR_DEPTH_TEXTURE_HANDLE, depth);
+
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(
+                MOTION_BLUR_PIPELINE_HANDLE,
+                build_motion_blur_pipeline(&mut shaders),
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        let depth = add_motion_blur_graph(&mut render_graph);
+        resources
+            .get_mut::<Assets<MotionBlurMaterial>>()
+            .unwrap()
+            .set_untracked(
+                MOTION_BLUR_MATERIAL_HANDLE,
+                MotionBlurMaterial {
+                    inverse_view_projection: Mat4::identity(),
+                    previous_view_projection: Mat4::identity(),
+                    params: Vec4::zero(),
+                    color: MOTION_BLUR_COLOR_TEXTURE_HANDLE,
+                    depth,
+                    velocity: MOTION_VECTORS_TEXTURE_HANDLE,
+                },
+            );
+    }
+}
+
+pub fn build_motion_blur_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: None,
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("render/motion_blur.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("render/motion_blur.frag"),
+            ))),
+        })
+    }
+}
+
+fn spawn_motion_blur_blit(mut commands: Commands) {
+    commands.spawn(Camera2dComponents {
+        camera: Camera {
+            name: Some(camera::MOTION_BLUR_CAMERA.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    commands.with(MotionBlurCamera);
+
+    // the sprite isn't part of the main pass, so it is only drawn by the blur pass
+    commands.spawn((
+        Sprite {
+            resize_mode: SpriteResizeMode::Manual,
+            ..Default::default()
+        },
+        QUAD_HANDLE,
+        MOTION_BLUR_MATERIAL_HANDLE,
+        Draw::default(),
+        RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+            MOTION_BLUR_PIPELINE_HANDLE,
+            PipelineSpecialization {
+                dynamic_bindings: vec![
+                    // Transform
+                    DynamicBinding {
+                        bind_group: 2,
+                        binding: 0,
+                    },
+                    // Sprite_size
+                    DynamicBinding {
+                        bind_group: 2,
+                        binding: 1,
+                    },
+                ],
+                ..Default::default()
+            },
+        )]),
+        Transform::default(),
+        GlobalTransform::default(),
+        MotionBlurBlit,
+    ));
+}
+
+/// The 3d camera's view projection in the previous frame
+#[derive(Debug, Default)]
+pub struct MotionBlurState {
+    previous_view_projection: Option<Mat4>,
+}
+
+/// Keeps the textures the size of the primary window and passes where the camera was in this and the previous frame
+/// to the blur pass
+pub fn motion_blur_system(
+    mut state: Local<MotionBlurState>,
+    active_cameras: Res<ActiveCameras>,
+    windows: Res<Windows>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<MotionBlurMaterial>>,
+    cameras: Query<(&Camera, &GlobalTransform, Option<&MotionBlur>)>,
+    mut blits: Query<With<MotionBlurBlit, &mut Sprite>>,
+) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+
+    let window_size = Vec2::new(window.width().max(1) as f32, window.height().max(1) as f32);
+    for handle in [
+        MOTION_BLUR_COLOR_TEXTURE_HANDLE,
+        MOTION_BLUR_DEPTH_TEXTURE_HANDLE,
+    ]
+    .iter()
+    {
+        let texture_size = textures.get(handle).map(|texture| texture.size);
+        if texture_size.map_or(false, |size| size != window_size) {
+            // the changed texture is recreated at the new size
+            textures.get_mut(handle).unwrap().size = window_size;
+        }
+    }
+    for mut sprite in blits.iter_mut() {
+        if sprite.size != window_size {
+            sprite.size = window_size;
+        }
+    }
+
+    let (camera, camera_transform, motion_blur) = if let Some(camera) = active_cameras
+        .get(base::camera::CAMERA3D)
+        .and_then(|entity| cameras.get(entity).ok())
+    {
+        camera
+    } else {
+        return;
+    };
+    let view_projection = camera.projection_matrix * camera_transform.compute_matrix().inverse();
+    let previous_view_projection = state
+        .previous_view_projection
+        .replace(view_projection)
+        .unwrap_or(view_projection);
+
+    let motion_blur = motion_blur.copied().unwrap_or(MotionBlur {
+        enabled: false,
+        ..Default::default()
+    });
+    let params = Vec4::new(
+        motion_blur.shutter_fraction(),
+        motion_blur.samples.max(1) as f32,
+        motion_blur.max_blur.max(1.0),
+        0.0,
+    );
+    if let Some(material) = materials.get_mut(&MOTION_BLUR_MATERIAL_HANDLE) {
+        material.inverse_view_projection = view_projection.inverse();
+        material.previous_view_projection = previous_view_projection;
+        material.params = params;
+    }
+}
+
+/// Returns the depth texture the blur pass samples
+fn add_motion_blur_graph(graph: &mut RenderGraph) -> Handle<Texture> {
+    graph.add_node(
+        node::MOTION_BLUR_COLOR_TEXTURE,
+        AssetTextureNode::new(MOTION_BLUR_COLOR_TEXTURE_HANDLE),
+    );
+
+    // move the passes that render the scene over to the sharp texture, the passes that draw over it keep rendering
+    // to the window
+    let redirected_nodes = redirect_scene_slot_edges(
+        graph,
+        base::node::PRIMARY_SWAP_CHAIN,
+        node::MOTION_BLUR_COLOR_TEXTURE,
+    );
+    // temporal anti-aliasing and depth of field already render the main pass depth to a texture that can be sampled
+    let (depth_node, depth) = if graph
+        .get_node_state(temporal_anti_aliasing::node::TAA_DEPTH_TEXTURE)
+        .is_ok()
+    {
+        (
+            temporal_anti_aliasing::node::TAA_DEPTH_TEXTURE,
+            TAA_DEPTH_TEXTURE_HANDLE,
+        )
+    } else if graph
+        .get_node_state(depth_of_field::node::DOF_DEPTH_TEXTURE)
+        .is_ok()
+    {
+        (
+            depth_of_field::node::DOF_DEPTH_TEXTURE,
+            DOF_DEPTH_TEXTURE_HANDLE,
+        )
+    } else {
+        graph.add_node(
+            node::MOTION_BLUR_DEPTH_TEXTURE,
+            AssetTextureNode::new(MOTION_BLUR_DEPTH_TEXTURE_HANDLE),
+        );
+        redirect_scene_slot_edges(
+            graph,
+            base::node::MAIN_DEPTH_TEXTURE,
+            node::MOTION_BLUR_DEPTH_TEXTURE,
+        );
+        (
+            node::MOTION_BLUR_DEPTH_TEXTURE,
+            MOTION_BLUR_DEPTH_TEXTURE_HANDLE,
+        )
+    };
+
+    // temporal anti-aliasing may have added the velocity pass already
+    motion_vectors::add_motion_vectors_graph(graph, depth_node);
+
+    let mut blur_pass_node = PassNode::<&MotionBlurBlit>::new(PassDescriptor {
+        color_attachments: vec![RenderPassColorAttachmentDescriptor {
+            attachment: TextureAttachment::Input("color_attachment".to_string()),
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::BLACK),
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    });
+    blur_pass_node.add_camera(camera::MOTION_BLUR_CAMERA);
+    graph.add_node(node::MOTION_BLUR_PASS, blur_pass_node);
+
+    graph.add_system_node(
+        node::MOTION_BLUR_CAMERA,
+        CameraNode::new(camera::MOTION_BLUR_CAMERA),
+    );
+    graph.add_system_node(
+        node::MOTION_BLUR_MATERIAL,
+        AssetRenderResourcesNode::<MotionBlurMaterial>::new(false),
+    );
+    for dependency in [
+        node::MOTION_BLUR_CAMERA,
+        node::MOTION_BLUR_MATERIAL,
+        motion_vectors::node::MOTION_VECTORS_PASS,
+        base::node::TEXTURE_COPY,
+        base::node::SHARED_BUFFERS,
+    ]
+    .iter()
+    {
+        graph
+            .add_node_edge(*dependency, node::MOTION_BLUR_PASS)
+            .unwrap();
+    }
+    // the blur pass samples what the redirected passes rendered
+    for redirected_node in redirected_nodes {
+        let _ = graph.add_node_edge(redirected_node, node::MOTION_BLUR_PASS);
+    }
+
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::MOTION_BLUR_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+    order_before_slot_consumers(
+        graph,
+        base::node::PRIMARY_SWAP_CHAIN,
+        node::MOTION_BLUR_PASS,
+    );
+
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blur_follows_shutter() {
+        let motion_blur = MotionBlur {
+            shutter_angle: 180.0,
+            max_blur: 20.0,
+            ..Default::default()
+        };
+        assert_eq!(motion_blur.shutter_fraction(), 0.5);
+        assert_eq!(motion_blur.blur(Vec2::new(10.0, 0.0)), Vec2::new(5.0, 0.0));
+        // blurs under a pixel are skipped and long blurs keep their direction
+        assert_eq!(motion_blur.blur(Vec2::new(1.0, 1.0)), Vec2::zero());
+        assert_eq!(
+            motion_blur.blur(Vec2::new(0.0, -100.0)),
+            Vec2::new(0.0, -20.0)
+        );
+
+        let open = MotionBlur {
+            shutter_angle: 720.0,
+            ..motion_blur
+        };
+        assert_eq!(open.shutter_fraction(), 1.0);
+        let disabled = MotionBlur {
+            enabled: false,
+            ..motion_blur
+        };
+        assert_eq!(disabled.blur(Vec2::new(10.0, 0.0)), Vec2::zero());
+    }
+}
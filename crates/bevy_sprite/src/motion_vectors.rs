@@ -0,0 +1,335 @@
+//! Motion vectors: how far each 3d mesh of the main pass moved on screen since the previous frame, with its own motion
+//! and the camera's.
+//!
+//! [TemporalAntiAliasingPlugin](crate::temporal_anti_aliasing::TemporalAntiAliasingPlugin) reprojects its history
+//! with them and [MotionBlurPlugin](crate::motion_blur::MotionBlurPlugin) blurs along them. Whichever of them is added
+//! first adds a velocity pass, which runs after the main pass and renders the motion of each mesh to
+//! [MOTION_VECTORS_TEXTURE_HANDLE]. The motion doesn't include the jitter of temporal anti-aliasing, so still meshes
+//! don't move.
+
+use crate::Sprite;
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Entity, IntoQuerySystem, Query, Res, ResMut, With, Without};
+use bevy_math::{Mat4, Vec2};
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    mesh::Mesh,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor,
+        RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, ColorStateDescriptor, ColorWrite, CompareFunction, CullMode,
+        DepthStencilStateDescriptor, DynamicBinding, FrontFace, PipelineDescriptor,
+        PipelineSpecialization, RasterizationStateDescriptor, RenderPipeline, RenderPipelines,
+        StencilStateDescriptor, StencilStateFaceDescriptor,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, MainPass},
+        AssetTextureNode, PassNode, RenderGraph, RenderResourcesNode,
+    },
+    renderer::RenderResources,
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{FilterMode, Texture, TextureFormat},
+};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_type_registry::TypeUuid;
+use bevy_window::Windows;
+
+/// The motion of the meshes since the previous frame, in texture coordinates. Alpha is 1.0 where a mesh was drawn.
+pub const MOTION_VECTORS_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 4452190837466102985);
+
+pub const MOTION_VECTORS_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 16038821769324407711);
+
+pub mod node {
+    pub const MOTION_VECTORS_TEXTURE: &str = "motion_vectors_texture";
+    pub const MOTION_VECTORS: &str = "motion_vectors";
+    pub const MOTION_VECTORS_PASS: &str = "motion_vectors_pass";
+}
+
+/// Where a mesh was on screen in this and in the previous frame, as seen by the 3d camera without its jitter. It is
+/// added to the 3d meshes of the main pass.
+#[derive(Debug, Clone, RenderResources)]
+pub struct MotionVectors {
+    pub model_view_projection: Mat4,
+    pub previous_model_view_projection: Mat4,
+}
+
+/// How far temporal anti-aliasing moved the 3d camera's projection this frame, in normalized device coordinates
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ProjectionJitter {
+    pub offset: Vec2,
+}
+
+impl ProjectionJitter {
+    /// Moves a jittered projection back to where it was before the jitter
+    pub fn unjitter(&self, projection: Mat4) -> Mat4 {
+        Mat4::from_translation(-self.offset.extend(0.0)) * projection
+    }
+}
+
+/// Adds the velocity pipeline and texture and the system that keeps [MotionVectors] up to date, unless another plugin
+/// already added them
+pub(crate) fn add_motion_vectors(app: &mut AppBuilder) {
+    if app.resources().get::<ProjectionJitter>().is_some() {
+        return;
+    }
+
+    app.add_resource(ProjectionJitter::default())
+        // runs after the camera and transform systems, so it sees where things are this frame
+        .add_system_to_stage(stage::POST_UPDATE, motion_vectors_system.system());
+
+    let resources = app.resources();
+    // the motion of a mesh shouldn't be blended with the motion of what is behind it. The texture is resized to the
+    // window before the first frame is rendered.
+    let mut velocity = Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::Rgba16Float);
+    velocity.sampler.min_filter = FilterMode::Nearest;
+    velocity.sampler.mag_filter = FilterMode::Nearest;
+    resources
+        .get_mut::<Assets<Texture>>()
+        .unwrap()
+        .set_untracked(MOTION_VECTORS_TEXTURE_HANDLE, velocity);
+
+    let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+    resources
+        .get_mut::<Assets<PipelineDescriptor>>()
+        .unwrap()
+        .set_untracked(
+            MOTION_VECTORS_PIPELINE_HANDLE,
+            build_motion_vectors_pipeline(&mut shaders),
+        );
+}
+
+/// Renders the motion of meshes to the velocity texture. It is only drawn by the velocity pass, and it doesn't write
+/// depth, so it only draws the surfaces the main pass left visible.
+pub fn build_motion_vectors_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        pass_tag: Some(node::MOTION_VECTORS_PASS.into()),
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: Some(DepthStencilStateDescriptor {
+            format: TextureFormat::Depth32Float,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::LessEqual,
+            stencil: StencilStateDescriptor {
+                front: StencilStateFaceDescriptor::IGNORE,
+                back: StencilStateFaceDescriptor::IGNORE,
+                read_mask: 0,
+                write_mask: 0,
+            },
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::Rgba16Float,
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("render/motion_vectors.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("render/motion_vectors.frag"),
+            ))),
+        })
+    }
+}
+
+/// The velocity pipeline of a mesh, with its uniforms bound dynamically
+pub fn motion_vectors_pipeline() -> RenderPipeline {
+    RenderPipeline::specialized(
+        MOTION_VECTORS_PIPELINE_HANDLE,
+        PipelineSpecialization {
+            dynamic_bindings: vec![
+                // Transform
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 0,
+                },
+                // MotionVectors_model_view_projection
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 1,
+                },
+                // MotionVectors_previous_model_view_projection
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 2,
+                },
+            ],
+            ..Default::default()
+        },
+    )
+}
+
+/// Keeps the velocity texture the size of the primary window, gives new 3d meshes [MotionVectors] and moves the
+/// matrices of the others a frame forward
+#[allow(clippy::too_many_arguments)]
+pub fn motion_vectors_system(
+    mut commands: Commands,
+    jitter: Res<ProjectionJitter>,
+    active_cameras: Res<ActiveCameras>,
+    windows: Res<Windows>,
+    mut textures: ResMut<Assets<Texture>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut new_meshes: Query<
+        With<
+            MainPass,
+            With<
+                Handle<Mesh>,
+                Without<
+                    Sprite,
+                    Without<MotionVectors, (Entity, &GlobalTransform, &mut RenderPipelines)>,
+                >,
+            >,
+        >,
+    >,
+    mut meshes: Query<(&GlobalTransform, &mut MotionVectors)>,
+) {
+    if let Some(window) = windows.get_primary() {
+        let window_size = Vec2::new(window.width().max(1) as f32, window.height().max(1) as f32);
+        let texture_size = textures
+            .get(&MOTION_VECTORS_TEXTURE_HANDLE)
+            .map(|texture| texture.size);
+        if texture_size.map_or(false, |size| size != window_size) {
+            // the changed texture is recreated at the new size
+            textures
+                .get_mut(&MOTION_VECTORS_TEXTURE_HANDLE)
+                .unwrap()
+                .size = window_size;
+        }
+    }
+
+    let (camera, camera_transform) = if let Some(camera) = active_cameras
+        .get(base::camera::CAMERA3D)
+        .and_then(|entity| cameras.get(entity).ok())
+    {
+        camera
+    } else {
+        return;
+    };
+    let view_projection =
+        jitter.unjitter(camera.projection_matrix) * camera_transform.compute_matrix().inverse();
+
+    // new meshes start out still
+    for (entity, transform, mut render_pipelines) in new_meshes.iter_mut() {
+        let model_view_projection = view_projection * transform.compute_matrix();
+        render_pipelines.pipelines.push(motion_vectors_pipeline());
+        commands.insert_one(
+            entity,
+            MotionVectors {
+                model_view_projection,
+                previous_model_view_projection: model_view_projection,
+            },
+        );
+    }
+    for (transform, mut motion_vectors) in meshes.iter_mut() {
+        let model_view_projection = view_projection * transform.compute_matrix();
+        // still meshes aren't uploaded again
+        if motion_vectors.model_view_projection != model_view_projection
+            || motion_vectors.previous_model_view_projection != model_view_projection
+        {
+            motion_vectors.previous_model_view_projection = motion_vectors.model_view_projection;
+            motion_vectors.model_view_projection = model_view_projection;
+        }
+    }
+}
+
+/// Adds the velocity pass, which tests against the main pass depth `depth_node` outputs, unless another plugin
+/// already added it
+pub(crate) fn add_motion_vectors_graph(graph: &mut RenderGraph, depth_node: &'static str) {
+    if graph.get_node_state(node::MOTION_VECTORS_PASS).is_ok() {
+        return;
+    }
+
+    graph.add_node(
+        node::MOTION_VECTORS_TEXTURE,
+        AssetTextureNode::new(MOTION_VECTORS_TEXTURE_HANDLE),
+    );
+    let mut pass_node = PassNode::<&MotionVectors>::new(PassDescriptor {
+        color_attachments: vec![RenderPassColorAttachmentDescriptor {
+            attachment: TextureAttachment::Input("color_attachment".to_string()),
+            resolve_target: None,
+            ops: Operations {
+                load: LoadOp::Clear(Color::NONE),
+                store: true,
+            },
+        }],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Load,
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    });
+    pass_node.add_camera(base::camera::CAMERA3D);
+    pass_node.set_pass_tag(node::MOTION_VECTORS_PASS);
+    graph.add_node(node::MOTION_VECTORS_PASS, pass_node);
+    graph.add_system_node(
+        node::MOTION_VECTORS,
+        RenderResourcesNode::<MotionVectors>::new(true),
+    );
+    for dependency in [
+        base::node::MAIN_PASS,
+        base::node::CAMERA3D,
+        node::MOTION_VECTORS,
+        base::node::TEXTURE_COPY,
+        base::node::SHARED_BUFFERS,
+    ]
+    .iter()
+    {
+        graph
+            .add_node_edge(*dependency, node::MOTION_VECTORS_PASS)
+            .unwrap();
+    }
+    graph
+        .add_slot_edge(
+            node::MOTION_VECTORS_TEXTURE,
+            AssetTextureNode::OUT_TEXTURE,
+            node::MOTION_VECTORS_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            depth_node,
+            AssetTextureNode::OUT_TEXTURE,
+            node::MOTION_VECTORS_PASS,
+            "depth",
+        )
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::Vec4;
+
+    #[test]
+    fn unjitter_restores_projection() {
+        let projection = Mat4::perspective_rh(1.0, 1.5, 0.1, 100.0);
+        let jitter = ProjectionJitter {
+            offset: Vec2::new(0.002, -0.001),
+        };
+        let jittered = Mat4::from_translation(jitter.offset.extend(0.0)) * projection;
+        let point = Vec4::new(1.0, -2.0, -10.0, 1.0);
+        let expected = projection * point;
+        let unjittered = jitter.unjitter(jittered) * point;
+        assert!((expected - unjittered).length() < 1e-5);
+    }
+}
@@ -0,0 +1,108 @@
+use bevy_ecs::{Query, QuerySet, Res};
+use bevy_math::{Vec2, Vec3};
+use bevy_render::{camera::ActiveCameras, render_graph::base::camera::CAMERA2D};
+use bevy_transform::prelude::Transform;
+
+/// Moves an entity along with the 2d camera to create the illusion of depth. A `factor` of 0 keeps the entity in
+/// place like any other entity, a `factor` of 1 moves it with the camera so it appears infinitely far away. Values in
+/// between are used for background layers, negative values for foreground layers that pass by faster than the world.
+///
+/// The layer's [Transform] is taken as its position while the camera is at the origin, so it shouldn't be changed after
+/// the layer is spawned.
+#[derive(Debug, Clone)]
+pub struct ParallaxLayer {
+    pub factor: Vec2,
+    /// Wraps the layer horizontally in steps of `tile_width`, so it never leaves the camera. A layer that repeats
+    /// every `tile_width` units and is one `tile_width` wider than the view scrolls forever, for example a sprite
+    /// with [ImageMode::Tile](crate::ImageMode::Tile).
+    pub tile_width: Option<f32>,
+    origin: Option<Vec3>,
+}
+
+impl ParallaxLayer {
+    pub fn new(factor: Vec2) -> Self {
+        ParallaxLayer {
+            factor,
+            tile_width: None,
+            origin: None,
+        }
+    }
+
+    /// Creates a layer that wraps horizontally every `tile_width` units
+    pub fn tiled(factor: Vec2, tile_width: f32) -> Self {
+        ParallaxLayer {
+            tile_width: Some(tile_width),
+            ..ParallaxLayer::new(factor)
+        }
+    }
+}
+
+impl Default for ParallaxLayer {
+    fn default() -> Self {
+        ParallaxLayer::new(Vec2::zero())
+    }
+}
+
+fn parallax_translation(origin: Vec3, camera: Vec3, factor: Vec2, tile_width: Option<f32>) -> Vec3 {
+    let mut translation = origin + (camera.truncate() * factor).extend(0.0);
+    if let Some(tile_width) = tile_width.filter(|tile_width| *tile_width > 0.0) {
+        let tiles = ((camera.x() - translation.x()) / tile_width).round();
+        *translation.x_mut() += tiles * tile_width;
+    }
+    translation
+}
+
+/// Offsets [ParallaxLayer]s relative to the 2d camera
+pub fn parallax_system(
+    active_cameras: Res<ActiveCameras>,
+    mut queries: QuerySet<(
+        Query<&Transform>,
+        Query<(&mut ParallaxLayer, &mut Transform)>,
+    )>,
+) {
+    let camera = if let Some(transform) = active_cameras
+        .get(CAMERA2D)
+        .and_then(|entity| queries.q0().get(entity).ok())
+    {
+        transform.translation
+    } else {
+        return;
+    };
+
+    for (mut layer, mut transform) in queries.q1_mut().iter_mut() {
+        let factor = layer.factor;
+        let origin = *layer.origin.get_or_insert_with(|| {
+            transform.translation - (camera.truncate() * factor).extend(0.0)
+        });
+        transform.translation = parallax_translation(origin, camera, factor, layer.tile_width);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallax_translation_follows_camera() {
+        let origin = Vec3::new(10.0, 20.0, -1.0);
+        let camera = Vec3::new(100.0, 50.0, 5.0);
+        assert_eq!(
+            parallax_translation(origin, camera, Vec2::new(0.5, 0.0), None),
+            Vec3::new(60.0, 20.0, -1.0)
+        );
+        assert_eq!(
+            parallax_translation(origin, camera, Vec2::zero(), None),
+            origin
+        );
+    }
+
+    #[test]
+    fn tiled_parallax_translation_wraps_around_camera() {
+        let origin = Vec3::new(0.0, 0.0, 0.0);
+        let camera = Vec3::new(1000.0, 0.0, 0.0);
+        let translation = parallax_translation(origin, camera, Vec2::new(0.5, 0.0), Some(300.0));
+        // 500 units behind the camera, wrapped forward by two tiles
+        assert_eq!(translation, Vec3::new(1100.0, 0.0, 0.0));
+        assert!((translation.x() - camera.x()).abs() <= 150.0);
+    }
+}
@@ -0,0 +1,20 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 1, binding = 0) uniform ColorblindFilterMaterial_matrix {
+    mat4 Matrix;
+};
+layout(set = 1, binding = 1) uniform texture2D ColorblindFilterMaterial_texture;
+layout(set = 1, binding = 2) uniform sampler ColorblindFilterMaterial_texture_sampler;
+
+void main() {
+    vec4 color = texture(
+        sampler2D(ColorblindFilterMaterial_texture, ColorblindFilterMaterial_texture_sampler),
+        v_Uv);
+    // the texture is sampled as linear color, which the matrix expects
+    vec3 filtered = (Matrix * vec4(color.rgb, 0.0)).rgb;
+    o_Target = vec4(clamp(filtered, 0.0, 1.0), color.a);
+}
@@ -0,0 +1,76 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+// rgb: the near layer premultiplied by its coverage, a: its coverage
+layout(location = 0) out vec4 o_Near;
+// rgb: the far layer, a: the radius the near layer reaches this pixel with
+layout(location = 1) out vec4 o_Far;
+
+layout(set = 1, binding = 0) uniform DofMaterial_inverse_projection {
+    mat4 InverseProjection;
+};
+// x: the focus distance, y: the blur radius at infinity, z: the largest blur radius
+layout(set = 1, binding = 1) uniform DofMaterial_params {
+    vec4 Params;
+};
+layout(set = 1, binding = 2) uniform texture2D DofMaterial_color;
+layout(set = 1, binding = 3) uniform sampler DofMaterial_color_sampler;
+layout(set = 1, binding = 4) uniform texture2D DofMaterial_depth;
+layout(set = 1, binding = 5) uniform sampler DofMaterial_depth_sampler;
+
+// the number of samples on each side of the pixel
+const int TAPS = 8;
+
+// the signed radius of the circle of confusion in pixels, negative in front of the focus distance
+float circle_of_confusion(vec2 uv) {
+    float depth = texture(sampler2D(DofMaterial_depth, DofMaterial_depth_sampler), uv).r;
+    vec4 view = InverseProjection * vec4(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    float distance = max(-view.z / view.w, 0.0001);
+    return clamp(Params.y * (distance - Params.x) / distance, -Params.z, Params.z);
+}
+
+void main() {
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2D(DofMaterial_color, DofMaterial_color_sampler), 0));
+    float step_size = max(Params.z / float(TAPS), 1.0);
+
+    vec3 colors[2 * TAPS + 1];
+    float cocs[2 * TAPS + 1];
+    float near_radius = 0.0;
+    for (int i = 0; i <= 2 * TAPS; ++i) {
+        float offset = float(i - TAPS) * step_size;
+        vec2 uv = v_Uv + vec2(offset * texel_size.x, 0.0);
+        colors[i] = texture(sampler2D(DofMaterial_color, DofMaterial_color_sampler), uv).rgb;
+        cocs[i] = circle_of_confusion(uv);
+        // a sample reaches this pixel if its circle of confusion covers the distance to it
+        if (-cocs[i] >= max(abs(offset), 0.5)) {
+            near_radius = max(near_radius, -cocs[i]);
+        }
+    }
+    float center_coc = cocs[TAPS];
+
+    vec3 near = vec3(0.0);
+    float near_coverage = 0.0;
+    float near_samples = 0.0;
+    vec3 far = vec3(0.0);
+    float far_weight = 0.0;
+    for (int i = 0; i <= 2 * TAPS; ++i) {
+        float reach = max(abs(float(i - TAPS) * step_size), 0.5);
+        // the near layer is spread over everything behind it, and is only partially covered towards its edges
+        if (reach <= near_radius) {
+            near_samples += 1.0;
+            if (-cocs[i] >= reach) {
+                near += colors[i];
+                near_coverage += 1.0;
+            }
+        }
+        // the far layer doesn't bleed over the sharper pixels in front of it
+        if (min(cocs[i], center_coc) >= reach) {
+            far += colors[i];
+            far_weight += 1.0;
+        }
+    }
+
+    o_Near = near_samples > 0.0 ? vec4(near, near_coverage) / near_samples : vec4(0.0);
+    o_Far = vec4(far_weight > 0.0 ? far / far_weight : colors[TAPS], near_radius);
+}
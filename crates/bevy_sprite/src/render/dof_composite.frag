@@ -0,0 +1,85 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 1, binding = 0) uniform DofMaterial_inverse_projection {
+    mat4 InverseProjection;
+};
+// x: the focus distance, y: the blur radius at infinity, z: the largest blur radius
+layout(set = 1, binding = 1) uniform DofMaterial_params {
+    vec4 Params;
+};
+layout(set = 1, binding = 2) uniform texture2D DofMaterial_color;
+layout(set = 1, binding = 3) uniform sampler DofMaterial_color_sampler;
+layout(set = 1, binding = 4) uniform texture2D DofMaterial_depth;
+layout(set = 1, binding = 5) uniform sampler DofMaterial_depth_sampler;
+// rgb: the horizontally blurred near layer premultiplied by its coverage, a: its coverage
+layout(set = 1, binding = 6) uniform texture2D DofMaterial_near;
+layout(set = 1, binding = 7) uniform sampler DofMaterial_near_sampler;
+// rgb: the horizontally blurred far layer, a: the radius the near layer reaches the pixel with
+layout(set = 1, binding = 8) uniform texture2D DofMaterial_far;
+layout(set = 1, binding = 9) uniform sampler DofMaterial_far_sampler;
+
+// the number of samples on each side of the pixel
+const int TAPS = 8;
+
+// the signed radius of the circle of confusion in pixels, negative in front of the focus distance
+float circle_of_confusion(vec2 uv) {
+    float depth = texture(sampler2D(DofMaterial_depth, DofMaterial_depth_sampler), uv).r;
+    vec4 view = InverseProjection * vec4(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    float distance = max(-view.z / view.w, 0.0001);
+    return clamp(Params.y * (distance - Params.x) / distance, -Params.z, Params.z);
+}
+
+void main() {
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2D(DofMaterial_color, DofMaterial_color_sampler), 0));
+    float step_size = max(Params.z / float(TAPS), 1.0);
+    vec3 sharp = texture(sampler2D(DofMaterial_color, DofMaterial_color_sampler), v_Uv).rgb;
+    float center_coc = circle_of_confusion(v_Uv);
+
+    // the same gather as the blur pass, vertically and over the horizontally blurred layers
+    vec4 near_samples[2 * TAPS + 1];
+    float near_radii[2 * TAPS + 1];
+    float near_radius = 0.0;
+    vec3 far = vec3(0.0);
+    float far_weight = 0.0;
+    for (int i = 0; i <= 2 * TAPS; ++i) {
+        float reach = max(abs(float(i - TAPS) * step_size), 0.5);
+        vec2 uv = v_Uv + vec2(0.0, float(i - TAPS) * step_size * texel_size.y);
+        vec4 far_sample = texture(sampler2D(DofMaterial_far, DofMaterial_far_sampler), uv);
+        near_samples[i] = texture(sampler2D(DofMaterial_near, DofMaterial_near_sampler), uv);
+        near_radii[i] = far_sample.a;
+        if (near_radii[i] >= reach) {
+            near_radius = max(near_radius, near_radii[i]);
+        }
+        if (min(circle_of_confusion(uv), center_coc) >= reach) {
+            far += far_sample.rgb;
+            far_weight += 1.0;
+        }
+    }
+
+    vec4 near = vec4(0.0);
+    float near_count = 0.0;
+    for (int i = 0; i <= 2 * TAPS; ++i) {
+        float reach = max(abs(float(i - TAPS) * step_size), 0.5);
+        if (reach <= near_radius) {
+            near_count += 1.0;
+            if (near_radii[i] >= reach) {
+                near += near_samples[i];
+            }
+        }
+    }
+
+    // fades from the sharp scene to the blurred layers over the first pixel of blur
+    vec3 color = sharp;
+    if (far_weight > 0.0) {
+        color = mix(sharp, far / far_weight, clamp(center_coc - 0.5, 0.0, 1.0));
+    }
+    if (near_count > 0.0) {
+        near /= near_count;
+        color = color * (1.0 - near.a) + near.rgb;
+    }
+    o_Target = vec4(color, 1.0);
+}
@@ -0,0 +1,58 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 1, binding = 0) uniform MotionBlurMaterial_inverse_view_projection {
+    mat4 InverseViewProjection;
+};
+layout(set = 1, binding = 1) uniform MotionBlurMaterial_previous_view_projection {
+    mat4 PreviousViewProjection;
+};
+// x: the fraction of the motion that is blurred, y: the number of samples, z: the longest blur in pixels
+layout(set = 1, binding = 2) uniform MotionBlurMaterial_params {
+    vec4 Params;
+};
+layout(set = 1, binding = 3) uniform texture2D MotionBlurMaterial_color;
+layout(set = 1, binding = 4) uniform sampler MotionBlurMaterial_color_sampler;
+layout(set = 1, binding = 5) uniform texture2D MotionBlurMaterial_depth;
+layout(set = 1, binding = 6) uniform sampler MotionBlurMaterial_depth_sampler;
+layout(set = 1, binding = 7) uniform texture2D MotionBlurMaterial_velocity;
+layout(set = 1, binding = 8) uniform sampler MotionBlurMaterial_velocity_sampler;
+
+void main() {
+    vec2 size = vec2(textureSize(sampler2D(MotionBlurMaterial_color, MotionBlurMaterial_color_sampler), 0));
+    vec4 color = texture(sampler2D(MotionBlurMaterial_color, MotionBlurMaterial_color_sampler), v_Uv);
+
+    vec4 velocity = texture(sampler2D(MotionBlurMaterial_velocity, MotionBlurMaterial_velocity_sampler), v_Uv);
+    vec2 motion = velocity.xy;
+    if (velocity.a == 0.0) {
+        // no mesh was drawn here, so only the camera moved. finds where the pixel was in the previous frame.
+        float depth = texture(sampler2D(MotionBlurMaterial_depth, MotionBlurMaterial_depth_sampler), v_Uv).r;
+        vec2 ndc = vec2(v_Uv.x * 2.0 - 1.0, 1.0 - v_Uv.y * 2.0);
+        vec4 world = InverseViewProjection * vec4(ndc, depth, 1.0);
+        vec4 previous = PreviousViewProjection * vec4(world.xyz / world.w, 1.0);
+        vec2 previous_uv = vec2(previous.x / previous.w * 0.5 + 0.5, 0.5 - previous.y / previous.w * 0.5);
+        motion = previous.w > 0.0 ? v_Uv - previous_uv : vec2(0.0);
+    }
+
+    // blurs shorter than a pixel aren't visible
+    vec2 blur = motion * size * Params.x;
+    float blur_length = length(blur);
+    if (blur_length < 1.0) {
+        o_Target = color;
+        return;
+    }
+    blur *= min(blur_length, Params.z) / blur_length;
+
+    // the samples are spread evenly over the blur, centered on the pixel
+    int samples = int(Params.y);
+    vec2 step = blur / size;
+    vec3 sum = vec3(0.0);
+    for (int i = 0; i < samples; ++i) {
+        float offset = (float(i) + 0.5) / float(samples) - 0.5;
+        sum += texture(sampler2D(MotionBlurMaterial_color, MotionBlurMaterial_color_sampler), v_Uv + step * offset).rgb;
+    }
+    o_Target = vec4(sum / float(samples), color.a);
+}
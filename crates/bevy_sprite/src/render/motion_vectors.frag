@@ -0,0 +1,15 @@
+#version 450
+
+layout(location = 0) in vec4 v_Position;
+layout(location = 1) in vec4 v_PreviousPosition;
+
+// xy: the motion since the previous frame in texture coordinates, a: 1.0 where a mesh was drawn
+layout(location = 0) out vec4 o_Target;
+
+void main() {
+    vec2 ndc = v_Position.xy / v_Position.w;
+    vec2 previous_ndc = v_PreviousPosition.xy / v_PreviousPosition.w;
+    // texture coordinates go down, normalized device coordinates go up
+    vec2 motion = (ndc - previous_ndc) * vec2(0.5, -0.5);
+    o_Target = vec4(motion, 0.0, 1.0);
+}
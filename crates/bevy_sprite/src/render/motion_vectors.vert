@@ -0,0 +1,30 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec3 Vertex_Normal;
+layout(location = 2) in vec2 Vertex_Uv;
+
+layout(location = 0) out vec4 v_Position;
+layout(location = 1) out vec4 v_PreviousPosition;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+};
+
+layout(set = 2, binding = 0) uniform Transform {
+    mat4 Model;
+};
+layout(set = 2, binding = 1) uniform MotionVectors_model_view_projection {
+    mat4 ModelViewProjection;
+};
+layout(set = 2, binding = 2) uniform MotionVectors_previous_model_view_projection {
+    mat4 PreviousModelViewProjection;
+};
+
+void main() {
+    v_Position = ModelViewProjection * vec4(Vertex_Position, 1.0);
+    v_PreviousPosition = PreviousModelViewProjection * vec4(Vertex_Position, 1.0);
+    // the same transform as the forward pipeline, so the depths match what the main pass rendered
+    vec3 world_position = (Model * vec4(Vertex_Position, 1.0)).xyz;
+    gl_Position = ViewProj * vec4(world_position, 1.0);
+}
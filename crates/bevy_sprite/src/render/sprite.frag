@@ -1,6 +1,10 @@
 #version 450
 
 layout(location = 0) in vec2 v_Uv;
+# ifdef MESH_VERTEX_COLOR
+layout(location = 1) in vec4 v_Color;
+# endif
+layout(location = 2) in vec2 v_Size;
 
 layout(location = 0) out vec4 o_Target;
 
@@ -13,12 +17,51 @@ layout(set = 1, binding = 1) uniform texture2D ColorMaterial_texture;
 layout(set = 1, binding = 2) uniform sampler ColorMaterial_texture_sampler;
 # endif
 
+layout(set = 2, binding = 2) uniform Sprite_image_mode {
+    vec4 SliceBorder; // left, right, top, bottom
+    uint ImageMode;
+};
+
+const uint IMAGE_MODE_TILE = 1;
+const uint IMAGE_MODE_SLICED = 2;
+
+// maps a position along one axis of the sprite to the texture, keeping the borders at their texture size
+float slice(float position, float size, float texture_size, float border_start, float border_end) {
+    // shrink the borders if they don't fit in the sprite
+    float scale = min(1.0, size / max(border_start + border_end, 0.0001));
+    float start = border_start * scale;
+    float end = border_end * scale;
+    if (position < start) {
+        return position / scale;
+    } else if (position > size - end) {
+        return texture_size - (size - position) / scale;
+    }
+    float center_size = max(size - start - end, 0.0001);
+    float texture_center_size = texture_size - border_start - border_end;
+    return border_start + (position - start) / center_size * texture_center_size;
+}
+
 void main() {
     vec4 color = Color;
+# ifdef MESH_VERTEX_COLOR
+    color *= v_Color;
+# endif
 # ifdef COLORMATERIAL_TEXTURE
+    vec2 uv = v_Uv;
+    vec2 texture_size = vec2(textureSize(
+        sampler2D(ColorMaterial_texture, ColorMaterial_texture_sampler), 0));
+    if (ImageMode == IMAGE_MODE_TILE) {
+        uv = fract(v_Uv * v_Size / texture_size);
+    } else if (ImageMode == IMAGE_MODE_SLICED) {
+        vec2 position = v_Uv * v_Size;
+        uv = vec2(
+            slice(position.x, v_Size.x, texture_size.x, SliceBorder.x, SliceBorder.y),
+            slice(position.y, v_Size.y, texture_size.y, SliceBorder.z, SliceBorder.w)
+        ) / texture_size;
+    }
     color *= texture(
         sampler2D(ColorMaterial_texture, ColorMaterial_texture_sampler),
-        v_Uv);
+        uv);
 # endif
     o_Target = color;
 }
@@ -0,0 +1,38 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec3 Vertex_Normal;
+layout(location = 2) in vec2 Vertex_Uv;
+# ifdef MESH_VERTEX_COLOR
+layout(location = 3) in vec4 Vertex_Color;
+# endif
+
+// the inputs of sprite material fragment shaders
+layout(location = 0) out vec2 v_Uv;
+layout(location = 1) out vec4 v_Color;
+layout(location = 2) out vec2 v_Size;
+layout(location = 3) out vec2 v_SpriteUv;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+};
+
+layout(set = 2, binding = 0) uniform Transform {
+    mat4 Model;
+};
+layout(set = 2, binding = 1) uniform Sprite_size {
+    vec2 size;
+};
+
+void main() {
+    v_Uv = Vertex_Uv;
+    v_SpriteUv = Vertex_Uv;
+    v_Size = size;
+# ifdef MESH_VERTEX_COLOR
+    v_Color = Vertex_Color;
+# else
+    v_Color = vec4(1.0);
+# endif
+    vec3 position = Vertex_Position * vec3(size, 1.0);
+    gl_Position = ViewProj * Model * vec4(position, 1.0);
+}
@@ -0,0 +1,55 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec3 Vertex_Normal;
+layout(location = 2) in vec2 Vertex_Uv;
+
+// the inputs of sprite material fragment shaders
+layout(location = 0) out vec2 v_Uv;
+layout(location = 1) out vec4 v_Color;
+layout(location = 2) out vec2 v_Size;
+layout(location = 3) out vec2 v_SpriteUv;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+};
+
+// the texture atlas is in set 3, so set 1 is left to the material like it is for single sprites
+layout(set = 3, binding = 0) uniform TextureAtlas_size {
+    vec2 AtlasSize;
+};
+
+struct Rect {
+    vec2 begin;
+    vec2 end;
+};
+
+layout(set = 3, binding = 1) buffer TextureAtlas_textures {
+    Rect[] Textures;
+};
+
+layout(set = 2, binding = 0) uniform Transform {
+    mat4 SpriteTransform;
+};
+
+layout(set = 2, binding = 1) uniform TextureAtlasSprite {
+    vec4 TextureAtlasSprite_color;
+    uint TextureAtlasSprite_index;
+};
+
+void main() {
+    Rect sprite_rect = Textures[TextureAtlasSprite_index];
+    vec2 sprite_dimensions = sprite_rect.end - sprite_rect.begin;
+    vec3 vertex_position = vec3(Vertex_Position.xy * sprite_dimensions, 0.0);
+    vec2 atlas_positions[4] = vec2[](
+        vec2(sprite_rect.begin.x, sprite_rect.end.y),
+        sprite_rect.begin,
+        vec2(sprite_rect.end.x, sprite_rect.begin.y),
+        sprite_rect.end
+    );
+    v_Uv = (atlas_positions[gl_VertexIndex] + vec2(0.01, 0.01)) / AtlasSize;
+    v_SpriteUv = Vertex_Uv;
+    v_Size = sprite_dimensions;
+    v_Color = TextureAtlasSprite_color;
+    gl_Position = ViewProj * SpriteTransform * vec4(ceil(vertex_position), 1.0);
+}
@@ -0,0 +1,74 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+layout(location = 1) out vec4 o_History;
+
+layout(set = 1, binding = 0) uniform TaaMaterial_inverse_view_projection {
+    mat4 InverseViewProjection;
+};
+layout(set = 1, binding = 1) uniform TaaMaterial_previous_view_projection {
+    mat4 PreviousViewProjection;
+};
+// xy: the jitter of this frame in normalized device coordinates, z: the weight of the history
+layout(set = 1, binding = 2) uniform TaaMaterial_params {
+    vec4 Params;
+};
+layout(set = 1, binding = 3) uniform texture2D TaaMaterial_color;
+layout(set = 1, binding = 4) uniform sampler TaaMaterial_color_sampler;
+layout(set = 1, binding = 5) uniform texture2D TaaMaterial_depth;
+layout(set = 1, binding = 6) uniform sampler TaaMaterial_depth_sampler;
+layout(set = 1, binding = 7) uniform texture2D TaaMaterial_history;
+layout(set = 1, binding = 8) uniform sampler TaaMaterial_history_sampler;
+// xy: the motion of the meshes since the previous frame in texture coordinates, a: 1.0 where a mesh was drawn
+layout(set = 1, binding = 9) uniform texture2D TaaMaterial_velocity;
+layout(set = 1, binding = 10) uniform sampler TaaMaterial_velocity_sampler;
+
+void main() {
+    vec2 texel_size = 1.0 / vec2(textureSize(sampler2D(TaaMaterial_color, TaaMaterial_color_sampler), 0));
+    vec3 current = texture(sampler2D(TaaMaterial_color, TaaMaterial_color_sampler), v_Uv).rgb;
+
+    // the history is clamped to the range of colors around the pixel, so colors that aren't there anymore fade out
+    // right away instead of leaving trails
+    vec3 neighborhood_min = current;
+    vec3 neighborhood_max = current;
+    for (int x = -1; x <= 1; ++x) {
+        for (int y = -1; y <= 1; ++y) {
+            vec3 neighbor = texture(
+                sampler2D(TaaMaterial_color, TaaMaterial_color_sampler),
+                v_Uv + vec2(x, y) * texel_size).rgb;
+            neighborhood_min = min(neighborhood_min, neighbor);
+            neighborhood_max = max(neighborhood_max, neighbor);
+        }
+    }
+
+    // finds where the surface seen through the pixel was in the previous frame, without this frame's jitter
+    vec2 ndc = vec2(v_Uv.x * 2.0 - 1.0, 1.0 - v_Uv.y * 2.0) - Params.xy;
+    vec2 history_uv;
+    bool behind_camera = false;
+    vec4 velocity = texture(sampler2D(TaaMaterial_velocity, TaaMaterial_velocity_sampler), v_Uv);
+    if (velocity.a > 0.0) {
+        // meshes follow their motion vectors, which include their own motion as well as the camera's
+        history_uv = vec2(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5) - velocity.xy;
+    } else {
+        // everything else only moved with the camera
+        float depth = texture(sampler2D(TaaMaterial_depth, TaaMaterial_depth_sampler), v_Uv).r;
+        vec4 world = InverseViewProjection * vec4(ndc, depth, 1.0);
+        vec4 previous = PreviousViewProjection * vec4(world.xyz / world.w, 1.0);
+        vec2 previous_ndc = previous.xy / previous.w;
+        history_uv = vec2(previous_ndc.x * 0.5 + 0.5, 0.5 - previous_ndc.y * 0.5);
+        behind_camera = previous.w <= 0.0;
+    }
+
+    float history_weight = Params.z;
+    if (behind_camera || any(lessThan(history_uv, vec2(0.0))) || any(greaterThan(history_uv, vec2(1.0)))) {
+        // the surface was off screen
+        history_weight = 0.0;
+    }
+    vec3 history = texture(sampler2D(TaaMaterial_history, TaaMaterial_history_sampler), history_uv).rgb;
+    history = clamp(history, neighborhood_min, neighborhood_max);
+
+    o_Target = vec4(mix(current, history, history_weight), 1.0);
+    o_History = o_Target;
+}
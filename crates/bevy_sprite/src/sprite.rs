@@ -1,8 +1,12 @@
 use crate::ColorMaterial;
 use bevy_asset::{Assets, Handle};
+use bevy_core::Bytes;
 use bevy_ecs::{Query, Res};
-use bevy_math::Vec2;
-use bevy_render::{renderer::RenderResources, texture::Texture};
+use bevy_math::{Vec2, Vec4};
+use bevy_render::{
+    renderer::{RenderResource, RenderResources},
+    texture::Texture,
+};
 use bevy_type_registry::TypeUuid;
 
 #[derive(Debug, Default, RenderResources, TypeUuid)]
@@ -11,6 +15,7 @@ pub struct Sprite {
     pub size: Vec2,
     #[render_resources(ignore)]
     pub resize_mode: SpriteResizeMode,
+    pub image_mode: ImageMode,
 }
 
 /// Determines how `Sprite` resize should be handled
@@ -26,12 +31,92 @@ impl Default for SpriteResizeMode {
     }
 }
 
+/// Determines how the material's texture fills a `Sprite` whose size differs from the texture size
+#[derive(Debug, Clone, Copy, PartialEq, RenderResource)]
+pub enum ImageMode {
+    /// Stretches the texture over the whole sprite
+    Stretch,
+    /// Repeats the texture at its original size, starting at the top left corner
+    Tile,
+    /// Keeps the border of the texture at its original size and stretches the center, which is useful for panels,
+    /// signs and platforms that are resized in the world
+    Sliced(SliceBorder),
+}
+
+impl Default for ImageMode {
+    fn default() -> Self {
+        ImageMode::Stretch
+    }
+}
+
+impl ImageMode {
+    fn index(&self) -> u32 {
+        match self {
+            ImageMode::Stretch => 0,
+            ImageMode::Tile => 1,
+            ImageMode::Sliced(_) => 2,
+        }
+    }
+}
+
+// matches the `Sprite_image_mode` uniform in sprite.frag
+impl Bytes for ImageMode {
+    fn write_bytes(&self, buffer: &mut [u8]) {
+        let border = match self {
+            ImageMode::Sliced(border) => {
+                Vec4::new(border.left, border.right, border.top, border.bottom)
+            }
+            _ => Vec4::zero(),
+        };
+        border.write_bytes(buffer);
+        self.index().write_bytes(&mut buffer[border.byte_len()..]);
+    }
+
+    fn byte_len(&self) -> usize {
+        Vec4::zero().byte_len() + self.index().byte_len()
+    }
+}
+
+/// The width of each edge of a sliced texture, in texture pixels
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SliceBorder {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl SliceBorder {
+    pub fn new(left: f32, right: f32, top: f32, bottom: f32) -> Self {
+        SliceBorder {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    /// A border with the same width on every edge
+    pub fn all(width: f32) -> Self {
+        SliceBorder::new(width, width, width, width)
+    }
+}
+
 impl Sprite {
     /// Creates new `Sprite` with `SpriteResizeMode::Manual` value for `resize_mode`
     pub fn new(size: Vec2) -> Self {
         Self {
             size,
             resize_mode: SpriteResizeMode::Manual,
+            image_mode: ImageMode::Stretch,
+        }
+    }
+
+    /// Creates new `Sprite` with `SpriteResizeMode::Manual` that fills `size` with its texture using `image_mode`
+    pub fn with_image_mode(size: Vec2, image_mode: ImageMode) -> Self {
+        Self {
+            image_mode,
+            ..Sprite::new(size)
         }
     }
 }
@@ -55,3 +140,22 @@ pub fn sprite_system(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_mode_bytes() {
+        let image_mode = ImageMode::Sliced(SliceBorder::new(1.0, 2.0, 3.0, 4.0));
+        let mut buffer = vec![0; image_mode.byte_len()];
+        image_mode.write_bytes(&mut buffer);
+
+        let mut expected = Vec::new();
+        for value in [1.0f32, 2.0, 3.0, 4.0].iter() {
+            expected.extend_from_slice(&value.to_ne_bytes());
+        }
+        expected.extend_from_slice(&2u32.to_ne_bytes());
+        assert_eq!(buffer, expected);
+    }
+}
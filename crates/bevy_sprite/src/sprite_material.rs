@@ -0,0 +1,197 @@
+use crate::render::sprite_pipeline_descriptor;
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_ecs::IntoQuerySystem;
+use bevy_render::{
+    pipeline::{DynamicBinding, PipelineDescriptor, PipelineSpecialization, RenderPipeline},
+    render_graph::{base, AssetRenderResourcesNode, RenderGraph},
+    renderer::RenderResources,
+    shader::{asset_shader_defs_system, Shader, ShaderDefs, ShaderStage, ShaderStages},
+};
+use bevy_type_registry::TypeUuid;
+use std::{borrow::Cow, marker::PhantomData};
+
+/// Draws sprites with a custom material `M` and fragment shader, for effects like dissolves, outlines and palette swaps.
+///
+/// The plugin builds variants of the sprite pipeline and the sprite sheet pipeline with the given fragment shader. The
+/// sprites are still positioned by the engine, which passes these inputs to the fragment shader:
+///
+/// ```glsl
+/// // where the sprite is on its texture, which is its region of the atlas for sprite sheets
+/// layout(location = 0) in vec2 v_Uv;
+/// // the color of a TextureAtlasSprite, the vertex color of a mesh with colors, or white
+/// layout(location = 1) in vec4 v_Color;
+/// // the size of the sprite in pixels
+/// layout(location = 2) in vec2 v_Size;
+/// // from (0, 0) at the top left of the sprite to (1, 1) at its bottom right
+/// layout(location = 3) in vec2 v_SpriteUv;
+/// ```
+///
+/// The render resources of the material are bound in set 1, like those of [ColorMaterial](crate::ColorMaterial), and
+/// its shader defs are set. In the sprite sheet variant `SPRITE_SHEET` is defined, and the atlas texture is bound as
+/// `TextureAtlas_texture` and `TextureAtlas_texture_sampler` in set 3, bindings 2 and 3.
+///
+/// Spawn the sprites with [MaterialSpriteComponents](crate::entity::MaterialSpriteComponents) or
+/// [MaterialSpriteSheetComponents](crate::entity::MaterialSpriteSheetComponents) and a `Handle<M>`.
+pub struct SpriteMaterialPlugin<M> {
+    fragment_shader: Cow<'static, str>,
+    marker: PhantomData<fn() -> M>,
+}
+
+impl<M> SpriteMaterialPlugin<M> {
+    /// `fragment_shader` is the GLSL source of the fragment shader
+    pub fn new(fragment_shader: impl Into<Cow<'static, str>>) -> Self {
+        SpriteMaterialPlugin {
+            fragment_shader: fragment_shader.into(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<M> Plugin for SpriteMaterialPlugin<M>
+where
+    M: RenderResources + ShaderDefs + TypeUuid + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<M>()
+            .add_system_to_stage(stage::POST_UPDATE, asset_shader_defs_system::<M>.system());
+
+        let resources = app.resources();
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
+        pipelines.set_untracked(
+            sprite_material_pipeline_handle::<M>(),
+            sprite_pipeline_descriptor(ShaderStages {
+                vertex: shaders.add(Shader::from_glsl(
+                    ShaderStage::Vertex,
+                    include_str!("render/sprite_material.vert"),
+                )),
+                fragment: Some(shaders.add(Shader::from_glsl(
+                    ShaderStage::Fragment,
+                    &self.fragment_shader,
+                ))),
+            }),
+        );
+        pipelines.set_untracked(
+            sprite_sheet_material_pipeline_handle::<M>(),
+            sprite_pipeline_descriptor(ShaderStages {
+                vertex: shaders.add(Shader::from_glsl(
+                    ShaderStage::Vertex,
+                    include_str!("render/sprite_sheet_material.vert"),
+                )),
+                fragment: Some(shaders.add(Shader::from_glsl(
+                    ShaderStage::Fragment,
+                    &sprite_sheet_fragment_shader(&self.fragment_shader),
+                ))),
+            }),
+        );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        let node = std::any::type_name::<M>();
+        render_graph.add_system_node(node, AssetRenderResourcesNode::<M>::new(false));
+        render_graph
+            .add_node_edge(node, base::node::MAIN_PASS)
+            .unwrap();
+    }
+}
+
+fn sprite_sheet_fragment_shader(fragment_shader: &str) -> String {
+    fragment_shader.replacen("#version 450", "#version 450\n#define SPRITE_SHEET", 1)
+}
+
+/// The pipeline of single sprites drawn with the material `M`
+pub fn sprite_material_pipeline_handle<M: TypeUuid>() -> Handle<PipelineDescriptor> {
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, M::TYPE_UUID.as_u128() as u64)
+}
+
+/// The pipeline of sprite sheet sprites drawn with the material `M`
+pub fn sprite_sheet_material_pipeline_handle<M: TypeUuid>() -> Handle<PipelineDescriptor> {
+    Handle::weak_from_u64(
+        PipelineDescriptor::TYPE_UUID,
+        (M::TYPE_UUID.as_u128() >> 64) as u64,
+    )
+}
+
+/// The sprite pipeline of the material `M`, with the sprite's uniforms bound dynamically
+pub fn sprite_material_pipeline<M: TypeUuid>() -> RenderPipeline {
+    RenderPipeline::specialized(
+        sprite_material_pipeline_handle::<M>(),
+        PipelineSpecialization {
+            dynamic_bindings: vec![
+                // Transform
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 0,
+                },
+                // Sprite_size
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 1,
+                },
+            ],
+            ..Default::default()
+        },
+    )
+}
+
+/// The sprite sheet pipeline of the material `M`, with the sprite's uniforms bound dynamically
+pub fn sprite_sheet_material_pipeline<M: TypeUuid>() -> RenderPipeline {
+    RenderPipeline::specialized(
+        sprite_sheet_material_pipeline_handle::<M>(),
+        PipelineSpecialization {
+            dynamic_bindings: vec![
+                // Transform
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 0,
+                },
+                // TextureAtlasSprite
+                DynamicBinding {
+                    bind_group: 2,
+                    binding: 1,
+                },
+            ],
+            ..Default::default()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_type_registry::Uuid;
+
+    struct Dissolve;
+    impl TypeUuid for Dissolve {
+        const TYPE_UUID: Uuid = Uuid::from_u128(0x3c1e_5a9b_7d42_4e0f_9b86_21f4_c0d7_a513);
+    }
+
+    struct Outline;
+    impl TypeUuid for Outline {
+        const TYPE_UUID: Uuid = Uuid::from_u128(0x8f20_b6c4_1e93_47da_a5c8_6d0e_92b1_f347);
+    }
+
+    #[test]
+    fn pipelines_per_material() {
+        let handles = [
+            sprite_material_pipeline_handle::<Dissolve>(),
+            sprite_sheet_material_pipeline_handle::<Dissolve>(),
+            sprite_material_pipeline_handle::<Outline>(),
+            sprite_sheet_material_pipeline_handle::<Outline>(),
+        ];
+        for (i, a) in handles.iter().enumerate() {
+            for b in handles[i + 1..].iter() {
+                assert_ne!(a, b);
+            }
+        }
+        assert_eq!(
+            sprite_material_pipeline_handle::<Dissolve>(),
+            sprite_material_pipeline_handle::<Dissolve>()
+        );
+
+        assert_eq!(
+            sprite_sheet_fragment_shader("#version 450\nvoid main() {}"),
+            "#version 450\n#define SPRITE_SHEET\nvoid main() {}"
+        );
+    }
+}
@@ -0,0 +1,549 @@
+//! Temporal anti-aliasing (TAA): the 3d camera's projection is moved by a different fraction of a pixel every frame,
+//! and a resolve pass blends each frame with the history of the previous frames.
+//!
+//! [TemporalAntiAliasingPlugin] points the main pass at a texture instead of the primary window, and renders its
+//! depth to a texture that can be sampled. Passes that draw over the scene, like the ui pass, still render to the
+//! window after the resolve pass, so they aren't jittered. The resolve pass reprojects the history with the motion
+//! vectors of the meshes, and with the depth and the camera's previous view projection where no mesh was drawn. It
+//! clamps the history to the colors around each pixel so it can't ghost where something was uncovered, and draws the
+//! result to the window and to the history of the next frame. Add the plugin after the other render plugins, so it
+//! can redirect their passes, but before [MotionBlurPlugin](crate::motion_blur::MotionBlurPlugin) and
+//! [ColorblindFilterPlugin](crate::colorblind_filter::ColorblindFilterPlugin).
+//!
+//! The motion vectors come from the velocity pass of [motion_vectors](crate::motion_vectors), which motion blur
+//! shares. The jitter of each frame is published as [ProjectionJitter], so the motion vectors don't include it.
+
+use crate::{
+    motion_vectors::{self, ProjectionJitter, MOTION_VECTORS_TEXTURE_HANDLE},
+    virtual_resolution::{order_before_slot_consumers, redirect_scene_slot_edges},
+    Sprite, SpriteResizeMode, QUAD_HANDLE,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_ecs::{Commands, IntoQuerySystem, Query, Res, ResMut, Resources, With, World};
+use bevy_math::{Mat4, Vec2, Vec4};
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    entity::Camera2dComponents,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassColorAttachmentDescriptor, TextureAttachment,
+    },
+    pipeline::{
+        BlendDescriptor, ColorStateDescriptor, ColorWrite, CullMode, DynamicBinding, FrontFace,
+        PipelineDescriptor, PipelineSpecialization, RasterizationStateDescriptor, RenderPipeline,
+        RenderPipelines,
+    },
+    prelude::{Color, Draw},
+    render_graph::{
+        base::{self, Msaa, SingleSampledFeatures},
+        AssetRenderResourcesNode, AssetTextureNode, CameraNode, Node, PassNode, RenderGraph,
+        ResourceSlotInfo, ResourceSlots, WindowSwapChainNode,
+    },
+    renderer::{RenderContext, RenderResourceType, RenderResources},
+    shader::{Shader, ShaderStage, ShaderStages},
+    texture::{FilterMode, Texture, TextureFormat, TEXTURE_ASSET_INDEX},
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+use bevy_window::Windows;
+use std::borrow::Cow;
+
+/// The texture the passes that render to the primary window render to instead
+pub const TAA_COLOR_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 5906213476601298841);
+
+/// The texture the main pass renders its depth to
+pub const TAA_DEPTH_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 17440151337702148103);
+
+/// The resolved frames. Each frame reads one and writes the other.
+pub const TAA_HISTORY_TEXTURE_HANDLES: [Handle<Texture>; 2] = [
+    Handle::weak_from_u64(Texture::TYPE_UUID, 3317625590142791860),
+    Handle::weak_from_u64(Texture::TYPE_UUID, 8830561472903718254),
+];
+
+pub const TAA_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 12876052331749603529);
+
+/// The material of the frames that write each history texture
+pub const TAA_MATERIAL_HANDLES: [Handle<TaaMaterial>; 2] = [
+    Handle::weak_from_u64(TaaMaterial::TYPE_UUID, 6210478263921450017),
+    Handle::weak_from_u64(TaaMaterial::TYPE_UUID, 14695981039346656037),
+];
+
+pub mod node {
+    pub const TAA_COLOR_TEXTURE: &str = "taa_color_texture";
+    pub const TAA_DEPTH_TEXTURE: &str = "taa_depth_texture";
+    pub const TAA_HISTORY_TEXTURE: &str = "taa_history_texture";
+    pub const TAA_MATERIAL: &str = "taa_material";
+    pub const TAA_CAMERA: &str = "taa_camera";
+    pub const TAA_RESOLVE_PASS: &str = "taa_resolve_pass";
+}
+
+pub mod camera {
+    pub const TAA_CAMERA: &str = "TaaCamera";
+}
+
+/// The number of different jitter offsets before the sequence repeats
+pub const TAA_JITTER_SAMPLES: u32 = 8;
+
+/// Settings of [TemporalAntiAliasingPlugin]
+#[derive(Debug)]
+pub struct TemporalAntiAliasing {
+    /// How much of the history is kept each frame, from 0.0 to 1.0. Higher values are smoother, lower values react
+    /// faster to changes.
+    pub history_weight: f32,
+    frame: u32,
+    unjittered_projection: Mat4,
+    jittered_projection: Mat4,
+    previous_view_projection: Option<Mat4>,
+}
+
+impl Default for TemporalAntiAliasing {
+    fn default() -> Self {
+        TemporalAntiAliasing {
+            history_weight: 0.9,
+            frame: 0,
+            unjittered_projection: Mat4::identity(),
+            jittered_projection: Mat4::identity(),
+            previous_view_projection: None,
+        }
+    }
+}
+
+impl TemporalAntiAliasing {
+    /// The history texture the resolve pass writes this frame
+    pub fn history_target(&self) -> usize {
+        (self.frame % 2) as usize
+    }
+}
+
+/// The offset of the projection in `frame`, in pixels from -0.5 to 0.5. The offsets follow the Halton (2, 3)
+/// sequence, which covers the pixel evenly.
+pub fn taa_jitter(frame: u32) -> Vec2 {
+    let index = frame % TAA_JITTER_SAMPLES + 1;
+    Vec2::new(halton(index, 2) - 0.5, halton(index, 3) - 0.5)
+}
+
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// Resolves the jittered frame with the history
+#[derive(Debug, RenderResources, TypeUuid)]
+#[uuid = "3f0c6f42-8d57-4c1b-b9a5-6a1e2f43d7c9"]
+pub struct TaaMaterial {
+    /// Turns positions in the unjittered frame back into world positions
+    pub inverse_view_projection: Mat4,
+    pub previous_view_projection: Mat4,
+    /// xy: the jitter of this frame in normalized device coordinates, z: the weight of the history
+    pub params: Vec4,
+    pub color: Handle<Texture>,
+    pub depth: Handle<Texture>,
+    pub history: Handle<Texture>,
+    /// The motion of the meshes since the previous frame, which moved on their own as well as with the camera
+    pub velocity: Handle<Texture>,
+}
+
+/// Marks the camera that draws the resolved frame
+#[derive(Debug, Default)]
+pub struct TaaCamera;
+
+/// Marks the sprite that draws the resolved frame
+#[derive(Debug, Default)]
+pub struct TaaResolveBlit;
+
+/// Outputs the history texture the resolve pass writes this frame
+#[derive(Debug, Default)]
+pub struct TaaHistoryNode;
+
+impl TaaHistoryNode {
+    pub const OUT_TEXTURE: &'static str = "texture";
+}
+
+static TAA_HISTORY_OUTPUT: &[ResourceSlotInfo] = &[ResourceSlotInfo {
+    name: Cow::Borrowed(TaaHistoryNode::OUT_TEXTURE),
+    resource_type: RenderResourceType::Texture,
+}];
+
+impl Node for TaaHistoryNode {
+    fn output(&self) -> &[ResourceSlotInfo] {
+        TAA_HISTORY_OUTPUT
+    }
+
+    fn update(
+        &mut self,
+        _world: &World,
+        resources: &Resources,
+        render_context: &mut dyn RenderContext,
+        _input: &ResourceSlots,
+        output: &mut ResourceSlots,
+    ) {
+        const TEXTURE: usize = 0;
+        let target = resources
+            .get::<TemporalAntiAliasing>()
+            .unwrap()
+            .history_target();
+        if let Some(texture) = render_context
+            .resources()
+            .get_asset_resource(&TAA_HISTORY_TEXTURE_HANDLES[target], TEXTURE_ASSET_INDEX)
+        {
+            output.set(TEXTURE, texture);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TemporalAntiAliasingPlugin;
+
+impl Plugin for TemporalAntiAliasingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // the depth is sampled by the resolve pass, which isn't possible with multisampled depth
+        if app.resources().get::<Msaa>().unwrap().samples > 1 {
+            log::warn!("temporal anti-aliasing is disabled because Msaa uses more than one sample");
+            return;
+        }
+        // keeps Msaa from changing to more samples later, for example with GraphicsQuality
+        app.resources()
+            .get_mut::<SingleSampledFeatures>()
+            .unwrap()
+            .add("temporal anti-aliasing");
+
+        app.add_asset::<TaaMaterial>()
+            .init_resource::<TemporalAntiAliasing>()
+            .add_startup_system(spawn_taa_resolve_blit.system())
+            // runs after the camera systems, so it jitters the projection they computed
+            .add_system_to_stage(stage::POST_UPDATE, taa_system.system());
+        // added after the jitter system, so the motion vectors see this frame's jitter
+        motion_vectors::add_motion_vectors(app);
+
+        let resources = app.resources();
+        resources
+            .get_mut::<ActiveCameras>()
+            .unwrap()
+            .add(camera::TAA_CAMERA);
+
+        // the textures are resized to the window before the first frame is rendered
+        let mut textures = resources.get_mut::<Assets<Texture>>().unwrap();
+        textures.set_untracked(
+            TAA_COLOR_TEXTURE_HANDLE,
+            Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::default()),
+        );
+        let mut depth =
+            Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::Depth32Float);
+        depth.sampler.min_filter = FilterMode::Nearest;
+        textures.set_untracked(TAA_DEPTH_TEXTURE_HANDLE, depth);
+        let mut materials = resources.get_mut::<Assets<TaaMaterial>>().unwrap();
+        for (target, history) in TAA_HISTORY_TEXTURE_HANDLES.iter().enumerate() {
+            let mut texture =
+                Texture::new_render_target(Vec2::new(1.0, 1.0), TextureFormat::default());
+            texture.sampler.mag_filter = FilterMode::Linear;
+            textures.set_untracked(history.clone_weak(), texture);
+            materials.set_untracked(
+                TAA_MATERIAL_HANDLES[target].clone_weak(),
+                TaaMaterial {
+                    inverse_view_projection: Mat4::identity(),
+                    previous_view_projection: Mat4::identity(),
+                    params: Vec4::zero(),
+                    color: TAA_COLOR_TEXTURE_HANDLE,
+                    depth: TAA_DEPTH_TEXTURE_HANDLE,
+                    history: TAA_HISTORY_TEXTURE_HANDLES[1 - target].clone_weak(),
+                    velocity: MOTION_VECTORS_TEXTURE_HANDLE,
+                },
+            );
+        }
+
+        let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(TAA_PIPELINE_HANDLE, build_taa_pipeline(&mut shaders));
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_taa_graph(&mut render_graph);
+    }
+}
+
+pub fn build_taa_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    let color_state = ColorStateDescriptor {
+        format: TextureFormat::default(),
+        color_blend: BlendDescriptor::REPLACE,
+        alpha_blend: BlendDescriptor::REPLACE,
+        write_mask: ColorWrite::ALL,
+    };
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        depth_stencil_state: None,
+        // the window and the history
+        color_states: vec![color_state.clone(), color_state],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("render/taa.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("render/taa.frag"),
+            ))),
+        })
+    }
+}
+
+fn spawn_taa_resolve_blit(mut commands: Commands) {
+    commands.spawn(Camera2dComponents {
+        camera: Camera {
+            name: Some(camera::TAA_CAMERA.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    commands.with(TaaCamera);
+
+    // the sprite isn't part of the main pass, so it is only drawn by the resolve pass
+    commands.spawn((
+        Sprite {
+            resize_mode: SpriteResizeMode::Manual,
+            ..Default::default()
+        },
+        QUAD_HANDLE,
+        TAA_MATERIAL_HANDLES[0].clone_weak(),
+        Draw::default(),
+        RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+            TAA_PIPELINE_HANDLE,
+            PipelineSpecialization {
+                dynamic_bindings: vec![
+                    // Transform
+                    DynamicBinding {
+                        bind_group: 2,
+                        binding: 0,
+                    },
+                    // Sprite_size
+                    DynamicBinding {
+                        bind_group: 2,
+                        binding: 1,
+                    },
+                ],
+                ..Default::default()
+            },
+        )]),
+        Transform::default(),
+        GlobalTransform::default(),
+        TaaResolveBlit,
+    ));
+}
+
+/// Jitters the 3d camera's projection, keeps the textures the size of the primary window and passes this frame's
+/// matrices to the resolve pass
+#[allow(clippy::too_many_arguments)]
+pub fn taa_system(
+    mut taa: ResMut<TemporalAntiAliasing>,
+    mut projection_jitter: ResMut<ProjectionJitter>,
+    active_cameras: Res<ActiveCameras>,
+    windows: Res<Windows>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut materials: ResMut<Assets<TaaMaterial>>,
+    mut cameras: Query<(&mut Camera, &GlobalTransform)>,
+    mut blits: Query<With<TaaResolveBlit, (&mut Sprite, &mut Handle<TaaMaterial>)>>,
+) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+
+    let window_size = Vec2::new(window.width().max(1) as f32, window.height().max(1) as f32);
+    let mut resized = false;
+    for handle in [
+        TAA_COLOR_TEXTURE_HANDLE,
+        TAA_DEPTH_TEXTURE_HANDLE,
+        TAA_HISTORY_TEXTURE_HANDLES[0].clone_weak(),
+        TAA_HISTORY_TEXTURE_HANDLES[1].clone_weak(),
+    ]
+    .iter()
+    {
+        let texture_size = textures.get(handle).map(|texture| texture.size);
+        if texture_size.map_or(false, |size| size != window_size) {
+            // the changed texture is recreated at the new size
+            textures.get_mut(handle).unwrap().size = window_size;
+            resized = true;
+        }
+    }
+    if resized {
+        // the recreated history is empty
+        taa.previous_view_projection = None;
+    }
+
+    let (mut camera, camera_transform) = if let Some(camera) = active_cameras
+        .get(base::camera::CAMERA3D)
+        .and_then(|entity| cameras.get_mut(entity).ok())
+    {
+        camera
+    } else {
+        return;
+    };
+
+    // the camera systems only recompute the projection when it needs to change
+    if camera.projection_matrix != taa.jittered_projection {
+        taa.unjittered_projection = camera.projection_matrix;
+    }
+    taa.frame = taa.frame.wrapping_add(1);
+    let jitter = taa_jitter(taa.frame) * 2.0 / window_size;
+    let jittered_projection =
+        Mat4::from_translation(jitter.extend(0.0)) * taa.unjittered_projection;
+    camera.projection_matrix = jittered_projection;
+    taa.jittered_projection = jittered_projection;
+    projection_jitter.offset = jitter;
+
+    let view_projection = taa.unjittered_projection * camera_transform.compute_matrix().inverse();
+    let (previous_view_projection, history_weight) = match taa.previous_view_projection {
+        Some(previous) => (previous, taa.history_weight.max(0.0).min(1.0)),
+        None => (view_projection, 0.0),
+    };
+    taa.previous_view_projection = Some(view_projection);
+
+    let target = taa.history_target();
+    if let Some(material) = materials.get_mut(&TAA_MATERIAL_HANDLES[target]) {
+        material.inverse_view_projection = view_projection.inverse();
+        material.previous_view_projection = previous_view_projection;
+        material.params = Vec4::new(jitter.x(), jitter.y(), history_weight, 0.0);
+    }
+
+    for (mut sprite, mut material) in blits.iter_mut() {
+        if sprite.size != window_size {
+            sprite.size = window_size;
+        }
+        if *material != TAA_MATERIAL_HANDLES[target] {
+            *material = TAA_MATERIAL_HANDLES[target].clone_weak();
+        }
+    }
+}
+
+fn add_taa_graph(graph: &mut RenderGraph) {
+    graph.add_node(
+        node::TAA_COLOR_TEXTURE,
+        AssetTextureNode::new(TAA_COLOR_TEXTURE_HANDLE),
+    );
+    graph.add_node(
+        node::TAA_DEPTH_TEXTURE,
+        AssetTextureNode::new(TAA_DEPTH_TEXTURE_HANDLE),
+    );
+    graph.add_node(node::TAA_HISTORY_TEXTURE, TaaHistoryNode);
+
+    // move the passes that render the scene over to the jittered texture, and their depth to a texture the resolve
+    // pass can sample. The passes that draw over the scene keep rendering to the window
+    let redirected_nodes = redirect_scene_slot_edges(
+        graph,
+        base::node::PRIMARY_SWAP_CHAIN,
+        node::TAA_COLOR_TEXTURE,
+    );
+    redirect_scene_slot_edges(
+        graph,
+        base::node::MAIN_DEPTH_TEXTURE,
+        node::TAA_DEPTH_TEXTURE,
+    );
+
+    let mut pass_node = PassNode::<&TaaResolveBlit>::new(PassDescriptor {
+        color_attachments: vec![
+            RenderPassColorAttachmentDescriptor {
+                attachment: TextureAttachment::Input("color_attachment".to_string()),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            },
+            RenderPassColorAttachmentDescriptor {
+                attachment: TextureAttachment::Input("history".to_string()),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            },
+        ],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    });
+    pass_node.add_camera(camera::TAA_CAMERA);
+    graph.add_node(node::TAA_RESOLVE_PASS, pass_node);
+
+    graph.add_system_node(node::TAA_CAMERA, CameraNode::new(camera::TAA_CAMERA));
+    graph.add_system_node(
+        node::TAA_MATERIAL,
+        AssetRenderResourcesNode::<TaaMaterial>::new(false),
+    );
+    motion_vectors::add_motion_vectors_graph(graph, node::TAA_DEPTH_TEXTURE);
+    for dependency in [
+        node::TAA_CAMERA,
+        node::TAA_MATERIAL,
+        motion_vectors::node::MOTION_VECTORS_PASS,
+        base::node::TEXTURE_COPY,
+        base::node::SHARED_BUFFERS,
+    ]
+    .iter()
+    {
+        graph
+            .add_node_edge(*dependency, node::TAA_RESOLVE_PASS)
+            .unwrap();
+    }
+    // the resolve pass samples what the redirected passes rendered
+    for redirected_node in redirected_nodes {
+        let _ = graph.add_node_edge(redirected_node, node::TAA_RESOLVE_PASS);
+    }
+
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::TAA_RESOLVE_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+    order_before_slot_consumers(
+        graph,
+        base::node::PRIMARY_SWAP_CHAIN,
+        node::TAA_RESOLVE_PASS,
+    );
+    graph
+        .add_slot_edge(
+            node::TAA_HISTORY_TEXTURE,
+            TaaHistoryNode::OUT_TEXTURE,
+            node::TAA_RESOLVE_PASS,
+            "history",
+        )
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn taa_jitter_sequence() {
+        let close = |a: Vec2, b: Vec2| (a - b).length() < 1e-6;
+        assert!(close(taa_jitter(0), Vec2::new(0.0, 1.0 / 3.0 - 0.5)));
+        assert!(close(taa_jitter(1), Vec2::new(-0.25, 2.0 / 3.0 - 0.5)));
+        assert_eq!(taa_jitter(TAA_JITTER_SAMPLES), taa_jitter(0));
+
+        let mut offsets = (0..TAA_JITTER_SAMPLES).map(taa_jitter).collect::<Vec<_>>();
+        offsets.dedup();
+        assert_eq!(offsets.len(), TAA_JITTER_SAMPLES as usize);
+        for offset in offsets {
+            assert!(offset.x().abs() <= 0.5 && offset.y().abs() <= 0.5);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{Rect, TextureAtlas};
+use bevy_asset::{Assets, Handle};
+use bevy_math::Vec2;
+use bevy_render::texture::{
+    Texture, TextureFormat, TextureRegion, TextureRegionError, TextureRegionWrites,
+};
+use bevy_utils::HashMap;
+use guillotiere::{size2, AllocId, AtlasAllocator};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TextureAtlasPackerError {
+    #[error("Texture is not loaded")]
+    NotLoaded,
+    #[error("Texture format {0:?} does not match the atlas format {1:?}")]
+    WrongFormat(TextureFormat, TextureFormat),
+    #[error("Not enough space left in the atlas for a {0}x{1} texture")]
+    NotEnoughSpace(u32, u32),
+    #[error("Failed to write texture to the atlas")]
+    Region(#[from] TextureRegionError),
+}
+
+#[derive(Debug)]
+struct PackedTexture {
+    allocation: AllocId,
+    rect: Rect,
+}
+
+/// Packs textures into a single atlas texture at runtime. Textures can be added and removed at any time, and only the
+/// added pixels are uploaded to the gpu. Use this for many small textures (icons, glyphs, decals) that would
+/// otherwise each need their own bind group.
+pub struct TextureAtlasPacker {
+    texture: Handle<Texture>,
+    size: Vec2,
+    format: TextureFormat,
+    padding: u32,
+    allocator: AtlasAllocator,
+    packed: HashMap<Handle<Texture>, PackedTexture>,
+    order: Vec<Handle<Texture>>,
+}
+
+impl TextureAtlasPacker {
+    /// Creates an empty, transparent atlas texture of the given size and format
+    pub fn new(
+        textures: &mut Assets<Texture>,
+        size: Vec2,
+        format: TextureFormat,
+        padding: u32,
+    ) -> Self {
+        let empty_pixel = vec![0; format.pixel_size()];
+        let texture = textures.add(Texture::new_fill(size, &empty_pixel, format));
+        Self {
+            texture,
+            size,
+            format,
+            padding,
+            allocator: AtlasAllocator::new(size2(size.x() as i32, size.y() as i32)),
+            packed: HashMap::default(),
+            order: Vec::new(),
+        }
+    }
+
+    /// The atlas texture that packed textures are written to
+    pub fn texture(&self) -> &Handle<Texture> {
+        &self.texture
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Packs `handle` into the atlas and returns its uv rect. Textures that are already packed are not packed again.
+    pub fn pack(
+        &mut self,
+        textures: &mut Assets<Texture>,
+        region_writes: &mut TextureRegionWrites,
+        handle: &Handle<Texture>,
+    ) -> Result<Rect, TextureAtlasPackerError> {
+        if let Some(uv_rect) = self.uv_rect(handle) {
+            return Ok(uv_rect);
+        }
+
+        let texture = textures
+            .get(handle)
+            .ok_or(TextureAtlasPackerError::NotLoaded)?;
+        if texture.format != self.format {
+            return Err(TextureAtlasPackerError::WrongFormat(
+                texture.format,
+                self.format,
+            ));
+        }
+
+        let width = texture.size.x() as u32;
+        let height = texture.size.y() as u32;
+        let allocation = self
+            .allocator
+            .allocate(size2(
+                (width + self.padding) as i32,
+                (height + self.padding) as i32,
+            ))
+            .ok_or(TextureAtlasPackerError::NotEnoughSpace(width, height))?;
+
+        let x = allocation.rectangle.min.x as u32;
+        let y = allocation.rectangle.min.y as u32;
+        let data = texture.data.clone();
+        if let Err(err) = region_writes.write(
+            textures,
+            &self.texture,
+            TextureRegion::new(x, y, width, height),
+            &data,
+        ) {
+            self.allocator.deallocate(allocation.id);
+            return Err(err.into());
+        }
+
+        let rect = Rect {
+            min: Vec2::new(x as f32, y as f32),
+            max: Vec2::new((x + width) as f32, (y + height) as f32),
+        };
+        self.packed.insert(
+            handle.clone_weak(),
+            PackedTexture {
+                allocation: allocation.id,
+                rect,
+            },
+        );
+        self.order.push(handle.clone_weak());
+        Ok(self.to_uv_rect(rect))
+    }
+
+    /// Frees the space `handle` takes up in the atlas. The freed pixels are overwritten by textures packed later.
+    pub fn remove(&mut self, handle: &Handle<Texture>) -> bool {
+        if let Some(packed) = self.packed.remove(handle) {
+            self.allocator.deallocate(packed.allocation);
+            self.order.retain(|packed_handle| packed_handle != handle);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The area `handle` takes up in the atlas, in pixels
+    pub fn rect(&self, handle: &Handle<Texture>) -> Option<Rect> {
+        self.packed.get(handle).map(|packed| packed.rect)
+    }
+
+    /// The area `handle` takes up in the atlas, in texture coordinates from 0 to 1
+    pub fn uv_rect(&self, handle: &Handle<Texture>) -> Option<Rect> {
+        self.rect(handle).map(|rect| self.to_uv_rect(rect))
+    }
+
+    pub fn len(&self) -> usize {
+        self.packed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packed.is_empty()
+    }
+
+    /// Creates a [TextureAtlas] for use with [TextureAtlasSprite](crate::TextureAtlasSprite). Textures are indexed in
+    /// the order they were packed in.
+    pub fn to_texture_atlas(&self) -> TextureAtlas {
+        let mut texture_atlas = TextureAtlas::new_empty(self.texture.clone(), self.size);
+        let mut texture_handles = HashMap::default();
+        for handle in self.order.iter() {
+            texture_handles.insert(handle.clone_weak(), texture_atlas.len());
+            texture_atlas.add_texture(self.packed[handle].rect);
+        }
+
+        texture_atlas.texture_handles = Some(texture_handles);
+        texture_atlas
+    }
+
+    fn to_uv_rect(&self, rect: Rect) -> Rect {
+        Rect {
+            min: rect.min / self.size,
+            max: rect.max / self.size,
+        }
+    }
+}
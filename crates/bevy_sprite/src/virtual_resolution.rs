@@ -0,0 +1,625 @@
+//! Renders the main passes at a fixed logical resolution and scales the result to the window.
+//!
+//! [VirtualResolutionPlugin] points every pass that renders to the primary window at a texture with the virtual
+//! resolution instead, and cameras that render to the primary window get a projection of that size. A final blit pass
+//! draws the texture to the window as large as it fits, and fills the rest with letterbox or pillarbox bars. Add the
+//! plugin after the other render plugins, so it can redirect their passes.
+
+use crate::{
+    colorblind_filter::camera::COLORBLIND_FILTER_CAMERA,
+    depth_of_field::node::DOF_COMPOSITE_PASS,
+    entity::SpriteComponents,
+    motion_blur::node::MOTION_BLUR_PASS,
+    temporal_anti_aliasing::{camera::TAA_CAMERA, node::TAA_RESOLVE_PASS},
+    ColorMaterial, Rect, Sprite, SpriteResizeMode, QUAD_HANDLE,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Component, IntoQuerySystem, Query, Res, ResMut, With, Without};
+use bevy_math::Vec2;
+use bevy_render::{
+    camera::{
+        ActiveCameras, Camera, CameraProjection, OrthographicProjection, PerspectiveProjection,
+    },
+    entity::Camera2dComponents,
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+        TextureAttachment,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, Msaa, MsaaTexture},
+        AssetTextureNode, CameraNode, Edge, NodeId, PassNode, RenderGraph, TextureNode,
+        WindowSwapChainNode, WindowTextureNode,
+    },
+    texture::{
+        Extent3d, FilterMode, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+        TextureUsage,
+    },
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+use bevy_window::{WindowId, Windows};
+
+/// The texture the main passes render to when a virtual resolution is used
+pub const VIRTUAL_RESOLUTION_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 4367285150277013471);
+
+pub mod node {
+    pub const VIRTUAL_RESOLUTION_TEXTURE: &str = "virtual_resolution_texture";
+    pub const VIRTUAL_RESOLUTION_DEPTH_TEXTURE: &str = "virtual_resolution_depth_texture";
+    pub const VIRTUAL_RESOLUTION_SAMPLED_COLOR_ATTACHMENT: &str =
+        "virtual_resolution_sampled_color_attachment";
+    pub const VIRTUAL_RESOLUTION_CAMERA: &str = "virtual_resolution_camera";
+    pub const VIRTUAL_RESOLUTION_PASS: &str = "virtual_resolution_pass";
+}
+
+pub mod camera {
+    pub const VIRTUAL_RESOLUTION_CAMERA: &str = "VirtualResolutionCamera";
+}
+
+/// Determines how the virtual resolution is scaled to the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScaleMode {
+    /// Scales the image as large as it fits in the window
+    Fit,
+    /// Scales the image by the largest whole number that fits in the window, so every virtual pixel covers the same
+    /// number of window pixels. Images that don't fit at all are scaled down like [ScaleMode::Fit].
+    IntegerFit,
+}
+
+impl Default for ScaleMode {
+    fn default() -> Self {
+        ScaleMode::Fit
+    }
+}
+
+/// The fixed logical resolution the primary window is rendered at. The default value renders at the window
+/// resolution, so systems can convert positions without knowing whether [VirtualResolutionPlugin] is used.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualResolution {
+    size: Option<Vec2>,
+    pub scale_mode: ScaleMode,
+}
+
+impl VirtualResolution {
+    pub fn new(width: u32, height: u32, scale_mode: ScaleMode) -> Self {
+        VirtualResolution {
+            size: Some(Vec2::new(width as f32, height as f32)),
+            scale_mode,
+        }
+    }
+
+    /// The virtual resolution, or `None` if the window resolution is used
+    pub fn size(&self) -> Option<Vec2> {
+        self.size
+    }
+
+    /// The size of the area that is rendered for a window of `window_size`
+    pub fn render_size(&self, window_size: Vec2) -> Vec2 {
+        self.size.unwrap_or(window_size)
+    }
+
+    /// The factor the virtual resolution is scaled by to fit a window of `window_size`
+    pub fn scale(&self, window_size: Vec2) -> f32 {
+        let size = self.render_size(window_size);
+        let scale = (window_size.x() / size.x()).min(window_size.y() / size.y());
+        match self.scale_mode {
+            ScaleMode::IntegerFit if scale >= 1.0 => scale.floor(),
+            _ => scale,
+        }
+    }
+
+    /// The area of a window of `window_size` the virtual resolution is drawn to, in window pixels. The rest of the
+    /// window is covered by the bars.
+    pub fn viewport(&self, window_size: Vec2) -> Rect {
+        let size = self.render_size(window_size) * self.scale(window_size);
+        let min = Vec2::new(
+            ((window_size.x() - size.x()) / 2.0).round(),
+            ((window_size.y() - size.y()) / 2.0).round(),
+        );
+        Rect {
+            min,
+            max: min + size,
+        }
+    }
+
+    /// Converts a position in a window of `window_size`, like a cursor position, to the virtual resolution. Positions
+    /// on the bars end up outside of the virtual resolution.
+    pub fn window_to_virtual(&self, window_size: Vec2, position: Vec2) -> Vec2 {
+        if self.size.is_none() {
+            return position;
+        }
+
+        let viewport = self.viewport(window_size);
+        (position - viewport.min) / self.scale(window_size)
+    }
+}
+
+/// Marks the camera that draws the virtual resolution to the window
+#[derive(Debug, Default)]
+pub struct VirtualResolutionCamera;
+
+/// Marks the sprite that shows the virtual resolution in the window
+#[derive(Debug, Default)]
+pub struct VirtualResolutionBlit;
+
+pub struct VirtualResolutionPlugin {
+    pub width: u32,
+    pub height: u32,
+    pub scale_mode: ScaleMode,
+    /// The filter used when scaling the image. [FilterMode::Nearest] keeps pixel art sharp.
+    pub filter: FilterMode,
+    pub bar_color: Color,
+}
+
+impl Default for VirtualResolutionPlugin {
+    fn default() -> Self {
+        VirtualResolutionPlugin {
+            width: 1280,
+            height: 720,
+            scale_mode: ScaleMode::Fit,
+            filter: FilterMode::Linear,
+            bar_color: Color::BLACK,
+        }
+    }
+}
+
+impl Plugin for VirtualResolutionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(VirtualResolution::new(
+            self.width,
+            self.height,
+            self.scale_mode,
+        ))
+        .add_startup_system(spawn_virtual_resolution_blit.system())
+        .add_system_to_stage(
+            stage::POST_UPDATE,
+            virtual_resolution_camera_system::<OrthographicProjection>.system(),
+        )
+        .add_system_to_stage(
+            stage::POST_UPDATE,
+            virtual_resolution_camera_system::<PerspectiveProjection>.system(),
+        )
+        .add_system_to_stage(stage::POST_UPDATE, virtual_resolution_blit_system.system());
+
+        let resources = app.resources();
+        resources
+            .get_mut::<ActiveCameras>()
+            .unwrap()
+            .add(camera::VIRTUAL_RESOLUTION_CAMERA);
+
+        let mut texture = Texture::new_render_target(
+            Vec2::new(self.width as f32, self.height as f32),
+            TextureFormat::default(),
+        );
+        texture.sampler.mag_filter = self.filter;
+        texture.sampler.min_filter = self.filter;
+        resources
+            .get_mut::<Assets<Texture>>()
+            .unwrap()
+            .set_untracked(VIRTUAL_RESOLUTION_TEXTURE_HANDLE, texture);
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_virtual_resolution_graph(&mut render_graph, self);
+    }
+}
+
+fn spawn_virtual_resolution_blit(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.spawn(Camera2dComponents {
+        camera: Camera {
+            name: Some(camera::VIRTUAL_RESOLUTION_CAMERA.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    commands.with(VirtualResolutionCamera);
+
+    // the sprite isn't part of the main pass, so it is only drawn by the blit pass
+    let sprite = SpriteComponents::default();
+    commands.spawn((
+        Sprite {
+            resize_mode: SpriteResizeMode::Manual,
+            ..Default::default()
+        },
+        QUAD_HANDLE,
+        materials.add(ColorMaterial::texture(VIRTUAL_RESOLUTION_TEXTURE_HANDLE)),
+        sprite.draw,
+        sprite.render_pipelines,
+        Transform::default(),
+        GlobalTransform::default(),
+        VirtualResolutionBlit,
+    ));
+}
+
+/// Gives cameras that render to the primary window a projection with the size of the [VirtualResolution]
+pub fn virtual_resolution_camera_system<T: CameraProjection + Component>(
+    virtual_resolution: Res<VirtualResolution>,
+    mut query: Query<Without<VirtualResolutionCamera, (&mut Camera, &mut T)>>,
+) {
+    let size = if let Some(size) = virtual_resolution.size() {
+        size
+    } else {
+        return;
+    };
+
+    for (mut camera, mut camera_projection) in query.iter_mut() {
+        // the colorblind filter and the taa resolve draw the scaled image to the whole window
+        if camera.window != WindowId::primary()
+            || camera.name.as_deref() == Some(COLORBLIND_FILTER_CAMERA)
+            || camera.name.as_deref() == Some(TAA_CAMERA)
+        {
+            continue;
+        }
+
+        camera_projection.update(size.x() as usize, size.y() as usize);
+        camera.projection_matrix = camera_projection.get_projection_matrix();
+    }
+}
+
+/// Fits the [VirtualResolutionBlit] sprite into the primary window
+pub fn virtual_resolution_blit_system(
+    virtual_resolution: Res<VirtualResolution>,
+    windows: Res<Windows>,
+    mut query: Query<With<VirtualResolutionBlit, (&mut Sprite, &mut Transform)>>,
+) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+
+    let window_size = Vec2::new(window.width() as f32, window.height() as f32);
+    let viewport = virtual_resolution.viewport(window_size);
+    // the blit camera looks at the center of the window
+    let center = (viewport.min + viewport.max - window_size) / 2.0;
+    for (mut sprite, mut transform) in query.iter_mut() {
+        sprite.size = viewport.max - viewport.min;
+        transform.translation = center.extend(0.0);
+    }
+}
+
+fn add_virtual_resolution_graph(graph: &mut RenderGraph, plugin: &VirtualResolutionPlugin) {
+    let texture_descriptor = |format| TextureDescriptor {
+        size: Extent3d {
+            width: plugin.width,
+            height: plugin.height,
+            depth: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsage::OUTPUT_ATTACHMENT,
+    };
+
+    graph.add_node(
+        node::VIRTUAL_RESOLUTION_TEXTURE,
+        AssetTextureNode::new(VIRTUAL_RESOLUTION_TEXTURE_HANDLE),
+    );
+    graph.add_node(
+        node::VIRTUAL_RESOLUTION_DEPTH_TEXTURE,
+        TextureNode::multisampled(
+            texture_descriptor(TextureFormat::Depth32Float),
+            MsaaTexture::Depth,
+        ),
+    );
+
+    // move every pass that renders to the window over to the virtual resolution textures
+    let mut redirected_nodes = redirect_slot_edges(
+        graph,
+        base::node::PRIMARY_SWAP_CHAIN,
+        node::VIRTUAL_RESOLUTION_TEXTURE,
+    );
+    redirected_nodes.extend(redirect_slot_edges(
+        graph,
+        base::node::MAIN_DEPTH_TEXTURE,
+        node::VIRTUAL_RESOLUTION_DEPTH_TEXTURE,
+    ));
+    graph.add_node(
+        node::VIRTUAL_RESOLUTION_SAMPLED_COLOR_ATTACHMENT,
+        TextureNode::multisampled(
+            texture_descriptor(TextureFormat::default()),
+            MsaaTexture::Color,
+        ),
+    );
+    redirected_nodes.extend(redirect_slot_edges(
+        graph,
+        base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+        node::VIRTUAL_RESOLUTION_SAMPLED_COLOR_ATTACHMENT,
+    ));
+
+    let mut pass_node = PassNode::<&VirtualResolutionBlit>::new(PassDescriptor {
+        color_attachments: vec![Msaa::color_attachment_descriptor(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Clear(plugin.bar_color),
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    });
+    pass_node.add_camera(camera::VIRTUAL_RESOLUTION_CAMERA);
+    graph.add_node(node::VIRTUAL_RESOLUTION_PASS, pass_node);
+
+    graph.add_system_node(
+        node::VIRTUAL_RESOLUTION_CAMERA,
+        CameraNode::new(camera::VIRTUAL_RESOLUTION_CAMERA),
+    );
+    graph
+        .add_node_edge(
+            node::VIRTUAL_RESOLUTION_CAMERA,
+            node::VIRTUAL_RESOLUTION_PASS,
+        )
+        .unwrap();
+    graph
+        .add_node_edge(base::node::TEXTURE_COPY, node::VIRTUAL_RESOLUTION_PASS)
+        .unwrap();
+    graph
+        .add_node_edge(base::node::SHARED_BUFFERS, node::VIRTUAL_RESOLUTION_PASS)
+        .unwrap();
+    // the blit pass samples what the redirected passes rendered
+    for redirected_node in redirected_nodes {
+        let _ = graph.add_node_edge(redirected_node, node::VIRTUAL_RESOLUTION_PASS);
+    }
+
+    // the window textures are only used by the blit pass now
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::VIRTUAL_RESOLUTION_PASS,
+            "color_resolve_target",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            base::node::MAIN_DEPTH_TEXTURE,
+            WindowTextureNode::OUT_TEXTURE,
+            node::VIRTUAL_RESOLUTION_PASS,
+            "depth",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+            WindowTextureNode::OUT_TEXTURE,
+            node::VIRTUAL_RESOLUTION_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+}
+
+/// Moves all slot edges that start at the first output of `from` to the first output of `to`. Returns the nodes the
+/// edges end at.
+pub(crate) fn redirect_slot_edges(
+    graph: &mut RenderGraph,
+    from: &'static str,
+    to: &'static str,
+) -> Vec<NodeId> {
+    redirect_slot_edges_where(graph, from, to, |_node| true)
+}
+
+/// The passes that render the scene, as opposed to the passes that draw over it like the ui pass: the main pass, the
+/// pass that draws the main pass's colors to the window when a node samples them, and the last pass of each
+/// post-processing effect that was added before
+const SCENE_PASSES: [&str; 5] = [
+    base::node::MAIN_PASS,
+    base::node::MAIN_COLOR_BLIT_PASS,
+    TAA_RESOLVE_PASS,
+    DOF_COMPOSITE_PASS,
+    MOTION_BLUR_PASS,
+];
+
+/// Like [redirect_slot_edges], but only moves the edges that end at a scene pass, so a post-processing effect leaves
+/// the passes that draw over the scene alone. Returns the nodes the edges end at.
+pub(crate) fn redirect_scene_slot_edges(
+    graph: &mut RenderGraph,
+    from: &'static str,
+    to: &'static str,
+) -> Vec<NodeId> {
+    let scene_passes = SCENE_PASSES
+        .iter()
+        .filter_map(|pass| graph.get_node_id(*pass).ok())
+        .collect::<Vec<_>>();
+    redirect_slot_edges_where(graph, from, to, |node| scene_passes.contains(&node))
+}
+
+fn redirect_slot_edges_where(
+    graph: &mut RenderGraph,
+    from: &'static str,
+    to: &'static str,
+    filter: impl Fn(NodeId) -> bool,
+) -> Vec<NodeId> {
+    let edges = graph
+        .iter_node_outputs(from)
+        .map(|outputs| {
+            outputs
+                .filter_map(|(edge, _node)| match edge {
+                    Edge::SlotEdge {
+                        input_node,
+                        input_index,
+                        output_index: 0,
+                        ..
+                    } if filter(*input_node) => Some((*input_node, *input_index)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    for (input_node, input_index) in edges.iter() {
+        graph
+            .remove_slot_edge(from, 0usize, *input_node, *input_index)
+            .unwrap();
+        graph
+            .add_slot_edge(to, 0usize, *input_node, *input_index)
+            .unwrap();
+    }
+
+    edges
+        .into_iter()
+        .map(|(input_node, _input_index)| input_node)
+        .collect()
+}
+
+/// Makes `node` run before the other nodes that use the first output of `output`. Effects that write their result to
+/// the window use this so passes that draw on top of the window, like the ui pass, draw over the result.
+pub(crate) fn order_before_slot_consumers(
+    graph: &mut RenderGraph,
+    output: &'static str,
+    node: &'static str,
+) {
+    let node_id = graph.get_node_id(node).unwrap();
+    let consumers = graph
+        .iter_node_outputs(output)
+        .map(|outputs| {
+            outputs
+                .filter_map(|(edge, _node)| match edge {
+                    Edge::SlotEdge {
+                        input_node,
+                        output_index: 0,
+                        ..
+                    } if *input_node != node_id => Some(*input_node),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    for consumer in consumers {
+        // the edge may already exist
+        let _ = graph.add_node_edge(node, consumer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{Resources, World};
+    use bevy_render::{
+        render_graph::{Node, ResourceSlotInfo, ResourceSlots},
+        renderer::{RenderContext, RenderResourceType},
+    };
+
+    #[test]
+    fn virtual_resolution_viewport() {
+        let virtual_resolution = VirtualResolution::new(320, 180, ScaleMode::Fit);
+
+        // pillarbox
+        let viewport = virtual_resolution.viewport(Vec2::new(1000.0, 360.0));
+        assert_eq!(viewport.min, Vec2::new(180.0, 0.0));
+        assert_eq!(viewport.max, Vec2::new(820.0, 360.0));
+
+        // letterbox
+        let window_size = Vec2::new(640.0, 600.0);
+        let viewport = virtual_resolution.viewport(window_size);
+        assert_eq!(viewport.min, Vec2::new(0.0, 120.0));
+        assert_eq!(viewport.max, Vec2::new(640.0, 480.0));
+        assert_eq!(
+            virtual_resolution.window_to_virtual(window_size, Vec2::new(320.0, 300.0)),
+            Vec2::new(160.0, 90.0)
+        );
+    }
+
+    struct TestNode {
+        inputs: Vec<ResourceSlotInfo>,
+        outputs: Vec<ResourceSlotInfo>,
+    }
+
+    impl TestNode {
+        fn new(inputs: usize, outputs: usize) -> Self {
+            let slots = |count: usize, prefix: &str| -> Vec<ResourceSlotInfo> {
+                (0..count)
+                    .map(|i| ResourceSlotInfo {
+                        name: format!("{}_{}", prefix, i).into(),
+                        resource_type: RenderResourceType::Texture,
+                    })
+                    .collect()
+            };
+            TestNode {
+                inputs: slots(inputs, "in"),
+                outputs: slots(outputs, "out"),
+            }
+        }
+    }
+
+    impl Node for TestNode {
+        fn input(&self) -> &[ResourceSlotInfo] {
+            &self.inputs
+        }
+
+        fn output(&self) -> &[ResourceSlotInfo] {
+            &self.outputs
+        }
+
+        fn update(
+            &mut self,
+            _: &World,
+            _: &Resources,
+            _: &mut dyn RenderContext,
+            _: &ResourceSlots,
+            _: &mut ResourceSlots,
+        ) {
+        }
+    }
+
+    #[test]
+    fn effects_only_redirect_scene_passes() {
+        const EFFECT_TEXTURE: &str = "effect_texture";
+        const UI_PASS: &str = "ui_pass";
+
+        let mut graph = RenderGraph::default();
+        let swap_chain = graph.add_node(base::node::PRIMARY_SWAP_CHAIN, TestNode::new(0, 1));
+        let effect_texture = graph.add_node(EFFECT_TEXTURE, TestNode::new(0, 1));
+        let main_pass = graph.add_node(base::node::MAIN_PASS, TestNode::new(1, 0));
+        let ui_pass = graph.add_node(UI_PASS, TestNode::new(1, 0));
+        let effect_pass = graph.add_node(TAA_RESOLVE_PASS, TestNode::new(1, 0));
+        for pass in [base::node::MAIN_PASS, UI_PASS].iter() {
+            graph
+                .add_slot_edge(base::node::PRIMARY_SWAP_CHAIN, 0, *pass, 0)
+                .unwrap();
+        }
+
+        assert_eq!(
+            redirect_scene_slot_edges(&mut graph, base::node::PRIMARY_SWAP_CHAIN, EFFECT_TEXTURE),
+            vec![main_pass]
+        );
+        graph
+            .add_slot_edge(base::node::PRIMARY_SWAP_CHAIN, 0, TAA_RESOLVE_PASS, 0)
+            .unwrap();
+        order_before_slot_consumers(&mut graph, base::node::PRIMARY_SWAP_CHAIN, TAA_RESOLVE_PASS);
+
+        let slot_edge = |output_node, input_node| Edge::SlotEdge {
+            input_node,
+            input_index: 0,
+            output_node,
+            output_index: 0,
+        };
+        assert!(graph.has_edge(&slot_edge(effect_texture, main_pass)));
+        assert!(!graph.has_edge(&slot_edge(swap_chain, main_pass)));
+        assert!(graph.has_edge(&slot_edge(swap_chain, ui_pass)));
+        assert!(graph.has_edge(&Edge::NodeEdge {
+            input_node: ui_pass,
+            output_node: effect_pass,
+        }));
+    }
+
+    #[test]
+    fn integer_scaling() {
+        let virtual_resolution = VirtualResolution::new(320, 180, ScaleMode::IntegerFit);
+        assert_eq!(virtual_resolution.scale(Vec2::new(1000.0, 600.0)), 3.0);
+        assert_eq!(virtual_resolution.scale(Vec2::new(160.0, 180.0)), 0.5);
+    }
+}
@@ -39,6 +39,12 @@ impl<T> Task<T> {
     pub async fn cancel(self) -> Option<T> {
         self.0.cancel().await
     }
+
+    /// Polls the task once without blocking, and returns its output if it finished. A task that returned its output
+    /// must not be polled again.
+    pub fn poll_once(&mut self) -> Option<T> {
+        futures_lite::future::block_on(futures_lite::future::poll_once(&mut self.0))
+    }
 }
 
 impl<T> Future for Task<T> {
@@ -1,4 +1,4 @@
-use crate::{Font, FontAtlasSet};
+use crate::{Font, FontAtlasSet, TextMeasure};
 use ab_glyph::{Glyph, PxScale, ScaleFont};
 use bevy_asset::Assets;
 use bevy_math::{Mat4, Vec2, Vec3};
@@ -85,75 +85,86 @@ impl<'a> Drawable for DrawableText<'a> {
         let font = &self.font.font;
         let scale = PxScale::from(self.style.font_size);
         let scaled_font = ab_glyph::Font::as_scaled(&font, scale);
-        let mut caret = self.position;
-        let mut last_glyph: Option<Glyph> = None;
+        let text_measure = TextMeasure::new(self.font, self.style.font_size, self.text);
+        let max_width = if self.container_size.x() > 0.0 {
+            Some(self.container_size.x())
+        } else {
+            None
+        };
+
+        // lines are laid out from the top of the container
+        for (line_index, line) in text_measure.lines(max_width).into_iter().enumerate() {
+            let mut caret = Vec3::new(
+                self.position.x(),
+                self.position.y() + self.container_size.y()
+                    - (line_index + 1) as f32 * text_measure.line_height(),
+                self.position.z(),
+            );
+            let mut last_glyph: Option<Glyph> = None;
 
-        // set local per-character bindings
-        for character in self.text.chars() {
-            if character.is_control() {
-                if character == '\n' {
-                    caret.set_x(self.position.x());
-                    // TODO: Necessary to also calculate scaled_font.line_gap() in here?
-                    caret.set_y(caret.y() - scaled_font.height());
+            // set local per-character bindings
+            for character in self.text[line].chars() {
+                if character.is_control() {
+                    continue;
                 }
-                continue;
-            }
 
-            let glyph = scaled_font.scaled_glyph(character);
-            if let Some(last_glyph) = last_glyph.take() {
-                caret.set_x(caret.x() + scaled_font.kern(last_glyph.id, glyph.id));
-            }
-            if let Some(glyph_atlas_info) = self
-                .font_atlas_set
-                .get_glyph_atlas_info(self.style.font_size, character)
-            {
-                if let Some(outlined) = scaled_font.outline_glyph(glyph.clone()) {
-                    let texture_atlas = self
-                        .texture_atlases
-                        .get(&glyph_atlas_info.texture_atlas)
-                        .unwrap();
-                    let glyph_rect = texture_atlas.textures[glyph_atlas_info.char_index as usize];
-                    let glyph_width = glyph_rect.width();
-                    let glyph_height = glyph_rect.height();
-                    let atlas_render_resource_bindings = self
-                        .asset_render_resource_bindings
-                        .get_mut(&glyph_atlas_info.texture_atlas)
-                        .unwrap();
-                    context.set_bind_groups_from_bindings(
-                        draw,
-                        &mut [atlas_render_resource_bindings],
-                    )?;
+                let glyph = scaled_font.scaled_glyph(character);
+                if let Some(last_glyph) = last_glyph.take() {
+                    caret.set_x(caret.x() + scaled_font.kern(last_glyph.id, glyph.id));
+                }
+                if let Some(glyph_atlas_info) = self
+                    .font_atlas_set
+                    .get_glyph_atlas_info(self.style.font_size, character)
+                {
+                    if let Some(outlined) = scaled_font.outline_glyph(glyph.clone()) {
+                        let texture_atlas = self
+                            .texture_atlases
+                            .get(&glyph_atlas_info.texture_atlas)
+                            .unwrap();
+                        let glyph_rect =
+                            texture_atlas.textures[glyph_atlas_info.char_index as usize];
+                        let glyph_width = glyph_rect.width();
+                        let glyph_height = glyph_rect.height();
+                        let atlas_render_resource_bindings = self
+                            .asset_render_resource_bindings
+                            .get_mut(&glyph_atlas_info.texture_atlas)
+                            .unwrap();
+                        context.set_bind_groups_from_bindings(
+                            draw,
+                            &mut [atlas_render_resource_bindings],
+                        )?;
 
-                    let bounds = outlined.px_bounds();
-                    let x = bounds.min.x + glyph_width / 2.0;
-                    // the 0.5 accounts for odd-numbered heights (bump up by 1 pixel)
-                    let y = -bounds.max.y + glyph_height / 2.0 - scaled_font.descent() + 0.5;
-                    let transform = Mat4::from_translation(caret + Vec3::new(x, y, 0.0));
-                    let sprite = TextureAtlasSprite {
-                        index: glyph_atlas_info.char_index,
-                        color: self.style.color,
-                    };
+                        let bounds = outlined.px_bounds();
+                        let x = bounds.min.x + glyph_width / 2.0;
+                        // the 0.5 accounts for odd-numbered heights (bump up by 1 pixel)
+                        let y = -bounds.max.y + glyph_height / 2.0 - scaled_font.descent() + 0.5;
+                        let transform = Mat4::from_translation(caret + Vec3::new(x, y, 0.0));
+                        let sprite = TextureAtlasSprite {
+                            index: glyph_atlas_info.char_index,
+                            color: self.style.color,
+                        };
 
-                    let transform_buffer = context
-                        .shared_buffers
-                        .get_buffer(&transform, BufferUsage::UNIFORM)
-                        .unwrap();
-                    let sprite_buffer = context
-                        .shared_buffers
-                        .get_buffer(&sprite, BufferUsage::UNIFORM)
-                        .unwrap();
-                    let sprite_bind_group = BindGroup::build()
-                        .add_binding(0, transform_buffer)
-                        .add_binding(1, sprite_buffer)
-                        .finish();
+                        let transform_buffer = context
+                            .shared_buffers
+                            .get_buffer(&transform, BufferUsage::UNIFORM)
+                            .unwrap();
+                        let sprite_buffer = context
+                            .shared_buffers
+                            .get_buffer(&sprite, BufferUsage::UNIFORM)
+                            .unwrap();
+                        let sprite_bind_group = BindGroup::build()
+                            .add_binding(0, transform_buffer)
+                            .add_binding(1, sprite_buffer)
+                            .finish();
 
-                    context.create_bind_group_resource(2, &sprite_bind_group)?;
-                    draw.set_bind_group(2, &sprite_bind_group);
-                    draw.draw_indexed(indices.clone(), 0, 0..1);
+                        context.create_bind_group_resource(2, &sprite_bind_group)?;
+                        draw.set_bind_group(2, &sprite_bind_group);
+                        draw.draw_indexed(indices.clone(), 0, 0..1);
+                    }
                 }
+                caret.set_x(caret.x() + scaled_font.h_advance(glyph.id));
+                last_glyph = Some(glyph);
             }
-            caret.set_x(caret.x() + scaled_font.h_advance(glyph.id));
-            last_glyph = Some(glyph);
         }
         Ok(())
     }
@@ -3,15 +3,23 @@ mod font;
 mod font_atlas;
 mod font_atlas_set;
 mod font_loader;
+mod localization;
+mod string_table;
+mod string_table_loader;
+mod text_measure;
 
 pub use draw::*;
 pub use font::*;
 pub use font_atlas::*;
 pub use font_atlas_set::*;
 pub use font_loader::*;
+pub use localization::*;
+pub use string_table::*;
+pub use string_table_loader::*;
+pub use text_measure::*;
 
 pub mod prelude {
-    pub use crate::{Font, TextStyle};
+    pub use crate::{t, Font, Localization, LocalizedText, StringTable, TextStyle};
 }
 
 use bevy_app::prelude::*;
@@ -24,6 +32,9 @@ impl Plugin for TextPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_asset::<Font>()
             .add_asset::<FontAtlasSet>()
-            .init_asset_loader::<FontLoader>();
+            .add_asset::<StringTable>()
+            .init_asset_loader::<FontLoader>()
+            .init_asset_loader::<StringTableLoader>()
+            .init_resource::<Localization>();
     }
 }
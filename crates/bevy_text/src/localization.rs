@@ -0,0 +1,102 @@
+use crate::StringTable;
+use bevy_asset::{Assets, Handle};
+use std::borrow::Cow;
+
+/// The string tables that [LocalizedText] is looked up in. Tables are searched in order, so earlier tables take
+/// precedence.
+///
+/// String tables are regular assets, so loading `strings/main.ftl` while the [Locale](bevy_asset::Locale) is `ja`
+/// loads `strings/main.ja.ftl` if it exists.
+#[derive(Debug, Default, Clone)]
+pub struct Localization {
+    pub string_tables: Vec<Handle<StringTable>>,
+}
+
+impl Localization {
+    pub fn new(string_table: Handle<StringTable>) -> Self {
+        Localization {
+            string_tables: vec![string_table],
+        }
+    }
+
+    /// Formats the message with the given `key` from the first string table that contains it
+    pub fn format(
+        &self,
+        string_tables: &Assets<StringTable>,
+        key: &str,
+        args: &[(&str, &str)],
+    ) -> Option<String> {
+        self.string_tables
+            .iter()
+            .filter_map(|handle| string_tables.get(handle))
+            .find_map(|string_table| string_table.format(key, args))
+    }
+}
+
+/// A component for text that is looked up in the [Localization] string tables. Text widgets with this component have
+/// their value replaced by the localized message. Messages that can't be found are displayed as their key.
+///
+/// This is usually created with the [t!](crate::t) macro.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LocalizedText {
+    pub key: Cow<'static, str>,
+    pub args: Vec<(Cow<'static, str>, String)>,
+}
+
+impl LocalizedText {
+    pub fn new<K: Into<Cow<'static, str>>>(key: K) -> Self {
+        LocalizedText {
+            key: key.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn with_arg<N: Into<Cow<'static, str>>, V: ToString>(mut self, name: N, value: V) -> Self {
+        self.set_arg(name, value);
+        self
+    }
+
+    /// Sets the value of the variable `name`. Changing a variable updates the displayed text.
+    pub fn set_arg<N: Into<Cow<'static, str>>, V: ToString>(&mut self, name: N, value: V) {
+        let name = name.into();
+        let value = value.to_string();
+        if let Some(arg) = self.args.iter_mut().find(|(arg, _)| *arg == name) {
+            arg.1 = value;
+        } else {
+            self.args.push((name, value));
+        }
+    }
+
+    pub fn format(
+        &self,
+        localization: &Localization,
+        string_tables: &Assets<StringTable>,
+    ) -> String {
+        let args = self
+            .args
+            .iter()
+            .map(|(name, value)| (name.as_ref(), value.as_str()))
+            .collect::<Vec<_>>();
+        localization
+            .format(string_tables, &self.key, &args)
+            .unwrap_or_else(|| self.key.to_string())
+    }
+}
+
+/// Creates a [LocalizedText] for the given message key and variables
+///
+/// ```
+/// # use bevy_text::t;
+/// let title = t!("title");
+/// let greeting = t!("hello", name = "Ferris", unread = 3);
+/// assert_eq!(greeting.args.len(), 2);
+/// ```
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::LocalizedText::new($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::LocalizedText::new($key)$(.with_arg(stringify!($name), $value))+
+    };
+}
@@ -0,0 +1,245 @@
+use bevy_type_registry::TypeUuid;
+use bevy_utils::HashMap;
+use thiserror::Error;
+
+/// The maximum depth of nested message references. This guards against references that form a cycle.
+const MAX_REFERENCE_DEPTH: usize = 8;
+
+#[derive(Error, Debug)]
+pub enum StringTableError {
+    #[error("Expected a message on line {0}.")]
+    ExpectedMessage(usize),
+    #[error("Invalid message identifier on line {0}.")]
+    InvalidIdentifier(usize),
+    #[error("Expected a message before the attribute or multiline value on line {0}.")]
+    MissingMessage(usize),
+}
+
+/// A table of localized strings, loaded from [Fluent](https://projectfluent.org) (`.ftl`) files.
+///
+/// Only a subset of Fluent is supported: messages, terms, attributes, multiline values, variables, string literals and
+/// message references. Attributes are stored as `message.attribute`. Selectors and functions are not supported.
+#[derive(Debug, Clone, Default, TypeUuid)]
+#[uuid = "ab9c5f66-7228-43f4-8645-5dadf198092e"]
+pub struct StringTable {
+    messages: HashMap<String, String>,
+}
+
+impl StringTable {
+    pub fn from_ftl(source: &str) -> Result<Self, StringTableError> {
+        let mut string_table = StringTable::default();
+        // the message that attributes belong to and the key that multiline values are appended to
+        let mut message: Option<String> = None;
+        let mut current_key: Option<String> = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('#') {
+                message = None;
+                current_key = None;
+            } else if line.starts_with(char::is_whitespace) {
+                if trimmed.starts_with('.') {
+                    let message = message
+                        .as_ref()
+                        .ok_or(StringTableError::MissingMessage(line_number))?;
+                    let (attribute, value) = parse_entry(&trimmed[1..], line_number)?;
+                    let key = format!("{}.{}", message, attribute);
+                    string_table.insert(key.clone(), value);
+                    current_key = Some(key);
+                } else {
+                    let key = current_key
+                        .as_ref()
+                        .ok_or(StringTableError::MissingMessage(line_number))?;
+                    let value = string_table.messages.get_mut(key).unwrap();
+                    if !value.is_empty() {
+                        value.push('\n');
+                    }
+                    value.push_str(trimmed);
+                }
+            } else {
+                let (id, value) = parse_entry(line, line_number)?;
+                string_table.insert(id.to_string(), value);
+                message = Some(id.to_string());
+                current_key = message.clone();
+            }
+        }
+
+        Ok(string_table)
+    }
+
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        self.messages.insert(key.into(), value.into());
+    }
+
+    /// Returns the unformatted value of the message with the given `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(|value| value.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Returns the value of the message with the given `key`, with its placeables replaced by the given variables,
+    /// string literals and referenced messages. Unknown variables and messages are left as they are.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        self.format_with_depth(key, args, 0)
+    }
+
+    fn format_with_depth(&self, key: &str, args: &[(&str, &str)], depth: usize) -> Option<String> {
+        let value = self.get(key)?;
+        let mut formatted = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find('{') {
+            let end = match find_placeable_end(&rest[start + 1..]) {
+                Some(end) => start + 1 + end,
+                None => break,
+            };
+            formatted.push_str(&rest[..start]);
+            let placeable = rest[start + 1..end].trim();
+            match self.format_placeable(placeable, args, depth) {
+                Some(value) => formatted.push_str(&value),
+                None => formatted.push_str(&rest[start..=end]),
+            }
+            rest = &rest[end + 1..];
+        }
+        formatted.push_str(rest);
+        Some(formatted)
+    }
+
+    fn format_placeable(
+        &self,
+        placeable: &str,
+        args: &[(&str, &str)],
+        depth: usize,
+    ) -> Option<String> {
+        if placeable.starts_with('$') {
+            let name = &placeable[1..];
+            args.iter()
+                .find(|(arg, _)| *arg == name)
+                .map(|(_, value)| value.to_string())
+        } else if placeable.len() >= 2 && placeable.starts_with('"') && placeable.ends_with('"') {
+            Some(placeable[1..placeable.len() - 1].to_string())
+        } else if depth < MAX_REFERENCE_DEPTH {
+            self.format_with_depth(placeable, args, depth + 1)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the index of the `}` that closes the placeable starting at `placeable`. Braces in string literals don't
+/// close the placeable.
+fn find_placeable_end(placeable: &str) -> Option<usize> {
+    let content = placeable.trim_start();
+    if content.starts_with('"') {
+        let offset = placeable.len() - content.len();
+        let quote_end = content[1..].find('"')? + 1;
+        let end = content[quote_end..].find('}')? + quote_end;
+        Some(offset + end)
+    } else {
+        placeable.find('}')
+    }
+}
+
+/// Parses an `identifier = value` entry
+fn parse_entry(line: &str, line_number: usize) -> Result<(&str, String), StringTableError> {
+    let separator = line
+        .find('=')
+        .ok_or(StringTableError::ExpectedMessage(line_number))?;
+    let id = line[..separator].trim();
+    if !is_identifier(id) {
+        return Err(StringTableError::InvalidIdentifier(line_number));
+    }
+
+    Ok((id, line[separator + 1..].trim().to_string()))
+}
+
+/// Messages start with a letter. Terms are messages that start with `-`.
+fn is_identifier(id: &str) -> bool {
+    let name = if id.starts_with('-') { &id[1..] } else { id };
+    let mut chars = name.chars();
+    chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FTL: &str = r#"
+# Greetings
+-brand = Bevy
+hello = Hello, { $name }!
+welcome = Welcome to { -brand }
+multiline =
+    First line
+    Second line
+login = Log in
+    .tooltip = Log in to { -brand }
+braces = { "{" }literal{ "}" }
+"#;
+
+    #[test]
+    fn parse_ftl() {
+        let string_table = StringTable::from_ftl(FTL).unwrap();
+        assert_eq!(string_table.len(), 7);
+        assert_eq!(string_table.get("hello"), Some("Hello, { $name }!"));
+        assert_eq!(
+            string_table.get("multiline"),
+            Some("First line\nSecond line")
+        );
+        assert_eq!(
+            string_table.get("login.tooltip"),
+            Some("Log in to { -brand }")
+        );
+        assert!(string_table.get("missing").is_none());
+    }
+
+    #[test]
+    fn format_placeables() {
+        let string_table = StringTable::from_ftl(FTL).unwrap();
+        assert_eq!(
+            string_table.format("hello", &[("name", "Ferris")]).unwrap(),
+            "Hello, Ferris!"
+        );
+        assert_eq!(
+            string_table.format("hello", &[]).unwrap(),
+            "Hello, { $name }!"
+        );
+        assert_eq!(
+            string_table.format("welcome", &[]).unwrap(),
+            "Welcome to Bevy"
+        );
+        assert_eq!(
+            string_table.format("login.tooltip", &[]).unwrap(),
+            "Log in to Bevy"
+        );
+        assert_eq!(string_table.format("braces", &[]).unwrap(), "{literal}");
+    }
+
+    #[test]
+    fn invalid_ftl() {
+        assert!(matches!(
+            StringTable::from_ftl("hello world"),
+            Err(StringTableError::ExpectedMessage(1))
+        ));
+        assert!(matches!(
+            StringTable::from_ftl("ok = fine\n1st = nope"),
+            Err(StringTableError::InvalidIdentifier(2))
+        ));
+        assert!(matches!(
+            StringTable::from_ftl("    .tooltip = orphan"),
+            Err(StringTableError::MissingMessage(1))
+        ));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::Font;
+use ab_glyph::{Glyph, ScaleFont};
+use bevy_math::Vec2;
+use std::ops::Range;
+
+/// Lines are allowed to be slightly wider than the width they are wrapped to. This keeps text that was laid out at its
+/// own width from wrapping due to rounding.
+const WRAP_TOLERANCE: f32 = 0.01;
+
+#[derive(Debug, Clone, PartialEq)]
+struct TextSegment {
+    /// The byte range of the segment's word in the text
+    range: Range<usize>,
+    width: f32,
+    /// The width of the whitespace that follows the word
+    space_width: f32,
+    /// Whether the whitespace that follows the word contains a line break
+    line_break: bool,
+}
+
+/// The measured words of a text. This is used to lay out text at different widths without measuring it again.
+///
+/// Text is wrapped at whitespace. Words that are wider than the available width are not broken up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextMeasure {
+    segments: Vec<TextSegment>,
+    line_height: f32,
+}
+
+impl TextMeasure {
+    pub fn new(font: &Font, font_size: f32, text: &str) -> Self {
+        let scaled_font = ab_glyph::Font::as_scaled(&font.font, font_size);
+        let mut segments = Vec::new();
+        let mut segment = TextSegment {
+            range: 0..0,
+            width: 0.0,
+            space_width: 0.0,
+            line_break: false,
+        };
+        let mut in_space = false;
+        let mut last_glyph: Option<Glyph> = None;
+
+        for (index, character) in text.char_indices() {
+            if character.is_whitespace() {
+                in_space = true;
+                last_glyph = None;
+                if character == '\n' {
+                    segment.line_break = true;
+                    segments.push(segment.clone());
+                    segment = TextSegment {
+                        range: index + 1..index + 1,
+                        width: 0.0,
+                        space_width: 0.0,
+                        line_break: false,
+                    };
+                    in_space = false;
+                } else if !character.is_control() {
+                    segment.space_width +=
+                        scaled_font.h_advance(scaled_font.scaled_glyph(character).id);
+                }
+                continue;
+            }
+
+            if in_space {
+                segments.push(segment.clone());
+                segment = TextSegment {
+                    range: index..index,
+                    width: 0.0,
+                    space_width: 0.0,
+                    line_break: false,
+                };
+                in_space = false;
+            }
+
+            if character.is_control() {
+                segment.range.end = index + character.len_utf8();
+                continue;
+            }
+
+            let glyph = scaled_font.scaled_glyph(character);
+            if let Some(last_glyph) = last_glyph.take() {
+                segment.width += scaled_font.kern(last_glyph.id, glyph.id);
+            }
+            segment.width += scaled_font.h_advance(glyph.id);
+            segment.range.end = index + character.len_utf8();
+            last_glyph = Some(glyph);
+        }
+        segments.push(segment);
+
+        TextMeasure {
+            segments,
+            line_height: scaled_font.height(),
+        }
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// Returns the byte ranges of the lines of the text when it is wrapped to `max_width`. Without a `max_width`, the
+    /// text only breaks at line breaks.
+    pub fn lines(&self, max_width: Option<f32>) -> Vec<Range<usize>> {
+        self.layout(max_width).0
+    }
+
+    /// Returns the size of the text when it is wrapped to `max_width`
+    pub fn size(&self, max_width: Option<f32>) -> Vec2 {
+        let (lines, width) = self.layout(max_width);
+        Vec2::new(width, lines.len() as f32 * self.line_height)
+    }
+
+    fn layout(&self, max_width: Option<f32>) -> (Vec<Range<usize>>, f32) {
+        let mut lines = Vec::new();
+        let mut widest_line = 0.0f32;
+        let mut line: Option<Range<usize>> = None;
+        let mut line_width = 0.0;
+        let mut space_width = 0.0;
+
+        for segment in self.segments.iter() {
+            match line {
+                Some(ref mut range)
+                    if max_width.map_or(true, |max_width| {
+                        line_width + space_width + segment.width <= max_width + WRAP_TOLERANCE
+                    }) =>
+                {
+                    range.end = segment.range.end;
+                    line_width += space_width + segment.width;
+                }
+                _ => {
+                    if let Some(range) = line.take() {
+                        lines.push(range);
+                        widest_line = widest_line.max(line_width);
+                    }
+                    line = Some(segment.range.clone());
+                    line_width = segment.width;
+                }
+            }
+            space_width = segment.space_width;
+
+            if segment.line_break {
+                // a segment always follows a line break, so the next line is started even if it is empty
+                lines.push(line.take().unwrap());
+                widest_line = widest_line.max(line_width);
+            }
+        }
+
+        if let Some(range) = line {
+            lines.push(range);
+            widest_line = widest_line.max(line_width);
+        }
+
+        (lines, widest_line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(range: Range<usize>, space_width: f32, line_break: bool) -> TextSegment {
+        TextSegment {
+            width: range.len() as f32,
+            range,
+            space_width,
+            line_break,
+        }
+    }
+
+    #[test]
+    fn wrap_lines() {
+        // "hello big world"
+        let measure = TextMeasure {
+            segments: vec![
+                segment(0..5, 1.0, false),
+                segment(6..9, 1.0, false),
+                segment(10..15, 0.0, false),
+            ],
+            line_height: 2.0,
+        };
+
+        assert_eq!(measure.lines(None), vec![0..15]);
+        assert_eq!(measure.size(None), Vec2::new(15.0, 2.0));
+        assert_eq!(measure.lines(Some(9.0)), vec![0..9, 10..15]);
+        assert_eq!(measure.size(Some(9.0)), Vec2::new(9.0, 4.0));
+        // words wider than the available width get their own line
+        assert_eq!(measure.lines(Some(2.0)), vec![0..5, 6..9, 10..15]);
+        assert_eq!(measure.size(Some(2.0)), Vec2::new(5.0, 6.0));
+    }
+
+    #[test]
+    fn line_breaks() {
+        // "a\n\nb"
+        let measure = TextMeasure {
+            segments: vec![
+                segment(0..1, 0.0, true),
+                segment(2..2, 0.0, true),
+                segment(3..4, 0.0, false),
+            ],
+            line_height: 1.0,
+        };
+
+        assert_eq!(measure.lines(None), vec![0..1, 2..2, 3..4]);
+        assert_eq!(measure.size(None), Vec2::new(1.0, 3.0));
+    }
+}
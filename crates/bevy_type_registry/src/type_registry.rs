@@ -84,6 +84,7 @@ pub struct ComponentRegistration {
     pub long_name: &'static str,
     component_add_fn: fn(&mut World, resources: &Resources, Entity, &dyn Property),
     component_apply_fn: fn(&mut World, Entity, &dyn Property),
+    component_remove_fn: fn(&mut World, Entity),
     component_properties_fn: fn(&Archetype, usize) -> &dyn Properties,
     component_copy_fn: fn(&World, &mut World, &Resources, Entity, Entity),
     copy_to_scene_fn: fn(&World, &mut World, &Resources, Entity, Entity),
@@ -114,6 +115,12 @@ impl ComponentRegistrationDefaults {
         component.apply(property);
     }
 
+    fn component_remove<T: Component>(world: &mut World, entity: Entity) {
+        if world.get::<T>(entity).is_ok() {
+            world.remove_one::<T>(entity).unwrap();
+        }
+    }
+
     fn component_copy<T: Component + Properties + FromResources>(
         source_world: &World,
         destination_world: &mut World,
@@ -162,6 +169,7 @@ impl ComponentRegistration {
             ty,
             component_add_fn: ComponentRegistrationDefaults::component_add::<T>,
             component_apply_fn: ComponentRegistrationDefaults::component_apply::<T>,
+            component_remove_fn: ComponentRegistrationDefaults::component_remove::<T>,
             component_copy_fn: ComponentRegistrationDefaults::component_copy::<T>,
             component_properties_fn: ComponentRegistrationDefaults::component_properties::<T>,
             copy_from_scene_fn: ComponentRegistrationDefaults::component_copy::<T>,
@@ -191,6 +199,11 @@ impl ComponentRegistration {
         (self.component_apply_fn)(world, entity, property);
     }
 
+    /// Removes the component from `entity`, if it has one
+    pub fn remove_component_from_entity(&self, world: &mut World, entity: Entity) {
+        (self.component_remove_fn)(world, entity);
+    }
+
     pub fn get_component_properties<'a>(
         &self,
         archetype: &'a Archetype,
@@ -0,0 +1,176 @@
+use crate::{
+    entity::NodeComponents,
+    focus::{latest_ui_cursor_moved, ui_cursor_position},
+    Display, FocusPolicy, Interaction, PositionType, Style, UiScale, Val, ZIndex,
+};
+use bevy_app::{EventReader, Events};
+use bevy_asset::Handle;
+use bevy_ecs::{Commands, Entity, Local, Query, Res, ResMut, With};
+use bevy_math::{Rect, Size, Vec2};
+use bevy_sprite::{virtual_resolution::VirtualResolution, ColorMaterial};
+use bevy_transform::components::GlobalTransform;
+use bevy_window::{CursorIcon, CursorMoved, Windows};
+
+/// An image the ui draws at the cursor position instead of the system cursor. Platforms don't agree on custom cursor
+/// images, so these are drawn as a ui node on top of all other nodes.
+#[derive(Debug, Clone)]
+pub struct CursorImage {
+    pub material: Handle<ColorMaterial>,
+    pub size: Vec2,
+    /// The point of the image that points at things, in pixels from the top left corner of the image
+    pub hotspot: Vec2,
+}
+
+#[derive(Debug, Clone)]
+pub enum Cursor {
+    Icon(CursorIcon),
+    Image(CursorImage),
+}
+
+impl From<CursorIcon> for Cursor {
+    fn from(icon: CursorIcon) -> Self {
+        Cursor::Icon(icon)
+    }
+}
+
+/// The cursor shown while the pointer hovers a node. Only nodes with an [Interaction] are hovered.
+#[derive(Debug, Clone)]
+pub struct HoverCursor(pub Cursor);
+
+/// The cursor the ui shows in the primary window
+#[derive(Debug, Clone)]
+pub struct UiCursor {
+    /// Shown while no interactive node is hovered
+    pub default: Cursor,
+    /// Shown while a node with an [Interaction] but no [HoverCursor] is hovered, like buttons
+    pub hover: Cursor,
+    /// Disable this to set the cursor icon of the window yourself
+    pub enabled: bool,
+}
+
+impl Default for UiCursor {
+    fn default() -> Self {
+        UiCursor {
+            default: Cursor::Icon(CursorIcon::Default),
+            hover: Cursor::Icon(CursorIcon::Hand),
+            enabled: true,
+        }
+    }
+}
+
+/// Marks the node that draws [CursorImage]s
+#[derive(Debug, Default)]
+pub struct CursorImageNode;
+
+#[derive(Default)]
+pub struct UiCursorState {
+    cursor_moved_event_reader: EventReader<CursorMoved>,
+    cursor_position: Vec2,
+    image_node: Option<Entity>,
+    /// Whether the system cursor was hidden to show a [CursorImage]
+    hid_system_cursor: bool,
+}
+
+/// Shows the [UiCursor] cursor or the [HoverCursor] of the hovered node
+#[allow(clippy::too_many_arguments)]
+pub fn ui_cursor_system(
+    mut commands: Commands,
+    mut state: Local<UiCursorState>,
+    ui_cursor: Res<UiCursor>,
+    cursor_moved_events: Res<Events<CursorMoved>>,
+    virtual_resolution: Res<VirtualResolution>,
+    ui_scale: Res<UiScale>,
+    mut windows: ResMut<Windows>,
+    node_query: Query<(&Interaction, &GlobalTransform, Option<&HoverCursor>)>,
+    mut image_query: Query<With<CursorImageNode, (&mut Style, &mut Handle<ColorMaterial>)>>,
+) {
+    if let Some(cursor_moved) =
+        latest_ui_cursor_moved(&mut state.cursor_moved_event_reader, &cursor_moved_events)
+    {
+        state.cursor_position = ui_cursor_position(cursor_moved, &windows, &virtual_resolution);
+    }
+
+    let window = match windows.get_primary_mut() {
+        Some(window) => window,
+        None => return,
+    };
+
+    // the topmost hovered node decides. clicked nodes keep their cursor while they are dragged
+    let mut hovered = None;
+    let mut hovered_z = std::f32::NEG_INFINITY;
+    for (interaction, global_transform, hover_cursor) in node_query.iter() {
+        if *interaction != Interaction::None && global_transform.translation.z() > hovered_z {
+            hovered_z = global_transform.translation.z();
+            hovered = Some(hover_cursor.map_or(&ui_cursor.hover, |hover_cursor| &hover_cursor.0));
+        }
+    }
+
+    let image = if !ui_cursor.enabled {
+        None
+    } else {
+        match hovered.unwrap_or(&ui_cursor.default) {
+            Cursor::Icon(icon) => {
+                if window.cursor_icon() != *icon {
+                    window.set_cursor_icon(*icon);
+                }
+                None
+            }
+            Cursor::Image(image) => Some(image),
+        }
+    };
+
+    if image.is_some() != state.hid_system_cursor {
+        state.hid_system_cursor = image.is_some();
+        window.set_cursor_visibility(!state.hid_system_cursor);
+    }
+
+    let image = match image {
+        Some(image) => image,
+        None => {
+            if let Some(entity) = state.image_node {
+                if let Ok(mut style) = image_query.get_component_mut::<Style>(entity) {
+                    style.display = Display::None;
+                }
+            }
+            return;
+        }
+    };
+
+    // styles are in unscaled pixels
+    let cursor_position = ui_scale.window_to_ui(state.cursor_position);
+    let style = Style {
+        position_type: PositionType::Absolute,
+        position: Rect {
+            left: Val::Px(cursor_position.x() - image.hotspot.x()),
+            bottom: Val::Px(cursor_position.y() - image.size.y() + image.hotspot.y()),
+            ..Default::default()
+        },
+        size: Size::new(Val::Px(image.size.x()), Val::Px(image.size.y())),
+        ..Default::default()
+    };
+
+    match state
+        .image_node
+        .and_then(|entity| image_query.get_mut(entity).ok())
+    {
+        Some((mut node_style, mut material)) => {
+            *node_style = style;
+            if *material != image.material {
+                *material = image.material.clone();
+            }
+        }
+        None => {
+            commands
+                .spawn(NodeComponents {
+                    style,
+                    material: image.material.clone(),
+                    ..Default::default()
+                })
+                .with(CursorImageNode)
+                // the image is always under the cursor, so it must not block the nodes under it
+                .with(FocusPolicy::Pass)
+                .with(ZIndex::Global(i32::MAX));
+            state.image_node = commands.current_entity();
+        }
+    }
+}
@@ -1,12 +1,12 @@
 use super::Node;
 use crate::{
     render::UI_PIPELINE_HANDLE,
-    widget::{Button, Image, Text},
-    CalculatedSize, FocusPolicy, Interaction, Style,
+    widget::{Button, Checkbox, Image, ProgressBar, RadioButton, Slider, Text},
+    CalculatedSize, FocusPolicy, Interaction, Style, Val,
 };
 use bevy_asset::Handle;
 use bevy_ecs::Bundle;
-use bevy_math::Vec3;
+use bevy_math::{Size, Vec3};
 use bevy_render::{
     camera::{Camera, OrthographicProjection, VisibleEntities, WindowOrigin},
     draw::Draw,
@@ -14,8 +14,30 @@ use bevy_render::{
     pipeline::{DynamicBinding, PipelineSpecialization, RenderPipeline, RenderPipelines},
 };
 use bevy_sprite::{ColorMaterial, QUAD_HANDLE};
+use bevy_text::TextMeasure;
 use bevy_transform::prelude::{GlobalTransform, Transform};
 
+fn node_render_pipelines() -> RenderPipelines {
+    RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
+        UI_PIPELINE_HANDLE,
+        PipelineSpecialization {
+            dynamic_bindings: vec![
+                // Transform
+                DynamicBinding {
+                    bind_group: 1,
+                    binding: 0,
+                },
+                // Node_size
+                DynamicBinding {
+                    bind_group: 1,
+                    binding: 1,
+                },
+            ],
+            ..Default::default()
+        },
+    )])
+}
+
 #[derive(Bundle, Clone, Debug)]
 pub struct NodeComponents {
     pub node: Node,
@@ -32,24 +54,7 @@ impl Default for NodeComponents {
     fn default() -> Self {
         NodeComponents {
             mesh: QUAD_HANDLE,
-            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
-                UI_PIPELINE_HANDLE,
-                PipelineSpecialization {
-                    dynamic_bindings: vec![
-                        // Transform
-                        DynamicBinding {
-                            bind_group: 1,
-                            binding: 0,
-                        },
-                        // Node_size
-                        DynamicBinding {
-                            bind_group: 1,
-                            binding: 1,
-                        },
-                    ],
-                    ..Default::default()
-                },
-            )]),
+            render_pipelines: node_render_pipelines(),
             node: Default::default(),
             style: Default::default(),
             material: Default::default(),
@@ -78,24 +83,7 @@ impl Default for ImageComponents {
     fn default() -> Self {
         ImageComponents {
             mesh: QUAD_HANDLE,
-            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
-                UI_PIPELINE_HANDLE,
-                PipelineSpecialization {
-                    dynamic_bindings: vec![
-                        // Transform
-                        DynamicBinding {
-                            bind_group: 1,
-                            binding: 0,
-                        },
-                        // Node_size
-                        DynamicBinding {
-                            bind_group: 1,
-                            binding: 1,
-                        },
-                    ],
-                    ..Default::default()
-                },
-            )]),
+            render_pipelines: node_render_pipelines(),
             node: Default::default(),
             image: Default::default(),
             calculated_size: Default::default(),
@@ -115,6 +103,7 @@ pub struct TextComponents {
     pub draw: Draw,
     pub text: Text,
     pub calculated_size: CalculatedSize,
+    pub text_measure: TextMeasure,
     pub focus_policy: FocusPolicy,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
@@ -131,6 +120,7 @@ impl Default for TextComponents {
             text: Default::default(),
             node: Default::default(),
             calculated_size: Default::default(),
+            text_measure: Default::default(),
             style: Default::default(),
             transform: Default::default(),
             global_transform: Default::default(),
@@ -158,24 +148,7 @@ impl Default for ButtonComponents {
         ButtonComponents {
             button: Button,
             mesh: QUAD_HANDLE,
-            render_pipelines: RenderPipelines::from_pipelines(vec![RenderPipeline::specialized(
-                UI_PIPELINE_HANDLE,
-                PipelineSpecialization {
-                    dynamic_bindings: vec![
-                        // Transform
-                        DynamicBinding {
-                            bind_group: 1,
-                            binding: 0,
-                        },
-                        // Node_size
-                        DynamicBinding {
-                            bind_group: 1,
-                            binding: 1,
-                        },
-                    ],
-                    ..Default::default()
-                },
-            )]),
+            render_pipelines: node_render_pipelines(),
             interaction: Default::default(),
             focus_policy: Default::default(),
             node: Default::default(),
@@ -188,6 +161,150 @@ impl Default for ButtonComponents {
     }
 }
 
+/// A [Checkbox] whose material is set from [WidgetMaterials](crate::widget::WidgetMaterials)
+#[derive(Bundle, Clone, Debug)]
+pub struct CheckboxComponents {
+    pub node: Node,
+    pub checkbox: Checkbox,
+    pub style: Style,
+    pub interaction: Interaction,
+    pub focus_policy: FocusPolicy,
+    pub mesh: Handle<Mesh>, // TODO: maybe abstract this out
+    pub material: Handle<ColorMaterial>,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for CheckboxComponents {
+    fn default() -> Self {
+        CheckboxComponents {
+            checkbox: Default::default(),
+            style: Style {
+                size: Size::new(Val::Px(20.0), Val::Px(20.0)),
+                ..Default::default()
+            },
+            mesh: QUAD_HANDLE,
+            render_pipelines: node_render_pipelines(),
+            interaction: Default::default(),
+            focus_policy: Default::default(),
+            node: Default::default(),
+            material: Default::default(),
+            draw: Default::default(),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+/// A [RadioButton] whose material is set from [WidgetMaterials](crate::widget::WidgetMaterials)
+#[derive(Bundle, Clone, Debug)]
+pub struct RadioButtonComponents {
+    pub node: Node,
+    pub radio_button: RadioButton,
+    pub style: Style,
+    pub interaction: Interaction,
+    pub focus_policy: FocusPolicy,
+    pub mesh: Handle<Mesh>, // TODO: maybe abstract this out
+    pub material: Handle<ColorMaterial>,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for RadioButtonComponents {
+    fn default() -> Self {
+        RadioButtonComponents {
+            radio_button: Default::default(),
+            style: Style {
+                size: Size::new(Val::Px(20.0), Val::Px(20.0)),
+                ..Default::default()
+            },
+            mesh: QUAD_HANDLE,
+            render_pipelines: node_render_pipelines(),
+            interaction: Default::default(),
+            focus_policy: Default::default(),
+            node: Default::default(),
+            material: Default::default(),
+            draw: Default::default(),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+/// A [Slider]. A fill node is added as a child when the slider is spawned.
+#[derive(Bundle, Clone, Debug)]
+pub struct SliderComponents {
+    pub node: Node,
+    pub slider: Slider,
+    pub style: Style,
+    pub interaction: Interaction,
+    pub focus_policy: FocusPolicy,
+    pub mesh: Handle<Mesh>, // TODO: maybe abstract this out
+    pub material: Handle<ColorMaterial>,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for SliderComponents {
+    fn default() -> Self {
+        SliderComponents {
+            slider: Default::default(),
+            style: Style {
+                size: Size::new(Val::Px(200.0), Val::Px(20.0)),
+                ..Default::default()
+            },
+            mesh: QUAD_HANDLE,
+            render_pipelines: node_render_pipelines(),
+            interaction: Default::default(),
+            focus_policy: Default::default(),
+            node: Default::default(),
+            material: Default::default(),
+            draw: Default::default(),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
+/// A [ProgressBar]. A fill node is added as a child when the progress bar is spawned.
+#[derive(Bundle, Clone, Debug)]
+pub struct ProgressBarComponents {
+    pub node: Node,
+    pub progress_bar: ProgressBar,
+    pub style: Style,
+    pub mesh: Handle<Mesh>, // TODO: maybe abstract this out
+    pub material: Handle<ColorMaterial>,
+    pub draw: Draw,
+    pub render_pipelines: RenderPipelines,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for ProgressBarComponents {
+    fn default() -> Self {
+        ProgressBarComponents {
+            progress_bar: Default::default(),
+            style: Style {
+                size: Size::new(Val::Px(200.0), Val::Px(20.0)),
+                ..Default::default()
+            },
+            mesh: QUAD_HANDLE,
+            render_pipelines: node_render_pipelines(),
+            node: Default::default(),
+            material: Default::default(),
+            draw: Default::default(),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        }
+    }
+}
+
 #[derive(Bundle, Debug)]
 pub struct UiCameraComponents {
     pub camera: Camera,
@@ -4,57 +4,57 @@ use crate::{
 };
 use bevy_math::{Rect, Size};
 
-fn from_rect<T, U>(rect: Rect<U>) -> stretch::geometry::Rect<T>
-where
-    T: From<U>,
-{
+fn from_val(scale: f32, val: Val) -> stretch::style::Dimension {
+    match val {
+        Val::Px(value) => stretch::style::Dimension::Points(value * scale),
+        val => val.into(),
+    }
+}
+
+fn from_rect(scale: f32, rect: Rect<Val>) -> stretch::geometry::Rect<stretch::style::Dimension> {
     stretch::geometry::Rect {
-        start: rect.left.into(),
-        end: rect.right.into(),
+        start: from_val(scale, rect.left),
+        end: from_val(scale, rect.right),
         // NOTE: top and bottom are intentionally flipped. stretch has a flipped y-axis
-        top: rect.bottom.into(),
-        bottom: rect.top.into(),
+        top: from_val(scale, rect.bottom),
+        bottom: from_val(scale, rect.top),
     }
 }
 
-fn from_size<T, U>(size: Size<U>) -> stretch::geometry::Size<T>
-where
-    T: From<U>,
-{
+fn from_size(scale: f32, size: Size<Val>) -> stretch::geometry::Size<stretch::style::Dimension> {
     stretch::geometry::Size {
-        width: size.width.into(),
-        height: size.height.into(),
+        width: from_val(scale, size.width),
+        height: from_val(scale, size.height),
     }
 }
 
-impl From<&Style> for stretch::style::Style {
-    fn from(value: &Style) -> Self {
-        Self {
-            overflow: stretch::style::Overflow::Visible,
-            display: value.display.into(),
-            position_type: value.position_type.into(),
-            direction: value.direction.into(),
-            flex_direction: value.flex_direction.into(),
-            flex_wrap: value.flex_wrap.into(),
-            align_items: value.align_items.into(),
-            align_self: value.align_self.into(),
-            align_content: value.align_content.into(),
-            justify_content: value.justify_content.into(),
-            position: from_rect(value.position),
-            margin: from_rect(value.margin),
-            padding: from_rect(value.padding),
-            border: from_rect(value.border),
-            flex_grow: value.flex_grow,
-            flex_shrink: value.flex_shrink,
-            flex_basis: value.flex_basis.into(),
-            size: from_size(value.size),
-            min_size: from_size(value.min_size),
-            max_size: from_size(value.max_size),
-            aspect_ratio: match value.aspect_ratio {
-                Some(value) => stretch::number::Number::Defined(value),
-                None => stretch::number::Number::Undefined,
-            },
-        }
+/// Converts a style to stretch, with pixel values multiplied by the [UiScale](crate::UiScale) `scale`
+pub(crate) fn from_style(scale: f32, value: &Style) -> stretch::style::Style {
+    stretch::style::Style {
+        overflow: stretch::style::Overflow::Visible,
+        display: value.display.into(),
+        position_type: value.position_type.into(),
+        direction: value.direction.into(),
+        flex_direction: value.flex_direction.into(),
+        flex_wrap: value.flex_wrap.into(),
+        align_items: value.align_items.into(),
+        align_self: value.align_self.into(),
+        align_content: value.align_content.into(),
+        justify_content: value.justify_content.into(),
+        position: from_rect(scale, value.position),
+        margin: from_rect(scale, value.margin),
+        padding: from_rect(scale, value.padding),
+        border: from_rect(scale, value.border),
+        flex_grow: value.flex_grow,
+        flex_shrink: value.flex_shrink,
+        flex_basis: from_val(scale, value.flex_basis),
+        size: from_size(scale, value.size),
+        min_size: from_size(scale, value.min_size),
+        max_size: from_size(scale, value.max_size),
+        aspect_ratio: match value.aspect_ratio {
+            Some(value) => stretch::number::Number::Defined(value),
+            None => stretch::number::Number::Undefined,
+        },
     }
 }
 
@@ -1,17 +1,25 @@
 mod convert;
 
-use crate::{CalculatedSize, Node, Style};
+use crate::{CalculatedSize, Node, Style, UiScale};
 use bevy_ecs::{Changed, Entity, Query, Res, ResMut, With, Without};
 use bevy_math::Vec2;
+use bevy_sprite::virtual_resolution::VirtualResolution;
+use bevy_text::TextMeasure;
 use bevy_transform::prelude::{Children, Parent, Transform};
 use bevy_utils::HashMap;
 use bevy_window::{Window, WindowId, Windows};
+use convert::from_style;
 use std::fmt;
-use stretch::{number::Number, Stretch};
+use stretch::{
+    number::{Number, OrElse},
+    Stretch,
+};
 
 pub struct FlexSurface {
     entity_to_stretch: HashMap<Entity, stretch::node::Node>,
     window_nodes: HashMap<WindowId, stretch::node::Node>,
+    /// The [UiScale] pixel values in styles are multiplied with
+    scale: f32,
     stretch: Stretch,
 }
 
@@ -20,6 +28,7 @@ impl fmt::Debug for FlexSurface {
         f.debug_struct("FlexSurface")
             .field("entity_to_stretch", &self.entity_to_stretch)
             .field("window_nodes", &self.window_nodes)
+            .field("scale", &self.scale)
             .finish()
     }
 }
@@ -29,6 +38,7 @@ impl Default for FlexSurface {
         Self {
             entity_to_stretch: Default::default(),
             window_nodes: Default::default(),
+            scale: 1.0,
             stretch: Stretch::new(),
         }
     }
@@ -38,7 +48,7 @@ impl FlexSurface {
     pub fn upsert_node(&mut self, entity: Entity, style: &Style) {
         let mut added = false;
         let stretch = &mut self.stretch;
-        let stretch_style = style.into();
+        let stretch_style = from_style(self.scale, style);
         let stretch_node = self.entity_to_stretch.entry(entity).or_insert_with(|| {
             added = true;
             stretch.new_node(stretch_style, Vec::new()).unwrap()
@@ -52,12 +62,12 @@ impl FlexSurface {
     }
 
     pub fn upsert_leaf(&mut self, entity: Entity, style: &Style, calculated_size: CalculatedSize) {
-        let stretch = &mut self.stretch;
-        let stretch_style = style.into();
+        let stretch_style = from_style(self.scale, style);
+        let scale = self.scale;
         let measure = Box::new(move |constraints: stretch::geometry::Size<Number>| {
             let mut size = stretch::geometry::Size {
-                width: calculated_size.size.width,
-                height: calculated_size.size.height,
+                width: calculated_size.size.width * scale,
+                height: calculated_size.size.height * scale,
             };
             match (constraints.width, constraints.height) {
                 (Number::Undefined, Number::Undefined) => {}
@@ -77,6 +87,33 @@ impl FlexSurface {
             Ok(size)
         });
 
+        self.upsert_measured_leaf(entity, stretch_style, measure);
+    }
+
+    /// Inserts or updates a leaf that is sized by its text. The text is wrapped to the available width. Text is
+    /// measured at its scaled font size already.
+    pub fn upsert_text_leaf(&mut self, entity: Entity, style: &Style, text_measure: TextMeasure) {
+        let measure = Box::new(move |constraints: stretch::geometry::Size<Number>| {
+            let max_width = match constraints.width {
+                Number::Defined(width) => Some(width),
+                Number::Undefined => None,
+            };
+            let size = text_measure.size(max_width);
+            Ok(stretch::geometry::Size {
+                width: constraints.width.or_else(size.x()),
+                height: constraints.height.or_else(size.y()),
+            })
+        });
+
+        self.upsert_measured_leaf(entity, from_style(self.scale, style), measure);
+    }
+
+    fn upsert_measured_leaf(
+        &mut self,
+        entity: Entity,
+        stretch_style: stretch::style::Style,
+        measure: stretch::node::MeasureFunc,
+    ) {
         if let Some(stretch_node) = self.entity_to_stretch.get(&entity) {
             self.stretch
                 .set_style(*stretch_node, stretch_style)
@@ -85,7 +122,7 @@ impl FlexSurface {
                 .set_measure(*stretch_node, Some(measure))
                 .unwrap();
         } else {
-            let stretch_node = stretch.new_leaf(stretch_style, measure).unwrap();
+            let stretch_node = self.stretch.new_leaf(stretch_style, measure).unwrap();
             self.entity_to_stretch.insert(entity, stretch_node);
         }
     }
@@ -104,8 +141,17 @@ impl FlexSurface {
     }
 
     pub fn update_window(&mut self, window: &Window) {
+        self.update_window_size(
+            window.id(),
+            Vec2::new(window.width() as f32, window.height() as f32),
+        );
+    }
+
+    /// Sets the size of the root node of `window_id`, which is the size of the window unless a
+    /// [VirtualResolution](bevy_sprite::virtual_resolution::VirtualResolution) is used
+    pub fn update_window_size(&mut self, window_id: WindowId, size: Vec2) {
         let stretch = &mut self.stretch;
-        let node = self.window_nodes.entry(window.id()).or_insert_with(|| {
+        let node = self.window_nodes.entry(window_id).or_insert_with(|| {
             stretch
                 .new_node(stretch::style::Style::default(), Vec::new())
                 .unwrap()
@@ -116,8 +162,8 @@ impl FlexSurface {
                 *node,
                 stretch::style::Style {
                     size: stretch::geometry::Size {
-                        width: stretch::style::Dimension::Points(window.width() as f32),
-                        height: stretch::style::Dimension::Points(window.height() as f32),
+                        width: stretch::style::Dimension::Points(size.x()),
+                        height: stretch::style::Dimension::Points(size.y()),
                     },
                     ..Default::default()
                 },
@@ -157,32 +203,110 @@ impl FlexSurface {
 unsafe impl Send for FlexSurface {}
 unsafe impl Sync for FlexSurface {}
 
+fn upsert_node(
+    flex_surface: &mut FlexSurface,
+    entity: Entity,
+    style: &Style,
+    calculated_size: Option<&CalculatedSize>,
+    text_measure: Option<&TextMeasure>,
+) {
+    // TODO: remove node from old hierarchy if its root has changed
+    if let Some(text_measure) = text_measure {
+        flex_surface.upsert_text_leaf(entity, style, text_measure.clone());
+    } else if let Some(calculated_size) = calculated_size {
+        flex_surface.upsert_leaf(entity, style, *calculated_size);
+    } else {
+        flex_surface.upsert_node(entity, style);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn flex_node_system(
     windows: Res<Windows>,
+    virtual_resolution: Res<VirtualResolution>,
+    ui_scale: Res<UiScale>,
     mut flex_surface: ResMut<FlexSurface>,
     root_node_query: Query<With<Node, Without<Parent, Entity>>>,
-    node_query: Query<With<Node, (Entity, Changed<Style>, Option<&CalculatedSize>)>>,
-    changed_size_query: Query<With<Node, (Entity, &Style, Changed<CalculatedSize>)>>,
+    node_query: Query<
+        With<
+            Node,
+            (
+                Entity,
+                Changed<Style>,
+                Option<&CalculatedSize>,
+                Option<&TextMeasure>,
+            ),
+        >,
+    >,
+    changed_size_query: Query<
+        With<
+            Node,
+            (
+                Entity,
+                &Style,
+                Changed<CalculatedSize>,
+                Option<&TextMeasure>,
+            ),
+        >,
+    >,
+    all_node_query: Query<
+        With<
+            Node,
+            (
+                Entity,
+                &Style,
+                Option<&CalculatedSize>,
+                Option<&TextMeasure>,
+            ),
+        >,
+    >,
     children_query: Query<With<Node, (Entity, Changed<Children>)>>,
     mut node_transform_query: Query<(Entity, &mut Node, &mut Transform, Option<&Parent>)>,
 ) {
     // update window root nodes
     for window in windows.iter() {
-        flex_surface.update_window(window);
+        let window_size = Vec2::new(window.width() as f32, window.height() as f32);
+        let render_size = if window.id() == WindowId::primary() {
+            virtual_resolution.render_size(window_size)
+        } else {
+            window_size
+        };
+        flex_surface.update_window_size(window.id(), render_size);
     }
 
-    // update changed nodes
-    for (entity, style, calculated_size) in node_query.iter() {
-        // TODO: remove node from old hierarchy if its root has changed
-        if let Some(calculated_size) = calculated_size {
-            flex_surface.upsert_leaf(entity, &style, *calculated_size);
-        } else {
-            flex_surface.upsert_node(entity, &style);
+    if flex_surface.scale != ui_scale.scale {
+        // every node is laid out again with the new scale
+        flex_surface.scale = ui_scale.scale;
+        for (entity, style, calculated_size, text_measure) in all_node_query.iter() {
+            upsert_node(
+                &mut flex_surface,
+                entity,
+                style,
+                calculated_size,
+                text_measure,
+            );
+        }
+    } else {
+        // update changed nodes
+        for (entity, style, calculated_size, text_measure) in node_query.iter() {
+            upsert_node(
+                &mut flex_surface,
+                entity,
+                &style,
+                calculated_size,
+                text_measure,
+            );
         }
     }
 
-    for (entity, style, calculated_size) in changed_size_query.iter() {
-        flex_surface.upsert_leaf(entity, &style, *calculated_size);
+    // text is only measured again when its content, its style or the ui scale changes, which also changes its
+    // calculated size
+    for (entity, style, calculated_size, text_measure) in changed_size_query.iter() {
+        if let Some(text_measure) = text_measure {
+            flex_surface.upsert_text_leaf(entity, &style, text_measure.clone());
+        } else {
+            flex_surface.upsert_leaf(entity, &style, *calculated_size);
+        }
     }
 
     // TODO: handle removed nodes
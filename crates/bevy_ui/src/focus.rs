@@ -2,10 +2,11 @@ use crate::Node;
 use bevy_app::{EventReader, Events};
 use bevy_core::FloatOrd;
 use bevy_ecs::prelude::*;
-use bevy_input::{mouse::MouseButton, Input};
+use bevy_input::{mouse::MouseButton, WindowInput};
 use bevy_math::Vec2;
+use bevy_sprite::virtual_resolution::VirtualResolution;
 use bevy_transform::components::GlobalTransform;
-use bevy_window::CursorMoved;
+use bevy_window::{CursorMoved, WindowId, Windows};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Interaction {
@@ -39,10 +40,44 @@ pub struct State {
     hovered_entity: Option<Entity>,
 }
 
+/// The window ui nodes are laid out in. For now all nodes live in the primary window, so only its cursor and mouse
+/// buttons interact with them.
+pub(crate) fn ui_window() -> WindowId {
+    WindowId::primary()
+}
+
+/// The latest unread cursor movement in the [ui_window], ignoring the cursors of other windows
+pub(crate) fn latest_ui_cursor_moved<'a>(
+    reader: &mut EventReader<CursorMoved>,
+    cursor_moved_events: &'a Events<CursorMoved>,
+) -> Option<&'a CursorMoved> {
+    reader.find_latest(cursor_moved_events, |cursor_moved| {
+        cursor_moved.id == ui_window()
+    })
+}
+
+/// Converts a cursor position to the coordinates ui nodes are laid out in, which differ from window coordinates
+/// when a [VirtualResolution] is used
+pub(crate) fn ui_cursor_position(
+    cursor_moved: &CursorMoved,
+    windows: &Windows,
+    virtual_resolution: &VirtualResolution,
+) -> Vec2 {
+    match windows.get(cursor_moved.id) {
+        Some(window) => virtual_resolution.window_to_virtual(
+            Vec2::new(window.width() as f32, window.height() as f32),
+            cursor_moved.position,
+        ),
+        None => cursor_moved.position,
+    }
+}
+
 pub fn ui_focus_system(
     mut state: Local<State>,
-    mouse_button_input: Res<Input<MouseButton>>,
+    mouse_button_input: Res<WindowInput<MouseButton>>,
     cursor_moved_events: Res<Events<CursorMoved>>,
+    windows: Res<Windows>,
+    virtual_resolution: Res<VirtualResolution>,
     mut node_query: Query<(
         Entity,
         &Node,
@@ -51,11 +86,13 @@ pub fn ui_focus_system(
         Option<&FocusPolicy>,
     )>,
 ) {
-    if let Some(cursor_moved) = state.cursor_moved_event_reader.latest(&cursor_moved_events) {
-        state.cursor_position = cursor_moved.position;
+    if let Some(cursor_moved) =
+        latest_ui_cursor_moved(&mut state.cursor_moved_event_reader, &cursor_moved_events)
+    {
+        state.cursor_position = ui_cursor_position(cursor_moved, &windows, &virtual_resolution);
     }
 
-    if mouse_button_input.just_released(MouseButton::Left) {
+    if mouse_button_input.just_released(ui_window(), MouseButton::Left) {
         for (_entity, _node, _global_transform, interaction, _focus_policy) in node_query.iter_mut()
         {
             if let Some(mut interaction) = interaction {
@@ -66,7 +103,7 @@ pub fn ui_focus_system(
         }
     }
 
-    let mouse_clicked = mouse_button_input.just_pressed(MouseButton::Left);
+    let mouse_clicked = mouse_button_input.just_pressed(ui_window(), MouseButton::Left);
     let mut hovered_entity = None;
 
     {
@@ -1,26 +1,42 @@
 mod anchors;
+mod cursor;
 pub mod entity;
 mod flex;
 mod focus;
+pub mod loading_screen;
 mod margins;
+pub mod minimap;
+mod navigation;
 mod node;
 mod render;
+pub mod transition;
+mod ui_scale;
 pub mod update;
 pub mod widget;
+mod world_anchor;
 
 pub use anchors::*;
+pub use cursor::*;
 pub use flex::*;
 pub use focus::*;
 pub use margins::*;
+pub use navigation::*;
 pub use node::*;
 pub use render::*;
+pub use ui_scale::*;
+pub use world_anchor::*;
 
 pub mod prelude {
     pub use crate::{
         entity::*,
         node::*,
-        widget::{Button, Text},
-        Anchors, Interaction, Margins,
+        widget::{
+            Button, ButtonClicked, Checkbox, CheckboxChanged, ProgressBar, RadioButton,
+            RadioButtonChanged, Slider, SliderChanged, Text, WidgetMaterials,
+        },
+        Anchors, Cursor, CursorImage, Focusable, HoverCursor, Interaction, Margins,
+        NavigationBindings, NavigationCancelled, OffScreenIndicator, UiCursor, UiFocus, UiScale,
+        WorldAnchor,
     };
 }
 
@@ -39,12 +55,40 @@ pub mod stage {
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.init_resource::<FlexSurface>()
+            .init_resource::<widget::WidgetMaterials>()
+            .init_resource::<UiCursor>()
+            .init_resource::<UiFocus>()
+            .init_resource::<UiScale>()
+            .init_resource::<NavigationBindings>()
+            .add_event::<NavigationCancelled>()
+            .add_event::<widget::ButtonClicked>()
+            .add_event::<widget::CheckboxChanged>()
+            .add_event::<widget::RadioButtonChanged>()
+            .add_event::<widget::SliderChanged>()
             .add_stage_before(bevy_app::stage::POST_UPDATE, stage::UI)
             .add_system_to_stage(bevy_app::stage::PRE_UPDATE, ui_focus_system.system())
+            .add_system_to_stage(bevy_app::stage::PRE_UPDATE, ui_cursor_system.system())
+            // must come after ui_focus_system, which unhovers nodes that are not under the cursor
+            .add_system_to_stage(bevy_app::stage::PRE_UPDATE, ui_navigation_system.system())
+            .add_system_to_stage(bevy_app::stage::PRE_UPDATE, widget::button_system.system())
+            .add_system_to_stage(
+                bevy_app::stage::PRE_UPDATE,
+                widget::checkbox_system.system(),
+            )
+            .add_system_to_stage(
+                bevy_app::stage::PRE_UPDATE,
+                widget::radio_button_system.system(),
+            )
+            .add_system_to_stage(bevy_app::stage::PRE_UPDATE, widget::slider_system.system())
             // add these stages to front because these must run before transform update systems
+            .add_system_to_stage(stage::UI, widget::localized_text_system.system())
             .add_system_to_stage(stage::UI, widget::text_system.system())
             .add_system_to_stage(stage::UI, widget::image_node_system.system())
+            .add_system_to_stage(stage::UI, widget::widget_fill_spawn_system.system())
+            .add_system_to_stage(stage::UI, widget::widget_fill_system.system())
+            .add_system_to_stage(stage::UI, widget::toggle_material_system.system())
             .add_system_to_stage(stage::UI, ui_z_system.system())
+            .add_system_to_stage(stage::UI, world_anchor_system.system())
             .add_system_to_stage(stage::UI, flex_node_system.system())
             .add_system_to_stage(bevy_render::stage::DRAW, widget::draw_text_system.system());
 
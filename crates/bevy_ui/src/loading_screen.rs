@@ -0,0 +1,261 @@
+//! Loading screens between [GameState]s.
+//!
+//! A loading state loads a collection of assets when the game enters it, shows a progress bar while they load and moves
+//! the game to the next state once every asset, and every asset they depend on, is loaded. Add a
+//! [LoadingScreenPlugin] for the game state type, then register loading states with
+//! [AddLoadingState::add_loading_state]. The built-in loading screen needs a ui camera.
+
+use crate::{
+    entity::NodeComponents, widget::ProgressBar, AlignItems, JustifyContent, PositionType, Style,
+    Val,
+};
+use bevy_app::prelude::*;
+use bevy_asset::{AssetServer, Assets, HandleUntyped, LoadProgress};
+use bevy_ecs::{Commands, Entity, IntoQuerySystem, Local, Query, Res, ResMut, With};
+use bevy_math::Size;
+use bevy_render::prelude::Color;
+use bevy_sprite::ColorMaterial;
+use bevy_transform::prelude::{BuildChildren, DespawnRecursiveExt};
+use std::marker::PhantomData;
+
+/// A state that loads assets before the game moves on to `next`
+#[derive(Debug)]
+struct LoadingStep<S> {
+    state: S,
+    next: S,
+    paths: Vec<String>,
+    handles: Vec<HandleUntyped>,
+}
+
+/// The loading states of the game state type `S`, and how far along the current one is
+#[derive(Debug)]
+pub struct LoadingScreen<S> {
+    steps: Vec<LoadingStep<S>>,
+    progress: Option<LoadProgress>,
+    pub style: LoadingScreenStyle,
+}
+
+impl<S> Default for LoadingScreen<S> {
+    fn default() -> Self {
+        LoadingScreen {
+            steps: Vec::new(),
+            progress: None,
+            style: Default::default(),
+        }
+    }
+}
+
+impl<S: PartialEq> LoadingScreen<S> {
+    /// Makes `state` a loading state that loads the assets at `paths` and then moves on to `next`
+    pub fn add_state(&mut self, state: S, next: S, paths: &[&str]) {
+        self.steps.push(LoadingStep {
+            state,
+            next,
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+            handles: Vec::new(),
+        });
+    }
+
+    /// How far along loading the current state's assets is. `None` while the game isn't in a loading state.
+    pub fn progress(&self) -> Option<LoadProgress> {
+        self.progress
+    }
+
+    /// The handles of the assets a loading state loaded. They are kept, so the assets stay loaded in later states.
+    /// Empty until the game enters the state.
+    pub fn handles(&self, state: &S) -> &[HandleUntyped] {
+        self.steps
+            .iter()
+            .find(|step| step.state == *state)
+            .map_or(&[][..], |step| step.handles.as_slice())
+    }
+}
+
+/// How the built-in loading screen looks
+#[derive(Debug, Clone)]
+pub struct LoadingScreenStyle {
+    /// Shows a full screen node with a [ProgressBar] while loading. Turn it off to show a custom loading screen that
+    /// reads [LoadingScreen::progress].
+    pub spawn_ui: bool,
+    pub background: Color,
+    /// The size of the progress bar. It uses the [WidgetMaterials](crate::widget::WidgetMaterials).
+    pub bar_size: Size<Val>,
+}
+
+impl Default for LoadingScreenStyle {
+    fn default() -> Self {
+        LoadingScreenStyle {
+            spawn_ui: true,
+            background: Color::rgb(0.05, 0.05, 0.05),
+            bar_size: Size::new(Val::Percent(50.0), Val::Px(20.0)),
+        }
+    }
+}
+
+/// Marks the root node of the built-in loading screen
+#[derive(Debug, Clone, Default)]
+pub struct LoadingScreenNode;
+
+/// Marks the progress bar of the built-in loading screen
+#[derive(Debug, Clone, Default)]
+pub struct LoadingScreenBar;
+
+/// Adds loading states to the [GameState] of type `S`. Add the game state before this plugin.
+pub struct LoadingScreenPlugin<S> {
+    pub style: LoadingScreenStyle,
+    marker: PhantomData<S>,
+}
+
+impl<S> Default for LoadingScreenPlugin<S> {
+    fn default() -> Self {
+        LoadingScreenPlugin {
+            style: Default::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<S> LoadingScreenPlugin<S> {
+    pub fn with_style(style: LoadingScreenStyle) -> Self {
+        LoadingScreenPlugin {
+            style,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<S: Clone + PartialEq + Send + Sync + 'static> Plugin for LoadingScreenPlugin<S> {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(LoadingScreen::<S> {
+            style: self.style.clone(),
+            ..Default::default()
+        })
+        .add_system_to_stage(stage::PRE_UPDATE, loading_screen_system::<S>.system());
+    }
+}
+
+pub trait AddLoadingState {
+    /// Makes `state` a loading state: entering it loads the assets at `paths`, and the game moves on to `next` once
+    /// they are loaded. Needs a [LoadingScreenPlugin] for `S`.
+    fn add_loading_state<S>(&mut self, state: S, next: S, paths: &[&str]) -> &mut Self
+    where
+        S: PartialEq + Send + Sync + 'static;
+}
+
+impl AddLoadingState for AppBuilder {
+    fn add_loading_state<S>(&mut self, state: S, next: S, paths: &[&str]) -> &mut Self
+    where
+        S: PartialEq + Send + Sync + 'static,
+    {
+        self.resources()
+            .get_mut::<LoadingScreen<S>>()
+            .expect("add a LoadingScreenPlugin for this game state before adding loading states")
+            .add_state(state, next, paths);
+        self
+    }
+}
+
+pub struct LoadingScreenSystemState<S> {
+    /// The game state the system saw last
+    current: Option<S>,
+}
+
+impl<S> Default for LoadingScreenSystemState<S> {
+    fn default() -> Self {
+        LoadingScreenSystemState { current: None }
+    }
+}
+
+fn spawn_loading_ui(
+    commands: &mut Commands,
+    color_materials: &mut Assets<ColorMaterial>,
+    style: &LoadingScreenStyle,
+) {
+    commands
+        .spawn(NodeComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: color_materials.add(style.background.into()),
+            ..Default::default()
+        })
+        .with(LoadingScreenNode)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeComponents {
+                    style: Style {
+                        size: style.bar_size,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with(ProgressBar::new(0.0))
+                .with(LoadingScreenBar);
+        });
+}
+
+/// Starts loading the assets of a loading state when the game enters it, updates the loading screen and moves the game
+/// to the next state once everything is loaded. Failed assets keep the game in the loading state, with the failures
+/// counted in [LoadingScreen::progress].
+#[allow(clippy::too_many_arguments)]
+pub fn loading_screen_system<S: Clone + PartialEq + Send + Sync + 'static>(
+    mut commands: Commands,
+    mut state: Local<LoadingScreenSystemState<S>>,
+    asset_server: Res<AssetServer>,
+    mut game_state: ResMut<GameState<S>>,
+    mut loading_screen: ResMut<LoadingScreen<S>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+    node_query: Query<With<LoadingScreenNode, Entity>>,
+    mut bar_query: Query<With<LoadingScreenBar, &mut ProgressBar>>,
+) {
+    let current = game_state.current().clone();
+    let step_index = loading_screen
+        .steps
+        .iter()
+        .position(|step| step.state == current);
+
+    if state.current.as_ref() != Some(&current) {
+        state.current = Some(current);
+        loading_screen.progress = None;
+        for entity in node_query.iter() {
+            commands.despawn_recursive(entity);
+        }
+
+        if let Some(step_index) = step_index {
+            let step = &mut loading_screen.steps[step_index];
+            if step.handles.is_empty() {
+                step.handles = step
+                    .paths
+                    .iter()
+                    .map(|path| asset_server.load_untyped(path.as_str()))
+                    .collect();
+            }
+            if loading_screen.style.spawn_ui {
+                spawn_loading_ui(&mut commands, &mut color_materials, &loading_screen.style);
+            }
+        }
+    }
+
+    let step_index = match step_index {
+        Some(step_index) => step_index,
+        None => return,
+    };
+    let step = &loading_screen.steps[step_index];
+    let progress =
+        asset_server.get_group_load_progress(step.handles.iter().map(|handle| handle.id));
+    let next = step.next.clone();
+    loading_screen.progress = Some(progress);
+    for mut progress_bar in bar_query.iter_mut() {
+        if progress_bar.progress != progress.fraction() {
+            progress_bar.progress = progress.fraction();
+        }
+    }
+
+    if progress.is_done() {
+        game_state.set(next);
+    }
+}
@@ -0,0 +1,530 @@
+//! A top-down map of the area around the player.
+//!
+//! [MinimapPlugin] adds an orthographic camera that looks down on the [Minimap] center and a pass that renders the
+//! main pass entities on the minimap's [RenderLayers] to [MINIMAP_TEXTURE_HANDLE]. Show it with an image node that
+//! uses [MINIMAP_MATERIAL_HANDLE] and a [MinimapImage] marker, which also gets an icon for every [MinimapIcon] entity.
+//! Add the plugin after the other render plugins, so the minimap pass waits for the same nodes as the main pass.
+
+use crate::{entity::NodeComponents, render, Display, FocusPolicy, PositionType, Style, Val};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{Commands, Entity, IntoQuerySystem, Query, QuerySet, Res, With};
+use bevy_math::{Mat4, Rect, Size, Vec2, Vec3};
+use bevy_render::{
+    camera::{ActiveCameras, Camera, DepthCalculation, RenderLayers, VisibleEntities},
+    pass::{
+        LoadOp, Operations, PassDescriptor, RenderPassDepthStencilAttachmentDescriptor,
+        TextureAttachment,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, MainPass, Msaa, MsaaTexture},
+        AssetTextureNode, CameraNode, PassNode, RenderGraph, TextureNode,
+    },
+    texture::{
+        Extent3d, Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+    },
+};
+use bevy_sprite::ColorMaterial;
+use bevy_transform::prelude::{
+    BuildChildren, DespawnRecursiveExt, GlobalTransform, Parent, Transform,
+};
+use bevy_type_registry::TypeUuid;
+use bevy_utils::HashSet;
+
+/// The texture the minimap pass renders to
+pub const MINIMAP_TEXTURE_HANDLE: Handle<Texture> =
+    Handle::weak_from_u64(Texture::TYPE_UUID, 4410827936150283367);
+
+/// A material that shows [MINIMAP_TEXTURE_HANDLE]
+pub const MINIMAP_MATERIAL_HANDLE: Handle<ColorMaterial> =
+    Handle::weak_from_u64(ColorMaterial::TYPE_UUID, 16032779542190846553);
+
+pub mod node {
+    pub const MINIMAP_CAMERA: &str = "minimap_camera";
+    pub const MINIMAP_TEXTURE: &str = "minimap_texture";
+    pub const MINIMAP_SAMPLED_COLOR_ATTACHMENT: &str = "minimap_sampled_color_attachment";
+    pub const MINIMAP_DEPTH_TEXTURE: &str = "minimap_depth_texture";
+    pub const MINIMAP_PASS: &str = "minimap_pass";
+}
+
+pub mod camera {
+    pub const MINIMAP_CAMERA: &str = "MinimapCamera";
+}
+
+/// What the minimap shows. The map is square and always shows the same area, whatever the size of the node it is
+/// shown in.
+#[derive(Debug, Clone)]
+pub struct Minimap {
+    /// The entity the map is centered on, usually the player
+    pub target: Option<Entity>,
+    /// The center of the map while it has no target
+    pub center: Vec3,
+    /// Half the width of the area the map shows
+    pub extent: f32,
+    /// The map shows everything within this distance above and below its center
+    pub height: f32,
+    /// Turns the map with the target, so the direction the target faces is always up. Otherwise -Z is up.
+    pub rotate_with_target: bool,
+    /// The layers the minimap camera draws. Put entities on other layers to leave them off the map, or on a layer
+    /// only the minimap draws to show them only on the map.
+    pub layers: RenderLayers,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Minimap {
+            target: None,
+            center: Vec3::zero(),
+            extent: 50.0,
+            height: 100.0,
+            rotate_with_target: false,
+            layers: Default::default(),
+        }
+    }
+}
+
+impl Minimap {
+    /// Where the map is centered and how it is turned, given the transform of its target
+    pub fn view(&self, target: Option<&GlobalTransform>) -> MinimapView {
+        let north = -Vec3::unit_z();
+        let (center, up) = match target {
+            Some(target) => {
+                let forward = target.rotation * north;
+                let forward = Vec3::new(forward.x(), 0.0, forward.z());
+                let up = if self.rotate_with_target && forward.length_squared() > 1e-6 {
+                    forward.normalize()
+                } else {
+                    north
+                };
+                (target.translation, up)
+            }
+            None => (self.center, north),
+        };
+        MinimapView {
+            center,
+            up,
+            extent: self.extent,
+        }
+    }
+}
+
+/// The area a [Minimap] shows in one frame
+#[derive(Debug, Clone, Copy)]
+pub struct MinimapView {
+    pub center: Vec3,
+    /// The horizontal direction that points up on the map
+    pub up: Vec3,
+    pub extent: f32,
+}
+
+impl MinimapView {
+    /// The direction that points right on the map
+    pub fn right(&self) -> Vec3 {
+        -Vec3::unit_y().cross(self.up)
+    }
+
+    /// Where `position` is shown on the map, from -1.0 to 1.0 between its edges with y pointing up
+    pub fn map_position(&self, position: Vec3) -> Vec2 {
+        let offset = position - self.center;
+        Vec2::new(offset.dot(self.right()), offset.dot(self.up)) / self.extent
+    }
+
+    fn camera_transform(&self, height: f32) -> GlobalTransform {
+        GlobalTransform::from_translation(self.center + Vec3::unit_y() * height)
+            .looking_at(self.center, self.up)
+    }
+}
+
+/// Marks an image node that shows the minimap. It gets a child node for every [MinimapIcon].
+#[derive(Debug, Clone, Default)]
+pub struct MinimapImage;
+
+/// Shows an icon for the entity on every [MinimapImage]
+#[derive(Debug, Clone)]
+pub struct MinimapIcon {
+    pub material: Handle<ColorMaterial>,
+    /// The size of the icon in pixels
+    pub size: f32,
+    /// Keeps the icon on the edge of the map while the entity is outside of it, for example for objectives.
+    /// Otherwise the icon is hidden.
+    pub clamp_to_edge: bool,
+}
+
+impl Default for MinimapIcon {
+    fn default() -> Self {
+        MinimapIcon {
+            material: Default::default(),
+            size: 8.0,
+            clamp_to_edge: false,
+        }
+    }
+}
+
+/// The node that shows the [MinimapIcon] of `entity` on a [MinimapImage]
+#[derive(Debug, Clone)]
+pub struct MinimapIconNode {
+    pub entity: Entity,
+}
+
+/// Marks the camera that renders the minimap pass
+#[derive(Debug, Default)]
+pub struct MinimapCamera;
+
+pub struct MinimapPlugin {
+    /// The width and height of the minimap texture
+    pub size: u32,
+    /// The color of the map where nothing was drawn
+    pub background: Color,
+}
+
+impl Default for MinimapPlugin {
+    fn default() -> Self {
+        MinimapPlugin {
+            size: 256,
+            background: Color::rgb(0.1, 0.1, 0.1),
+        }
+    }
+}
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if app.resources().get::<Minimap>().is_none() {
+            app.resources_mut().insert(Minimap::default());
+        }
+        app.add_startup_system(spawn_minimap_camera.system())
+            .add_system_to_stage(stage::UPDATE, minimap_icon_system.system())
+            .add_system_to_stage(stage::POST_UPDATE, minimap_camera_system.system());
+
+        let resources = app.resources();
+        resources
+            .get_mut::<ActiveCameras>()
+            .unwrap()
+            .add(camera::MINIMAP_CAMERA);
+        let size = Vec2::new(self.size as f32, self.size as f32);
+        resources
+            .get_mut::<Assets<Texture>>()
+            .unwrap()
+            .set_untracked(
+                MINIMAP_TEXTURE_HANDLE,
+                Texture::new_render_target(size, TextureFormat::default()),
+            );
+        resources
+            .get_mut::<Assets<ColorMaterial>>()
+            .unwrap()
+            .set_untracked(
+                MINIMAP_MATERIAL_HANDLE,
+                ColorMaterial::texture(MINIMAP_TEXTURE_HANDLE),
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_minimap_graph(&mut render_graph, self.size, self.background);
+    }
+}
+
+fn spawn_minimap_camera(mut commands: Commands, minimap: Res<Minimap>) {
+    commands.spawn((
+        Camera {
+            name: Some(camera::MINIMAP_CAMERA.to_string()),
+            depth_calculation: DepthCalculation::Distance,
+            ..Default::default()
+        },
+        MinimapCamera,
+        minimap.layers,
+        VisibleEntities::default(),
+        Transform::default(),
+        GlobalTransform::default(),
+    ));
+}
+
+/// Moves the [MinimapCamera] above the [Minimap] center. The camera has no projection component, because the camera
+/// systems would fit it to the window, so its projection is set here.
+pub fn minimap_camera_system(
+    minimap: Res<Minimap>,
+    mut queries: QuerySet<(
+        Query<&GlobalTransform>,
+        Query<
+            With<
+                MinimapCamera,
+                (
+                    &mut Camera,
+                    &mut RenderLayers,
+                    &mut Transform,
+                    &mut GlobalTransform,
+                ),
+            >,
+        >,
+    )>,
+) {
+    let target = minimap
+        .target
+        .and_then(|entity| queries.q0().get(entity).ok())
+        .cloned();
+    let view = minimap.view(target.as_ref());
+    let camera_transform = view.camera_transform(minimap.height);
+
+    for (mut camera, mut layers, mut transform, mut global_transform) in queries.q1_mut().iter_mut()
+    {
+        camera.projection_matrix = Mat4::orthographic_rh(
+            -minimap.extent,
+            minimap.extent,
+            -minimap.extent,
+            minimap.extent,
+            0.0,
+            minimap.height * 2.0,
+        );
+        if *layers != minimap.layers {
+            *layers = minimap.layers;
+        }
+        *transform = Transform {
+            translation: camera_transform.translation,
+            rotation: camera_transform.rotation,
+            scale: camera_transform.scale,
+        };
+        *global_transform = camera_transform.clone();
+    }
+}
+
+/// The style that places an icon at `position` on the map, or hides it if it is off the map
+fn icon_style(icon: &MinimapIcon, position: Vec2) -> Style {
+    let on_map = position.x().abs() <= 1.0 && position.y().abs() <= 1.0;
+    let position = position.max(-Vec2::one()).min(Vec2::one());
+    let half_size = icon.size / 2.0;
+    Style {
+        display: if on_map || icon.clamp_to_edge {
+            Display::Flex
+        } else {
+            Display::None
+        },
+        position_type: PositionType::Absolute,
+        position: Rect {
+            left: Val::Percent((position.x() + 1.0) * 50.0),
+            bottom: Val::Percent((position.y() + 1.0) * 50.0),
+            ..Default::default()
+        },
+        // centers the icon on its position
+        margin: Rect {
+            left: Val::Px(-half_size),
+            bottom: Val::Px(-half_size),
+            ..Default::default()
+        },
+        size: Size::new(Val::Px(icon.size), Val::Px(icon.size)),
+        ..Default::default()
+    }
+}
+
+/// Adds a [MinimapIconNode] to each [MinimapImage] for every [MinimapIcon], moves it to where its entity is on the
+/// map and removes it once the entity is gone. Runs before the ui layout, so icons follow the positions entities had
+/// at the end of the previous frame.
+pub fn minimap_icon_system(
+    mut commands: Commands,
+    minimap: Res<Minimap>,
+    target_query: Query<&GlobalTransform>,
+    icon_query: Query<(Entity, &MinimapIcon, &GlobalTransform)>,
+    image_query: Query<With<MinimapImage, Entity>>,
+    mut icon_node_query: Query<(
+        Entity,
+        &MinimapIconNode,
+        &Parent,
+        &mut Style,
+        &mut Handle<ColorMaterial>,
+    )>,
+) {
+    let target = minimap
+        .target
+        .and_then(|entity| target_query.get(entity).ok());
+    let view = minimap.view(target);
+
+    let mut shown_icons = HashSet::default();
+    for (node_entity, icon_node, parent, mut style, mut material) in icon_node_query.iter_mut() {
+        let (icon, transform) = match icon_query.get(icon_node.entity) {
+            Ok((_entity, icon, transform)) => (icon, transform),
+            Err(_) => {
+                commands.despawn_recursive(node_entity);
+                continue;
+            }
+        };
+        shown_icons.insert((parent.0, icon_node.entity));
+        *style = icon_style(icon, view.map_position(transform.translation));
+        if *material != icon.material {
+            *material = icon.material.clone();
+        }
+    }
+
+    for image_entity in image_query.iter() {
+        for (entity, icon, transform) in icon_query.iter() {
+            if shown_icons.contains(&(image_entity, entity)) {
+                continue;
+            }
+            commands
+                .spawn(NodeComponents {
+                    style: icon_style(icon, view.map_position(transform.translation)),
+                    material: icon.material.clone(),
+                    ..Default::default()
+                })
+                .with(MinimapIconNode { entity })
+                .with(FocusPolicy::Pass);
+            let icon_node = commands.current_entity().unwrap();
+            commands.push_children(image_entity, &[icon_node]);
+        }
+    }
+}
+
+fn add_minimap_graph(graph: &mut RenderGraph, size: u32, background: Color) {
+    let texture_descriptor = |format| TextureDescriptor {
+        size: Extent3d {
+            width: size,
+            height: size,
+            depth: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsage::OUTPUT_ATTACHMENT,
+    };
+
+    graph.add_system_node(
+        node::MINIMAP_CAMERA,
+        CameraNode::new(camera::MINIMAP_CAMERA),
+    );
+    graph.add_node(
+        node::MINIMAP_TEXTURE,
+        AssetTextureNode::new(MINIMAP_TEXTURE_HANDLE),
+    );
+    graph.add_node(
+        node::MINIMAP_DEPTH_TEXTURE,
+        TextureNode::multisampled(
+            texture_descriptor(TextureFormat::Depth32Float),
+            MsaaTexture::Depth,
+        ),
+    );
+
+    let mut minimap_pass_node = PassNode::<&MainPass>::new(PassDescriptor {
+        color_attachments: vec![Msaa::color_attachment_descriptor(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Clear(background),
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: Some(RenderPassDepthStencilAttachmentDescriptor {
+            attachment: TextureAttachment::Input("depth".to_string()),
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        }),
+        sample_count: 1,
+    });
+    minimap_pass_node.add_camera(camera::MINIMAP_CAMERA);
+
+    // the minimap pass draws the same entities as the main pass, so it depends on the same nodes
+    let main_pass_dependencies = graph
+        .iter_node_inputs(base::node::MAIN_PASS)
+        .map(|inputs| inputs.map(|(_edge, node)| node.id).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    graph.add_node(node::MINIMAP_PASS, minimap_pass_node);
+    for dependency in main_pass_dependencies {
+        // a node can be connected to the main pass more than once, in which case the edge already exists
+        let _ = graph.add_node_edge(dependency, node::MINIMAP_PASS);
+    }
+    graph
+        .add_node_edge(node::MINIMAP_CAMERA, node::MINIMAP_PASS)
+        .unwrap();
+    // the ui pass shows the minimap texture
+    graph
+        .add_node_edge(node::MINIMAP_PASS, render::node::UI_PASS)
+        .unwrap();
+
+    graph.add_node(
+        node::MINIMAP_SAMPLED_COLOR_ATTACHMENT,
+        TextureNode::multisampled(
+            texture_descriptor(TextureFormat::default()),
+            MsaaTexture::Color,
+        ),
+    );
+    graph
+        .add_slot_edge(
+            node::MINIMAP_SAMPLED_COLOR_ATTACHMENT,
+            TextureNode::OUT_TEXTURE,
+            node::MINIMAP_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+
+    graph
+        .add_slot_edge(
+            node::MINIMAP_TEXTURE,
+            AssetTextureNode::OUT_TEXTURE,
+            node::MINIMAP_PASS,
+            "color_resolve_target",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            node::MINIMAP_DEPTH_TEXTURE,
+            TextureNode::OUT_TEXTURE,
+            node::MINIMAP_PASS,
+            "depth",
+        )
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_math::Quat;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_near(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn map_position_follows_target() {
+        let minimap = Minimap {
+            extent: 10.0,
+            ..Default::default()
+        };
+        // facing +X
+        let target = GlobalTransform {
+            translation: Vec3::new(5.0, 0.0, 5.0),
+            rotation: Quat::from_rotation_y(-FRAC_PI_2),
+            ..Default::default()
+        };
+
+        let view = minimap.view(Some(&target));
+        assert_near(
+            view.map_position(Vec3::new(5.0, 3.0, 0.0)),
+            Vec2::new(0.0, 0.5),
+        );
+        assert_near(
+            view.map_position(Vec3::new(10.0, 0.0, 5.0)),
+            Vec2::new(0.5, 0.0),
+        );
+
+        let rotating = Minimap {
+            rotate_with_target: true,
+            ..minimap
+        };
+        let view = rotating.view(Some(&target));
+        assert_near(
+            view.map_position(Vec3::new(10.0, 0.0, 5.0)),
+            Vec2::new(0.0, 0.5),
+        );
+        assert_near(
+            view.map_position(Vec3::new(5.0, 0.0, 10.0)),
+            Vec2::new(0.5, 0.0),
+        );
+
+        // the camera looks down with the map's up direction at the top of its view
+        let camera = view.camera_transform(20.0);
+        assert!((camera.translation - Vec3::new(5.0, 20.0, 5.0)).length() < 1e-5);
+        assert!((camera.rotation * -Vec3::unit_z() + Vec3::unit_y()).length() < 1e-5);
+        assert!((camera.rotation * Vec3::unit_y() - Vec3::unit_x()).length() < 1e-5);
+    }
+}
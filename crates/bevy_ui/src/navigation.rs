@@ -0,0 +1,378 @@
+use crate::{focus::ui_window, Interaction, Node};
+use bevy_app::{EventReader, Events};
+use bevy_core::Time;
+use bevy_ecs::{Entity, Local, Query, Res, ResMut};
+use bevy_input::{
+    gamepad::{
+        Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, GamepadEvent,
+        GamepadEventType,
+    },
+    keyboard::KeyCode,
+    Axis, Input, WindowInput,
+};
+use bevy_math::Vec2;
+use bevy_transform::components::GlobalTransform;
+use bevy_window::CursorMoved;
+
+/// A node that can be focused with directional navigation. Neighbors are found from the positions of the nodes, unless
+/// they are set here.
+#[derive(Debug, Clone, Default)]
+pub struct Focusable {
+    pub up: Option<Entity>,
+    pub down: Option<Entity>,
+    pub left: Option<Entity>,
+    pub right: Option<Entity>,
+}
+
+/// The [Focusable] node that navigation input moves from and confirms. While the focus is visible, the focused node
+/// is [Interaction::Hovered], so widgets highlight it like a hovered node.
+#[derive(Debug, Default)]
+pub struct UiFocus {
+    pub focused: Option<Entity>,
+    /// The focus is hidden while the mouse is used and shown again on navigation input
+    pub visible: bool,
+}
+
+impl UiFocus {
+    pub fn focus(&mut self, entity: Entity) {
+        self.focused = Some(entity);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl NavigationDirection {
+    /// The direction in ui coordinates, where y points up
+    pub fn vector(self) -> Vec2 {
+        match self {
+            NavigationDirection::Up => Vec2::new(0.0, 1.0),
+            NavigationDirection::Down => Vec2::new(0.0, -1.0),
+            NavigationDirection::Left => Vec2::new(-1.0, 0.0),
+            NavigationDirection::Right => Vec2::new(1.0, 0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationAction {
+    Move(NavigationDirection),
+    /// Clicks the focused node
+    Confirm,
+    /// Sends a [NavigationCancelled] event, for example to close a menu
+    Cancel,
+}
+
+/// Sent when the [NavigationAction::Cancel] input is pressed
+#[derive(Debug, Clone)]
+pub struct NavigationCancelled {
+    pub focused: Option<Entity>,
+}
+
+/// The inputs that navigate the ui. Every connected gamepad can navigate.
+#[derive(Debug, Clone)]
+pub struct NavigationBindings {
+    pub keys: Vec<(KeyCode, NavigationAction)>,
+    pub gamepad_buttons: Vec<(GamepadButtonType, NavigationAction)>,
+    /// The left stick navigates when it is pushed further than this. Set it above 1.0 to turn stick navigation off.
+    pub stick_threshold: f32,
+    /// Seconds a direction is held before it repeats
+    pub repeat_delay: f32,
+    /// Seconds between repeats of a held direction
+    pub repeat_interval: f32,
+}
+
+impl Default for NavigationBindings {
+    fn default() -> Self {
+        use NavigationAction::*;
+        use NavigationDirection::*;
+        NavigationBindings {
+            keys: vec![
+                (KeyCode::Up, Move(Up)),
+                (KeyCode::Down, Move(Down)),
+                (KeyCode::Left, Move(Left)),
+                (KeyCode::Right, Move(Right)),
+                (KeyCode::Return, Confirm),
+                (KeyCode::Space, Confirm),
+                (KeyCode::Escape, Cancel),
+            ],
+            gamepad_buttons: vec![
+                (GamepadButtonType::DPadUp, Move(Up)),
+                (GamepadButtonType::DPadDown, Move(Down)),
+                (GamepadButtonType::DPadLeft, Move(Left)),
+                (GamepadButtonType::DPadRight, Move(Right)),
+                (GamepadButtonType::South, Confirm),
+                (GamepadButtonType::East, Cancel),
+            ],
+            stick_threshold: 0.6,
+            repeat_delay: 0.4,
+            repeat_interval: 0.1,
+        }
+    }
+}
+
+/// Finds the node a focus at `from` moves to in `direction`. Nodes that are close to the line from `from` in
+/// `direction` are preferred over nodes that are closer but off to the side.
+pub fn find_neighbor(
+    from: Vec2,
+    direction: NavigationDirection,
+    candidates: impl IntoIterator<Item = (Entity, Vec2)>,
+) -> Option<Entity> {
+    let direction = direction.vector();
+    let mut best = None;
+    let mut best_score = std::f32::INFINITY;
+    for (entity, position) in candidates {
+        let offset = position - from;
+        let distance = offset.dot(direction);
+        if distance <= 0.0 {
+            continue;
+        }
+
+        let sideways = (offset - direction * distance).length();
+        let score = distance + 2.0 * sideways;
+        if score < best_score {
+            best_score = score;
+            best = Some(entity);
+        }
+    }
+
+    best
+}
+
+#[derive(Default)]
+pub struct UiNavigationState {
+    cursor_moved_event_reader: EventReader<CursorMoved>,
+    gamepad_event_reader: EventReader<GamepadEvent>,
+    gamepads: Vec<Gamepad>,
+    held_direction: Option<NavigationDirection>,
+    repeat_timer: f32,
+    clicked: Option<Entity>,
+}
+
+impl UiNavigationState {
+    /// The direction the left stick of any gamepad is pushed in
+    fn stick_direction(
+        &self,
+        axes: &Axis<GamepadAxis>,
+        threshold: f32,
+    ) -> Option<NavigationDirection> {
+        for gamepad in self.gamepads.iter() {
+            let x = axes
+                .get(GamepadAxis(*gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or(0.0);
+            let y = axes
+                .get(GamepadAxis(*gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or(0.0);
+            if x.abs().max(y.abs()) <= threshold {
+                continue;
+            }
+
+            return Some(if x.abs() > y.abs() {
+                if x > 0.0 {
+                    NavigationDirection::Right
+                } else {
+                    NavigationDirection::Left
+                }
+            } else if y > 0.0 {
+                NavigationDirection::Up
+            } else {
+                NavigationDirection::Down
+            });
+        }
+
+        None
+    }
+}
+
+/// Moves the [UiFocus] between [Focusable] nodes and clicks or cancels with the [NavigationBindings]
+#[allow(clippy::too_many_arguments)]
+pub fn ui_navigation_system(
+    mut state: Local<UiNavigationState>,
+    mut focus: ResMut<UiFocus>,
+    mut cancelled_events: ResMut<Events<NavigationCancelled>>,
+    bindings: Res<NavigationBindings>,
+    time: Res<Time>,
+    keyboard_input: Res<WindowInput<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_events: Res<Events<GamepadEvent>>,
+    cursor_moved_events: Res<Events<CursorMoved>>,
+    focusable_query: Query<(Entity, &Focusable, &Node, &GlobalTransform)>,
+    mut interaction_query: Query<&mut Interaction>,
+) {
+    let state = &mut *state;
+    for GamepadEvent(gamepad, event_type) in state.gamepad_event_reader.iter(&gamepad_events) {
+        match event_type {
+            GamepadEventType::Connected => state.gamepads.push(*gamepad),
+            GamepadEventType::Disconnected => state.gamepads.retain(|g| g != gamepad),
+            _ => {}
+        }
+    }
+
+    // moving the cursor over the ui switches back to pointing
+    if state
+        .cursor_moved_event_reader
+        .iter(&cursor_moved_events)
+        .any(|cursor_moved| cursor_moved.id == ui_window())
+    {
+        focus.visible = false;
+    }
+
+    // clicks from confirm inputs last a single frame
+    if let Some(entity) = state.clicked.take() {
+        if let Ok(mut interaction) = interaction_query.get_mut(entity) {
+            if *interaction == Interaction::Clicked {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+
+    let mut actions = Vec::new();
+    for (key, action) in bindings.keys.iter() {
+        if keyboard_input.just_pressed(ui_window(), *key) {
+            actions.push(*action);
+        }
+    }
+    for (button_type, action) in bindings.gamepad_buttons.iter() {
+        if gamepad_input
+            .get_just_pressed()
+            .any(|button| button.1 == *button_type)
+        {
+            actions.push(*action);
+        }
+    }
+
+    // held stick directions repeat, like held keys in a text field
+    let stick_direction = state.stick_direction(&gamepad_axes, bindings.stick_threshold);
+    if stick_direction != state.held_direction {
+        state.held_direction = stick_direction;
+        state.repeat_timer = bindings.repeat_delay;
+        if let Some(direction) = stick_direction {
+            actions.push(NavigationAction::Move(direction));
+        }
+    } else if let Some(direction) = stick_direction {
+        state.repeat_timer -= time.raw_delta_seconds;
+        if state.repeat_timer <= 0.0 {
+            state.repeat_timer += bindings.repeat_interval;
+            actions.push(NavigationAction::Move(direction));
+        }
+    }
+
+    // the focused node may have been despawned
+    if let Some(focused) = focus.focused {
+        if focusable_query.get(focused).is_err() {
+            focus.focused = None;
+        }
+    }
+
+    for action in actions {
+        focus.visible = true;
+        let focused = match focus.focused {
+            Some(focused) => focused,
+            None => {
+                // start at the top left node
+                focus.focused = focusable_query
+                    .iter()
+                    .map(|(entity, _, _, transform)| (entity, transform.translation.truncate()))
+                    .max_by(|(_, a), (_, b)| {
+                        (a.y() - a.x())
+                            .partial_cmp(&(b.y() - b.x()))
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .map(|(entity, _)| entity);
+                continue;
+            }
+        };
+
+        match action {
+            NavigationAction::Move(direction) => {
+                let (_, focusable, _, transform) = focusable_query.get(focused).unwrap();
+                let neighbor = match direction {
+                    NavigationDirection::Up => focusable.up,
+                    NavigationDirection::Down => focusable.down,
+                    NavigationDirection::Left => focusable.left,
+                    NavigationDirection::Right => focusable.right,
+                };
+                let from = transform.translation.truncate();
+                let neighbor = neighbor.or_else(|| {
+                    find_neighbor(
+                        from,
+                        direction,
+                        focusable_query
+                            .iter()
+                            .filter(|(entity, _, node, _)| {
+                                *entity != focused && node.size != Vec2::zero()
+                            })
+                            .map(|(entity, _, _, transform)| {
+                                (entity, transform.translation.truncate())
+                            }),
+                    )
+                });
+                if let Some(neighbor) = neighbor {
+                    if let Ok(mut interaction) = interaction_query.get_mut(focused) {
+                        if *interaction == Interaction::Hovered {
+                            *interaction = Interaction::None;
+                        }
+                    }
+                    focus.focused = Some(neighbor);
+                }
+            }
+            NavigationAction::Confirm => {
+                if let Ok(mut interaction) = interaction_query.get_mut(focused) {
+                    *interaction = Interaction::Clicked;
+                    state.clicked = Some(focused);
+                }
+            }
+            NavigationAction::Cancel => cancelled_events.send(NavigationCancelled {
+                focused: Some(focused),
+            }),
+        }
+    }
+
+    if focus.visible && state.clicked.is_none() {
+        if let Some(focused) = focus.focused {
+            if let Ok(mut interaction) = interaction_query.get_mut(focused) {
+                if *interaction == Interaction::None {
+                    *interaction = Interaction::Hovered;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_neighbor_prefers_aligned_nodes() {
+        let entities = (0..3).map(Entity::new).collect::<Vec<_>>();
+        let candidates = vec![
+            (entities[0], Vec2::new(100.0, 0.0)),
+            // closer, but far off to the side
+            (entities[1], Vec2::new(60.0, 50.0)),
+            (entities[2], Vec2::new(-50.0, 0.0)),
+        ];
+        assert_eq!(
+            find_neighbor(Vec2::zero(), NavigationDirection::Right, candidates.clone()),
+            Some(entities[0])
+        );
+        assert_eq!(
+            find_neighbor(Vec2::zero(), NavigationDirection::Up, candidates.clone()),
+            Some(entities[1])
+        );
+        assert_eq!(
+            find_neighbor(Vec2::zero(), NavigationDirection::Left, candidates.clone()),
+            Some(entities[2])
+        );
+        assert_eq!(
+            find_neighbor(Vec2::zero(), NavigationDirection::Down, candidates),
+            None
+        );
+    }
+}
@@ -7,6 +7,25 @@ pub struct Node {
     pub size: Vec2,
 }
 
+/// Controls the draw order of a UI node. Nodes with a higher z-index are drawn on top of nodes with a lower z-index
+/// and receive interactions before them. A node's descendants are drawn with it.
+///
+/// Nodes without a `ZIndex` behave like `ZIndex::Local(0)`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ZIndex {
+    /// Orders the node among its siblings. Siblings with the same z-index are drawn in hierarchy order.
+    Local(i32),
+    /// Orders the node among the root nodes, regardless of where it is in the hierarchy. Use this for popups and
+    /// tooltips that should be drawn above everything else.
+    Global(i32),
+}
+
+impl Default for ZIndex {
+    fn default() -> Self {
+        ZIndex::Local(0)
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Val {
     Undefined,
@@ -87,11 +87,10 @@ impl UiRenderGraphBuilder for RenderGraph {
     fn add_ui_graph(&mut self, resources: &Resources) -> &mut Self {
         let mut pipelines = resources.get_mut::<Assets<PipelineDescriptor>>().unwrap();
         let mut shaders = resources.get_mut::<Assets<Shader>>().unwrap();
-        let msaa = resources.get::<Msaa>().unwrap();
         pipelines.set_untracked(UI_PIPELINE_HANDLE, build_ui_pipeline(&mut shaders));
 
         let mut ui_pass_node = PassNode::<&Node>::new(PassDescriptor {
-            color_attachments: vec![msaa.color_attachment_descriptor(
+            color_attachments: vec![Msaa::color_attachment_descriptor(
                 TextureAttachment::Input("color_attachment".to_string()),
                 TextureAttachment::Input("color_resolve_target".to_string()),
                 Operations {
@@ -107,7 +106,7 @@ impl UiRenderGraphBuilder for RenderGraph {
                 }),
                 stencil_ops: None,
             }),
-            sample_count: msaa.samples,
+            sample_count: 1,
         });
 
         ui_pass_node.add_camera(camera::UI_CAMERA);
@@ -117,11 +116,7 @@ impl UiRenderGraphBuilder for RenderGraph {
             base::node::PRIMARY_SWAP_CHAIN,
             WindowSwapChainNode::OUT_TEXTURE,
             node::UI_PASS,
-            if msaa.samples > 1 {
-                "color_resolve_target"
-            } else {
-                "color_attachment"
-            },
+            "color_resolve_target",
         )
         .unwrap();
 
@@ -133,15 +128,13 @@ impl UiRenderGraphBuilder for RenderGraph {
         )
         .unwrap();
 
-        if msaa.samples > 1 {
-            self.add_slot_edge(
-                base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
-                WindowSwapChainNode::OUT_TEXTURE,
-                node::UI_PASS,
-                "color_attachment",
-            )
-            .unwrap();
-        }
+        self.add_slot_edge(
+            base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::UI_PASS,
+            "color_attachment",
+        )
+        .unwrap();
 
         // ensure ui pass runs after main pass
         self.add_node_edge(base::node::MAIN_PASS, node::UI_PASS)
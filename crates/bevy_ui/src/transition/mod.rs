@@ -0,0 +1,476 @@
+//! Screen transitions that hide state changes between menus and levels.
+//!
+//! [ScreenTransitionPlugin] adds a pass that draws a fullscreen overlay on top of everything else in the primary
+//! window, the ui included. Start a transition through the [ScreenTransition] resource, for example
+//! [ScreenTransition::fade_out] when leaving a level, and switch states when the [ScreenTransitionFinished] event
+//! reports that the screen is covered. [ScreenTransition::fade_in] then reveals the new state.
+//!
+//! The overlay is drawn by its own camera on [TRANSITION_LAYER], which is reserved for it. Add the plugin after the other
+//! render and ui plugins, so the transition pass runs after every pass that draws to the window.
+
+use bevy_app::prelude::*;
+use bevy_asset::{AddAsset, Assets, Handle};
+use bevy_core::Time;
+use bevy_ecs::{Commands, IntoQuerySystem, Query, Res, ResMut, With};
+use bevy_math::{Vec2, Vec4};
+use bevy_render::{
+    camera::{ActiveCameras, Camera, RenderLayers, VisibleEntities},
+    draw::Draw,
+    mesh::{shape, Mesh},
+    pass::{LoadOp, Operations, PassDescriptor, TextureAttachment},
+    pipeline::{
+        BlendDescriptor, BlendFactor, BlendOperation, ColorStateDescriptor, ColorWrite, CullMode,
+        FrontFace, PipelineDescriptor, RasterizationStateDescriptor, RenderPipelines,
+    },
+    prelude::Color,
+    render_graph::{
+        base::{self, Msaa},
+        AssetRenderResourcesNode, CameraNode, PassNode, RenderGraph, WindowSwapChainNode,
+    },
+    renderer::RenderResources,
+    shader::{asset_shader_defs_system, Shader, ShaderDefs, ShaderStage, ShaderStages},
+    texture::{Texture, TextureFormat},
+};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_type_registry::TypeUuid;
+
+/// The render layer of the transition overlay. Other cameras and entities shouldn't use it.
+pub const TRANSITION_LAYER: u8 = 31;
+
+pub const TRANSITION_PIPELINE_HANDLE: Handle<PipelineDescriptor> =
+    Handle::weak_from_u64(PipelineDescriptor::TYPE_UUID, 6619387302175849213);
+
+pub const TRANSITION_MATERIAL_HANDLE: Handle<TransitionMaterial> =
+    Handle::weak_from_u64(TransitionMaterial::TYPE_UUID, 13407218853309624517);
+
+/// A quad that covers the whole viewport in normalized device coordinates
+pub const TRANSITION_QUAD_HANDLE: Handle<Mesh> =
+    Handle::weak_from_u64(Mesh::TYPE_UUID, 2871093645502817364);
+
+pub mod node {
+    pub const TRANSITION_CAMERA: &str = "transition_camera";
+    pub const TRANSITION_MATERIAL: &str = "transition_material";
+    pub const TRANSITION_PASS: &str = "transition_pass";
+}
+
+pub mod camera {
+    pub const TRANSITION_CAMERA: &str = "TransitionCamera";
+}
+
+/// How the overlay is drawn. It is kept up to date by the [screen_transition_system].
+#[derive(Debug, Clone, PartialEq, RenderResources, ShaderDefs, TypeUuid)]
+#[uuid = "8c1f3b5e-62d4-4a9e-b07c-d3e5a1f49c28"]
+pub struct TransitionMaterial {
+    pub color: Color,
+    /// The direction the covered area grows in, with y up, and the width of its edge. A zero direction covers the
+    /// whole screen evenly.
+    pub wipe: Vec4,
+    pub coverage: f32,
+    #[shader_def]
+    pub texture: Option<Handle<Texture>>,
+}
+
+impl Default for TransitionMaterial {
+    fn default() -> Self {
+        TransitionMaterial {
+            color: Color::BLACK,
+            wipe: Vec4::zero(),
+            coverage: 0.0,
+            texture: None,
+        }
+    }
+}
+
+/// How a transition covers the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionEffect {
+    /// The whole screen fades evenly
+    Fade,
+    /// An edge sweeps across the screen in `direction`, with y up. `softness` is the width of the edge, as a
+    /// fraction of the screen.
+    Wipe { direction: Vec2, softness: f32 },
+}
+
+/// The transition shown on top of the primary window
+#[derive(Debug, Clone)]
+pub struct ScreenTransition {
+    effect: TransitionEffect,
+    color: Color,
+    texture: Option<Handle<Texture>>,
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+    running: bool,
+}
+
+impl Default for ScreenTransition {
+    fn default() -> Self {
+        ScreenTransition {
+            effect: TransitionEffect::Fade,
+            color: Color::BLACK,
+            texture: None,
+            from: 0.0,
+            to: 0.0,
+            duration: 0.0,
+            elapsed: 0.0,
+            running: false,
+        }
+    }
+}
+
+/// Sent when a [ScreenTransition] finishes
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenTransitionFinished {
+    /// Whether the transition left the screen covered, like [ScreenTransition::fade_out] does. This is usually when
+    /// to switch states.
+    pub covered: bool,
+}
+
+impl ScreenTransition {
+    /// Covers the screen with `color`. The screen stays covered until another transition starts.
+    pub fn fade_out(&mut self, color: Color, duration: f32) {
+        self.start(TransitionEffect::Fade, color, None, 0.0, 1.0, duration);
+    }
+
+    /// Reveals the screen from behind `color`
+    pub fn fade_in(&mut self, color: Color, duration: f32) {
+        self.start(TransitionEffect::Fade, color, None, 1.0, 0.0, duration);
+    }
+
+    /// Covers the screen with `color` from one side, sweeping in `direction`
+    pub fn wipe_out(&mut self, color: Color, direction: Vec2, duration: f32) {
+        let effect = TransitionEffect::Wipe {
+            direction,
+            softness: 0.05,
+        };
+        self.start(effect, color, None, 0.0, 1.0, duration);
+    }
+
+    /// Reveals the screen from behind `color` from one side, sweeping in `direction`
+    pub fn wipe_in(&mut self, color: Color, direction: Vec2, duration: f32) {
+        // the revealed area grows in `direction`, so the covered area shrinks towards it
+        let effect = TransitionEffect::Wipe {
+            direction: -direction,
+            softness: 0.05,
+        };
+        self.start(effect, color, None, 1.0, 0.0, duration);
+    }
+
+    /// Fades from `texture` to the screen. Render the previous state to the texture, for example with a camera that
+    /// renders to a texture target, to crossfade between the two states.
+    pub fn crossfade_from(&mut self, texture: Handle<Texture>, duration: f32) {
+        self.start(
+            TransitionEffect::Fade,
+            Color::WHITE,
+            Some(texture),
+            1.0,
+            0.0,
+            duration,
+        );
+    }
+
+    /// Starts a custom transition, where the screen goes from `from` to `to` coverage
+    pub fn start(
+        &mut self,
+        effect: TransitionEffect,
+        color: Color,
+        texture: Option<Handle<Texture>>,
+        from: f32,
+        to: f32,
+        duration: f32,
+    ) {
+        *self = ScreenTransition {
+            effect,
+            color,
+            texture,
+            from,
+            to,
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+            running: true,
+        };
+    }
+
+    /// Removes the overlay right away, without sending a [ScreenTransitionFinished] event
+    pub fn clear(&mut self) {
+        *self = ScreenTransition::default();
+    }
+
+    pub fn effect(&self) -> TransitionEffect {
+        self.effect
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// How much of the screen is covered, from 0.0 to 1.0
+    pub fn coverage(&self) -> f32 {
+        let t = if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0)
+        } else {
+            1.0
+        };
+        self.from + (self.to - self.from) * t
+    }
+
+    /// Moves the transition forward. Returns true in the call it finishes in.
+    pub fn advance(&mut self, seconds: f32) -> bool {
+        if !self.running {
+            return false;
+        }
+
+        self.elapsed += seconds;
+        if self.elapsed >= self.duration {
+            self.elapsed = self.duration;
+            self.running = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn material(&self) -> TransitionMaterial {
+        let wipe = match self.effect {
+            TransitionEffect::Fade => Vec4::zero(),
+            TransitionEffect::Wipe {
+                direction,
+                softness,
+            } => Vec4::new(direction.x(), direction.y(), softness, 0.0),
+        };
+        TransitionMaterial {
+            color: self.color,
+            wipe,
+            coverage: self.coverage(),
+            texture: self.texture.clone(),
+        }
+    }
+}
+
+/// Marks the fullscreen quad that draws the [ScreenTransition]
+#[derive(Debug, Default)]
+pub struct TransitionOverlay;
+
+/// Adds the [ScreenTransition] resource and the pass that draws it
+#[derive(Default)]
+pub struct ScreenTransitionPlugin;
+
+impl Plugin for ScreenTransitionPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<TransitionMaterial>()
+            .init_resource::<ScreenTransition>()
+            .add_event::<ScreenTransitionFinished>()
+            .add_startup_system(spawn_transition_overlay.system())
+            .add_system_to_stage(stage::POST_UPDATE, screen_transition_system.system())
+            .add_system_to_stage(
+                stage::POST_UPDATE,
+                asset_shader_defs_system::<TransitionMaterial>.system(),
+            );
+
+        let resources = app.resources();
+        resources
+            .get_mut::<ActiveCameras>()
+            .unwrap()
+            .add(camera::TRANSITION_CAMERA);
+        resources
+            .get_mut::<Assets<TransitionMaterial>>()
+            .unwrap()
+            .set_untracked(TRANSITION_MATERIAL_HANDLE, TransitionMaterial::default());
+        resources.get_mut::<Assets<Mesh>>().unwrap().set_untracked(
+            TRANSITION_QUAD_HANDLE,
+            Mesh::from(shape::Quad::new(Vec2::new(2.0, 2.0))),
+        );
+        resources
+            .get_mut::<Assets<PipelineDescriptor>>()
+            .unwrap()
+            .set_untracked(
+                TRANSITION_PIPELINE_HANDLE,
+                build_transition_pipeline(&mut resources.get_mut::<Assets<Shader>>().unwrap()),
+            );
+
+        let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        add_transition_graph(&mut render_graph);
+    }
+}
+
+pub fn build_transition_pipeline(shaders: &mut Assets<Shader>) -> PipelineDescriptor {
+    PipelineDescriptor {
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        color_states: vec![ColorStateDescriptor {
+            format: TextureFormat::default(),
+            color_blend: BlendDescriptor {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                operation: BlendOperation::Add,
+            },
+            alpha_blend: BlendDescriptor {
+                src_factor: BlendFactor::One,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            write_mask: ColorWrite::ALL,
+        }],
+        ..PipelineDescriptor::new(ShaderStages {
+            vertex: shaders.add(Shader::from_glsl(
+                ShaderStage::Vertex,
+                include_str!("transition.vert"),
+            )),
+            fragment: Some(shaders.add(Shader::from_glsl(
+                ShaderStage::Fragment,
+                include_str!("transition.frag"),
+            ))),
+        })
+    }
+}
+
+fn spawn_transition_overlay(mut commands: Commands) {
+    let layers = RenderLayers::layer(TRANSITION_LAYER);
+    commands
+        .spawn((
+            TRANSITION_QUAD_HANDLE,
+            TRANSITION_MATERIAL_HANDLE,
+            Draw {
+                is_visible: false,
+                ..Default::default()
+            },
+            RenderPipelines::from_handles(&[TRANSITION_PIPELINE_HANDLE]),
+            TransitionOverlay,
+            layers,
+        ))
+        // the camera's view projection is the identity, so the quad is drawn in normalized device coordinates
+        .spawn((
+            Camera {
+                name: Some(camera::TRANSITION_CAMERA.to_string()),
+                ..Default::default()
+            },
+            layers,
+            VisibleEntities::default(),
+            Transform::default(),
+            GlobalTransform::default(),
+        ));
+}
+
+/// Advances the [ScreenTransition], sends [ScreenTransitionFinished] events and updates the overlay
+pub fn screen_transition_system(
+    time: Res<Time>,
+    mut transition: ResMut<ScreenTransition>,
+    mut finished_events: ResMut<Events<ScreenTransitionFinished>>,
+    mut materials: ResMut<Assets<TransitionMaterial>>,
+    mut query: Query<With<TransitionOverlay, &mut Draw>>,
+) {
+    if transition.advance(time.raw_delta_seconds) {
+        finished_events.send(ScreenTransitionFinished {
+            covered: transition.coverage() >= 1.0,
+        });
+    }
+
+    let material = transition.material();
+    let visible = material.coverage > 0.0;
+    if materials.get(&TRANSITION_MATERIAL_HANDLE) != Some(&material) {
+        materials.set_untracked(TRANSITION_MATERIAL_HANDLE, material);
+    }
+    for mut draw in query.iter_mut() {
+        if draw.is_visible != visible {
+            draw.is_visible = visible;
+        }
+    }
+}
+
+fn add_transition_graph(graph: &mut RenderGraph) {
+    // every pass that draws to the window runs before the transition pass
+    let window_passes = [
+        base::node::PRIMARY_SWAP_CHAIN,
+        base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+    ]
+    .iter()
+    .filter_map(|name| graph.iter_node_outputs(*name).ok())
+    .flat_map(|outputs| outputs.map(|(_edge, node)| node.id).collect::<Vec<_>>())
+    .collect::<Vec<_>>();
+
+    graph.add_system_node(
+        node::TRANSITION_CAMERA,
+        CameraNode::new(camera::TRANSITION_CAMERA),
+    );
+    graph.add_system_node(
+        node::TRANSITION_MATERIAL,
+        AssetRenderResourcesNode::<TransitionMaterial>::new(false),
+    );
+
+    let mut transition_pass_node = PassNode::<&TransitionOverlay>::new(PassDescriptor {
+        color_attachments: vec![Msaa::color_attachment_descriptor(
+            TextureAttachment::Input("color_attachment".to_string()),
+            TextureAttachment::Input("color_resolve_target".to_string()),
+            Operations {
+                load: LoadOp::Load,
+                store: true,
+            },
+        )],
+        depth_stencil_attachment: None,
+        sample_count: 1,
+    });
+    transition_pass_node.add_camera(camera::TRANSITION_CAMERA);
+    graph.add_node(node::TRANSITION_PASS, transition_pass_node);
+
+    for window_pass in window_passes {
+        // a pass can use more than one window texture, in which case the edge already exists
+        let _ = graph.add_node_edge(window_pass, node::TRANSITION_PASS);
+    }
+    graph
+        .add_node_edge(node::TRANSITION_CAMERA, node::TRANSITION_PASS)
+        .unwrap();
+    graph
+        .add_node_edge(node::TRANSITION_MATERIAL, node::TRANSITION_PASS)
+        .unwrap();
+
+    graph
+        .add_slot_edge(
+            base::node::PRIMARY_SWAP_CHAIN,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::TRANSITION_PASS,
+            "color_resolve_target",
+        )
+        .unwrap();
+    graph
+        .add_slot_edge(
+            base::node::MAIN_SAMPLED_COLOR_ATTACHMENT,
+            WindowSwapChainNode::OUT_TEXTURE,
+            node::TRANSITION_PASS,
+            "color_attachment",
+        )
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transition_progress() {
+        let mut transition = ScreenTransition::default();
+        assert!(!transition.is_running());
+        assert_eq!(transition.coverage(), 0.0);
+        assert!(!transition.advance(1.0));
+
+        transition.fade_out(Color::BLACK, 2.0);
+        assert!(!transition.advance(1.0));
+        assert_eq!(transition.coverage(), 0.5);
+        assert!(transition.advance(1.5));
+        assert_eq!(transition.coverage(), 1.0);
+        assert!(!transition.is_running());
+        // the screen stays covered, and the event is only sent once
+        assert!(!transition.advance(1.0));
+        assert_eq!(transition.coverage(), 1.0);
+
+        transition.fade_in(Color::BLACK, 0.0);
+        assert!(transition.advance(0.0));
+        assert_eq!(transition.coverage(), 0.0);
+    }
+}
@@ -0,0 +1,44 @@
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 1, binding = 0) uniform TransitionMaterial_color {
+    vec4 Color;
+};
+layout(set = 1, binding = 1) uniform TransitionMaterial_wipe {
+    // xy: the direction the covered area grows in, with y up. z: the width of its edge
+    vec4 Wipe;
+};
+layout(set = 1, binding = 2) uniform TransitionMaterial_coverage {
+    float Coverage;
+};
+
+# ifdef TRANSITIONMATERIAL_TEXTURE
+layout(set = 1, binding = 3) uniform texture2D TransitionMaterial_texture;
+layout(set = 1, binding = 4) uniform sampler TransitionMaterial_texture_sampler;
+# endif
+
+void main() {
+    vec4 color = Color;
+# ifdef TRANSITIONMATERIAL_TEXTURE
+    color *= texture(
+        sampler2D(TransitionMaterial_texture, TransitionMaterial_texture_sampler),
+        v_Uv);
+# endif
+
+    float alpha = Coverage;
+    vec2 direction = Wipe.xy;
+    if (dot(direction, direction) > 0.0) {
+        // how far along the wipe the pixel is, from 0.0 at the corner it starts in to 1.0 at the opposite corner
+        vec2 centered = vec2(v_Uv.x - 0.5, 0.5 - v_Uv.y);
+        float extent = abs(direction.x) + abs(direction.y);
+        float position = dot(centered, direction) / extent + 0.5;
+        float edge = max(Wipe.z, 0.0001);
+        float covered_to = Coverage * (1.0 + edge);
+        alpha = 1.0 - smoothstep(covered_to - edge, covered_to, position);
+    }
+
+    o_Target = vec4(color.rgb, color.a * alpha);
+}
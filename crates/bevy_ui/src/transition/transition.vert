@@ -0,0 +1,17 @@
+#version 450
+
+layout(location = 0) in vec3 Vertex_Position;
+layout(location = 1) in vec3 Vertex_Normal;
+layout(location = 2) in vec2 Vertex_Uv;
+
+layout(location = 0) out vec2 v_Uv;
+
+layout(set = 0, binding = 0) uniform Camera {
+    mat4 ViewProj;
+};
+
+void main() {
+    // the transition camera's view projection is the identity, so the quad covers the viewport
+    v_Uv = vec2(Vertex_Position.x * 0.5 + 0.5, 0.5 - Vertex_Position.y * 0.5);
+    gl_Position = ViewProj * vec4(Vertex_Position.xy, 0.0, 1.0);
+}
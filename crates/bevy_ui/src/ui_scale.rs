@@ -0,0 +1,24 @@
+use bevy_math::Vec2;
+
+/// Multiplies the size of the ui on top of the window's scale factor, for example to make text larger for players
+/// that sit far away from the screen or have trouble reading small text. It can be changed at any time.
+///
+/// Pixel values in node styles, the sizes of images and the font sizes of text are multiplied by `scale` when the ui
+/// is laid out, and text is rasterized at the scaled font size so it stays sharp.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiScale {
+    pub scale: f32,
+}
+
+impl Default for UiScale {
+    fn default() -> Self {
+        UiScale { scale: 1.0 }
+    }
+}
+
+impl UiScale {
+    /// Converts a position or size in window pixels to the unscaled pixels of node styles
+    pub fn window_to_ui(&self, value: Vec2) -> Vec2 {
+        value / self.scale.max(std::f32::EPSILON)
+    }
+}
@@ -1,49 +1,152 @@
-use super::Node;
+use super::{Node, ZIndex};
 use bevy_ecs::{Entity, Query, With, Without};
-use bevy_transform::{
-    hierarchy,
-    prelude::{Children, Parent, Transform},
-};
+use bevy_transform::prelude::{Children, Parent, Transform};
+use bevy_utils::HashMap;
 
 pub const UI_Z_STEP: f32 = 0.001;
 
+/// Assigns each UI node a z value according to the hierarchy and [ZIndex] components. Nodes are drawn and hit-tested
+/// in order of their global z value.
 pub fn ui_z_system(
-    root_node_query: Query<With<Node, Without<Parent, Entity>>>,
-    mut node_query: Query<(Entity, &Node, &mut Transform)>,
+    root_node_query: Query<With<Node, Without<Parent, (Entity, Option<&ZIndex>)>>>,
+    global_node_query: Query<With<Node, With<Parent, (Entity, &ZIndex)>>>,
+    mut node_query: Query<(Entity, &Node, &mut Transform, Option<&ZIndex>)>,
+    parent_query: Query<&Parent>,
     children_query: Query<&Children>,
 ) {
-    let mut current_global_z = 0.0;
+    // root nodes and nodes with a global z-index are ordered together
+    let mut root_nodes = root_node_query
+        .iter()
+        .map(|(entity, z_index)| {
+            let z_index = match z_index {
+                Some(ZIndex::Local(z_index)) | Some(ZIndex::Global(z_index)) => *z_index,
+                None => 0,
+            };
+            (z_index, entity)
+        })
+        .collect::<Vec<_>>();
+    root_nodes.extend(
+        global_node_query
+            .iter()
+            .filter_map(|(entity, z_index)| match z_index {
+                ZIndex::Global(z_index) => Some((*z_index, entity)),
+                ZIndex::Local(_) => None,
+            }),
+    );
+    root_nodes.sort_by_key(|(z_index, _)| *z_index);
 
-    for entity in root_node_query.iter() {
-        if let Some(result) = hierarchy::run_on_hierarchy(
+    let mut global_z = HashMap::default();
+    let mut current_global_z = 0.0;
+    for (_, entity) in root_nodes {
+        assign_global_z(
+            &node_query,
             &children_query,
-            &mut node_query,
             entity,
-            Some(current_global_z),
-            Some(current_global_z),
-            &mut update_node_entity,
-        ) {
-            current_global_z = result;
+            &mut current_global_z,
+            &mut global_z,
+        );
+    }
+
+    // transforms are relative to the parent, which isn't necessarily drawn right before its children
+    for (entity, z) in global_z.iter() {
+        let parent_z = parent_query
+            .get(*entity)
+            .ok()
+            .and_then(|parent| global_z.get(&parent.0))
+            .cloned()
+            .unwrap_or(0.0);
+        if let Ok(mut transform) = node_query.get_component_mut::<Transform>(*entity) {
+            transform.translation.set_z(z - parent_z);
         }
     }
 }
 
-fn update_node_entity(
-    node_query: &mut Query<(Entity, &Node, &mut Transform)>,
+fn assign_global_z(
+    node_query: &Query<(Entity, &Node, &mut Transform, Option<&ZIndex>)>,
+    children_query: &Query<&Children>,
     entity: Entity,
-    parent_result: Option<f32>,
-    previous_result: Option<f32>,
-) -> Option<f32> {
-    let mut z = UI_Z_STEP;
-    let parent_global_z = parent_result.unwrap();
-    if let Some(previous_global_z) = previous_result {
-        z += previous_global_z - parent_global_z;
-    };
-    let global_z = z + parent_global_z;
-
-    if let Ok(mut transform) = node_query.get_component_mut::<Transform>(entity) {
-        transform.translation.set_z(z);
+    current_global_z: &mut f32,
+    global_z: &mut HashMap<Entity, f32>,
+) {
+    *current_global_z += UI_Z_STEP;
+    global_z.insert(entity, *current_global_z);
+
+    if let Ok(children) = children_query.get(entity) {
+        // children with a global z-index are drawn with the root nodes instead
+        let mut ordered_children = children
+            .iter()
+            .filter_map(|child| match node_query.get_component::<ZIndex>(*child) {
+                Ok(ZIndex::Global(_)) => None,
+                Ok(ZIndex::Local(z_index)) => Some((*z_index, *child)),
+                Err(_) => Some((0, *child)),
+            })
+            .collect::<Vec<_>>();
+        ordered_children.sort_by_key(|(z_index, _)| *z_index);
+
+        for (_, child) in ordered_children {
+            assign_global_z(
+                node_query,
+                children_query,
+                child,
+                current_global_z,
+                global_z,
+            );
+        }
     }
+}
 
-    Some(global_z)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{Commands, IntoQuerySystem, Resources, Schedule, World};
+    use bevy_transform::hierarchy::BuildChildren;
+
+    fn node_with_z(world: &mut World, z_index: Option<ZIndex>) -> Entity {
+        let entity = world.spawn((Node::default(), Transform::default()));
+        if let Some(z_index) = z_index {
+            world.insert_one(entity, z_index).unwrap();
+        }
+        entity
+    }
+
+    fn global_z(world: &World, entity: Entity) -> f32 {
+        let mut z = 0.0;
+        let mut current = Some(entity);
+        while let Some(entity) = current {
+            z += world.get::<Transform>(entity).unwrap().translation.z();
+            current = world.get::<Parent>(entity).ok().map(|parent| parent.0);
+        }
+        z
+    }
+
+    #[test]
+    fn z_index_orders_nodes() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+
+        let root = node_with_z(&mut world, None);
+        let back = node_with_z(&mut world, Some(ZIndex::Local(1)));
+        let front = node_with_z(&mut world, Some(ZIndex::Local(2)));
+        let middle = node_with_z(&mut world, None);
+        let popup = node_with_z(&mut world, Some(ZIndex::Global(2)));
+        let other_root = node_with_z(&mut world, Some(ZIndex::Local(1)));
+
+        let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
+        commands.push_children(root, &[front, back, middle]);
+        commands.push_children(back, &[popup]);
+        commands.apply(&mut world, &mut resources);
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", ui_z_system.system());
+        schedule.run(&mut world, &mut resources);
+
+        let z = |entity| global_z(&world, entity);
+        assert!(z(root) < z(middle));
+        assert!(z(middle) < z(back));
+        assert!(z(back) < z(front));
+        assert!(z(front) < z(other_root));
+        assert!(z(other_root) < z(popup));
+    }
 }
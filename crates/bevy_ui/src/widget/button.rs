@@ -1,2 +1,23 @@
+use crate::Interaction;
+use bevy_app::Events;
+use bevy_ecs::{Entity, Mutated, Query, ResMut};
+
 #[derive(Debug, Clone)]
 pub struct Button;
+
+/// Sent when a [Button] is pressed
+#[derive(Debug, Clone)]
+pub struct ButtonClicked {
+    pub entity: Entity,
+}
+
+pub fn button_system(
+    mut button_clicked_events: ResMut<Events<ButtonClicked>>,
+    mut query: Query<(Entity, &Button, Mutated<Interaction>)>,
+) {
+    for (entity, _button, interaction) in query.iter_mut() {
+        if *interaction == Interaction::Clicked {
+            button_clicked_events.send(ButtonClicked { entity });
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use super::WidgetMaterials;
+use crate::Interaction;
+use bevy_app::Events;
+use bevy_asset::Handle;
+use bevy_ecs::{Entity, Mutated, Query, QuerySet, Res, ResMut};
+use bevy_sprite::ColorMaterial;
+
+/// A widget that toggles between checked and unchecked when it is clicked
+#[derive(Debug, Clone, Default)]
+pub struct Checkbox {
+    pub checked: bool,
+}
+
+/// Sent when a [Checkbox] is toggled by the user
+#[derive(Debug, Clone)]
+pub struct CheckboxChanged {
+    pub entity: Entity,
+    pub checked: bool,
+}
+
+/// A widget that is selected when it is clicked. Selecting a radio button deselects all other radio buttons in the
+/// same `group`.
+#[derive(Debug, Clone, Default)]
+pub struct RadioButton {
+    pub group: u32,
+    pub selected: bool,
+}
+
+/// Sent when a [RadioButton] is selected by the user
+#[derive(Debug, Clone)]
+pub struct RadioButtonChanged {
+    pub entity: Entity,
+    pub group: u32,
+}
+
+pub fn checkbox_system(
+    mut checkbox_changed_events: ResMut<Events<CheckboxChanged>>,
+    mut query: Query<(Entity, &mut Checkbox, Mutated<Interaction>)>,
+) {
+    for (entity, mut checkbox, interaction) in query.iter_mut() {
+        if *interaction == Interaction::Clicked {
+            checkbox.checked = !checkbox.checked;
+            checkbox_changed_events.send(CheckboxChanged {
+                entity,
+                checked: checkbox.checked,
+            });
+        }
+    }
+}
+
+pub fn radio_button_system(
+    mut radio_button_changed_events: ResMut<Events<RadioButtonChanged>>,
+    mut queries: QuerySet<(
+        Query<(Entity, &RadioButton, Mutated<Interaction>)>,
+        Query<(Entity, &mut RadioButton)>,
+    )>,
+) {
+    let mut selected = Vec::new();
+    for (entity, radio_button, interaction) in queries.q0_mut().iter_mut() {
+        if *interaction == Interaction::Clicked && !radio_button.selected {
+            selected.push((entity, radio_button.group));
+        }
+    }
+
+    for (selected_entity, group) in selected {
+        for (entity, mut radio_button) in queries.q1_mut().iter_mut() {
+            if radio_button.group != group {
+                continue;
+            }
+
+            let is_selected = entity == selected_entity;
+            if radio_button.selected != is_selected {
+                radio_button.selected = is_selected;
+            }
+        }
+
+        radio_button_changed_events.send(RadioButtonChanged {
+            entity: selected_entity,
+            group,
+        });
+    }
+}
+
+/// Sets the material of checkboxes and radio buttons to match their state
+pub fn toggle_material_system(
+    widget_materials: Res<WidgetMaterials>,
+    mut queries: QuerySet<(
+        Query<(&Checkbox, &Interaction, &mut Handle<ColorMaterial>)>,
+        Query<(&RadioButton, &Interaction, &mut Handle<ColorMaterial>)>,
+    )>,
+) {
+    for (checkbox, interaction, mut material) in queries.q0_mut().iter_mut() {
+        let toggle_material = widget_materials.toggle_material(checkbox.checked, *interaction);
+        if *material != *toggle_material {
+            *material = toggle_material.clone();
+        }
+    }
+
+    for (radio_button, interaction, mut material) in queries.q1_mut().iter_mut() {
+        let toggle_material = widget_materials.toggle_material(radio_button.selected, *interaction);
+        if *material != *toggle_material {
+            *material = toggle_material.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::{IntoQuerySystem, Resources, Schedule, World};
+
+    #[test]
+    fn selecting_a_radio_button_clears_its_group() {
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Events::<RadioButtonChanged>::default());
+
+        let mut spawn =
+            |group, selected| world.spawn((RadioButton { group, selected }, Interaction::None));
+        let small = spawn(0, true);
+        let medium = spawn(0, false);
+        let large = spawn(0, false);
+        let red = spawn(1, true);
+        let blue = spawn(1, false);
+
+        world.clear_trackers();
+        *world.get_mut::<Interaction>(large).unwrap() = Interaction::Clicked;
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update");
+        schedule.add_system_to_stage("update", radio_button_system.system());
+        schedule.run(&mut world, &mut resources);
+
+        let selected = |entity| world.get::<RadioButton>(entity).unwrap().selected;
+        assert!(!selected(small));
+        assert!(!selected(medium));
+        assert!(selected(large));
+        assert!(selected(red));
+        assert!(!selected(blue));
+
+        let events = resources.get::<Events<RadioButtonChanged>>().unwrap();
+        let changed = events
+            .get_reader()
+            .iter(&events)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(changed.len(), 1);
+        assert_eq!((changed[0].entity, changed[0].group), (large, 0));
+    }
+}
@@ -0,0 +1,47 @@
+use crate::Interaction;
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{FromResources, Resources};
+use bevy_render::color::Color;
+use bevy_sprite::ColorMaterial;
+
+/// The materials used by the standard widgets. Replace the handles (or modify the materials they point to) to restyle
+/// every widget at once.
+#[derive(Debug, Clone)]
+pub struct WidgetMaterials {
+    pub normal: Handle<ColorMaterial>,
+    pub hovered: Handle<ColorMaterial>,
+    pub pressed: Handle<ColorMaterial>,
+    pub checked: Handle<ColorMaterial>,
+    pub track: Handle<ColorMaterial>,
+    pub fill: Handle<ColorMaterial>,
+}
+
+impl WidgetMaterials {
+    /// Returns the material of a toggleable widget in the given state
+    pub fn toggle_material(
+        &self,
+        checked: bool,
+        interaction: Interaction,
+    ) -> &Handle<ColorMaterial> {
+        match interaction {
+            _ if checked => &self.checked,
+            Interaction::Clicked => &self.pressed,
+            Interaction::Hovered => &self.hovered,
+            Interaction::None => &self.normal,
+        }
+    }
+}
+
+impl FromResources for WidgetMaterials {
+    fn from_resources(resources: &Resources) -> Self {
+        let mut materials = resources.get_mut::<Assets<ColorMaterial>>().unwrap();
+        WidgetMaterials {
+            normal: materials.add(Color::rgb(0.15, 0.15, 0.15).into()),
+            hovered: materials.add(Color::rgb(0.25, 0.25, 0.25).into()),
+            pressed: materials.add(Color::rgb(0.35, 0.35, 0.35).into()),
+            checked: materials.add(Color::rgb(0.35, 0.75, 0.35).into()),
+            track: materials.add(Color::rgb(0.1, 0.1, 0.1).into()),
+            fill: materials.add(Color::rgb(0.35, 0.75, 0.35).into()),
+        }
+    }
+}
@@ -1,7 +1,15 @@
 mod button;
+mod checkbox;
 mod image;
+mod materials;
+mod progress_bar;
+mod slider;
 mod text;
 
 pub use button::*;
+pub use checkbox::*;
 pub use image::*;
+pub use materials::*;
+pub use progress_bar::*;
+pub use slider::*;
 pub use text::*;
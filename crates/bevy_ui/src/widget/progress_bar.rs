@@ -0,0 +1,112 @@
+use super::{Slider, WidgetMaterials};
+use crate::{entity::NodeComponents, FocusPolicy, Style, Val};
+use bevy_asset::Handle;
+use bevy_ecs::{Added, Commands, Entity, Mutated, Query, QuerySet, Res, With};
+use bevy_math::Size;
+use bevy_sprite::ColorMaterial;
+use bevy_transform::prelude::{BuildChildren, Children};
+
+/// A widget that displays how far along a task is
+#[derive(Debug, Clone, Default)]
+pub struct ProgressBar {
+    /// The progress in the range `0.0..=1.0`
+    pub progress: f32,
+}
+
+impl ProgressBar {
+    pub fn new(progress: f32) -> Self {
+        ProgressBar { progress }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.progress.max(0.0).min(1.0)
+    }
+}
+
+/// Marks the child node that fills [ProgressBar] and [Slider] widgets up to their current value
+#[derive(Debug, Clone, Default)]
+pub struct WidgetFill;
+
+fn fill_style(fraction: f32) -> Style {
+    Style {
+        size: Size::new(Val::Percent(fraction * 100.0), Val::Percent(100.0)),
+        ..Default::default()
+    }
+}
+
+fn spawn_fill(
+    commands: &mut Commands,
+    widget_materials: &WidgetMaterials,
+    parent: Entity,
+    material: &mut Handle<ColorMaterial>,
+    fraction: f32,
+) {
+    if *material == Handle::default() {
+        *material = widget_materials.track.clone();
+    }
+
+    commands
+        .spawn(NodeComponents {
+            style: fill_style(fraction),
+            material: widget_materials.fill.clone(),
+            ..Default::default()
+        })
+        .with(WidgetFill)
+        .with(FocusPolicy::Pass);
+    let fill = commands.current_entity().unwrap();
+    commands.push_children(parent, &[fill]);
+}
+
+/// Adds a [WidgetFill] child to new progress bars and sliders. Widgets without a material get the
+/// [WidgetMaterials::track] material.
+pub fn widget_fill_spawn_system(
+    mut commands: Commands,
+    widget_materials: Res<WidgetMaterials>,
+    mut queries: QuerySet<(
+        Query<(Entity, Added<ProgressBar>, &mut Handle<ColorMaterial>)>,
+        Query<(Entity, Added<Slider>, &mut Handle<ColorMaterial>)>,
+    )>,
+) {
+    for (entity, progress_bar, mut material) in queries.q0_mut().iter_mut() {
+        spawn_fill(
+            &mut commands,
+            &widget_materials,
+            entity,
+            &mut material,
+            progress_bar.fraction(),
+        );
+    }
+
+    for (entity, slider, mut material) in queries.q1_mut().iter_mut() {
+        spawn_fill(
+            &mut commands,
+            &widget_materials,
+            entity,
+            &mut material,
+            slider.fraction(),
+        );
+    }
+}
+
+/// Resizes the [WidgetFill] of progress bars and sliders when their value changes
+pub fn widget_fill_system(
+    mut progress_bar_query: Query<(Mutated<ProgressBar>, &Children)>,
+    mut slider_query: Query<(Mutated<Slider>, &Children)>,
+    mut fill_query: Query<With<WidgetFill, &mut Style>>,
+) {
+    let changed_fills = progress_bar_query
+        .iter_mut()
+        .map(|(progress_bar, children)| (progress_bar.fraction(), children))
+        .chain(
+            slider_query
+                .iter_mut()
+                .map(|(slider, children)| (slider.fraction(), children)),
+        );
+    for (fraction, children) in changed_fills {
+        for child in children.iter() {
+            if let Ok(mut style) = fill_query.get_mut(*child) {
+                style.size.width = Val::Percent(fraction * 100.0);
+            }
+        }
+    }
+}
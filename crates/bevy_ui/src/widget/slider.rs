@@ -0,0 +1,134 @@
+use crate::{
+    focus::{latest_ui_cursor_moved, ui_cursor_position},
+    Interaction, Node,
+};
+use bevy_app::{EventReader, Events};
+use bevy_ecs::{Entity, Local, Query, Res, ResMut};
+use bevy_math::Vec2;
+use bevy_sprite::virtual_resolution::VirtualResolution;
+use bevy_transform::components::GlobalTransform;
+use bevy_window::{CursorMoved, Windows};
+
+/// A widget that selects a value in the range `min..=max` by dragging along its width
+#[derive(Debug, Clone)]
+pub struct Slider {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    /// If set, the value snaps to multiples of `step` (starting from `min`)
+    pub step: Option<f32>,
+}
+
+impl Default for Slider {
+    fn default() -> Self {
+        Slider {
+            value: 0.0,
+            min: 0.0,
+            max: 1.0,
+            step: None,
+        }
+    }
+}
+
+impl Slider {
+    pub fn new(value: f32, min: f32, max: f32) -> Self {
+        Slider {
+            value,
+            min,
+            max,
+            step: None,
+        }
+    }
+
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// Returns how far along the slider the current value is, from 0.0 at `min` to 1.0 at `max`
+    pub fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min))
+                .max(0.0)
+                .min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the value at the given `fraction` of the slider, snapped to `step`
+    pub fn value_at(&self, fraction: f32) -> f32 {
+        let fraction = fraction.max(0.0).min(1.0);
+        let mut value = self.min + fraction * (self.max - self.min);
+        if let Some(step) = self.step {
+            if step > 0.0 {
+                value = self.min + ((value - self.min) / step).round() * step;
+            }
+        }
+
+        value.max(self.min).min(self.max)
+    }
+}
+
+/// Sent when the value of a [Slider] is changed by the user
+#[derive(Debug, Clone)]
+pub struct SliderChanged {
+    pub entity: Entity,
+    pub value: f32,
+}
+
+#[derive(Default)]
+pub struct SliderState {
+    cursor_moved_event_reader: EventReader<CursorMoved>,
+    cursor_position: Vec2,
+}
+
+pub fn slider_system(
+    mut state: Local<SliderState>,
+    cursor_moved_events: Res<Events<CursorMoved>>,
+    windows: Res<Windows>,
+    virtual_resolution: Res<VirtualResolution>,
+    mut slider_changed_events: ResMut<Events<SliderChanged>>,
+    mut query: Query<(Entity, &mut Slider, &Interaction, &Node, &GlobalTransform)>,
+) {
+    if let Some(cursor_moved) =
+        latest_ui_cursor_moved(&mut state.cursor_moved_event_reader, &cursor_moved_events)
+    {
+        state.cursor_position = ui_cursor_position(cursor_moved, &windows, &virtual_resolution);
+    }
+
+    for (entity, mut slider, interaction, node, global_transform) in query.iter_mut() {
+        // sliders stay "clicked" until the mouse button is released, even if the cursor leaves them
+        if *interaction != Interaction::Clicked || node.size.x() <= 0.0 {
+            continue;
+        }
+
+        let left = global_transform.translation.x() - node.size.x() / 2.0;
+        let fraction = (state.cursor_position.x() - left) / node.size.x();
+        let value = slider.value_at(fraction);
+        if (value - slider.value).abs() > std::f32::EPSILON {
+            slider.value = value;
+            slider_changed_events.send(SliderChanged { entity, value });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Slider;
+
+    #[test]
+    fn slider_value_at() {
+        let slider = Slider::new(0.0, -10.0, 10.0);
+        assert_eq!(slider.value_at(0.0), -10.0);
+        assert_eq!(slider.value_at(0.5), 0.0);
+        assert_eq!(slider.value_at(2.0), 10.0);
+
+        let slider = slider.with_step(5.0);
+        assert_eq!(slider.value_at(0.3), -5.0);
+        assert_eq!(slider.value_at(0.9), 10.0);
+
+        let slider = Slider::new(25.0, 0.0, 100.0);
+        assert_eq!(slider.fraction(), 0.25);
+    }
+}
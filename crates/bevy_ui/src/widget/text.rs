@@ -1,7 +1,8 @@
-use crate::{CalculatedSize, Node};
-use bevy_asset::{Assets, Handle};
+use crate::{CalculatedSize, Node, UiScale};
+use bevy_app::prelude::{EventReader, Events};
+use bevy_asset::{AssetEvent, Assets, Handle};
 use bevy_ecs::{Changed, Entity, Local, Query, QuerySet, Res, ResMut};
-use bevy_math::Size;
+use bevy_math::{Size, Vec2};
 use bevy_render::{
     draw::{Draw, DrawContext, Drawable},
     mesh::Mesh,
@@ -10,12 +11,17 @@ use bevy_render::{
     texture::Texture,
 };
 use bevy_sprite::{TextureAtlas, QUAD_HANDLE};
-use bevy_text::{DrawableText, Font, FontAtlasSet, TextStyle};
+use bevy_text::{
+    DrawableText, Font, FontAtlasSet, Localization, LocalizedText, StringTable, TextMeasure,
+    TextStyle,
+};
 use bevy_transform::prelude::GlobalTransform;
 
 #[derive(Debug, Default)]
 pub struct QueuedText {
     entities: Vec<Entity>,
+    /// The [UiScale] the text was measured at
+    scale: f32,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -25,38 +31,82 @@ pub struct Text {
     pub style: TextStyle,
 }
 
+#[derive(Default)]
+pub struct LocalizedTextState {
+    string_table_event_reader: EventReader<AssetEvent<StringTable>>,
+    string_tables: Vec<Handle<StringTable>>,
+}
+
+/// Updates the value of [Text] components from their [LocalizedText]. All localized text is updated when the
+/// [Localization] or one of its string tables changes.
+pub fn localized_text_system(
+    mut state: Local<LocalizedTextState>,
+    localization: Res<Localization>,
+    string_tables: Res<Assets<StringTable>>,
+    string_table_events: Res<Events<AssetEvent<StringTable>>>,
+    mut queries: QuerySet<(
+        Query<(Changed<LocalizedText>, &mut Text)>,
+        Query<(&LocalizedText, &mut Text)>,
+    )>,
+) {
+    let mut string_tables_changed = state
+        .string_table_event_reader
+        .iter(&string_table_events)
+        .count()
+        > 0;
+    if state.string_tables != localization.string_tables {
+        state.string_tables = localization.string_tables.clone();
+        string_tables_changed = true;
+    }
+
+    if string_tables_changed {
+        for (localized_text, mut text) in queries.q1_mut().iter_mut() {
+            text.value = localized_text.format(&localization, &string_tables);
+        }
+    } else {
+        for (localized_text, mut text) in queries.q0_mut().iter_mut() {
+            text.value = localized_text.format(&localization, &string_tables);
+        }
+    }
+}
+
 pub fn text_system(
     mut queued_text: Local<QueuedText>,
+    ui_scale: Res<UiScale>,
     mut textures: ResMut<Assets<Texture>>,
     fonts: Res<Assets<Font>>,
     mut font_atlas_sets: ResMut<Assets<FontAtlasSet>>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut queries: QuerySet<(
-        Query<(Entity, Changed<Text>, &mut CalculatedSize)>,
-        Query<(&Text, &mut CalculatedSize)>,
+        Query<(
+            Entity,
+            Changed<Text>,
+            &mut CalculatedSize,
+            Option<&mut TextMeasure>,
+        )>,
+        Query<(Entity, &Text, &mut CalculatedSize, Option<&mut TextMeasure>)>,
     )>,
 ) {
+    // text is rasterized at its scaled size, so all of it is measured again when the scale changes
+    let rescaled = queued_text.scale != ui_scale.scale;
+    queued_text.scale = ui_scale.scale;
+
     // add queued text to atlases
     let mut new_queued_text = Vec::new();
     for entity in queued_text.entities.drain(..) {
-        if let Ok((text, mut calculated_size)) = queries.q1_mut().get_mut(entity) {
-            let font_atlases = font_atlas_sets
-                .get_or_insert_with(text.font.id, || FontAtlasSet::new(text.font.clone_weak()));
-            // TODO: this call results in one or more TextureAtlases, whose render resources are created in the RENDER_GRAPH_SYSTEMS
-            // stage. That logic runs _before_ the DRAW stage, which means we cant call add_glyphs_to_atlas in the draw stage
-            // without our render resources being a frame behind. Therefore glyph atlasing either needs its own system or the TextureAtlas
-            // resource generation needs to happen AFTER the render graph systems. maybe draw systems should execute within the
-            // render graph so ordering like this can be taken into account? Maybe the RENDER_GRAPH_SYSTEMS stage should be removed entirely
-            // in favor of node.update()? Regardless, in the immediate short term the current approach is fine.
-            if let Some(width) = font_atlases.add_glyphs_to_atlas(
+        if let Ok((_entity, text, mut calculated_size, mut text_measure)) =
+            queries.q1_mut().get_mut(entity)
+        {
+            if !measure_text(
+                &text,
+                ui_scale.scale,
                 &fonts,
+                &mut font_atlas_sets,
                 &mut texture_atlases,
                 &mut textures,
-                text.style.font_size,
-                &text.value,
+                &mut calculated_size,
+                text_measure.as_deref_mut(),
             ) {
-                calculated_size.size = Size::new(width, text.style.font_size);
-            } else {
                 new_queued_text.push(entity);
             }
         }
@@ -64,35 +114,85 @@ pub fn text_system(
 
     queued_text.entities = new_queued_text;
 
-    // add changed text to atlases
-    for (entity, text, mut calculated_size) in queries.q0_mut().iter_mut() {
-        let font_atlases = font_atlas_sets
-            .get_or_insert_with(text.font.id, || FontAtlasSet::new(text.font.clone_weak()));
-        // TODO: this call results in one or more TextureAtlases, whose render resources are created in the RENDER_GRAPH_SYSTEMS
-        // stage. That logic runs _before_ the DRAW stage, which means we cant call add_glyphs_to_atlas in the draw stage
-        // without our render resources being a frame behind. Therefore glyph atlasing either needs its own system or the TextureAtlas
-        // resource generation needs to happen AFTER the render graph systems. maybe draw systems should execute within the
-        // render graph so ordering like this can be taken into account? Maybe the RENDER_GRAPH_SYSTEMS stage should be removed entirely
-        // in favor of node.update()? Regardless, in the immediate short term the current approach is fine.
-        if let Some(width) = font_atlases.add_glyphs_to_atlas(
-            &fonts,
-            &mut texture_atlases,
-            &mut textures,
-            text.style.font_size,
-            &text.value,
-        ) {
-            calculated_size.size = Size::new(width, text.style.font_size);
-        } else {
-            queued_text.entities.push(entity);
+    if rescaled {
+        for (entity, text, mut calculated_size, mut text_measure) in queries.q1_mut().iter_mut() {
+            if !measure_text(
+                &text,
+                ui_scale.scale,
+                &fonts,
+                &mut font_atlas_sets,
+                &mut texture_atlases,
+                &mut textures,
+                &mut calculated_size,
+                text_measure.as_deref_mut(),
+            ) {
+                queued_text.entities.push(entity);
+            }
+        }
+    } else {
+        // add changed text to atlases
+        for (entity, text, mut calculated_size, mut text_measure) in queries.q0_mut().iter_mut() {
+            if !measure_text(
+                &text,
+                ui_scale.scale,
+                &fonts,
+                &mut font_atlas_sets,
+                &mut texture_atlases,
+                &mut textures,
+                &mut calculated_size,
+                text_measure.as_deref_mut(),
+            ) {
+                queued_text.entities.push(entity);
+            }
         }
     }
 }
 
+/// Adds the glyphs of the text at its font size multiplied by `scale` to the font atlases and measures the text. The
+/// [CalculatedSize] is set to the size of the text without wrapping. Returns false if the font hasn't been loaded yet.
+#[allow(clippy::too_many_arguments)]
+fn measure_text(
+    text: &Text,
+    scale: f32,
+    fonts: &Assets<Font>,
+    font_atlas_sets: &mut Assets<FontAtlasSet>,
+    texture_atlases: &mut Assets<TextureAtlas>,
+    textures: &mut Assets<Texture>,
+    calculated_size: &mut CalculatedSize,
+    text_measure: Option<&mut TextMeasure>,
+) -> bool {
+    let font_size = text.style.font_size * scale;
+    let font_atlases = font_atlas_sets
+        .get_or_insert_with(text.font.id, || FontAtlasSet::new(text.font.clone_weak()));
+    // TODO: this call results in one or more TextureAtlases, whose render resources are created in the RENDER_GRAPH_SYSTEMS
+    // stage. That logic runs _before_ the DRAW stage, which means we cant call add_glyphs_to_atlas in the draw stage
+    // without our render resources being a frame behind. Therefore glyph atlasing either needs its own system or the TextureAtlas
+    // resource generation needs to happen AFTER the render graph systems. maybe draw systems should execute within the
+    // render graph so ordering like this can be taken into account? Maybe the RENDER_GRAPH_SYSTEMS stage should be removed entirely
+    // in favor of node.update()? Regardless, in the immediate short term the current approach is fine.
+    if font_atlases
+        .add_glyphs_to_atlas(fonts, texture_atlases, textures, font_size, &text.value)
+        .is_none()
+    {
+        return false;
+    }
+
+    let font = fonts.get(&text.font).unwrap();
+    let measure = TextMeasure::new(font, font_size, &text.value);
+    let size = measure.size(None);
+    calculated_size.size = Size::new(size.x(), size.y());
+    if let Some(text_measure) = text_measure {
+        *text_measure = measure;
+    }
+    true
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn draw_text_system(
     mut draw_context: DrawContext,
     fonts: Res<Assets<Font>>,
     msaa: Res<Msaa>,
+    ui_scale: Res<UiScale>,
     font_atlas_sets: Res<Assets<FontAtlasSet>>,
     texture_atlases: Res<Assets<TextureAtlas>>,
     meshes: Res<Assets<Mesh>>,
@@ -110,8 +210,18 @@ pub fn draw_text_system(
     };
 
     for (mut draw, text, node, global_transform) in query.iter_mut() {
+        // nodes inside a Display::None node are laid out with a size of zero
+        if node.size == Vec2::zero() {
+            continue;
+        }
+
         if let Some(font) = fonts.get(&text.font) {
             let position = global_transform.translation - (node.size / 2.0).extend(0.0);
+            // the glyphs were added to the font atlas at the scaled size
+            let style = TextStyle {
+                font_size: text.style.font_size * ui_scale.scale,
+                color: text.style.color,
+            };
             let mut drawable_text = DrawableText {
                 font,
                 font_atlas_set: font_atlas_sets.get(text.font.id).unwrap(),
@@ -120,7 +230,7 @@ pub fn draw_text_system(
                 asset_render_resource_bindings: &mut asset_render_resource_bindings,
                 position,
                 msaa: &msaa,
-                style: &text.style,
+                style: &style,
                 text: &text.value,
                 container_size: node.size,
                 font_quad_vertex_descriptor: &font_quad_vertex_descriptor,
@@ -0,0 +1,227 @@
+use crate::{Display, Node, PositionType, Style, UiScale, Val};
+use bevy_ecs::{Entity, Query, Res};
+use bevy_math::{Vec2, Vec3};
+use bevy_render::{
+    camera::{ActiveCameras, Camera},
+    render_graph::base,
+};
+use bevy_sprite::virtual_resolution::VirtualResolution;
+use bevy_transform::components::GlobalTransform;
+use bevy_window::Windows;
+
+/// Positions a ui node over the screen position of a world entity, for example for health bars and waypoint markers.
+/// The node is centered on the entity and hidden while the entity is off screen, unless `clamp_margin` is set.
+/// Anchored nodes should not have a parent node, because their position is relative to it.
+#[derive(Debug, Clone)]
+pub struct WorldAnchor {
+    pub entity: Entity,
+    /// Added to the entity's position, for example to place a health bar above a character's head
+    pub offset: Vec3,
+    /// The camera the entity is seen through. `None` uses the active 3d camera.
+    pub camera: Option<Entity>,
+    /// If set, the node stays this many pixels inside the edges of the camera's viewport while the entity is off
+    /// screen instead of being hidden
+    pub clamp_margin: Option<f32>,
+}
+
+impl WorldAnchor {
+    pub fn new(entity: Entity) -> Self {
+        WorldAnchor {
+            entity,
+            offset: Vec3::zero(),
+            camera: None,
+            clamp_margin: None,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn clamped(mut self, margin: f32) -> Self {
+        self.clamp_margin = Some(margin);
+        self
+    }
+}
+
+/// Tells where the entity of a clamped [WorldAnchor] is while it is off screen, for example to rotate an arrow
+/// towards it
+#[derive(Debug, Clone, Default)]
+pub struct OffScreenIndicator {
+    pub off_screen: bool,
+    /// The screen direction from the center of the viewport to the entity, with y pointing up
+    pub direction: Vec2,
+}
+
+/// The position a node anchored at `target` is shown at in a viewport from `min` to `max`, and whether the target is
+/// off screen. `target` is `None` for targets behind the camera, which are moved to the edge in `direction`.
+fn anchored_position(
+    target: Option<Vec2>,
+    direction: Vec2,
+    min: Vec2,
+    max: Vec2,
+    clamp_margin: Option<f32>,
+) -> Option<(Vec2, bool)> {
+    let on_screen = target.map_or(false, |target| {
+        target.x() >= min.x()
+            && target.x() <= max.x()
+            && target.y() >= min.y()
+            && target.y() <= max.y()
+    });
+    let margin = match clamp_margin {
+        Some(margin) => margin,
+        None if on_screen => return target.map(|target| (target, false)),
+        None => return None,
+    };
+
+    let center = (min + max) / 2.0;
+    let half_size = ((max - min) / 2.0 - Vec2::new(margin, margin)).max(Vec2::zero());
+    let position = match target {
+        Some(target) if on_screen => target.max(center - half_size).min(center + half_size),
+        _ => {
+            // scale the direction until it reaches the edge
+            let scale_x = if direction.x() != 0.0 {
+                half_size.x() / direction.x().abs()
+            } else {
+                std::f32::INFINITY
+            };
+            let scale_y = if direction.y() != 0.0 {
+                half_size.y() / direction.y().abs()
+            } else {
+                std::f32::INFINITY
+            };
+            let scale = scale_x.min(scale_y);
+            if scale.is_finite() {
+                center + direction * scale
+            } else {
+                center
+            }
+        }
+    };
+
+    Some((position, !on_screen))
+}
+
+/// Where the node of `anchor` is shown in ui coordinates, whether its entity is off screen, and the screen direction
+/// to the entity. Returns `None` if the node is hidden.
+fn anchor_screen_position(
+    anchor: &WorldAnchor,
+    windows: &Windows,
+    active_cameras: &ActiveCameras,
+    virtual_resolution: &VirtualResolution,
+    camera_query: &Query<(&Camera, &GlobalTransform)>,
+    target_query: &Query<&GlobalTransform>,
+) -> Option<(Vec2, bool, Vec2)> {
+    let camera_entity = anchor
+        .camera
+        .or_else(|| active_cameras.get(base::camera::CAMERA3D))?;
+    let (camera, camera_transform) = camera_query.get(camera_entity).ok()?;
+    let window = windows.get(camera.window)?;
+    let window_size = Vec2::new(window.width() as f32, window.height() as f32);
+    let target_transform = target_query.get(anchor.entity).ok()?;
+    let world_position = target_transform.translation + anchor.offset;
+
+    let target = camera
+        .world_to_ndc(camera_transform, world_position)
+        .map(|ndc| camera.ndc_to_screen(window_size, ndc.truncate()));
+    let min = camera.ndc_to_screen(window_size, Vec2::new(-1.0, -1.0));
+    let max = camera.ndc_to_screen(window_size, Vec2::new(1.0, 1.0));
+    let direction = match target {
+        Some(target) => target - (min + max) / 2.0,
+        None => {
+            // behind the camera, so use the direction in the camera's view plane
+            let local = camera_transform
+                .compute_matrix()
+                .inverse()
+                .transform_point3(world_position);
+            Vec2::new(local.x(), local.y())
+        }
+    };
+    let direction = if direction.length_squared() > 0.0 {
+        direction.normalize()
+    } else {
+        Vec2::zero()
+    };
+
+    let (position, off_screen) =
+        anchored_position(target, direction, min, max, anchor.clamp_margin)?;
+    let position = virtual_resolution.window_to_virtual(window_size, position);
+    Some((position, off_screen, direction))
+}
+
+/// Moves nodes with a [WorldAnchor] to the screen position of their entity. Runs before the ui layout, so nodes
+/// follow the positions entities had at the end of the previous frame.
+pub fn world_anchor_system(
+    windows: Res<Windows>,
+    active_cameras: Res<ActiveCameras>,
+    virtual_resolution: Res<VirtualResolution>,
+    ui_scale: Res<UiScale>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    target_query: Query<&GlobalTransform>,
+    mut node_query: Query<(
+        &WorldAnchor,
+        &Node,
+        &mut Style,
+        Option<&mut OffScreenIndicator>,
+    )>,
+) {
+    for (anchor, node, mut style, indicator) in node_query.iter_mut() {
+        match anchor_screen_position(
+            anchor,
+            &windows,
+            &active_cameras,
+            &virtual_resolution,
+            &camera_query,
+            &target_query,
+        ) {
+            Some((position, off_screen, direction)) => {
+                style.display = Display::Flex;
+                style.position_type = PositionType::Absolute;
+                // styles are in unscaled pixels
+                let corner = ui_scale.window_to_ui(position - node.size / 2.0);
+                style.position.left = Val::Px(corner.x());
+                style.position.bottom = Val::Px(corner.y());
+                if let Some(mut indicator) = indicator {
+                    indicator.off_screen = off_screen;
+                    indicator.direction = direction;
+                }
+            }
+            None => style.display = Display::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchored_position_clamps() {
+        let min = Vec2::zero();
+        let max = Vec2::new(200.0, 100.0);
+        let right = Vec2::new(1.0, 0.0);
+        assert_eq!(
+            anchored_position(Some(Vec2::new(50.0, 50.0)), right, min, max, None),
+            Some((Vec2::new(50.0, 50.0), false))
+        );
+        assert_eq!(
+            anchored_position(Some(Vec2::new(300.0, 50.0)), right, min, max, None),
+            None
+        );
+        assert_eq!(
+            anchored_position(Some(Vec2::new(300.0, 50.0)), right, min, max, Some(10.0)),
+            Some((Vec2::new(190.0, 50.0), true))
+        );
+        // on screen, but within the margin
+        assert_eq!(
+            anchored_position(Some(Vec2::new(5.0, 50.0)), right, min, max, Some(10.0)),
+            Some((Vec2::new(10.0, 50.0), false))
+        );
+        // behind the camera
+        assert_eq!(
+            anchored_position(None, Vec2::new(0.0, -1.0), min, max, Some(10.0)),
+            Some((Vec2::new(100.0, 10.0), true))
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use bevy_app::prelude::*;
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_ecs::{IntoQuerySystem, Res, ResMut};
+use bevy_render::mesh::MeshBuffers;
+
+/// Adds diagnostics that show how well mesh gpu buffers are shared between meshes with identical data
+#[derive(Default)]
+pub struct MeshBufferDiagnosticsPlugin;
+
+impl Plugin for MeshBufferDiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(Self::setup_system.system())
+            .add_system(Self::diagnostic_system.system());
+    }
+}
+
+impl MeshBufferDiagnosticsPlugin {
+    pub const MESHES: DiagnosticId =
+        DiagnosticId::from_u128(141583647209365187203415926580271946315);
+    pub const MESH_BUFFER_SETS: DiagnosticId =
+        DiagnosticId::from_u128(276015839467120583914762035819470263841);
+    pub const DEDUPLICATION_RATIO: DiagnosticId =
+        DiagnosticId::from_u128(92837461027364019283746510293847561029);
+    pub const MESH_BUFFER_BYTES: DiagnosticId =
+        DiagnosticId::from_u128(203948571620394857162039485716203948571);
+    pub const SAVED_BYTES: DiagnosticId =
+        DiagnosticId::from_u128(318273645091827364509182736450918273645);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(Self::MESHES, "meshes", 10));
+        diagnostics.add(Diagnostic::new(
+            Self::MESH_BUFFER_SETS,
+            "mesh_buffer_sets",
+            10,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::DEDUPLICATION_RATIO,
+            "mesh_deduplication_ratio",
+            10,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::MESH_BUFFER_BYTES,
+            "mesh_buffer_bytes",
+            10,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::SAVED_BYTES,
+            "mesh_buffer_saved_bytes",
+            10,
+        ));
+    }
+
+    pub fn diagnostic_system(mut diagnostics: ResMut<Diagnostics>, mesh_buffers: Res<MeshBuffers>) {
+        diagnostics.add_measurement(Self::MESHES, mesh_buffers.mesh_count() as f64);
+        diagnostics.add_measurement(
+            Self::MESH_BUFFER_SETS,
+            mesh_buffers.buffer_set_count() as f64,
+        );
+        diagnostics.add_measurement(
+            Self::DEDUPLICATION_RATIO,
+            mesh_buffers.deduplication_ratio(),
+        );
+        diagnostics.add_measurement(Self::MESH_BUFFER_BYTES, mesh_buffers.bytes() as f64);
+        diagnostics.add_measurement(Self::SAVED_BYTES, mesh_buffers.saved_bytes() as f64);
+    }
+}
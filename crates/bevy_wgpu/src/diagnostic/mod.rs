@@ -1,2 +1,10 @@
+mod mesh_buffer_diagnostics_plugin;
+#[cfg(not(target_arch = "wasm32"))]
+mod wgpu_frame_pacing_diagnostics_plugin;
+mod wgpu_render_statistics_diagnostics_plugin;
 mod wgpu_resource_diagnostics_plugin;
+pub use mesh_buffer_diagnostics_plugin::MeshBufferDiagnosticsPlugin;
+#[cfg(not(target_arch = "wasm32"))]
+pub use wgpu_frame_pacing_diagnostics_plugin::WgpuFramePacingDiagnosticsPlugin;
+pub use wgpu_render_statistics_diagnostics_plugin::WgpuRenderStatisticsDiagnosticsPlugin;
 pub use wgpu_resource_diagnostics_plugin::WgpuResourceDiagnosticsPlugin;
@@ -0,0 +1,49 @@
+use crate::WgpuFramePacingStats;
+use bevy_app::prelude::*;
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_ecs::{IntoQuerySystem, Res, ResMut};
+
+/// Adds frame latency and frame pacing diagnostics for the wgpu renderer
+#[derive(Default)]
+pub struct WgpuFramePacingDiagnosticsPlugin;
+
+impl Plugin for WgpuFramePacingDiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(Self::setup_system.system())
+            .add_system(Self::diagnostic_system.system());
+    }
+}
+
+impl WgpuFramePacingDiagnosticsPlugin {
+    pub const FRAME_LATENCY: DiagnosticId =
+        DiagnosticId::from_u128(164873213860912381409364815226584316512);
+    pub const FRAME_PACING_WAIT: DiagnosticId =
+        DiagnosticId::from_u128(75420134802139844367210943985126570841);
+    pub const FRAMES_IN_FLIGHT: DiagnosticId =
+        DiagnosticId::from_u128(291645278610253497512384209517385243157);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(Self::FRAME_LATENCY, "frame_latency_ms", 20));
+        diagnostics.add(Diagnostic::new(
+            Self::FRAME_PACING_WAIT,
+            "frame_pacing_wait_ms",
+            20,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::FRAMES_IN_FLIGHT,
+            "frames_in_flight",
+            20,
+        ));
+    }
+
+    pub fn diagnostic_system(
+        mut diagnostics: ResMut<Diagnostics>,
+        stats: Res<WgpuFramePacingStats>,
+    ) {
+        if let Some(latency) = stats.latency {
+            diagnostics.add_measurement(Self::FRAME_LATENCY, latency.as_secs_f64() * 1000.0);
+        }
+        diagnostics.add_measurement(Self::FRAME_PACING_WAIT, stats.wait.as_secs_f64() * 1000.0);
+        diagnostics.add_measurement(Self::FRAMES_IN_FLIGHT, stats.frames_in_flight as f64);
+    }
+}
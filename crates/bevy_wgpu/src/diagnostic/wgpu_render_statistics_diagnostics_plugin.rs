@@ -0,0 +1,112 @@
+use crate::{WgpuRenderPassStatistics, WgpuRenderStatistics};
+use bevy_app::prelude::*;
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_ecs::{IntoQuerySystem, Res, ResMut};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Adds draw call, triangle and state change diagnostics for the wgpu renderer. Totals are reported for the whole
+/// frame and, when `per_pass` is enabled, for each render graph node that runs a render pass.
+#[derive(Default)]
+pub struct WgpuRenderStatisticsDiagnosticsPlugin {
+    pub per_pass: bool,
+}
+
+struct PerPassStatistics(bool);
+
+impl Plugin for WgpuRenderStatisticsDiagnosticsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_resource(PerPassStatistics(self.per_pass))
+            .add_startup_system(Self::setup_system.system())
+            .add_system(Self::diagnostic_system.system());
+    }
+}
+
+impl WgpuRenderStatisticsDiagnosticsPlugin {
+    pub const DRAW_CALLS: DiagnosticId =
+        DiagnosticId::from_u128(230876148914578129470561245780156724193);
+    pub const TRIANGLES: DiagnosticId =
+        DiagnosticId::from_u128(18264095613470965423817650942186534077);
+    pub const STATE_CHANGES: DiagnosticId =
+        DiagnosticId::from_u128(112958630147806452381904765213847059612);
+
+    const PASS_DIAGNOSTIC_BASE: u128 = 269134587016245893074621538940162735104;
+    const MAX_HISTORY_LENGTH: usize = 20;
+
+    /// Returns the id of the draw call, triangle and state change diagnostics of the given render graph node
+    pub fn pass_diagnostic_ids(pass: &str) -> [DiagnosticId; 3] {
+        let mut hasher = DefaultHasher::new();
+        pass.hash(&mut hasher);
+        let base = Self::PASS_DIAGNOSTIC_BASE ^ ((hasher.finish() as u128) << 2);
+        [
+            DiagnosticId::from_u128(base),
+            DiagnosticId::from_u128(base | 1),
+            DiagnosticId::from_u128(base | 2),
+        ]
+    }
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(
+            Self::DRAW_CALLS,
+            "draw_calls",
+            Self::MAX_HISTORY_LENGTH,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::TRIANGLES,
+            "triangles",
+            Self::MAX_HISTORY_LENGTH,
+        ));
+        diagnostics.add(Diagnostic::new(
+            Self::STATE_CHANGES,
+            "state_changes",
+            Self::MAX_HISTORY_LENGTH,
+        ));
+    }
+
+    fn add_measurements(
+        diagnostics: &mut Diagnostics,
+        ids: [DiagnosticId; 3],
+        statistics: &WgpuRenderPassStatistics,
+    ) {
+        diagnostics.add_measurement(ids[0], statistics.draw_calls as f64);
+        diagnostics.add_measurement(ids[1], statistics.triangles as f64);
+        diagnostics.add_measurement(ids[2], statistics.state_changes() as f64);
+    }
+
+    fn diagnostic_system(
+        mut diagnostics: ResMut<Diagnostics>,
+        per_pass: Res<PerPassStatistics>,
+        render_statistics: Res<WgpuRenderStatistics>,
+    ) {
+        Self::add_measurements(
+            &mut diagnostics,
+            [Self::DRAW_CALLS, Self::TRIANGLES, Self::STATE_CHANGES],
+            &render_statistics.total(),
+        );
+
+        if !per_pass.0 {
+            return;
+        }
+
+        for (pass, statistics) in render_statistics.passes.iter() {
+            let ids = Self::pass_diagnostic_ids(pass);
+            // passes are discovered as they run, so their diagnostics are registered lazily
+            if diagnostics.get(ids[0]).is_none() {
+                for (id, statistic) in ids
+                    .iter()
+                    .zip(&["draw_calls", "triangles", "state_changes"])
+                {
+                    diagnostics.add(Diagnostic::new(
+                        *id,
+                        &format!("{}_{}", pass, statistic),
+                        Self::MAX_HISTORY_LENGTH,
+                    ));
+                }
+            }
+
+            Self::add_measurements(&mut diagnostics, ids, statistics);
+        }
+    }
+}
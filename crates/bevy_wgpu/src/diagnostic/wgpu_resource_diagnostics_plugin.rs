@@ -25,6 +25,8 @@ impl WgpuResourceDiagnosticsPlugin {
         DiagnosticId::from_u128(133146619577893994787249934474491530491);
     pub const RENDER_PIPELINES: DiagnosticId =
         DiagnosticId::from_u128(278527620040377353875091478462209885377);
+    pub const COMPUTE_PIPELINES: DiagnosticId =
+        DiagnosticId::from_u128(160383711862374015934716209455812384761);
     pub const SAMPLERS: DiagnosticId =
         DiagnosticId::from_u128(305855369913076220671125671543184691267);
     pub const SHADER_MODULES: DiagnosticId =
@@ -79,6 +81,12 @@ impl WgpuResourceDiagnosticsPlugin {
             "render_pipelines",
             10,
         ));
+
+        diagnostics.add(Diagnostic::new(
+            Self::COMPUTE_PIPELINES,
+            "compute_pipelines",
+            10,
+        ));
     }
 
     pub fn diagnostic_system(
@@ -179,5 +187,14 @@ impl WgpuResourceDiagnosticsPlugin {
                 .read()
                 .len() as f64,
         );
+
+        diagnostics.add_measurement(
+            Self::COMPUTE_PIPELINES,
+            render_resource_context
+                .resources
+                .compute_pipelines
+                .read()
+                .len() as f64,
+        );
     }
 }
@@ -1,19 +1,39 @@
 pub mod diagnostic;
 pub mod renderer;
+mod wgpu_compute_pass;
+#[cfg(feature = "interop")]
+mod wgpu_device;
+#[cfg(not(target_arch = "wasm32"))]
+mod wgpu_frame_pacer;
 mod wgpu_render_pass;
+mod wgpu_render_statistics;
 mod wgpu_renderer;
+mod wgpu_resource_report;
 mod wgpu_resources;
 mod wgpu_type_converter;
 
 use futures_lite::future;
+pub use wgpu_compute_pass::*;
+#[cfg(feature = "interop")]
+pub use wgpu_device::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use wgpu_frame_pacer::*;
 pub use wgpu_render_pass::*;
+pub use wgpu_render_statistics::*;
 pub use wgpu_renderer::*;
+pub use wgpu_resource_report::*;
 pub use wgpu_resources::*;
 
+/// The wgpu version the renderer uses, for implementing [WgpuDevice] integrations
+#[cfg(feature = "interop")]
+pub use wgpu;
+
 use bevy_app::prelude::*;
 use bevy_ecs::{IntoQuerySystem, IntoThreadLocalSystem, Resources, World};
-use bevy_render::renderer::{free_shared_buffers_system, RenderResourceContext, SharedBuffers};
-use renderer::WgpuRenderResourceContext;
+use bevy_render::renderer::{
+    free_shared_buffers_system, RenderCapabilities, RenderResourceContext, SharedBuffers,
+};
+use renderer::{WgpuRenderResourceContext, WgpuSubmissionMode};
 
 #[derive(Default)]
 pub struct WgpuPlugin;
@@ -36,18 +56,57 @@ pub fn get_wgpu_render_system(resources: &mut Resources) -> impl FnMut(&mut Worl
     let options = resources
         .get_cloned::<WgpuOptions>()
         .unwrap_or_else(WgpuOptions::default);
-    let mut wgpu_renderer = future::block_on(WgpuRenderer::new(options));
+    let mut wgpu_renderer = future::block_on(WgpuRenderer::new(options.clone()));
     let resource_context = WgpuRenderResourceContext::new(wgpu_renderer.device.clone());
+    if let Some(max_frames_in_flight) = options.max_frames_in_flight {
+        // keep released resources alive until every frame that could use them has completed
+        resource_context
+            .resources
+            .resource_lifetimes
+            .write()
+            .free_delay = max_frames_in_flight.max(1) as u64;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    resources.insert(WgpuFramePacingStats::default());
+    resources.insert(WgpuRenderStatistics::default());
     resources.insert::<Box<dyn RenderResourceContext>>(Box::new(resource_context.clone()));
+    #[cfg(feature = "interop")]
+    {
+        resources.insert(WgpuDevice {
+            device: wgpu_renderer.device.clone(),
+            queue: wgpu_renderer.queue.clone(),
+        });
+        resources.insert(resource_context.resources.clone());
+    }
     resources.insert(SharedBuffers::new(Box::new(resource_context)));
+    resources.insert(RenderCapabilities::from_adapter_info(
+        &wgpu_renderer.adapter_info,
+    ));
+    resources.insert(wgpu_renderer.adapter_info.clone());
     move |world, resources| {
         wgpu_renderer.update(world, resources);
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct WgpuOptions {
-    power_pref: WgpuPowerOptions,
+    pub power_pref: WgpuPowerOptions,
+    /// How the command buffers recorded each frame are grouped into queue submissions
+    pub submission_mode: WgpuSubmissionMode,
+    /// The maximum number of frames the gpu can fall behind the renderer. Lower values reduce input latency at the
+    /// cost of less overlap between cpu and gpu work. `None`, the default, leaves frame queuing up to the driver. This
+    /// is ignored on the web, where the browser paces frames.
+    pub max_frames_in_flight: Option<usize>,
+}
+
+impl Default for WgpuOptions {
+    fn default() -> Self {
+        WgpuOptions {
+            power_pref: Default::default(),
+            submission_mode: Default::default(),
+            max_frames_in_flight: None,
+        }
+    }
 }
 
 #[derive(Clone)]
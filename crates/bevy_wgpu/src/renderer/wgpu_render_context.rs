@@ -1,9 +1,9 @@
 use super::WgpuRenderResourceContext;
-use crate::{wgpu_type_converter::WgpuInto, WgpuRenderPass, WgpuResourceRefs};
+use crate::{wgpu_type_converter::WgpuInto, WgpuComputePass, WgpuRenderPass, WgpuResourceRefs};
 
 use bevy_render::{
     pass::{
-        PassDescriptor, RenderPass, RenderPassColorAttachmentDescriptor,
+        ComputePass, PassDescriptor, RenderPass, RenderPassColorAttachmentDescriptor,
         RenderPassDepthStencilAttachmentDescriptor, TextureAttachment,
     },
     renderer::{
@@ -12,8 +12,8 @@ use bevy_render::{
     },
     texture::Extent3d,
 };
-
-use std::sync::Arc;
+use smallvec::SmallVec;
+use std::{borrow::Cow, sync::Arc};
 
 #[derive(Debug, Default)]
 pub struct LazyCommandEncoder {
@@ -55,6 +55,9 @@ pub struct WgpuRenderContext {
     pub device: Arc<wgpu::Device>,
     pub command_encoder: LazyCommandEncoder,
     pub render_resource_context: WgpuRenderResourceContext,
+    /// The name of the render graph node that is currently using this context. Render pass statistics are recorded
+    /// under this name.
+    pub current_node: Option<Cow<'static, str>>,
 }
 
 impl WgpuRenderContext {
@@ -63,9 +66,16 @@ impl WgpuRenderContext {
             device,
             render_resource_context: resources,
             command_encoder: LazyCommandEncoder::default(),
+            current_node: None,
         }
     }
 
+    /// The command encoder of the current frame. Commands encoded here, like the passes of an external renderer, are
+    /// submitted with the rest of the frame in render graph order.
+    pub fn command_encoder(&mut self) -> &mut wgpu::CommandEncoder {
+        self.command_encoder.get_or_create(&self.device)
+    }
+
     /// Consume this context, finalize the current CommandEncoder (if it exists), and take the current WgpuResources.
     /// This is intended to be called from a worker thread right before synchronizing with the main thread.   
     pub fn finish(&mut self) -> Option<wgpu::CommandBuffer> {
@@ -114,6 +124,50 @@ impl RenderContext for WgpuRenderContext {
         )
     }
 
+    fn copy_texture_to_buffer(
+        &mut self,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_buffer: BufferId,
+        destination_offset: u64,
+        destination_bytes_per_row: u32,
+        size: Extent3d,
+    ) {
+        self.render_resource_context.copy_texture_to_buffer(
+            self.command_encoder.get_or_create(&self.device),
+            source_texture,
+            source_origin,
+            source_mip_level,
+            destination_buffer,
+            destination_offset,
+            destination_bytes_per_row,
+            size,
+        )
+    }
+
+    fn copy_texture_to_texture(
+        &mut self,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_texture: TextureId,
+        destination_origin: [u32; 3],
+        destination_mip_level: u32,
+        size: Extent3d,
+    ) {
+        self.render_resource_context.copy_texture_to_texture(
+            self.command_encoder.get_or_create(&self.device),
+            source_texture,
+            source_origin,
+            source_mip_level,
+            destination_texture,
+            destination_origin,
+            destination_mip_level,
+            size,
+        )
+    }
+
     fn resources(&self) -> &dyn RenderResourceContext {
         &self.render_resource_context
     }
@@ -134,7 +188,7 @@ impl RenderContext for WgpuRenderContext {
         let resource_lock = self.render_resource_context.resources.read();
         let refs = resource_lock.refs();
         let mut encoder = self.command_encoder.take().unwrap();
-        {
+        let statistics = {
             let render_pass = create_render_pass(
                 pass_descriptor,
                 render_resource_bindings,
@@ -146,9 +200,40 @@ impl RenderContext for WgpuRenderContext {
                 render_context: self,
                 wgpu_resources: refs,
                 pipeline_descriptor: None,
+                statistics: Default::default(),
             };
 
             run_pass(&mut wgpu_render_pass);
+            wgpu_render_pass.statistics
+        };
+
+        self.command_encoder.set(encoder);
+        self.render_resource_context
+            .render_statistics
+            .lock()
+            .record(
+                self.current_node
+                    .clone()
+                    .unwrap_or(Cow::Borrowed("unnamed_pass")),
+                statistics,
+            );
+    }
+
+    fn begin_compute_pass(&mut self, run_pass: &mut dyn FnMut(&mut dyn ComputePass)) {
+        if !self.command_encoder.is_some() {
+            self.command_encoder.create(&self.device);
+        }
+        let resource_lock = self.render_resource_context.resources.read();
+        let refs = resource_lock.refs();
+        let mut encoder = self.command_encoder.take().unwrap();
+        {
+            let mut wgpu_compute_pass = WgpuComputePass {
+                compute_pass: encoder.begin_compute_pass(),
+                render_context: self,
+                wgpu_resources: refs,
+            };
+
+            run_pass(&mut wgpu_compute_pass);
         }
 
         self.command_encoder.set(encoder);
@@ -168,7 +253,7 @@ pub fn create_render_pass<'a, 'b>(
             .map(|c| {
                 create_wgpu_color_attachment_descriptor(global_render_resource_bindings, refs, c)
             })
-            .collect::<Vec<wgpu::RenderPassColorAttachmentDescriptor>>(),
+            .collect::<SmallVec<[wgpu::RenderPassColorAttachmentDescriptor; 4]>>(),
         depth_stencil_attachment: pass_descriptor.depth_stencil_attachment.as_ref().map(|d| {
             create_wgpu_depth_stencil_attachment_descriptor(
                 global_render_resource_bindings,
@@ -185,7 +270,7 @@ fn get_texture_view<'a>(
     attachment: &TextureAttachment,
 ) -> &'a wgpu::TextureView {
     match attachment {
-        TextureAttachment::Name(name) => match global_render_resource_bindings.get(&name) {
+        TextureAttachment::Name(name) => match global_render_resource_bindings.get(name) {
             Some(RenderResourceBinding::Texture(resource)) => refs.textures.get(&resource).unwrap(),
             _ => {
                 panic!("Color attachment {} does not exist", name);
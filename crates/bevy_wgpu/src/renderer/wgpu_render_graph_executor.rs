@@ -1,16 +1,40 @@
 use super::{WgpuRenderContext, WgpuRenderResourceContext};
 use bevy_ecs::{Resources, World};
 use bevy_render::{
-    render_graph::{Edge, NodeId, ResourceSlots, StageBorrow},
+    render_graph::{
+        validate_node_inputs, Edge, NodeId, RenderGraphValidation, ResourceSlots, StageBorrow,
+    },
     renderer::RenderResourceContext,
 };
 use bevy_utils::HashMap;
 use parking_lot::RwLock;
+use smallvec::SmallVec;
 use std::sync::Arc;
 
+/// Controls how the command buffers recorded by the render graph are grouped into queue submissions. Every
+/// `queue.submit` has a fixed driver cost, so fewer submissions are generally faster.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WgpuSubmissionMode {
+    /// Submits each render context's command buffer on its own. This is the slowest mode, but it makes it easier to
+    /// find the work that caused a gpu error.
+    PerContext,
+    /// Submits the command buffers of all render contexts in a render graph stage together
+    PerStage,
+    /// Submits every command buffer recorded during the frame with a single `queue.submit`. Command buffers are
+    /// submitted in stage order, so dependencies between passes are still respected.
+    PerFrame,
+}
+
+impl Default for WgpuSubmissionMode {
+    fn default() -> Self {
+        WgpuSubmissionMode::PerStage
+    }
+}
+
 #[derive(Debug)]
 pub struct WgpuRenderGraphExecutor {
     pub max_thread_count: usize,
+    pub submission_mode: WgpuSubmissionMode,
 }
 
 impl WgpuRenderGraphExecutor {
@@ -19,7 +43,7 @@ impl WgpuRenderGraphExecutor {
         world: &World,
         resources: &Resources,
         device: Arc<wgpu::Device>,
-        queue: &mut wgpu::Queue,
+        queue: &wgpu::Queue,
         stages: &mut [StageBorrow],
     ) {
         let mut render_resource_context = resources
@@ -28,7 +52,11 @@ impl WgpuRenderGraphExecutor {
         let render_resource_context = render_resource_context
             .downcast_mut::<WgpuRenderResourceContext>()
             .unwrap();
+        let mut validation = resources
+            .get_mut::<RenderGraphValidation>()
+            .filter(|validation| validation.enabled);
         let node_outputs: Arc<RwLock<HashMap<NodeId, ResourceSlots>>> = Default::default();
+        let mut command_buffers = SmallVec::<[wgpu::CommandBuffer; 4]>::new();
         for stage in stages.iter_mut() {
             // TODO: sort jobs and slice by "amount of work" / weights
             // stage.jobs.sort_by_key(|j| j.node_states.len());
@@ -48,6 +76,14 @@ impl WgpuRenderGraphExecutor {
                 let mut render_context = WgpuRenderContext::new(device, render_resource_context);
                 for job in jobs_chunk.iter_mut() {
                     for node_state in job.node_states.iter_mut() {
+                        if !node_state.is_active() {
+                            // skipped nodes pass on the outputs of the last frame they ran
+                            node_outputs
+                                .write()
+                                .insert(node_state.id, node_state.output_slots.clone());
+                            continue;
+                        }
+
                         // bind inputs from connected node outputs
                         for (i, mut input_slot) in node_state.input_slots.iter_mut().enumerate() {
                             if let Edge::SlotEdge {
@@ -70,6 +106,13 @@ impl WgpuRenderGraphExecutor {
                                 panic!("no edge connected to input")
                             }
                         }
+                        if let Some(validation) = validation.as_mut() {
+                            validation.report(validate_node_inputs(
+                                node_state,
+                                &render_context.render_resource_context,
+                            ));
+                        }
+                        render_context.current_node = node_state.name.clone();
                         node_state.node.update(
                             world,
                             resources,
@@ -89,14 +132,23 @@ impl WgpuRenderGraphExecutor {
             // })
             // .unwrap();
 
-            let mut command_buffers = Vec::new();
             for _i in 0..actual_thread_count {
                 let command_buffer = receiver.recv().unwrap();
                 if let Some(command_buffer) = command_buffer {
-                    command_buffers.push(command_buffer);
+                    if self.submission_mode == WgpuSubmissionMode::PerContext {
+                        queue.submit(Some(command_buffer));
+                    } else {
+                        command_buffers.push(command_buffer);
+                    }
                 }
             }
 
+            if self.submission_mode == WgpuSubmissionMode::PerStage && !command_buffers.is_empty() {
+                queue.submit(command_buffers.drain(..));
+            }
+        }
+
+        if !command_buffers.is_empty() {
             queue.submit(command_buffers.drain(..));
         }
     }
@@ -1,22 +1,28 @@
 use crate::{
     wgpu_type_converter::{OwnedWgpuVertexBufferDescriptor, WgpuInto},
-    WgpuBindGroupInfo, WgpuResources,
+    WgpuBindGroupInfo, WgpuRenderStatistics, WgpuResources,
 };
 
 use bevy_asset::{Assets, Handle, HandleUntyped};
 use bevy_render::{
     pipeline::{
-        BindGroupDescriptor, BindGroupDescriptorId, BindingShaderStage, PipelineDescriptor,
+        BindGroupDescriptor, BindGroupDescriptorId, BindingShaderStage, ComputePipelineDescriptor,
+        PipelineDescriptor,
     },
     renderer::{
-        BindGroup, BufferId, BufferInfo, RenderResourceBinding, RenderResourceContext,
-        RenderResourceId, SamplerId, TextureId,
+        BindGroup, BufferId, BufferInfo, BufferUsage, RenderResourceBinding, RenderResourceContext,
+        RenderResourceId, RenderResourceOwner, SamplerId, TextureId,
     },
     shader::Shader,
-    texture::{Extent3d, SamplerDescriptor, TextureDescriptor},
+    texture::{
+        Extent3d, SamplerDescriptor, TextureDescriptor, TextureDimension, TextureFormat,
+        TextureUsage,
+    },
 };
 use bevy_window::{Window, WindowId};
 use futures_lite::future;
+use parking_lot::Mutex;
+use smallvec::SmallVec;
 use std::{borrow::Cow, ops::Range, sync::Arc};
 use wgpu::util::DeviceExt;
 
@@ -24,6 +30,8 @@ use wgpu::util::DeviceExt;
 pub struct WgpuRenderResourceContext {
     pub device: Arc<wgpu::Device>,
     pub resources: WgpuResources,
+    /// Statistics of the render passes recorded since the last call to [Self::take_render_statistics]
+    pub render_statistics: Arc<Mutex<WgpuRenderStatistics>>,
 }
 
 impl WgpuRenderResourceContext {
@@ -31,9 +39,14 @@ impl WgpuRenderResourceContext {
         WgpuRenderResourceContext {
             device,
             resources: WgpuResources::default(),
+            render_statistics: Default::default(),
         }
     }
 
+    pub fn take_render_statistics(&self) -> WgpuRenderStatistics {
+        std::mem::take(&mut *self.render_statistics.lock())
+    }
+
     pub fn set_window_surface(&self, window_id: WindowId, surface: wgpu::Surface) {
         let mut window_surfaces = self.resources.window_surfaces.write();
         window_surfaces.insert(window_id, surface);
@@ -100,6 +113,83 @@ impl WgpuRenderResourceContext {
         );
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_texture_to_buffer(
+        &self,
+        command_encoder: &mut wgpu::CommandEncoder,
+        source_texture: TextureId,
+        source_origin: [u32; 3], // TODO: replace with math type
+        source_mip_level: u32,
+        destination_buffer: BufferId,
+        destination_offset: u64,
+        destination_bytes_per_row: u32,
+        size: Extent3d,
+    ) {
+        let buffers = self.resources.buffers.read();
+        let textures = self.resources.textures.read();
+
+        let source = textures.get(&source_texture).unwrap();
+        let destination = buffers.get(&destination_buffer).unwrap();
+        command_encoder.copy_texture_to_buffer(
+            wgpu::TextureCopyView {
+                texture: source,
+                mip_level: source_mip_level,
+                origin: wgpu::Origin3d {
+                    x: source_origin[0],
+                    y: source_origin[1],
+                    z: source_origin[2],
+                },
+            },
+            wgpu::BufferCopyView {
+                buffer: destination,
+                layout: wgpu::TextureDataLayout {
+                    offset: destination_offset,
+                    bytes_per_row: destination_bytes_per_row,
+                    rows_per_image: size.height,
+                },
+            },
+            size.wgpu_into(),
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_texture_to_texture(
+        &self,
+        command_encoder: &mut wgpu::CommandEncoder,
+        source_texture: TextureId,
+        source_origin: [u32; 3],
+        source_mip_level: u32,
+        destination_texture: TextureId,
+        destination_origin: [u32; 3],
+        destination_mip_level: u32,
+        size: Extent3d,
+    ) {
+        let textures = self.resources.textures.read();
+        let source = textures.get(&source_texture).unwrap();
+        let destination = textures.get(&destination_texture).unwrap();
+        command_encoder.copy_texture_to_texture(
+            wgpu::TextureCopyView {
+                texture: source,
+                mip_level: source_mip_level,
+                origin: wgpu::Origin3d {
+                    x: source_origin[0],
+                    y: source_origin[1],
+                    z: source_origin[2],
+                },
+            },
+            wgpu::TextureCopyView {
+                texture: destination,
+                mip_level: destination_mip_level,
+                origin: wgpu::Origin3d {
+                    x: destination_origin[0],
+                    y: destination_origin[1],
+                    z: destination_origin[2],
+                },
+            },
+            size.wgpu_into(),
+        );
+    }
+
     pub fn create_bind_group_layout(&self, descriptor: &BindGroupDescriptor) {
         if self
             .resources
@@ -125,6 +215,8 @@ impl WgpuRenderResourceContext {
                     wgpu::ShaderStage::VERTEX
                 } else if binding.shader_stage == BindingShaderStage::FRAGMENT {
                     wgpu::ShaderStage::FRAGMENT
+                } else if binding.shader_stage == BindingShaderStage::COMPUTE {
+                    wgpu::ShaderStage::COMPUTE
                 } else {
                     panic!("Invalid binding shader stage.")
                 };
@@ -154,6 +246,42 @@ impl WgpuRenderResourceContext {
         swap_chain_outputs.insert(id, next_texture);
         Some(id)
     }
+
+    /// Windows without a surface, like the ones created for headless rendering, render to a texture the size of the
+    /// window instead of a swap chain. The texture can be copied from, so its contents can be read back.
+    fn next_offscreen_texture(&self, window: &Window) -> TextureId {
+        let descriptor = TextureDescriptor {
+            size: Extent3d {
+                width: window.width(),
+                height: window.height(),
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::default(),
+            usage: TextureUsage::OUTPUT_ATTACHMENT | TextureUsage::COPY_SRC,
+        };
+        let texture = self
+            .resources
+            .window_offscreen_textures
+            .read()
+            .get(&window.id())
+            .cloned();
+        if let Some(texture) = texture {
+            if self.get_texture_descriptor(texture) == Some(descriptor) {
+                return texture;
+            }
+            self.remove_texture(texture);
+        }
+
+        let texture = self.create_texture(descriptor);
+        self.resources
+            .window_offscreen_textures
+            .write()
+            .insert(window.id(), texture);
+        texture
+    }
 }
 
 impl RenderResourceContext for WgpuRenderResourceContext {
@@ -228,6 +356,11 @@ impl RenderResourceContext for WgpuRenderResourceContext {
 
         buffers.remove(&buffer);
         buffer_infos.remove(&buffer);
+        self.resources
+            .pending_buffer_maps
+            .lock()
+            .maps
+            .remove(&buffer);
     }
 
     fn remove_texture(&self, texture: TextureId) {
@@ -247,7 +380,10 @@ impl RenderResourceContext for WgpuRenderResourceContext {
 
     fn create_shader_module_from_source(&self, shader_handle: &Handle<Shader>, shader: &Shader) {
         let mut shader_modules = self.resources.shader_modules.write();
-        let spirv: Cow<[u32]> = shader.get_spirv(None).into();
+        let spirv: Cow<[u32]> = shader
+            .get_spirv(None)
+            .expect("shaders compile before their modules are created")
+            .into();
         let shader_module = self
             .device
             .create_shader_module(wgpu::ShaderModuleSource::SpirV(spirv));
@@ -273,9 +409,12 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         let mut window_swap_chains = self.resources.window_swap_chains.write();
 
         let swap_chain_descriptor: wgpu::SwapChainDescriptor = window.wgpu_into();
-        let surface = surfaces
-            .get(&window.id())
-            .expect("No surface found for window");
+        let surface = if let Some(surface) = surfaces.get(&window.id()) {
+            surface
+        } else {
+            // windows without a surface render offscreen
+            return;
+        };
         let swap_chain = self
             .device
             .create_swap_chain(surface, &swap_chain_descriptor);
@@ -284,7 +423,16 @@ impl RenderResourceContext for WgpuRenderResourceContext {
     }
 
     fn next_swap_chain_texture(&self, window: &bevy_window::Window) -> TextureId {
-        if let Some(texture_id) = self.try_next_swap_chain_texture(window.id()) {
+        if !self
+            .resources
+            .window_surfaces
+            .read()
+            .contains_key(&window.id())
+        {
+            return self.next_offscreen_texture(window);
+        }
+
+        let texture_id = if let Some(texture_id) = self.try_next_swap_chain_texture(window.id()) {
             texture_id
         } else {
             self.resources
@@ -294,16 +442,41 @@ impl RenderResourceContext for WgpuRenderResourceContext {
             self.create_swap_chain(window);
             self.try_next_swap_chain_texture(window.id())
                 .expect("Failed to acquire next swap chain texture!")
-        }
+        };
+
+        // swap chain textures aren't created by us, but render graph validation needs to know what they look like
+        self.resources.texture_descriptors.write().insert(
+            texture_id,
+            TextureDescriptor {
+                size: Extent3d {
+                    width: window.width(),
+                    height: window.height(),
+                    depth: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::default(),
+                usage: TextureUsage::OUTPUT_ATTACHMENT,
+            },
+        );
+        texture_id
     }
 
     fn drop_swap_chain_texture(&self, texture: TextureId) {
         let mut swap_chain_outputs = self.resources.swap_chain_frames.write();
-        swap_chain_outputs.remove(&texture);
+        // offscreen window textures are kept until the window is resized
+        if swap_chain_outputs.remove(&texture).is_some() {
+            self.resources.texture_descriptors.write().remove(&texture);
+        }
     }
 
     fn drop_all_swap_chain_textures(&self) {
         let mut swap_chain_outputs = self.resources.swap_chain_frames.write();
+        let mut texture_descriptors = self.resources.texture_descriptors.write();
+        for texture in swap_chain_outputs.keys() {
+            texture_descriptors.remove(texture);
+        }
         swap_chain_outputs.clear();
     }
 
@@ -331,6 +504,35 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         asset_resources.remove(&(handle, index));
     }
 
+    fn set_resource_owner(&self, owner: RenderResourceOwner, resource: RenderResourceId) {
+        self.resources
+            .resource_lifetimes
+            .write()
+            .set_owner(owner, resource);
+    }
+
+    fn release_owner_resources(&self, owner: RenderResourceOwner) {
+        self.resources
+            .resource_lifetimes
+            .write()
+            .release_owner(owner);
+    }
+
+    fn release_resource(&self, resource: RenderResourceId) {
+        self.resources.resource_lifetimes.write().release(resource);
+    }
+
+    fn free_released_resources(&self) {
+        let expired = self.resources.resource_lifetimes.write().end_frame();
+        for resource in expired {
+            match resource {
+                RenderResourceId::Buffer(buffer) => self.remove_buffer(buffer),
+                RenderResourceId::Texture(texture) => self.remove_texture(texture),
+                RenderResourceId::Sampler(sampler) => self.remove_sampler(sampler),
+            }
+        }
+    }
+
     fn create_render_pipeline(
         &self,
         pipeline_handle: Handle<PipelineDescriptor>,
@@ -358,7 +560,7 @@ impl RenderResourceContext for WgpuRenderResourceContext {
             .bind_groups
             .iter()
             .map(|bind_group| bind_group_layouts.get(&bind_group.id).unwrap())
-            .collect::<Vec<&wgpu::BindGroupLayout>>();
+            .collect::<SmallVec<[&wgpu::BindGroupLayout; 4]>>();
 
         let pipeline_layout = self
             .device
@@ -372,13 +574,13 @@ impl RenderResourceContext for WgpuRenderResourceContext {
             .vertex_buffer_descriptors
             .iter()
             .map(|v| v.wgpu_into())
-            .collect::<Vec<OwnedWgpuVertexBufferDescriptor>>();
+            .collect::<SmallVec<[OwnedWgpuVertexBufferDescriptor; 4]>>();
 
         let color_states = pipeline_descriptor
             .color_states
             .iter()
             .map(|c| c.wgpu_into())
-            .collect::<Vec<wgpu::ColorStateDescriptor>>();
+            .collect::<SmallVec<[wgpu::ColorStateDescriptor; 4]>>();
 
         self.create_shader_module(&pipeline_descriptor.shader_stages.vertex, shaders);
 
@@ -425,7 +627,7 @@ impl RenderResourceContext for WgpuRenderResourceContext {
                 vertex_buffers: &owned_vertex_buffer_descriptors
                     .iter()
                     .map(|v| v.into())
-                    .collect::<Vec<wgpu::VertexBufferDescriptor>>(),
+                    .collect::<SmallVec<[wgpu::VertexBufferDescriptor; 4]>>(),
             },
             sample_count: pipeline_descriptor.sample_count,
             sample_mask: pipeline_descriptor.sample_mask,
@@ -439,6 +641,60 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         render_pipelines.insert(pipeline_handle, render_pipeline);
     }
 
+    fn create_compute_pipeline(
+        &self,
+        pipeline_handle: Handle<ComputePipelineDescriptor>,
+        pipeline_descriptor: &ComputePipelineDescriptor,
+        shaders: &Assets<Shader>,
+    ) {
+        if self
+            .resources
+            .compute_pipelines
+            .read()
+            .get(&pipeline_handle)
+            .is_some()
+        {
+            return;
+        }
+
+        let layout = pipeline_descriptor.get_layout().unwrap();
+        for bind_group_descriptor in layout.bind_groups.iter() {
+            self.create_bind_group_layout(&bind_group_descriptor);
+        }
+
+        let bind_group_layouts = self.resources.bind_group_layouts.read();
+        let bind_group_layouts = layout
+            .bind_groups
+            .iter()
+            .map(|bind_group| bind_group_layouts.get(&bind_group.id).unwrap())
+            .collect::<SmallVec<[&wgpu::BindGroupLayout; 4]>>();
+
+        let pipeline_layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: bind_group_layouts.as_slice(),
+                push_constant_ranges: &[],
+            });
+
+        self.create_shader_module(&pipeline_descriptor.shader, shaders);
+        let shader_modules = self.resources.shader_modules.read();
+        let shader_module = shader_modules.get(&pipeline_descriptor.shader).unwrap();
+
+        let compute_pipeline =
+            self.device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: pipeline_descriptor.name.as_deref(),
+                    layout: Some(&pipeline_layout),
+                    compute_stage: wgpu::ProgrammableStageDescriptor {
+                        module: shader_module,
+                        entry_point: "main",
+                    },
+                });
+        let mut compute_pipelines = self.resources.compute_pipelines.write();
+        compute_pipelines.insert(pipeline_handle, compute_pipeline);
+    }
+
     fn bind_group_descriptor_exists(
         &self,
         bind_group_descriptor_id: BindGroupDescriptorId,
@@ -491,7 +747,7 @@ impl RenderResourceContext for WgpuRenderResourceContext {
                         resource: wgpu_resource,
                     }
                 })
-                .collect::<Vec<wgpu::BindGroupEntry>>();
+                .collect::<SmallVec<[wgpu::BindGroupEntry; 8]>>();
 
             let bind_group_layout = bind_group_layouts.get(&bind_group_descriptor_id).unwrap();
             let wgpu_bind_group_descriptor = wgpu::BindGroupDescriptor {
@@ -522,6 +778,14 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         self.resources.buffer_infos.read().get(&buffer).cloned()
     }
 
+    fn get_texture_descriptor(&self, texture: TextureId) -> Option<TextureDescriptor> {
+        self.resources
+            .texture_descriptors
+            .read()
+            .get(&texture)
+            .copied()
+    }
+
     fn write_mapped_buffer(
         &self,
         id: BufferId,
@@ -537,17 +801,71 @@ impl RenderResourceContext for WgpuRenderResourceContext {
         write(&mut data, self);
     }
 
+    fn read_mapped_buffer(
+        &self,
+        id: BufferId,
+        range: Range<u64>,
+        read: &mut dyn FnMut(&[u8], &dyn RenderResourceContext),
+    ) {
+        let buffer = {
+            let buffers = self.resources.buffers.read();
+            buffers.get(&id).unwrap().clone()
+        };
+        let buffer_slice = buffer.slice(range);
+        let data = buffer_slice.get_mapped_range();
+        read(&data, self);
+    }
+
     fn map_buffer(&self, id: BufferId) {
+        let mode = match self.resources.buffer_infos.read().get(&id) {
+            Some(info) if info.buffer_usage.contains(BufferUsage::MAP_READ) => wgpu::MapMode::Read,
+            _ => wgpu::MapMode::Write,
+        };
         let buffers = self.resources.buffers.read();
         let buffer = buffers.get(&id).unwrap();
         let buffer_slice = buffer.slice(..);
-        let data = buffer_slice.map_async(wgpu::MapMode::Write);
+        let data = buffer_slice.map_async(mode);
         self.device.poll(wgpu::Maintain::Wait);
         if future::block_on(data).is_err() {
             panic!("failed to map buffer to host");
         }
     }
 
+    fn map_buffer_async(&self, id: BufferId) {
+        let mode = match self.resources.buffer_infos.read().get(&id) {
+            Some(info) if info.buffer_usage.contains(BufferUsage::MAP_READ) => wgpu::MapMode::Read,
+            _ => wgpu::MapMode::Write,
+        };
+        let buffers = self.resources.buffers.read();
+        let buffer = buffers.get(&id).unwrap();
+        let map = Box::pin(buffer.slice(..).map_async(mode));
+        self.resources
+            .pending_buffer_maps
+            .lock()
+            .maps
+            .insert(id, map);
+    }
+
+    fn is_buffer_mapped(&self, id: BufferId) -> bool {
+        let mut pending_buffer_maps = self.resources.pending_buffer_maps.lock();
+        let map = match pending_buffer_maps.maps.get_mut(&id) {
+            Some(map) => map,
+            None => return false,
+        };
+        // runs the callbacks of the maps that completed
+        self.device.poll(wgpu::Maintain::Poll);
+        match future::block_on(future::poll_once(map)) {
+            Some(result) => {
+                pending_buffer_maps.maps.remove(&id);
+                if result.is_err() {
+                    panic!("failed to map buffer to host");
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
     fn unmap_buffer(&self, id: BufferId) {
         let buffers = self.resources.buffers.read();
         let buffer = buffers.get(&id).unwrap();
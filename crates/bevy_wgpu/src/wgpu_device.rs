@@ -0,0 +1,15 @@
+use std::sync::Arc;
+
+/// The wgpu device and queue of the renderer, for integrating external renderers and ui toolkits. This resource is
+/// only added with the `interop` feature.
+///
+/// Use the device to create pipelines, buffers and textures. To draw into the engine's frame, add a render graph node
+/// that downcasts its `RenderContext` to a [WgpuRenderContext](crate::renderer::WgpuRenderContext) and encodes its
+/// passes with [command_encoder](crate::renderer::WgpuRenderContext::command_encoder). The views of engine textures,
+/// including the swap chain textures of windows, are in its `render_resource_context.resources`. Command buffers
+/// submitted to the queue directly run before the engine's frame.
+#[derive(Debug, Clone)]
+pub struct WgpuDevice {
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+}
@@ -0,0 +1,143 @@
+use futures_lite::future;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+const FENCE_SIZE: u64 = 4;
+/// How long before a frame is expected to complete the pacer stops sleeping and starts polling for it. Sleeps can
+/// overshoot by about this much.
+const SPIN_MARGIN: Duration = Duration::from_millis(1);
+
+type FenceFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send + Sync>>;
+
+struct InFlightFrame {
+    fence: wgpu::Buffer,
+    done: FenceFuture,
+    started: Instant,
+}
+
+/// Frame pacing measurements of the wgpu renderer. This is updated by the renderer every frame.
+#[derive(Debug, Clone, Default)]
+pub struct WgpuFramePacingStats {
+    /// The time between the renderer starting to encode a frame and the gpu finishing that frame's work. This is
+    /// measured for the most recently completed frame.
+    pub latency: Option<Duration>,
+    /// The time the renderer waited for earlier frames to complete before it could start encoding the current frame
+    pub wait: Duration,
+    /// The number of frames the gpu hasn't finished yet
+    pub frames_in_flight: usize,
+}
+
+/// Limits how many frames can be queued up on the gpu. Without a limit, drivers are free to queue several frames,
+/// which adds unpredictable input latency. Pacing is opt-in with
+/// [WgpuOptions::max_frames_in_flight](crate::WgpuOptions::max_frames_in_flight).
+///
+/// wgpu doesn't expose fences, so the end of each frame's work is detected by mapping a small buffer that is written
+/// after the frame was submitted. The map completes once the gpu has finished everything submitted before it.
+pub struct WgpuFramePacer {
+    pub max_frames_in_flight: Option<usize>,
+    pub stats: WgpuFramePacingStats,
+    in_flight: VecDeque<InFlightFrame>,
+    free_fences: Vec<wgpu::Buffer>,
+    fence_source: Option<wgpu::Buffer>,
+    frame_start: Option<Instant>,
+}
+
+impl WgpuFramePacer {
+    pub fn new(max_frames_in_flight: Option<usize>) -> Self {
+        WgpuFramePacer {
+            max_frames_in_flight: max_frames_in_flight.map(|max| max.max(1)),
+            stats: Default::default(),
+            in_flight: VecDeque::new(),
+            free_fences: Vec::new(),
+            fence_source: None,
+            frame_start: None,
+        }
+    }
+
+    /// Waits until fewer than `max_frames_in_flight` frames are queued on the gpu. Call this before encoding a frame.
+    pub fn begin_frame(&mut self, device: &wgpu::Device) {
+        let wait_start = Instant::now();
+        device.poll(wgpu::Maintain::Poll);
+        self.collect_completed_frames();
+
+        if let Some(max_frames_in_flight) = self.max_frames_in_flight {
+            while self.in_flight.len() >= max_frames_in_flight {
+                // sleep through most of the wait instead of burning a core, and only poll for the last moment
+                match self.expected_wait() {
+                    Some(wait) if wait > SPIN_MARGIN => std::thread::sleep(wait - SPIN_MARGIN),
+                    _ => std::thread::yield_now(),
+                }
+                device.poll(wgpu::Maintain::Poll);
+                self.collect_completed_frames();
+            }
+        }
+
+        self.stats.wait = wait_start.elapsed();
+        self.frame_start = Some(Instant::now());
+    }
+
+    /// Marks the end of the current frame. Call this after all of the frame's work has been submitted.
+    pub fn end_frame(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let fence = self.free_fences.pop().unwrap_or_else(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("frame_fence"),
+                size: FENCE_SIZE,
+                usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+        let fence_source = self.fence_source.get_or_insert_with(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("frame_fence_source"),
+                size: FENCE_SIZE,
+                usage: wgpu::BufferUsage::COPY_SRC,
+                mapped_at_creation: false,
+            })
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("frame_fence"),
+        });
+        encoder.copy_buffer_to_buffer(fence_source, 0, &fence, 0, FENCE_SIZE);
+        queue.submit(Some(encoder.finish()));
+
+        let done = Box::pin(fence.slice(..).map_async(wgpu::MapMode::Read));
+        self.in_flight.push_back(InFlightFrame {
+            fence,
+            done,
+            started: self.frame_start.take().unwrap_or_else(Instant::now),
+        });
+        self.stats.frames_in_flight = self.in_flight.len();
+    }
+
+    /// How long until the oldest frame in flight completes, estimated from the latency of the last completed frame
+    fn expected_wait(&self) -> Option<Duration> {
+        let oldest = self.in_flight.front()?;
+        let latency = self.stats.latency?;
+        latency.checked_sub(oldest.started.elapsed())
+    }
+
+    fn collect_completed_frames(&mut self) {
+        while let Some(frame) = self.in_flight.front_mut() {
+            let result = if let Some(result) = future::block_on(future::poll_once(&mut frame.done))
+            {
+                result
+            } else {
+                break;
+            };
+
+            let frame = self.in_flight.pop_front().unwrap();
+            self.stats.latency = Some(frame.started.elapsed());
+            if result.is_ok() {
+                frame.fence.unmap();
+                self.free_fences.push(frame.fence);
+            }
+        }
+
+        self.stats.frames_in_flight = self.in_flight.len();
+    }
+}
@@ -1,6 +1,7 @@
-use crate::{renderer::WgpuRenderContext, WgpuResourceRefs};
+use crate::{renderer::WgpuRenderContext, WgpuRenderPassStatistics, WgpuResourceRefs};
 use bevy_asset::Handle;
 use bevy_render::{
+    draw::DrawIndexedIndirectArgs,
     pass::RenderPass,
     pipeline::{BindGroupDescriptorId, PipelineDescriptor},
     renderer::{BindGroupId, BufferId, RenderContext},
@@ -13,6 +14,7 @@ pub struct WgpuRenderPass<'a> {
     pub render_context: &'a WgpuRenderContext,
     pub wgpu_resources: WgpuResourceRefs<'a>,
     pub pipeline_descriptor: Option<&'a PipelineDescriptor>,
+    pub statistics: WgpuRenderPassStatistics,
 }
 
 impl<'a> RenderPass for WgpuRenderPass<'a> {
@@ -24,6 +26,7 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
         let buffer = self.wgpu_resources.buffers.get(&buffer_id).unwrap();
         self.render_pass
             .set_vertex_buffer(start_slot, buffer.slice(offset..));
+        self.statistics.vertex_buffer_changes += 1;
     }
 
     fn set_viewport(&mut self, x: f32, y: f32, w: f32, h: f32, min_depth: f32, max_depth: f32) {
@@ -31,6 +34,10 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
             .set_viewport(x, y, w, h, min_depth, max_depth);
     }
 
+    fn set_scissor_rect(&mut self, x: u32, y: u32, w: u32, h: u32) {
+        self.render_pass.set_scissor_rect(x, y, w, h);
+    }
+
     fn set_stencil_reference(&mut self, reference: u32) {
         self.render_pass.set_stencil_reference(reference);
     }
@@ -38,14 +45,47 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
     fn set_index_buffer(&mut self, buffer_id: BufferId, offset: u64) {
         let buffer = self.wgpu_resources.buffers.get(&buffer_id).unwrap();
         self.render_pass.set_index_buffer(buffer.slice(offset..));
+        self.statistics.index_buffer_changes += 1;
     }
 
     fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+        self.statistics
+            .add_draw(indices.end - indices.start, instances.end - instances.start);
         self.render_pass
             .draw_indexed(indices, base_vertex, instances);
     }
 
+    fn draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: BufferId,
+        indirect_offset: u64,
+        count: u32,
+    ) {
+        let buffer = self.wgpu_resources.buffers.get(&indirect_buffer).unwrap();
+        self.statistics.add_indirect_draws(count);
+        if count > 1
+            && self
+                .render_context
+                .device
+                .features()
+                .contains(wgpu::Features::MULTI_DRAW_INDIRECT)
+        {
+            self.render_pass
+                .multi_draw_indexed_indirect(buffer, indirect_offset, count);
+        } else {
+            let stride = std::mem::size_of::<DrawIndexedIndirectArgs>() as u64;
+            for i in 0..count as u64 {
+                self.render_pass
+                    .draw_indexed_indirect(buffer, indirect_offset + i * stride);
+            }
+        }
+    }
+
     fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.statistics.add_draw(
+            vertices.end - vertices.start,
+            instances.end - instances.start,
+        );
         self.render_pass.draw(vertices, instances);
     }
 
@@ -78,6 +118,7 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
                 );
                 self.render_pass
                     .set_bind_group(index, wgpu_bind_group, dynamic_uniform_indices);
+                self.statistics.bind_group_changes += 1;
             }
         }
     }
@@ -91,5 +132,6 @@ impl<'a> RenderPass for WgpuRenderPass<'a> {
             "Attempted to use a pipeline that does not exist in this RenderPass's RenderContext",
         );
         self.render_pass.set_pipeline(pipeline);
+        self.statistics.pipeline_changes += 1;
     }
 }
@@ -0,0 +1,91 @@
+use bevy_utils::HashMap;
+use std::{borrow::Cow, ops::AddAssign};
+
+/// Counts of the gpu commands recorded by a render pass
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct WgpuRenderPassStatistics {
+    pub draw_calls: usize,
+    /// The number of triangles drawn, assuming triangle list topology
+    pub triangles: u64,
+    pub pipeline_changes: usize,
+    pub bind_group_changes: usize,
+    pub vertex_buffer_changes: usize,
+    pub index_buffer_changes: usize,
+}
+
+impl WgpuRenderPassStatistics {
+    pub fn state_changes(&self) -> usize {
+        self.pipeline_changes
+            + self.bind_group_changes
+            + self.vertex_buffer_changes
+            + self.index_buffer_changes
+    }
+
+    pub(crate) fn add_draw(&mut self, vertices: u32, instances: u32) {
+        self.draw_calls += 1;
+        self.triangles += (vertices / 3) as u64 * instances as u64;
+    }
+
+    /// Indirect draws count as draw calls, but their triangles aren't known on the cpu
+    pub(crate) fn add_indirect_draws(&mut self, count: u32) {
+        self.draw_calls += count as usize;
+    }
+}
+
+impl AddAssign for WgpuRenderPassStatistics {
+    fn add_assign(&mut self, rhs: Self) {
+        self.draw_calls += rhs.draw_calls;
+        self.triangles += rhs.triangles;
+        self.pipeline_changes += rhs.pipeline_changes;
+        self.bind_group_changes += rhs.bind_group_changes;
+        self.vertex_buffer_changes += rhs.vertex_buffer_changes;
+        self.index_buffer_changes += rhs.index_buffer_changes;
+    }
+}
+
+/// Render pass statistics of the last frame, grouped by the name of the render graph node that ran the pass. This is
+/// updated by the wgpu renderer every frame.
+#[derive(Debug, Default, Clone)]
+pub struct WgpuRenderStatistics {
+    pub passes: HashMap<Cow<'static, str>, WgpuRenderPassStatistics>,
+}
+
+impl WgpuRenderStatistics {
+    pub fn record(&mut self, pass: Cow<'static, str>, statistics: WgpuRenderPassStatistics) {
+        *self.passes.entry(pass).or_insert_with(Default::default) += statistics;
+    }
+
+    /// The sum of the statistics of all passes
+    pub fn total(&self) -> WgpuRenderPassStatistics {
+        let mut total = WgpuRenderPassStatistics::default();
+        for statistics in self.passes.values() {
+            total += *statistics;
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics_are_aggregated_per_pass() {
+        let mut pass = WgpuRenderPassStatistics::default();
+        pass.add_draw(6, 2);
+        pass.pipeline_changes += 1;
+        pass.bind_group_changes += 2;
+
+        let mut statistics = WgpuRenderStatistics::default();
+        statistics.record("main_pass".into(), pass);
+        statistics.record("main_pass".into(), pass);
+        statistics.record("ui_pass".into(), pass);
+
+        assert_eq!(statistics.passes.len(), 2);
+        let main_pass = statistics.passes["main_pass"];
+        assert_eq!(main_pass.draw_calls, 2);
+        assert_eq!(main_pass.triangles, 8);
+        assert_eq!(main_pass.state_changes(), 6);
+        assert_eq!(statistics.total().draw_calls, 3);
+    }
+}
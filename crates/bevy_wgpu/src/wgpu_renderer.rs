@@ -1,12 +1,15 @@
 use crate::{
-    renderer::{WgpuRenderGraphExecutor, WgpuRenderResourceContext},
-    WgpuOptions, WgpuPowerOptions,
+    renderer::{WgpuRenderGraphExecutor, WgpuRenderResourceContext, WgpuSubmissionMode},
+    wgpu_type_converter::WgpuInto,
+    WgpuOptions, WgpuPowerOptions, WgpuRenderStatistics,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{WgpuFramePacer, WgpuFramePacingStats};
 use bevy_app::prelude::*;
 use bevy_ecs::{Resources, World};
 use bevy_render::{
-    render_graph::{DependentNodeStager, RenderGraph, RenderGraphStager},
-    renderer::RenderResourceContext,
+    render_graph::{DependentNodeStager, RenderGraph, RenderGraphStager, RenderGraphValidation},
+    renderer::{AdapterInfo, RenderResourceContext},
 };
 use bevy_window::{WindowCreated, WindowResized, Windows};
 use std::{ops::Deref, sync::Arc};
@@ -14,25 +17,44 @@ use std::{ops::Deref, sync::Arc};
 pub struct WgpuRenderer {
     pub instance: wgpu::Instance,
     pub device: Arc<wgpu::Device>,
-    pub queue: wgpu::Queue,
+    pub queue: Arc<wgpu::Queue>,
+    pub adapter_info: AdapterInfo,
+    pub submission_mode: WgpuSubmissionMode,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub frame_pacer: WgpuFramePacer,
     pub window_resized_event_reader: EventReader<WindowResized>,
     pub window_created_event_reader: EventReader<WindowCreated>,
     pub intialized: bool,
 }
 
+async fn request_adapter(
+    instance: &wgpu::Instance,
+    options: &WgpuOptions,
+) -> Option<wgpu::Adapter> {
+    instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: match options.power_pref {
+                WgpuPowerOptions::HighPerformance => wgpu::PowerPreference::HighPerformance,
+                WgpuPowerOptions::Adaptive => wgpu::PowerPreference::Default,
+                WgpuPowerOptions::LowPower => wgpu::PowerPreference::LowPower,
+            },
+            compatible_surface: None,
+        })
+        .await
+}
+
 impl WgpuRenderer {
+    /// Whether [WgpuRenderer::new] would find an adapter for `options`. Rendering tests use it to skip themselves on
+    /// machines without a gpu instead of panicking.
+    pub fn adapter_available(options: &WgpuOptions) -> bool {
+        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        futures_lite::future::block_on(request_adapter(&instance, options)).is_some()
+    }
+
     pub async fn new(options: WgpuOptions) -> Self {
         let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: match options.power_pref {
-                    WgpuPowerOptions::HighPerformance => wgpu::PowerPreference::HighPerformance,
-                    WgpuPowerOptions::Adaptive => wgpu::PowerPreference::Default,
-                    WgpuPowerOptions::LowPower => wgpu::PowerPreference::LowPower,
-                },
-                compatible_surface: None,
-            })
+        let adapter = request_adapter(&instance, &options)
             .await
             .expect("Unable to find a GPU! Make sure you have installed required drivers!");
 
@@ -41,10 +63,15 @@ impl WgpuRenderer {
         #[cfg(not(feature = "trace"))]
         let trace_path = None;
 
+        // enable the optional features bevy knows how to use when the adapter supports them
+        let features = adapter.features()
+            & (wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::DEPTH_CLAMPING
+                | wgpu::Features::MULTI_DRAW_INDIRECT);
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
+                    features,
                     limits: wgpu::Limits::default(),
                     shader_validation: true,
                 },
@@ -52,11 +79,22 @@ impl WgpuRenderer {
             )
             .await
             .unwrap();
+        let adapter_info = get_adapter_info(&adapter, &device);
+        log::info!(
+            "Using {:?} adapter \"{}\" ({:?})",
+            adapter_info.backend,
+            adapter_info.name,
+            adapter_info.device_type
+        );
         let device = Arc::new(device);
         WgpuRenderer {
             instance,
             device,
-            queue,
+            queue: Arc::new(queue),
+            adapter_info,
+            submission_mode: options.submission_mode,
+            #[cfg(not(target_arch = "wasm32"))]
+            frame_pacer: WgpuFramePacer::new(options.max_frames_in_flight),
             window_resized_event_reader: Default::default(),
             window_created_event_reader: Default::default(),
             intialized: false,
@@ -79,18 +117,31 @@ impl WgpuRenderer {
             let window = windows
                 .get(window_created_event.id)
                 .expect("Received window created event for non-existent window");
+            // windows that weren't created by winit don't get a surface and render offscreen instead
             #[cfg(feature = "bevy_winit")]
             {
-                let winit_windows = resources.get::<bevy_winit::WinitWindows>().unwrap();
-                let winit_window = winit_windows.get_window(window.id()).unwrap();
-                let surface = unsafe { self.instance.create_surface(winit_window.deref()) };
-                render_resource_context.set_window_surface(window.id(), surface);
+                let winit_windows = resources.get::<bevy_winit::WinitWindows>();
+                if let Some(winit_window) = winit_windows
+                    .as_ref()
+                    .and_then(|winit_windows| winit_windows.get_window(window.id()))
+                {
+                    let surface = unsafe { self.instance.create_surface(winit_window.deref()) };
+                    render_resource_context.set_window_surface(window.id(), surface);
+                }
             }
         }
     }
 
     pub fn run_graph(&mut self, world: &mut World, resources: &mut Resources) {
         let mut render_graph = resources.get_mut::<RenderGraph>().unwrap();
+        render_graph.prepare(world, resources);
+
+        if let Some(mut validation) = resources.get_mut::<RenderGraphValidation>() {
+            if validation.enabled {
+                validation.report(render_graph.validate());
+            }
+        }
+
         // stage nodes
         let mut stager = DependentNodeStager::loose_grouping();
         let stages = stager.get_stages(&render_graph).unwrap();
@@ -99,22 +150,68 @@ impl WgpuRenderer {
         // execute stages
         let graph_executor = WgpuRenderGraphExecutor {
             max_thread_count: 2,
+            submission_mode: self.submission_mode,
         };
         graph_executor.execute(
             world,
             resources,
             self.device.clone(),
-            &mut self.queue,
+            &self.queue,
             &mut borrowed,
         );
     }
 
     pub fn update(&mut self, world: &mut World, resources: &mut Resources) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.frame_pacer.begin_frame(&self.device);
+
         self.handle_window_created_events(resources);
         self.run_graph(world, resources);
 
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.frame_pacer.end_frame(&self.device, &self.queue);
+            if let Some(mut stats) = resources.get_mut::<WgpuFramePacingStats>() {
+                *stats = self.frame_pacer.stats.clone();
+            }
+        }
+
         let render_resource_context = resources.get::<Box<dyn RenderResourceContext>>().unwrap();
+        if let Some(mut render_statistics) = resources.get_mut::<WgpuRenderStatistics>() {
+            let render_resource_context = render_resource_context
+                .downcast_ref::<WgpuRenderResourceContext>()
+                .unwrap();
+            *render_statistics = render_resource_context.take_render_statistics();
+        }
         render_resource_context.drop_all_swap_chain_textures();
         render_resource_context.clear_bind_groups();
     }
 }
+
+fn get_adapter_info(adapter: &wgpu::Adapter, device: &wgpu::Device) -> AdapterInfo {
+    #[cfg(not(target_arch = "wasm32"))]
+    let info = {
+        let info = adapter.get_info();
+        AdapterInfo {
+            name: info.name,
+            vendor: info.vendor,
+            device: info.device,
+            device_type: info.device_type.wgpu_into(),
+            backend: info.backend.wgpu_into(),
+            ..Default::default()
+        }
+    };
+    // the browser doesn't expose any information about the adapter
+    #[cfg(target_arch = "wasm32")]
+    let info = AdapterInfo {
+        name: "WebGPU".to_string(),
+        backend: wgpu::Backend::BrowserWebGpu.wgpu_into(),
+        ..Default::default()
+    };
+
+    AdapterInfo {
+        limits: device.limits().wgpu_into(),
+        features: device.features().wgpu_into(),
+        ..info
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{renderer::WgpuRenderResourceContext, WgpuResources};
+use bevy_asset::{AssetServer, HandleId};
+use bevy_ecs::{Resources, World};
+use bevy_render::{
+    renderer::{
+        RenderResourceContext, RenderResourceId, RenderResourceLifetimes, RenderResourceOwner,
+    },
+    texture::TextureDescriptor,
+};
+use bevy_utils::{HashMap, HashSet};
+use std::fmt;
+
+/// A gpu resource and what keeps it alive, as reported by [WgpuResourceReport]
+#[derive(Debug, Clone)]
+pub struct WgpuResourceUsage {
+    pub resource: RenderResourceId,
+    /// The size of a buffer, or of a texture with all of its mip levels. Samplers have no size.
+    pub bytes: usize,
+    /// The entities and assets the resource was created for. Assets that look the resource up with
+    /// `get_asset_resource` count as owners too.
+    pub owners: Vec<RenderResourceOwner>,
+    /// The owners whose entity was despawned or whose asset was freed
+    pub missing_owners: Vec<RenderResourceOwner>,
+    /// Whether the resource was released and is waiting for the frames that use it to finish before it is freed
+    pub released: bool,
+}
+
+impl WgpuResourceUsage {
+    /// Whether the resource was created for owners that all stopped existing without releasing it. These resources
+    /// are leaked.
+    pub fn is_orphan(&self) -> bool {
+        !self.released && !self.owners.is_empty() && self.missing_owners.len() == self.owners.len()
+    }
+
+    /// Whether nothing tracks the resource. Render graph nodes often manage their own resources, so these aren't
+    /// necessarily leaked, but the renderer never frees them on its own.
+    pub fn is_untracked(&self) -> bool {
+        !self.released && self.owners.is_empty()
+    }
+}
+
+/// Maps the buffers, textures and samplers of a [WgpuRenderResourceContext] back to the entities and assets that own
+/// them, to find the resources that outlived their owners. See [WgpuRenderResourceContext::resource_report].
+///
+/// Owners are released a frame or two after their entity is despawned or their asset is freed, so resources can show
+/// up as orphans briefly. Resources that stay orphaned across reports are leaked.
+#[derive(Debug, Clone, Default)]
+pub struct WgpuResourceReport {
+    pub resources: Vec<WgpuResourceUsage>,
+    /// Owners that still refer to resources that were already freed. Using these resources fails.
+    pub dangling: Vec<(RenderResourceOwner, RenderResourceId)>,
+}
+
+impl WgpuResourceReport {
+    /// Collects the resources that currently exist in `resources`. `owner_exists` decides whether an owning entity or
+    /// asset still exists.
+    pub fn new(
+        resources: &WgpuResources,
+        owner_exists: impl Fn(RenderResourceOwner) -> bool,
+    ) -> Self {
+        let mut existing = Vec::new();
+        for (buffer, info) in resources.buffer_infos.read().iter() {
+            existing.push((RenderResourceId::Buffer(*buffer), info.size));
+        }
+        for (texture, descriptor) in resources.texture_descriptors.read().iter() {
+            existing.push((
+                RenderResourceId::Texture(*texture),
+                texture_bytes(descriptor),
+            ));
+        }
+        for sampler in resources.samplers.read().keys() {
+            existing.push((RenderResourceId::Sampler(*sampler), 0));
+        }
+        let asset_resources = resources
+            .asset_resources
+            .read()
+            .iter()
+            .map(|((handle, _), resource)| (handle.id, resource.clone()))
+            .collect::<Vec<_>>();
+
+        Self::from_parts(
+            existing,
+            &resources.resource_lifetimes.read(),
+            asset_resources,
+            owner_exists,
+        )
+    }
+
+    fn from_parts(
+        existing: Vec<(RenderResourceId, usize)>,
+        lifetimes: &RenderResourceLifetimes,
+        asset_resources: Vec<(HandleId, RenderResourceId)>,
+        owner_exists: impl Fn(RenderResourceOwner) -> bool,
+    ) -> Self {
+        let mut owners = HashMap::<RenderResourceId, Vec<RenderResourceOwner>>::default();
+        let mut add_owner = |owner: RenderResourceOwner, resource: &RenderResourceId| {
+            let resource_owners = owners.entry(resource.clone()).or_insert_with(Vec::new);
+            if !resource_owners.contains(&owner) {
+                resource_owners.push(owner);
+            }
+        };
+        for (owner, resources) in lifetimes.owners() {
+            for resource in resources.iter() {
+                add_owner(owner, resource);
+            }
+        }
+        for (handle, resource) in asset_resources.iter() {
+            add_owner(RenderResourceOwner::Asset(*handle), resource);
+        }
+        let released = lifetimes.released().cloned().collect::<HashSet<_>>();
+
+        let mut report = WgpuResourceReport::default();
+        for (resource, bytes) in existing {
+            let owners = owners.remove(&resource).unwrap_or_default();
+            let missing_owners = owners
+                .iter()
+                .copied()
+                .filter(|owner| !owner_exists(*owner))
+                .collect();
+            report.resources.push(WgpuResourceUsage {
+                released: released.contains(&resource),
+                resource,
+                bytes,
+                owners,
+                missing_owners,
+            });
+        }
+
+        // the owners of resources that don't exist anymore are left over
+        for (resource, owners) in owners {
+            for owner in owners {
+                report.dangling.push((owner, resource.clone()));
+            }
+        }
+        report
+    }
+
+    /// Resources whose owners are all gone, but that were never released
+    pub fn orphans(&self) -> impl Iterator<Item = &WgpuResourceUsage> {
+        self.resources.iter().filter(|usage| usage.is_orphan())
+    }
+
+    pub fn untracked(&self) -> impl Iterator<Item = &WgpuResourceUsage> {
+        self.resources.iter().filter(|usage| usage.is_untracked())
+    }
+
+    pub fn released(&self) -> impl Iterator<Item = &WgpuResourceUsage> {
+        self.resources.iter().filter(|usage| usage.released)
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.resources.iter().map(|usage| usage.bytes).sum()
+    }
+
+    pub fn orphaned_bytes(&self) -> usize {
+        self.orphans().map(|usage| usage.bytes).sum()
+    }
+}
+
+impl fmt::Display for WgpuResourceReport {
+    /// Lists the orphans from the largest to the smallest, followed by the dangling references
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} gpu resources, {} bytes: {} orphaned ({} bytes), {} untracked, {} released, {} dangling references",
+            self.resources.len(),
+            self.bytes(),
+            self.orphans().count(),
+            self.orphaned_bytes(),
+            self.untracked().count(),
+            self.released().count(),
+            self.dangling.len(),
+        )?;
+        let mut orphans = self.orphans().collect::<Vec<_>>();
+        orphans.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        for orphan in orphans {
+            writeln!(
+                f,
+                "{:>10} bytes  orphaned {:?}, owned by {:?}",
+                orphan.bytes, orphan.resource, orphan.owners
+            )?;
+        }
+        for (owner, resource) in self.dangling.iter() {
+            writeln!(f, "  dangling {:?}, owned by {:?}", resource, owner)?;
+        }
+        Ok(())
+    }
+}
+
+/// The size of a texture with all of its mip levels. Array layers don't shrink with the mip level.
+fn texture_bytes(descriptor: &TextureDescriptor) -> usize {
+    let size = descriptor.size;
+    let texels = (0..descriptor.mip_level_count.max(1))
+        .map(|level| {
+            let width = (size.width >> level).max(1) as usize;
+            let height = (size.height >> level).max(1) as usize;
+            width * height * size.depth as usize
+        })
+        .sum::<usize>();
+    texels * descriptor.format.pixel_size() * descriptor.sample_count.max(1) as usize
+}
+
+impl WgpuRenderResourceContext {
+    /// Reports which gpu resources are owned by entities that were despawned or by assets that were freed. This walks
+    /// every resource, so it is meant to be run on demand while looking for leaks, not every frame.
+    pub fn resource_report(&self, world: &World, resources: &Resources) -> WgpuResourceReport {
+        let asset_server = resources.get::<AssetServer>();
+        WgpuResourceReport::new(&self.resources, |owner| match owner {
+            RenderResourceOwner::Entity(entity, _) => world.contains(entity),
+            RenderResourceOwner::Asset(handle) => asset_server
+                .as_ref()
+                .map_or(true, |asset_server| !asset_server.is_freed(handle)),
+        })
+    }
+}
+
+/// Runs [WgpuRenderResourceContext::resource_report] on the current render resource context. Returns `None` when the
+/// wgpu renderer isn't in use.
+pub fn wgpu_resource_report(world: &World, resources: &Resources) -> Option<WgpuResourceReport> {
+    let render_resource_context = resources.get::<Box<dyn RenderResourceContext>>()?;
+    let render_resource_context =
+        render_resource_context.downcast_ref::<WgpuRenderResourceContext>()?;
+    Some(render_resource_context.resource_report(world, resources))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_ecs::Entity;
+    use bevy_render::{
+        renderer::{BufferId, SamplerId, TextureId},
+        texture::Texture,
+    };
+    use std::any::TypeId;
+
+    #[test]
+    fn orphans_and_dangling_references() {
+        let mut lifetimes = RenderResourceLifetimes::default();
+        let live_entity = RenderResourceOwner::Entity(Entity::new(0), TypeId::of::<u32>());
+        let despawned_entity = RenderResourceOwner::Entity(Entity::new(1), TypeId::of::<u32>());
+        let freed_asset = HandleId::random::<Texture>();
+
+        let owned = RenderResourceId::Buffer(BufferId::new());
+        let orphaned = RenderResourceId::Buffer(BufferId::new());
+        let shared = RenderResourceId::Texture(TextureId::new());
+        let released = RenderResourceId::Texture(TextureId::new());
+        let untracked = RenderResourceId::Sampler(SamplerId::new());
+        let freed = RenderResourceId::Buffer(BufferId::new());
+        lifetimes.set_owner(live_entity, owned.clone());
+        lifetimes.set_owner(despawned_entity, orphaned.clone());
+        lifetimes.set_owner(despawned_entity, shared.clone());
+        lifetimes.set_owner(live_entity, shared.clone());
+        lifetimes.set_owner(despawned_entity, released.clone());
+        lifetimes.release(released.clone());
+
+        let report = WgpuResourceReport::from_parts(
+            vec![
+                (owned, 16),
+                (orphaned.clone(), 32),
+                (shared, 64),
+                (released.clone(), 128),
+                (untracked.clone(), 0),
+            ],
+            &lifetimes,
+            vec![
+                (freed_asset, orphaned.clone()),
+                (freed_asset, freed.clone()),
+            ],
+            |owner| owner == live_entity,
+        );
+
+        let orphans = report.orphans().collect::<Vec<_>>();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].resource, orphaned);
+        assert_eq!(orphans[0].owners.len(), 2);
+        assert_eq!(report.orphaned_bytes(), 32);
+        assert_eq!(report.untracked().next().unwrap().resource, untracked);
+        assert_eq!(report.released().next().unwrap().resource, released);
+        assert_eq!(
+            report.dangling,
+            vec![(RenderResourceOwner::Asset(freed_asset), freed)]
+        );
+        assert_eq!(report.bytes(), 240);
+    }
+}
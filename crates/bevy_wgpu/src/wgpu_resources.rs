@@ -1,14 +1,32 @@
 use bevy_asset::{Handle, HandleUntyped};
 use bevy_render::{
-    pipeline::{BindGroupDescriptorId, PipelineDescriptor},
-    renderer::{BindGroupId, BufferId, BufferInfo, RenderResourceId, SamplerId, TextureId},
+    pipeline::{BindGroupDescriptorId, ComputePipelineDescriptor, PipelineDescriptor},
+    renderer::{
+        BindGroupId, BufferId, BufferInfo, RenderResourceId, RenderResourceLifetimes, SamplerId,
+        TextureId,
+    },
     shader::Shader,
     texture::TextureDescriptor,
 };
 use bevy_utils::HashMap;
 use bevy_window::WindowId;
-use parking_lot::{RwLock, RwLockReadGuard};
-use std::sync::Arc;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+
+pub type BufferMapFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+/// The maps started by [map_buffer_async](bevy_render::renderer::RenderResourceContext::map_buffer_async) that
+/// haven't completed yet
+#[derive(Default)]
+pub struct PendingBufferMaps {
+    pub maps: HashMap<BufferId, BufferMapFuture>,
+}
+
+impl fmt::Debug for PendingBufferMaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.maps.keys()).finish()
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct WgpuBindGroupInfo {
@@ -44,6 +62,8 @@ pub struct WgpuResourcesReadLock<'a> {
     pub swap_chain_frames: RwLockReadGuard<'a, HashMap<TextureId, wgpu::SwapChainFrame>>,
     pub render_pipelines:
         RwLockReadGuard<'a, HashMap<Handle<PipelineDescriptor>, wgpu::RenderPipeline>>,
+    pub compute_pipelines:
+        RwLockReadGuard<'a, HashMap<Handle<ComputePipelineDescriptor>, wgpu::ComputePipeline>>,
     pub bind_groups: RwLockReadGuard<'a, HashMap<BindGroupDescriptorId, WgpuBindGroupInfo>>,
 }
 
@@ -54,6 +74,7 @@ impl<'a> WgpuResourcesReadLock<'a> {
             textures: &self.textures,
             swap_chain_frames: &self.swap_chain_frames,
             render_pipelines: &self.render_pipelines,
+            compute_pipelines: &self.compute_pipelines,
             bind_groups: &self.bind_groups,
         }
     }
@@ -66,6 +87,7 @@ pub struct WgpuResourceRefs<'a> {
     pub textures: &'a HashMap<TextureId, wgpu::TextureView>,
     pub swap_chain_frames: &'a HashMap<TextureId, wgpu::SwapChainFrame>,
     pub render_pipelines: &'a HashMap<Handle<PipelineDescriptor>, wgpu::RenderPipeline>,
+    pub compute_pipelines: &'a HashMap<Handle<ComputePipelineDescriptor>, wgpu::ComputePipeline>,
     pub bind_groups: &'a HashMap<BindGroupDescriptorId, WgpuBindGroupInfo>,
 }
 
@@ -76,15 +98,21 @@ pub struct WgpuResources {
     pub window_surfaces: Arc<RwLock<HashMap<WindowId, wgpu::Surface>>>,
     pub window_swap_chains: Arc<RwLock<HashMap<WindowId, wgpu::SwapChain>>>,
     pub swap_chain_frames: Arc<RwLock<HashMap<TextureId, wgpu::SwapChainFrame>>>,
+    /// Stands in for the swap chains of windows without a surface
+    pub window_offscreen_textures: Arc<RwLock<HashMap<WindowId, TextureId>>>,
     pub buffers: Arc<RwLock<HashMap<BufferId, Arc<wgpu::Buffer>>>>,
+    pub pending_buffer_maps: Arc<Mutex<PendingBufferMaps>>,
     pub texture_views: Arc<RwLock<HashMap<TextureId, wgpu::TextureView>>>,
     pub textures: Arc<RwLock<HashMap<TextureId, wgpu::Texture>>>,
     pub samplers: Arc<RwLock<HashMap<SamplerId, wgpu::Sampler>>>,
     pub shader_modules: Arc<RwLock<HashMap<Handle<Shader>, wgpu::ShaderModule>>>,
     pub render_pipelines: Arc<RwLock<HashMap<Handle<PipelineDescriptor>, wgpu::RenderPipeline>>>,
+    pub compute_pipelines:
+        Arc<RwLock<HashMap<Handle<ComputePipelineDescriptor>, wgpu::ComputePipeline>>>,
     pub bind_groups: Arc<RwLock<HashMap<BindGroupDescriptorId, WgpuBindGroupInfo>>>,
     pub bind_group_layouts: Arc<RwLock<HashMap<BindGroupDescriptorId, wgpu::BindGroupLayout>>>,
     pub asset_resources: Arc<RwLock<HashMap<(HandleUntyped, u64), RenderResourceId>>>,
+    pub resource_lifetimes: Arc<RwLock<RenderResourceLifetimes>>,
 }
 
 impl WgpuResources {
@@ -94,6 +122,7 @@ impl WgpuResources {
             textures: self.texture_views.read(),
             swap_chain_frames: self.swap_chain_frames.read(),
             render_pipelines: self.render_pipelines.read(),
+            compute_pipelines: self.compute_pipelines.read(),
             bind_groups: self.bind_groups.read(),
         }
     }
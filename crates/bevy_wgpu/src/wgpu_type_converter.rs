@@ -8,7 +8,7 @@ use bevy_render::{
         StencilStateDescriptor, StencilStateFaceDescriptor, VertexAttributeDescriptor,
         VertexBufferDescriptor, VertexFormat,
     },
-    renderer::BufferUsage,
+    renderer::{BufferUsage, RenderBackend, RenderDeviceType, RenderFeatures, RenderLimits},
     texture::{
         AddressMode, Extent3d, FilterMode, SamplerDescriptor, TextureComponentType,
         TextureDescriptor, TextureDimension, TextureFormat, TextureUsage, TextureViewDimension,
@@ -561,6 +561,8 @@ impl WgpuFrom<FilterMode> for wgpu::FilterMode {
 
 impl WgpuFrom<&Window> for wgpu::SwapChainDescriptor {
     fn from(window: &Window) -> Self {
+        // NOTE: wgpu doesn't expose the surface's composite alpha mode yet. transparent windows rely on the platform
+        // compositor honoring the alpha channel written by the swap chain, so the format must keep an alpha channel.
         wgpu::SwapChainDescriptor {
             usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
             format: TextureFormat::default().wgpu_into(),
@@ -574,3 +576,73 @@ impl WgpuFrom<&Window> for wgpu::SwapChainDescriptor {
         }
     }
 }
+
+impl WgpuFrom<wgpu::Backend> for RenderBackend {
+    fn from(val: wgpu::Backend) -> Self {
+        match val {
+            wgpu::Backend::Empty => RenderBackend::Empty,
+            wgpu::Backend::Vulkan => RenderBackend::Vulkan,
+            wgpu::Backend::Metal => RenderBackend::Metal,
+            wgpu::Backend::Dx12 => RenderBackend::Dx12,
+            wgpu::Backend::Dx11 => RenderBackend::Dx11,
+            wgpu::Backend::Gl => RenderBackend::Gl,
+            wgpu::Backend::BrowserWebGpu => RenderBackend::BrowserWebGpu,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl WgpuFrom<wgpu::DeviceType> for RenderDeviceType {
+    fn from(val: wgpu::DeviceType) -> Self {
+        match val {
+            wgpu::DeviceType::Other => RenderDeviceType::Other,
+            wgpu::DeviceType::IntegratedGpu => RenderDeviceType::IntegratedGpu,
+            wgpu::DeviceType::DiscreteGpu => RenderDeviceType::DiscreteGpu,
+            wgpu::DeviceType::VirtualGpu => RenderDeviceType::VirtualGpu,
+            wgpu::DeviceType::Cpu => RenderDeviceType::Cpu,
+        }
+    }
+}
+
+impl WgpuFrom<wgpu::Features> for RenderFeatures {
+    fn from(val: wgpu::Features) -> Self {
+        // compute pipelines and anisotropic filtering are part of wgpu's baseline, so every device supports them
+        let mut features = RenderFeatures::COMPUTE | RenderFeatures::SAMPLER_ANISOTROPY;
+        features.set(
+            RenderFeatures::TEXTURE_COMPRESSION_BC,
+            val.contains(wgpu::Features::TEXTURE_COMPRESSION_BC),
+        );
+        features.set(
+            RenderFeatures::DEPTH_CLAMPING,
+            val.contains(wgpu::Features::DEPTH_CLAMPING),
+        );
+        features.set(
+            RenderFeatures::PUSH_CONSTANTS,
+            val.contains(wgpu::Features::PUSH_CONSTANTS),
+        );
+        features.set(
+            RenderFeatures::MULTI_DRAW_INDIRECT,
+            val.contains(wgpu::Features::MULTI_DRAW_INDIRECT),
+        );
+        features
+    }
+}
+
+impl WgpuFrom<wgpu::Limits> for RenderLimits {
+    fn from(val: wgpu::Limits) -> Self {
+        RenderLimits {
+            max_bind_groups: val.max_bind_groups,
+            max_dynamic_uniform_buffers_per_pipeline_layout: val
+                .max_dynamic_uniform_buffers_per_pipeline_layout,
+            max_dynamic_storage_buffers_per_pipeline_layout: val
+                .max_dynamic_storage_buffers_per_pipeline_layout,
+            max_sampled_textures_per_shader_stage: val.max_sampled_textures_per_shader_stage,
+            max_samplers_per_shader_stage: val.max_samplers_per_shader_stage,
+            max_storage_buffers_per_shader_stage: val.max_storage_buffers_per_shader_stage,
+            max_storage_textures_per_shader_stage: val.max_storage_textures_per_shader_stage,
+            max_uniform_buffers_per_shader_stage: val.max_uniform_buffers_per_shader_stage,
+            max_uniform_buffer_binding_size: val.max_uniform_buffer_binding_size,
+            max_push_constant_size: val.max_push_constant_size,
+        }
+    }
+}
@@ -35,8 +35,22 @@ pub struct WindowCloseRequested {
     pub id: WindowId,
 }
 
+/// An event that is sent whenever a window gains or loses focus
+#[derive(Debug, Clone)]
+pub struct WindowFocused {
+    pub id: WindowId,
+    pub focused: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct CursorMoved {
     pub id: WindowId,
     pub position: Vec2,
 }
+
+/// An event that is sent whenever a window receives a character from the keyboard or an input method
+#[derive(Debug, Clone)]
+pub struct ReceivedCharacter {
+    pub id: WindowId,
+    pub char: char,
+}
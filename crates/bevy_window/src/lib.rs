@@ -1,15 +1,21 @@
+mod cursor;
 mod event;
+mod raw_window_handle;
 mod system;
 mod window;
 mod windows;
 
+pub use cursor::*;
 pub use event::*;
+pub use raw_window_handle::*;
 pub use system::*;
 pub use window::*;
 pub use windows::*;
 
 pub mod prelude {
-    pub use crate::{CursorMoved, Window, WindowDescriptor, Windows};
+    pub use crate::{
+        CursorIcon, CursorMoved, ReceivedCharacter, Window, WindowDescriptor, Windows,
+    };
 }
 
 use bevy_app::prelude::*;
@@ -36,7 +42,9 @@ impl Plugin for WindowPlugin {
             .add_event::<WindowCreated>()
             .add_event::<WindowCloseRequested>()
             .add_event::<CloseWindow>()
+            .add_event::<WindowFocused>()
             .add_event::<CursorMoved>()
+            .add_event::<ReceivedCharacter>()
             .init_resource::<Windows>();
 
         if self.add_primary_window {
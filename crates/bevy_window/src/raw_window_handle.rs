@@ -0,0 +1,27 @@
+use raw_window_handle::RawWindowHandle;
+
+/// The platform handle of a window, for libraries that create their own surface for it, like external renderers and
+/// ui toolkits. Only the windowing backend can create it, so it always belongs to a window the backend created.
+#[derive(Debug, Copy, Clone)]
+pub struct RawWindowHandleWrapper(RawWindowHandle);
+
+impl RawWindowHandleWrapper {
+    pub(crate) fn new(handle: RawWindowHandle) -> Self {
+        RawWindowHandleWrapper(handle)
+    }
+
+    /// Returns the platform handle of the window.
+    ///
+    /// # Safety
+    /// The handle is only valid until the window is closed, and must not be used after that. Most platforms only
+    /// allow calling into the window from the thread that created it, which is the main thread: on macOS and Windows,
+    /// the handle must only be used there, even though the wrapper can be sent to other threads.
+    pub unsafe fn get_handle(&self) -> RawWindowHandle {
+        self.0
+    }
+}
+
+// SAFE: the handle is an opaque identifier that can only be read through the unsafe `get_handle`, whose callers are
+// responsible for using it from the threads the platform allows
+unsafe impl Send for RawWindowHandleWrapper {}
+unsafe impl Sync for RawWindowHandleWrapper {}
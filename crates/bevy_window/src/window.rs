@@ -1,3 +1,5 @@
+use crate::{CursorIcon, RawWindowHandleWrapper};
+use raw_window_handle::RawWindowHandle;
 use uuid::Uuid;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -40,11 +42,17 @@ pub struct Window {
     vsync: bool,
     resizable: bool,
     decorations: bool,
+    transparent: bool,
     cursor_visible: bool,
     cursor_locked: bool,
+    cursor_icon: CursorIcon,
     mode: WindowMode,
+    focused: bool,
+    raw_window_handle: Option<RawWindowHandleWrapper>,
     #[cfg(target_arch = "wasm32")]
     pub canvas: Option<String>,
+    #[cfg(target_arch = "wasm32")]
+    pub fit_canvas_to_parent: bool,
     command_queue: Vec<WindowCommand>,
 }
 
@@ -76,6 +84,9 @@ pub enum WindowCommand {
     SetCursorVisibility {
         visible: bool,
     },
+    SetCursorIcon {
+        icon: CursorIcon,
+    },
 }
 
 /// Defines the way a window is displayed
@@ -100,11 +111,17 @@ impl Window {
             vsync: window_descriptor.vsync,
             resizable: window_descriptor.resizable,
             decorations: window_descriptor.decorations,
+            transparent: window_descriptor.transparent,
             cursor_visible: window_descriptor.cursor_visible,
             cursor_locked: window_descriptor.cursor_locked,
+            cursor_icon: CursorIcon::Default,
             mode: window_descriptor.mode,
+            focused: false,
+            raw_window_handle: None,
             #[cfg(target_arch = "wasm32")]
             canvas: window_descriptor.canvas.clone(),
+            #[cfg(target_arch = "wasm32")]
+            fit_canvas_to_parent: window_descriptor.fit_canvas_to_parent,
             command_queue: Vec::new(),
         }
     }
@@ -175,6 +192,12 @@ impl Window {
             .push(WindowCommand::SetDecorations { decorations });
     }
 
+    /// Whether the window was created with a transparent background. Transparency can only be set when the
+    /// window is created, so there is no corresponding setter.
+    pub fn transparent(&self) -> bool {
+        self.transparent
+    }
+
     pub fn cursor_locked(&self) -> bool {
         self.cursor_locked
     }
@@ -196,6 +219,16 @@ impl Window {
         });
     }
 
+    pub fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        self.cursor_icon = icon;
+        self.command_queue
+            .push(WindowCommand::SetCursorIcon { icon });
+    }
+
     pub fn mode(&self) -> WindowMode {
         self.mode
     }
@@ -208,6 +241,50 @@ impl Window {
         });
     }
 
+    /// Switches between [WindowMode::BorderlessFullscreen] and [WindowMode::Windowed]. On the web this uses the
+    /// browser fullscreen API. Browsers only allow entering fullscreen in response to user input, so the request is
+    /// completed on the next click or key press on the canvas.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.set_mode(if fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        });
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        !matches!(self.mode, WindowMode::Windowed)
+    }
+
+    #[doc(hidden)]
+    pub fn update_mode_from_backend(&mut self, mode: WindowMode) {
+        self.mode = mode;
+    }
+
+    /// Whether the window currently receives keyboard input
+    #[inline]
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    #[inline]
+    pub fn update_focused_from_backend(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// The platform handle of the window, once the backend created it
+    #[inline]
+    pub fn raw_window_handle(&self) -> Option<RawWindowHandleWrapper> {
+        self.raw_window_handle
+    }
+
+    /// # Safety
+    /// `handle` must be the handle of the platform window the backend created for this window
+    #[inline]
+    pub unsafe fn update_raw_window_handle_from_backend(&mut self, handle: RawWindowHandle) {
+        self.raw_window_handle = Some(RawWindowHandleWrapper::new(handle));
+    }
+
     pub fn drain_commands(&mut self) -> impl Iterator<Item = WindowCommand> + '_ {
         self.command_queue.drain(..)
     }
@@ -221,11 +298,17 @@ pub struct WindowDescriptor {
     pub vsync: bool,
     pub resizable: bool,
     pub decorations: bool,
+    /// Creates the window with a transparent background. Combine this with a `ClearColor` whose alpha is less than
+    /// 1.0 (and usually `decorations: false`) to build overlay-style applications.
+    pub transparent: bool,
     pub cursor_visible: bool,
     pub cursor_locked: bool,
     pub mode: WindowMode,
     #[cfg(target_arch = "wasm32")]
     pub canvas: Option<String>,
+    /// Resizes the canvas to fill its parent element whenever the parent's size changes
+    #[cfg(target_arch = "wasm32")]
+    pub fit_canvas_to_parent: bool,
 }
 
 impl Default for WindowDescriptor {
@@ -237,11 +320,14 @@ impl Default for WindowDescriptor {
             vsync: true,
             resizable: true,
             decorations: true,
+            transparent: false,
             cursor_locked: false,
             cursor_visible: true,
             mode: WindowMode::Windowed,
             #[cfg(target_arch = "wasm32")]
             canvas: None,
+            #[cfg(target_arch = "wasm32")]
+            fit_canvas_to_parent: false,
         }
     }
 }
@@ -27,6 +27,11 @@ impl Windows {
         self.get_mut(WindowId::primary())
     }
 
+    /// The window that currently receives keyboard input, if any
+    pub fn get_focused(&self) -> Option<&Window> {
+        self.windows.values().find(|window| window.is_focused())
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Window> {
         self.windows.values()
     }
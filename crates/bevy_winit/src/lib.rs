@@ -13,8 +13,10 @@ use bevy_app::{prelude::*, AppExit};
 use bevy_ecs::{IntoThreadLocalSystem, Resources, World};
 use bevy_math::Vec2;
 use bevy_window::{
-    CreateWindow, CursorMoved, Window, WindowCloseRequested, WindowCreated, WindowResized, Windows,
+    CreateWindow, CursorMoved, ReceivedCharacter, Window, WindowCloseRequested, WindowCreated,
+    WindowFocused, WindowResized, Windows,
 };
+use raw_window_handle::HasRawWindowHandle;
 use winit::{
     event::{self, DeviceEvent, Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
@@ -35,9 +37,14 @@ impl Plugin for WinitPlugin {
             .init_resource::<WinitWindows>()
             .set_runner(winit_runner)
             .add_system(change_window.thread_local_system());
+
+        #[cfg(target_arch = "wasm32")]
+        app.add_system(fit_canvas_to_parent.thread_local_system());
     }
 }
 
+// the requested resolution is not used when fullscreen is mapped to the browser fullscreen API
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
 fn change_window(_: &mut World, resources: &mut Resources) {
     let winit_windows = resources.get::<WinitWindows>().unwrap();
     let mut windows = resources.get_mut::<Windows>().unwrap();
@@ -55,6 +62,12 @@ fn change_window(_: &mut World, resources: &mut Resources) {
                         bevy_window::WindowMode::BorderlessFullscreen => {
                             window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)))
                         }
+                        // the browser fullscreen API has no video modes to pick from
+                        #[cfg(target_arch = "wasm32")]
+                        bevy_window::WindowMode::Fullscreen { .. } => {
+                            window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(None)))
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
                         bevy_window::WindowMode::Fullscreen { use_size } => window.set_fullscreen(
                             Some(winit::window::Fullscreen::Exclusive(match use_size {
                                 true => get_fitting_videomode(
@@ -93,11 +106,50 @@ fn change_window(_: &mut World, resources: &mut Resources) {
                     let window = winit_windows.get_window(id).unwrap();
                     window.set_cursor_visible(visible);
                 }
+                bevy_window::WindowCommand::SetCursorIcon { icon } => {
+                    let window = winit_windows.get_window(id).unwrap();
+                    window.set_cursor_icon(converters::convert_cursor_icon(icon));
+                }
             }
         }
     }
 }
 
+#[cfg(target_arch = "wasm32")]
+fn fit_canvas_to_parent(_: &mut World, resources: &mut Resources) {
+    use winit::platform::web::WindowExtWebSys;
+
+    let winit_windows = resources.get::<WinitWindows>().unwrap();
+    let windows = resources.get::<Windows>().unwrap();
+
+    for bevy_window in windows.iter() {
+        if !bevy_window.fit_canvas_to_parent {
+            continue;
+        }
+
+        let window = winit_windows.get_window(bevy_window.id()).unwrap();
+        // while fullscreen, the browser decides the canvas size
+        if window.fullscreen().is_some() {
+            continue;
+        }
+
+        let parent = if let Some(parent) = window.canvas().parent_element() {
+            parent
+        } else {
+            continue;
+        };
+
+        // client sizes are in CSS pixels, which winit treats as logical pixels
+        let parent_size =
+            winit::dpi::LogicalSize::new(parent.client_width(), parent.client_height());
+        let current_size = window.inner_size().to_logical::<i32>(window.scale_factor());
+        if parent_size.width > 0 && parent_size.height > 0 && parent_size != current_size {
+            // winit sends a Resized event for the new size, which resizes the swap chain
+            window.set_inner_size(parent_size);
+        }
+    }
+}
+
 fn run<F>(event_loop: EventLoop<()>, event_handler: F) -> !
 where
     F: 'static + FnMut(Event<'_, ()>, &EventLoopWindowTarget<()>, &mut ControlFlow),
@@ -193,6 +245,15 @@ pub fn winit_runner(mut app: App) {
                 let window = windows.get_mut(window_id).unwrap();
                 window.update_resolution_from_backend(size.width, size.height);
 
+                // browsers can leave fullscreen on their own (ex: when escape is pressed)
+                #[cfg(target_arch = "wasm32")]
+                {
+                    let winit_window = winit_windows.get_window(window_id).unwrap();
+                    if window.is_fullscreen() && winit_window.fullscreen().is_none() {
+                        window.update_mode_from_backend(bevy_window::WindowMode::Windowed);
+                    }
+                }
+
                 let mut resize_events = app.resources.get_mut::<Events<WindowResized>>().unwrap();
                 resize_events.send(WindowResized {
                     id: window_id,
@@ -217,7 +278,36 @@ pub fn winit_runner(mut app: App) {
                 WindowEvent::KeyboardInput { ref input, .. } => {
                     let mut keyboard_input_events =
                         app.resources.get_mut::<Events<KeyboardInput>>().unwrap();
-                    keyboard_input_events.send(converters::convert_keyboard_input(input));
+                    let winit_windows = app.resources.get_mut::<WinitWindows>().unwrap();
+                    let window_id = winit_windows.get_window_id(winit_window_id).unwrap();
+                    keyboard_input_events
+                        .send(converters::convert_keyboard_input(window_id, input));
+                }
+                WindowEvent::Focused(focused) => {
+                    let mut window_focused_events =
+                        app.resources.get_mut::<Events<WindowFocused>>().unwrap();
+                    let winit_windows = app.resources.get_mut::<WinitWindows>().unwrap();
+                    let mut windows = app.resources.get_mut::<Windows>().unwrap();
+                    let window_id = winit_windows.get_window_id(winit_window_id).unwrap();
+                    if let Some(window) = windows.get_mut(window_id) {
+                        window.update_focused_from_backend(focused);
+                    }
+                    window_focused_events.send(WindowFocused {
+                        id: window_id,
+                        focused,
+                    });
+                }
+                WindowEvent::ReceivedCharacter(char) => {
+                    let mut received_character_events = app
+                        .resources
+                        .get_mut::<Events<ReceivedCharacter>>()
+                        .unwrap();
+                    let winit_windows = app.resources.get_mut::<WinitWindows>().unwrap();
+                    let window_id = winit_windows.get_window_id(winit_window_id).unwrap();
+                    received_character_events.send(ReceivedCharacter {
+                        id: window_id,
+                        char,
+                    });
                 }
                 WindowEvent::CursorMoved { position, .. } => {
                     let mut cursor_moved_events =
@@ -236,31 +326,38 @@ pub fn winit_runner(mut app: App) {
                 WindowEvent::MouseInput { state, button, .. } => {
                     let mut mouse_button_input_events =
                         app.resources.get_mut::<Events<MouseButtonInput>>().unwrap();
+                    let winit_windows = app.resources.get_mut::<WinitWindows>().unwrap();
+                    let window_id = winit_windows.get_window_id(winit_window_id).unwrap();
                     mouse_button_input_events.send(MouseButtonInput {
+                        window_id,
                         button: converters::convert_mouse_button(button),
                         state: converters::convert_element_state(state),
                     });
                 }
-                WindowEvent::MouseWheel { delta, .. } => match delta {
-                    event::MouseScrollDelta::LineDelta(x, y) => {
-                        let mut mouse_wheel_input_events =
-                            app.resources.get_mut::<Events<MouseWheel>>().unwrap();
-                        mouse_wheel_input_events.send(MouseWheel {
-                            unit: MouseScrollUnit::Line,
-                            x,
-                            y,
-                        });
-                    }
-                    event::MouseScrollDelta::PixelDelta(p) => {
-                        let mut mouse_wheel_input_events =
-                            app.resources.get_mut::<Events<MouseWheel>>().unwrap();
-                        mouse_wheel_input_events.send(MouseWheel {
-                            unit: MouseScrollUnit::Pixel,
-                            x: p.x as f32,
-                            y: p.y as f32,
-                        });
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let mut mouse_wheel_input_events =
+                        app.resources.get_mut::<Events<MouseWheel>>().unwrap();
+                    let winit_windows = app.resources.get_mut::<WinitWindows>().unwrap();
+                    let window_id = winit_windows.get_window_id(winit_window_id).unwrap();
+                    match delta {
+                        event::MouseScrollDelta::LineDelta(x, y) => {
+                            mouse_wheel_input_events.send(MouseWheel {
+                                window_id,
+                                unit: MouseScrollUnit::Line,
+                                x,
+                                y,
+                            });
+                        }
+                        event::MouseScrollDelta::PixelDelta(p) => {
+                            mouse_wheel_input_events.send(MouseWheel {
+                                window_id,
+                                unit: MouseScrollUnit::Pixel,
+                                x: p.x as f32,
+                                y: p.y as f32,
+                            });
+                        }
                     }
-                },
+                }
                 WindowEvent::Touch(touch) => {
                     let mut touch_input_events =
                         app.resources.get_mut::<Events<TouchInput>>().unwrap();
@@ -305,9 +402,15 @@ fn handle_create_window_events(
     let create_window_events = resources.get::<Events<CreateWindow>>().unwrap();
     let mut window_created_events = resources.get_mut::<Events<WindowCreated>>().unwrap();
     for create_window_event in create_window_event_reader.iter(&create_window_events) {
-        let window = Window::new(create_window_event.id, &create_window_event.descriptor);
+        let mut window = Window::new(create_window_event.id, &create_window_event.descriptor);
         winit_windows.create_window(event_loop, &window);
         let window_id = window.id();
+        if let Some(winit_window) = winit_windows.get_window(window_id) {
+            // SAFE: the handle belongs to the winit window that was just created for this window
+            unsafe {
+                window.update_raw_window_handle_from_backend(winit_window.raw_window_handle());
+            }
+        }
         windows.add(window);
         window_created_events.send(WindowCreated { id: window_id });
     }
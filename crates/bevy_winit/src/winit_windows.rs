@@ -47,7 +47,9 @@ impl WinitWindows {
         };
 
         #[allow(unused_mut)]
-        let mut winit_window_builder = winit_window_builder.with_title(window.title());
+        let mut winit_window_builder = winit_window_builder
+            .with_title(window.title())
+            .with_transparent(window.transparent());
 
         #[cfg(target_arch = "wasm32")]
         {
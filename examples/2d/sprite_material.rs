@@ -0,0 +1,139 @@
+use bevy::{
+    prelude::*,
+    render::{renderer::RenderResources, shader::ShaderDefs},
+    type_registry::TypeUuid,
+};
+
+/// Dissolves a sprite and an animated sprite sheet sprite with a custom sprite material. The engine still sizes,
+/// positions and animates the sprites, the material only decides the color of their pixels.
+fn main() {
+    App::build()
+        .add_default_plugins()
+        .add_plugin(SpriteMaterialPlugin::<Dissolve>::new(FRAGMENT_SHADER))
+        .add_startup_system(setup.system())
+        .add_system(animate_sprite_system.system())
+        .add_system(dissolve_system.system())
+        .run();
+}
+
+/// Burns the sprite away from its pixels with the lowest noise to the highest
+#[derive(RenderResources, ShaderDefs, TypeUuid)]
+#[uuid = "c5a3e1d2-4b8f-4e6a-9d17-2f0b8c6e4a91"]
+struct Dissolve {
+    edge_color: Color,
+    /// How much of the sprite is dissolved, from 0.0 to 1.0
+    progress: f32,
+    /// Sprite sheet sprites use their atlas texture instead
+    #[shader_def]
+    texture: Option<Handle<Texture>>,
+}
+
+const FRAGMENT_SHADER: &str = r#"
+#version 450
+
+layout(location = 0) in vec2 v_Uv;
+layout(location = 1) in vec4 v_Color;
+layout(location = 2) in vec2 v_Size;
+layout(location = 3) in vec2 v_SpriteUv;
+
+layout(location = 0) out vec4 o_Target;
+
+layout(set = 1, binding = 0) uniform Dissolve_edge_color {
+    vec4 EdgeColor;
+};
+layout(set = 1, binding = 1) uniform Dissolve_progress {
+    float Progress;
+};
+# ifdef DISSOLVE_TEXTURE
+layout(set = 1, binding = 2) uniform texture2D Dissolve_texture;
+layout(set = 1, binding = 3) uniform sampler Dissolve_texture_sampler;
+# endif
+# ifdef SPRITE_SHEET
+layout(set = 3, binding = 2) uniform texture2D TextureAtlas_texture;
+layout(set = 3, binding = 3) uniform sampler TextureAtlas_texture_sampler;
+# endif
+
+float hash(vec2 cell) {
+    return fract(sin(dot(cell, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main() {
+    vec4 color = v_Color;
+# ifdef DISSOLVE_TEXTURE
+    color *= texture(sampler2D(Dissolve_texture, Dissolve_texture_sampler), v_Uv);
+# endif
+# ifdef SPRITE_SHEET
+    color *= texture(sampler2D(TextureAtlas_texture, TextureAtlas_texture_sampler), v_Uv);
+# endif
+
+    // the noise is the same across each 2x2 pixel block of the sprite, however large it is drawn
+    float noise = hash(floor(v_SpriteUv * v_Size / 2.0));
+    if (noise < Progress) {
+        discard;
+    }
+    if (noise < Progress + 0.08 && color.a > 0.0) {
+        color.rgb = EdgeColor.rgb;
+    }
+    o_Target = color;
+}
+"#;
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut materials: ResMut<Assets<Dissolve>>,
+) {
+    let texture_handle = asset_server.load("textures/rpg/chars/gabe/gabe-idle-run.png");
+    let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(24.0, 24.0), 7, 1);
+    let texture_atlas_handle = texture_atlases.add(texture_atlas);
+
+    commands
+        .spawn(Camera2dComponents::default())
+        .spawn(MaterialSpriteComponents {
+            transform: Transform::from_translation(Vec3::new(-200.0, 0.0, 0.0)),
+            ..MaterialSpriteComponents::new::<Dissolve>(Sprite::new(Vec2::new(256.0, 256.0)))
+        })
+        .with(materials.add(Dissolve {
+            edge_color: Color::rgb(1.0, 0.5, 0.1),
+            progress: 0.0,
+            texture: Some(asset_server.load("branding/icon.png")),
+        }))
+        .spawn(MaterialSpriteSheetComponents {
+            transform: Transform {
+                scale: Vec3::splat(6.0),
+                ..Transform::from_translation(Vec3::new(200.0, 0.0, 0.0))
+            },
+            ..MaterialSpriteSheetComponents::new::<Dissolve>(
+                TextureAtlasSprite::default(),
+                texture_atlas_handle,
+            )
+        })
+        .with(materials.add(Dissolve {
+            edge_color: Color::rgb(0.2, 0.6, 1.0),
+            progress: 0.0,
+            texture: None,
+        }))
+        .with(Timer::from_seconds(0.1, true));
+}
+
+fn animate_sprite_system(
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut query: Query<(&mut Timer, &mut TextureAtlasSprite, &Handle<TextureAtlas>)>,
+) {
+    for (timer, mut sprite, texture_atlas_handle) in query.iter_mut() {
+        if timer.finished {
+            let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
+            sprite.index = ((sprite.index as usize + 1) % texture_atlas.textures.len()) as u32;
+        }
+    }
+}
+
+/// Dissolves the sprites and brings them back, over and over
+fn dissolve_system(time: Res<Time>, mut materials: ResMut<Assets<Dissolve>>) {
+    let progress = (time.seconds_since_startup as f32 * 0.8).sin() * 0.55 + 0.5;
+    let handles = materials.ids().collect::<Vec<_>>();
+    for id in handles {
+        materials.get_mut(id).unwrap().progress = progress;
+    }
+}
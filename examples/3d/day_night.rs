@@ -0,0 +1,70 @@
+use bevy::{
+    pbr::sky::{SkyComponents, SkyPlugin, Sun, SunComponents},
+    prelude::*,
+};
+
+/// Runs a day and night cycle: the sun moves across the sky and colors the light, the sky and the fog
+fn main() {
+    App::build()
+        .add_resource(Fog {
+            color: Color::rgba(0.7, 0.8, 0.9, 0.6),
+            start: 5.0,
+            end: 40.0,
+        })
+        .add_default_plugins()
+        .add_plugin(SkyPlugin)
+        .add_startup_system(setup.system())
+        .add_system(time_control_system.system())
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let cube = meshes.add(Mesh::from(shape::Cube { size: 1.0 }));
+    let cube_material = materials.add(Color::rgb(0.8, 0.7, 0.6).into());
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 100.0 })),
+            material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+            ..Default::default()
+        })
+        // a full day passes in 24 seconds
+        .spawn(SunComponents {
+            sun: Sun {
+                time_of_day: 5.0,
+                hours_per_second: 1.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .spawn(SkyComponents::default())
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(-8.0, 4.0, 12.0))
+                .looking_at(Vec3::new(0.0, 2.0, -10.0), Vec3::unit_y()),
+            ..Default::default()
+        });
+
+    // a row of cubes that fades into the fog
+    for i in 0..10 {
+        commands.spawn(PbrComponents {
+            mesh: cube.clone(),
+            material: cube_material.clone(),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.5, -(i as f32) * 5.0)),
+            ..Default::default()
+        });
+    }
+}
+
+/// Hold space to speed up time
+fn time_control_system(keyboard_input: Res<Input<KeyCode>>, mut query: Query<&mut Sun>) {
+    for mut sun in query.iter_mut() {
+        sun.hours_per_second = if keyboard_input.pressed(KeyCode::Space) {
+            6.0
+        } else {
+            1.0
+        };
+    }
+}
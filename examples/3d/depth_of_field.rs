@@ -0,0 +1,75 @@
+use bevy::{
+    prelude::*,
+    sprite::depth_of_field::{DepthOfField, DepthOfFieldPlugin},
+};
+
+/// Shows a row of cubes through a lens with a shallow depth of field. Press up and down to move the focus further away
+/// or closer, and left and right to open or close the aperture.
+fn main() {
+    App::build()
+        .add_default_plugins()
+        .add_plugin(DepthOfFieldPlugin)
+        .add_startup_system(setup.system())
+        .add_system(focus_system.system())
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let cube = meshes.add(Mesh::from(shape::Cube { size: 1.0 }));
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 60.0 })),
+            material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+            ..Default::default()
+        })
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 8.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(2.0, 1.5, 6.0))
+                .looking_at(Vec3::new(0.0, 0.5, -10.0), Vec3::unit_y()),
+            ..Default::default()
+        })
+        .with(DepthOfField {
+            focus_distance: 6.0,
+            f_number: 1.4,
+            ..Default::default()
+        });
+
+    for i in 0..10 {
+        let shade = i as f32 / 10.0;
+        commands.spawn(PbrComponents {
+            mesh: cube.clone(),
+            material: materials.add(Color::rgb(0.9, 0.6 * shade + 0.2, 0.3).into()),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.5, 4.0 - i as f32 * 3.0)),
+            ..Default::default()
+        });
+    }
+}
+
+fn focus_system(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut query: Query<&mut DepthOfField>,
+) {
+    for mut depth_of_field in query.iter_mut() {
+        if keyboard_input.pressed(KeyCode::Up) {
+            depth_of_field.focus_distance += 5.0 * time.delta_seconds;
+        }
+        if keyboard_input.pressed(KeyCode::Down) {
+            depth_of_field.focus_distance =
+                (depth_of_field.focus_distance - 5.0 * time.delta_seconds).max(0.5);
+        }
+        if keyboard_input.just_pressed(KeyCode::Left) {
+            depth_of_field.f_number = (depth_of_field.f_number / 2.0).max(0.7);
+        }
+        if keyboard_input.just_pressed(KeyCode::Right) {
+            depth_of_field.f_number = (depth_of_field.f_number * 2.0).min(22.4);
+        }
+    }
+}
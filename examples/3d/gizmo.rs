@@ -0,0 +1,100 @@
+use bevy::{
+    pbr::gizmo::{Gizmo, GizmoMode, GizmoPlugin},
+    prelude::*,
+};
+
+/// Drag the handles with the left mouse button to move, rotate or scale the selected cube. Tab selects the next cube,
+/// 1, 2 and 3 switch between translate, rotate and scale handles, and L toggles local axes. Drag with the right mouse
+/// button to orbit the camera.
+fn main() {
+    App::build()
+        .add_resource(Msaa { samples: 4 })
+        .add_default_plugins()
+        .add_plugin(CameraControllerPlugin)
+        .add_plugin(GizmoPlugin::default())
+        .add_startup_system(setup.system())
+        .add_system(gizmo_controls_system.system())
+        .run();
+}
+
+struct Selectable;
+
+fn setup(
+    mut commands: Commands,
+    mut gizmo: ResMut<Gizmo>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let cube = meshes.add(Mesh::from(shape::Cube { size: 0.5 }));
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 20.0 })),
+            material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+            ..Default::default()
+        })
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 8.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents::default())
+        .with(OrbitCamera {
+            rotate_button: MouseButton::Right,
+            ..Default::default()
+        });
+
+    for (i, color) in [
+        Color::rgb(0.8, 0.7, 0.6),
+        Color::rgb(0.6, 0.7, 0.8),
+        Color::rgb(0.7, 0.8, 0.6),
+    ]
+    .iter()
+    .enumerate()
+    {
+        commands
+            .spawn(PbrComponents {
+                mesh: cube.clone(),
+                material: materials.add((*color).into()),
+                transform: Transform::from_translation(Vec3::new(i as f32 * 2.0 - 2.0, 0.5, 0.0)),
+                ..Default::default()
+            })
+            .with(Selectable);
+        if gizmo.selected.is_none() {
+            gizmo.selected = commands.current_entity();
+        }
+    }
+}
+
+fn gizmo_controls_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut gizmo: ResMut<Gizmo>,
+    query: Query<With<Selectable, Entity>>,
+) {
+    if gizmo.is_dragging() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        let entities = query.iter().collect::<Vec<_>>();
+        let next = gizmo
+            .selected
+            .and_then(|selected| entities.iter().position(|entity| *entity == selected))
+            .map_or(0, |index| (index + 1) % entities.len());
+        gizmo.selected = entities.get(next).cloned();
+    }
+
+    for (key, mode) in [
+        (KeyCode::Key1, GizmoMode::Translate),
+        (KeyCode::Key2, GizmoMode::Rotate),
+        (KeyCode::Key3, GizmoMode::Scale),
+    ]
+    .iter()
+    {
+        if keyboard_input.just_pressed(*key) {
+            gizmo.mode = *mode;
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::L) {
+        gizmo.local = !gizmo.local;
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::{
+    pbr::particles::{ParticleEmitter, ParticleEmitterComponents, ParticlePlugin},
+    prelude::*,
+};
+
+/// Simulates a fountain of sparks and a column of sorted smoke on the gpu. Needs compute support.
+fn main() {
+    App::build()
+        .add_resource(Msaa { samples: 4 })
+        .add_default_plugins()
+        .add_plugin(ParticlePlugin)
+        .add_startup_system(setup.system())
+        .add_system(rotate_emitter.system())
+        .run();
+}
+
+/// Tilts the fountain around so its particles trail behind
+struct Rotating;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 10.0 })),
+            material: materials.add(Color::rgb(0.2, 0.2, 0.2).into()),
+            ..Default::default()
+        })
+        .spawn(ParticleEmitterComponents {
+            emitter: ParticleEmitter {
+                capacity: 50_000,
+                rate: 10_000.0,
+                lifetime: 2.5,
+                velocity: Vec3::new(0.0, 5.0, 0.0),
+                velocity_randomness: 1.5,
+                acceleration: Vec3::new(0.0, -9.8, 0.0),
+                start_size: 0.05,
+                end_size: 0.01,
+                start_color: Color::rgb(4.0, 2.0, 0.5),
+                end_color: Color::rgba(1.0, 0.1, 0.0, 0.0),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(Vec3::new(-1.5, 0.0, 0.0)),
+            ..Default::default()
+        })
+        .with(Rotating)
+        // smoke overlaps itself a lot, so it is sorted back to front
+        .spawn(ParticleEmitterComponents {
+            emitter: ParticleEmitter {
+                capacity: 2_000,
+                rate: 200.0,
+                lifetime: 8.0,
+                velocity: Vec3::new(0.0, 0.6, 0.0),
+                velocity_randomness: 0.2,
+                acceleration: Vec3::new(0.1, 0.0, 0.0),
+                radius: 0.3,
+                start_size: 0.3,
+                end_size: 1.5,
+                start_color: Color::rgba(0.5, 0.5, 0.5, 0.5),
+                end_color: Color::rgba(0.8, 0.8, 0.8, 0.0),
+                sort: true,
+            },
+            transform: Transform::from_translation(Vec3::new(1.5, 0.0, 0.0)),
+            ..Default::default()
+        })
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 8.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(0.0, 3.0, 10.0))
+                .looking_at(Vec3::new(0.0, 2.0, 0.0), Vec3::unit_y()),
+            ..Default::default()
+        });
+}
+
+fn rotate_emitter(time: Res<Time>, mut query: Query<(&Rotating, &mut Transform)>) {
+    for (_rotating, mut transform) in query.iter_mut() {
+        transform.rotation = Quat::from_rotation_z(time.seconds_since_startup.sin() as f32 * 0.5);
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::{
+    prelude::*,
+    sprite::motion_blur::{MotionBlur, MotionBlurPlugin},
+};
+
+/// Races a line of cubes along a track, followed by a camera as fast as the slowest one. The cube the camera keeps up
+/// with stays sharp, while the track and the cubes overtaking it are blurred. Press space to toggle the blur, and up and down to open or
+/// close the shutter.
+fn main() {
+    App::build()
+        .add_default_plugins()
+        .add_plugin(MotionBlurPlugin)
+        .add_startup_system(setup.system())
+        .add_system(race_system.system())
+        .add_system(shutter_system.system())
+        .run();
+}
+
+/// Moves along the track at its speed. The track itself stands still, but it is moved back to the start together with
+/// the racers.
+struct Racer {
+    speed: f32,
+}
+
+/// Marks the camera, which races along with the cubes
+struct Chase;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let cube = meshes.add(Mesh::from(shape::Cube { size: 1.0 }));
+    let stripe = meshes.add(Mesh::from(shape::Plane { size: 2.0 }));
+    let stripe_material = materials.add(Color::rgb(0.9, 0.9, 0.9).into());
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 400.0 })),
+            material: materials.add(Color::rgb(0.3, 0.3, 0.35).into()),
+            ..Default::default()
+        })
+        .with(Racer { speed: 0.0 })
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 8.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(-0.75, 2.5, 8.0))
+                .looking_at(Vec3::new(-2.25, 0.5, 0.0), Vec3::unit_y()),
+            ..Default::default()
+        })
+        .with(MotionBlur::default())
+        .with(Racer { speed: 20.0 })
+        .with(Chase);
+
+    // stripes along the track, so the camera's own motion is visible
+    for i in 0..100 {
+        commands
+            .spawn(PbrComponents {
+                mesh: stripe.clone(),
+                material: stripe_material.clone(),
+                transform: Transform {
+                    scale: Vec3::new(0.1, 1.0, 0.5),
+                    ..Transform::from_translation(Vec3::new(0.0, 0.01, 190.0 - i as f32 * 4.0))
+                },
+                ..Default::default()
+            })
+            .with(Racer { speed: 0.0 });
+    }
+
+    for i in 0..4 {
+        let shade = i as f32 / 4.0;
+        commands
+            .spawn(PbrComponents {
+                mesh: cube.clone(),
+                material: materials.add(Color::rgb(0.9, 0.6 * shade + 0.2, 0.3).into()),
+                transform: Transform::from_translation(Vec3::new(i as f32 * 1.5 - 2.25, 0.5, 0.0)),
+                ..Default::default()
+            })
+            .with(Racer {
+                speed: 20.0 + i as f32 * 4.0,
+            });
+    }
+}
+
+fn race_system(time: Res<Time>, mut racers: Query<(&Racer, &mut Transform, Option<&Chase>)>) {
+    let mut camera_z = 0.0;
+    for (racer, mut transform, chase) in racers.iter_mut() {
+        let z = transform.translation.z() - racer.speed * time.delta_seconds;
+        transform.translation.set_z(z);
+        if chase.is_some() {
+            camera_z = z;
+        }
+    }
+
+    // everything moves back to the start together, so nothing moves on screen, and cubes that got far ahead start
+    // over behind the camera to overtake it again
+    let track_shift = if camera_z < -180.0 { 360.0 } else { 0.0 };
+    camera_z += track_shift;
+    for (racer, mut transform, chase) in racers.iter_mut() {
+        let mut z = transform.translation.z() + track_shift;
+        if racer.speed > 0.0 && chase.is_none() && z < camera_z - 60.0 {
+            z += 80.0;
+        }
+        transform.translation.set_z(z);
+    }
+}
+
+fn shutter_system(keyboard_input: Res<Input<KeyCode>>, mut query: Query<&mut MotionBlur>) {
+    for mut motion_blur in query.iter_mut() {
+        if keyboard_input.just_pressed(KeyCode::Space) {
+            motion_blur.enabled = !motion_blur.enabled;
+        }
+        if keyboard_input.just_pressed(KeyCode::Up) {
+            motion_blur.shutter_angle = (motion_blur.shutter_angle + 45.0).min(360.0);
+        }
+        if keyboard_input.just_pressed(KeyCode::Down) {
+            motion_blur.shutter_angle = (motion_blur.shutter_angle - 45.0).max(0.0);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::{
+    pbr::procgen::{IsoSurface, ProcgenPlugin, SdfEdit, SdfShape},
+    prelude::*,
+    render::camera::{Camera, CameraControllerPlugin, OrbitCamera},
+};
+
+/// Meshes rolling hills and a floating rock from a signed distance field. Click to carve a crater where the cursor
+/// points, or shift click to pile up a mound. Only the chunks around an edit are meshed again, in the background. Drag
+/// with the right mouse button to rotate the camera.
+fn main() {
+    App::build()
+        .add_default_plugins()
+        .add_plugin(CameraControllerPlugin)
+        .add_plugin(ProcgenPlugin)
+        .add_startup_system(setup.system())
+        .add_system(edit_system.system())
+        .run();
+}
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let mut surface = IsoSurface::new(|position: Vec3| {
+        let hills = 12.0 + (position.x() * 0.15).sin() * 3.0 + (position.z() * 0.1).cos() * 4.0;
+        let ground = position.y() - hills;
+        let rock = (position - Vec3::new(32.0, 24.0, 32.0)).length() - 5.0;
+        ground.min(rock)
+    });
+    surface.material = materials.add(Color::rgb(0.45, 0.55, 0.3).into());
+    surface.chunks = (4, 2, 4);
+
+    commands
+        .spawn((
+            surface,
+            // centers the surface on the origin
+            Transform::from_translation(Vec3::new(-32.0, -16.0, -32.0)),
+            GlobalTransform::default(),
+        ))
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(20.0, 40.0, 20.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents::default())
+        .with(OrbitCamera {
+            radius: 60.0,
+            rotate_button: MouseButton::Right,
+            ..Default::default()
+        });
+}
+
+#[derive(Default)]
+struct EditState {
+    cursor_moved_event_reader: EventReader<CursorMoved>,
+    cursor_position: Option<Vec2>,
+}
+
+fn edit_system(
+    mut state: Local<EditState>,
+    cursor_moved_events: Res<Events<CursorMoved>>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut surface_query: Query<(&mut IsoSurface, &GlobalTransform)>,
+) {
+    if let Some(event) = state.cursor_moved_event_reader.latest(&cursor_moved_events) {
+        state.cursor_position = Some(event.position);
+    }
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let cursor_position = if let Some(cursor_position) = state.cursor_position {
+        cursor_position
+    } else {
+        return;
+    };
+
+    for (camera, camera_transform) in camera_query.iter() {
+        let ray =
+            if let Some(ray) = camera.screen_to_ray(&windows, camera_transform, cursor_position) {
+                ray
+            } else {
+                continue;
+            };
+        for (mut surface, surface_transform) in surface_query.iter_mut() {
+            // raycasts and edits are in the surface's local space
+            let world_to_surface = surface_transform.compute_matrix().inverse();
+            let hit = surface.raycast(
+                world_to_surface.transform_point3(ray.origin),
+                world_to_surface.transform_vector3(ray.direction),
+                200.0,
+            );
+            if let Some(hit) = hit {
+                let crater = SdfShape::Sphere {
+                    center: hit,
+                    radius: 4.0,
+                };
+                if keyboard_input.pressed(KeyCode::LShift) {
+                    surface.edit(SdfEdit::union(crater));
+                } else {
+                    surface.edit(SdfEdit::subtract(crater));
+                }
+            }
+        }
+    }
+}
@@ -38,6 +38,7 @@ fn setup(
         albedo: Color::rgba(1.0, 0.0, 0.0, 0.5),
         albedo_texture: Some(texture_handle.clone()),
         shaded: false,
+        ..Default::default()
     });
 
     // and lets make this one blue! (and also slightly transparent)
@@ -45,6 +46,7 @@ fn setup(
         albedo: Color::rgba(0.0, 0.0, 1.0, 0.5),
         albedo_texture: Some(texture_handle),
         shaded: false,
+        ..Default::default()
     });
 
     // add entities to the world
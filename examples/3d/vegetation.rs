@@ -0,0 +1,71 @@
+use bevy::{
+    core::Rng,
+    pbr::vegetation::{
+        Scatter, VegetationComponents, VegetationInstances, VegetationMaterial, VegetationPlugin,
+        Wind,
+    },
+    prelude::*,
+};
+
+/// Scatters thousands of grass blades over a field and sways them in the wind, all in a single draw call. The blades
+/// outside of the camera's view are culled on the gpu.
+fn main() {
+    App::build()
+        .add_resource(Msaa { samples: 4 })
+        .add_default_plugins()
+        .add_plugin(VegetationPlugin)
+        .add_plugin(GpuCullingPlugin)
+        .add_startup_system(setup.system())
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut vegetation_materials: ResMut<Assets<VegetationMaterial>>,
+) {
+    let instances = Scatter {
+        min: Vec2::new(-10.0, -10.0),
+        max: Vec2::new(10.0, 10.0),
+        density: 20.0,
+        ..Default::default()
+    }
+    .instances(&mut Rng::with_seed(7));
+
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 20.0 })),
+            material: materials.add(Color::rgb(0.25, 0.35, 0.15).into()),
+            ..Default::default()
+        })
+        .spawn(VegetationComponents {
+            // the blade's origin is at its center, so it is raised to stand on the ground and only its upper half sways
+            mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::new(0.08, 0.6)))),
+            material: materials.add(StandardMaterial {
+                albedo: Color::rgb(0.35, 0.6, 0.2),
+                double_sided: true,
+                ..Default::default()
+            }),
+            vegetation_material: vegetation_materials.add(VegetationMaterial {
+                wind: Wind {
+                    strength: 0.15,
+                    height: 0.3,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            instances: VegetationInstances { instances },
+            transform: Transform::from_translation(Vec3::new(0.0, 0.3, 0.0)),
+            ..Default::default()
+        })
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 8.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(-6.0, 3.0, 10.0))
+                .looking_at(Vec3::zero(), Vec3::unit_y()),
+            ..Default::default()
+        });
+}
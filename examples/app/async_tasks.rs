@@ -0,0 +1,75 @@
+use bevy::{prelude::*, tasks::AsyncComputeTaskPool};
+use rand::random;
+use std::time::Duration;
+
+/// Runs slow work on the async compute task pool without stalling frames. Each pending entity waits for a task that
+/// takes a few seconds to size its cube, and the cube appears once the task finished. Space despawns the entities
+/// whose tasks are still running, which cancels the tasks.
+fn main() {
+    App::build()
+        .add_default_plugins()
+        .add_async_task::<CubeSize>()
+        .add_startup_system(setup.system())
+        .add_system(spawn_cubes_system.system())
+        .add_system(cancel_system.system())
+        .run();
+}
+
+/// The output of the slow task
+struct CubeSize(f32);
+
+fn setup(mut commands: Commands, task_pool: Res<AsyncComputeTaskPool>) {
+    for i in 0..10 {
+        let task = AsyncTask::spawn(&task_pool, async move {
+            // stands in for pathfinding, mesh generation or a network request
+            std::thread::sleep(Duration::from_millis(500 + random::<u64>() % 4000));
+            CubeSize(0.2 + i as f32 * 0.05)
+        });
+        commands.spawn((
+            task,
+            Transform::from_translation(Vec3::new(i as f32 - 4.5, 0.0, 0.0)),
+        ));
+    }
+
+    commands
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 8.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(0.0, 4.0, 10.0))
+                .looking_at(Vec3::default(), Vec3::unit_y()),
+            ..Default::default()
+        });
+}
+
+fn spawn_cubes_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(Entity, Added<CubeSize>, &Transform)>,
+) {
+    for (entity, cube_size, transform) in query.iter() {
+        commands.insert(
+            entity,
+            PbrComponents {
+                mesh: meshes.add(Mesh::from(shape::Cube { size: cube_size.0 })),
+                material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+                transform: *transform,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+fn cancel_system(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    query: Query<With<AsyncTask<CubeSize>, Entity>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        for entity in query.iter() {
+            commands.despawn(entity);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::{
+    console::{ConsoleError, ConsolePlugin, Cvars, RegisterConsole},
+    prelude::*,
+};
+
+/// Press ` to open the console. `cube.speed 3` spins the cube faster, `cube.size 2` grows it and `cube.reset` turns
+/// it back. `cvars` lists every cvar, including the engine's like `timescale 0.5` or `ui.scale 2`.
+fn main() {
+    App::build()
+        .add_default_plugins()
+        .add_plugin(ConsolePlugin::default())
+        .add_resource(CubeSize { size: 1.0 })
+        // a cvar that lives in the Cvars resource
+        .add_cvar("cube.speed", 1.0f32, "Rotations of the cube per second")
+        // a cvar bound to a field of a resource
+        .add_cvar_binding(
+            "cube.size",
+            "The size of the cube",
+            |cube_size: &CubeSize| cube_size.size,
+            |cube_size, size| cube_size.size = size,
+        )
+        .add_console_command("cube.reset", "Turns the cube back", |world, _, _| {
+            for (_, mut transform) in world.query_mut::<(&Cube, &mut Transform)>() {
+                transform.rotation = Quat::identity();
+            }
+            Ok("The cube was turned back".to_string())
+        })
+        .add_console_command(
+            "cube.turn",
+            "Turns the cube by an angle in degrees",
+            |world, _, args| {
+                let degrees = args
+                    .first()
+                    .and_then(|degrees| degrees.parse::<f32>().ok())
+                    .ok_or_else(|| ConsoleError::Usage("cube.turn <degrees>".to_string()))?;
+                for (_, mut transform) in world.query_mut::<(&Cube, &mut Transform)>() {
+                    transform.rotate(Quat::from_rotation_y(degrees.to_radians()));
+                }
+                Ok(String::new())
+            },
+        )
+        .add_startup_system(setup.system())
+        .add_system(spin_system.system())
+        .add_system(size_system.system())
+        .run();
+}
+
+struct Cube;
+
+struct CubeSize {
+    size: f32,
+}
+
+fn spin_system(time: Res<Time>, cvars: Res<Cvars>, mut query: Query<With<Cube, &mut Transform>>) {
+    let speed = cvars.get::<f32>("cube.speed").copied().unwrap_or(0.0);
+    let angle = speed * std::f32::consts::PI * 2.0 * time.delta_seconds;
+    for mut transform in query.iter_mut() {
+        transform.rotate(Quat::from_rotation_y(angle));
+    }
+}
+
+fn size_system(cube_size: ChangedRes<CubeSize>, mut query: Query<With<Cube, &mut Transform>>) {
+    for mut transform in query.iter_mut() {
+        transform.scale = Vec3::splat(cube_size.size);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+            material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+            ..Default::default()
+        })
+        .with(Cube)
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 8.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(-3.0, 3.0, 5.0))
+                .looking_at(Vec3::default(), Vec3::unit_y()),
+            ..Default::default()
+        })
+        .spawn(UiCameraComponents::default());
+}
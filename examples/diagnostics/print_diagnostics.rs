@@ -11,7 +11,15 @@ fn main() {
         // Adds a system that prints diagnostics to the console
         .add_plugin(PrintDiagnosticsPlugin::default())
         // Any plugin can register diagnostics
+        // Uncomment this to add archetype count, archetype churn and component memory diagnostics:
+        // .add_plugin(bevy::diagnostic::ArchetypeDiagnosticsPlugin::default())
         // Uncomment this to add some render resource diagnostics:
         // .add_plugin(bevy::wgpu::diagnostic::WgpuResourceDiagnosticsPlugin::default())
+        // Uncomment this to add frame latency diagnostics:
+        // .add_plugin(bevy::wgpu::diagnostic::WgpuFramePacingDiagnosticsPlugin::default())
+        // Uncomment this to add draw call, triangle and state change diagnostics:
+        // .add_plugin(bevy::wgpu::diagnostic::WgpuRenderStatisticsDiagnosticsPlugin::default())
+        // Uncomment this to add diagnostics that show how many meshes share their gpu buffers:
+        // .add_plugin(bevy::wgpu::diagnostic::MeshBufferDiagnosticsPlugin::default())
         .run();
 }
@@ -0,0 +1,25 @@
+use bevy::{
+    diagnostic::{
+        stress::{StressTest, StressTestPlugin},
+        FrameTimeDiagnosticsPlugin, PrintDiagnosticsPlugin,
+    },
+    prelude::*,
+};
+
+/// Spawns a procedurally generated workload and prints the frame time. Run it with `--features stress`.
+fn main() {
+    App::build()
+        .add_resource(StressTest {
+            meshes: 5000,
+            lights: 8,
+            sprites: 2000,
+            hierarchies: 16,
+            hierarchy_depth: 32,
+            ..Default::default()
+        })
+        .add_default_plugins()
+        .add_plugin(StressTestPlugin)
+        .add_plugin(FrameTimeDiagnosticsPlugin::default())
+        .add_plugin(PrintDiagnosticsPlugin::default())
+        .run();
+}
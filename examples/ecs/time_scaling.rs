@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+/// Slows down and pauses gameplay time. Up and Down change the relative speed of [Time] and Space pauses it. The top
+/// square moves by the scaled delta every frame, the bottom one moves in fixed steps, and both slow down and stop
+/// together.
+fn main() {
+    App::build()
+        .add_default_plugins()
+        .add_startup_system(setup.system())
+        .add_system(time_control_system.system())
+        .add_system(frame_movement_system.system())
+        .add_system(fixed_movement_system.system())
+        .run();
+}
+
+/// Moves back and forth across the window
+struct Mover {
+    velocity: f32,
+}
+
+/// Marks the mover that is moved in fixed steps
+struct FixedStep;
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands
+        .spawn(Camera2dComponents::default())
+        .spawn(SpriteComponents {
+            material: materials.add(Color::rgb(0.8, 0.5, 0.3).into()),
+            sprite: Sprite::new(Vec2::new(40.0, 40.0)),
+            transform: Transform::from_translation(Vec3::new(0.0, 60.0, 0.0)),
+            ..Default::default()
+        })
+        .with(Mover { velocity: 300.0 })
+        .spawn(SpriteComponents {
+            material: materials.add(Color::rgb(0.3, 0.5, 0.8).into()),
+            sprite: Sprite::new(Vec2::new(40.0, 40.0)),
+            transform: Transform::from_translation(Vec3::new(0.0, -60.0, 0.0)),
+            ..Default::default()
+        })
+        .with(Mover { velocity: 300.0 })
+        .with(FixedStep);
+}
+
+fn time_control_system(keyboard_input: Res<Input<KeyCode>>, mut time: ResMut<Time>) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+
+    let speed = time.relative_speed();
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        time.set_relative_speed((speed * 2.0).min(4.0));
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        time.set_relative_speed(speed / 2.0);
+    }
+}
+
+fn move_and_bounce(mover: &mut Mover, transform: &mut Transform, delta_seconds: f32) {
+    let mut x = transform.translation.x() + mover.velocity * delta_seconds;
+    if x.abs() > 300.0 {
+        x = x.max(-300.0).min(300.0);
+        mover.velocity = -mover.velocity;
+    }
+    transform.translation.set_x(x);
+}
+
+fn frame_movement_system(
+    time: Res<Time>,
+    mut query: Query<Without<FixedStep, (&mut Mover, &mut Transform)>>,
+) {
+    for (mut mover, mut transform) in query.iter_mut() {
+        move_and_bounce(&mut mover, &mut transform, time.delta_seconds);
+    }
+}
+
+fn fixed_movement_system(
+    fixed_timestep: Res<FixedTimestep>,
+    mut query: Query<With<FixedStep, (&mut Mover, &mut Transform)>>,
+) {
+    for (mut mover, mut transform) in query.iter_mut() {
+        for _ in 0..fixed_timestep.steps() {
+            move_and_bounce(&mut mover, &mut transform, fixed_timestep.step_seconds());
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::{
+    editor::{Editable, Editor, EditorPlugin},
+    prelude::*,
+};
+
+/// Opens the level editor on a small level. Click a cube or a hierarchy row to select it, drag the handles to move it
+/// and edit its fields in the inspector. P and a click makes the selected entity a child of the clicked one, Delete
+/// removes it, Ctrl+Z and Ctrl+Y undo and redo, Ctrl+S saves the level to `assets/scenes/level.scn` and F1 closes and
+/// reopens the editor. Drag with the right mouse button to orbit the camera.
+fn main() {
+    App::build()
+        .add_resource(Msaa { samples: 4 })
+        .add_default_plugins()
+        .add_plugin(CameraControllerPlugin)
+        .add_plugin(EditorPlugin::default())
+        .add_startup_system(setup.system())
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut editor: ResMut<Editor>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    editor.enabled = true;
+
+    let cube = meshes.add(Mesh::from(shape::Cube { size: 0.5 }));
+    commands
+        // not editable, so it can't be selected or saved
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 20.0 })),
+            material: materials.add(Color::rgb(0.3, 0.5, 0.3).into()),
+            ..Default::default()
+        })
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 8.0, 4.0)),
+            ..Default::default()
+        })
+        .with(Editable::new("light"))
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(0.0, 4.0, 10.0))
+                .looking_at(Vec3::zero(), Vec3::unit_y()),
+            ..Default::default()
+        })
+        .with(OrbitCamera {
+            rotate_button: MouseButton::Right,
+            ..Default::default()
+        })
+        .spawn(UiCameraComponents::default());
+
+    for (i, color) in [
+        Color::rgb(0.8, 0.7, 0.6),
+        Color::rgb(0.6, 0.7, 0.8),
+        Color::rgb(0.7, 0.8, 0.6),
+    ]
+    .iter()
+    .enumerate()
+    {
+        commands
+            .spawn(PbrComponents {
+                mesh: cube.clone(),
+                material: materials.add((*color).into()),
+                transform: Transform::from_translation(Vec3::new(i as f32 * 2.0 - 2.0, 0.5, 0.0)),
+                ..Default::default()
+            })
+            .with(Editable::new(format!("cube {}", i)));
+    }
+}
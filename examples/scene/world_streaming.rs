@@ -0,0 +1,104 @@
+use bevy::{
+    core::Rng,
+    prelude::*,
+    render::camera::{CameraControllerPlugin, FlyCamera},
+    scene::{ChunkCoordinates, StreamingViewer, WorldStreaming, WorldStreamingPlugin},
+};
+
+/// Flies over an endless field of pillars. Chunks are generated around the camera as it moves and unloaded behind it,
+/// together with the material each chunk created for itself. Click to grab the cursor, move with WASD and look around
+/// with the mouse.
+fn main() {
+    let mut streaming = WorldStreaming::new(CHUNK_SIZE, generate_chunk);
+    streaming.load_distance = 60.0;
+    streaming.unload_distance = 80.0;
+
+    App::build()
+        .add_default_plugins()
+        .add_plugin(CameraControllerPlugin)
+        .add_plugin(WorldStreamingPlugin)
+        .add_resource(streaming)
+        .add_startup_system(setup.system())
+        .run();
+}
+
+const CHUNK_SIZE: f32 = 16.0;
+
+/// Meshes that all chunks share
+struct ChunkMeshes {
+    ground: Handle<Mesh>,
+    pillar: Handle<Mesh>,
+}
+
+fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands
+        .insert_resource(ChunkMeshes {
+            ground: meshes.add(Mesh::from(shape::Plane { size: CHUNK_SIZE })),
+            pillar: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+        })
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 30.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(Camera3dComponents {
+            transform: Transform::from_translation(Vec3::new(0.0, 6.0, 0.0)),
+            ..Default::default()
+        })
+        .with(FlyCamera {
+            speed: 20.0,
+            ..Default::default()
+        })
+        .with(StreamingViewer);
+}
+
+/// Generates the same ground tile and pillars for a chunk every time it is loaded
+fn generate_chunk(
+    world: &mut World,
+    resources: &mut Resources,
+    coordinates: ChunkCoordinates,
+    chunk: Entity,
+) {
+    let seed = ((coordinates.x as u64) << 32) ^ (coordinates.z as u32 as u64);
+    let mut rng = Rng::with_seed(seed);
+    let chunk_meshes = resources.get::<ChunkMeshes>().unwrap();
+    let mut materials = resources.get_mut::<Assets<StandardMaterial>>().unwrap();
+    // the chunk's own material is freed when the chunk is unloaded
+    let material = materials.add(
+        Color::rgb(
+            rng.range(0.3..0.9),
+            rng.range(0.3..0.9),
+            rng.range(0.3..0.9),
+        )
+        .into(),
+    );
+
+    world.build().set_entity(chunk).with_children(|chunk| {
+        chunk.spawn(PbrComponents {
+            mesh: chunk_meshes.ground.clone(),
+            material: material.clone(),
+            transform: Transform::from_translation(Vec3::new(
+                CHUNK_SIZE / 2.0,
+                0.0,
+                CHUNK_SIZE / 2.0,
+            )),
+            ..Default::default()
+        });
+        for _ in 0..4 {
+            let height = rng.range(1.0..8.0);
+            chunk.spawn(PbrComponents {
+                mesh: chunk_meshes.pillar.clone(),
+                material: material.clone(),
+                transform: Transform {
+                    translation: Vec3::new(
+                        rng.range(0.0..CHUNK_SIZE),
+                        height / 2.0,
+                        rng.range(0.0..CHUNK_SIZE),
+                    ),
+                    scale: Vec3::new(1.0, height, 1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        }
+    });
+}
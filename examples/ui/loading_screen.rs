@@ -0,0 +1,55 @@
+use bevy::{
+    prelude::*,
+    ui::loading_screen::{AddLoadingState, LoadingScreenPlugin},
+};
+
+const HELMET: &str = "models/FlightHelmet/FlightHelmet.gltf";
+
+#[derive(Debug, Clone, PartialEq)]
+enum AppState {
+    Loading,
+    Playing,
+}
+
+/// Shows a loading screen until a model and all of its textures are loaded, then moves on and spawns the model
+fn main() {
+    App::build()
+        .add_resource(Msaa { samples: 4 })
+        .add_default_plugins()
+        .add_game_state(AppState::Loading)
+        .add_plugin(LoadingScreenPlugin::<AppState>::default())
+        .add_loading_state(AppState::Loading, AppState::Playing, &[HELMET])
+        .add_startup_system(setup.system())
+        .add_system(enter_playing_system.system())
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(UiCameraComponents::default());
+}
+
+fn enter_playing_system(
+    mut commands: Commands,
+    mut state_changed_reader: Local<EventReader<GameStateChanged<AppState>>>,
+    state_changed_events: Res<Events<GameStateChanged<AppState>>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in state_changed_reader.iter(&state_changed_events) {
+        if event.to != AppState::Playing {
+            continue;
+        }
+
+        // the model is already loaded, so this spawns it right away
+        commands
+            .spawn_scene(asset_server.load(HELMET))
+            .spawn(LightComponents {
+                transform: Transform::from_translation(Vec3::new(4.0, 5.0, 4.0)),
+                ..Default::default()
+            })
+            .spawn(Camera3dComponents {
+                transform: Transform::from_translation(Vec3::new(0.7, 0.7, 1.0))
+                    .looking_at(Vec3::new(0.0, 0.3, 0.0), Vec3::unit_y()),
+                ..Default::default()
+            });
+    }
+}
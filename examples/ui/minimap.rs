@@ -0,0 +1,154 @@
+use bevy::{
+    prelude::*,
+    ui::minimap::{Minimap, MinimapIcon, MinimapImage, MinimapPlugin, MINIMAP_MATERIAL_HANDLE},
+};
+
+/// Shows a minimap that turns with the player. Drive the player with the arrow keys. The trees have icons on the map,
+/// and the objective's icon stays on the edge of the map while it is out of range.
+fn main() {
+    App::build()
+        .add_resource(Msaa { samples: 4 })
+        .add_resource(Minimap {
+            extent: 15.0,
+            rotate_with_target: true,
+            // only the ground and the player are drawn on the map, everything else shows up as an icon
+            layers: RenderLayers::layer(1),
+            ..Default::default()
+        })
+        .add_default_plugins()
+        .add_plugin(MinimapPlugin::default())
+        .add_startup_system(setup.system())
+        .add_system(player_movement_system.system())
+        .run();
+}
+
+struct Player;
+
+fn setup(
+    mut commands: Commands,
+    mut minimap: ResMut<Minimap>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let tree_mesh = meshes.add(Mesh::from(shape::Cube { size: 1.0 }));
+    let tree_material = materials.add(Color::rgb(0.2, 0.5, 0.2).into());
+    let tree_icon = color_materials.add(Color::rgb(0.3, 0.8, 0.3).into());
+
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Plane { size: 100.0 })),
+            material: materials.add(Color::rgb(0.4, 0.35, 0.3).into()),
+            ..Default::default()
+        })
+        .with(RenderLayers::layer(0).with(1));
+    for x in -5..=5 {
+        for z in -5..=5 {
+            commands
+                .spawn(PbrComponents {
+                    mesh: tree_mesh.clone(),
+                    material: tree_material.clone(),
+                    transform: Transform::from_translation(Vec3::new(
+                        x as f32 * 8.0,
+                        0.5,
+                        z as f32 * 8.0 + 4.0,
+                    )),
+                    ..Default::default()
+                })
+                .with(MinimapIcon {
+                    material: tree_icon.clone(),
+                    size: 6.0,
+                    ..Default::default()
+                });
+        }
+    }
+
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Icosphere {
+                radius: 1.0,
+                subdivisions: 2,
+            })),
+            material: materials.add(Color::rgb(0.9, 0.8, 0.1).into()),
+            transform: Transform::from_translation(Vec3::new(30.0, 1.0, -30.0)),
+            ..Default::default()
+        })
+        .with(MinimapIcon {
+            material: color_materials.add(Color::rgb(1.0, 0.9, 0.1).into()),
+            size: 12.0,
+            clamp_to_edge: true,
+        });
+
+    commands
+        .spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 0.5 })),
+            material: materials.add(Color::rgb(0.8, 0.2, 0.2).into()),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.5, 0.0)),
+            ..Default::default()
+        })
+        .with(Player)
+        .with(RenderLayers::layer(0).with(1))
+        .with(MinimapIcon {
+            material: color_materials.add(Color::rgb(1.0, 0.2, 0.2).into()),
+            size: 10.0,
+            ..Default::default()
+        })
+        // the player's camera follows it as a child
+        .with_children(|parent| {
+            parent.spawn(Camera3dComponents {
+                transform: Transform::from_translation(Vec3::new(0.0, 4.0, 8.0))
+                    .looking_at(Vec3::new(0.0, 0.0, -4.0), Vec3::unit_y()),
+                ..Default::default()
+            });
+        });
+    minimap.target = commands.current_entity();
+
+    commands
+        .spawn(LightComponents {
+            transform: Transform::from_translation(Vec3::new(4.0, 20.0, 4.0)),
+            ..Default::default()
+        })
+        .spawn(UiCameraComponents::default())
+        .spawn(ImageComponents {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..Default::default()
+                },
+                size: Size::new(Val::Px(200.0), Val::Px(200.0)),
+                ..Default::default()
+            },
+            material: MINIMAP_MATERIAL_HANDLE,
+            ..Default::default()
+        })
+        .with(MinimapImage);
+}
+
+fn player_movement_system(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut query: Query<With<Player, &mut Transform>>,
+) {
+    for mut transform in query.iter_mut() {
+        let mut turn = 0.0;
+        if keyboard_input.pressed(KeyCode::Left) {
+            turn += 1.0;
+        }
+        if keyboard_input.pressed(KeyCode::Right) {
+            turn -= 1.0;
+        }
+        let mut speed = 0.0;
+        if keyboard_input.pressed(KeyCode::Up) {
+            speed += 1.0;
+        }
+        if keyboard_input.pressed(KeyCode::Down) {
+            speed -= 1.0;
+        }
+
+        transform.rotate(Quat::from_rotation_y(turn * 2.0 * time.delta_seconds));
+        let forward = transform.rotation * -Vec3::unit_z();
+        transform.translation += forward * speed * 8.0 * time.delta_seconds;
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::{
+    prelude::*,
+    render::pass::ClearColor,
+    ui::transition::{
+        ScreenTransition, ScreenTransitionFinished, ScreenTransitionPlugin, TransitionEffect,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AppState {
+    Menu,
+    Level,
+}
+
+/// Switches between a menu and a level behind a screen transition. Press space to fade through black, or W to wipe
+/// across the screen.
+fn main() {
+    App::build()
+        .add_resource(Msaa { samples: 4 })
+        .add_resource(ClearColor(Color::rgb(0.2, 0.2, 0.5)))
+        .add_default_plugins()
+        // added after the default plugins, so the transition is drawn over the ui
+        .add_plugin(ScreenTransitionPlugin)
+        .add_game_state(AppState::Menu)
+        .add_startup_system(setup.system())
+        .add_system(start_transition_system.system())
+        .add_system(finish_transition_system.system())
+        .add_system(state_text_system.system())
+        .run();
+}
+
+struct StateText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(UiCameraComponents::default())
+        .spawn(TextComponents {
+            style: Style {
+                align_self: AlignSelf::Center,
+                margin: Rect::all(Val::Auto),
+                ..Default::default()
+            },
+            text: Text {
+                value: String::new(),
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                style: TextStyle {
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                },
+            },
+            ..Default::default()
+        })
+        .with(StateText);
+}
+
+fn start_transition_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut transition: ResMut<ScreenTransition>,
+) {
+    if transition.is_running() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        transition.fade_out(Color::BLACK, 0.5);
+    } else if keyboard_input.just_pressed(KeyCode::W) {
+        transition.wipe_out(Color::BLACK, Vec2::new(1.0, 0.0), 0.5);
+    }
+}
+
+/// Switches states once the screen is covered, then reveals the new state
+fn finish_transition_system(
+    mut finished_reader: Local<EventReader<ScreenTransitionFinished>>,
+    finished_events: Res<Events<ScreenTransitionFinished>>,
+    mut game_state: ResMut<GameState<AppState>>,
+    mut transition: ResMut<ScreenTransition>,
+) {
+    for event in finished_reader.iter(&finished_events) {
+        if !event.covered {
+            continue;
+        }
+
+        let next = match game_state.current() {
+            AppState::Menu => AppState::Level,
+            AppState::Level => AppState::Menu,
+        };
+        game_state.set(next);
+        // reveal the new state the same way the old one was covered
+        match transition.effect() {
+            TransitionEffect::Fade => transition.fade_in(Color::BLACK, 0.5),
+            TransitionEffect::Wipe { direction, .. } => {
+                transition.wipe_in(Color::BLACK, direction, 0.5)
+            }
+        }
+    }
+}
+
+fn state_text_system(
+    mut state_changed_reader: Local<EventReader<GameStateChanged<AppState>>>,
+    state_changed_events: Res<Events<GameStateChanged<AppState>>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut query: Query<With<StateText, &mut Text>>,
+) {
+    for event in state_changed_reader.iter(&state_changed_events) {
+        let (value, color) = match event.to {
+            AppState::Menu => ("Menu", Color::rgb(0.2, 0.2, 0.5)),
+            AppState::Level => ("Level", Color::rgb(0.2, 0.5, 0.2)),
+        };
+        clear_color.0 = color;
+        for mut text in query.iter_mut() {
+            text.value = value.to_string();
+        }
+    }
+}
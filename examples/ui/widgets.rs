@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+/// This example illustrates the standard widgets and their change events.
+fn main() {
+    App::build()
+        .add_default_plugins()
+        .add_startup_system(setup.system())
+        .add_system(widget_events_system.system())
+        .add_system(progress_system.system())
+        .run();
+}
+
+#[derive(Default)]
+struct WidgetEventReaders {
+    button_clicked: EventReader<ButtonClicked>,
+    checkbox_changed: EventReader<CheckboxChanged>,
+    radio_button_changed: EventReader<RadioButtonChanged>,
+    slider_changed: EventReader<SliderChanged>,
+}
+
+fn widget_events_system(
+    mut readers: Local<WidgetEventReaders>,
+    button_clicked_events: Res<Events<ButtonClicked>>,
+    checkbox_changed_events: Res<Events<CheckboxChanged>>,
+    radio_button_changed_events: Res<Events<RadioButtonChanged>>,
+    slider_changed_events: Res<Events<SliderChanged>>,
+) {
+    for event in readers.button_clicked.iter(&button_clicked_events) {
+        println!("button clicked: {:?}", event.entity);
+    }
+    for event in readers.checkbox_changed.iter(&checkbox_changed_events) {
+        println!("checkbox {:?} checked: {}", event.entity, event.checked);
+    }
+    for event in readers
+        .radio_button_changed
+        .iter(&radio_button_changed_events)
+    {
+        println!(
+            "radio button {:?} selected in group {}",
+            event.entity, event.group
+        );
+    }
+    for event in readers.slider_changed.iter(&slider_changed_events) {
+        println!("slider {:?} value: {:.2}", event.entity, event.value);
+    }
+}
+
+fn progress_system(time: Res<Time>, mut query: Query<&mut ProgressBar>) {
+    for mut progress_bar in query.iter_mut() {
+        progress_bar.progress = (progress_bar.progress + time.delta_seconds * 0.2) % 1.0;
+    }
+}
+
+fn setup(mut commands: Commands, widget_materials: Res<WidgetMaterials>) {
+    let row_style = Style {
+        margin: Rect::all(Val::Px(10.0)),
+        ..Default::default()
+    };
+
+    commands
+        .spawn(UiCameraComponents::default())
+        .spawn(NodeComponents {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            material: widget_materials.track.clone(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn(ButtonComponents {
+                    style: Style {
+                        size: Size::new(Val::Px(150.0), Val::Px(40.0)),
+                        ..row_style.clone()
+                    },
+                    material: widget_materials.normal.clone(),
+                    ..Default::default()
+                })
+                .spawn(CheckboxComponents {
+                    style: Style {
+                        size: Size::new(Val::Px(20.0), Val::Px(20.0)),
+                        ..row_style.clone()
+                    },
+                    ..Default::default()
+                });
+
+            // selecting a radio button only deselects the other buttons of its group
+            for group in 0..2 {
+                for option in 0..3 {
+                    parent.spawn(RadioButtonComponents {
+                        radio_button: RadioButton {
+                            group,
+                            selected: option == 0,
+                        },
+                        style: Style {
+                            size: Size::new(Val::Px(20.0), Val::Px(20.0)),
+                            ..row_style.clone()
+                        },
+                        ..Default::default()
+                    });
+                }
+            }
+
+            parent
+                .spawn(SliderComponents {
+                    slider: Slider::new(0.5, 0.0, 1.0).with_step(0.05),
+                    style: Style {
+                        size: Size::new(Val::Px(200.0), Val::Px(20.0)),
+                        ..row_style.clone()
+                    },
+                    ..Default::default()
+                })
+                .spawn(ProgressBarComponents {
+                    style: Style {
+                        size: Size::new(Val::Px(200.0), Val::Px(20.0)),
+                        ..row_style
+                    },
+                    ..Default::default()
+                });
+        });
+}
@@ -4,8 +4,8 @@ use bevy::{
         camera::{ActiveCameras, Camera},
         pass::*,
         render_graph::{
-            base::MainPass, CameraNode, PassNode, RenderGraph, WindowSwapChainNode,
-            WindowTextureNode,
+            base::{MainPass, MsaaTexture},
+            CameraNode, PassNode, RenderGraph, WindowSwapChainNode, WindowTextureNode,
         },
         texture::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage},
     },
@@ -27,7 +27,6 @@ fn setup(
     mut active_cameras: ResMut<ActiveCameras>,
     mut render_graph: ResMut<RenderGraph>,
     asset_server: Res<AssetServer>,
-    msaa: Res<Msaa>,
 ) {
     let window_id = WindowId::new();
 
@@ -51,17 +50,17 @@ fn setup(
         WindowSwapChainNode::new(window_id),
     );
 
-    // add a new depth texture node for our new window
+    // add a new depth texture node for our new window, which has as many samples as Msaa
     render_graph.add_node(
         "second_window_depth_texture",
-        WindowTextureNode::new(
+        WindowTextureNode::multisampled(
             window_id,
             TextureDescriptor {
                 format: TextureFormat::Depth32Float,
                 usage: TextureUsage::OUTPUT_ATTACHMENT,
-                sample_count: msaa.samples,
                 ..Default::default()
             },
+            MsaaTexture::Depth,
         ),
     );
 
@@ -70,7 +69,7 @@ fn setup(
 
     // add a new render pass for our new window / camera
     let mut second_window_pass = PassNode::<&MainPass>::new(PassDescriptor {
-        color_attachments: vec![msaa.color_attachment_descriptor(
+        color_attachments: vec![Msaa::color_attachment_descriptor(
             TextureAttachment::Input("color_attachment".to_string()),
             TextureAttachment::Input("color_resolve_target".to_string()),
             Operations {
@@ -86,7 +85,7 @@ fn setup(
             }),
             stencil_ops: None,
         }),
-        sample_count: msaa.samples,
+        sample_count: 1,
     });
 
     second_window_pass.add_camera("Secondary");
@@ -99,11 +98,7 @@ fn setup(
             "second_window_swap_chain",
             WindowSwapChainNode::OUT_TEXTURE,
             "second_window_pass",
-            if msaa.samples > 1 {
-                "color_resolve_target"
-            } else {
-                "color_attachment"
-            },
+            "color_resolve_target",
         )
         .unwrap();
 
@@ -120,35 +115,35 @@ fn setup(
         .add_node_edge("secondary_camera", "second_window_pass")
         .unwrap();
 
-    if msaa.samples > 1 {
-        render_graph.add_node(
-            "second_multi_sampled_color_attachment",
-            WindowTextureNode::new(
-                window_id,
-                TextureDescriptor {
-                    size: Extent3d {
-                        depth: 1,
-                        width: 1,
-                        height: 1,
-                    },
-                    mip_level_count: 1,
-                    sample_count: msaa.samples,
-                    dimension: TextureDimension::D2,
-                    format: TextureFormat::default(),
-                    usage: TextureUsage::OUTPUT_ATTACHMENT,
+    // the pass renders to this texture and resolves it to the swap chain while Msaa has more than one sample
+    render_graph.add_node(
+        "second_multi_sampled_color_attachment",
+        WindowTextureNode::multisampled(
+            window_id,
+            TextureDescriptor {
+                size: Extent3d {
+                    depth: 1,
+                    width: 1,
+                    height: 1,
                 },
-            ),
-        );
-
-        render_graph
-            .add_slot_edge(
-                "second_multi_sampled_color_attachment",
-                WindowSwapChainNode::OUT_TEXTURE,
-                "second_window_pass",
-                "color_attachment",
-            )
-            .unwrap();
-    }
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::default(),
+                usage: TextureUsage::OUTPUT_ATTACHMENT,
+            },
+            MsaaTexture::Color,
+        ),
+    );
+
+    render_graph
+        .add_slot_edge(
+            "second_multi_sampled_color_attachment",
+            WindowSwapChainNode::OUT_TEXTURE,
+            "second_window_pass",
+            "color_attachment",
+        )
+        .unwrap();
 
     // SETUP SCENE
 
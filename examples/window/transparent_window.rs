@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+
+/// This example shows how to create a transparent, undecorated window, which is useful for overlays and widgets
+fn main() {
+    App::build()
+        // a clear color with zero alpha lets the desktop show through the window
+        .add_resource(ClearColor(Color::NONE))
+        .add_resource(WindowDescriptor {
+            transparent: true,
+            decorations: false,
+            ..Default::default()
+        })
+        .add_default_plugins()
+        .add_startup_system(setup.system())
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let texture_handle = asset_server.load("branding/icon.png");
+    commands
+        .spawn(Camera2dComponents::default())
+        .spawn(SpriteComponents {
+            material: materials.add(texture_handle.into()),
+            ..Default::default()
+        });
+}
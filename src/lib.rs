@@ -88,6 +88,11 @@ pub mod scene {
     pub use bevy_scene::*;
 }
 
+pub mod settings {
+    //! Persist settings resources between runs.
+    pub use bevy_settings::*;
+}
+
 pub mod tasks {
     pub use bevy_tasks::*;
 }
@@ -114,6 +119,18 @@ pub mod audio {
     pub use bevy_audio::*;
 }
 
+#[cfg(feature = "bevy_console")]
+pub mod console {
+    //! An in-game console with commands and cvars.
+    pub use bevy_console::*;
+}
+
+#[cfg(feature = "bevy_editor")]
+pub mod editor {
+    //! A level editor that runs inside the game.
+    pub use bevy_editor::*;
+}
+
 #[cfg(feature = "bevy_gltf")]
 pub mod gltf {
     //! Support for GLTF file loading.
@@ -0,0 +1,150 @@
+//! Renders small scenes and compares them to the reference images in `tests/golden`, so changes to the renderer can't
+//! silently change its output. The scenes render offscreen, but they need a gpu, so each test passes without
+//! rendering anything when no adapter is available.
+//!
+//! The scenes only use unlit colors and put every edge on a pixel boundary, so their reference images were computed
+//! from the scene definitions rather than captured from one machine's gpu. A scene without a reference image fails.
+//! After adding a scene or an intended change to the output, set `BEVY_UPDATE_GOLDEN=1` to write the missing
+//! reference images and replace the existing ones, then review the new images before committing them.
+
+use bevy::{
+    audio::AudioPlugin,
+    prelude::*,
+    render::{
+        camera::PerspectiveProjection,
+        capture::{FrameCapturePlugin, GoldenImageTest},
+        renderer::headless_window_system,
+    },
+    wgpu::{WgpuOptions, WgpuRenderer},
+    winit::WinitPlugin,
+};
+use std::{f32::consts::PI, path::Path};
+
+const SIZE: u32 = 256;
+
+fn golden_image_test(name: &str, build: impl FnOnce(&mut AppBuilder)) {
+    if !WgpuRenderer::adapter_available(&WgpuOptions::default()) {
+        println!(
+            "skipping golden image test {}: no gpu adapter is available",
+            name
+        );
+        return;
+    }
+
+    let mut builder = App::build();
+    builder
+        .add_resource(WindowDescriptor {
+            width: SIZE,
+            height: SIZE,
+            ..Default::default()
+        })
+        .add_plugin_group_with(DefaultPlugins, |group| {
+            group.disable::<AudioPlugin>().disable::<WinitPlugin>()
+        })
+        .add_plugin(FrameCapturePlugin)
+        .add_system_to_stage(stage::PRE_UPDATE, headless_window_system.system());
+    build(&mut builder);
+
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    if let Err(err) = GoldenImageTest::new(directory, name).run(builder.app) {
+        panic!("{}", err);
+    }
+}
+
+#[test]
+fn pbr_scene() {
+    golden_image_test("pbr_scene", |app| {
+        app.add_startup_system(setup_pbr_scene.system());
+    });
+}
+
+/// Returns the translation and size of a quad at `depth` in front of the default 3d camera that covers the pixels
+/// from `min` (inclusive) to `max` (exclusive), counted from the top left corner
+fn pixel_quad(min: (f32, f32), max: (f32, f32), depth: f32) -> (Vec3, Vec2) {
+    let projection = PerspectiveProjection::default();
+    let half_extent = depth * (projection.fov / 2.0).tan();
+    let to_world = |pixel: f32| (pixel / (SIZE as f32 / 2.0) - 1.0) * half_extent;
+    let (left, right) = (to_world(min.0), to_world(max.0));
+    let (top, bottom) = (-to_world(min.1), -to_world(max.1));
+    (
+        Vec3::new((left + right) / 2.0, (top + bottom) / 2.0, -depth),
+        Vec2::new(right - left, top - bottom),
+    )
+}
+
+fn setup_pbr_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // the near quad is spawned first, so the overlap only shows it if the depth test works. the last quad faces away
+    // from the camera and has to be culled.
+    let quads = [
+        (
+            (96.0, 96.0),
+            (224.0, 224.0),
+            2.0,
+            Color::rgb(0.2, 0.4, 0.8),
+            0.0,
+        ),
+        (
+            (32.0, 32.0),
+            (160.0, 160.0),
+            4.0,
+            Color::rgb(0.8, 0.3, 0.2),
+            0.0,
+        ),
+        (
+            (16.0, 160.0),
+            (112.0, 240.0),
+            3.0,
+            Color::rgb(1.0, 1.0, 0.0),
+            PI,
+        ),
+    ];
+    for (min, max, depth, color, rotation) in quads.iter() {
+        let (translation, size) = pixel_quad(*min, *max, *depth);
+        commands.spawn(PbrComponents {
+            mesh: meshes.add(Mesh::from(shape::Quad::new(size))),
+            material: materials.add(StandardMaterial {
+                albedo: *color,
+                shaded: false,
+                ..Default::default()
+            }),
+            transform: Transform {
+                translation,
+                rotation: Quat::from_rotation_y(*rotation),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+    commands.spawn(Camera3dComponents::default());
+}
+
+#[test]
+fn sprite_scene() {
+    golden_image_test("sprite_scene", |app| {
+        app.add_startup_system(setup_sprite_scene.system());
+    });
+}
+
+fn setup_sprite_scene(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands.spawn(Camera2dComponents::default());
+    let colors = [
+        Color::rgb(0.9, 0.2, 0.2),
+        Color::rgb(0.2, 0.9, 0.2),
+        Color::rgba(0.2, 0.2, 0.9, 0.5),
+    ];
+    for (index, color) in colors.iter().enumerate() {
+        // the sprites overlap, so both depth sorting and blending show up in the image. the 2d camera maps a world
+        // unit to a pixel, so their edges land on pixel boundaries.
+        let offset = index as f32 * 40.0 - 40.0;
+        commands.spawn(SpriteComponents {
+            material: materials.add((*color).into()),
+            sprite: Sprite::new(Vec2::new(100.0, 100.0)),
+            transform: Transform::from_translation(Vec3::new(offset, offset, index as f32)),
+            ..Default::default()
+        });
+    }
+}
@@ -24,6 +24,7 @@ crates=(
     bevy_gilrs
     bevy_pbr
     bevy_scene
+    bevy_settings
     bevy_sprite
     bevy_text
     bevy_ui